@@ -1,11 +1,17 @@
+use crate::alignment::Alignment;
 use crate::arch::Arch;
+use crate::arch::PltProtection;
+use crate::elf::PAC_PLT_ENTRY_SIZE;
 use crate::elf::PLT_ENTRY_SIZE;
+use crate::elf::THUNK_SIZE;
 use crate::resolution::ValueFlags;
+use anyhow::Context as _;
 use anyhow::Result;
 use anyhow::anyhow;
 use linker_utils::aarch64::DEFAULT_AARCH64_PAGE_IGNORED_MASK;
 use linker_utils::aarch64::DEFAULT_AARCH64_PAGE_MASK;
 use linker_utils::aarch64::DEFAULT_AARCH64_PAGE_SIZE;
+use linker_utils::aarch64::DEFAULT_AARCH64_PAGE_SIZE_BITS;
 use linker_utils::aarch64::RelaxationKind;
 use linker_utils::aarch64::relocation_type_from_raw;
 use linker_utils::elf::DynamicRelocationKind;
@@ -25,8 +31,45 @@ const PLT_ENTRY_TEMPLATE: &[u8] = &[
     0x1f, 0x20, 0x03, 0xd5, // nop
 ];
 
+const BTI_PLT_ENTRY_TEMPLATE: &[u8] = &[
+    0x5f, 0x24, 0x03, 0xd5, // bti c
+    0x10, 0x00, 0x00, 0x90, // adrp x16, page(&(.got.plt[n]))
+    0x11, 0x02, 0x40, 0xf9, // ldr x17, [x16, offset(&(.got.plt[n]))]
+    0x20, 0x02, 0x1f, 0xd6, // br x17
+];
+
+const PAC_PLT_ENTRY_TEMPLATE: &[u8] = &[
+    0x10, 0x00, 0x00, 0x90, // adrp x16, page(&(.got.plt[n]))
+    0x11, 0x02, 0x40, 0xf9, // ldr x17, [x16, offset(&(.got.plt[n]))]
+    0x10, 0x02, 0x00, 0x91, // add x16, x16, offset(&(.got.plt[n]))
+    0x9f, 0x21, 0x03, 0xd5, // autia1716
+    0x20, 0x02, 0x1f, 0xd6, // br x17
+    0x1f, 0x20, 0x03, 0xd5, // nop
+];
+
+const BTI_PAC_PLT_ENTRY_TEMPLATE: &[u8] = &[
+    0x5f, 0x24, 0x03, 0xd5, // bti c
+    0x10, 0x00, 0x00, 0x90, // adrp x16, page(&(.got.plt[n]))
+    0x11, 0x02, 0x40, 0xf9, // ldr x17, [x16, offset(&(.got.plt[n]))]
+    0x10, 0x02, 0x00, 0x91, // add x16, x16, offset(&(.got.plt[n]))
+    0x9f, 0x21, 0x03, 0xd5, // autia1716
+    0x20, 0x02, 0x1f, 0xd6, // br x17
+];
+
+/// Range-extension thunk (veneer) used when a branch can't reach its target. This is the same
+/// sequence that GNU ld uses for its ADRP-based veneers. It clobbers x16, which the ABI permits.
+const THUNK_TEMPLATE: &[u8] = &[
+    0x10, 0x00, 0x00, 0x90, // adrp x16, page(target)
+    0x10, 0x02, 0x00, 0x91, // add x16, x16, offset(target)
+    0x00, 0x02, 0x1f, 0xd6, // br x16
+];
+
 const _ASSERTS: () = {
+    assert!(THUNK_TEMPLATE.len() as u64 == THUNK_SIZE);
     assert!(PLT_ENTRY_TEMPLATE.len() as u64 == PLT_ENTRY_SIZE);
+    assert!(BTI_PLT_ENTRY_TEMPLATE.len() as u64 == PLT_ENTRY_SIZE);
+    assert!(PAC_PLT_ENTRY_TEMPLATE.len() as u64 == PAC_PLT_ENTRY_SIZE);
+    assert!(BTI_PAC_PLT_ENTRY_TEMPLATE.len() as u64 == PAC_PLT_ENTRY_SIZE);
 };
 
 impl crate::arch::Arch for AArch64 {
@@ -48,6 +91,10 @@ impl crate::arch::Arch for AArch64 {
         })
     }
 
+    fn tls_dtv_offset() -> u64 {
+        0
+    }
+
     fn get_dynamic_relocation_type(relocation: DynamicRelocationKind) -> u32 {
         relocation.aarch64_r_type()
     }
@@ -60,29 +107,319 @@ impl crate::arch::Arch for AArch64 {
         plt_entry: &mut [u8],
         got_address: u64,
         plt_address: u64,
+        _got_base: Option<u64>,
+        protection: PltProtection,
     ) -> crate::error::Result {
         // TODO: For simplicity, we assume now the PLT entry precedes the GOT entry, so we can
         // make the offset calculation in the unsigned type.
         debug_assert!(plt_address < got_address);
 
-        plt_entry.copy_from_slice(PLT_ENTRY_TEMPLATE);
-        let plt_page_address = plt_address & DEFAULT_AARCH64_PAGE_IGNORED_MASK;
-        let offset = got_address.wrapping_sub(plt_page_address);
+        let template = match (protection.landing_pad, protection.authenticate) {
+            (false, false) => PLT_ENTRY_TEMPLATE,
+            (true, false) => BTI_PLT_ENTRY_TEMPLATE,
+            (false, true) => PAC_PLT_ENTRY_TEMPLATE,
+            (true, true) => BTI_PAC_PLT_ENTRY_TEMPLATE,
+        };
+        plt_entry.copy_from_slice(template);
+
+        // With a landing pad, the `adrp` is the second instruction.
+        let adrp_offset = if protection.landing_pad { 4 } else { 0 };
+        let adrp_page_address =
+            (plt_address + adrp_offset as u64) & DEFAULT_AARCH64_PAGE_IGNORED_MASK;
+        let offset = got_address.wrapping_sub(adrp_page_address);
         anyhow::ensure!(offset < (1 << 32), "PLT is more than 4GiB away from GOT");
+        let instructions = &mut plt_entry[adrp_offset..];
         RelocationInstruction::Adr.write_to_value(
             // The immediate value represents a distance in pages.
             offset / DEFAULT_AARCH64_PAGE_SIZE,
             false,
-            &mut plt_entry[0..4],
+            &mut instructions[0..4],
         );
         RelocationInstruction::LdrRegister.write_to_value(
             // The immediate offset is scaled by 8 as we are loading 8 bytes.
             (offset & DEFAULT_AARCH64_PAGE_MASK) / 8,
             false,
-            &mut plt_entry[4..8],
+            &mut instructions[4..8],
         );
+        if protection.authenticate {
+            // `autia1716` uses the address of the GOT entry in x16 as the modifier.
+            RelocationInstruction::Add.write_to_value(
+                offset & DEFAULT_AARCH64_PAGE_MASK,
+                false,
+                &mut instructions[8..12],
+            );
+        }
         Ok(())
     }
+
+    fn is_thunkable_branch(r_type: u32) -> bool {
+        matches!(
+            r_type,
+            object::elf::R_AARCH64_CALL26
+                | object::elf::R_AARCH64_JUMP26
+                | object::elf::R_AARCH64_CONDBR19
+                | object::elf::R_AARCH64_TSTBR14
+        )
+    }
+
+    fn write_thunk(
+        thunk: &mut [u8],
+        thunk_address: u64,
+        target_address: u64,
+    ) -> crate::error::Result {
+        thunk.copy_from_slice(THUNK_TEMPLATE);
+        let page_offset = (target_address & DEFAULT_AARCH64_PAGE_IGNORED_MASK)
+            .wrapping_sub(thunk_address & DEFAULT_AARCH64_PAGE_IGNORED_MASK)
+            as i64;
+        anyhow::ensure!(
+            (-(1 << 32)..(1 << 32)).contains(&page_offset),
+            "Thunk at 0x{thunk_address:x} is more than 4GiB away from its target 0x{target_address:x}"
+        );
+        RelocationInstruction::Adr.write_to_value(
+            // The immediate value represents a distance in pages.
+            ((page_offset >> DEFAULT_AARCH64_PAGE_SIZE_BITS) as u64) & ((1 << 21) - 1),
+            false,
+            &mut thunk[0..4],
+        );
+        RelocationInstruction::Add.write_to_value(
+            target_address & DEFAULT_AARCH64_PAGE_MASK,
+            false,
+            &mut thunk[4..8],
+        );
+        Ok(())
+    }
+
+    fn find_erratum_sequences(section_data: &[u8], alignment: Alignment, sequences: &mut Vec<u64>) {
+        // The section's alignment tells us the low bits of each sequence's address within a page,
+        // which is all of them if the section is at least page-aligned.
+        let known_bits = alignment.mask() & DEFAULT_AARCH64_PAGE_MASK;
+        for offset in (0..section_data.len()).step_by(4) {
+            let known = offset as u64 & known_bits;
+            if known != 0xff8 & known_bits && known != 0xffc & known_bits {
+                continue;
+            }
+            if erratum_843419_load_store_offset(&section_data[offset..]).is_some() {
+                sequences.push(offset as u64);
+            }
+        }
+    }
+
+    fn needs_erratum_patch(address: u64) -> bool {
+        address & DEFAULT_AARCH64_PAGE_MASK >= 0xff8
+    }
+
+    fn fix_erratum_sequence(
+        section_data: &mut [u8],
+        offset: usize,
+        section_address: u64,
+        patch: &mut [u8],
+        patch_address: u64,
+    ) -> crate::error::Result<bool> {
+        let adrp_address = section_address + offset as u64;
+        if !Self::needs_erratum_patch(adrp_address) {
+            return Ok(false);
+        }
+        let load_store_offset = erratum_843419_load_store_offset(&section_data[offset..])
+            .context("Erratum 843419 sequence no longer matches after relocation")?;
+
+        // If the page is close enough, then an `adr` can compute the same address without the
+        // erratum applying. This is what GNU ld does.
+        let adrp = read_instruction(&section_data[offset..]);
+        let page_delta =
+            (i64::from(adrp >> 29 & 0x3) | (i64::from(adrp >> 5 & 0x7_ffff) << 2)) << 43 >> 31;
+        let page_address =
+            (adrp_address & DEFAULT_AARCH64_PAGE_IGNORED_MASK).wrapping_add(page_delta as u64);
+        let adr_offset = page_address.wrapping_sub(adrp_address) as i64;
+        if (-(1 << 20)..(1 << 20)).contains(&adr_offset) {
+            let adr = &mut section_data[offset..offset + 4];
+            adr.copy_from_slice(&(ADR_OPCODE | (adrp & 0x1f)).to_le_bytes());
+            RelocationInstruction::Adr.write_to_value(
+                adr_offset as u64 & ((1 << 21) - 1),
+                false,
+                adr,
+            );
+            return Ok(false);
+        }
+
+        // Otherwise, as lld does, we move the load or store into a patch that branches back.
+        let insn_offset = offset + load_store_offset;
+        let insn_address = section_address + insn_offset as u64;
+        let insn = &mut section_data[insn_offset..insn_offset + 4];
+        patch[..4].copy_from_slice(insn);
+        write_branch(&mut patch[4..8], patch_address + 4, insn_address + 4)?;
+        write_branch(insn, insn_address, patch_address)?;
+        Ok(true)
+    }
+}
+
+const ADR_OPCODE: u32 = 0x1000_0000;
+const B_OPCODE: u32 = 0x1400_0000;
+
+fn read_instruction(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes(bytes[..4].try_into().unwrap())
+}
+
+/// Writes an unconditional branch from `from` to `to`.
+fn write_branch(out: &mut [u8], from: u64, to: u64) -> crate::error::Result {
+    let offset = to.wrapping_sub(from) as i64;
+    anyhow::ensure!(
+        (-(1 << 27)..(1 << 27)).contains(&offset),
+        "Branch from 0x{from:x} to 0x{to:x} is out of range"
+    );
+    out.copy_from_slice(&B_OPCODE.to_le_bytes());
+    RelocationInstruction::JumpCall.write_to_value(
+        ((offset >> 2) as u64) & ((1 << 26) - 1),
+        false,
+        out,
+    );
+    Ok(())
+}
+
+/// Checks whether `code` starts with an instruction sequence that triggers Cortex-A53 erratum
+/// 843419 if the `adrp` is at offset 0xff8 or 0xffc within a page. If it does, returns the offset of
+/// the load or store that uses the result of the `adrp`. The classification follows lld.
+fn erratum_843419_load_store_offset(code: &[u8]) -> Option<usize> {
+    let instruction = |index: usize| code.get(index * 4..index * 4 + 4).map(read_instruction);
+    let adrp = instruction(0)?;
+    let second = instruction(1)?;
+    let third = instruction(2)?;
+    if !is_adrp(adrp) {
+        return None;
+    }
+    let register = adrp & 0x1f;
+    if is_843419_sequence(register, second, third) {
+        return Some(8);
+    }
+    if !is_branch(third) && is_843419_sequence(register, second, instruction(3)?) {
+        return Some(12);
+    }
+    None
+}
+
+/// Checks whether `first` is a load or store that doesn't write `register` and `last` is a load or
+/// store with an unsigned offset that uses `register` as its base.
+fn is_843419_sequence(register: u32, first: u32, last: u32) -> bool {
+    is_load_store_class(first)
+        && (is_load_store_exclusive(first)
+            || is_load_literal(first)
+            || is_single_register_load_store(first)
+            || is_stp(first)
+            || is_stnp(first)
+            || is_st1(first))
+        && !load_store_writes_register(first, register)
+        && is_load_store_register_unsigned(last)
+        && (last >> 5) & 0x1f == register
+}
+
+fn is_adrp(insn: u32) -> bool {
+    insn & 0x9f00_0000 == 0x9000_0000
+}
+
+fn is_branch(insn: u32) -> bool {
+    // B, BL, B.cond, CBZ, CBNZ, TBZ, TBNZ and branches to registers.
+    insn & 0x7c00_0000 == 0x1400_0000
+        || insn & 0xff00_0010 == 0x5400_0000
+        || insn & 0x7e00_0000 == 0x3400_0000
+        || insn & 0x7e00_0000 == 0x3600_0000
+        || insn & 0xfe00_0000 == 0xd600_0000
+}
+
+fn is_load_store_class(insn: u32) -> bool {
+    insn & 0x0a00_0000 == 0x0800_0000
+}
+
+fn is_load_store_exclusive(insn: u32) -> bool {
+    insn & 0x3f00_0000 == 0x0800_0000
+}
+
+fn is_load_exclusive(insn: u32) -> bool {
+    insn & 0x3f40_0000 == 0x0840_0000
+}
+
+fn is_load_literal(insn: u32) -> bool {
+    insn & 0x3b00_0000 == 0x1800_0000
+}
+
+fn is_stnp(insn: u32) -> bool {
+    insn & 0x3bc0_0000 == 0x2800_0000
+}
+
+fn is_stp_post(insn: u32) -> bool {
+    insn & 0x3bc0_0000 == 0x2880_0000
+}
+
+fn is_stp_pre(insn: u32) -> bool {
+    insn & 0x3bc0_0000 == 0x2980_0000
+}
+
+fn is_stp(insn: u32) -> bool {
+    is_stp_post(insn) || insn & 0x3bc0_0000 == 0x2900_0000 || is_stp_pre(insn)
+}
+
+fn is_st1_multiple_post(insn: u32) -> bool {
+    insn & 0xbfe0_0000 == 0x0c80_0000
+}
+
+fn is_st1_single_post(insn: u32) -> bool {
+    insn & 0xbfe0_0000 == 0x0d80_0000
+}
+
+fn is_st1(insn: u32) -> bool {
+    insn & 0xbfff_0000 == 0x0c00_0000
+        || is_st1_multiple_post(insn)
+        || insn & 0xbfff_0000 == 0x0d00_0000
+        || is_st1_single_post(insn)
+}
+
+fn is_load_store_immediate_post(insn: u32) -> bool {
+    insn & 0x3b20_0c00 == 0x3800_0400
+}
+
+fn is_load_store_immediate_pre(insn: u32) -> bool {
+    insn & 0x3b20_0c00 == 0x3800_0c00
+}
+
+fn is_load_store_register_unsigned(insn: u32) -> bool {
+    insn & 0x3b00_0000 == 0x3900_0000
+}
+
+/// Loads and stores of a single register that aren't exclusive, literal or pairs.
+fn is_single_register_load_store(insn: u32) -> bool {
+    insn & 0x3b00_0c00 == 0x3800_0000
+        || is_load_store_immediate_post(insn)
+        || insn & 0x3b20_0c00 == 0x3800_0800
+        || is_load_store_immediate_pre(insn)
+        || insn & 0x3b20_0c00 == 0x3820_0800
+        || is_load_store_register_unsigned(insn)
+}
+
+fn is_load(insn: u32) -> bool {
+    if is_load_exclusive(insn) || is_load_literal(insn) {
+        return true;
+    }
+    if !is_single_register_load_store(insn) {
+        return false;
+    }
+    let size = insn >> 30;
+    let vector = (insn >> 26) & 1;
+    let opc = (insn >> 22) & 0x3;
+    // opc 0 is a store, as is opc 2 for 8-bit vector registers. opc 2 for 64-bit general-purpose
+    // registers is a prefetch.
+    opc != 0 && !(size == 0 && vector == 1 && opc == 2) && !(size == 3 && vector == 0 && opc == 2)
+}
+
+fn has_writeback(insn: u32) -> bool {
+    is_load_store_immediate_pre(insn)
+        || is_load_store_immediate_post(insn)
+        || is_stp_pre(insn)
+        || is_stp_post(insn)
+        || is_st1_single_post(insn)
+        || is_st1_multiple_post(insn)
+}
+
+fn load_store_writes_register(insn: u32, register: u32) -> bool {
+    (is_load(insn) && insn & 0x1f == register)
+        || (has_writeback(insn) && (insn >> 5) & 0x1f == register)
 }
 
 #[derive(Debug, Clone)]
@@ -265,3 +602,91 @@ impl crate::arch::Relaxation for Relaxation {
         self.kind.next_modifier()
     }
 }
+
+#[test]
+fn test_erratum_843419_sequences() {
+    fn code(instructions: &[u32]) -> Vec<u8> {
+        instructions.iter().flat_map(|i| i.to_le_bytes()).collect()
+    }
+    const ADRP_X0: u32 = 0x9000_0000;
+    const LDR_X1_SP: u32 = 0xf940_03e1;
+    const LDR_X0_X0: u32 = 0xf940_0000;
+    const LDR_X2_X0: u32 = 0xf940_0002;
+    const NOP: u32 = 0xd503_201f;
+
+    let offset = |instructions: &[u32]| erratum_843419_load_store_offset(&code(instructions));
+    assert_eq!(offset(&[ADRP_X0, LDR_X1_SP, LDR_X2_X0]), Some(8));
+    assert_eq!(offset(&[ADRP_X0, LDR_X1_SP, NOP, LDR_X2_X0]), Some(12));
+    // The second instruction overwrites the register that the `adrp` wrote.
+    assert_eq!(offset(&[ADRP_X0, LDR_X0_X0, LDR_X2_X0]), None);
+    // The second instruction isn't a load or store.
+    assert_eq!(offset(&[ADRP_X0, NOP, LDR_X2_X0]), None);
+    // A branch as the third instruction ends the sequence.
+    assert_eq!(offset(&[ADRP_X0, LDR_X1_SP, B_OPCODE, LDR_X2_X0]), None);
+
+    // Returns the offsets from 0xff0 to 0x1000 at which a sequence is found in a section with the
+    // specified alignment.
+    let found_at = |alignment| {
+        (0xff0..=0x1000)
+            .step_by(4)
+            .filter(|&offset| {
+                let mut section = code(&vec![NOP; offset / 4]);
+                section.extend(code(&[ADRP_X0, LDR_X1_SP, LDR_X2_X0]));
+                let mut sequences = Vec::new();
+                AArch64::find_erratum_sequences(
+                    &section,
+                    Alignment::new(alignment).unwrap(),
+                    &mut sequences,
+                );
+                sequences == [offset as u64]
+            })
+            .collect::<Vec<_>>()
+    };
+    // In a page-aligned section, we know exactly which sequences are at 0xff8 and 0xffc.
+    assert_eq!(found_at(0x1000), [0xff8, 0xffc]);
+    assert_eq!(found_at(0x8000), [0xff8, 0xffc]);
+    // Otherwise, we can only rule out sequences whose low bits don't match.
+    assert_eq!(found_at(16), [0xff8, 0xffc]);
+    assert_eq!(found_at(8), [0xff0, 0xff4, 0xff8, 0xffc, 0x1000]);
+    assert_eq!(found_at(4), [0xff0, 0xff4, 0xff8, 0xffc, 0x1000]);
+}
+
+#[test]
+fn test_protected_plt_entries() {
+    fn code(instructions: &[u32]) -> Vec<u8> {
+        instructions.iter().flat_map(|i| i.to_le_bytes()).collect()
+    }
+    fn plt_entry(landing_pad: bool, authenticate: bool) -> Vec<u8> {
+        let protection = PltProtection {
+            landing_pad,
+            authenticate,
+        };
+        let mut entry = vec![0; protection.entry_size() as usize];
+        AArch64::write_plt_entry(&mut entry, 0x20010, 0x10000, None, protection).unwrap();
+        entry
+    }
+    const BTI_C: u32 = 0xd503_245f;
+    const ADRP_X16: u32 = 0x9000_0090; // adrp x16, #0x10000
+    const LDR_X17: u32 = 0xf940_0a11; // ldr x17, [x16, #0x10]
+    const ADD_X16: u32 = 0x9100_4210; // add x16, x16, #0x10
+    const AUTIA1716: u32 = 0xd503_219f;
+    const BR_X17: u32 = 0xd61f_0220;
+    const NOP: u32 = 0xd503_201f;
+
+    assert_eq!(
+        plt_entry(false, false),
+        code(&[ADRP_X16, LDR_X17, BR_X17, NOP])
+    );
+    assert_eq!(
+        plt_entry(true, false),
+        code(&[BTI_C, ADRP_X16, LDR_X17, BR_X17])
+    );
+    assert_eq!(
+        plt_entry(false, true),
+        code(&[ADRP_X16, LDR_X17, ADD_X16, AUTIA1716, BR_X17, NOP])
+    );
+    assert_eq!(
+        plt_entry(true, true),
+        code(&[BTI_C, ADRP_X16, LDR_X17, ADD_X16, AUTIA1716, BR_X17])
+    );
+}
@@ -1,14 +1,22 @@
 //! Abstraction over different CPU architectures.
 
+use crate::alignment::Alignment;
 use crate::args::OutputKind;
+use crate::elf::ElfClass;
+use crate::elf::RelocationFormat;
 use crate::error::Result;
 use crate::resolution::ValueFlags;
+use crate::shrinking::RelaxationEdit;
+use crate::shrinking::ShrinkContext;
 use anyhow::bail;
 use linker_utils::elf::DynamicRelocationKind;
 use linker_utils::elf::RelocationKindInfo;
 use linker_utils::elf::SectionFlags;
 use linker_utils::relaxation::RelocationModifier;
+use object::elf::EM_386;
 use object::elf::EM_AARCH64;
+use object::elf::EM_LOONGARCH;
+use object::elf::EM_RISCV;
 use object::elf::EM_X86_64;
 use std::borrow::Cow;
 use std::str::FromStr;
@@ -19,23 +27,157 @@ pub(crate) trait Arch {
     // Get ELF header magic for the architecture.
     fn elf_header_arch_magic() -> u16;
 
+    // The amount by which the runtime biases offsets within a module's TLS block. Static values
+    // that we write for such offsets need the same bias.
+    fn tls_dtv_offset() -> u64;
+
     // Get dynamic relocation value specific for the architecture.
     fn get_dynamic_relocation_type(relocation: DynamicRelocationKind) -> u32;
 
-    // Write PLT entry for the architecture.
-    fn write_plt_entry(plt_entry: &mut [u8], got_address: u64, plt_address: u64) -> Result;
+    // Write PLT entry for the architecture. `got_base` is the address of `_GLOBAL_OFFSET_TABLE_`
+    // if the output is position-independent, for architectures that then need to address GOT
+    // entries relative to it.
+    fn write_plt_entry(
+        plt_entry: &mut [u8],
+        got_address: u64,
+        plt_address: u64,
+        got_base: Option<u64>,
+        protection: PltProtection,
+    ) -> Result;
 
     // Make architecture-specific parsing of the relocation types.
     fn relocation_from_raw(r_type: u32) -> Result<RelocationKindInfo>;
 
     // Get string representation of a relocation specific for the architecture.
     fn rel_type_to_string(r_type: u32) -> Cow<'static, str>;
+
+    // Returns whether relocations of the specified type are branches that we can redirect via a
+    // range-extension thunk if their target turns out to be out of range. Architectures where
+    // branches can reach anywhere that we put code don't need thunks.
+    fn is_thunkable_branch(_r_type: u32) -> bool {
+        false
+    }
+
+    // Write a range-extension thunk of `THUNK_SIZE` bytes that branches to `target_address`.
+    fn write_thunk(_thunk: &mut [u8], _thunk_address: u64, _target_address: u64) -> Result {
+        bail!("Range-extension thunks are not supported on this architecture")
+    }
+
+    // Record the offsets within `section_data` of instruction sequences that might trigger a CPU
+    // erratum that we've been asked to work around, depending on the address at which they end up.
+    // Sequences that can't end up at such an address in a section with the specified alignment are
+    // skipped. Most architectures don't have any errata that we work around.
+    fn find_erratum_sequences(
+        _section_data: &[u8],
+        _alignment: Alignment,
+        _sequences: &mut Vec<u64>,
+    ) {
+    }
+
+    // Returns whether an erratum sequence found by `find_erratum_sequences` needs to be worked
+    // around if it starts at `address`.
+    fn needs_erratum_patch(_address: u64) -> bool {
+        false
+    }
+
+    // Work around the erratum for the sequence at `offset` in `section_data` if its final address
+    // requires it, either by rewriting it in place or by moving an instruction into `patch`, which
+    // will be at `patch_address`. Returns whether `patch` was used.
+    fn fix_erratum_sequence(
+        _section_data: &mut [u8],
+        _offset: usize,
+        _section_address: u64,
+        _patch: &mut [u8],
+        _patch_address: u64,
+    ) -> Result<bool> {
+        bail!("Erratum patches are not supported on this architecture")
+    }
+
+    // Find the linker relaxations in `section_data` that delete bytes or that are needed because of
+    // bytes deleted earlier in the section, adding them to `edits` in order of offset. Most
+    // architectures don't have any relaxations that change the size of sections.
+    fn find_relaxation_edits(
+        _section_data: &[u8],
+        _relocations: &crate::elf::RelocationList,
+        _context: &ShrinkContext,
+        _edits: &mut Vec<RelaxationEdit>,
+    ) -> Result {
+        Ok(())
+    }
+
+    // Rewrite whatever instructions `edit` changes. `input` is the original section data and `out`
+    // is the output, both starting from the offset of the edit's relocation.
+    fn apply_relaxation_edit(_input: &[u8], _edit: &RelaxationEdit, _out: &mut [u8]) {
+        unreachable!("Architecture doesn't produce relaxation edits");
+    }
+
+    // Merge the architecture-specific `e_flags` from the headers of our input objects into the
+    // flags for the output file header.
+    fn merge_e_flags(_flags: impl Iterator<Item = u32>) -> Result<u32> {
+        Ok(0)
+    }
+}
+
+/// Control-flow protection that our PLT entries need to provide.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct PltProtection {
+    /// Whether entries need to start with a landing pad for indirect branches. Our x86-64 entries
+    /// always start with `endbr64`, so this only affects AArch64, where it adds `bti c`.
+    pub(crate) landing_pad: bool,
+
+    /// Whether entries need to authenticate the address that they load from the GOT before
+    /// branching to it. Only supported on AArch64.
+    pub(crate) authenticate: bool,
+}
+
+impl PltProtection {
+    /// Returns the size of each PLT entry. Entries that authenticate need extra instructions.
+    pub(crate) fn entry_size(self) -> u64 {
+        if self.authenticate {
+            crate::elf::PAC_PLT_ENTRY_SIZE
+        } else {
+            crate::elf::PLT_ENTRY_SIZE
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum Architecture {
     X86_64,
     AArch64,
+    RiscV64,
+    LoongArch64,
+    I386,
+}
+
+impl Architecture {
+    /// Returns whether files for this architecture use 32 or 64 bit ELF structures.
+    pub(crate) fn elf_class(self) -> ElfClass {
+        match self {
+            Architecture::I386 => ElfClass::Elf32,
+            Architecture::X86_64
+            | Architecture::AArch64
+            | Architecture::RiscV64
+            | Architecture::LoongArch64 => ElfClass::Elf64,
+        }
+    }
+
+    /// Returns how relocations for this architecture store their addends. This applies both to the
+    /// relocations that we read and to the dynamic relocations that we write.
+    pub(crate) fn relocation_format(self) -> RelocationFormat {
+        match self {
+            Architecture::I386 => RelocationFormat::Rel,
+            Architecture::X86_64
+            | Architecture::AArch64
+            | Architecture::RiscV64
+            | Architecture::LoongArch64 => RelocationFormat::Rela,
+        }
+    }
+
+    /// Returns whether we can link big-endian files for this architecture.
+    pub(crate) fn supports_big_endian(self) -> bool {
+        self == Architecture::AArch64
+    }
 }
 
 impl FromStr for Architecture {
@@ -45,6 +187,9 @@ impl FromStr for Architecture {
         match s {
             "elf_x86_64" => Ok(Architecture::X86_64),
             "aarch64elf" | "aarch64linux" => Ok(Architecture::AArch64),
+            "elf64lriscv" => Ok(Architecture::RiscV64),
+            "elf64loongarch" => Ok(Architecture::LoongArch64),
+            "elf_i386" => Ok(Architecture::I386),
             _ => bail!("-m {s} is not yet supported"),
         }
     }
@@ -57,6 +202,9 @@ impl TryFrom<u16> for Architecture {
         match arch {
             EM_X86_64 => Ok(Self::X86_64),
             EM_AARCH64 => Ok(Self::AArch64),
+            EM_RISCV => Ok(Self::RiscV64),
+            EM_LOONGARCH => Ok(Self::LoongArch64),
+            EM_386 => Ok(Self::I386),
             _ => bail!("Unsupported architecture: 0x{:x}", arch),
         }
     }
@@ -14,6 +14,10 @@
 
 use crate::alignment::Alignment;
 use crate::arch::Architecture;
+use crate::arch::PltProtection;
+use crate::diagnostics::ColourChoice;
+use crate::diagnostics::WarningCategory;
+use crate::elf::ElfClass;
 use crate::error::Result;
 use crate::input_data::FileId;
 use crate::linker_script::maybe_forced_sysroot;
@@ -21,7 +25,9 @@ use crate::save_dir::SaveDir;
 use anyhow::Context as _;
 use anyhow::bail;
 use anyhow::ensure;
+use object::Endianness;
 use rayon::ThreadPoolBuilder;
+use std::cell::Cell;
 use std::num::NonZeroUsize;
 use std::path::Path;
 use std::path::PathBuf;
@@ -31,6 +37,11 @@ use std::sync::atomic::AtomicI64;
 
 pub struct Args {
     pub(crate) arch: Architecture,
+
+    /// The byte order of the output. Set by the emulation or by `-EB` / `-EL`. All of our inputs
+    /// must have the same byte order.
+    pub(crate) endian: Endianness,
+
     pub(crate) lib_search_path: Vec<Box<Path>>,
     pub(crate) inputs: Vec<Input>,
     pub(crate) output: Arc<Path>,
@@ -50,17 +61,113 @@ pub struct Args {
     pub(crate) should_write_eh_frame_hdr: bool,
     pub(crate) write_trace: bool,
     pub(crate) rpaths: Vec<String>,
+    pub(crate) rpath_link: Vec<Box<Path>>,
     pub(crate) soname: Option<String>,
     pub(crate) files_per_group: Option<u32>,
     pub(crate) gc_sections: bool,
+
+    /// Whether references to `__start_SEC` / `__stop_SEC` should not prevent sections named `SEC`
+    /// from being garbage collected.
+    pub(crate) start_stop_gc: bool,
+
+    /// Whether `.text.hot.*`, `.text.unlikely.*` etc should be placed into output sections named
+    /// for their prefix rather than into `.text`.
+    pub(crate) keep_text_section_prefix: bool,
+
+    /// How input sections should be sorted within their output sections.
+    pub(crate) sort_section: Option<SortSection>,
+
+    /// Whether to work around Cortex-A53 erratum 843419, where a load or store that uses an address
+    /// computed by an `adrp` at the end of a page can access the wrong address.
+    pub(crate) fix_cortex_a53_843419: bool,
+
+    /// Whether to apply linker relaxations that make sections smaller. Only RISC-V has these.
+    pub(crate) relax: bool,
+
     pub(crate) should_fork: bool,
     pub(crate) build_id: BuildIdOption,
     pub(crate) file_write_mode: Option<FileWriteMode>,
-    pub(crate) no_undefined: bool,
+    /// Whether to report undefined symbols referenced by regular objects. If not set, then this
+    /// depends on the output kind.
+    pub(crate) report_undefined_in_objects: Option<bool>,
+
+    /// Whether to report undefined symbols referenced by shared objects that we link against. If not
+    /// set, then this depends on the output kind.
+    pub(crate) report_undefined_in_shared_libs: Option<bool>,
+
+    /// Whether unresolved symbols should be reported as warnings rather than errors.
+    pub(crate) warn_unresolved_symbols: bool,
     pub(crate) allow_copy_relocations: bool,
+
+    /// Whether we may emit dynamic relocations that modify read-only sections at runtime. Set by
+    /// `-z notext`.
+    pub(crate) allow_text_relocations: bool,
+
+    /// Whether to warn when we emit text relocations.
+    pub(crate) warn_text_relocations: bool,
+
+    /// Control-flow protection features that the output should claim to support even if some of
+    /// our inputs don't. Set by `-z ibt`, `-z shstk` and `-z force-bti`.
+    pub(crate) force_ibt: bool,
+    pub(crate) force_shstk: bool,
+    pub(crate) force_bti: bool,
+
+    /// Whether PLT entries should authenticate the addresses that they load from the GOT. Set by
+    /// `-z pac-plt`.
+    pub(crate) pac_plt: bool,
+
+    /// How to report input objects that lack the x86 IBT or SHSTK properties.
+    pub(crate) cet_report: FeatureReport,
+
+    /// How to report input objects that lack the AArch64 BTI property. If not set, then we warn if
+    /// BTI is being forced.
+    pub(crate) bti_report: Option<FeatureReport>,
     pub(crate) sysroot: Option<Box<Path>>,
     pub(crate) undefined: Vec<String>,
 
+    /// Whether to allow the version script to assign versions to symbols that aren't defined.
+    pub(crate) undefined_version: bool,
+
+    /// Archives whose global symbols should not be exported.
+    pub(crate) exclude_libs: ExcludeLibs,
+
+    /// Overrides the address at which the output image starts.
+    pub(crate) image_base: Option<u64>,
+
+    /// Addresses at which particular output sections should be placed, as set by `-Ttext`,
+    /// `--section-start` etc. If a section is specified more than once, the last one wins.
+    pub(crate) section_start: Vec<(String, u64)>,
+
+    /// Which local symbols to leave out of the symbol table.
+    pub(crate) discard: DiscardMode,
+
+    /// If set, the symbol table should only contain the symbols listed in this file.
+    pub(crate) retain_symbols_file: Option<PathBuf>,
+
+    /// If set, we write a Make-style dependency file listing all our inputs to this path.
+    pub(crate) dependency_file: Option<PathBuf>,
+
+    /// If set, we write a tar file containing all our inputs and our arguments to this path so that
+    /// the link can be reproduced elsewhere.
+    pub(crate) reproduce: Option<PathBuf>,
+
+    /// The arguments that we were parsed from, excluding the program name.
+    pub(crate) raw_args: Vec<String>,
+
+    /// The paths that we found in `raw_args`, in order.
+    pub(crate) raw_paths: Vec<RawPath>,
+
+    /// Whether linking should fail if we issue any warnings.
+    pub(crate) fatal_warnings: bool,
+
+    /// Whether warnings should be suppressed.
+    pub(crate) no_warnings: bool,
+
+    pub(crate) colour_diagnostics: ColourChoice,
+
+    /// The maximum number of errors to report before stopping. Zero means no limit.
+    pub(crate) error_limit: usize,
+
     /// If set, GC stats will be written to the specified filename.
     pub(crate) write_gc_stats: Option<PathBuf>,
 
@@ -72,6 +179,11 @@ pub struct Args {
 
     pub(crate) print_allocations: Option<FileId>,
     pub(crate) execstack: bool,
+
+    /// Extra bits for `DT_FLAGS` and `DT_FLAGS_1`, as requested by `-z` options such as `-z origin`
+    /// and `-z nodelete`.
+    pub(crate) dt_flags: u32,
+    pub(crate) dt_flags_1: u32,
     pub(crate) verify_allocation_consistency: bool,
     pub(crate) should_print_version: bool,
     pub(crate) demangle: bool,
@@ -81,6 +193,31 @@ pub struct Args {
     relocation_model: RelocationModel,
 }
 
+/// A path within one of our raw arguments. `--reproduce` needs to rewrite these.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct RawPath {
+    /// The index of the argument within `Args::raw_args`.
+    pub(crate) index: usize,
+
+    /// The length of the path, which is always at the end of the argument. e.g. for
+    /// `--version-script=a.ver`, this is 5.
+    pub(crate) len: usize,
+
+    pub(crate) kind: RawPathKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RawPathKind {
+    /// A file or directory that we read from.
+    Input,
+
+    /// A file that we write.
+    Output,
+
+    /// The path of the reproducer itself.
+    Reproduce,
+}
+
 #[derive(Debug)]
 pub(crate) enum BuildIdOption {
     None,
@@ -89,6 +226,26 @@ pub(crate) enum BuildIdOption {
     Uuid,
 }
 
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) enum ExcludeLibs {
+    #[default]
+    None,
+
+    /// Symbols from all archives should be excluded.
+    All,
+
+    /// Symbols from archives with any of these names should be excluded.
+    Named(Vec<String>),
+}
+
+/// What to do when we encounter a reference to a symbol that isn't defined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum UnresolvedSymbolAction {
+    Ignore,
+    Warn,
+    Error,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum OutputKind {
     StaticExecutable(RelocationModel),
@@ -139,6 +296,38 @@ pub(crate) struct Input {
     pub(crate) modifiers: Modifiers,
 }
 
+/// What to do about input objects that lack a control-flow protection feature. Selected by
+/// `-z cet-report=` and `-z bti-report=`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FeatureReport {
+    None,
+    Warning,
+    Error,
+}
+
+/// Which local symbols get discarded from the symbol table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DiscardMode {
+    /// Discard temporary local symbols, i.e. those with names starting with `.L`.
+    Temporary,
+
+    /// Discard all local symbols.
+    All,
+
+    /// Keep all local symbols, even temporary ones.
+    None,
+}
+
+/// How input sections get sorted within an output section, as requested by `--sort-section`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SortSection {
+    /// Sort by input section name. Sections with the same name stay in input order.
+    Name,
+
+    /// Sort by descending alignment. This is how we order input sections anyway.
+    Alignment,
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub(crate) enum InputSpec {
     File(Box<Path>),
@@ -159,6 +348,9 @@ pub(crate) const FILES_PER_GROUP_ENV: &str = "WILD_FILES_PER_GROUP";
 /// inconsistency.
 pub(crate) const WRITE_VERIFY_ALLOCATIONS_ENV: &str = "WILD_VERIFY_ALLOCATIONS";
 
+/// The default for --error-limit. This matches what LLD uses.
+const DEFAULT_ERROR_LIMIT: usize = 20;
+
 // These flags don't currently affect our behaviour. TODO: Assess whether we should error or warn if
 // these are given. This is tricky though. On the one hand we want to be a drop-in replacement for
 // other linkers. On the other, we should perhaps somehow let the user know that we don't support a
@@ -173,32 +365,22 @@ const SILENTLY_IGNORED_FLAGS: &[&str] = &[
     // built-in search paths. Perhaps we should?
     "nostdlib",
     // TODO
-    "no-undefined-version",
     "export-dynamic",
-    "fatal-warnings",
-    "color-diagnostics",
-    "undefined-version",
     "sort-common",
-    "no-relax",
 ];
 
 const IGNORED_FLAGS: &[&str] = &[
     "gdb-index",
     "disable-new-dtags",
     "fix-cortex-a53-835769",
-    "fix-cortex-a53-843419",
     "no-export-dynamic",
 ];
 
 // These flags map to the default behavior of the linker.
 const DEFAULT_FLAGS: &[&str] = &[
     "no-call-graph-profile-sort",
-    "relax",
     "no-copy-dt-needed-entries",
     "no-add-needed",
-    "discard-locals",
-    "X",  // alias for --discard-locals
-    "EL", // little endian
     "enable-new-dtags",
 ];
 
@@ -210,6 +392,9 @@ impl Default for Args {
     fn default() -> Self {
         Args {
             arch: default_target_arch(),
+            // As with the architecture, we default to the byte order of the machine that we're
+            // running on.
+            endian: Endianness::default(),
 
             lib_search_path: Vec::new(),
             inputs: Vec::new(),
@@ -227,10 +412,23 @@ impl Default for Args {
             // will almost always be as slow or slower than --gc-sections. For that reason, the latter is
             // probably a good default.
             gc_sections: true,
+            start_stop_gc: false,
+            keep_text_section_prefix: false,
+            sort_section: None,
+            fix_cortex_a53_843419: false,
+            relax: true,
             prepopulate_maps: false,
             sym_info: None,
             merge_strings: true,
             allow_copy_relocations: true,
+            allow_text_relocations: false,
+            warn_text_relocations: false,
+            force_ibt: false,
+            force_shstk: false,
+            force_bti: false,
+            pac_plt: false,
+            cet_report: FeatureReport::None,
+            bti_report: None,
             debug_fuel: None,
             validate_output: std::env::var(VALIDATE_ENV).is_ok_and(|v| v == "1"),
             write_layout: std::env::var(WRITE_LAYOUT_ENV).is_ok_and(|v| v == "1"),
@@ -249,23 +447,59 @@ impl Default for Args {
             gc_stats_ignore: Vec::new(),
             verbose_gc_stats: false,
             rpaths: Vec::new(),
+            rpath_link: Vec::new(),
             soname: None,
             execstack: false,
+            dt_flags: 0,
+            dt_flags_1: 0,
             should_fork: true,
             file_write_mode: None,
             build_id: BuildIdOption::None,
             files_per_group: None,
-            no_undefined: false,
+            report_undefined_in_objects: None,
+            report_undefined_in_shared_libs: None,
+            warn_unresolved_symbols: false,
             should_print_version: false,
             sysroot: None,
             demangle: true,
             undefined: Vec::new(),
+            undefined_version: false,
+            exclude_libs: ExcludeLibs::None,
+            image_base: None,
+            discard: DiscardMode::Temporary,
+            retain_symbols_file: None,
+            section_start: Vec::new(),
+            dependency_file: None,
+            reproduce: None,
+            raw_args: Vec::new(),
+            raw_paths: Vec::new(),
+            fatal_warnings: false,
+            no_warnings: false,
+            colour_diagnostics: ColourChoice::Auto,
+            error_limit: DEFAULT_ERROR_LIMIT,
         }
     }
 }
 
 // Parse the supplied input arguments, which should not include the program name.
-pub(crate) fn parse<S: AsRef<str>, I: Iterator<Item = S>>(mut input: I) -> Result<Args> {
+pub(crate) fn parse<S: AsRef<str>, I: Iterator<Item = S>>(input: I) -> Result<Args> {
+    let mut raw_args = Vec::new();
+    let num_raw_args = Cell::new(0);
+    let mut input = input.inspect(|arg| {
+        raw_args.push(arg.as_ref().to_owned());
+        num_raw_args.set(num_raw_args.get() + 1);
+    });
+
+    // Records that `path` is at the end of the argument that we read most recently.
+    let mut raw_paths = Vec::new();
+    let mut record_path = |path: &str, kind: RawPathKind| {
+        raw_paths.push(RawPath {
+            index: num_raw_args.get() - 1,
+            len: path.len(),
+            kind,
+        });
+    };
+
     let mut args = Args {
         files_per_group: std::env::var(FILES_PER_GROUP_ENV)
             .ok()
@@ -280,6 +514,10 @@ pub(crate) fn parse<S: AsRef<str>, I: Iterator<Item = S>>(mut input: I) -> Resul
 
     let mut modifier_stack = vec![Modifiers::default()];
 
+    // `-EB` and `-EL` take precedence over the byte order implied by the emulation, regardless of
+    // the order in which they're given.
+    let mut endian_override = None;
+
     if std::env::var(REFERENCE_LINKER_ENV).is_ok() {
         args.write_layout = true;
         args.write_trace = true;
@@ -307,13 +545,42 @@ pub(crate) fn parse<S: AsRef<str>, I: Iterator<Item = S>>(mut input: I) -> Resul
         let mut handle_z_option = |arg: &str| -> Result {
             match arg {
                 "now" => {}
-                "origin" => {}
+                "origin" => {
+                    args.dt_flags |= object::elf::DF_ORIGIN;
+                    args.dt_flags_1 |= object::elf::DF_1_ORIGIN;
+                }
+                "global" => args.dt_flags_1 |= object::elf::DF_1_GLOBAL,
+                "initfirst" => args.dt_flags_1 |= object::elf::DF_1_INITFIRST,
+                "interpose" => args.dt_flags_1 |= object::elf::DF_1_INTERPOSE,
+                "loadfltr" => args.dt_flags_1 |= object::elf::DF_1_LOADFLTR,
+                "nodefaultlib" => args.dt_flags_1 |= object::elf::DF_1_NODEFLIB,
+                "nodelete" => args.dt_flags_1 |= object::elf::DF_1_NODELETE,
+                "nodlopen" => args.dt_flags_1 |= object::elf::DF_1_NOOPEN,
+                "nodump" => args.dt_flags_1 |= object::elf::DF_1_NODUMP,
                 "relro" => {}
-                "notext" => {}
-                "nostart-stop-gc" => {}
+                "text" => args.allow_text_relocations = false,
+                "notext" => args.allow_text_relocations = true,
+                "start-stop-gc" => args.start_stop_gc = true,
+                "nostart-stop-gc" => args.start_stop_gc = false,
+                "keep-text-section-prefix" => args.keep_text_section_prefix = true,
+                "nokeep-text-section-prefix" => args.keep_text_section_prefix = false,
                 "execstack" => args.execstack = true,
                 "noexecstack" => args.execstack = false,
                 "nocopyreloc" => args.allow_copy_relocations = false,
+                "ibt" => args.force_ibt = true,
+                // Our x86-64 PLT entries always start with `endbr64`.
+                "ibtplt" => {}
+                "shstk" => args.force_shstk = true,
+                "force-bti" => args.force_bti = true,
+                "pac-plt" => args.pac_plt = true,
+                "defs" => args.report_undefined_in_objects = Some(true),
+                "undefs" => args.report_undefined_in_objects = Some(false),
+                _ if arg.starts_with("cet-report=") => {
+                    args.cet_report = FeatureReport::parse(&arg["cet-report=".len()..])?;
+                }
+                _ if arg.starts_with("bti-report=") => {
+                    args.bti_report = Some(FeatureReport::parse(&arg["bti-report=".len()..])?);
+                }
                 _ => {
                     warn_unsupported(&format!("-z {arg}"))?;
                     // TODO: Handle these
@@ -331,10 +598,12 @@ pub(crate) fn parse<S: AsRef<str>, I: Iterator<Item = S>>(mut input: I) -> Resul
             };
             if rest.is_empty() {
                 if let Some(next) = input.next() {
+                    record_path(next.as_ref(), RawPathKind::Input);
                     args.lib_search_path
                         .push(handle_sysroot(Path::new(next.as_ref())));
                 }
             } else {
+                record_path(rest, RawPathKind::Input);
                 args.lib_search_path.push(handle_sysroot(Path::new(rest)));
             }
         } else if let Some(rest) = arg.strip_prefix("-l") {
@@ -347,11 +616,16 @@ pub(crate) fn parse<S: AsRef<str>, I: Iterator<Item = S>>(mut input: I) -> Resul
             modifier_stack.last_mut().unwrap().allow_shared = false;
         } else if long_arg_eq("Bdynamic") {
             modifier_stack.last_mut().unwrap().allow_shared = true;
-        } else if arg == "-o" {
-            args.output = input
-                .next()
-                .map(|a| Arc::from(Path::new(a.as_ref())))
-                .context("Missing argument to -o")?;
+        } else if arg == "-o" || long_arg_eq("output") {
+            let path = input.next().context("Missing argument to -o")?;
+            record_path(path.as_ref(), RawPathKind::Output);
+            args.output = Arc::from(Path::new(path.as_ref()));
+        } else if let Some(path) = long_arg_split_prefix("output=") {
+            record_path(path, RawPathKind::Output);
+            args.output = Arc::from(Path::new(path));
+        } else if let Some(path) = arg.strip_prefix("-o") {
+            record_path(path, RawPathKind::Output);
+            args.output = Arc::from(Path::new(path));
         } else if long_arg_eq("dynamic-linker") {
             args.is_dynamic_executable = true;
             args.dynamic_linker = input.next().map(|a| Box::from(Path::new(a.as_ref())));
@@ -384,6 +658,11 @@ pub(crate) fn parse<S: AsRef<str>, I: Iterator<Item = S>>(mut input: I) -> Resul
                     "Invalid build-id value `{s}` valid values are `none`, `fast`, `md5`, `sha1` and `uuid`"
                 ),
             };
+        } else if let Some(value) = long_arg_split_prefix("sort-section=") {
+            args.sort_section = Some(SortSection::parse(value)?);
+        } else if long_arg_eq("sort-section") {
+            let value = input.next().context("Missing argument to --sort-section")?;
+            args.sort_section = Some(SortSection::parse(value.as_ref())?);
         } else if let Some(value) = long_arg_split_prefix("icf=") {
             match value {
                 "none" => {}
@@ -409,18 +688,25 @@ pub(crate) fn parse<S: AsRef<str>, I: Iterator<Item = S>>(mut input: I) -> Resul
             args.gc_sections = true;
         } else if long_arg_eq("no-gc-sections") {
             args.gc_sections = false;
+        } else if long_arg_eq("fix-cortex-a53-843419") {
+            args.fix_cortex_a53_843419 = true;
+        } else if long_arg_eq("relax") {
+            args.relax = true;
+        } else if long_arg_eq("no-relax") {
+            args.relax = false;
         } else if long_arg_eq("no-fork") {
             args.should_fork = false;
         } else if long_arg_eq("update-in-place") {
             args.file_write_mode = Some(FileWriteMode::UpdateInPlace);
         } else if arg == "-m" {
             let arg_value = input.next().context("Missing argument to -m")?;
-            let arg_value = arg_value.as_ref();
-            args.arch = Architecture::from_str(arg_value)?;
+            (args.arch, args.endian) = parse_emulation(arg_value.as_ref())?;
         } else if let Some(arg_value) = arg.strip_prefix("-m") {
-            args.arch = Architecture::from_str(arg_value)?;
+            (args.arch, args.endian) = parse_emulation(arg_value)?;
         } else if long_arg_eq("EB") {
-            bail!("Big-endian target is not supported");
+            endian_override = Some(Endianness::Big);
+        } else if long_arg_eq("EL") {
+            endian_override = Some(Endianness::Little);
         } else if arg == "-z" {
             handle_z_option(input.next().context("Missing argument to -z")?.as_ref())?;
         } else if let Some(arg) = arg.strip_prefix("-z") {
@@ -458,9 +744,11 @@ pub(crate) fn parse<S: AsRef<str>, I: Iterator<Item = S>>(mut input: I) -> Resul
                 .context("Missing argument to -version-script")?
                 .as_ref()
                 .to_owned();
+            record_path(&script, RawPathKind::Input);
             save_dir.handle_file(&script)?;
             args.version_script_path = Some(PathBuf::from(script));
         } else if let Some(script) = long_arg_split_prefix("version-script=") {
+            record_path(script, RawPathKind::Input);
             save_dir.handle_file(script)?;
             args.version_script_path = Some(PathBuf::from(script));
         } else if long_arg_eq("rpath") {
@@ -503,10 +791,63 @@ pub(crate) fn parse<S: AsRef<str>, I: Iterator<Item = S>>(mut input: I) -> Resul
                 .to_owned();
             warn_unsupported(&format!("--plugin {other}"))?;
         } else if let Some(rest) = long_arg_split_prefix("dependency-file=") {
-            warn_unsupported(&format!("--dependency-file={rest}"))?;
+            record_path(rest, RawPathKind::Output);
+            args.dependency_file = Some(PathBuf::from(rest));
+        } else if long_arg_eq("dependency-file") {
+            let path = input
+                .next()
+                .context("Missing argument to --dependency-file")?;
+            record_path(path.as_ref(), RawPathKind::Output);
+            args.dependency_file = Some(PathBuf::from(path.as_ref()));
+        } else if long_arg_eq("discard-locals") || arg == "-X" {
+            args.discard = DiscardMode::Temporary;
+        } else if long_arg_eq("discard-all") || arg == "-x" {
+            args.discard = DiscardMode::All;
+        } else if long_arg_eq("discard-none") {
+            args.discard = DiscardMode::None;
+        } else if let Some(rest) = long_arg_split_prefix("retain-symbols-file=") {
+            record_path(rest, RawPathKind::Input);
+            args.retain_symbols_file = Some(PathBuf::from(rest));
+        } else if long_arg_eq("retain-symbols-file") {
+            let path = input
+                .next()
+                .context("Missing argument to --retain-symbols-file")?;
+            record_path(path.as_ref(), RawPathKind::Input);
+            args.retain_symbols_file = Some(PathBuf::from(path.as_ref()));
+        } else if let Some(rest) = long_arg_split_prefix("image-base=") {
+            args.image_base = Some(parse_image_base(rest)?);
+        } else if long_arg_eq("image-base") {
+            let value = input.next().context("Missing argument to --image-base")?;
+            args.image_base = Some(parse_image_base(value.as_ref())?);
+        } else if let Some(rest) = long_arg_split_prefix("section-start=") {
+            let (name, address) = rest
+                .split_once('=')
+                .with_context(|| format!("Invalid --section-start `{rest}`"))?;
+            args.set_section_start(name, address)?;
+        } else if let Some(section) = strip_option(arg).and_then(section_for_t_option) {
+            let value = input
+                .next()
+                .with_context(|| format!("Missing argument to {arg}"))?;
+            args.set_section_start(section, value.as_ref())?;
+        } else if let Some((section, address)) = strip_option(arg)
+            .and_then(|a| a.split_once('='))
+            .and_then(|(option, address)| Some((section_for_t_option(option)?, address)))
+        {
+            args.set_section_start(section, address)?;
+        } else if let Some(rest) = long_arg_split_prefix("reproduce=") {
+            record_path(rest, RawPathKind::Reproduce);
+            args.reproduce = Some(PathBuf::from(rest));
+        } else if long_arg_eq("reproduce") {
+            let path = input.next().context("Missing argument to --reproduce")?;
+            record_path(path.as_ref(), RawPathKind::Reproduce);
+            args.reproduce = Some(PathBuf::from(path.as_ref()));
         } else if long_arg_eq("rpath-link") {
-            // TODO
-            input.next();
+            let dirs = input.next().context("Missing argument to -rpath-link")?;
+            record_path(dirs.as_ref(), RawPathKind::Input);
+            args.add_rpath_link(dirs.as_ref());
+        } else if let Some(rest) = long_arg_split_prefix("rpath-link=") {
+            record_path(rest, RawPathKind::Input);
+            args.add_rpath_link(rest);
         } else if long_arg_eq("validate-output") {
             args.validate_output = true;
         } else if long_arg_eq("write-layout") {
@@ -529,7 +870,45 @@ pub(crate) fn parse<S: AsRef<str>, I: Iterator<Item = S>>(mut input: I) -> Resul
             // results.
             args.num_threads = NonZeroUsize::new(1).unwrap();
         } else if long_arg_eq("no-undefined") {
-            args.no_undefined = true;
+            args.report_undefined_in_objects = Some(true);
+        } else if long_arg_eq("allow-shlib-undefined") {
+            args.report_undefined_in_shared_libs = Some(false);
+        } else if long_arg_eq("no-allow-shlib-undefined") {
+            args.report_undefined_in_shared_libs = Some(true);
+        } else if long_arg_eq("undefined-version") {
+            args.undefined_version = true;
+        } else if long_arg_eq("no-undefined-version") {
+            args.undefined_version = false;
+        } else if let Some(rest) = long_arg_split_prefix("unresolved-symbols=") {
+            args.set_unresolved_symbols(rest)?;
+        } else if long_arg_eq("unresolved-symbols") {
+            let method = input
+                .next()
+                .context("Missing argument to --unresolved-symbols")?;
+            args.set_unresolved_symbols(method.as_ref())?;
+        } else if long_arg_eq("warn-unresolved-symbols") {
+            args.warn_unresolved_symbols = true;
+        } else if long_arg_eq("error-unresolved-symbols") {
+            args.warn_unresolved_symbols = false;
+        } else if long_arg_eq("warn-textrel") {
+            args.warn_text_relocations = true;
+        } else if long_arg_eq("fatal-warnings") {
+            args.fatal_warnings = true;
+        } else if long_arg_eq("no-fatal-warnings") {
+            args.fatal_warnings = false;
+        } else if long_arg_eq("no-warnings") || arg == "-w" {
+            args.no_warnings = true;
+        } else if long_arg_eq("color-diagnostics") {
+            args.colour_diagnostics = ColourChoice::Always;
+        } else if let Some(rest) = long_arg_split_prefix("color-diagnostics=") {
+            args.colour_diagnostics = ColourChoice::parse(rest)?;
+        } else if long_arg_eq("no-color-diagnostics") {
+            args.colour_diagnostics = ColourChoice::Never;
+        } else if let Some(rest) = long_arg_split_prefix("error-limit=") {
+            args.error_limit = parse_error_limit(rest)?;
+        } else if long_arg_eq("error-limit") {
+            let limit = input.next().context("Missing argument to --error-limit")?;
+            args.error_limit = parse_error_limit(limit.as_ref())?;
         } else if let Some(rest) = long_arg_split_prefix("undefined=") {
             args.undefined.push(rest.to_owned());
         } else if arg == "-u" {
@@ -540,6 +919,15 @@ pub(crate) fn parse<S: AsRef<str>, I: Iterator<Item = S>>(mut input: I) -> Resul
                     .as_ref()
                     .to_owned(),
             );
+        } else if long_arg_eq("exclude-libs") {
+            args.exclude_libs.add(
+                input
+                    .next()
+                    .context("Missing argument to --exclude-libs")?
+                    .as_ref(),
+            );
+        } else if let Some(rest) = long_arg_split_prefix("exclude-libs=") {
+            args.exclude_libs.add(rest);
         } else if long_arg_eq("demangle") {
             args.demangle = true;
         } else if long_arg_eq("no-demangle") {
@@ -562,6 +950,7 @@ pub(crate) fn parse<S: AsRef<str>, I: Iterator<Item = S>>(mut input: I) -> Resul
             .is_some_and(|stripped_arg| SILENTLY_IGNORED_FLAGS.contains(&stripped_arg))
         {
         } else if let Some(sysroot) = long_arg_split_prefix("sysroot=") {
+            record_path(sysroot, RawPathKind::Input);
             let sysroot = Path::new(sysroot);
             args.sysroot = Some(Box::from(sysroot));
             for path in &mut args.lib_search_path {
@@ -572,6 +961,7 @@ pub(crate) fn parse<S: AsRef<str>, I: Iterator<Item = S>>(mut input: I) -> Resul
         } else if arg.starts_with('-') {
             unrecognised.push(format!("`{arg}`"));
         } else {
+            record_path(arg, RawPathKind::Input);
             save_dir.handle_file(arg)?;
             args.inputs.push(Input {
                 spec: InputSpec::File(Box::from(Path::new(arg))),
@@ -585,6 +975,13 @@ pub(crate) fn parse<S: AsRef<str>, I: Iterator<Item = S>>(mut input: I) -> Resul
         bail!("Unrecognised argument(s): {}", unrecognised.join(" "));
     }
 
+    if let Some(endian) = endian_override {
+        args.endian = endian;
+    }
+    if args.endian == Endianness::Big && !args.arch.supports_big_endian() {
+        bail!("Big-endian output is not supported for {:?}", args.arch);
+    }
+
     // Copy relocations are only permitted when building executables.
     if args.output_kind() == OutputKind::SharedObject {
         args.allow_copy_relocations = false;
@@ -592,10 +989,14 @@ pub(crate) fn parse<S: AsRef<str>, I: Iterator<Item = S>>(mut input: I) -> Resul
 
     save_dir.finish()?;
 
+    drop(input);
+    args.raw_args = raw_args;
+    args.raw_paths = raw_paths;
+
     Ok(args)
 }
 
-const fn default_target_arch() -> Architecture {
+pub(crate) const fn default_target_arch() -> Architecture {
     // We default to targeting the architecture that we're running on. We don't support running on
     // architectures that we can't target.
     #[cfg(target_arch = "x86_64")]
@@ -606,6 +1007,22 @@ const fn default_target_arch() -> Architecture {
     {
         Architecture::AArch64
     }
+    #[cfg(target_arch = "riscv64")]
+    {
+        Architecture::RiscV64
+    }
+    #[cfg(target_arch = "loongarch64")]
+    {
+        Architecture::LoongArch64
+    }
+}
+
+/// Parses the value of `-m`, which determines both the architecture and the byte order.
+fn parse_emulation(value: &str) -> Result<(Architecture, Endianness)> {
+    match value {
+        "aarch64elfb" | "aarch64linuxb" => Ok((Architecture::AArch64, Endianness::Big)),
+        _ => Ok((Architecture::from_str(value)?, Endianness::Little)),
+    }
 }
 
 fn parse_from_argument_file(path: &Path) -> Result<Args> {
@@ -616,6 +1033,8 @@ fn parse_from_argument_file(path: &Path) -> Result<Args> {
 
 impl Args {
     pub fn parse<S: AsRef<str>, I: Iterator<Item = S>>(input: I) -> Result<Args> {
+        // Parsing arguments is the first thing that we do for a link.
+        crate::diagnostics::reset();
         parse(input)
     }
 
@@ -626,8 +1045,73 @@ impl Args {
         Ok(())
     }
 
+    fn set_unresolved_symbols(&mut self, method: &str) -> Result {
+        let (in_objects, in_shared_libs) = match method {
+            "ignore-all" => (false, false),
+            "report-all" => (true, true),
+            "ignore-in-object-files" => (false, true),
+            "ignore-in-shared-libs" => (true, false),
+            other => bail!("Invalid value for --unresolved-symbols: `{other}`"),
+        };
+        self.report_undefined_in_objects = Some(in_objects);
+        self.report_undefined_in_shared_libs = Some(in_shared_libs);
+        Ok(())
+    }
+
+    /// Returns what we should do with undefined symbols referenced by regular objects. By default,
+    /// these are allowed when producing a shared object, since they may be provided at runtime.
+    pub(crate) fn unresolved_symbols_in_objects(&self) -> UnresolvedSymbolAction {
+        self.unresolved_symbol_action(
+            self.report_undefined_in_objects
+                .unwrap_or(self.output_kind() != OutputKind::SharedObject),
+        )
+    }
+
+    /// Returns what we should do with undefined symbols referenced by shared objects that we link
+    /// against. By default, these are only reported when producing an executable.
+    pub(crate) fn unresolved_symbols_in_shared_libs(&self) -> UnresolvedSymbolAction {
+        self.unresolved_symbol_action(
+            self.report_undefined_in_shared_libs
+                .unwrap_or(self.output_kind().is_executable()),
+        )
+    }
+
+    fn unresolved_symbol_action(&self, report: bool) -> UnresolvedSymbolAction {
+        if !report {
+            UnresolvedSymbolAction::Ignore
+        } else if self.warn_unresolved_symbols {
+            UnresolvedSymbolAction::Warn
+        } else {
+            UnresolvedSymbolAction::Error
+        }
+    }
+
+    fn add_rpath_link(&mut self, dirs: &str) {
+        self.rpath_link.extend(
+            dirs.split(':')
+                .filter(|dir| !dir.is_empty())
+                .map(|dir| Box::from(Path::new(dir))),
+        );
+    }
+
+    /// Sets the address for the output section `name`. Like GNU ld, the address is always
+    /// interpreted as hex, even without a `0x` prefix.
+    fn set_section_start(&mut self, name: &str, address: &str) -> Result {
+        let hex = address
+            .strip_prefix("0x")
+            .or(address.strip_prefix("0X"))
+            .unwrap_or(address);
+        let address = u64::from_str_radix(hex, 16)
+            .with_context(|| format!("Invalid address `{address}` for section `{name}`"))?;
+        self.section_start.retain(|(existing, _)| existing != name);
+        self.section_start.push((name.to_owned(), address));
+        Ok(())
+    }
+
     pub(crate) fn base_address(&self) -> u64 {
-        if self.is_relocatable() {
+        if let Some(image_base) = self.image_base {
+            image_base
+        } else if self.is_relocatable() {
             0
         } else {
             crate::elf::NON_PIE_START_MEM_ADDRESS
@@ -711,8 +1195,72 @@ impl Args {
         match self.arch {
             Architecture::X86_64 => Alignment { exponent: 12 },
             Architecture::AArch64 => Alignment { exponent: 16 },
+            Architecture::RiscV64 => Alignment { exponent: 12 },
+            // LoongArch Linux kernels are normally configured with 16KiB pages.
+            Architecture::LoongArch64 => Alignment { exponent: 14 },
+            Architecture::I386 => Alignment { exponent: 12 },
         }
     }
+
+    pub(crate) fn elf_class(&self) -> ElfClass {
+        self.arch.elf_class()
+    }
+
+    /// Returns the size of each GOT entry, which is the size of a pointer.
+    pub(crate) fn got_entry_size(&self) -> u64 {
+        self.elf_class().pointer_size()
+    }
+
+    /// Returns the size of each of the dynamic relocations that we write. These are REL rather than
+    /// RELA entries on some architectures, but we still refer to them as RELA internally.
+    pub(crate) fn rela_entry_size(&self) -> u64 {
+        self.elf_class()
+            .relocation_entry_size(self.arch.relocation_format())
+    }
+
+    /// Returns whether our PLT entries should authenticate the addresses that they load from the
+    /// GOT. This determines the size of the entries, so is known before layout.
+    pub(crate) fn authenticate_plt(&self) -> bool {
+        self.pac_plt && self.arch == Architecture::AArch64
+    }
+
+    pub(crate) fn plt_entry_size(&self) -> u64 {
+        PltProtection {
+            landing_pad: false,
+            authenticate: self.authenticate_plt(),
+        }
+        .entry_size()
+    }
+
+    /// Returns how we should report objects that lack the AArch64 BTI property.
+    pub(crate) fn bti_report(&self) -> FeatureReport {
+        self.bti_report.unwrap_or(if self.force_bti {
+            FeatureReport::Warning
+        } else {
+            FeatureReport::None
+        })
+    }
+}
+
+impl FeatureReport {
+    fn parse(value: &str) -> Result<Self> {
+        Ok(match value {
+            "none" => FeatureReport::None,
+            "warning" => FeatureReport::Warning,
+            "error" => FeatureReport::Error,
+            other => bail!("Invalid report level `{other}`, expected none, warning or error"),
+        })
+    }
+}
+
+impl SortSection {
+    fn parse(value: &str) -> Result<Self> {
+        Ok(match value {
+            "name" => SortSection::Name,
+            "alignment" => SortSection::Alignment,
+            other => bail!("Invalid --sort-section value `{other}`, expected name or alignment"),
+        })
+    }
 }
 
 fn parse_number(s: &str) -> Result<u64> {
@@ -734,6 +1282,33 @@ impl Default for Modifiers {
     }
 }
 
+impl ExcludeLibs {
+    /// Adds archives from a comma or colon separated list. `ALL` matches all archives.
+    fn add(&mut self, list: &str) {
+        for name in list.split([',', ':']).filter(|n| !n.is_empty()) {
+            match self {
+                ExcludeLibs::All => return,
+                _ if name == "ALL" => *self = ExcludeLibs::All,
+                ExcludeLibs::None => *self = ExcludeLibs::Named(vec![name.to_owned()]),
+                ExcludeLibs::Named(names) => names.push(name.to_owned()),
+            }
+        }
+    }
+
+    /// Returns whether symbols defined by members of the archive at `archive_path` should be
+    /// excluded from export. Archives are matched by their file name without directory.
+    pub(crate) fn should_exclude(&self, archive_path: &Path) -> bool {
+        match self {
+            ExcludeLibs::None => false,
+            ExcludeLibs::All => true,
+            ExcludeLibs::Named(names) => archive_path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|file_name| names.iter().any(|n| n == file_name)),
+        }
+    }
+}
+
 impl OutputKind {
     pub(crate) fn is_executable(self) -> bool {
         !matches!(self, OutputKind::SharedObject)
@@ -833,12 +1408,40 @@ fn arguments_from_string(input: &str) -> Result<Vec<String>> {
     Ok(out)
 }
 
+/// Returns the name of the section whose address is set by options like `-Ttext`.
+fn section_for_t_option(option: &str) -> Option<&'static str> {
+    match option {
+        "Ttext" => Some(".text"),
+        "Tdata" => Some(".data"),
+        "Tbss" => Some(".bss"),
+        _ => None,
+    }
+}
+
+/// Parses an address for --image-base, which like lld, we accept in either decimal or hex.
+fn parse_image_base(value: &str) -> Result<u64> {
+    let parsed = match value.strip_prefix("0x").or(value.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => value.parse(),
+    };
+    parsed.with_context(|| format!("Invalid --image-base `{value}`"))
+}
+
+fn parse_error_limit(value: &str) -> Result<usize> {
+    value
+        .parse()
+        .with_context(|| format!("Invalid --error-limit `{value}`"))
+}
+
 fn warn_unsupported(opt: &str) -> Result {
     match std::env::var(WILD_UNSUPPORTED_ENV)
         .unwrap_or_default()
         .as_str()
     {
-        "warn" | "" => crate::error::warning(&format!("{opt} is not yet supported")),
+        "warn" | "" => crate::diagnostics::warning(
+            WarningCategory::UnsupportedOption,
+            &format!("{opt} is not yet supported"),
+        ),
         "ignore" => {}
         "error" => bail!("{opt} is not yet supported"),
         other => bail!("Unsupported value for {WILD_UNSUPPORTED_ENV}={other}"),
@@ -848,9 +1451,17 @@ fn warn_unsupported(opt: &str) -> Result {
 
 #[cfg(test)]
 mod tests {
+    use super::DiscardMode;
+    use super::ExcludeLibs;
+    use super::FeatureReport;
     use super::SILENTLY_IGNORED_FLAGS;
+    use super::SortSection;
+    use super::UnresolvedSymbolAction;
+    use crate::arch::Architecture;
     use crate::args::InputSpec;
+    use crate::diagnostics::ColourChoice;
     use itertools::Itertools;
+    use object::Endianness;
     use std::num::NonZeroUsize;
     use std::path::Path;
     use std::path::PathBuf;
@@ -953,6 +1564,12 @@ mod tests {
         "--sysroot=/usr/aarch64-linux-gnu",
         "--demangle",
         "--no-demangle",
+        "--exclude-libs",
+        "libfoo.a,libbar.a",
+        "--exclude-libs=libbaz.a:",
+        "-rpath-link",
+        "/opt/a:/opt/b",
+        "--rpath-link=/opt/c",
     ];
 
     #[track_caller]
@@ -991,6 +1608,295 @@ mod tests {
             args.sysroot,
             Some(Box::from(Path::new("/usr/aarch64-linux-gnu")))
         );
+        assert_eq!(
+            args.exclude_libs,
+            ExcludeLibs::Named(vec![
+                "libfoo.a".to_owned(),
+                "libbar.a".to_owned(),
+                "libbaz.a".to_owned()
+            ])
+        );
+        assert!(
+            args.exclude_libs
+                .should_exclude(Path::new("/usr/lib/libbar.a"))
+        );
+        assert!(!args.exclude_libs.should_exclude(Path::new("libc.a")));
+        assert_contains(&args.rpath_link, "/opt/a");
+        assert_contains(&args.rpath_link, "/opt/b");
+        assert_contains(&args.rpath_link, "/opt/c");
+    }
+
+    #[test]
+    fn test_unresolved_symbols() {
+        let parse = |input: &[&str]| super::parse(input.iter()).unwrap();
+
+        let args = parse(&[]);
+        assert_eq!(
+            args.unresolved_symbols_in_objects(),
+            UnresolvedSymbolAction::Error
+        );
+        assert_eq!(
+            args.unresolved_symbols_in_shared_libs(),
+            UnresolvedSymbolAction::Error
+        );
+
+        let args = parse(&["-shared"]);
+        assert_eq!(
+            args.unresolved_symbols_in_objects(),
+            UnresolvedSymbolAction::Ignore
+        );
+        assert_eq!(
+            args.unresolved_symbols_in_shared_libs(),
+            UnresolvedSymbolAction::Ignore
+        );
+
+        let args = parse(&["-shared", "-z", "defs", "--no-allow-shlib-undefined"]);
+        assert_eq!(
+            args.unresolved_symbols_in_objects(),
+            UnresolvedSymbolAction::Error
+        );
+        assert_eq!(
+            args.unresolved_symbols_in_shared_libs(),
+            UnresolvedSymbolAction::Error
+        );
+
+        let args = parse(&[
+            "--unresolved-symbols=ignore-in-shared-libs",
+            "--warn-unresolved-symbols",
+        ]);
+        assert_eq!(
+            args.unresolved_symbols_in_objects(),
+            UnresolvedSymbolAction::Warn
+        );
+        assert_eq!(
+            args.unresolved_symbols_in_shared_libs(),
+            UnresolvedSymbolAction::Ignore
+        );
+
+        // Later flags override earlier ones.
+        let args = parse(&[
+            "--unresolved-symbols=ignore-all",
+            "--no-allow-shlib-undefined",
+        ]);
+        assert_eq!(
+            args.unresolved_symbols_in_objects(),
+            UnresolvedSymbolAction::Ignore
+        );
+        assert_eq!(
+            args.unresolved_symbols_in_shared_libs(),
+            UnresolvedSymbolAction::Error
+        );
+
+        assert!(super::parse(["--unresolved-symbols=bogus"].iter()).is_err());
+    }
+
+    #[test]
+    fn test_diagnostic_options() {
+        let args = super::parse(["-w", "--fatal-warnings", "--error-limit=5"].iter()).unwrap();
+        assert!(args.no_warnings);
+        assert!(args.fatal_warnings);
+        assert_eq!(args.error_limit, 5);
+        assert_eq!(args.colour_diagnostics, ColourChoice::Auto);
+
+        let args = super::parse(["--color-diagnostics", "--error-limit", "0"].iter()).unwrap();
+        assert_eq!(args.colour_diagnostics, ColourChoice::Always);
+        assert_eq!(args.error_limit, 0);
+
+        let args = super::parse(["--color-diagnostics=never"].iter()).unwrap();
+        assert_eq!(args.colour_diagnostics, ColourChoice::Never);
+
+        assert!(super::parse(["--error-limit=lots"].iter()).is_err());
+    }
+
+    #[test]
+    fn test_address_options() {
+        let args = super::parse(
+            [
+                "--image-base=0x200000",
+                "-Ttext=0x800000",
+                "-Tdata",
+                "a00000",
+                "--section-start=.foo=1000",
+                "-Ttext=0x900000",
+            ]
+            .iter(),
+        )
+        .unwrap();
+        assert_eq!(args.base_address(), 0x200000);
+        assert_eq!(
+            args.section_start,
+            vec![
+                (".data".to_owned(), 0xa00000),
+                (".foo".to_owned(), 0x1000),
+                (".text".to_owned(), 0x900000),
+            ]
+        );
+
+        let args = super::parse(["--image-base", "4096"].iter()).unwrap();
+        assert_eq!(args.image_base, Some(4096));
+
+        assert!(super::parse(["-Tbss=xyz"].iter()).is_err());
+        assert!(super::parse(["--section-start=.text"].iter()).is_err());
+    }
+
+    #[test]
+    fn test_discard_options() {
+        assert_eq!(
+            super::parse(["-X"].iter()).unwrap().discard,
+            DiscardMode::Temporary
+        );
+        assert_eq!(
+            super::parse(["-x"].iter()).unwrap().discard,
+            DiscardMode::All
+        );
+        assert_eq!(
+            super::parse(["--discard-all", "--discard-none"].iter())
+                .unwrap()
+                .discard,
+            DiscardMode::None
+        );
+        let args = super::parse(["--retain-symbols-file", "keep.txt"].iter()).unwrap();
+        assert_eq!(args.retain_symbols_file, Some(PathBuf::from("keep.txt")));
+    }
+
+    #[test]
+    fn test_dynamic_flags_z_options() {
+        let args = super::parse(["-z", "origin", "-znodelete", "-z", "interpose"].iter()).unwrap();
+        assert_eq!(args.dt_flags, object::elf::DF_ORIGIN);
+        assert_eq!(
+            args.dt_flags_1,
+            object::elf::DF_1_ORIGIN | object::elf::DF_1_NODELETE | object::elf::DF_1_INTERPOSE
+        );
+    }
+
+    #[test]
+    fn test_text_relocation_options() {
+        let args = super::parse(std::iter::empty::<&str>()).unwrap();
+        assert!(!args.allow_text_relocations);
+        assert!(!args.warn_text_relocations);
+        let args = super::parse(["-z", "notext", "--warn-textrel"].iter()).unwrap();
+        assert!(args.allow_text_relocations);
+        assert!(args.warn_text_relocations);
+        let args = super::parse(["-z", "notext", "-z", "text"].iter()).unwrap();
+        assert!(!args.allow_text_relocations);
+    }
+
+    #[test]
+    fn test_output_spellings() {
+        for spelling in [
+            &["-o", "out"][..],
+            &["--output", "out"],
+            &["--output=out"],
+            &["-oout"],
+        ] {
+            let args = super::parse(spelling.iter()).unwrap();
+            assert_eq!(&*args.output, Path::new("out"), "{spelling:?}");
+        }
+    }
+
+    #[test]
+    fn test_start_stop_gc() {
+        let args = super::parse(std::iter::empty::<&str>()).unwrap();
+        assert!(!args.start_stop_gc);
+        let args = super::parse(["-z", "start-stop-gc"].iter()).unwrap();
+        assert!(args.start_stop_gc);
+        let args = super::parse(["-z", "start-stop-gc", "-z", "nostart-stop-gc"].iter()).unwrap();
+        assert!(!args.start_stop_gc);
+    }
+
+    #[test]
+    fn test_section_ordering_options() {
+        let args = super::parse(std::iter::empty::<&str>()).unwrap();
+        assert!(!args.keep_text_section_prefix);
+        assert_eq!(args.sort_section, None);
+        let args =
+            super::parse(["-z", "keep-text-section-prefix", "--sort-section=name"].iter()).unwrap();
+        assert!(args.keep_text_section_prefix);
+        assert_eq!(args.sort_section, Some(SortSection::Name));
+        let args = super::parse(["--sort-section", "alignment"].iter()).unwrap();
+        assert_eq!(args.sort_section, Some(SortSection::Alignment));
+        assert!(super::parse(["--sort-section=size"].iter()).is_err());
+    }
+
+    #[test]
+    fn test_fix_cortex_a53_843419() {
+        let args = super::parse(std::iter::empty::<&str>()).unwrap();
+        assert!(!args.fix_cortex_a53_843419);
+        let args = super::parse(["--fix-cortex-a53-843419"].iter()).unwrap();
+        assert!(args.fix_cortex_a53_843419);
+    }
+
+    #[test]
+    fn test_relax() {
+        let args = super::parse(std::iter::empty::<&str>()).unwrap();
+        assert!(args.relax);
+        let args = super::parse(["-m", "elf64lriscv", "--no-relax"].iter()).unwrap();
+        assert_eq!(args.arch, Architecture::RiscV64);
+        assert!(!args.relax);
+        let args = super::parse(["--no-relax", "--relax"].iter()).unwrap();
+        assert!(args.relax);
+    }
+
+    #[test]
+    fn test_endianness() {
+        let args = super::parse(["-m", "aarch64linux"].iter()).unwrap();
+        assert_eq!(args.endian, Endianness::Little);
+        let args = super::parse(["-m", "aarch64linuxb"].iter()).unwrap();
+        assert_eq!(args.arch, Architecture::AArch64);
+        assert_eq!(args.endian, Endianness::Big);
+        let args = super::parse(["-m", "aarch64linux", "-EB"].iter()).unwrap();
+        assert_eq!(args.endian, Endianness::Big);
+        let args = super::parse(["-EL", "-m", "aarch64elfb"].iter()).unwrap();
+        assert_eq!(args.endian, Endianness::Little);
+        assert!(super::parse(["-m", "elf_x86_64", "-EB"].iter()).is_err());
+    }
+
+    #[test]
+    fn test_control_flow_protection_options() {
+        let args = super::parse(std::iter::empty::<&str>()).unwrap();
+        assert_eq!(args.cet_report, FeatureReport::None);
+        assert_eq!(args.bti_report(), FeatureReport::None);
+        let args = super::parse(["-z", "ibt", "-zshstk", "-z", "cet-report=error"].iter()).unwrap();
+        assert!(args.force_ibt);
+        assert!(args.force_shstk);
+        assert_eq!(args.cet_report, FeatureReport::Error);
+        let args = super::parse(["-m", "aarch64linux", "-z", "force-bti", "-z", "pac-plt"].iter())
+            .unwrap();
+        assert!(args.force_bti);
+        assert!(args.authenticate_plt());
+        assert_eq!(args.bti_report(), FeatureReport::Warning);
+        assert_eq!(args.plt_entry_size(), crate::elf::PAC_PLT_ENTRY_SIZE);
+        let args = super::parse(["-z", "force-bti", "-z", "bti-report=none"].iter()).unwrap();
+        assert_eq!(args.bti_report(), FeatureReport::None);
+        assert!(super::parse(["-z", "cet-report=maybe"].iter()).is_err());
+    }
+
+    #[test]
+    fn test_undefined_version() {
+        assert!(
+            !super::parse(std::iter::empty::<&str>())
+                .unwrap()
+                .undefined_version
+        );
+        assert!(
+            super::parse(["--undefined-version"].iter())
+                .unwrap()
+                .undefined_version
+        );
+        assert!(
+            !super::parse(["--undefined-version", "--no-undefined-version"].iter())
+                .unwrap()
+                .undefined_version
+        );
+    }
+
+    #[test]
+    fn test_exclude_libs_all() {
+        let mut exclude_libs = ExcludeLibs::default();
+        exclude_libs.add("libfoo.a:ALL");
+        exclude_libs.add("libbar.a");
+        assert_eq!(exclude_libs, ExcludeLibs::All);
+        assert!(exclude_libs.should_exclude(Path::new("libc.a")));
     }
 
     #[test]
@@ -0,0 +1,82 @@
+//! Writes a Make-style dependency file (--dependency-file) listing all the files that we read while
+//! linking. This allows build systems to relink when any of them change, including libraries that
+//! were found by searching the library path.
+
+use crate::args::Args;
+use crate::error::Result;
+use crate::input_data::InputData;
+use anyhow::Context as _;
+use std::fmt::Write as _;
+use std::path::Path;
+
+pub(crate) fn write(args: &Args, input_data: &InputData) -> Result {
+    let Some(dependency_file) = args.dependency_file.as_ref() else {
+        return Ok(());
+    };
+    std::fs::write(
+        dependency_file,
+        contents(&args.output, &input_data.read_paths),
+    )
+    .with_context(|| {
+        format!(
+            "Failed to write dependency file `{}`",
+            dependency_file.display()
+        )
+    })
+}
+
+/// Returns the contents of the dependency file. Each prerequisite also gets an empty rule so that
+/// Make doesn't fail if the prerequisite is later deleted.
+fn contents(output: &Path, inputs: &[impl AsRef<Path>]) -> String {
+    let mut out = String::new();
+    escape(output, &mut out);
+    out.push(':');
+    for input in inputs {
+        out.push_str(" \\\n  ");
+        escape(input.as_ref(), &mut out);
+    }
+    out.push('\n');
+    for input in inputs {
+        out.push('\n');
+        escape(input.as_ref(), &mut out);
+        out.push_str(":\n");
+    }
+    out
+}
+
+/// Escapes `path` in the way that Make expects. Backslashes that precede a space need to be doubled
+/// so that they're not treated as escaping the space.
+fn escape(path: &Path, out: &mut String) {
+    let path = path.to_string_lossy();
+    let mut num_backslashes = 0;
+    for c in path.chars() {
+        match c {
+            ' ' => {
+                for _ in 0..num_backslashes {
+                    out.push('\\');
+                }
+                out.push('\\');
+            }
+            '#' => out.push('\\'),
+            '$' => out.push('$'),
+            _ => {}
+        }
+        num_backslashes = if c == '\\' { num_backslashes + 1 } else { 0 };
+        let _ = write!(out, "{c}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contents() {
+        let inputs = ["a.o", "/lib/lib c.so", "dir#1/$x.a", r"a\ b.o"];
+        assert_eq!(
+            contents(Path::new("out"), &inputs),
+            "out: \\\n  a.o \\\n  /lib/lib\\ c.so \\\n  dir\\#1/$$x.a \\\n  a\\\\\\ b.o\n\
+             \na.o:\n\n/lib/lib\\ c.so:\n\ndir\\#1/$$x.a:\n\na\\\\\\ b.o:\n"
+        );
+    }
+}
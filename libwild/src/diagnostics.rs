@@ -0,0 +1,236 @@
+//! Reporting of warnings and errors to the user.
+//!
+//! Warnings can be issued from anywhere, including while we're still parsing arguments, so their
+//! state is global. Until `configure` has been called, warnings are buffered, since we don't yet know
+//! if they should be suppressed. Repeated warnings are only reported once. The state is reset when
+//! we start parsing the arguments for a link, so that it doesn't carry over between links run by the
+//! same process.
+
+use crate::args::Args;
+use crate::error::Error;
+use crate::error::Result;
+use anyhow::anyhow;
+use std::collections::BTreeSet;
+use std::io::IsTerminal as _;
+use std::sync::Mutex;
+
+const RED: &str = "\x1b[1;31m";
+const MAGENTA: &str = "\x1b[1;35m";
+const RESET: &str = "\x1b[0m";
+
+/// What kind of problem a warning is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum WarningCategory {
+    /// A command-line option that we don't yet support.
+    UnsupportedOption,
+
+    /// A shared object that one of our inputs depends on couldn't be found.
+    MissingDependency,
+
+    /// A symbol was referenced, but never defined.
+    UndefinedSymbol,
+
+    /// A dynamic relocation needed to be applied to a read-only section.
+    TextRelocation,
+
+    /// An input object lacks a control-flow protection property that we were asked to check for.
+    MissingProperty,
+
+    /// A `.gnu.warning` section asked us to warn when its object or a particular symbol was used.
+    GnuWarning,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum ColourChoice {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+struct Config {
+    suppress_warnings: bool,
+    colour: bool,
+}
+
+struct State {
+    /// None until `configure` is called.
+    config: Option<Config>,
+
+    /// Warnings issued before we were configured.
+    pending: Vec<(WarningCategory, String)>,
+
+    /// Warnings that we've already issued, so that we don't issue them again.
+    seen: BTreeSet<(WarningCategory, String)>,
+
+    /// The number of warnings that we've reported.
+    num_reported: usize,
+}
+
+static STATE: Mutex<State> = Mutex::new(State::new());
+
+impl WarningCategory {
+    fn name(self) -> &'static str {
+        match self {
+            WarningCategory::UnsupportedOption => "unsupported-option",
+            WarningCategory::MissingDependency => "missing-dependency",
+            WarningCategory::UndefinedSymbol => "undefined-symbol",
+            WarningCategory::TextRelocation => "text-relocation",
+            WarningCategory::MissingProperty => "missing-property",
+            WarningCategory::GnuWarning => "gnu-warning",
+        }
+    }
+}
+
+impl ColourChoice {
+    pub(crate) fn parse(value: &str) -> Result<Self> {
+        Ok(match value {
+            "auto" => ColourChoice::Auto,
+            "always" => ColourChoice::Always,
+            "never" => ColourChoice::Never,
+            other => anyhow::bail!("Invalid colour choice `{other}`"),
+        })
+    }
+
+    fn should_colour(self) -> bool {
+        match self {
+            ColourChoice::Auto => {
+                std::io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+            }
+            ColourChoice::Always => true,
+            ColourChoice::Never => false,
+        }
+    }
+}
+
+/// Forgets any configuration and warnings from a previous link.
+pub(crate) fn reset() {
+    *STATE.lock().unwrap() = State::new();
+}
+
+/// Applies the diagnostic-related options from `args` and reports any warnings that were issued
+/// before now.
+pub(crate) fn configure(args: &Args) {
+    let mut state = STATE.lock().unwrap();
+    state.config = Some(Config {
+        suppress_warnings: args.no_warnings,
+        colour: args.colour_diagnostics.should_colour(),
+    });
+    for (category, message) in std::mem::take(&mut state.pending) {
+        state.report(category, &message);
+    }
+}
+
+/// Issues a warning. Warnings go to stderr so that they don't get mixed up with any output that
+/// the caller might be parsing.
+pub(crate) fn warning(category: WarningCategory, message: &str) {
+    let mut state = STATE.lock().unwrap();
+    if state.config.is_some() {
+        state.report(category, message);
+    } else {
+        state.pending.push((category, message.to_owned()));
+    }
+}
+
+/// Returns an error if we issued any warnings and --fatal-warnings was specified.
+pub(crate) fn check_fatal_warnings(args: &Args) -> Result {
+    let num_reported = STATE.lock().unwrap().num_reported;
+    if args.fatal_warnings && num_reported > 0 {
+        anyhow::bail!(
+            "{num_reported} warning{} treated as error{} due to --fatal-warnings",
+            plural(num_reported),
+            plural(num_reported),
+        );
+    }
+    Ok(())
+}
+
+/// Combines `errors` into a single error that mentions at most `args.error_limit` of them. The
+/// errors are sorted so that our output doesn't depend on the order in which threads reported them.
+/// Returns `None` if there were no errors.
+pub(crate) fn combine_errors(mut errors: Vec<Error>, args: &Args) -> Option<Error> {
+    if errors.len() <= 1 {
+        return errors.pop();
+    }
+    let mut messages: Vec<String> = errors.iter().map(|e| format!("{e:#}")).collect();
+    messages.sort();
+    messages.dedup();
+    let total = messages.len();
+    if args.error_limit != 0 && total > args.error_limit {
+        messages.truncate(args.error_limit);
+        messages.push(format!(
+            "Too many errors ({total}), stopping after {}. Use --error-limit=0 to see all errors",
+            args.error_limit
+        ));
+    }
+    Some(anyhow!("{}", messages.join("\n")))
+}
+
+/// Prints an error that caused linking to fail.
+pub(crate) fn print_error(error: &Error) {
+    let state = STATE.lock().unwrap();
+    let colour = state.config.as_ref().is_some_and(|config| config.colour);
+    if colour {
+        eprintln!("{RED}Error:{RESET} {error:?}");
+    } else {
+        eprintln!("Error: {error:?}");
+    }
+}
+
+impl State {
+    const fn new() -> Self {
+        State {
+            config: None,
+            pending: Vec::new(),
+            seen: BTreeSet::new(),
+            num_reported: 0,
+        }
+    }
+
+    fn report(&mut self, category: WarningCategory, message: &str) {
+        let Some(config) = self.config.as_ref() else {
+            return;
+        };
+        if config.suppress_warnings {
+            return;
+        }
+        let colour = config.colour;
+        if !self.seen.insert((category, message.to_owned())) {
+            return;
+        }
+        self.num_reported += 1;
+        let category = category.name();
+        if colour {
+            eprintln!("{MAGENTA}WARNING:{RESET} wild: {message} [{category}]");
+        } else {
+            eprintln!("WARNING: wild: {message} [{category}]");
+        }
+    }
+}
+
+fn plural(count: usize) -> &'static str {
+    if count == 1 { "" } else { "s" }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_combine_errors() {
+        let mut args = Args::default();
+        args.error_limit = 2;
+        let errors = vec![anyhow!("c"), anyhow!("a"), anyhow!("b"), anyhow!("a")];
+        let combined = combine_errors(errors, &args).unwrap().to_string();
+        assert_eq!(
+            combined,
+            "a\nb\nToo many errors (3), stopping after 2. Use --error-limit=0 to see all errors"
+        );
+
+        args.error_limit = 0;
+        let errors = vec![anyhow!("b"), anyhow!("a")];
+        assert_eq!(combine_errors(errors, &args).unwrap().to_string(), "a\nb");
+
+        assert!(combine_errors(Vec::new(), &args).is_none());
+    }
+}
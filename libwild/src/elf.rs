@@ -7,6 +7,7 @@ use anyhow::bail;
 use anyhow::ensure;
 use bytemuck::Pod;
 use bytemuck::Zeroable;
+use itertools::Either;
 use linker_utils::aarch64::DEFAULT_AARCH64_PAGE_IGNORED_MASK;
 use linker_utils::elf::BitMask;
 use linker_utils::elf::PageMask;
@@ -15,13 +16,19 @@ use linker_utils::elf::RelocationSize;
 use linker_utils::elf::SectionType;
 use linker_utils::elf::extract_bits;
 use linker_utils::elf::sht;
-use object::LittleEndian;
-use object::read::elf::CompressionHeader;
-use object::read::elf::Dyn;
-use object::read::elf::FileHeader as _;
-use object::read::elf::ProgramHeader as _;
+use object::Endian as _;
+use object::Endianness;
+use object::U16;
+use object::U32;
+use object::U64;
+use object::read::StringTable;
+use object::read::elf::CompressionHeader as _;
+use object::read::elf::Dyn as _;
+use object::read::elf::Rel as _;
+use object::read::elf::Rela as _;
 use object::read::elf::RelocationSections;
 use object::read::elf::SectionHeader as _;
+use object::read::elf::Sym as _;
 use std::borrow::Cow;
 use std::io::Read as _;
 use std::mem::offset_of;
@@ -35,32 +42,32 @@ pub const NON_PIE_START_MEM_ADDRESS: u64 = 0x400_000;
 
 pub(crate) const TLS_MODULE_BASE_SYMBOL_NAME: &str = "_TLS_MODULE_BASE_";
 
-pub(crate) type FileHeader = object::elf::FileHeader64<LittleEndian>;
-pub(crate) type ProgramHeader = object::elf::ProgramHeader64<LittleEndian>;
-pub(crate) type SectionHeader = object::elf::SectionHeader64<LittleEndian>;
-pub(crate) type Symbol = object::elf::Sym64<LittleEndian>;
-pub(crate) type SymtabEntry = object::elf::Sym64<LittleEndian>;
-pub(crate) type DynamicEntry = object::elf::Dyn64<LittleEndian>;
-pub(crate) type Rela = object::elf::Rela64<LittleEndian>;
-pub(crate) type GnuHashHeader = object::elf::GnuHashHeader<LittleEndian>;
-pub(crate) type Verdef = object::elf::Verdef<LittleEndian>;
-pub(crate) type Verdaux = object::elf::Verdaux<LittleEndian>;
-pub(crate) type Verneed = object::elf::Verneed<LittleEndian>;
-pub(crate) type Vernaux = object::elf::Vernaux<LittleEndian>;
-pub(crate) type Versym = object::elf::Versym<LittleEndian>;
+pub(crate) type FileHeader = object::elf::FileHeader64<Endianness>;
+pub(crate) type ProgramHeader = object::elf::ProgramHeader64<Endianness>;
+pub(crate) type SectionHeader = object::elf::SectionHeader64<Endianness>;
+pub(crate) type Symbol = object::elf::Sym64<Endianness>;
+pub(crate) type SymtabEntry = object::elf::Sym64<Endianness>;
+pub(crate) type DynamicEntry = object::elf::Dyn64<Endianness>;
+pub(crate) type Rela = object::elf::Rela64<Endianness>;
+pub(crate) type GnuHashHeader = object::elf::GnuHashHeader<Endianness>;
+pub(crate) type Verdef = object::elf::Verdef<Endianness>;
+pub(crate) type Verdaux = object::elf::Verdaux<Endianness>;
+pub(crate) type Verneed = object::elf::Verneed<Endianness>;
+pub(crate) type Vernaux = object::elf::Vernaux<Endianness>;
+pub(crate) type Versym = object::elf::Versym<Endianness>;
 pub(crate) type VerdefIterator<'data> = object::read::elf::VerdefIterator<'data, FileHeader>;
-pub(crate) type NoteHeader = object::elf::NoteHeader64<LittleEndian>;
+pub(crate) type NoteHeader = object::elf::NoteHeader64<Endianness>;
 
-type SectionTable<'data> = object::read::elf::SectionTable<'data, FileHeader>;
-type SymbolTable<'data> = object::read::elf::SymbolTable<'data, FileHeader>;
+type FileHeader32 = object::elf::FileHeader32<Endianness>;
 
+/// An input ELF file of either class. Section headers, symbols and relocations are read from the
+/// file in whatever class it has and are returned as the equivalent 64 bit structures.
 pub(crate) struct File<'data> {
     pub(crate) arch: Architecture,
+    pub(crate) endian: Endianness,
     pub(crate) data: &'data [u8],
-    pub(crate) sections: SectionTable<'data>,
-    /// This may be symtab or dynsym depending on the file type.
-    pub(crate) symbols: SymbolTable<'data>,
-    pub(crate) program_headers: &'data [ProgramHeader],
+    tables: Tables<'data>,
+    section_names: StringTable<'data>,
     pub(crate) versym: &'data [Versym],
 
     /// An iterator over the version definitions and the corresponding linked string table index.
@@ -68,6 +75,49 @@ pub(crate) struct File<'data> {
 
     /// Number of verdef versions according to the dynamic table.
     pub(crate) verdefnum: u64,
+
+    /// Architecture-specific flags from the file header.
+    pub(crate) e_flags: u32,
+}
+
+/// The tables of an ELF file whose layout depends on the class of the file.
+struct ClassTables<'data, H: object::read::elf::FileHeader> {
+    sections: object::read::elf::SectionTable<'data, H>,
+    /// This may be symtab or dynsym depending on the file type.
+    symbols: object::read::elf::SymbolTable<'data, H>,
+    dynamic: &'data [H::Dyn],
+}
+
+enum Tables<'data> {
+    Elf32(ClassTables<'data, FileHeader32>),
+    Elf64(ClassTables<'data, FileHeader>),
+}
+
+/// Evaluates `$body` with `$tables` bound to the class-specific tables of `$file`.
+macro_rules! with_tables {
+    ($file:expr, |$tables:ident| $body:expr) => {
+        match &$file.tables {
+            Tables::Elf32($tables) => $body,
+            Tables::Elf64($tables) => $body,
+        }
+    };
+}
+
+/// A file header of a class that we can read.
+trait ElfFileHeader: object::read::elf::FileHeader<Endian = Endianness> {
+    fn tables(tables: ClassTables<'_, Self>) -> Tables<'_>;
+}
+
+impl ElfFileHeader for FileHeader32 {
+    fn tables(tables: ClassTables<'_, Self>) -> Tables<'_> {
+        Tables::Elf32(tables)
+    }
+}
+
+impl ElfFileHeader for FileHeader {
+    fn tables(tables: ClassTables<'_, Self>) -> Tables<'_> {
+        Tables::Elf64(tables)
+    }
 }
 
 // Not needing Drop opens the option of storing this type in an arena that doesn't support dropping
@@ -76,85 +126,129 @@ const _: () = assert!(!core::mem::needs_drop::<File>());
 
 impl<'data> File<'data> {
     pub(crate) fn parse(data: &'data [u8], is_dynamic: bool) -> Result<Self> {
-        let header = FileHeader::parse(data)?;
+        if crate::elf32::is_elf32(data) {
+            Self::parse_with_header::<FileHeader32>(data, is_dynamic)
+        } else {
+            Self::parse_with_header::<FileHeader>(data, is_dynamic)
+        }
+    }
+
+    fn parse_with_header<H: ElfFileHeader>(data: &'data [u8], is_dynamic: bool) -> Result<Self> {
+        let header = H::parse(data)?;
         let endian = header.endian()?;
         let architecture = header.e_machine(endian).try_into()?;
         let sections = header.sections(endian, data)?;
+        let section_names = header.section_strings(endian, data, sections.iter().as_slice())?;
 
-        let mut symbols = SymbolTable::default();
+        let mut symbols = object::read::elf::SymbolTable::default();
         let mut versym: &[Versym] = &[];
         let mut verdef = None;
-        let mut dynamic = None;
-        let mut verdefnum = 0;
+        let mut dynamic: &[H::Dyn] = &[];
 
         // Find all the sections that we're interested in in a single scan of the section table so
         // as to avoid multiple scans.
         for (section_index, section) in sections.enumerate() {
-            match SectionType::from_header(section) {
+            match SectionType::from_u32(section.sh_type(endian)) {
                 sht::DYNSYM if is_dynamic => {
-                    symbols = SymbolTable::parse(endian, data, &sections, section_index, section)?;
+                    symbols = object::read::elf::SymbolTable::parse(
+                        endian,
+                        data,
+                        &sections,
+                        section_index,
+                        section,
+                    )?;
                 }
                 sht::SYMTAB if !is_dynamic => {
-                    symbols = SymbolTable::parse(endian, data, &sections, section_index, section)?;
+                    symbols = object::read::elf::SymbolTable::parse(
+                        endian,
+                        data,
+                        &sections,
+                        section_index,
+                        section,
+                    )?;
                 }
                 sht::GNU_VERSYM => {
                     versym = section.data_as_array(endian, data)?;
                 }
                 sht::GNU_VERDEF => {
-                    verdef = section.gnu_verdef(endian, data)?;
+                    // Version definitions have the same layout in both classes.
+                    verdef = section_header(section, endian).gnu_verdef(endian, data)?;
                 }
                 sht::DYNAMIC => {
-                    dynamic = section.dynamic(endian, data)?;
+                    if let Some((entries, _)) = section.dynamic(endian, data)? {
+                        dynamic = entries;
+                    }
                 }
                 _ => {}
             }
         }
 
-        if let Some((dynamic, _)) = dynamic {
-            for dy in dynamic {
-                if dy.d_tag(endian) == u64::from(object::elf::DT_VERDEFNUM) {
-                    verdefnum = dy.d_val(endian);
-                }
-            }
-        }
-
-        let program_headers = get_entries(
-            data,
-            header.e_phoff(endian) as usize,
-            header.e_phnum(endian) as usize,
-        )
-        .context("Failed to read program headers")?;
+        let verdefnum = dynamic
+            .iter()
+            .find(|dy| dy.tag32(endian) == Some(object::elf::DT_VERDEFNUM))
+            .map_or(0, |dy| dy.d_val(endian).into());
 
         Ok(Self {
             arch: architecture,
+            endian,
             data,
-            sections,
-            symbols,
-            program_headers,
+            tables: H::tables(ClassTables {
+                sections,
+                symbols,
+                dynamic,
+            }),
+            section_names,
             versym,
             verdef,
             verdefnum,
+            e_flags: header.e_flags(endian),
         })
     }
 
-    pub(crate) fn section(&self, index: object::SectionIndex) -> Result<&'data SectionHeader> {
-        Ok(self.sections.section(index)?)
+    pub(crate) fn num_sections(&self) -> usize {
+        with_tables!(self, |tables| tables.sections.len())
+    }
+
+    pub(crate) fn section(&self, index: object::SectionIndex) -> Result<SectionHeader> {
+        with_tables!(self, |tables| Ok(section_header(
+            tables.sections.section(index)?,
+            self.endian
+        )))
+    }
+
+    /// Returns all section headers, including the null section at index 0.
+    pub(crate) fn sections(
+        &self,
+    ) -> impl ExactSizeIterator<Item = (object::SectionIndex, SectionHeader)> + '_ {
+        let e = self.endian;
+        let headers = match &self.tables {
+            Tables::Elf32(tables) => {
+                Either::Left(tables.sections.iter().map(move |s| section_header(s, e)))
+            }
+            Tables::Elf64(tables) => Either::Right(tables.sections.iter().copied()),
+        };
+        headers
+            .enumerate()
+            .map(|(i, section)| (object::SectionIndex(i), section))
     }
 
     pub(crate) fn section_by_name(
         &self,
         name: &str,
-    ) -> Option<(object::SectionIndex, &'data SectionHeader)> {
-        self.sections.section_by_name(LittleEndian, name.as_bytes())
+    ) -> Option<(object::SectionIndex, SectionHeader)> {
+        self.sections().find(|(_, section)| {
+            self.section_name(section)
+                .is_ok_and(|section_name| section_name == name.as_bytes())
+        })
     }
 
     pub(crate) fn section_name(&self, section: &SectionHeader) -> Result<&'data [u8]> {
-        Ok(self.sections.section_name(LittleEndian, section)?)
+        Ok(section.name(self.endian, self.section_names)?)
     }
 
     pub(crate) fn section_display_name(&self, index: object::SectionIndex) -> Cow<'data, str> {
         self.section(index)
-            .and_then(|section| self.section_name(section))
+            .and_then(|section| self.section_name(&section))
             .map_or_else(
                 |_| format!("<index {}>", index.0).into(),
                 String::from_utf8_lossy,
@@ -163,7 +257,20 @@ impl<'data> File<'data> {
 
     /// Returns the raw section data. Doesn't handle decompression.
     pub(crate) fn raw_section_data(&self, section: &SectionHeader) -> Result<&'data [u8]> {
-        Ok(section.data(LittleEndian, self.data)?)
+        Ok(section.data(self.endian, self.data)?)
+    }
+
+    /// Returns the compression header of `section`, or None if the section isn't compressed.
+    fn compression(&self, section: &SectionHeader) -> Result<Option<Compression>> {
+        if section.sh_flags(self.endian) & u64::from(object::elf::SHF_COMPRESSED) == 0 {
+            return Ok(None);
+        }
+        let data = self.raw_section_data(section)?;
+        match &self.tables {
+            Tables::Elf32(_) => Compression::read::<FileHeader32>(data, self.endian),
+            Tables::Elf64(_) => Compression::read::<FileHeader>(data, self.endian),
+        }
+        .map(Some)
     }
 
     pub(crate) fn section_data(
@@ -172,18 +279,21 @@ impl<'data> File<'data> {
         member: &bumpalo_herd::Member<'data>,
         loaded_metrics: &LoadedMetrics,
     ) -> Result<&'data [u8]> {
-        let data = section.data(LittleEndian, self.data)?;
+        let data = section.data(self.endian, self.data)?;
         loaded_metrics
             .loaded_bytes
             .fetch_add(data.len(), Ordering::Relaxed);
 
-        if let Some((compression, _, _)) = section.compression(LittleEndian, self.data)? {
+        if let Some(compression) = self.compression(section)? {
             loaded_metrics
                 .loaded_compressed_bytes
                 .fetch_add(data.len(), Ordering::Relaxed);
-            let len = self.section_size(section)?;
-            let decompressed = member.alloc_slice_fill_default(len as usize);
-            decompress_into(compression, &data[COMPRESSION_HEADER_SIZE..], decompressed)?;
+            let decompressed = member.alloc_slice_fill_default(compression.size as usize);
+            decompress_into(
+                compression.ch_type,
+                &data[compression.header_size..],
+                decompressed,
+            )?;
             loaded_metrics
                 .decompressed_bytes
                 .fetch_add(decompressed.len(), Ordering::Relaxed);
@@ -196,11 +306,11 @@ impl<'data> File<'data> {
     /// Copies the data for the specified section into `out`, which must be the correct size.
     /// Decompresses the data if necessary.
     pub(crate) fn copy_section_data(&self, section: &SectionHeader, out: &mut [u8]) -> Result {
-        let data = section.data(LittleEndian, self.data)?;
+        let data = section.data(self.endian, self.data)?;
 
-        if let Some((compression, _, _)) = section.compression(LittleEndian, self.data)? {
-            decompress_into(compression, &data[COMPRESSION_HEADER_SIZE..], out)?;
-        } else if section.sh_type(LittleEndian) == object::elf::SHT_NOBITS {
+        if let Some(compression) = self.compression(section)? {
+            decompress_into(compression.ch_type, &data[compression.header_size..], out)?;
+        } else if section.sh_type(self.endian) == object::elf::SHT_NOBITS {
             out.fill(0);
         } else {
             out.copy_from_slice(data);
@@ -209,16 +319,16 @@ impl<'data> File<'data> {
     }
 
     pub(crate) fn section_size(&self, section: &SectionHeader) -> Result<u64> {
-        Ok(section.compression(LittleEndian, self.data)?.map_or_else(
-            || section.sh_size.get(LittleEndian),
-            |compression| compression.0.ch_size(LittleEndian),
+        Ok(self.compression(section)?.map_or_else(
+            || section.sh_size.get(self.endian),
+            |compression| compression.size,
         ))
     }
 
     pub(crate) fn section_alignment(&self, section: &SectionHeader) -> Result<u64> {
-        Ok(section.compression(LittleEndian, self.data)?.map_or_else(
-            || section.sh_addralign(LittleEndian),
-            |compression| compression.0.ch_addralign(LittleEndian),
+        Ok(self.compression(section)?.map_or_else(
+            || section.sh_addralign(self.endian),
+            |compression| compression.alignment,
         ))
     }
 
@@ -226,23 +336,109 @@ impl<'data> File<'data> {
         &self,
         index: object::SectionIndex,
         relocations: &RelocationSections,
-    ) -> Result<&'data [Rela]> {
-        let Some(rela_index) = relocations.get(index) else {
-            return Ok(&[]);
+    ) -> Result<RelocationList<'data>> {
+        let e = self.endian;
+        let entries = 'entries: {
+            let Some(rel_index) = relocations.get(index) else {
+                break 'entries RelocationEntries::None;
+            };
+            match &self.tables {
+                Tables::Elf64(tables) => {
+                    match tables.sections.section(rel_index)?.rela(e, self.data)? {
+                        Some((rela, _)) => RelocationEntries::Rela64(rela),
+                        None => RelocationEntries::None,
+                    }
+                }
+                Tables::Elf32(tables) => {
+                    let rel_section = tables.sections.section(rel_index)?;
+                    if let Some((rela, _)) = rel_section.rela(e, self.data)? {
+                        break 'entries RelocationEntries::Rela32(rela);
+                    }
+                    let Some((rel, _)) = rel_section.rel(e, self.data)? else {
+                        break 'entries RelocationEntries::None;
+                    };
+                    if self.arch != Architecture::I386 {
+                        bail!("REL relocations are only supported for i386");
+                    }
+                    RelocationEntries::Rel32(self.implicit_addends(index, rel)?)
+                }
+            }
+        };
+        Ok(RelocationList { endian: e, entries })
+    }
+
+    /// Reads the addends of REL relocations `rel` from the places to which they apply in the
+    /// section at `index`.
+    fn implicit_addends(
+        &self,
+        index: object::SectionIndex,
+        rel: &'data [object::elf::Rel32<Endianness>],
+    ) -> Result<ImplicitAddendRelocations<'data>> {
+        let e = self.endian;
+        let section = self.section(index)?;
+        let data = if section.sh_type(e) == object::elf::SHT_NOBITS {
+            Cow::Borrowed(&[][..])
+        } else if self.compression(&section)?.is_some() {
+            let mut decompressed = vec![0; self.section_size(&section)? as usize];
+            self.copy_section_data(&section, &mut decompressed)?;
+            Cow::Owned(decompressed)
+        } else {
+            Cow::Borrowed(self.raw_section_data(&section)?)
         };
-        let rela_section = self.sections.section(rela_index)?;
-        let Some((rela, _)) = rela_section.rela(LittleEndian, self.data)? else {
-            return Ok(&[]);
+        for r in rel {
+            let size = implicit_addend_size(r.r_type(e));
+            let offset = r.r_offset(e) as usize;
+            if size != 0 && !data.is_empty() && data.get(offset..offset + size).is_none() {
+                bail!("Relocation offset is outside of its section");
+            }
+        }
+        Ok(ImplicitAddendRelocations {
+            relocations: rel,
+            section_data: data,
+        })
+    }
+
+    /// Returns the string table in the section at `index`.
+    pub(crate) fn strings(&self, index: object::SectionIndex) -> Result<StringTable<'data>> {
+        with_tables!(self, |tables| Ok(tables.sections.strings(
+            self.endian,
+            self.data,
+            index
+        )?))
+    }
+
+    pub(crate) fn num_symbols(&self) -> usize {
+        with_tables!(self, |tables| tables.symbols.len())
+    }
+
+    pub(crate) fn symbol(&self, index: object::SymbolIndex) -> Result<Symbol> {
+        with_tables!(self, |tables| Ok(symbol(
+            tables.symbols.symbol(index)?,
+            self.endian
+        )))
+    }
+
+    /// Returns all symbols, including the null symbol at index 0.
+    pub(crate) fn symbols(
+        &self,
+    ) -> impl ExactSizeIterator<Item = (object::SymbolIndex, Symbol)> + '_ {
+        let e = self.endian;
+        let symbols = match &self.tables {
+            Tables::Elf32(tables) => Either::Left(tables.symbols.iter().map(move |s| symbol(s, e))),
+            Tables::Elf64(tables) => Either::Right(tables.symbols.iter().copied()),
         };
-        Ok(rela)
+        symbols
+            .enumerate()
+            .map(|(i, symbol)| (object::SymbolIndex(i), symbol))
     }
 
-    pub(crate) fn symbol(&self, index: object::SymbolIndex) -> Result<&'data Symbol> {
-        Ok(self.symbols.symbol(index)?)
+    /// Returns the string table used by the symbol table.
+    pub(crate) fn symbol_strings(&self) -> StringTable<'data> {
+        with_tables!(self, |tables| tables.symbols.strings())
     }
 
     pub(crate) fn symbol_name(&self, symbol: &Symbol) -> Result<&'data [u8]> {
-        Ok(self.symbols.symbol_name(LittleEndian, symbol)?)
+        Ok(symbol.name(self.endian, self.symbol_strings())?)
     }
 
     pub(crate) fn symbol_section(
@@ -250,37 +446,256 @@ impl<'data> File<'data> {
         symbol: &Symbol,
         index: object::SymbolIndex,
     ) -> Result<Option<object::SectionIndex>> {
-        Ok(self.symbols.symbol_section(LittleEndian, symbol, index)?)
+        let e = self.endian;
+        match symbol.st_shndx(e) {
+            object::elf::SHN_UNDEF => Ok(None),
+            object::elf::SHN_XINDEX => {
+                let shndx = with_tables!(self, |tables| tables.symbols.shndx(e, index))
+                    .context("Missing ELF symbol extended index")?;
+                Ok((shndx != 0).then_some(object::SectionIndex(shndx as usize)))
+            }
+            shndx if shndx < object::elf::SHN_LORESERVE => {
+                Ok(Some(object::SectionIndex(shndx.into())))
+            }
+            _ => Ok(None),
+        }
     }
 
-    pub(crate) fn dynamic_tags(&self) -> Result<&'data [DynamicEntry]> {
-        let e = LittleEndian;
-        for header in self.program_headers {
-            if header.p_type(e) == object::elf::PT_DYNAMIC {
-                return get_entries(
-                    self.data,
-                    header.p_offset(e) as usize,
-                    header.p_filesz(e) as usize / size_of::<DynamicEntry>(),
-                )
-                .context("Failed to read dynamic table");
+    /// Returns the signature of the COMDAT group that contains the section at `index`, if any.
+    /// Groups that aren't COMDAT groups are ignored.
+    pub(crate) fn comdat_group_signature(
+        &self,
+        index: object::SectionIndex,
+    ) -> Result<Option<&'data [u8]>> {
+        let e = self.endian;
+        for (_, section) in self.sections() {
+            let Some((flags, members)) = section.group(e, self.data)? else {
+                continue;
+            };
+            if flags & object::elf::GRP_COMDAT == 0
+                || !members
+                    .iter()
+                    .any(|member| member.get(e) as usize == index.0)
+            {
+                continue;
             }
+            let signature = self.symbol(object::SymbolIndex(section.sh_info(e) as usize))?;
+            return Ok(Some(self.symbol_name(&signature)?));
+        }
+        Ok(None)
+    }
+
+    /// Calls `f` with the type and data of each GNU property in the note section at `index`.
+    /// Properties are padded differently depending on the class of the file.
+    pub(crate) fn for_each_gnu_property(
+        &self,
+        index: object::SectionIndex,
+        mut f: impl FnMut(u32, &'data [u8]) -> Result,
+    ) -> Result {
+        let e = self.endian;
+        with_tables!(self, |tables| {
+            let Some(notes) = tables.sections.section(index)?.notes(e, self.data)? else {
+                return Ok(());
+            };
+            for note in notes {
+                for property in note?
+                    .gnu_properties(e)
+                    .ok_or(anyhow!("Invalid type of .note.gnu.property"))?
+                {
+                    let property = property?;
+                    f(property.pr_type(), property.pr_data())?;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    pub(crate) fn dynamic_tags(&self) -> impl Iterator<Item = DynamicEntry> + '_ {
+        let e = self.endian;
+        match &self.tables {
+            Tables::Elf32(tables) => {
+                Either::Left(tables.dynamic.iter().map(move |entry| DynamicEntry {
+                    d_tag: U64::new(e, u64::from(entry.d_tag.get(e))),
+                    d_val: U64::new(e, u64::from(entry.d_val.get(e))),
+                }))
+            }
+            Tables::Elf64(tables) => Either::Right(tables.dynamic.iter().copied()),
         }
-        Ok(&[])
     }
 
     pub(crate) fn parse_relocations(&self) -> Result<RelocationSections> {
-        Ok(self
+        with_tables!(self, |tables| Ok(tables
             .sections
-            .relocation_sections(LittleEndian, self.symbols.section())?)
+            .relocation_sections(self.endian, tables.symbols.section())?))
+    }
+}
+
+/// The header of a compressed section.
+struct Compression {
+    ch_type: u32,
+    /// The size of the section once decompressed.
+    size: u64,
+    alignment: u64,
+    /// The size of the header, which comes before the compressed data.
+    header_size: usize,
+}
+
+impl Compression {
+    fn read<H: object::read::elf::FileHeader<Endian = Endianness>>(
+        data: &[u8],
+        e: Endianness,
+    ) -> Result<Compression> {
+        let (header, _) = object::pod::from_bytes::<H::CompressionHeader>(data)
+            .map_err(|()| anyhow!("Invalid ELF compression header size or alignment"))?;
+        Ok(Compression {
+            ch_type: header.ch_type(e),
+            size: header.ch_size(e).into(),
+            alignment: header.ch_addralign(e).into(),
+            header_size: size_of::<H::CompressionHeader>(),
+        })
+    }
+}
+
+fn section_header<S: object::read::elf::SectionHeader<Endian = Endianness>>(
+    section: &S,
+    e: Endianness,
+) -> SectionHeader {
+    SectionHeader {
+        sh_name: U32::new(e, section.sh_name(e)),
+        sh_type: U32::new(e, section.sh_type(e)),
+        sh_flags: U64::new(e, section.sh_flags(e).into()),
+        sh_addr: U64::new(e, section.sh_addr(e).into()),
+        sh_offset: U64::new(e, section.sh_offset(e).into()),
+        sh_size: U64::new(e, section.sh_size(e).into()),
+        sh_link: U32::new(e, section.sh_link(e)),
+        sh_info: U32::new(e, section.sh_info(e)),
+        sh_addralign: U64::new(e, section.sh_addralign(e).into()),
+        sh_entsize: U64::new(e, section.sh_entsize(e).into()),
+    }
+}
+
+fn symbol<S: object::read::elf::Sym<Endian = Endianness>>(sym: &S, e: Endianness) -> Symbol {
+    Symbol {
+        st_name: U32::new(e, sym.st_name(e)),
+        st_info: sym.st_info(),
+        st_other: sym.st_other(),
+        st_shndx: U16::new(e, sym.st_shndx(e)),
+        st_value: U64::new(e, sym.st_value(e).into()),
+        st_size: U64::new(e, sym.st_size(e).into()),
+    }
+}
+
+fn rela_entry(r_offset: u64, r_sym: u32, r_type: u32, r_addend: i64, e: Endianness) -> Rela {
+    Rela {
+        r_offset: U64::new(e, r_offset),
+        r_info: U64::new(e, (u64::from(r_sym) << 32) | u64::from(r_type)),
+        r_addend: object::I64::new(e, r_addend),
     }
 }
 
-fn decompress_into(
-    compression: &object::elf::CompressionHeader64<LittleEndian>,
-    input: &[u8],
-    out: &mut [u8],
-) -> Result {
-    match compression.ch_type.get(LittleEndian) {
+/// Returns the number of bytes of the place to which an i386 REL relocation applies, which is
+/// where its addend is stored. Unsupported relocation types get reported later, with more context.
+fn implicit_addend_size(r_type: u32) -> usize {
+    linker_utils::i386::relocation_kind_and_size(r_type).map_or(0, |(_, size)| size)
+}
+
+/// The relocations for a section. Relocations are returned as 64 bit RELA relocations, regardless
+/// of how they're stored in the file.
+pub(crate) struct RelocationList<'data> {
+    endian: Endianness,
+    entries: RelocationEntries<'data>,
+}
+
+enum RelocationEntries<'data> {
+    None,
+    Rela32(&'data [object::elf::Rela32<Endianness>]),
+    Rela64(&'data [Rela]),
+    Rel32(ImplicitAddendRelocations<'data>),
+}
+
+/// REL relocations, together with the data of the section that they apply to, which is where their
+/// addends are stored.
+struct ImplicitAddendRelocations<'data> {
+    relocations: &'data [object::elf::Rel32<Endianness>],
+    section_data: Cow<'data, [u8]>,
+}
+
+impl RelocationList<'_> {
+    pub(crate) fn len(&self) -> usize {
+        match &self.entries {
+            RelocationEntries::None => 0,
+            RelocationEntries::Rela32(rela) => rela.len(),
+            RelocationEntries::Rela64(rela) => rela.len(),
+            RelocationEntries::Rel32(rel) => rel.relocations.len(),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub(crate) fn get(&self, index: usize) -> Option<Rela> {
+        (index < self.len()).then(|| self.decode(index))
+    }
+
+    pub(crate) fn iter(&self) -> impl DoubleEndedIterator<Item = Rela> + ExactSizeIterator + '_ {
+        (0..self.len()).map(|index| self.decode(index))
+    }
+
+    /// Returns the index of the first relocation for which `pred` returns false. Like
+    /// `slice::partition_point`, this requires that `pred` is true for a prefix of the list.
+    pub(crate) fn partition_point(&self, mut pred: impl FnMut(&Rela) -> bool) -> usize {
+        let mut low = 0;
+        let mut high = self.len();
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if pred(&self.decode(mid)) {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        low
+    }
+
+    /// Returns the relocation at `index`, which must be in bounds.
+    fn decode(&self, index: usize) -> Rela {
+        let e = self.endian;
+        match &self.entries {
+            RelocationEntries::None => unreachable!(),
+            RelocationEntries::Rela32(rela) => {
+                let r = &rela[index];
+                rela_entry(
+                    u64::from(r.r_offset(e)),
+                    r.r_sym(e),
+                    r.r_type(e),
+                    i64::from(r.r_addend(e)),
+                    e,
+                )
+            }
+            RelocationEntries::Rela64(rela) => rela[index],
+            RelocationEntries::Rel32(rel) => {
+                let r = &rel.relocations[index];
+                let r_type = r.r_type(e);
+                let offset = r.r_offset(e) as usize;
+                let size = implicit_addend_size(r_type);
+                // Sign-extend from the size of the relocation. Offsets were checked when the list
+                // was created.
+                let addend = match rel.section_data.get(offset..offset + size) {
+                    Some(bytes) if size != 0 => {
+                        let shift = 64 - size * 8;
+                        ((read_uint(e, bytes) as i64) << shift) >> shift
+                    }
+                    _ => 0,
+                };
+                rela_entry(u64::from(r.r_offset(e)), r.r_sym(e), r_type, addend, e)
+            }
+        }
+    }
+}
+
+pub(crate) fn decompress_into(ch_type: u32, input: &[u8], out: &mut [u8]) -> Result {
+    match ch_type {
         object::elf::ELFCOMPRESS_ZLIB => {
             flate2::Decompress::new(true).decompress(
                 input,
@@ -300,28 +715,6 @@ fn decompress_into(
     Ok(())
 }
 
-/// Get some entries from `data` as a slice of some Pod type. Alignment of `T` must be 1.
-pub(crate) fn get_entries<T: object::Pod>(
-    data: &[u8],
-    offset: usize,
-    entry_count: usize,
-) -> Result<&[T]> {
-    debug_assert_eq!(align_of::<T>(), 1);
-    if offset >= data.len() {
-        bail!("Invalid offset 0x{offset}");
-    }
-    Ok(object::slice_from_bytes(&data[offset..], entry_count)
-        .map_err(|()| {
-            anyhow!(
-                "Tried to extract 0x{:x} entries of size 0x{:x} from 0x{:x}",
-                entry_count,
-                size_of::<T>(),
-                data.len(),
-            )
-        })?
-        .0)
-}
-
 /// The module number for TLS variables in the current executable.
 pub(crate) const CURRENT_EXE_TLS_MOD: u64 = 1;
 
@@ -341,9 +734,6 @@ pub(crate) struct EhFrameHdr {
 
 pub(crate) const FRAME_POINTER_FIELD_OFFSET: usize = offset_of!(EhFrameHdr, frame_pointer);
 
-/// The offset of the offset within the structure passed to __tls_get_addr.
-pub(crate) const TLS_OFFSET_OFFSET: u64 = 8;
-
 #[derive(Zeroable, Pod, Clone, Copy)]
 #[repr(C)]
 pub(crate) struct EhFrameHdrEntry {
@@ -358,6 +748,17 @@ pub(crate) struct EhFrameEntryPrefix {
     pub(crate) cie_id: u32,
 }
 
+impl EhFrameEntryPrefix {
+    /// Reads a prefix from the start of `bytes`, converting the fields to native byte order.
+    pub(crate) fn read(e: Endianness, bytes: &[u8]) -> Self {
+        let prefix: EhFrameEntryPrefix = bytemuck::pod_read_unaligned(&bytes[..size_of::<Self>()]);
+        Self {
+            length: e.read_u32(prefix.length),
+            cie_id: e.read_u32(prefix.cie_id),
+        }
+    }
+}
+
 #[allow(unused)]
 #[repr(u8)]
 pub(crate) enum ExceptionHeaderFormat {
@@ -386,24 +787,117 @@ pub(crate) enum ExceptionHeaderApplication {
 /// The offset of the pc_begin field in an FDE.
 pub(crate) const FDE_PC_BEGIN_OFFSET: usize = 8;
 
-/// Offset in the file where we store the program headers. We always store these straight after the
-/// file header.
-pub(crate) const PHEADER_OFFSET: u64 = FILE_HEADER_SIZE as u64;
-
 /// These sizes are from the spec (for 64 bit ELF).
 pub(crate) const FILE_HEADER_SIZE: u16 = 0x40;
 pub(crate) const PROGRAM_HEADER_SIZE: u16 = 0x38;
 pub(crate) const SECTION_HEADER_SIZE: u16 = 0x40;
-pub(crate) const COMPRESSION_HEADER_SIZE: usize =
-    size_of::<object::elf::CompressionHeader64<LittleEndian>>();
 
-pub(crate) const GOT_ENTRY_SIZE: u64 = 0x8;
-// TODO: Right now, both x86_64 and AArch64 have 16 byte long entries, but
-// the size should be generic over A: Arch.
+/// Whether an ELF file uses 32 or 64 bit structures. `File` decodes the structures of either class
+/// into their 64 bit equivalents as they're accessed. When writing, we use whichever the
+/// architecture requires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ElfClass {
+    Elf32,
+    Elf64,
+}
+
+/// How relocations store their addends. REL relocations have implicit addends that are stored in
+/// the place being relocated, whereas RELA relocations have explicit addends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RelocationFormat {
+    Rel,
+    Rela,
+}
+
+impl ElfClass {
+    pub(crate) const fn ident(self) -> u8 {
+        match self {
+            ElfClass::Elf32 => object::elf::ELFCLASS32,
+            ElfClass::Elf64 => object::elf::ELFCLASS64,
+        }
+    }
+
+    pub(crate) const fn file_header_size(self) -> u16 {
+        match self {
+            ElfClass::Elf32 => size_of::<object::elf::FileHeader32<Endianness>>() as u16,
+            ElfClass::Elf64 => FILE_HEADER_SIZE,
+        }
+    }
+
+    pub(crate) const fn program_header_size(self) -> u16 {
+        match self {
+            ElfClass::Elf32 => size_of::<object::elf::ProgramHeader32<Endianness>>() as u16,
+            ElfClass::Elf64 => PROGRAM_HEADER_SIZE,
+        }
+    }
+
+    pub(crate) const fn section_header_size(self) -> u16 {
+        match self {
+            ElfClass::Elf32 => size_of::<object::elf::SectionHeader32<Endianness>>() as u16,
+            ElfClass::Elf64 => SECTION_HEADER_SIZE,
+        }
+    }
+
+    pub(crate) const fn symtab_entry_size(self) -> u64 {
+        match self {
+            ElfClass::Elf32 => size_of::<object::elf::Sym32<Endianness>>() as u64,
+            ElfClass::Elf64 => size_of::<SymtabEntry>() as u64,
+        }
+    }
+
+    pub(crate) const fn dynamic_entry_size(self) -> u64 {
+        match self {
+            ElfClass::Elf32 => size_of::<object::elf::Dyn32<Endianness>>() as u64,
+            ElfClass::Elf64 => size_of::<DynamicEntry>() as u64,
+        }
+    }
+
+    pub(crate) const fn pointer_size(self) -> u64 {
+        match self {
+            ElfClass::Elf32 => 4,
+            ElfClass::Elf64 => 8,
+        }
+    }
+
+    pub(crate) const fn relocation_entry_size(self, format: RelocationFormat) -> u64 {
+        match (self, format) {
+            (ElfClass::Elf32, RelocationFormat::Rel) => {
+                size_of::<object::elf::Rel32<Endianness>>() as u64
+            }
+            (ElfClass::Elf32, RelocationFormat::Rela) => {
+                size_of::<object::elf::Rela32<Endianness>>() as u64
+            }
+            (ElfClass::Elf64, RelocationFormat::Rel) => {
+                size_of::<object::elf::Rel64<Endianness>>() as u64
+            }
+            (ElfClass::Elf64, RelocationFormat::Rela) => size_of::<Rela>() as u64,
+        }
+    }
+
+    /// Returns the size of each property in a GNU property note. Properties are padded to the size
+    /// of a pointer.
+    pub(crate) const fn gnu_note_property_entry_size(self) -> usize {
+        match self {
+            ElfClass::Elf32 => 12,
+            ElfClass::Elf64 => size_of::<NoteProperty>(),
+        }
+    }
+}
+
+// TODO: Right now, both x86_64 and AArch64 have 16 byte long entries (unless AArch64 entries need
+// to authenticate), but the size should be generic over A: Arch.
 pub(crate) const PLT_ENTRY_SIZE: u64 = 0x10;
-pub(crate) const RELA_ENTRY_SIZE: u64 = 0x18;
+/// The size of AArch64 PLT entries that authenticate the GOT entry with PAC (`-z pac-plt`).
+pub(crate) const PAC_PLT_ENTRY_SIZE: u64 = 0x18;
+/// The size of the range-extension thunks that we insert for AArch64 branches that can't reach
+/// their target.
+pub(crate) const THUNK_SIZE: u64 = 0xc;
+/// The size of the patches that we branch to in order to avoid Cortex-A53 erratum 843419. Each is a
+/// copy of the problematic instruction followed by a branch back.
+pub(crate) const ERRATUM_PATCH_SIZE: u64 = 0x8;
+/// How far the branches to and from an erratum patch can reach in either direction.
+pub(crate) const ERRATUM_PATCH_REACH: u64 = 1 << 27;
 
-pub(crate) const SYMTAB_ENTRY_SIZE: u64 = size_of::<SymtabEntry>() as u64;
 pub(crate) const GNU_VERSION_ENTRY_SIZE: u64 = size_of::<Versym>() as u64;
 
 const _ASSERTS: () = {
@@ -413,13 +907,12 @@ const _ASSERTS: () = {
 };
 
 pub(crate) const GNU_NOTE_NAME: &[u8] = b"GNU\0";
-pub(crate) const GNU_NOTE_PROPERTY_ENTRY_SIZE: usize = 16;
 
 /// For additional information on Elf_Prop, see
 /// Linux Extensions to gABI at https://gitlab.com/x86-psABIs/Linux-ABI.
 ///
 /// Right now, all properties that pr_datasz equal to 4 and so the pr_padding is always
-/// 4 bytes!
+/// 4 bytes for 64 bit ELF and absent for 32 bit ELF!
 ///
 /// typedef struct {
 /// Elf_Word pr_type;
@@ -475,14 +968,44 @@ pub(crate) fn get_page_mask(mask: Option<PageMask>) -> PageMaskValue {
             got: DEFAULT_AARCH64_PAGE_IGNORED_MASK,
             ..Default::default()
         },
+        PageMask::Position => PageMaskValue {
+            place: DEFAULT_AARCH64_PAGE_IGNORED_MASK,
+            ..Default::default()
+        },
     }
 }
 
+/// Writes the low `out.len()` bytes of `value` to `out` in the specified byte order.
+#[inline(always)]
+pub(crate) fn write_uint(endian: Endianness, value: u64, out: &mut [u8]) {
+    let len = out.len();
+    if endian.is_big_endian() {
+        out.copy_from_slice(&value.to_be_bytes()[size_of::<u64>() - len..]);
+    } else {
+        out.copy_from_slice(&value.to_le_bytes()[..len]);
+    }
+}
+
+/// Reads an unsigned integer of size `bytes.len()` in the specified byte order.
+pub(crate) fn read_uint(endian: Endianness, bytes: &[u8]) -> u64 {
+    let mut value = [0; size_of::<u64>()];
+    if endian.is_big_endian() {
+        value[size_of::<u64>() - bytes.len()..].copy_from_slice(bytes);
+        u64::from_be_bytes(value)
+    } else {
+        value[..bytes.len()].copy_from_slice(bytes);
+        u64::from_le_bytes(value)
+    }
+}
+
+/// Writes `value` to `output` as required by `rel_info`. Relocations of data use the byte order of
+/// the output, whereas instructions are always little endian, even on big endian AArch64.
 #[inline(always)]
 pub(crate) fn write_relocation_to_buffer(
     rel_info: RelocationKindInfo,
     value: u64,
     output: &mut [u8],
+    endian: Endianness,
 ) -> Result<()> {
     rel_info.verify(value as i64)?;
 
@@ -492,16 +1015,19 @@ pub(crate) fn write_relocation_to_buffer(
                 byte_size <= output.len(),
                 "Relocation outside of bounds of section"
             );
-            let value_bytes = value.to_le_bytes();
-            output[..byte_size].copy_from_slice(&value_bytes[..byte_size]);
+            write_uint(endian, value, &mut output[..byte_size]);
         }
         RelocationSize::BitMasking(BitMask {
             range,
             instruction: insn,
         }) => {
+            ensure!(
+                insn.num_bytes() <= output.len(),
+                "Relocation outside of bounds of section"
+            );
             let extracted_value = extract_bits(value, range.start, range.end);
             let negative = (value as i64).is_negative();
-            insn.write_to_value(extracted_value, negative, &mut output[..4]);
+            insn.write_to_value(extracted_value, negative, output);
         }
     }
 
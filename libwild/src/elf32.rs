@@ -0,0 +1,144 @@
+//! Support for writing 32 bit ELF files. Internally, we build 64 bit ELF structures, then for 32 bit
+//! outputs, the headers and table entries are narrowed as they're written. 32 bit inputs are read
+//! in place via `elf::File`.
+
+use crate::elf::ElfClass;
+use crate::elf::FileHeader;
+use crate::elf::ProgramHeader;
+use crate::elf::SectionHeader;
+use crate::error::Result;
+use anyhow::anyhow;
+use object::Endianness;
+use object::U16;
+use object::U32;
+use object::U64;
+use object::from_bytes_mut;
+use object::read::elf::FileHeader as _;
+
+type FileHeader32 = object::elf::FileHeader32<Endianness>;
+type ProgramHeader32 = object::elf::ProgramHeader32<Endianness>;
+type SectionHeader32 = object::elf::SectionHeader32<Endianness>;
+
+/// Returns whether `data` looks like a 32 bit ELF file.
+pub(crate) fn is_elf32(data: &[u8]) -> bool {
+    // The class immediately follows the 4 byte magic.
+    data.starts_with(&object::elf::ELFMAG) && data.get(4) == Some(&object::elf::ELFCLASS32)
+}
+
+/// Returns the machine from the header of an ELF file of either class.
+pub(crate) fn machine(data: &[u8]) -> Result<u16> {
+    if is_elf32(data) {
+        let header = FileHeader32::parse(data)?;
+        Ok(header.e_machine(header.endian()?))
+    } else {
+        let header = FileHeader::parse(data)?;
+        Ok(header.e_machine(header.endian()?))
+    }
+}
+
+fn u32_of(value: U64<Endianness>, e: Endianness) -> U32<Endianness> {
+    U32::new(e, value.get(e) as u32)
+}
+
+/// Writes `header` to `out` in the format of `elf_class`.
+pub(crate) fn write_file_header(
+    header: &FileHeader,
+    elf_class: ElfClass,
+    out: &mut [u8],
+) -> Result {
+    match elf_class {
+        ElfClass::Elf64 => {
+            *from_bytes_mut::<FileHeader>(out)
+                .map_err(|_| anyhow!("Invalid file header allocation"))?
+                .0 = *header;
+        }
+        ElfClass::Elf32 => {
+            let e = header.endian()?;
+            *from_bytes_mut::<FileHeader32>(out)
+                .map_err(|_| anyhow!("Invalid file header allocation"))?
+                .0 = FileHeader32 {
+                e_ident: header.e_ident,
+                e_type: header.e_type,
+                e_machine: header.e_machine,
+                e_version: header.e_version,
+                e_entry: u32_of(header.e_entry, e),
+                e_phoff: u32_of(header.e_phoff, e),
+                e_shoff: u32_of(header.e_shoff, e),
+                e_flags: header.e_flags,
+                e_ehsize: U16::new(e, elf_class.file_header_size()),
+                e_phentsize: U16::new(e, elf_class.program_header_size()),
+                e_phnum: header.e_phnum,
+                e_shentsize: U16::new(e, elf_class.section_header_size()),
+                e_shnum: header.e_shnum,
+                e_shstrndx: header.e_shstrndx,
+            };
+        }
+    }
+    Ok(())
+}
+
+/// Writes `header` to `out` in the format of `elf_class`. `out` must be exactly the size of a
+/// program header of that class.
+pub(crate) fn write_program_header(
+    header: &ProgramHeader,
+    elf_class: ElfClass,
+    e: Endianness,
+    out: &mut [u8],
+) -> Result {
+    match elf_class {
+        ElfClass::Elf64 => {
+            *from_bytes_mut::<ProgramHeader>(out)
+                .map_err(|_| anyhow!("Invalid program header allocation"))?
+                .0 = *header;
+        }
+        ElfClass::Elf32 => {
+            *from_bytes_mut::<ProgramHeader32>(out)
+                .map_err(|_| anyhow!("Invalid program header allocation"))?
+                .0 = ProgramHeader32 {
+                p_type: header.p_type,
+                p_offset: u32_of(header.p_offset, e),
+                p_vaddr: u32_of(header.p_vaddr, e),
+                p_paddr: u32_of(header.p_paddr, e),
+                p_filesz: u32_of(header.p_filesz, e),
+                p_memsz: u32_of(header.p_memsz, e),
+                p_flags: header.p_flags,
+                p_align: u32_of(header.p_align, e),
+            };
+        }
+    }
+    Ok(())
+}
+
+/// Writes `header` to `out` in the format of `elf_class`. `out` must be exactly the size of a
+/// section header of that class.
+pub(crate) fn write_section_header(
+    header: &SectionHeader,
+    elf_class: ElfClass,
+    e: Endianness,
+    out: &mut [u8],
+) -> Result {
+    match elf_class {
+        ElfClass::Elf64 => {
+            *from_bytes_mut::<SectionHeader>(out)
+                .map_err(|_| anyhow!("Invalid section header allocation"))?
+                .0 = *header;
+        }
+        ElfClass::Elf32 => {
+            *from_bytes_mut::<SectionHeader32>(out)
+                .map_err(|_| anyhow!("Invalid section header allocation"))?
+                .0 = SectionHeader32 {
+                sh_name: header.sh_name,
+                sh_type: header.sh_type,
+                sh_flags: u32_of(header.sh_flags, e),
+                sh_addr: u32_of(header.sh_addr, e),
+                sh_offset: u32_of(header.sh_offset, e),
+                sh_size: u32_of(header.sh_size, e),
+                sh_link: header.sh_link,
+                sh_info: header.sh_info,
+                sh_addralign: u32_of(header.sh_addralign, e),
+                sh_entsize: u32_of(header.sh_entsize, e),
+            };
+        }
+    }
+    Ok(())
+}
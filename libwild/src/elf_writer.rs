@@ -1,10 +1,10 @@
-use self::elf::GNU_NOTE_PROPERTY_ENTRY_SIZE;
 use self::elf::NoteHeader;
 use self::elf::NoteProperty;
 use self::elf::TLS_MODULE_BASE_SYMBOL_NAME;
 use self::elf::get_page_mask;
 use crate::alignment;
 use crate::arch::Arch;
+use crate::arch::PltProtection;
 use crate::arch::Relaxation as _;
 use crate::args::Args;
 use crate::args::BuildIdOption;
@@ -16,10 +16,12 @@ use crate::elf;
 use crate::elf::DynamicEntry;
 use crate::elf::EhFrameHdr;
 use crate::elf::EhFrameHdrEntry;
+use crate::elf::ElfClass;
 use crate::elf::FileHeader;
 use crate::elf::GNU_NOTE_NAME;
 use crate::elf::GnuHashHeader;
 use crate::elf::ProgramHeader;
+use crate::elf::RelocationFormat;
 use crate::elf::SectionHeader;
 use crate::elf::SymtabEntry;
 use crate::elf::Verdaux;
@@ -29,6 +31,7 @@ use crate::elf::Verneed;
 use crate::elf::Versym;
 use crate::elf::slice_from_all_bytes_mut;
 use crate::elf::write_relocation_to_buffer;
+use crate::elf32;
 use crate::error::Result;
 use crate::layout::DynamicLayout;
 use crate::layout::EpilogueLayout;
@@ -47,6 +50,7 @@ use crate::layout::Section;
 use crate::layout::SymbolCopyInfo;
 use crate::layout::VersionDef;
 use crate::layout::compute_allocations;
+use crate::layout::section_output_order;
 use crate::output_section_id;
 use crate::output_section_id::OrderEvent;
 use crate::output_section_id::OutputSectionId;
@@ -54,13 +58,15 @@ use crate::output_section_id::OutputSections;
 use crate::output_section_map::OutputSectionMap;
 use crate::output_section_part_map::OutputSectionPartMap;
 use crate::output_trace::TraceOutput;
+use crate::parsing::InternalSymDefInfo;
 use crate::part_id;
+use crate::part_id::PartId;
 use crate::program_segments::STACK;
 use crate::resolution::SectionSlot;
 use crate::resolution::ValueFlags;
 use crate::sharding::ShardKey;
 use crate::slice::slice_take_prefix_mut;
-use crate::slice::take_first_mut;
+use crate::slice::try_slice_take_prefix_mut;
 use crate::string_merging::get_merged_string_output_address;
 use crate::symbol::UnversionedSymbolName;
 use crate::symbol_db::SymbolDb;
@@ -69,8 +75,11 @@ use ahash::AHashMap;
 use anyhow::Context;
 use anyhow::anyhow;
 use anyhow::bail;
+use anyhow::ensure;
 use linker_utils::elf::DynamicRelocationKind;
 use linker_utils::elf::RelocationKind;
+use linker_utils::elf::RelocationKindInfo;
+use linker_utils::elf::RelocationSize;
 use linker_utils::elf::SectionFlags;
 use linker_utils::elf::secnames::DEBUG_LOC_SECTION_NAME;
 use linker_utils::elf::secnames::DEBUG_RANGES_SECTION_NAME;
@@ -79,7 +88,11 @@ use linker_utils::elf::shf;
 use linker_utils::elf::sht;
 use linker_utils::relaxation::RelocationModifier;
 use memmap2::MmapOptions;
-use object::LittleEndian;
+use object::Endian as _;
+use object::Endianness;
+use object::U16;
+use object::U32;
+use object::U64;
 use object::elf::NT_GNU_BUILD_ID;
 use object::elf::NT_GNU_PROPERTY_TYPE_0;
 use object::from_bytes_mut;
@@ -266,6 +279,19 @@ impl Output {
         }
     }
 
+    /// Deletes the output file instead of writing it. If we already started creating the file in
+    /// the background, then we wait for that to finish first.
+    pub(crate) fn discard(&self) {
+        if let FileCreator::Background {
+            sized_output_sender: None,
+            sized_output_recv,
+        } = &self.creator
+        {
+            drop(wait_for_sized_output(sized_output_recv));
+        }
+        delete_old_output(&self.path);
+    }
+
     #[tracing::instrument(skip_all, name = "Write output file")]
     pub fn write<'data, A: Arch>(&mut self, layout: &Layout<'data>) -> Result {
         if layout.args().write_layout {
@@ -397,7 +423,10 @@ impl SizedOutput {
 
         if layout.args().should_write_eh_frame_hdr {
             let mut section_buffers = split_output_into_sections(layout, &mut self.out);
-            sort_eh_frame_hdr_entries(section_buffers.get_mut(output_section_id::EH_FRAME_HDR));
+            sort_eh_frame_hdr_entries(
+                section_buffers.get_mut(output_section_id::EH_FRAME_HDR),
+                layout.args().endian,
+            );
         }
 
         self.write_gnu_build_id_note(&layout.args().build_id, layout)?;
@@ -425,7 +454,7 @@ impl SizedOutput {
         };
 
         let mut buffers = split_output_into_sections(layout, &mut self.out);
-        let e = LittleEndian;
+        let e = layout.args().endian;
         let (note_header, mut rest) =
             from_bytes_mut::<NoteHeader>(buffers.get_mut(output_section_id::NOTE_GNU_BUILD_ID))
                 .map_err(|_| insufficient_allocation(".note.gnu.build-id"))?;
@@ -547,6 +576,11 @@ fn split_output_into_sections<'out>(
 ) -> OutputSectionMap<&'out mut [u8]> {
     let mut section_allocations = Vec::with_capacity(layout.section_layouts.len());
     layout.section_layouts.for_each(|id, s| {
+        // Sections like `.init_array.N` are written into the buffer of their primary section,
+        // which covers them.
+        if layout.output_sections.output_info(id).primary.is_some() {
+            return;
+        }
         section_allocations.push(SectionAllocation {
             id,
             offset: s.file_offset,
@@ -558,7 +592,7 @@ fn split_output_into_sections<'out>(
     // OutputSectionMap is ordered by section ID, which is not the same as output order. We
     // split the output file by output order, putting the relevant parts of the buffer into the
     // map.
-    let mut section_data = OutputSectionMap::with_size(section_allocations.len());
+    let mut section_data = OutputSectionMap::with_size(layout.section_layouts.len());
     let mut offset = 0;
     for a in section_allocations {
         let Some(padding) = a.offset.checked_sub(offset) else {
@@ -575,10 +609,10 @@ fn split_output_into_sections<'out>(
 }
 
 #[tracing::instrument(skip_all, name = "Sort .eh_frame_hdr")]
-fn sort_eh_frame_hdr_entries(eh_frame_hdr: &mut [u8]) {
+fn sort_eh_frame_hdr_entries(eh_frame_hdr: &mut [u8], e: Endianness) {
     let entry_bytes = &mut eh_frame_hdr[size_of::<elf::EhFrameHdr>()..];
     let entries: &mut [elf::EhFrameHdrEntry] = bytemuck::cast_slice_mut(entry_bytes);
-    entries.sort_by_key(|e| e.frame_ptr);
+    entries.sort_by_key(|entry| e.read_i32(entry.frame_ptr));
 }
 
 /// Splits the writable buffers for each segment further into separate buffers for each alignment.
@@ -586,13 +620,40 @@ fn split_buffers_by_alignment<'out>(
     section_buffers: &'out mut OutputSectionMap<&mut [u8]>,
     layout: &Layout,
 ) -> OutputSectionPartMap<&'out mut [u8]> {
+    let mut next_offsets = OutputSectionMap::from_values(
+        layout
+            .section_layouts
+            .values_iter()
+            .map(|s| s.file_offset)
+            .collect(),
+    );
+    // The section whose parts we last wrote into each buffer.
+    let mut last_sections: OutputSectionMap<Option<OutputSectionId>> =
+        OutputSectionMap::with_size(layout.section_layouts.len());
     layout.section_part_layouts.output_order_map(
         &layout.output_sections,
         |part_id, _alignment, rec| {
-            crate::slice::slice_take_prefix_mut(
-                section_buffers.get_mut(part_id.output_section_id()),
-                rec.file_size,
-            )
+            let part_section_id = part_id.output_section_id();
+            let section_id = layout
+                .output_sections
+                .output_info(part_section_id)
+                .primary
+                .unwrap_or(part_section_id);
+            let buffer = section_buffers.get_mut(section_id);
+            // Skip any alignment padding between the sections that make up a primary section.
+            // Within a section, parts are written back-to-back, since packed sections like `.init`
+            // mustn't have padding between their parts.
+            let next_offset = next_offsets.get_mut(section_id);
+            let last_section = last_sections.get_mut(section_id);
+            if last_section.is_some_and(|last| last != part_section_id) {
+                crate::slice::slice_take_prefix_mut(
+                    buffer,
+                    rec.file_offset.saturating_sub(*next_offset),
+                );
+            }
+            *last_section = Some(part_section_id);
+            *next_offset = (*next_offset).max(rec.file_offset + rec.file_size);
+            crate::slice::slice_take_prefix_mut(buffer, rec.file_size)
         },
     )
 }
@@ -601,30 +662,27 @@ fn write_program_headers(program_headers_out: &mut ProgramHeaderWriter, layout:
     for segment_layout in &layout.segment_layouts.segments {
         let segment_sizes = &segment_layout.sizes;
         let segment_id = segment_layout.id;
-        let segment_header = program_headers_out.take_header()?;
         let mut alignment = segment_sizes.alignment;
         if segment_id.segment_type() == object::elf::PT_LOAD {
             alignment = alignment.max(layout.args().loadable_segment_alignment());
         }
-        let e = LittleEndian;
-        segment_header.p_type.set(e, segment_id.segment_type());
+        let e = layout.args().endian;
 
         // Support executable stack (Wild defaults to non-executable stack)
         let mut segment_flags = segment_id.segment_flags();
         if segment_id == STACK && layout.args().execstack {
             segment_flags |= object::elf::PF_X;
         }
-        segment_header.p_flags.set(e, segment_flags);
-        segment_header
-            .p_offset
-            .set(e, segment_sizes.file_offset as u64);
-        segment_header.p_vaddr.set(e, segment_sizes.mem_offset);
-        segment_header.p_paddr.set(e, segment_sizes.mem_offset);
-        segment_header
-            .p_filesz
-            .set(e, segment_sizes.file_size as u64);
-        segment_header.p_memsz.set(e, segment_sizes.mem_size);
-        segment_header.p_align.set(e, alignment.value());
+        program_headers_out.write_header(&ProgramHeader {
+            p_type: U32::new(e, segment_id.segment_type()),
+            p_flags: U32::new(e, segment_flags),
+            p_offset: U64::new(e, segment_sizes.file_offset as u64),
+            p_vaddr: U64::new(e, segment_sizes.mem_offset),
+            p_paddr: U64::new(e, segment_sizes.mem_offset),
+            p_filesz: U64::new(e, segment_sizes.file_size as u64),
+            p_memsz: U64::new(e, segment_sizes.mem_size),
+            p_align: U64::new(e, alignment.value()),
+        })?;
     }
     Ok(())
 }
@@ -632,49 +690,65 @@ fn write_program_headers(program_headers_out: &mut ProgramHeaderWriter, layout:
 fn populate_file_header<A: Arch>(
     layout: &Layout,
     header_info: &HeaderInfo,
-    header: &mut FileHeader,
+    out: &mut [u8],
 ) -> Result {
     let args = layout.args();
+    let elf_class = header_info.elf_class;
     let ty = if args.output_kind().is_relocatable() {
         object::elf::ET_DYN
     } else {
         object::elf::ET_EXEC
     };
-    let e = LittleEndian;
-    header.e_ident.magic = object::elf::ELFMAG;
-    header.e_ident.class = object::elf::ELFCLASS64;
-    header.e_ident.data = object::elf::ELFDATA2LSB; // Little endian
-    header.e_ident.version = 1;
-    header.e_ident.os_abi = object::elf::ELFOSABI_NONE;
-    header.e_ident.abi_version = 0;
-    header.e_ident.padding = Default::default();
-    header.e_type.set(e, ty);
-    header.e_machine.set(e, A::elf_header_arch_magic());
-    header.e_version.set(e, u32::from(object::elf::EV_CURRENT));
-    header.e_entry.set(e, layout.entry_symbol_address()?);
-    header.e_phoff.set(e, elf::PHEADER_OFFSET);
-    header.e_shoff.set(
-        e,
-        u64::from(elf::FILE_HEADER_SIZE) + header_info.program_headers_size(),
-    );
-    header.e_flags.set(e, 0);
-    header.e_ehsize.set(e, elf::FILE_HEADER_SIZE);
-    header.e_phentsize.set(e, elf::PROGRAM_HEADER_SIZE);
-    header
-        .e_phnum
-        .set(e, header_info.active_segment_ids.len() as u16);
-    header.e_shentsize.set(e, elf::SECTION_HEADER_SIZE);
-    header
-        .e_shnum
-        .set(e, header_info.num_output_sections_with_content);
-    header.e_shstrndx.set(
-        e,
-        layout
-            .output_sections
-            .output_index_of_section(output_section_id::SHSTRTAB)
-            .expect("we always write .shstrtab"),
-    );
-    Ok(())
+    let e = args.endian;
+    let header = FileHeader {
+        e_ident: object::elf::Ident {
+            magic: object::elf::ELFMAG,
+            class: elf_class.ident(),
+            data: if e.is_big_endian() {
+                object::elf::ELFDATA2MSB
+            } else {
+                object::elf::ELFDATA2LSB
+            },
+            version: 1,
+            os_abi: object::elf::ELFOSABI_NONE,
+            abi_version: 0,
+            padding: Default::default(),
+        },
+        e_type: U16::new(e, ty),
+        e_machine: U16::new(e, A::elf_header_arch_magic()),
+        e_version: U32::new(e, u32::from(object::elf::EV_CURRENT)),
+        e_entry: U64::new(e, layout.entry_symbol_address()?),
+        // For 32 bit outputs, there may be padding between the file header and the program
+        // headers, so we take the offsets from the layout.
+        e_phoff: U64::new(
+            e,
+            layout
+                .section_layouts
+                .get(output_section_id::PROGRAM_HEADERS)
+                .file_offset as u64,
+        ),
+        e_shoff: U64::new(
+            e,
+            layout
+                .section_layouts
+                .get(output_section_id::SECTION_HEADERS)
+                .file_offset as u64,
+        ),
+        e_flags: U32::new(e, layout.e_flags),
+        e_ehsize: U16::new(e, elf_class.file_header_size()),
+        e_phentsize: U16::new(e, elf_class.program_header_size()),
+        e_phnum: U16::new(e, header_info.num_program_headers() as u16),
+        e_shentsize: U16::new(e, elf_class.section_header_size()),
+        e_shnum: U16::new(e, header_info.num_output_sections_with_content),
+        e_shstrndx: U16::new(
+            e,
+            layout
+                .output_sections
+                .output_index_of_section(output_section_id::SHSTRTAB)
+                .expect("we always write .shstrtab"),
+        ),
+    };
+    elf32::write_file_header(&header, elf_class, out)
 }
 
 impl<'data> FileLayout<'data> {
@@ -703,6 +777,8 @@ struct VersionWriter<'out> {
 
     /// None if versioning is disabled, which we do if no symbols have versions.
     versym: Option<&'out mut [Versym]>,
+
+    endian: Endianness,
 }
 
 impl<'out> VersionWriter<'out> {
@@ -710,11 +786,13 @@ impl<'out> VersionWriter<'out> {
         version_d: &'out mut [u8],
         version_r: &'out mut [u8],
         versym: Option<&'out mut [Versym]>,
+        endian: Endianness,
     ) -> Self {
         Self {
             version_d,
             version_r,
             versym,
+            endian,
         }
     }
 
@@ -722,7 +800,7 @@ impl<'out> VersionWriter<'out> {
         if let Some(versym_table) = self.versym.as_mut() {
             let versym = crate::slice::take_first_mut(versym_table)
                 .ok_or_else(|| insufficient_allocation(".gnu.version"))?;
-            versym.0.set(LittleEndian, index);
+            versym.0.set(self.endian, index);
         }
         Ok(())
     }
@@ -793,13 +871,26 @@ impl<'out> VersionWriter<'out> {
 }
 
 struct TableWriter<'data, 'layout, 'out> {
+    args: &'layout Args,
     output_kind: OutputKind,
-    got: &'out mut [u64],
+    plt_protection: PltProtection,
+    relocation_format: RelocationFormat,
+    got: &'out mut [u8],
+    got_entry_size: usize,
+
+    /// The address of `_GLOBAL_OFFSET_TABLE_` if the output is position-independent. Some
+    /// architectures' PLT entries are then relative to it.
+    got_base: Option<u64>,
+
     plt_got: &'out mut [u8],
-    rela_plt: &'out mut [elf::Rela],
+    rela_plt: DynamicRelocationTable<'out>,
     tls: Range<u64>,
-    rela_dyn_relative: &'out mut [crate::elf::Rela],
-    rela_dyn_general: &'out mut [crate::elf::Rela],
+
+    /// The address that offsets from the thread pointer are relative to.
+    thread_pointer: u64,
+
+    rela_dyn_relative: DynamicRelocationTable<'out>,
+    rela_dyn_general: DynamicRelocationTable<'out>,
     dynsym_writer: SymbolTableWriter<'data, 'layout, 'out>,
     debug_symbol_writer: SymbolTableWriter<'data, 'layout, 'out>,
     eh_frame_start_address: u64,
@@ -821,14 +912,27 @@ impl<'data, 'layout, 'out> TableWriter<'data, 'layout, 'out> {
         buffers: &mut OutputSectionPartMap<&'out mut [u8]>,
         eh_frame_start_address: u64,
     ) -> TableWriter<'data, 'layout, 'out> {
-        let dynsym_writer =
-            SymbolTableWriter::new_dynamic(dynstr_start_offset, buffers, &layout.output_sections);
-        let debug_symbol_writer =
-            SymbolTableWriter::new(strtab_start_offset, buffers, &layout.output_sections);
+        let dynsym_writer = SymbolTableWriter::new_dynamic(
+            dynstr_start_offset,
+            buffers,
+            &layout.output_sections,
+            layout.args().elf_class(),
+            layout.args().endian,
+        );
+        let debug_symbol_writer = SymbolTableWriter::new(
+            strtab_start_offset,
+            buffers,
+            &layout.output_sections,
+            layout.args().elf_class(),
+            layout.args().endian,
+        );
 
         Self::new(
-            layout.args().output_kind(),
+            layout.args(),
+            layout.plt_protection,
+            layout.args().is_relocatable().then(|| layout.got_base()),
             layout.tls_start_address()..layout.tls_end_address(),
+            layout.thread_pointer_address(),
             buffers,
             dynsym_writer,
             debug_symbol_writer,
@@ -837,8 +941,11 @@ impl<'data, 'layout, 'out> TableWriter<'data, 'layout, 'out> {
     }
 
     fn new(
-        output_kind: OutputKind,
+        args: &'layout Args,
+        plt_protection: PltProtection,
+        got_base: Option<u64>,
         tls: Range<u64>,
+        thread_pointer: u64,
         buffers: &mut OutputSectionPartMap<&'out mut [u8]>,
         dynsym_writer: SymbolTableWriter<'data, 'layout, 'out>,
         debug_symbol_writer: SymbolTableWriter<'data, 'layout, 'out>,
@@ -846,22 +953,39 @@ impl<'data, 'layout, 'out> TableWriter<'data, 'layout, 'out> {
     ) -> TableWriter<'data, 'layout, 'out> {
         let eh_frame = buffers.take(part_id::EH_FRAME);
         let eh_frame_hdr = buffers.take(part_id::EH_FRAME_HDR);
-        let dynamic = DynamicEntriesWriter::new(buffers.take(part_id::DYNAMIC));
+        let dynamic = DynamicEntriesWriter::new(
+            buffers.take(part_id::DYNAMIC),
+            args.elf_class(),
+            args.endian,
+        );
         let versym = slice_from_all_bytes_mut(buffers.take(part_id::GNU_VERSION));
         let version_writer = VersionWriter::new(
             buffers.take(part_id::GNU_VERSION_D),
             buffers.take(part_id::GNU_VERSION_R),
             versym.is_empty().not().then_some(versym),
+            args.endian,
         );
 
         TableWriter {
-            output_kind,
-            got: bytemuck::cast_slice_mut(buffers.take(part_id::GOT)),
+            args,
+            output_kind: args.output_kind(),
+            plt_protection,
+            relocation_format: args.arch.relocation_format(),
+            got: buffers.take(part_id::GOT),
+            got_entry_size: args.got_entry_size() as usize,
+            got_base,
             plt_got: buffers.take(part_id::PLT_GOT),
-            rela_plt: slice_from_all_bytes_mut(buffers.take(part_id::RELA_PLT)),
+            rela_plt: DynamicRelocationTable::new(buffers.take(part_id::RELA_PLT), args),
             tls,
-            rela_dyn_relative: slice_from_all_bytes_mut(buffers.take(part_id::RELA_DYN_RELATIVE)),
-            rela_dyn_general: slice_from_all_bytes_mut(buffers.take(part_id::RELA_DYN_GENERAL)),
+            thread_pointer,
+            rela_dyn_relative: DynamicRelocationTable::new(
+                buffers.take(part_id::RELA_DYN_RELATIVE),
+                args,
+            ),
+            rela_dyn_general: DynamicRelocationTable::new(
+                buffers.take(part_id::RELA_DYN_GENERAL),
+                args,
+            ),
             dynsym_writer,
             debug_symbol_writer,
             eh_frame_start_address,
@@ -887,11 +1011,11 @@ impl<'data, 'layout, 'out> TableWriter<'data, 'layout, 'out> {
         {
             if resolution_flags.contains(ResolutionFlags::GOT_TLS_OFFSET) {
                 self.process_got_tls_offset::<A>(res, got_address)?;
-                got_address += crate::elf::GOT_ENTRY_SIZE;
+                got_address += self.got_entry_size as u64;
             }
             if resolution_flags.contains(ResolutionFlags::GOT_TLS_MODULE) {
                 self.process_got_tls_mod::<A>(res, got_address)?;
-                got_address += 2 * crate::elf::GOT_ENTRY_SIZE;
+                got_address += 2 * self.got_entry_size as u64;
             }
             if resolution_flags.contains(ResolutionFlags::GOT_TLS_DESCRIPTOR) {
                 self.process_got_tls_descriptor::<A>(res, got_address)?;
@@ -899,7 +1023,7 @@ impl<'data, 'layout, 'out> TableWriter<'data, 'layout, 'out> {
             return Ok(());
         }
 
-        let got_entry = self.take_next_got_entry()?;
+        let mut got_entry = self.take_next_got_entry()?;
 
         if res.value_flags.contains(ValueFlags::DYNAMIC)
             || (resolution_flags.contains(ResolutionFlags::EXPORT_DYNAMIC)
@@ -907,7 +1031,7 @@ impl<'data, 'layout, 'out> TableWriter<'data, 'layout, 'out> {
                 && !res.value_flags.contains(ValueFlags::IFUNC)
         {
             debug_assert_bail!(
-                *compute_allocations(res, self.output_kind).get(part_id::RELA_DYN_GENERAL) > 0,
+                *compute_allocations(res, self.args).get(part_id::RELA_DYN_GENERAL) > 0,
                 "Tried to write glob-dat with no allocation. {}",
                 ResFlagsDisplay(res)
             );
@@ -918,9 +1042,13 @@ impl<'data, 'layout, 'out> TableWriter<'data, 'layout, 'out> {
                 DynamicRelocationKind::GotEntry,
             )?;
         } else if res.value_flags.contains(ValueFlags::IFUNC) {
+            if self.relocation_format == RelocationFormat::Rel {
+                // The resolver address is the implicit addend of the IRELATIVE relocation.
+                got_entry.set(res.raw_value);
+            }
             self.write_ifunc_relocation::<A>(res)?;
         } else {
-            *got_entry = res.raw_value;
+            got_entry.set(res.raw_value);
             if res.value_flags.contains(ValueFlags::ADDRESS) && self.output_kind.is_relocatable() {
                 self.write_address_relocation::<A>(got_address, res.raw_value as i64)?;
             }
@@ -932,7 +1060,7 @@ impl<'data, 'layout, 'out> TableWriter<'data, 'layout, 'out> {
     }
 
     fn process_got_tls_offset<A: Arch>(&mut self, res: &Resolution, got_address: u64) -> Result {
-        let got_entry = self.take_next_got_entry()?;
+        let mut got_entry = self.take_next_got_entry()?;
         if res.value_flags.contains(ValueFlags::DYNAMIC)
             || (res
                 .resolution_flags
@@ -944,7 +1072,7 @@ impl<'data, 'layout, 'out> TableWriter<'data, 'layout, 'out> {
         let address = res.raw_value;
         if address == 0 {
             // Resolution is undefined.
-            *got_entry = 0;
+            got_entry.set(0);
             return Ok(());
         }
         // TLS_MODULE_BASE points at the end of the .tbss in some cases, thus relax the verification.
@@ -955,38 +1083,43 @@ impl<'data, 'layout, 'out> TableWriter<'data, 'layout, 'out> {
             );
         }
         if self.output_kind.is_executable() {
-            // Convert the address to an offset relative to the TCB which is the end of the
-            // TLS segment.
-            *got_entry = address.wrapping_sub(self.tls.end);
+            // Convert the address to an offset relative to the thread pointer.
+            got_entry.set(address.wrapping_sub(self.thread_pointer));
         } else {
             debug_assert_bail!(
-                *compute_allocations(res, self.output_kind).get(part_id::RELA_DYN_GENERAL) > 0,
+                *compute_allocations(res, self.args).get(part_id::RELA_DYN_GENERAL) > 0,
                 "Tried to write tpoff with no allocation. {}",
                 ResFlagsDisplay(res)
             );
-            self.write_tpoff_relocation::<A>(got_address, 0, address.sub(self.tls.start) as i64)?;
+            let offset = address.sub(self.tls.start);
+            if self.relocation_format == RelocationFormat::Rel {
+                got_entry.set(offset);
+            }
+            self.write_tpoff_relocation::<A>(got_address, 0, offset as i64)?;
         }
         Ok(())
     }
 
     fn process_got_tls_mod<A: Arch>(&mut self, res: &Resolution, got_address: u64) -> Result {
-        let got_entry = self.take_next_got_entry()?;
+        let mut got_entry = self.take_next_got_entry()?;
         if self.output_kind.is_executable() {
-            *got_entry = elf::CURRENT_EXE_TLS_MOD;
+            got_entry.set(elf::CURRENT_EXE_TLS_MOD);
         } else {
             let dynamic_symbol_index = res.dynamic_symbol_index.map_or(0, std::num::NonZero::get);
             debug_assert_bail!(
-                *compute_allocations(res, self.output_kind).get(part_id::RELA_DYN_GENERAL) > 0,
+                *compute_allocations(res, self.args).get(part_id::RELA_DYN_GENERAL) > 0,
                 "Tried to write dtpmod with no allocation. {}",
                 ResFlagsDisplay(res)
             );
             self.write_dtpmod_relocation::<A>(got_address, dynamic_symbol_index)?;
         }
-        let offset_entry = self.take_next_got_entry()?;
+        let mut offset_entry = self.take_next_got_entry()?;
         if let Some(dynamic_symbol_index) = res.dynamic_symbol_index {
             if !res.value_flags.contains(ValueFlags::CAN_BYPASS_GOT) {
+                // The offset within the structure passed to __tls_get_addr comes after the module
+                // ID.
                 self.write_dtpoff_relocation::<A>(
-                    got_address + crate::elf::TLS_OFFSET_OFFSET,
+                    got_address + self.got_entry_size as u64,
                     dynamic_symbol_index.get(),
                 )?;
             }
@@ -994,7 +1127,7 @@ impl<'data, 'layout, 'out> TableWriter<'data, 'layout, 'out> {
         }
         // Convert the address to an offset within the TLS segment
         let address = res.address()?;
-        *offset_entry = address - self.tls.start;
+        offset_entry.set((address - self.tls.start).wrapping_sub(A::tls_dtv_offset()));
         Ok(())
     }
 
@@ -1005,7 +1138,7 @@ impl<'data, 'layout, 'out> TableWriter<'data, 'layout, 'out> {
     ) -> Result {
         // TLS descriptor occupies 2 entries
         self.take_next_got_entry()?;
-        self.take_next_got_entry()?;
+        let mut argument_entry = self.take_next_got_entry()?;
 
         anyhow::ensure!(
             !self.output_kind.is_static_executable(),
@@ -1014,7 +1147,7 @@ impl<'data, 'layout, 'out> TableWriter<'data, 'layout, 'out> {
 
         let dynamic_symbol_index = res.dynamic_symbol_index.map_or(0, std::num::NonZero::get);
         debug_assert_bail!(
-            *compute_allocations(res, self.output_kind).get(part_id::RELA_DYN_GENERAL) > 0,
+            *compute_allocations(res, self.args).get(part_id::RELA_DYN_GENERAL) > 0,
             "Tried to write TLS descriptor with no allocation. {}",
             ResFlagsDisplay(res)
         );
@@ -1023,6 +1156,10 @@ impl<'data, 'layout, 'out> TableWriter<'data, 'layout, 'out> {
         } else {
             0
         };
+        if self.relocation_format == RelocationFormat::Rel {
+            // The implicit addend is stored in the descriptor's argument.
+            argument_entry.set(addend as u64);
+        }
         self.write_tls_descriptor_relocation::<A>(got_address, dynamic_symbol_index, addend)?;
 
         Ok(())
@@ -1030,36 +1167,45 @@ impl<'data, 'layout, 'out> TableWriter<'data, 'layout, 'out> {
 
     fn write_plt_entry<A: Arch>(&mut self, got_address: u64, plt_address: u64) -> Result {
         let plt_entry = self.take_plt_got_entry()?;
-        A::write_plt_entry(plt_entry, got_address, plt_address)
+        A::write_plt_entry(
+            plt_entry,
+            got_address,
+            plt_address,
+            self.got_base,
+            self.plt_protection,
+        )
     }
 
     fn take_plt_got_entry(&mut self) -> Result<&'out mut [u8]> {
-        if self.plt_got.len() < elf::PLT_ENTRY_SIZE as usize {
+        let entry_size = self.plt_protection.entry_size() as usize;
+        if self.plt_got.len() < entry_size {
             bail!("Didn't allocate enough space in .plt.got");
         }
-        Ok(slice_take_prefix_mut(
-            &mut self.plt_got,
-            elf::PLT_ENTRY_SIZE as usize,
-        ))
+        Ok(slice_take_prefix_mut(&mut self.plt_got, entry_size))
     }
 
-    fn take_next_got_entry(&mut self) -> Result<&'out mut u64> {
-        crate::slice::take_first_mut(&mut self.got).ok_or_else(|| insufficient_allocation(".got"))
+    fn take_next_got_entry(&mut self) -> Result<GotEntry<'out>> {
+        try_slice_take_prefix_mut(&mut self.got, self.got_entry_size)
+            .map(|out| GotEntry {
+                out,
+                endian: self.args.endian,
+            })
+            .ok_or_else(|| insufficient_allocation(".got"))
     }
 
     /// Checks that we used all of the entries that we requested during layout.
     fn validate_empty(&self, mem_sizes: &OutputSectionPartMap<u64>) -> Result {
-        if !self.rela_dyn_relative.is_empty() {
+        if !self.rela_dyn_relative.out.is_empty() {
             return Err(excessive_allocation(
                 ".rela.dyn (relative)",
-                self.rela_dyn_relative.len() as u64 * elf::RELA_ENTRY_SIZE,
+                self.rela_dyn_relative.out.len() as u64,
                 *mem_sizes.get(part_id::RELA_DYN_RELATIVE),
             ));
         }
-        if !self.rela_dyn_general.is_empty() {
+        if !self.rela_dyn_general.out.is_empty() {
             return Err(excessive_allocation(
                 ".rela.dyn (general)",
-                self.rela_dyn_general.len() as u64 * elf::RELA_ENTRY_SIZE,
+                self.rela_dyn_general.out.len() as u64,
                 *mem_sizes.get(part_id::RELA_DYN_GENERAL),
             ));
         }
@@ -1084,22 +1230,16 @@ impl<'data, 'layout, 'out> TableWriter<'data, 'layout, 'out> {
     }
 
     fn write_ifunc_relocation<A: Arch>(&mut self, res: &Resolution) -> Result {
-        let out = slice_take_prefix_mut(&mut self.rela_plt, 1);
-        let out = &mut out[0];
-        let e = LittleEndian;
-        out.r_addend.set(e, res.raw_value as i64);
         let got_address = res
             .got_address
             .context("Missing GOT entry for ifunc")?
             .get();
-        out.r_offset.set(e, got_address);
-        out.r_info.set(
-            e,
-            u64::from(A::get_dynamic_relocation_type(
-                DynamicRelocationKind::Irelative,
-            )),
-        );
-        Ok(())
+        self.rela_plt.write(
+            got_address,
+            0,
+            A::get_dynamic_relocation_type(DynamicRelocationKind::Irelative),
+            res.raw_value as i64,
+        )
     }
 
     fn write_dtpmod_relocation<A: Arch>(
@@ -1162,16 +1302,14 @@ impl<'data, 'layout, 'out> TableWriter<'data, 'layout, 'out> {
             self.output_kind.is_relocatable(),
             "write_address_relocation called when output is not relocatable"
         );
-        let e = LittleEndian;
-        let rela = crate::slice::take_first_mut(&mut self.rela_dyn_relative)
-            .ok_or_else(|| insufficient_allocation(".rela.dyn (relative)"))?;
-        rela.r_offset.set(e, place);
-        rela.r_addend.set(e, relative_address);
-        rela.r_info.set(
-            e,
-            A::get_dynamic_relocation_type(DynamicRelocationKind::Relative).into(),
-        );
-        Ok(())
+        self.rela_dyn_relative
+            .write(
+                place,
+                0,
+                A::get_dynamic_relocation_type(DynamicRelocationKind::Relative),
+                relative_address,
+            )
+            .map_err(|_| insufficient_allocation(".rela.dyn (relative)"))
     }
 
     fn write_dynamic_symbol_relocation<A: Arch>(
@@ -1186,17 +1324,12 @@ impl<'data, 'layout, 'out> TableWriter<'data, 'layout, 'out> {
             self.output_kind.needs_dynsym(),
             "Tried to write dynamic relocation with non-relocatable output"
         );
-        let e = LittleEndian;
-        let rela = self.take_rela_dyn()?;
-        rela.r_offset.set(e, place);
-        rela.r_addend.set(e, addend);
-        rela.set_r_info(
-            LittleEndian,
-            false,
+        self.write_rela_dyn_general(
+            place,
             symbol_index,
             A::get_dynamic_relocation_type(kind),
-        );
-        Ok(())
+            addend,
+        )
     }
 
     fn write_rela_dyn_general(
@@ -1210,17 +1343,10 @@ impl<'data, 'layout, 'out> TableWriter<'data, 'layout, 'out> {
             self.output_kind.needs_dynsym(),
             "write_rela_dyn_general called when output is not dynamic"
         );
-        let rela = self.take_rela_dyn()?;
-        rela.r_offset.set(LittleEndian, place);
-        rela.r_addend.set(LittleEndian, addend);
-        rela.set_r_info(LittleEndian, false, dynamic_symbol_index, r_type);
-        Ok(())
-    }
-
-    fn take_rela_dyn(&mut self) -> Result<&mut object::elf::Rela64<LittleEndian>> {
         tracing::trace!("Consume .rela.dyn general");
-        crate::slice::take_first_mut(&mut self.rela_dyn_general)
-            .ok_or_else(|| insufficient_allocation(".rela.dyn (non-relative)"))
+        self.rela_dyn_general
+            .write(place, dynamic_symbol_index, r_type, addend)
+            .map_err(|_| insufficient_allocation(".rela.dyn (non-relative)"))
     }
 
     fn take_eh_frame_hdr(&mut self) -> &'out mut EhFrameHdr {
@@ -1251,9 +1377,139 @@ impl<'data, 'layout, 'out> TableWriter<'data, 'layout, 'out> {
     }
 }
 
+/// A GOT entry, which is either 4 or 8 bytes depending on the ELF class of the output.
+struct GotEntry<'out> {
+    out: &'out mut [u8],
+    endian: Endianness,
+}
+
+impl GotEntry<'_> {
+    fn set(&mut self, value: u64) {
+        crate::elf::write_uint(self.endian, value, self.out);
+    }
+}
+
+/// A table of dynamic relocations. Entries are written as either REL or RELA and with a size that
+/// depends on the ELF class.
+struct DynamicRelocationTable<'out> {
+    out: &'out mut [u8],
+    elf_class: ElfClass,
+    format: RelocationFormat,
+    endian: Endianness,
+}
+
+impl<'out> DynamicRelocationTable<'out> {
+    fn new(out: &'out mut [u8], args: &Args) -> Self {
+        Self {
+            out,
+            elf_class: args.elf_class(),
+            format: args.arch.relocation_format(),
+            endian: args.endian,
+        }
+    }
+
+    /// Writes the next entry. With the REL format, the addend is dropped, so the caller is
+    /// responsible for having written it into the place.
+    fn write(&mut self, r_offset: u64, symbol_index: u32, r_type: u32, addend: i64) -> Result {
+        let entry_size = self.elf_class.relocation_entry_size(self.format) as usize;
+        let out = try_slice_take_prefix_mut(&mut self.out, entry_size)
+            .context("Insufficient dynamic relocation entries allocated")?;
+        let e = self.endian;
+        match (self.elf_class, self.format) {
+            (ElfClass::Elf64, RelocationFormat::Rela) => {
+                let rela: &mut object::elf::Rela64<Endianness> = from_bytes_mut(out)
+                    .map_err(|_| anyhow!("Invalid .rela alignment"))?
+                    .0;
+                rela.r_offset.set(e, r_offset);
+                rela.r_addend.set(e, addend);
+                rela.set_r_info(e, false, symbol_index, r_type);
+            }
+            (ElfClass::Elf64, RelocationFormat::Rel) => {
+                let rel: &mut object::elf::Rel64<Endianness> = from_bytes_mut(out)
+                    .map_err(|_| anyhow!("Invalid .rel alignment"))?
+                    .0;
+                rel.r_offset.set(e, r_offset);
+                rel.set_r_info(e, symbol_index, r_type);
+            }
+            (ElfClass::Elf32, RelocationFormat::Rela) => {
+                let rela: &mut object::elf::Rela32<Endianness> = from_bytes_mut(out)
+                    .map_err(|_| anyhow!("Invalid .rela alignment"))?
+                    .0;
+                rela.r_offset.set(e, r_offset as u32);
+                rela.r_addend.set(e, addend as i32);
+                rela.set_r_info(e, symbol_index, r_type as u8);
+            }
+            (ElfClass::Elf32, RelocationFormat::Rel) => {
+                let rel: &mut object::elf::Rel32<Endianness> = from_bytes_mut(out)
+                    .map_err(|_| anyhow!("Invalid .rel alignment"))?
+                    .0;
+                rel.r_offset.set(e, r_offset as u32);
+                rel.set_r_info(e, symbol_index, r_type as u8);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A mutable reference to a symbol table entry of whichever ELF class we're writing.
+enum SymtabEntryMut<'out> {
+    Elf32(&'out mut object::elf::Sym32<Endianness>),
+    Elf64(&'out mut SymtabEntry),
+}
+
+impl SymtabEntryMut<'_> {
+    fn set_info(&mut self, st_info: u8) {
+        match self {
+            SymtabEntryMut::Elf32(sym) => sym.st_info = st_info,
+            SymtabEntryMut::Elf64(sym) => sym.st_info = st_info,
+        }
+    }
+
+    fn set_other(&mut self, st_other: u8) {
+        match self {
+            SymtabEntryMut::Elf32(sym) => sym.st_other = st_other,
+            SymtabEntryMut::Elf64(sym) => sym.st_other = st_other,
+        }
+    }
+
+    fn set_size(&mut self, e: Endianness, size: u64) {
+        match self {
+            SymtabEntryMut::Elf32(sym) => sym.st_size.set(e, size as u32),
+            SymtabEntryMut::Elf64(sym) => sym.st_size.set(e, size),
+        }
+    }
+
+    fn set_st_info(&mut self, bind: u8, st_type: u8) {
+        match self {
+            SymtabEntryMut::Elf32(sym) => sym.set_st_info(bind, st_type),
+            SymtabEntryMut::Elf64(sym) => sym.set_st_info(bind, st_type),
+        }
+    }
+
+    fn set_fields(&mut self, e: Endianness, string_offset: u32, shndx: u16, value: u64, size: u64) {
+        match self {
+            SymtabEntryMut::Elf32(sym) => {
+                sym.st_name.set(e, string_offset);
+                sym.st_shndx.set(e, shndx);
+                sym.st_value.set(e, value as u32);
+                sym.st_size.set(e, size as u32);
+            }
+            SymtabEntryMut::Elf64(sym) => {
+                sym.st_name.set(e, string_offset);
+                sym.st_shndx.set(e, shndx);
+                sym.st_value.set(e, value);
+                sym.st_size.set(e, size);
+            }
+        }
+        self.set_other(0);
+    }
+}
+
 struct SymbolTableWriter<'data, 'layout, 'out> {
-    local_entries: &'out mut [SymtabEntry],
-    global_entries: &'out mut [SymtabEntry],
+    local_entries: &'out mut [u8],
+    global_entries: &'out mut [u8],
+    elf_class: ElfClass,
+    endian: Endianness,
     output_sections: &'layout OutputSections<'data>,
     strtab_writer: StrTabWriter<'out>,
     is_dynamic: bool,
@@ -1264,13 +1520,17 @@ impl<'data, 'layout, 'out> SymbolTableWriter<'data, 'layout, 'out> {
         start_string_offset: u32,
         buffers: &mut OutputSectionPartMap<&'out mut [u8]>,
         output_sections: &'layout OutputSections<'data>,
+        elf_class: ElfClass,
+        endian: Endianness,
     ) -> Self {
-        let local_entries = slice_from_all_bytes_mut(buffers.take(part_id::SYMTAB_LOCAL));
-        let global_entries = slice_from_all_bytes_mut(buffers.take(part_id::SYMTAB_GLOBAL));
+        let local_entries = buffers.take(part_id::SYMTAB_LOCAL);
+        let global_entries = buffers.take(part_id::SYMTAB_GLOBAL);
         let strings = buffers.take(part_id::STRTAB);
         Self {
             local_entries,
             global_entries,
+            elf_class,
+            endian,
             output_sections,
             strtab_writer: StrTabWriter {
                 next_offset: start_string_offset,
@@ -1284,12 +1544,16 @@ impl<'data, 'layout, 'out> SymbolTableWriter<'data, 'layout, 'out> {
         string_offset: u32,
         buffers: &mut OutputSectionPartMap<&'out mut [u8]>,
         output_sections: &'layout OutputSections<'data>,
+        elf_class: ElfClass,
+        endian: Endianness,
     ) -> Self {
-        let global_entries = slice_from_all_bytes_mut(buffers.take(part_id::DYNSYM));
+        let global_entries = buffers.take(part_id::DYNSYM);
         let strings = slice_from_all_bytes_mut(buffers.take(part_id::DYNSTR));
         Self {
             local_entries: Default::default(),
             global_entries,
+            elf_class,
+            endian,
             output_sections,
             strtab_writer: StrTabWriter {
                 next_offset: string_offset,
@@ -1299,14 +1563,17 @@ impl<'data, 'layout, 'out> SymbolTableWriter<'data, 'layout, 'out> {
         }
     }
 
+    /// Copies `sym` into the symbol table. If `is_local` is set, then the symbol is written as a
+    /// local even if it was global in its input file.
     #[inline(always)]
     fn copy_symbol(
         &mut self,
         sym: &crate::elf::Symbol,
+        is_local: bool,
         name: &[u8],
         output_section_id: OutputSectionId,
         value: u64,
-    ) -> Result {
+    ) -> Result<SymtabEntryMut<'_>> {
         let shndx = self
             .output_sections
             .output_index_of_section(output_section_id)
@@ -1318,7 +1585,7 @@ impl<'data, 'layout, 'out> SymbolTableWriter<'data, 'layout, 'out> {
                     output_section_id,
                 )
             })?;
-        self.copy_symbol_shndx(sym, name, shndx, value)
+        self.copy_symbol_with_binding(sym, is_local, name, shndx, value)
     }
 
     #[inline(always)]
@@ -1328,25 +1595,40 @@ impl<'data, 'layout, 'out> SymbolTableWriter<'data, 'layout, 'out> {
         name: &[u8],
         shndx: u16,
         value: u64,
+    ) -> Result<SymtabEntryMut<'_>> {
+        self.copy_symbol_with_binding(sym, sym.is_local(), name, shndx, value)
+    }
+
+    fn copy_absolute_symbol(
+        &mut self,
+        sym: &crate::elf::Symbol,
+        is_local: bool,
+        name: &[u8],
     ) -> Result {
-        let e = LittleEndian;
-        let is_local = sym.is_local();
-        let size = sym.st_size(e);
-        let entry = self.define_symbol(is_local, shndx, value, size, name)?;
-        entry.st_info = sym.st_info();
-        entry.st_other = sym.st_other();
+        let value = sym.st_value(self.endian);
+        self.copy_symbol_with_binding(sym, is_local, name, object::elf::SHN_ABS, value)?;
         Ok(())
     }
 
-    fn copy_absolute_symbol(&mut self, sym: &crate::elf::Symbol, name: &[u8]) -> Result {
-        let e = LittleEndian;
-        let is_local = sym.is_local();
-        let value = sym.st_value(e);
+    #[inline(always)]
+    fn copy_symbol_with_binding(
+        &mut self,
+        sym: &crate::elf::Symbol,
+        is_local: bool,
+        name: &[u8],
+        shndx: u16,
+        value: u64,
+    ) -> Result<SymtabEntryMut<'_>> {
+        let e = self.endian;
         let size = sym.st_size(e);
-        let entry = self.define_symbol(is_local, object::elf::SHN_ABS, value, size, name)?;
-        entry.st_info = sym.st_info();
-        entry.st_other = sym.st_other();
-        Ok(())
+        let mut entry = self.define_symbol(is_local, shndx, value, size, name)?;
+        if is_local && !sym.is_local() {
+            entry.set_st_info(object::elf::STB_LOCAL, sym.st_type());
+        } else {
+            entry.set_info(sym.st_info());
+        }
+        entry.set_other(sym.st_other());
+        Ok(entry)
     }
 
     #[inline(always)]
@@ -1357,40 +1639,56 @@ impl<'data, 'layout, 'out> SymbolTableWriter<'data, 'layout, 'out> {
         value: u64,
         size: u64,
         name: &[u8],
-    ) -> Result<&mut SymtabEntry> {
-        let entry = if is_local {
-            take_first_mut(&mut self.local_entries).with_context(|| {
-                format!(
-                    "Insufficient .symtab local entries allocated for symbol `{}`",
-                    String::from_utf8_lossy(name),
-                )
-            })?
+    ) -> Result<SymtabEntryMut<'_>> {
+        let entry_size = self.elf_class.symtab_entry_size() as usize;
+        let entries = if is_local {
+            &mut self.local_entries
         } else {
-            if self.is_dynamic {
-                tracing::trace!(name = %String::from_utf8_lossy(name), "Write .dynsym");
-            }
-            take_first_mut(&mut self.global_entries).with_context(|| {
-                format!(
-                    "Insufficient {} entries allocated for symbol `{}`",
-                    if self.is_dynamic {
-                        DYNSYM_SECTION_NAME_STR
-                    } else {
-                        ".symtab global"
-                    },
-                    String::from_utf8_lossy(name),
-                )
-            })?
+            &mut self.global_entries
+        };
+        if entries.len() < entry_size {
+            return Err(self.insufficient_entries(is_local, name));
+        }
+        let bytes = slice_take_prefix_mut(entries, entry_size);
+        let mut entry = match self.elf_class {
+            ElfClass::Elf32 => SymtabEntryMut::Elf32(
+                from_bytes_mut(bytes)
+                    .map_err(|_| anyhow!("Invalid symtab alignment"))?
+                    .0,
+            ),
+            ElfClass::Elf64 => SymtabEntryMut::Elf64(
+                from_bytes_mut(bytes)
+                    .map_err(|_| anyhow!("Invalid symtab alignment"))?
+                    .0,
+            ),
         };
-        let e = LittleEndian;
+        if self.is_dynamic && !is_local {
+            tracing::trace!(name = %String::from_utf8_lossy(name), "Write .dynsym");
+        }
         let string_offset = self.strtab_writer.write_str(name);
-        entry.st_name.set(e, string_offset);
-        entry.st_other = 0;
-        entry.st_shndx.set(e, shndx);
-        entry.st_value.set(e, value);
-        entry.st_size.set(e, size);
+        entry.set_fields(self.endian, string_offset, shndx, value, size);
         Ok(entry)
     }
 
+    fn insufficient_entries(&self, is_local: bool, name: &[u8]) -> anyhow::Error {
+        if is_local {
+            anyhow!(
+                "Insufficient .symtab local entries allocated for symbol `{}`",
+                String::from_utf8_lossy(name),
+            )
+        } else {
+            anyhow!(
+                "Insufficient {} entries allocated for symbol `{}`",
+                if self.is_dynamic {
+                    DYNSYM_SECTION_NAME_STR
+                } else {
+                    ".symtab global"
+                },
+                String::from_utf8_lossy(name),
+            )
+        }
+    }
+
     /// Verifies that we've used up all the space allocated to this writer. i.e. checks that we
     /// didn't allocate too much or missed writing something that we were supposed to write.
     fn check_exhausted(&self) -> Result {
@@ -1405,8 +1703,8 @@ impl<'data, 'layout, 'out> SymbolTableWriter<'data, 'layout, 'out> {
             };
             bail!(
                 "Didn't use up all allocated {table_names} space. local={} global={} strings={}",
-                self.local_entries.len(),
-                self.global_entries.len(),
+                self.local_entries.len() / self.elf_class.symtab_entry_size() as usize,
+                self.global_entries.len() / self.elf_class.symtab_entry_size() as usize,
                 self.strtab_writer.out.len()
             );
         }
@@ -1424,10 +1722,21 @@ impl<'data> ObjectLayout<'data> {
     ) -> Result {
         let _span = debug_span!("write_file", filename = %self.input).entered();
         let _file_span = layout.args().trace_span_for_file(self.file_id);
-        for sec in &self.sections {
-            match sec {
+        let mut erratum_patches = Vec::new();
+        for section_index in
+            section_output_order(self.section_order.as_deref(), self.sections.len())
+        {
+            match &self.sections[section_index.0] {
                 SectionSlot::Loaded(sec) => {
-                    self.write_section::<A>(layout, sec, buffers, table_writer, trace)?;
+                    self.write_section::<A>(
+                        layout,
+                        sec,
+                        buffers,
+                        table_writer,
+                        trace,
+                        &mut erratum_patches,
+                    )?;
+                    self.write_thunks::<A>(sec, buffers, layout, &erratum_patches)?;
                 }
                 SectionSlot::LoadedDebugInfo(sec) => {
                     self.write_debug_section::<A>(layout, sec, buffers)?;
@@ -1455,10 +1764,10 @@ impl<'data> ObjectLayout<'data> {
                     let symbol = self
                         .object
                         .symbol(self.symbol_id_range.id_to_input(symbol_id))?;
-                    let name = self.object.symbol_name(symbol)?;
+                    let name = self.object.symbol_name(&symbol)?;
                     table_writer
                         .dynsym_writer
-                        .copy_symbol_shndx(symbol, name, 0, 0)?;
+                        .copy_symbol_shndx(&symbol, name, 0, 0)?;
                     if layout.gnu_version_enabled() {
                         table_writer
                             .version_writer
@@ -1481,8 +1790,21 @@ impl<'data> ObjectLayout<'data> {
         buffers: &mut OutputSectionPartMap<&mut [u8]>,
         table_writer: &mut TableWriter,
         trace: &TraceOutput,
+        erratum_patches: &mut Vec<ErratumPatch>,
     ) -> Result {
         let out = self.write_section_raw(layout, sec, buffers)?;
+        if let Some(shrunk) = self.shrunk_section(sec.index) {
+            let input = self
+                .object
+                .raw_section_data(&self.object.section(sec.index)?)?;
+            for edit in &shrunk.edits {
+                let offset = shrunk.output_offset(edit.offset) as usize;
+                A::apply_relaxation_edit(&input[edit.offset as usize..], edit, &mut out[offset..]);
+            }
+        }
+        if sec.is_reversed {
+            reverse_pointers(out, layout.args().elf_class().pointer_size() as usize)?;
+        }
         self.apply_relocations::<A>(out, sec, layout, table_writer, trace)
             .with_context(|| {
                 format!(
@@ -1491,6 +1813,14 @@ impl<'data> ObjectLayout<'data> {
                     self.input
                 )
             })?;
+        self.fix_errata::<A>(out, sec, erratum_patches)
+            .with_context(|| {
+                format!(
+                    "Failed to apply erratum workarounds in section `{}` of {}",
+                    self.object.section_display_name(sec.index),
+                    self.input
+                )
+            })?;
         if sec.resolution_kind.contains(ResolutionFlags::GOT)
             || sec.resolution_kind.contains(ResolutionFlags::PLT)
         {
@@ -1499,6 +1829,129 @@ impl<'data> ObjectLayout<'data> {
         Ok(())
     }
 
+    /// Works around CPU errata for any sequences in `sec` that ended up at addresses where they'd
+    /// trigger them. Patches that we need are added to `erratum_patches` so that they can be
+    /// written along with our thunks.
+    fn fix_errata<A: Arch>(
+        &self,
+        out: &mut [u8],
+        sec: &Section,
+        erratum_patches: &mut Vec<ErratumPatch>,
+    ) -> Result {
+        let Some(area) = self.thunk_area(sec.part_id, sec.index) else {
+            return Ok(());
+        };
+        let start = area
+            .erratum_sequences
+            .partition_point(|(section_index, _)| section_index.0 < sec.index.0);
+        if start == area.erratum_sequences.len() {
+            return Ok(());
+        }
+        let section_address = self.section_resolutions[sec.index.0]
+            .address()
+            .context("Attempted to fix errata in a section that we didn't load")?;
+        for (index, &(_, offset)) in area
+            .erratum_sequences
+            .iter()
+            .enumerate()
+            .skip(start)
+            .take_while(|(_, (section_index, _))| *section_index == sec.index)
+        {
+            let mut patch = ErratumPatch {
+                address: area.erratum_patch_address(index),
+                bytes: [0; crate::elf::ERRATUM_PATCH_SIZE as usize],
+            };
+            if A::fix_erratum_sequence(
+                out,
+                offset as usize,
+                section_address,
+                &mut patch.bytes,
+                patch.address,
+            )? {
+                erratum_patches.push(patch);
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes any range-extension thunks and erratum patches that layout placed immediately after
+    /// `sec`.
+    fn write_thunks<A: Arch>(
+        &self,
+        sec: &Section,
+        buffers: &mut OutputSectionPartMap<&mut [u8]>,
+        layout: &Layout<'data>,
+        erratum_patches: &[ErratumPatch],
+    ) -> Result {
+        let start = self
+            .thunk_areas
+            .partition_point(|area| area.last_section.0 < sec.index.0);
+        for area in self.thunk_areas[start..]
+            .iter()
+            .take_while(|area| area.last_section == sec.index)
+        {
+            let out =
+                slice_take_prefix_mut(buffers.get_mut(area.part_id), area.capacity() as usize);
+            let thunk_size = crate::elf::THUNK_SIZE as usize;
+            for (index, (&(symbol_index, addend), thunk)) in area
+                .targets
+                .iter()
+                .zip(out.chunks_exact_mut(thunk_size))
+                .enumerate()
+            {
+                let target_address = self
+                    .thunk_target_address(symbol_index, addend, layout)
+                    .with_context(|| {
+                        format!(
+                            "Failed to write thunk for {}",
+                            layout.symbol_debug(self.symbol_id_range.input_to_id(symbol_index))
+                        )
+                    })?;
+                A::write_thunk(
+                    thunk,
+                    area.address + (index * thunk_size) as u64,
+                    target_address,
+                )?;
+            }
+            let area_end = area.address + area.capacity();
+            for patch in erratum_patches
+                .iter()
+                .filter(|patch| (area.address..area_end).contains(&patch.address))
+            {
+                let offset = (patch.address - area.address) as usize;
+                out[offset..offset + patch.bytes.len()].copy_from_slice(&patch.bytes);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns where a thunk for a branch to `symbol_index` plus `addend` should branch to. This
+    /// needs to match where `apply_relocation` would have pointed the branch itself.
+    fn thunk_target_address(
+        &self,
+        symbol_index: object::SymbolIndex,
+        addend: i64,
+        layout: &Layout<'data>,
+    ) -> Result<u64> {
+        let symbol_id = self.symbol_id_range.input_to_id(symbol_index);
+        let resolution = layout
+            .merged_symbol_resolution(symbol_id)
+            .context("Missing resolution")?;
+        if resolution.plt_address.is_some()
+            && (resolution.value_flags.contains(ValueFlags::IFUNC)
+                || !resolution.value_flags.contains(ValueFlags::CAN_BYPASS_GOT))
+        {
+            return Ok(resolution.plt_address()?.wrapping_add(addend as u64));
+        }
+        resolution.value_with_addend(
+            addend,
+            symbol_index,
+            self,
+            &layout.merged_strings,
+            &layout.merged_string_start_addresses,
+        )
+    }
+
     fn write_debug_section<A: Arch>(
         &self,
         layout: &Layout<'data>,
@@ -1539,8 +1992,13 @@ impl<'data> ObjectLayout<'data> {
             }
             let out = slice_take_prefix_mut(section_buffer, allocation_size);
             // Cut off any padding so that our output buffer is the size of our input buffer.
-            let object_section = self.object.section(sec.index)?;
+            let object_section = &self.object.section(sec.index)?;
             let section_size = self.object.section_size(object_section)?;
+            if let Some(shrunk) = self.shrunk_section(sec.index) {
+                let out: &'out mut [u8] = &mut out[..sec.size as usize];
+                shrunk.copy_without_deleted(self.object.raw_section_data(object_section)?, out);
+                return Ok(out);
+            }
             let out: &'out mut [u8] = &mut out[..section_size as usize];
             self.object.copy_section_data(object_section, out)?;
             Ok(out)
@@ -1557,24 +2015,25 @@ impl<'data> ObjectLayout<'data> {
     ) -> Result {
         for ((sym_index, sym), sym_state) in self
             .object
-            .symbols
-            .enumerate()
+            .symbols()
             .zip(&layout.symbol_resolution_flags[self.symbol_id_range.as_usize()])
         {
             let symbol_id = self.symbol_id_range.input_to_id(sym_index);
             if let Some(info) = SymbolCopyInfo::new(
                 self.object,
                 sym_index,
-                sym,
+                &sym,
                 symbol_id,
                 &layout.symbol_db,
                 *sym_state,
                 &self.sections,
             ) {
-                let e = LittleEndian;
+                let e = self.object.endian;
+                let mut shrunk = None;
                 let section_id = if let Some(section_index) =
-                    self.object.symbol_section(sym, sym_index)?
+                    self.object.symbol_section(&sym, sym_index)?
                 {
+                    shrunk = self.shrunk_section(section_index);
                     match &self.sections[section_index.0] {
                         SectionSlot::Loaded(section) => section.output_section_id(),
                         SectionSlot::MergeStrings(section) => section.part_id.output_section_id(),
@@ -1585,7 +2044,7 @@ impl<'data> ObjectLayout<'data> {
                     output_section_id::BSS
                 } else if sym.is_absolute(e) {
                     symbol_writer
-                        .copy_absolute_symbol(sym, info.name)
+                        .copy_absolute_symbol(&sym, info.is_local, info.name)
                         .with_context(|| {
                             format!(
                                 "Failed to absolute {}",
@@ -1606,11 +2065,17 @@ impl<'data> ObjectLayout<'data> {
                     )?;
                     symbol_value -= tls_start_address;
                 }
-                symbol_writer
-                    .copy_symbol(sym, info.name, section_id, symbol_value)
+                let mut entry = symbol_writer
+                    .copy_symbol(&sym, info.is_local, info.name, section_id, symbol_value)
                     .with_context(|| {
                         format!("Failed to copy {}", layout.symbol_debug(symbol_id))
                     })?;
+                if let Some(shrunk) = shrunk {
+                    // Linker relaxation may have deleted bytes from within the symbol.
+                    let start = sym.st_value(e);
+                    let end = start + sym.st_size(e);
+                    entry.set_size(e, shrunk.output_offset(end) - shrunk.output_offset(start));
+                }
             }
         }
         Ok(())
@@ -1628,28 +2093,43 @@ impl<'data> ObjectLayout<'data> {
             .address()
             .context("Attempted to apply relocations to a section that we didn't load")?;
 
-        let object_section = self.object.section(section.index)?;
-        let section_flags = SectionFlags::from_header(object_section);
+        let object_section = &self.object.section(section.index)?;
+        let section_flags = SectionFlags::from_header(object_section, self.object.endian);
         let mut modifier = RelocationModifier::Normal;
         let relocations = self.relocations(section.index)?;
+        let shrunk = self.shrunk_section(section.index);
         layout
             .relocation_statistics
             .get(section.part_id.output_section_id())
             .fetch_add(relocations.len() as u64, Relaxed);
-        for rel in relocations {
+        for rel in relocations.iter() {
             if modifier == RelocationModifier::SkipNextRelocation {
                 modifier = RelocationModifier::Normal;
                 continue;
             }
-            let offset_in_section = rel.r_offset.get(LittleEndian);
+            let mut offset_in_section = rel.r_offset.get(self.object.endian);
+            if let Some(shrunk) = shrunk {
+                if shrunk.is_deleted(offset_in_section) {
+                    continue;
+                }
+                offset_in_section = shrunk.output_offset(offset_in_section);
+            }
+            if section.is_reversed {
+                offset_in_section = section
+                    .size
+                    .checked_sub(offset_in_section + layout.args().elf_class().pointer_size())
+                    .context("Relocation outside of reversed section")?;
+            }
             modifier = apply_relocation::<A>(
                 self,
                 offset_in_section,
-                rel,
+                &rel,
                 SectionInfo {
                     section_address,
                     is_writable: section.is_writable,
                     section_flags,
+                    part_id: section.part_id,
+                    section_index: section.index,
                 },
                 layout,
                 out,
@@ -1659,7 +2139,7 @@ impl<'data> ObjectLayout<'data> {
             .with_context(|| {
                 format!(
                     "Failed to apply {} at offset 0x{offset_in_section:x}",
-                    self.display_relocation::<A>(rel, layout)
+                    self.display_relocation::<A>(&rel, layout)
                 )
             })?;
         }
@@ -1672,7 +2152,7 @@ impl<'data> ObjectLayout<'data> {
         section: &Section,
         layout: &Layout<'data>,
     ) -> Result {
-        let object_section = self.object.section(section.index)?;
+        let object_section = &self.object.section(section.index)?;
         let section_name = self.object.section_name(object_section)?;
         let tombstone_value: u64 =
             // TODO: Starting with DWARF 6, the tombstone value will be defined as -1 and -2.
@@ -1692,15 +2172,22 @@ impl<'data> ObjectLayout<'data> {
             .relocation_statistics
             .get(section.part_id.output_section_id())
             .fetch_add(relocations.len() as u64, Relaxed);
-        for rel in relocations {
-            let offset_in_section = rel.r_offset.get(LittleEndian);
-            apply_debug_relocation::<A>(self, offset_in_section, rel, layout, tombstone_value, out)
-                .with_context(|| {
-                    format!(
-                        "Failed to apply {} at offset 0x{offset_in_section:x}",
-                        self.display_relocation::<A>(rel, layout)
-                    )
-                })?;
+        for rel in relocations.iter() {
+            let offset_in_section = rel.r_offset.get(self.object.endian);
+            apply_debug_relocation::<A>(
+                self,
+                offset_in_section,
+                &rel,
+                layout,
+                tombstone_value,
+                out,
+            )
+            .with_context(|| {
+                format!(
+                    "Failed to apply {} at offset 0x{offset_in_section:x}",
+                    self.display_relocation::<A>(&rel, layout)
+                )
+            })?;
         }
         Ok(())
     }
@@ -1712,12 +2199,13 @@ impl<'data> ObjectLayout<'data> {
         table_writer: &mut TableWriter,
         trace: &TraceOutput,
     ) -> Result {
-        let eh_frame_section = self.object.section(eh_frame_section_index)?;
+        let eh_frame_section = &self.object.section(eh_frame_section_index)?;
         let data = self.object.raw_section_data(eh_frame_section)?;
         const PREFIX_LEN: usize = size_of::<elf::EhFrameEntryPrefix>();
-        let e = LittleEndian;
-        let section_flags = SectionFlags::from_header(eh_frame_section);
-        let mut relocations = self.relocations(eh_frame_section_index)?.iter().peekable();
+        let e = self.object.endian;
+        let section_flags = SectionFlags::from_header(eh_frame_section, e);
+        let relocations = self.relocations(eh_frame_section_index)?;
+        let mut relocations = relocations.iter().peekable();
         let mut input_pos = 0;
         let mut output_pos = 0;
         let frame_info_ptr_base = table_writer.eh_frame_start_address;
@@ -1727,8 +2215,7 @@ impl<'data> ObjectLayout<'data> {
         let mut cies_offset_conversion: AHashMap<u32, u32> = AHashMap::new();
 
         while input_pos + PREFIX_LEN <= data.len() {
-            let prefix: elf::EhFrameEntryPrefix =
-                bytemuck::pod_read_unaligned(&data[input_pos..input_pos + PREFIX_LEN]);
+            let prefix = elf::EhFrameEntryPrefix::read(e, &data[input_pos..input_pos + PREFIX_LEN]);
             let size = size_of_val(&prefix.length) + prefix.length as usize;
             let next_input_pos = input_pos + size;
             let next_output_pos = output_pos + size;
@@ -1761,8 +2248,11 @@ impl<'data> ObjectLayout<'data> {
                                     ".eh_frame pc-begin refers to symbol that's not defined in file"
                                 );
                             };
-                            let offset_in_section =
+                            let mut offset_in_section =
                                 (elf_symbol.st_value(e) as i64 + rel.r_addend.get(e)) as u64;
+                            if let Some(shrunk) = self.shrunk_section(section_index) {
+                                offset_in_section = shrunk.output_offset(offset_in_section);
+                            }
                             if let Some(section_address) =
                                 self.section_resolutions[section_index.0].address()
                             {
@@ -1783,11 +2273,15 @@ impl<'data> ObjectLayout<'data> {
                                         as i64
                                         - eh_frame_hdr_address as i64;
                                     *hdr_out = EhFrameHdrEntry {
-                                        frame_ptr: i32::try_from(frame_ptr)
-                                            .context("32 bit overflow in frame_ptr")?,
-                                        frame_info_ptr: i32::try_from(frame_info_ptr).context(
-                                            "32 bit overflow when computing frame_info_ptr",
-                                        )?,
+                                        frame_ptr: e.write_i32(
+                                            i32::try_from(frame_ptr)
+                                                .context("32 bit overflow in frame_ptr")?,
+                                        ),
+                                        frame_info_ptr: e.write_i32(
+                                            i32::try_from(frame_info_ptr).context(
+                                                "32 bit overflow when computing frame_info_ptr",
+                                            )?,
+                                        ),
                                     };
                                 }
                                 // TODO: Experiment with skipping this lookup if the `input_cie_pos`
@@ -1803,7 +2297,7 @@ impl<'data> ObjectLayout<'data> {
                 let entry_out = table_writer.take_eh_frame_data(next_output_pos - output_pos)?;
                 entry_out.copy_from_slice(&data[input_pos..next_input_pos]);
                 if let Some(output_cie_offset) = output_cie_offset {
-                    entry_out[4..8].copy_from_slice(&output_cie_offset.to_le_bytes());
+                    entry_out[4..8].copy_from_slice(&e.write_u32_bytes(output_cie_offset));
                 }
                 while let Some(rel) = relocations.peek() {
                     let rel_offset = rel.r_offset.get(e);
@@ -1820,6 +2314,8 @@ impl<'data> ObjectLayout<'data> {
                                 + table_writer.eh_frame_start_address,
                             is_writable: false,
                             section_flags,
+                            part_id: part_id::EH_FRAME,
+                            section_index: eh_frame_section_index,
                         },
                         layout,
                         entry_out,
@@ -1887,7 +2383,7 @@ struct DisplayRelocation<'a, 'data, A: Arch> {
 
 impl<A: Arch> Display for DisplayRelocation<'_, '_, A> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let e = LittleEndian;
+        let e = self.object.object.endian;
         write!(
             f,
             "relocation of type {} to ",
@@ -1909,6 +2405,33 @@ struct SectionInfo {
     section_address: u64,
     is_writable: bool,
     section_flags: SectionFlags,
+    part_id: PartId,
+    section_index: object::SectionIndex,
+}
+
+/// The contents of a patch that works around a CPU erratum, waiting to be written after the last
+/// section in its run.
+struct ErratumPatch {
+    address: u64,
+    bytes: [u8; crate::elf::ERRATUM_PATCH_SIZE as usize],
+}
+
+/// Reverses the order of the pointers in `out`. Legacy `.ctors` and `.dtors` sections are run from
+/// the end to the start, whereas `.init_array` and `.fini_array`, where we put them, are run from
+/// start to end.
+fn reverse_pointers(out: &mut [u8], pointer_size: usize) -> Result {
+    if out.len() % pointer_size != 0 {
+        bail!(
+            "Section size 0x{:x} isn't a multiple of the pointer size",
+            out.len()
+        );
+    }
+    let num_pointers = out.len() / pointer_size;
+    for i in 0..num_pointers / 2 {
+        let (start, end) = out.split_at_mut((num_pointers - 1 - i) * pointer_size);
+        start[i * pointer_size..(i + 1) * pointer_size].swap_with_slice(&mut end[..pointer_size]);
+    }
+    Ok(())
 }
 
 /// Applies the relocation `rel` at `offset_in_section`, where the section bytes are `out`. See "ELF
@@ -1934,10 +2457,16 @@ fn apply_relocation<A: Arch>(
     )
     .entered();
 
-    let e = LittleEndian;
-    let symbol_index = rel
-        .symbol(e, false)
-        .context("Unsupported absolute relocation")?;
+    let e = object_layout.object.endian;
+    let Some(symbol_index) = rel.symbol(e, false) else {
+        // Relocations such as R_RISCV_RELAX and R_RISCV_ALIGN only provide hints for linker
+        // relaxation and don't refer to a symbol.
+        ensure!(
+            A::relocation_from_raw(rel.r_type(e, false))?.kind == RelocationKind::None,
+            "Unsupported absolute relocation"
+        );
+        return Ok(RelocationModifier::Normal);
+    };
     let local_symbol_id = object_layout.symbol_id_range.input_to_id(symbol_index);
     let resolution = layout
         .merged_symbol_resolution(local_symbol_id)
@@ -1948,11 +2477,42 @@ fn apply_relocation<A: Arch>(
             )
         })?;
 
+    let r_type = rel.r_type(e, false);
+
+    // The low part of a RISC-V PC-relative address refers to a label on the instruction that
+    // computed the high part. The symbol and addend that we need are on that instruction's
+    // relocation.
+    let mut pcrel_hi = None;
+    let (symbol_index, resolution, mut addend) = if A::relocation_from_raw(r_type)?.kind
+        == RelocationKind::PcRelLoRiscV
+    {
+        let hi = pcrel_hi_relocation(object_layout, section_info.section_index, symbol_index)?;
+        let hi_symbol_index = hi
+            .symbol(e, false)
+            .context("Unsupported absolute relocation")?;
+        let hi_symbol_id = object_layout.symbol_id_range.input_to_id(hi_symbol_index);
+        let hi_resolution = layout
+            .merged_symbol_resolution(hi_symbol_id)
+            .with_context(|| {
+                format!(
+                    "Missing resolution for: {}",
+                    layout.symbol_db.symbol_debug(hi_symbol_id)
+                )
+            })?;
+        let hi_offset = hi.r_offset.get(e);
+        let hi_place = section_address
+            + object_layout
+                .shrunk_section(section_info.section_index)
+                .map_or(hi_offset, |shrunk| shrunk.output_offset(hi_offset));
+        pcrel_hi = Some((hi_place, A::relocation_from_raw(hi.r_type(e, false))?.kind));
+        (hi_symbol_index, hi_resolution, hi.r_addend.get(e))
+    } else {
+        (symbol_index, resolution, rel.r_addend.get(e))
+    };
+
     let value_flags = resolution.value_flags;
     let resolution_flags = resolution.resolution_flags;
-    let mut addend = rel.r_addend.get(e);
     let mut next_modifier = RelocationModifier::Normal;
-    let r_type = rel.r_type(e, false);
     let rel_info;
     let output_kind = layout.args().output_kind();
 
@@ -1978,6 +2538,7 @@ fn apply_relocation<A: Arch>(
     let place = section_address + offset_in_section;
 
     let mask = get_page_mask(rel_info.mask);
+    let got_entry_size = layout.args().got_entry_size();
     let value = match rel_info.kind {
         RelocationKind::Absolute => {
             assert!(rel_info.mask.is_none());
@@ -2044,16 +2605,16 @@ fn apply_relocation<A: Arch>(
             .wrapping_sub(place.bitand(mask.place)),
         // TLS-related relocations
         RelocationKind::TlsGd => resolution
-            .tlsgd_got_address()?
+            .tlsgd_got_address(got_entry_size)?
             .bitand(mask.got_entry)
             .wrapping_add(addend as u64)
             .wrapping_sub(place.bitand(mask.place)),
         RelocationKind::TlsGdGot => resolution
-            .tlsgd_got_address()?
+            .tlsgd_got_address(got_entry_size)?
             .bitand(mask.got_entry)
             .wrapping_add(addend as u64),
         RelocationKind::TlsGdGotBase => resolution
-            .tlsgd_got_address()?
+            .tlsgd_got_address(got_entry_size)?
             .bitand(mask.got_entry)
             .wrapping_add(addend as u64)
             .wrapping_sub(layout.got_base().bitand(mask.got)),
@@ -2102,28 +2663,73 @@ fn apply_relocation<A: Arch>(
             .bitand(mask.got_entry)
             .wrapping_add(addend as u64)
             .wrapping_sub(layout.got_base().bitand(mask.got)),
+        RelocationKind::DtpOffRiscV => resolution
+            .value()
+            .wrapping_sub(layout.tls_start_address())
+            .wrapping_sub(A::tls_dtv_offset())
+            .wrapping_add(addend as u64),
         RelocationKind::TpOff => resolution
             .value()
-            .wrapping_sub(layout.tls_end_address())
+            .wrapping_sub(layout.thread_pointer_address())
             .wrapping_add(addend as u64),
         RelocationKind::TpOffAArch64 => resolution
             .value()
             .wrapping_sub(layout.tls_start_address_aarch64())
             .wrapping_add(addend as u64),
         RelocationKind::TlsDesc => resolution
-            .tls_descriptor_got_address()?
+            .tls_descriptor_got_address(got_entry_size)?
             .bitand(mask.got_entry)
             .wrapping_add(addend as u64)
             .wrapping_sub(place.bitand(mask.place)),
         RelocationKind::TlsDescGot => resolution
-            .tls_descriptor_got_address()?
+            .tls_descriptor_got_address(got_entry_size)?
             .bitand(mask.got_entry)
             .wrapping_add(addend as u64),
         RelocationKind::TlsDescGotBase => resolution
-            .tls_descriptor_got_address()?
+            .tls_descriptor_got_address(got_entry_size)?
             .bitand(mask.got_entry)
             .wrapping_add(addend as u64)
             .wrapping_sub(layout.got_base().bitand(mask.got)),
+        RelocationKind::PcRelLoRiscV => {
+            let (hi_place, hi_kind) = pcrel_hi.context("Missing high-part relocation")?;
+            let target = match hi_kind {
+                RelocationKind::Relative => resolution.value_with_addend(
+                    addend,
+                    symbol_index,
+                    object_layout,
+                    &layout.merged_strings,
+                    &layout.merged_string_start_addresses,
+                )?,
+                RelocationKind::GotRelative | RelocationKind::GotTpOff => {
+                    resolution.got_address()?.wrapping_add(addend as u64)
+                }
+                RelocationKind::TlsGd => resolution
+                    .tlsgd_got_address(got_entry_size)?
+                    .wrapping_add(addend as u64),
+                RelocationKind::TlsDesc => resolution
+                    .tls_descriptor_got_address(got_entry_size)?
+                    .wrapping_add(addend as u64),
+                other => bail!("Unsupported high-part relocation kind {other:?}"),
+            };
+            target.wrapping_sub(hi_place)
+        }
+        RelocationKind::AbsoluteSet
+        | RelocationKind::AbsoluteAddition
+        | RelocationKind::AbsoluteSubtraction => {
+            let symbol_value = resolution.value_with_addend(
+                addend,
+                symbol_index,
+                object_layout,
+                &layout.merged_strings,
+                &layout.merged_string_start_addresses,
+            )?;
+            combine_with_existing_value(
+                rel_info,
+                symbol_value,
+                &out[offset_in_section as usize..],
+                layout.args().endian,
+            )?
+        }
         RelocationKind::None | RelocationKind::TlsDescCall => 0,
     };
 
@@ -2158,11 +2764,94 @@ fn apply_relocation<A: Arch>(
             "relocation applied");
     }
 
-    write_relocation_to_buffer(rel_info, value, &mut out[offset_in_section as usize..])?;
+    // If a branch can't reach its target, send it via the thunk that layout allocated for it.
+    let value = if A::is_thunkable_branch(r_type)
+        && !(rel_info.range.min..rel_info.range.max).contains(&(value as i64))
+        && let Some(thunk_address) = object_layout.thunk_address(
+            section_info.part_id,
+            section_info.section_index,
+            symbol_index,
+            rel.r_addend.get(e),
+        ) {
+        trace.emit(original_place, || {
+            format!("branch redirected via thunk at 0x{thunk_address:x}")
+        });
+        thunk_address.wrapping_sub(place)
+    } else {
+        value
+    };
+
+    write_relocation_to_buffer(
+        rel_info,
+        value,
+        &mut out[offset_in_section as usize..],
+        layout.args().endian,
+    )?;
 
     Ok(next_modifier)
 }
 
+/// Returns the relocation on the instruction that computed the high part of a RISC-V PC-relative
+/// address. `label_index` is the symbol referenced by the relocation for the low part.
+fn pcrel_hi_relocation<'data>(
+    object_layout: &ObjectLayout<'data>,
+    section_index: object::SectionIndex,
+    label_index: object::SymbolIndex,
+) -> Result<elf::Rela> {
+    let e = object_layout.object.endian;
+    let label = &object_layout.object.symbol(label_index)?;
+    let hi_offset = label.st_value(e);
+    let relocations = object_layout.relocations(section_index)?;
+    // Relocations are normally sorted by offset, so try a binary search first.
+    let start = relocations.partition_point(|rel| rel.r_offset.get(e) < hi_offset);
+    let is_hi = |rel: &elf::Rela| {
+        rel.r_offset.get(e) == hi_offset
+            && !matches!(
+                rel.r_type(e, false),
+                object::elf::R_RISCV_RELAX | object::elf::R_RISCV_NONE
+            )
+    };
+    relocations
+        .iter()
+        .skip(start)
+        .take_while(|rel| rel.r_offset.get(e) == hi_offset)
+        .find(is_hi)
+        .or_else(|| relocations.iter().find(is_hi))
+        .with_context(|| format!("No high-part relocation found at offset 0x{hi_offset:x}"))
+}
+
+/// Computes the value for a relocation that sets, adds to or subtracts from whatever value is
+/// already present at the location being relocated.
+pub(crate) fn combine_with_existing_value(
+    rel_info: RelocationKindInfo,
+    symbol_value: u64,
+    existing: &[u8],
+    endian: Endianness,
+) -> Result<u64> {
+    let read_existing = || -> Result<u64> {
+        Ok(match rel_info.size {
+            RelocationSize::ByteSize(byte_size) => crate::elf::read_uint(
+                endian,
+                existing
+                    .get(..byte_size)
+                    .context("Relocation outside of bounds of section")?,
+            ),
+            RelocationSize::BitMasking(bit_mask) => {
+                ensure!(
+                    bit_mask.instruction.num_bytes() <= existing.len(),
+                    "Relocation outside of bounds of section"
+                );
+                bit_mask.instruction.read_value(existing).0
+            }
+        })
+    };
+    Ok(match rel_info.kind {
+        RelocationKind::AbsoluteAddition => read_existing()?.wrapping_add(symbol_value),
+        RelocationKind::AbsoluteSubtraction => read_existing()?.wrapping_sub(symbol_value),
+        _ => symbol_value,
+    })
+}
+
 fn apply_debug_relocation<A: Arch>(
     object_layout: &ObjectLayout,
     offset_in_section: u64,
@@ -2171,38 +2860,64 @@ fn apply_debug_relocation<A: Arch>(
     section_tombstone_value: u64,
     out: &mut [u8],
 ) -> Result<()> {
-    let e = LittleEndian;
+    let e = object_layout.object.endian;
     let symbol_index = rel
         .symbol(e, false)
         .context("Unsupported absolute relocation")?;
-    let sym = object_layout.object.symbol(symbol_index)?;
+    let sym = &object_layout.object.symbol(symbol_index)?;
     let section_index = object_layout.object.symbol_section(sym, symbol_index)?;
 
     let addend = rel.r_addend.get(e);
     let r_type = rel.r_type(e, false);
     let rel_info = A::relocation_from_raw(r_type)?;
 
-    let resolution = layout
-        .merged_symbol_resolution(object_layout.symbol_id_range.input_to_id(symbol_index))
-        .or_else(|| {
-            section_index.and_then(|section_index| {
-                object_layout.section_resolutions[section_index.0].full_resolution()
-            })
-        });
+    let symbol_resolution =
+        layout.merged_symbol_resolution(object_layout.symbol_id_range.input_to_id(symbol_index));
+    let resolution = symbol_resolution.or_else(|| {
+        section_index.and_then(|section_index| {
+            object_layout.section_resolutions[section_index.0].full_resolution()
+        })
+    });
 
     let value = if let Some(resolution) = resolution {
+        let symbol_value = || {
+            if symbol_resolution.is_some() {
+                return resolution.value_with_addend(
+                    addend,
+                    symbol_index,
+                    object_layout,
+                    &layout.merged_strings,
+                    &layout.merged_string_start_addresses,
+                );
+            }
+            // We're using the resolution of the symbol's section, so need to add the symbol's
+            // offset within that section.
+            let offset = sym.st_value(e).wrapping_add(addend as u64);
+            Ok(resolution.raw_value.wrapping_add(
+                section_index
+                    .and_then(|section_index| object_layout.shrunk_section(section_index))
+                    .map_or(offset, |shrunk| shrunk.output_offset(offset)),
+            ))
+        };
         match rel_info.kind {
-            RelocationKind::Absolute => resolution.value_with_addend(
-                addend,
-                symbol_index,
-                object_layout,
-                &layout.merged_strings,
-                &layout.merged_string_start_addresses,
+            RelocationKind::Absolute => symbol_value()?,
+            RelocationKind::AbsoluteSet
+            | RelocationKind::AbsoluteAddition
+            | RelocationKind::AbsoluteSubtraction => combine_with_existing_value(
+                rel_info,
+                symbol_value()?,
+                &out[offset_in_section as usize..],
+                layout.args().endian,
             )?,
             RelocationKind::DtpOff => resolution
                 .value()
                 .wrapping_sub(layout.tls_end_address())
                 .wrapping_add(addend as u64),
+            RelocationKind::DtpOffRiscV => resolution
+                .value()
+                .wrapping_sub(layout.tls_start_address())
+                .wrapping_sub(A::tls_dtv_offset())
+                .wrapping_add(addend as u64),
             kind => bail!("Unsupported debug relocation kind {kind:?}"),
         }
     } else if let Some(section_index) = section_index {
@@ -2224,7 +2939,12 @@ fn apply_debug_relocation<A: Arch>(
         bail!("Could not find a relocation resolution for a debug info section");
     };
 
-    write_relocation_to_buffer(rel_info, value, &mut out[offset_in_section as usize..])?;
+    write_relocation_to_buffer(
+        rel_info,
+        value,
+        &mut out[offset_in_section as usize..],
+        layout.args().endian,
+    )?;
 
     Ok(())
 }
@@ -2240,14 +2960,18 @@ fn write_absolute_relocation<A: Arch>(
     object_layout: &ObjectLayout,
     layout: &Layout,
 ) -> Result<u64> {
-    if resolution.value_flags.contains(ValueFlags::DYNAMIC) && section_info.is_writable {
+    if resolution.value_flags.contains(ValueFlags::DYNAMIC)
+        && (section_info.is_writable
+            || crate::layout::needs_dynamic_text_relocation(resolution.value_flags, layout.args()))
+    {
         table_writer.write_dynamic_symbol_relocation::<A>(
             place,
             addend,
             resolution.dynamic_symbol_index()?,
             DynamicRelocationKind::Absolute,
         )?;
-        Ok(0)
+        // With REL relocations, the addend is read from the place.
+        Ok(implicit_addend(table_writer, addend as u64))
     } else if table_writer.output_kind.is_relocatable() && !resolution.is_absolute() {
         let address = resolution.value_with_addend(
             addend,
@@ -2257,7 +2981,7 @@ fn write_absolute_relocation<A: Arch>(
             &layout.merged_string_start_addresses,
         )?;
         table_writer.write_address_relocation::<A>(place, address as i64)?;
-        Ok(0)
+        Ok(implicit_addend(table_writer, address))
     } else if resolution.value_flags.contains(ValueFlags::IFUNC) {
         Ok(resolution.plt_address()?.wrapping_add(addend as u64))
     } else {
@@ -2271,6 +2995,15 @@ fn write_absolute_relocation<A: Arch>(
     }
 }
 
+/// Returns the value that should be written to the place of a dynamic relocation with the supplied
+/// addend.
+fn implicit_addend(table_writer: &TableWriter, addend: u64) -> u64 {
+    match table_writer.relocation_format {
+        RelocationFormat::Rela => 0,
+        RelocationFormat::Rel => addend,
+    }
+}
+
 impl PreludeLayout {
     fn write_file<A: Arch>(
         &self,
@@ -2278,16 +3011,20 @@ impl PreludeLayout {
         table_writer: &mut TableWriter,
         layout: &Layout,
     ) -> Result {
-        let header: &mut FileHeader = from_bytes_mut(buffers.get_mut(part_id::FILE_HEADER))
-            .map_err(|_| anyhow!("Invalid file header allocation"))?
-            .0;
-        populate_file_header::<A>(layout, &self.header_info, header)?;
+        populate_file_header::<A>(
+            layout,
+            &self.header_info,
+            buffers.get_mut(part_id::FILE_HEADER),
+        )?;
 
-        let mut program_headers =
-            ProgramHeaderWriter::new(buffers.get_mut(part_id::PROGRAM_HEADERS));
+        let mut program_headers = ProgramHeaderWriter::new(
+            buffers.get_mut(part_id::PROGRAM_HEADERS),
+            self.header_info.elf_class,
+            layout.args().endian,
+        );
         write_program_headers(&mut program_headers, layout)?;
 
-        write_section_headers(buffers.get_mut(part_id::SECTION_HEADERS), layout);
+        write_section_headers(buffers.get_mut(part_id::SECTION_HEADERS), layout)?;
 
         write_section_header_strings(buffers.get_mut(part_id::SHSTRTAB), &layout.output_sections);
 
@@ -2382,7 +3119,7 @@ impl PreludeLayout {
             table_writer.process_resolution::<A>(&Resolution {
                 raw_value: 0,
                 dynamic_symbol_index: None,
-                got_address: Some(got_address.saturating_add(elf::GOT_ENTRY_SIZE)),
+                got_address: Some(got_address.saturating_add(layout.args().got_entry_size())),
                 plt_address: None,
                 resolution_flags: ResolutionFlags::GOT,
                 value_flags: ValueFlags::ABSOLUTE,
@@ -2414,7 +3151,7 @@ fn write_verdef(
     soname: Option<&[u8]>,
     epilogue_offsets: &EpilogueOffsets,
 ) -> Result {
-    let e = LittleEndian;
+    let e = table_writer.args.endian;
 
     // Offsets of version strings, except the base version
     let mut version_string_offsets = Vec::with_capacity(verdefs.len() - 1);
@@ -2519,6 +3256,7 @@ fn write_epilogue_dynamic_entries(
     let inputs = DynamicEntryInputs {
         args: layout.args(),
         has_static_tls: layout.has_static_tls,
+        has_text_relocations: layout.has_text_relocations,
         section_layouts: &layout.section_layouts,
         section_part_layouts: &layout.section_part_layouts,
         non_addressable_counts: layout.non_addressable_counts,
@@ -2558,12 +3296,18 @@ impl EpilogueLayout<'_> {
         if layout.args().needs_dynamic() {
             write_epilogue_dynamic_entries(layout, table_writer, &mut epilogue_offsets)?;
         }
-        write_gnu_hash_tables(self, buffers)?;
+        write_gnu_hash_tables(self, buffers, layout.args())?;
 
         write_dynamic_symbol_definitions(self, table_writer, layout)?;
 
         if !&self.gnu_property_notes.is_empty() {
-            write_gnu_property_notes(self, buffers)?;
+            write_gnu_property_notes(self, buffers, layout.args())?;
+        }
+
+        if !self.riscv_attributes.is_empty() {
+            buffers
+                .get_mut(part_id::RISCV_ATTRIBUTES)
+                .copy_from_slice(&self.riscv_attributes);
         }
 
         if let Some(verdefs) = &self.verdefs {
@@ -2582,15 +3326,17 @@ impl EpilogueLayout<'_> {
 fn write_gnu_property_notes(
     epilogue: &EpilogueLayout,
     buffers: &mut OutputSectionPartMap<&mut [u8]>,
+    args: &Args,
 ) -> Result {
-    let e = LittleEndian;
+    let e = args.endian;
+    let elf_class = args.elf_class();
     let (note_header, mut rest) =
         from_bytes_mut::<NoteHeader>(buffers.get_mut(part_id::NOTE_GNU_PROPERTY))
             .map_err(|_| anyhow!("Insufficient .note.gnu.property allocation"))?;
     note_header.n_namesz.set(e, GNU_NOTE_NAME.len() as u32);
     note_header.n_descsz.set(
         e,
-        (epilogue.gnu_property_notes.len() * GNU_NOTE_PROPERTY_ENTRY_SIZE) as u32,
+        (epilogue.gnu_property_notes.len() * elf_class.gnu_note_property_entry_size()) as u32,
     );
     note_header.n_type.set(e, NT_GNU_PROPERTY_TYPE_0);
 
@@ -2598,12 +3344,21 @@ fn write_gnu_property_notes(
     name_out.copy_from_slice(GNU_NOTE_NAME);
 
     for note in &epilogue.gnu_property_notes {
-        let entry_bytes = crate::slice::slice_take_prefix_mut(&mut rest, size_of::<NoteProperty>());
-        let property: &mut NoteProperty = bytemuck::from_bytes_mut(entry_bytes);
-        property.pr_type = note.ptype;
-        property.pr_datasz = size_of_val(&property.pr_data) as u32;
-        property.pr_data = note.data;
-        property.pr_padding = 0;
+        let entry_bytes = crate::slice::slice_take_prefix_mut(
+            &mut rest,
+            elf_class.gnu_note_property_entry_size(),
+        );
+        // 32 bit properties aren't padded, so we write all but the padding, then zero whatever is
+        // left.
+        let property = NoteProperty {
+            pr_type: e.write_u32(note.ptype),
+            pr_datasz: e.write_u32(size_of_val(&note.data) as u32),
+            pr_data: e.write_u32(note.data),
+            pr_padding: 0,
+        };
+        let property_bytes = bytemuck::bytes_of(&property);
+        let len = entry_bytes.len();
+        entry_bytes.copy_from_slice(&property_bytes[..len]);
     }
 
     Ok(())
@@ -2612,6 +3367,7 @@ fn write_gnu_property_notes(
 fn write_gnu_hash_tables(
     epilogue: &EpilogueLayout,
     buffers: &mut OutputSectionPartMap<&mut [u8]>,
+    args: &Args,
 ) -> Result {
     let Some(gnu_hash_layout) = epilogue.gnu_hash_layout.as_ref() else {
         return Ok(());
@@ -2620,15 +3376,20 @@ fn write_gnu_hash_tables(
     let (header, rest) =
         object::from_bytes_mut::<GnuHashHeader>(buffers.get_mut(part_id::GNU_HASH))
             .map_err(|_| anyhow!("Insufficient .gnu.hash allocation"))?;
-    let e = LittleEndian;
+    let e = args.endian;
+    let elf_class = args.elf_class();
     header.bucket_count.set(e, gnu_hash_layout.bucket_count);
     header.bloom_shift.set(e, gnu_hash_layout.bloom_shift);
     header.bloom_count.set(e, gnu_hash_layout.bloom_count);
     header.symbol_base.set(e, gnu_hash_layout.symbol_base);
 
-    let (bloom, rest) =
-        object::slice_from_bytes_mut::<u64>(rest, gnu_hash_layout.bloom_count as usize)
-            .map_err(|_| anyhow!("Insufficient bytes for .gnu.hash bloom filter"))?;
+    // Bloom filter words are the size of a pointer.
+    let word_size = elf_class.pointer_size() as usize;
+    let bloom_size = gnu_hash_layout.bloom_count as usize * word_size;
+    if rest.len() < bloom_size {
+        bail!("Insufficient bytes for .gnu.hash bloom filter");
+    }
+    let (bloom_out, rest) = rest.split_at_mut(bloom_size);
     let (buckets, rest) =
         object::slice_from_bytes_mut::<u32>(rest, gnu_hash_layout.bucket_count as usize)
             .map_err(|_| anyhow!("Insufficient bytes for .gnu.hash buckets"))?;
@@ -2636,11 +3397,11 @@ fn write_gnu_hash_tables(
         object::slice_from_bytes_mut::<u32>(rest, epilogue.dynamic_symbol_definitions.len())
             .map_err(|_| anyhow!("Insufficient bytes for .gnu.hash chains"))?;
 
-    bloom.fill(0);
+    let mut bloom = vec![0_u64; gnu_hash_layout.bloom_count as usize];
 
     let mut sym_defs = epilogue.dynamic_symbol_definitions.iter().peekable();
 
-    let elf_class_bits = size_of::<u64>() as u32 * 8;
+    let elf_class_bits = word_size as u32 * 8;
 
     let mut start_of_chain = true;
     for (i, chain_out) in chains.iter_mut().enumerate() {
@@ -2670,6 +3431,13 @@ fn write_gnu_hash_tables(
             start_of_chain = true;
         }
     }
+
+    for (word, out) in bloom.iter().zip(bloom_out.chunks_exact_mut(word_size)) {
+        crate::elf::write_uint(e, *word, out);
+    }
+    for value in buckets.iter_mut().chain(chains.iter_mut()) {
+        *value = e.write_u32(*value);
+    }
     Ok(())
 }
 
@@ -2698,7 +3466,7 @@ fn write_dynamic_symbol_definitions(
                             .version_script
                             .version_for_symbol(&UnversionedSymbolName::prehashed(sym_def.name))
                             .unwrap_or(object::elf::VER_NDX_GLOBAL);
-                        version_out.0.set(LittleEndian, version);
+                        version_out.0.set(layout.args().endian, version);
                     }
                 }
             }
@@ -2716,6 +3484,7 @@ fn write_dynamic_symbol_definitions(
                         object.symbol_id_range.id_to_offset(sym_def.symbol_id),
                         &object.version_mapping,
                         versym,
+                        object.object.endian,
                     )?;
                 }
             }
@@ -2743,7 +3512,7 @@ fn write_copy_relocation_dynamic_symbol_definition(
         "Tried to write copy relocation for symbol without COPY_RELOCATION flag"
     );
     let sym_index = sym_def.symbol_id.to_input(object.symbol_id_range);
-    let sym = object.object.symbol(sym_index)?;
+    let sym = &object.object.symbol(sym_index)?;
     let name = sym_def.name;
     let shndx = layout
         .output_sections
@@ -2770,7 +3539,7 @@ fn write_regular_object_dynamic_symbol_definition(
     dynamic_symbol_writer: &mut SymbolTableWriter,
 ) -> Result {
     let sym_index = sym_def.symbol_id.to_input(object.symbol_id_range);
-    let sym = object.object.symbol(sym_index)?;
+    let sym = &object.object.symbol(sym_index)?;
     let name = sym_def.name;
     if let Some(section_index) = object.object.symbol_section(sym, sym_index)? {
         let SectionSlot::Loaded(section) = &object.sections[section_index.0] else {
@@ -2793,7 +3562,7 @@ fn write_regular_object_dynamic_symbol_definition(
             symbol_value -= tls_start_address;
         }
         dynamic_symbol_writer
-            .copy_symbol(sym, name, output_section_id, symbol_value)
+            .copy_symbol(sym, sym.is_local(), name, output_section_id, symbol_value)
             .with_context(|| {
                 format!("Failed to copy dynamic {}", layout.symbol_debug(symbol_id))
             })?;
@@ -2825,6 +3594,19 @@ fn write_internal_symbols(
         };
 
         let symbol_name = layout.symbol_db.symbol_name(symbol_id)?;
+        if !layout.symbol_db.is_retained_in_symtab(symbol_name.bytes()) {
+            continue;
+        }
+
+        // A __start_ / __stop_ symbol whose section was discarded by `-z start-stop-gc`.
+        if matches!(def_info, InternalSymDefInfo::Undefined) && !symbol_id.is_undefined() {
+            let mut entry = symbol_writer
+                .define_symbol(false, 0, 0, 0, symbol_name.bytes())
+                .with_context(|| format!("Failed to write {}", layout.symbol_debug(symbol_id)))?;
+            entry.set_st_info(object::elf::STB_WEAK, object::elf::STT_NOTYPE);
+            continue;
+        }
+
         let mut shndx = def_info
             .section_id()
             .map(|section_id| {
@@ -2849,7 +3631,7 @@ fn write_internal_symbols(
         }
 
         let address = resolution.value();
-        let entry = symbol_writer
+        let mut entry = symbol_writer
             .define_symbol(false, shndx, address, 0, symbol_name.bytes())
             .with_context(|| format!("Failed to write {}", layout.symbol_debug(symbol_id)))?;
 
@@ -2872,11 +3654,14 @@ fn write_eh_frame_hdr(table_writer: &mut TableWriter, layout: &Layout) -> Result
 
     header.frame_pointer_encoding =
         elf::ExceptionHeaderFormat::I32 as u8 | elf::ExceptionHeaderApplication::Relative as u8;
-    header.frame_pointer = eh_frame_ptr(layout)?;
+    header.frame_pointer = layout.args().endian.write_i32(eh_frame_ptr(layout)?);
 
     header.count_encoding =
         elf::ExceptionHeaderFormat::U32 as u8 | elf::ExceptionHeaderApplication::Absolute as u8;
-    header.entry_count = eh_frame_hdr_entry_count(layout)?;
+    header.entry_count = layout
+        .args()
+        .endian
+        .write_u32(eh_frame_hdr_entry_count(layout)?);
 
     Ok(())
 }
@@ -2945,8 +3730,8 @@ const EPILOGUE_DYNAMIC_ENTRY_WRITERS: &[DynamicEntryWriter] = &[
     DynamicEntryWriter::new(object::elf::DT_SYMTAB, |inputs| {
         inputs.vma_of_section(output_section_id::DYNSYM)
     }),
-    DynamicEntryWriter::new(object::elf::DT_SYMENT, |_inputs| {
-        size_of::<elf::SymtabEntry>() as u64
+    DynamicEntryWriter::new(object::elf::DT_SYMENT, |inputs| {
+        inputs.args.elf_class().symtab_entry_size()
     }),
     DynamicEntryWriter::optional(
         object::elf::DT_VERDEF,
@@ -3025,7 +3810,10 @@ const EPILOGUE_DYNAMIC_ENTRY_WRITERS: &[DynamicEntryWriter] = &[
     DynamicEntryWriter::optional(
         object::elf::DT_PLTREL,
         |inputs| inputs.section_part_layouts.get(part_id::RELA_PLT).mem_size > 0,
-        |_| object::elf::DT_RELA.into(),
+        |inputs| match inputs.args.arch.relocation_format() {
+            RelocationFormat::Rel => object::elf::DT_REL.into(),
+            RelocationFormat::Rela => object::elf::DT_RELA.into(),
+        },
     ),
     DynamicEntryWriter::optional(
         object::elf::DT_PLTRELSZ,
@@ -3034,13 +3822,16 @@ const EPILOGUE_DYNAMIC_ENTRY_WRITERS: &[DynamicEntryWriter] = &[
     ),
     DynamicEntryWriter::optional(object::elf::DT_RELA, has_rela_dyn, |inputs| {
         inputs.vma_of_section(output_section_id::RELA_DYN)
-    }),
+    })
+    .with_rel_tag(object::elf::DT_REL),
     DynamicEntryWriter::optional(object::elf::DT_RELASZ, has_rela_dyn, |inputs| {
         inputs.size_of_section(output_section_id::RELA_DYN)
-    }),
-    DynamicEntryWriter::optional(object::elf::DT_RELAENT, has_rela_dyn, |_inputs| {
-        elf::RELA_ENTRY_SIZE
-    }),
+    })
+    .with_rel_tag(object::elf::DT_RELSZ),
+    DynamicEntryWriter::optional(object::elf::DT_RELAENT, has_rela_dyn, |inputs| {
+        inputs.args.rela_entry_size()
+    })
+    .with_rel_tag(object::elf::DT_RELENT),
     // Note, rela-count is just the count of the relative relocations and doesn't include any
     // glob-dat relocations. This is as opposed to rela-size, which includes both.
     DynamicEntryWriter::new(object::elf::DT_RELACOUNT, |inputs| {
@@ -3048,11 +3839,17 @@ const EPILOGUE_DYNAMIC_ENTRY_WRITERS: &[DynamicEntryWriter] = &[
             .section_part_layouts
             .get(part_id::RELA_DYN_RELATIVE)
             .mem_size
-            / size_of::<elf::Rela>() as u64
-    }),
+            / inputs.args.rela_entry_size()
+    })
+    .with_rel_tag(object::elf::DT_RELCOUNT),
     DynamicEntryWriter::new(object::elf::DT_GNU_HASH, |inputs| {
         inputs.vma_of_section(output_section_id::GNU_HASH)
     }),
+    DynamicEntryWriter::optional(
+        object::elf::DT_TEXTREL,
+        |inputs| inputs.has_text_relocations,
+        |_inputs| 0,
+    ),
     DynamicEntryWriter::optional(
         object::elf::DT_FLAGS,
         |inputs| inputs.dt_flags() != 0,
@@ -3068,6 +3865,10 @@ const EPILOGUE_DYNAMIC_ENTRY_WRITERS: &[DynamicEntryWriter] = &[
 
 struct DynamicEntryWriter {
     tag: u32,
+
+    /// The tag to use instead of `tag` when the architecture uses REL-format relocations.
+    rel_tag: Option<u32>,
+
     is_present_cb: fn(&DynamicEntryInputs) -> bool,
     cb: fn(&DynamicEntryInputs) -> u64,
 }
@@ -3075,6 +3876,7 @@ struct DynamicEntryWriter {
 struct DynamicEntryInputs<'layout> {
     args: &'layout Args,
     has_static_tls: bool,
+    has_text_relocations: bool,
     section_layouts: &'layout OutputSectionMap<OutputRecordLayout>,
     section_part_layouts: &'layout OutputSectionPartMap<OutputRecordLayout>,
     non_addressable_counts: NonAddressableCounts,
@@ -3082,16 +3884,25 @@ struct DynamicEntryInputs<'layout> {
 
 impl DynamicEntryInputs<'_> {
     fn dt_flags(&self) -> u64 {
-        let mut flags = 0;
+        let mut flags = self.args.dt_flags;
         flags |= object::elf::DF_BIND_NOW;
         if !self.args.output_kind().is_executable() && self.has_static_tls {
             flags |= object::elf::DF_STATIC_TLS;
         }
+        if self.has_text_relocations {
+            flags |= object::elf::DF_TEXTREL;
+        }
         u64::from(flags)
     }
 
     fn dt_flags_1(&self) -> u64 {
-        let mut flags = 0;
+        let mut flags = self.args.dt_flags_1;
+        if self.args.output_kind().is_executable() {
+            // Like GNU ld, ignore flags that only affect how a shared object gets loaded.
+            flags &= !(object::elf::DF_1_NODELETE
+                | object::elf::DF_1_NOOPEN
+                | object::elf::DF_1_INITFIRST);
+        }
         flags |= object::elf::DF_1_NOW;
         if self.args.output_kind().is_executable() && self.args.is_relocatable() {
             flags |= object::elf::DF_1_PIE;
@@ -3116,6 +3927,7 @@ impl<'data> DynamicEntryWriter {
     const fn new(tag: u32, cb: fn(&DynamicEntryInputs) -> u64) -> DynamicEntryWriter {
         DynamicEntryWriter {
             tag,
+            rel_tag: None,
             is_present_cb: |_| true,
             cb,
         }
@@ -3128,11 +3940,17 @@ impl<'data> DynamicEntryWriter {
     ) -> DynamicEntryWriter {
         DynamicEntryWriter {
             tag,
+            rel_tag: None,
             is_present_cb,
             cb,
         }
     }
 
+    const fn with_rel_tag(mut self, rel_tag: u32) -> DynamicEntryWriter {
+        self.rel_tag = Some(rel_tag);
+        self
+    }
+
     fn is_present(&self, inputs: &DynamicEntryInputs<'data>) -> bool {
         (self.is_present_cb)(inputs)
     }
@@ -3142,35 +3960,58 @@ impl<'data> DynamicEntryWriter {
             return Ok(());
         }
         let value = (self.cb)(inputs);
-        out.write(self.tag, value)
+        let tag = match (inputs.args.arch.relocation_format(), self.rel_tag) {
+            (RelocationFormat::Rel, Some(rel_tag)) => rel_tag,
+            _ => self.tag,
+        };
+        out.write(tag, value)
     }
 }
 
 struct DynamicEntriesWriter<'out> {
-    out: &'out mut [DynamicEntry],
+    out: &'out mut [u8],
+    elf_class: ElfClass,
+    endian: Endianness,
 }
 
 impl DynamicEntriesWriter<'_> {
-    fn new(buffer: &mut [u8]) -> DynamicEntriesWriter {
+    fn new(buffer: &mut [u8], elf_class: ElfClass, endian: Endianness) -> DynamicEntriesWriter {
         DynamicEntriesWriter {
-            out: slice_from_all_bytes_mut(buffer),
+            out: buffer,
+            elf_class,
+            endian,
         }
     }
 
     fn write(&mut self, tag: u32, value: u64) -> Result {
-        let entry = crate::slice::take_first_mut(&mut self.out)
-            .ok_or_else(|| insufficient_allocation(".dynamic"))?;
-        let e = LittleEndian;
-        entry.d_tag.set(e, u64::from(tag));
-        entry.d_val.set(e, value);
+        let entry =
+            try_slice_take_prefix_mut(&mut self.out, self.elf_class.dynamic_entry_size() as usize)
+                .ok_or_else(|| insufficient_allocation(".dynamic"))?;
+        let e = self.endian;
+        match self.elf_class {
+            ElfClass::Elf32 => {
+                let entry: &mut object::elf::Dyn32<Endianness> = from_bytes_mut(entry)
+                    .map_err(|_| anyhow!("Invalid .dynamic allocation"))?
+                    .0;
+                entry.d_tag.set(e, tag);
+                entry.d_val.set(e, value as u32);
+            }
+            ElfClass::Elf64 => {
+                let entry: &mut DynamicEntry = from_bytes_mut(entry)
+                    .map_err(|_| anyhow!("Invalid .dynamic allocation"))?
+                    .0;
+                entry.d_tag.set(e, u64::from(tag));
+                entry.d_val.set(e, value);
+            }
+        }
         Ok(())
     }
 }
 
-fn write_section_headers(out: &mut [u8], layout: &Layout) {
-    let entries: &mut [SectionHeader] = slice_from_all_bytes_mut(out);
+fn write_section_headers(out: &mut [u8], layout: &Layout) -> Result {
+    let elf_class = layout.args().elf_class();
     let output_sections = &layout.output_sections;
-    let mut entries = entries.iter_mut();
+    let mut entries = out.chunks_exact_mut(usize::from(elf_class.section_header_size()));
     let mut name_offset = 0;
     let info_inputs = layout.info_inputs();
 
@@ -3180,13 +4021,14 @@ fn write_section_headers(out: &mut [u8], layout: &Layout) {
         };
         let section_type = output_sections.section_type(section_id);
         let section_layout = layout.section_layouts.get(section_id);
-        if output_sections
-            .output_index_of_section(section_id)
-            .is_none()
-        {
+        if !output_sections.has_section_header(section_id) {
             continue;
         }
-        let entsize = section_id.element_size();
+        let entsize = if section_id == output_section_id::PLT_GOT {
+            layout.plt_protection.entry_size()
+        } else {
+            section_id.element_size(layout.args())
+        };
         let size;
         let alignment;
         if section_type == sht::NULL {
@@ -3199,38 +4041,76 @@ fn write_section_headers(out: &mut [u8], layout: &Layout) {
         let link = output_section_id::link_ids(section_id)
             .iter()
             .find_map(|link_id| output_sections.output_index_of_section(*link_id))
+            .or_else(|| link_order_output_link(layout, section_id))
             .unwrap_or(0);
-        let entry = entries.next().unwrap();
-        let e = LittleEndian;
-        entry.sh_name.set(e, name_offset);
-        entry.sh_type.set(e, section_type.raw());
-        // TODO: Sections are always uncompressed and the output compression is not supported yet.
-        entry.sh_flags.set(
-            e,
-            output_sections
-                .section_flags(section_id)
-                .without(shf::COMPRESSED)
-                .raw(),
-        );
-        entry.sh_addr.set(e, section_layout.mem_offset);
-        entry.sh_offset.set(e, section_layout.file_offset as u64);
-        entry.sh_size.set(e, size);
-        entry.sh_link.set(e, link.into());
-        entry.sh_info.set(e, section_id.info(&info_inputs));
-        entry.sh_addralign.set(e, alignment);
-        entry.sh_entsize.set(e, entsize);
+        let e = layout.args().endian;
+        let entry = SectionHeader {
+            sh_name: U32::new(e, name_offset),
+            sh_type: U32::new(e, section_type.raw()),
+            // TODO: Sections are always uncompressed and the output compression is not supported
+            // yet.
+            sh_flags: U64::new(
+                e,
+                output_sections
+                    .section_flags(section_id)
+                    .without(shf::COMPRESSED)
+                    .raw(),
+            ),
+            sh_addr: U64::new(e, section_layout.mem_offset),
+            sh_offset: U64::new(e, section_layout.file_offset as u64),
+            sh_size: U64::new(e, size),
+            sh_link: U32::new(e, link.into()),
+            sh_info: U32::new(e, section_id.info(&info_inputs)),
+            sh_addralign: U64::new(e, alignment),
+            sh_entsize: U64::new(e, entsize),
+        };
+        elf32::write_section_header(&entry, elf_class, e, entries.next().unwrap())?;
         name_offset += layout.output_sections.name(section_id).len() as u32 + 1;
     }
     assert!(
         entries.next().is_none(),
         "Allocated section entries that weren't used"
     );
+    Ok(())
+}
+
+/// Returns the output index of the section that SHF_LINK_ORDER section `section_id` should link to.
+/// Like GNU ld, we use the output section of whichever input section the first link-order input
+/// section links to.
+fn link_order_output_link(layout: &Layout, section_id: OutputSectionId) -> Option<u16> {
+    if !layout
+        .output_sections
+        .section_flags(section_id)
+        .contains(shf::LINK_ORDER)
+    {
+        return None;
+    }
+    layout
+        .group_layouts
+        .iter()
+        .flat_map(|group| &group.files)
+        .filter_map(|file| match file {
+            FileLayout::Object(obj) => Some(obj),
+            _ => None,
+        })
+        .flat_map(|obj| {
+            obj.sections.iter().filter_map(|slot| match slot {
+                SectionSlot::Loaded(sec) if sec.output_section_id() == section_id => {
+                    match obj.sections.get(sec.link_order_target?.0)? {
+                        SectionSlot::Loaded(target) => Some(target.output_section_id()),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            })
+        })
+        .find_map(|target_id| layout.output_sections.output_index_of_section(target_id))
 }
 
 fn write_section_header_strings(mut out: &mut [u8], sections: &OutputSections) {
     for event in sections.sections_and_segments_events() {
         if let OrderEvent::Section(id) = event {
-            if sections.output_index_of_section(id).is_some() {
+            if sections.has_section_header(id) {
                 let name = sections.name(id);
                 let name_out = crate::slice::slice_take_prefix_mut(&mut out, name.len() + 1);
                 name_out[..name.len()].copy_from_slice(name.bytes());
@@ -3241,19 +4121,27 @@ fn write_section_header_strings(mut out: &mut [u8], sections: &OutputSections) {
 }
 
 struct ProgramHeaderWriter<'out> {
-    headers: &'out mut [ProgramHeader],
+    headers: &'out mut [u8],
+    elf_class: ElfClass,
+    endian: Endianness,
 }
 
 impl<'out> ProgramHeaderWriter<'out> {
-    fn new(bytes: &'out mut [u8]) -> Self {
+    fn new(bytes: &'out mut [u8], elf_class: ElfClass, endian: Endianness) -> Self {
         Self {
-            headers: slice_from_all_bytes_mut(bytes),
+            headers: bytes,
+            elf_class,
+            endian,
         }
     }
 
-    fn take_header(&mut self) -> Result<&mut ProgramHeader> {
-        crate::slice::take_first_mut(&mut self.headers)
-            .ok_or_else(|| anyhow!("Insufficient header slots"))
+    fn write_header(&mut self, header: &ProgramHeader) -> Result {
+        let out = try_slice_take_prefix_mut(
+            &mut self.headers,
+            usize::from(self.elf_class.program_header_size()),
+        )
+        .ok_or_else(|| anyhow!("Insufficient header slots"))?;
+        elf32::write_program_header(header, self.elf_class, self.endian, out)
     }
 }
 
@@ -3286,12 +4174,12 @@ impl<'data> DynamicLayout<'data> {
 
         self.write_copy_relocations::<A>(table_writer, layout)?;
 
-        for ((symbol_id, resolution), symbol) in layout
+        for ((symbol_id, resolution), (_, symbol)) in layout
             .resolutions_in_range(self.symbol_id_range)
-            .zip(self.object.symbols.iter())
+            .zip(self.object.symbols())
         {
             if let Some(res) = resolution {
-                let name = self.object.symbol_name(symbol)?;
+                let name = self.object.symbol_name(&symbol)?;
 
                 if res
                     .resolution_flags
@@ -3301,7 +4189,8 @@ impl<'data> DynamicLayout<'data> {
                     // written by the epilogue not by us. However, we do need to write a regular
                     // symtab entry.
                     table_writer.debug_symbol_writer.copy_symbol(
-                        symbol,
+                        &symbol,
+                        symbol.is_local(),
                         name,
                         output_section_id::BSS,
                         res.value(),
@@ -3309,7 +4198,7 @@ impl<'data> DynamicLayout<'data> {
                 } else {
                     table_writer
                         .dynsym_writer
-                        .copy_symbol_shndx(symbol, name, 0, 0)?;
+                        .copy_symbol_shndx(&symbol, name, 0, 0)?;
 
                     if let Some(versym) = table_writer.version_writer.versym.as_mut() {
                         write_symbol_version(
@@ -3317,6 +4206,7 @@ impl<'data> DynamicLayout<'data> {
                             self.symbol_id_range.id_to_offset(symbol_id),
                             &self.version_mapping,
                             versym,
+                            self.object.endian,
                         )?;
                     }
                 }
@@ -3332,12 +4222,8 @@ impl<'data> DynamicLayout<'data> {
 
         if let Some(verneed_info) = &self.verneed_info {
             let mut verdefs = verneed_info.defs.clone();
-            let e = LittleEndian;
-            let strings = self.object.sections.strings(
-                e,
-                self.object.data,
-                verneed_info.string_table_index,
-            )?;
+            let e = self.object.endian;
+            let strings = self.object.strings(verneed_info.string_table_index)?;
             let ver_need = table_writer.version_writer.take_verneed()?;
             let next_verneed_offset = if self.is_last_verneed {
                 0
@@ -3454,6 +4340,7 @@ fn write_symbol_version(
     local_symbol_index: usize,
     version_mapping: &[u16],
     versym_out: &mut &mut [Versym],
+    e: Endianness,
 ) -> Result {
     let version_out =
         crate::slice::take_first_mut(versym_out).context("Insufficient .gnu.version allocation")?;
@@ -3461,14 +4348,14 @@ fn write_symbol_version(
         versym_in
             .get(local_symbol_index)
             .map_or(object::elf::VER_NDX_GLOBAL, |versym| {
-                let input_version = versym.0.get(LittleEndian) & object::elf::VERSYM_VERSION;
+                let input_version = versym.0.get(e) & object::elf::VERSYM_VERSION;
                 if input_version <= object::elf::VER_NDX_GLOBAL {
                     input_version
                 } else {
                     version_mapping[usize::from(input_version) - 1]
                 }
             });
-    version_out.0.set(LittleEndian, output_version);
+    version_out.0.set(e, output_version);
     Ok(())
 }
 
@@ -3523,7 +4410,7 @@ impl Display for ResFlagsDisplay<'_> {
 
 pub(crate) fn verify_resolution_allocation(
     output_sections: &OutputSections,
-    output_kind: OutputKind,
+    args: &Args,
     mem_sizes: &OutputSectionPartMap<u64>,
     resolution: &Resolution,
 ) -> Result {
@@ -3544,11 +4431,26 @@ pub(crate) fn verify_resolution_allocation(
         crate::slice::slice_take_prefix_mut(&mut all_mem, size as usize)
     });
 
-    let dynsym_writer = SymbolTableWriter::new_dynamic(0, &mut buffers, output_sections);
-    let debug_symbol_writer = SymbolTableWriter::new(0, &mut buffers, output_sections);
+    let dynsym_writer = SymbolTableWriter::new_dynamic(
+        0,
+        &mut buffers,
+        output_sections,
+        args.elf_class(),
+        args.endian,
+    );
+    let debug_symbol_writer = SymbolTableWriter::new(
+        0,
+        &mut buffers,
+        output_sections,
+        args.elf_class(),
+        args.endian,
+    );
     let mut table_writer = TableWriter::new(
-        output_kind,
+        args,
+        PltProtection::default(),
+        None,
         0..100,
+        100,
         &mut buffers,
         dynsym_writer,
         debug_symbol_writer,
@@ -22,12 +22,6 @@ macro_rules! debug_assert_bail {
     };
 }
 
-/// Prints a warning. By using our own macro for this, it'll be easier to find places that issue
-/// warnings if we want to say have a flag to suppress them.
-pub(crate) fn warning(message: &str) {
-    println!("WARNING: wild: {message}");
-}
-
 impl Display for AlreadyInitialised {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "Attempted to initialise global state more than once")
@@ -1,9 +1,11 @@
 //! Code for identifying what sort of file we're dealing with based on the bytes of the file.
 
 use crate::elf;
+use crate::elf32;
 use crate::error::Result;
+use anyhow::anyhow;
 use anyhow::bail;
-use object::LittleEndian;
+use object::Endianness;
 use object::read::elf::FileHeader;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -22,32 +24,10 @@ impl FileKind {
         } else if bytes.starts_with(&object::archive::THIN_MAGIC) {
             Ok(FileKind::ThinArchive)
         } else if bytes.starts_with(&object::elf::ELFMAG) {
-            const HEADER_LEN: usize = size_of::<elf::FileHeader>();
-            if bytes.len() < HEADER_LEN {
-                bail!("Invalid ELF file");
-            }
-            let header: &elf::FileHeader = object::from_bytes(&bytes[..HEADER_LEN]).unwrap().0;
-            if header.e_ident.class != object::elf::ELFCLASS64 {
-                bail!("Only 64 bit ELF is currently supported");
-            }
-            if header.e_ident.data != object::elf::ELFDATA2LSB {
-                bail!("Only little endian is currently supported");
-            }
-
-            let sections = header.sections(LittleEndian, bytes)?;
-            if sections.iter().any(|sec| {
-                sections
-                    .section_name(LittleEndian, sec)
-                    .map(|section_name| section_name.starts_with(b".gnu.lto_.symtab"))
-                    .unwrap_or(false)
-            }) {
-                bail!("GCC IR (LTO mode) is not supported yet");
-            }
-
-            match header.e_type.get(LittleEndian) {
-                object::elf::ET_REL => Ok(FileKind::ElfObject),
-                object::elf::ET_DYN => Ok(FileKind::ElfDynamic),
-                t => bail!("Unsupported ELF kind {t}"),
+            if elf32::is_elf32(bytes) {
+                identify_elf::<object::elf::FileHeader32<Endianness>>(bytes)
+            } else {
+                identify_elf::<elf::FileHeader>(bytes)
             }
         } else if bytes.is_ascii() {
             Ok(FileKind::Text)
@@ -58,3 +38,24 @@ impl FileKind {
         }
     }
 }
+
+fn identify_elf<H: FileHeader<Endian = Endianness>>(bytes: &[u8]) -> Result<FileKind> {
+    let header = H::parse(bytes).map_err(|_| anyhow!("Invalid ELF file"))?;
+    let e = header.endian()?;
+
+    let sections = header.sections(e, bytes)?;
+    if sections.iter().any(|sec| {
+        sections
+            .section_name(e, sec)
+            .map(|section_name| section_name.starts_with(b".gnu.lto_.symtab"))
+            .unwrap_or(false)
+    }) {
+        bail!("GCC IR (LTO mode) is not supported yet");
+    }
+
+    match header.e_type(e) {
+        object::elf::ET_REL => Ok(FileKind::ElfObject),
+        object::elf::ET_DYN => Ok(FileKind::ElfDynamic),
+        t => bail!("Unsupported ELF kind {t}"),
+    }
+}
@@ -0,0 +1,407 @@
+//! Contains i386-specific code. Like on x86-64, we perform relaxations that avoid going via the GOT
+//! where possible and relax TLS access models in executables.
+
+use crate::arch::Arch;
+use crate::arch::PltProtection;
+use crate::args::OutputKind;
+use crate::elf::PLT_ENTRY_SIZE;
+use crate::resolution::ValueFlags;
+use anyhow::Result;
+use anyhow::anyhow;
+use linker_utils::elf::AllowedRange;
+use linker_utils::elf::DynamicRelocationKind;
+use linker_utils::elf::RelocationKind;
+use linker_utils::elf::RelocationKindInfo;
+use linker_utils::elf::RelocationSize;
+use linker_utils::elf::SectionFlags;
+use linker_utils::elf::i386_rel_type_to_string;
+use linker_utils::elf::shf;
+use linker_utils::i386::RelaxationKind;
+use linker_utils::relaxation::RelocationModifier;
+
+pub(crate) struct I386;
+
+/// PLT entry for position-dependent outputs, where we can use the absolute address of the GOT
+/// entry.
+const PLT_ENTRY_TEMPLATE: &[u8] = &[
+    0xf3, 0x0f, 0x1e, 0xfb, // endbr32
+    0xff, 0x25, 0x0, 0x0, 0x0, 0x0, // jmp *{GOT address}
+    0x66, 0x0f, 0x1f, 0x44, 0x0, 0x0, // nopw 0x0(%eax,%eax,1)
+];
+
+/// PLT entry for position-independent outputs. The psABI requires that callers of PLT entries in
+/// position-independent code set %ebx to the address of `_GLOBAL_OFFSET_TABLE_`.
+const PIC_PLT_ENTRY_TEMPLATE: &[u8] = &[
+    0xf3, 0x0f, 0x1e, 0xfb, // endbr32
+    0xff, 0xa3, 0x0, 0x0, 0x0, 0x0, // jmp *{GOT offset}(%ebx)
+    0x66, 0x0f, 0x1f, 0x44, 0x0, 0x0, // nopw 0x0(%eax,%eax,1)
+];
+
+const _ASSERTS: () = {
+    assert!(PLT_ENTRY_TEMPLATE.len() as u64 == PLT_ENTRY_SIZE);
+    assert!(PIC_PLT_ENTRY_TEMPLATE.len() as u64 == PLT_ENTRY_SIZE);
+};
+
+impl crate::arch::Arch for I386 {
+    type Relaxation = Relaxation;
+
+    fn elf_header_arch_magic() -> u16 {
+        object::elf::EM_386
+    }
+
+    #[inline(always)]
+    fn relocation_from_raw(r_type: u32) -> Result<RelocationKindInfo> {
+        let (kind, size) =
+            linker_utils::i386::relocation_kind_and_size(r_type).ok_or_else(|| {
+                anyhow!(
+                    "Unsupported relocation type {}",
+                    Self::rel_type_to_string(r_type)
+                )
+            })?;
+        Ok(RelocationKindInfo {
+            kind,
+            size: RelocationSize::ByteSize(size),
+            mask: None,
+            range: AllowedRange::no_check(),
+            alignment: 1,
+        })
+    }
+
+    fn tls_dtv_offset() -> u64 {
+        0
+    }
+
+    fn get_dynamic_relocation_type(relocation: DynamicRelocationKind) -> u32 {
+        relocation.i386_r_type()
+    }
+
+    fn write_plt_entry(
+        plt_entry: &mut [u8],
+        got_address: u64,
+        _plt_address: u64,
+        got_base: Option<u64>,
+        _protection: PltProtection,
+    ) -> crate::error::Result {
+        let operand = if let Some(got_base) = got_base {
+            plt_entry.copy_from_slice(PIC_PLT_ENTRY_TEMPLATE);
+            got_address.wrapping_sub(got_base) as u32
+        } else {
+            plt_entry.copy_from_slice(PLT_ENTRY_TEMPLATE);
+            u32::try_from(got_address).map_err(|_| anyhow!("GOT address doesn't fit in 32 bits"))?
+        };
+        plt_entry[6..10].copy_from_slice(&operand.to_le_bytes());
+        Ok(())
+    }
+
+    fn rel_type_to_string(r_type: u32) -> std::borrow::Cow<'static, str> {
+        i386_rel_type_to_string(r_type)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Relaxation {
+    kind: RelaxationKind,
+    rel_info: RelocationKindInfo,
+}
+
+impl crate::arch::Relaxation for Relaxation {
+    #[inline(always)]
+    fn new(
+        relocation_kind: u32,
+        section_bytes: &[u8],
+        offset_in_section: u64,
+        value_flags: ValueFlags,
+        output_kind: OutputKind,
+        section_flags: SectionFlags,
+        _non_zero_address: bool,
+    ) -> Option<Self> {
+        #[allow(clippy::unnecessary_wraps)]
+        #[inline(always)]
+        fn create(kind: RelaxationKind, new_r_type: u32) -> Option<Relaxation> {
+            // This only fails for relocation types that we don't support and if we relax to a type
+            // we don't support, then that's a bug.
+            let rel_info = I386::relocation_from_raw(new_r_type).unwrap();
+            Some(Relaxation { kind, rel_info })
+        }
+
+        let is_known_address = value_flags.contains(ValueFlags::ADDRESS);
+        let is_absolute = value_flags.contains(ValueFlags::ABSOLUTE)
+            && !value_flags.contains(ValueFlags::DYNAMIC);
+        let non_relocatable = !output_kind.is_relocatable();
+        let is_absolute_address = is_known_address && non_relocatable;
+        let can_bypass_got = value_flags.contains(ValueFlags::CAN_BYPASS_GOT);
+
+        // IFuncs cannot be referenced directly. They always need to go via the GOT.
+        if value_flags.contains(ValueFlags::IFUNC) {
+            return match relocation_kind {
+                object::elf::R_386_PC32 => create(RelaxationKind::NoOp, object::elf::R_386_PLT32),
+                _ => None,
+            };
+        }
+
+        // All relaxations below only apply to executable code, so we shouldn't attempt them if a
+        // relocation is in a non-executable section.
+        if !section_flags.contains(shf::EXECINSTR) {
+            return None;
+        }
+
+        let offset = offset_in_section as usize;
+        match relocation_kind {
+            object::elf::R_386_GOT32 | object::elf::R_386_GOT32X => {
+                let opcode = *section_bytes.get(offset.checked_sub(2)?)?;
+                let mod_rm = section_bytes[offset - 1];
+
+                // Without a base register, the instruction references the absolute address of the
+                // GOT entry rather than an offset from the GOT base. This is only permitted in
+                // position-dependent code.
+                if mod_rm & 0xc7 == 0x05 {
+                    if relocation_kind == object::elf::R_386_GOT32X
+                        && (is_absolute || is_absolute_address)
+                    {
+                        match opcode {
+                            // mov x, reg
+                            0x8b => {
+                                return create(
+                                    RelaxationKind::MovIndirectToAbsolute,
+                                    object::elf::R_386_32,
+                                );
+                            }
+                            // add x, reg
+                            0x03 => {
+                                return create(
+                                    RelaxationKind::AddIndirectToAbsolute,
+                                    object::elf::R_386_32,
+                                );
+                            }
+                            _ => {}
+                        }
+                    }
+                    return Some(Relaxation {
+                        kind: RelaxationKind::NoOp,
+                        rel_info: RelocationKindInfo {
+                            kind: RelocationKind::Got,
+                            ..I386::relocation_from_raw(relocation_kind).unwrap()
+                        },
+                    });
+                }
+
+                if relocation_kind == object::elf::R_386_GOT32X
+                    && (is_absolute || is_absolute_address)
+                {
+                    match opcode {
+                        // mov x@GOT(%reg1), %reg2
+                        0x8b => {
+                            return create(
+                                RelaxationKind::MovIndirectToAbsolute,
+                                object::elf::R_386_32,
+                            );
+                        }
+                        // add x@GOT(%reg1), %reg2
+                        0x03 => {
+                            return create(
+                                RelaxationKind::AddIndirectToAbsolute,
+                                object::elf::R_386_32,
+                            );
+                        }
+                        _ => {}
+                    }
+                }
+                if can_bypass_got {
+                    match (opcode, mod_rm & 0x38) {
+                        // mov x@GOT(%reg1), %reg2
+                        (0x8b, _) => {
+                            return create(
+                                RelaxationKind::MovIndirectToLea,
+                                object::elf::R_386_GOTOFF,
+                            );
+                        }
+                        // call *x@GOT(%reg)
+                        (0xff, 0x10) if relocation_kind == object::elf::R_386_GOT32X => {
+                            return create(
+                                RelaxationKind::CallIndirectToRelative,
+                                object::elf::R_386_PC32,
+                            );
+                        }
+                        // jmp *x@GOT(%reg)
+                        (0xff, 0x20) if relocation_kind == object::elf::R_386_GOT32X => {
+                            return create(
+                                RelaxationKind::JmpIndirectToRelative,
+                                object::elf::R_386_PC32,
+                            );
+                        }
+                        _ => {}
+                    }
+                }
+                return None;
+            }
+            object::elf::R_386_PLT32 if can_bypass_got => {
+                return create(RelaxationKind::NoOp, object::elf::R_386_PC32);
+            }
+            object::elf::R_386_TLS_GD if output_kind.is_executable() => {
+                let got_register = tls_gd_got_register(section_bytes, offset)?;
+                if can_bypass_got {
+                    return create(RelaxationKind::TlsGdToLocalExec, object::elf::R_386_TLS_LE);
+                }
+                return create(
+                    RelaxationKind::TlsGdToInitialExec(got_register),
+                    object::elf::R_386_TLS_GOTIE,
+                );
+            }
+            object::elf::R_386_TLS_LDM if output_kind.is_executable() => {
+                // lea x@tlsldm(%ebx),%eax
+                if section_bytes.get(offset.checked_sub(2)?..offset)? == [0x8d, 0x83] {
+                    return create(RelaxationKind::TlsLdToLocalExec, object::elf::R_386_NONE);
+                }
+            }
+            object::elf::R_386_TLS_IE if can_bypass_got => {
+                let opcode = *section_bytes.get(offset.checked_sub(1)?)?;
+                if opcode == 0xa1 {
+                    // mov x@indntpoff, %eax
+                    return create(
+                        RelaxationKind::MovEaxIndirectToAbsolute,
+                        object::elf::R_386_TLS_LE,
+                    );
+                }
+                return create_tls_ie_to_local_exec(section_bytes, offset);
+            }
+            object::elf::R_386_TLS_GOTIE if can_bypass_got => {
+                return create_tls_ie_to_local_exec(section_bytes, offset);
+            }
+            object::elf::R_386_TLS_GOTDESC
+                if can_bypass_got && output_kind.is_static_executable() =>
+            {
+                // lea x@tlsdesc(%reg),%eax
+                if *section_bytes.get(offset.checked_sub(2)?)? == 0x8d {
+                    return create(
+                        RelaxationKind::TlsDescToLocalExec,
+                        object::elf::R_386_TLS_LE,
+                    );
+                }
+            }
+            object::elf::R_386_TLS_GOTDESC if output_kind.is_executable() => {
+                // lea x@tlsdesc(%reg),%eax
+                if *section_bytes.get(offset.checked_sub(2)?)? == 0x8d {
+                    return create(
+                        RelaxationKind::TlsDescToInitialExec,
+                        object::elf::R_386_TLS_GOTIE,
+                    );
+                }
+            }
+            object::elf::R_386_TLS_DESC_CALL if output_kind.is_executable() => {
+                // call *x@tlscall(%eax)
+                if section_bytes.get(offset..offset + 2)? == [0xff, 0x10] {
+                    return create(RelaxationKind::SkipTlsDescCall, object::elf::R_386_NONE);
+                }
+            }
+            _ => return None,
+        }
+        None
+    }
+
+    fn apply(&self, section_bytes: &mut [u8], offset_in_section: &mut u64, addend: &mut i64) {
+        self.kind.apply(section_bytes, offset_in_section, addend);
+    }
+
+    fn rel_info(&self) -> RelocationKindInfo {
+        self.rel_info
+    }
+
+    fn debug_kind(&self) -> impl std::fmt::Debug {
+        &self.kind
+    }
+
+    fn next_modifier(&self) -> RelocationModifier {
+        self.kind.next_modifier()
+    }
+}
+
+/// Relaxes `mov x@gotntpoff(%reg1),%reg2` or `add x@gotntpoff(%reg1),%reg2` (or the equivalent
+/// without a base register) to local exec.
+fn create_tls_ie_to_local_exec(section_bytes: &[u8], offset: usize) -> Option<Relaxation> {
+    let kind = match section_bytes.get(offset.checked_sub(2)?)? {
+        0x8b => RelaxationKind::MovIndirectToAbsolute,
+        0x03 => RelaxationKind::AddIndirectToAbsolute,
+        _ => return None,
+    };
+    Some(Relaxation {
+        kind,
+        rel_info: I386::relocation_from_raw(object::elf::R_386_TLS_LE).unwrap(),
+    })
+}
+
+/// Identifies the register used to address the GOT in a general dynamic TLS sequence. Returns
+/// `None` if the code isn't one of the sequences we know how to relax.
+fn tls_gd_got_register(bytes: &[u8], offset: usize) -> Option<u8> {
+    // lea x@tlsgd(,%ebx,1),%eax
+    // call ___tls_get_addr@PLT
+    if bytes.get(offset.checked_sub(3)?..offset)? == [0x8d, 0x04, 0x1d]
+        && bytes.get(offset + 4) == Some(&0xe8)
+    {
+        return Some(3);
+    }
+
+    // lea x@tlsgd(%reg),%eax
+    // call *___tls_get_addr@GOT(%reg)
+    let mod_rm = *bytes.get(offset - 1)?;
+    if bytes[offset - 2] == 0x8d
+        && mod_rm & 0xf8 == 0x80
+        && mod_rm & 0x7 != 0x4
+        && bytes.get(offset + 4..offset + 6) == Some(&[0xff, 0x90 | (mod_rm & 0x7)])
+    {
+        return Some(mod_rm & 0x7);
+    }
+
+    None
+}
+
+#[test]
+fn test_relaxation() {
+    use crate::arch::Relaxation as _;
+    use crate::args::RelocationModel;
+
+    #[track_caller]
+    fn check(relocation_kind: u32, bytes_in: &[u8], address: &[u8], absolute: &[u8]) {
+        let mut out = bytes_in.to_owned();
+        let mut offset = bytes_in.len() as u64;
+        if let Some(r) = Relaxation::new(
+            relocation_kind,
+            bytes_in,
+            offset,
+            ValueFlags::ADDRESS | ValueFlags::CAN_BYPASS_GOT,
+            OutputKind::StaticExecutable(RelocationModel::Relocatable),
+            shf::EXECINSTR,
+            true,
+        ) {
+            r.apply(&mut out, &mut offset, &mut 0);
+
+            assert_eq!(
+                out, address,
+                "resolved: Expected {address:x?}, got {out:x?}"
+            );
+        }
+        if let Some(r) = Relaxation::new(
+            relocation_kind,
+            bytes_in,
+            offset,
+            ValueFlags::ABSOLUTE,
+            OutputKind::StaticExecutable(RelocationModel::NonRelocatable),
+            shf::EXECINSTR,
+            true,
+        ) {
+            out.copy_from_slice(bytes_in);
+            r.apply(&mut out, &mut offset, &mut 0);
+            assert_eq!(
+                out, absolute,
+                "unresolved: Expected {absolute:x?}, got {out:x?}"
+            );
+        }
+    }
+
+    // mov x@GOT(%ebx),%eax
+    check(
+        object::elf::R_386_GOT32X,
+        &[0x8b, 0x83],
+        &[0x8d, 0x83],
+        &[0xc7, 0xc0],
+    );
+}
@@ -7,8 +7,11 @@ use crate::args::Args;
 use crate::args::Input;
 use crate::args::InputSpec;
 use crate::args::Modifiers;
+use crate::diagnostics::WarningCategory;
 use crate::error::Result;
 use crate::file_kind::FileKind;
+use crate::needed_libs::DynamicDependencyInfo;
+use crate::needed_libs::NeededLibSearchPath;
 use ahash::HashSet;
 use ahash::RandomState;
 use anyhow::Context;
@@ -23,12 +26,23 @@ use std::path::PathBuf;
 pub(crate) struct InputData {
     pub(crate) files: Vec<InputFile>,
     pub(crate) version_script_data: Option<VersionScriptData>,
+    pub(crate) retain_symbols_data: Option<RetainSymbolsData>,
+
+    /// The paths of all files that we read in order to determine our inputs, in the order in which
+    /// we read them. Unlike `files`, this includes linker scripts, thin archives and version
+    /// scripts.
+    pub(crate) read_paths: Vec<PathBuf>,
 }
 
 pub(crate) struct VersionScriptData {
     pub(crate) raw: String,
 }
 
+/// The contents of the file passed to --retain-symbols-file, which lists one symbol per line.
+pub(crate) struct RetainSymbolsData {
+    pub(crate) raw: String,
+}
+
 /// Identifies an input file. IDs start from 0 which is reserved for our prelude file.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub(crate) struct FileId(u32);
@@ -48,6 +62,11 @@ pub(crate) struct InputFile {
     pub(crate) kind: FileKind,
     pub(crate) modifiers: Modifiers,
 
+    /// Whether this is a shared object that wasn't supplied as an input, but was found because it
+    /// was named by a DT_NEEDED entry in some other shared object. Such objects can only be used to
+    /// resolve references from shared objects and are never added to our DT_NEEDED entries.
+    pub(crate) is_indirect_dependency: bool,
+
     data: Option<FileData>,
 }
 
@@ -71,6 +90,21 @@ impl InputFile {
     pub(crate) fn data(&self) -> &[u8] {
         self.data.as_deref().unwrap_or_default()
     }
+
+    /// Returns the name by which a DT_NEEDED entry would refer to this shared object. That's the
+    /// DT_SONAME if there is one, otherwise the filename.
+    fn dependency_name(&self, info: &DynamicDependencyInfo) -> Vec<u8> {
+        info.soname.map_or_else(
+            || {
+                self.filename
+                    .file_name()
+                    .unwrap_or_default()
+                    .as_encoded_bytes()
+                    .to_vec()
+            },
+            |soname| soname.to_vec(),
+        )
+    }
 }
 
 #[derive(Debug)]
@@ -94,20 +128,114 @@ impl InputData {
             .map(|path| read_version_script(path))
             .transpose()?;
 
+        let retain_symbols_data = args
+            .retain_symbols_file
+            .as_ref()
+            .map(|path| read_retain_symbols_file(path))
+            .transpose()?;
+
         let mut filenames = HashSet::with_hasher(RandomState::new());
 
         let mut input_data = Self {
             files,
             version_script_data,
+            retain_symbols_data,
+            read_paths: args
+                .version_script_path
+                .iter()
+                .chain(&args.retain_symbols_file)
+                .cloned()
+                .collect(),
         };
 
         for input in &args.inputs {
             input_data.register_input(input, args.sysroot.as_deref(), args, &mut filenames)?;
         }
 
+        input_data.load_indirect_dependencies(args, &mut filenames)?;
+
         Ok(input_data)
     }
 
+    /// Loads any shared objects named by DT_NEEDED entries of our shared-object inputs, including
+    /// the dependencies of those objects, that weren't already supplied as inputs.
+    fn load_indirect_dependencies(
+        &mut self,
+        args: &Args,
+        filenames: &mut HashSet<PathBuf>,
+    ) -> Result {
+        if !self.files.iter().any(|f| f.kind == FileKind::ElfDynamic) {
+            return Ok(());
+        }
+
+        // Names by which DT_NEEDED entries can refer to shared objects that we've already got.
+        let mut available_names = HashSet::with_hasher(RandomState::new());
+        for file in &self.files {
+            if file.kind == FileKind::ElfDynamic {
+                let info = DynamicDependencyInfo::read(file.data())
+                    .with_context(|| format!("Failed to read `{}`", file.filename.display()))?;
+                available_names.insert(file.dependency_name(&info));
+            }
+        }
+
+        let search_path = NeededLibSearchPath::new(args);
+
+        // Process files in order, including any that we add as we go.
+        let mut index = 0;
+        while index < self.files.len() {
+            let file = &self.files[index];
+            index += 1;
+            if file.kind != FileKind::ElfDynamic {
+                continue;
+            }
+
+            let info = DynamicDependencyInfo::read(file.data())
+                .with_context(|| format!("Failed to read `{}`", file.filename.display()))?;
+            let mut new_files = Vec::new();
+
+            for needed in &info.needed {
+                if !available_names.insert(needed.to_vec()) {
+                    continue;
+                }
+                let needed = String::from_utf8_lossy(needed);
+                let Some((path, data)) = search_path.find(&needed, &file.filename, &info, args)
+                else {
+                    crate::diagnostics::warning(
+                        WarningCategory::MissingDependency,
+                        &format!(
+                            "{needed}, needed by {}, not found (try using -rpath or -rpath-link)",
+                            file.filename.display()
+                        ),
+                    );
+                    continue;
+                };
+                if !filenames.insert(path.clone()) {
+                    continue;
+                }
+                self.read_paths.push(path.clone());
+                let new_file = InputFile {
+                    filename: path,
+                    original_filename: PathBuf::from(needed.as_ref()),
+                    kind: FileKind::ElfDynamic,
+                    modifiers: Modifiers {
+                        as_needed: true,
+                        ..Modifiers::default()
+                    },
+                    is_indirect_dependency: true,
+                    data: Some(data),
+                };
+                let new_info = DynamicDependencyInfo::read(new_file.data())
+                    .with_context(|| format!("Failed to read `{}`", new_file.filename.display()))?;
+                available_names.insert(new_file.dependency_name(&new_info));
+                new_files.push(new_file);
+            }
+
+            self.files.extend(new_files);
+        }
+
+        Ok(())
+    }
+
     fn register_input(
         &mut self,
         input: &Input,
@@ -123,6 +251,7 @@ impl InputData {
         }
 
         let data = FileData::new(absolute_path.as_path(), args.prepopulate_maps)?;
+        self.read_paths.push(absolute_path.clone());
 
         let kind = FileKind::identify_bytes(&data.bytes)?;
 
@@ -148,6 +277,7 @@ impl InputData {
                             let path = entry.identifier(extended_filenames).as_path();
                             let entry_path = parent_path.join(path);
                             let file_data = FileData::new(&entry_path, args.prepopulate_maps)?;
+                            self.read_paths.push(entry_path.clone());
                             self.files.push(InputFile {
                                 filename: entry_path.clone(),
                                 original_filename: entry_path,
@@ -156,6 +286,7 @@ impl InputData {
                                     archive_semantics: true,
                                     ..input.modifiers
                                 },
+                                is_indirect_dependency: false,
                                 data: Some(file_data),
                             });
                         }
@@ -169,6 +300,7 @@ impl InputData {
                     original_filename: paths.original,
                     kind,
                     modifiers: input.modifiers,
+                    is_indirect_dependency: false,
                     data: Some(data),
                 });
             }
@@ -217,6 +349,12 @@ fn read_version_script(path: &Path) -> Result<VersionScriptData> {
     Ok(VersionScriptData { raw: data })
 }
 
+fn read_retain_symbols_file(path: &Path) -> Result<RetainSymbolsData> {
+    let data = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read retain-symbols file `{}`", path.display()))?;
+    Ok(RetainSymbolsData { raw: data })
+}
+
 impl Input {
     fn path(&self, args: &Args) -> Result<InputPath> {
         match &self.spec {
@@ -3,20 +3,26 @@
 //! where in the output file then allocates addresses for each symbol.
 
 use self::elf::GNU_NOTE_NAME;
-use self::elf::GNU_NOTE_PROPERTY_ENTRY_SIZE;
 use self::elf::NoteHeader;
 use self::elf::Symbol;
 use self::output_section_id::InfoInputs;
 use crate::alignment;
 use crate::alignment::Alignment;
 use crate::arch::Arch;
+use crate::arch::Architecture;
+use crate::arch::PltProtection;
 use crate::arch::Relaxation as _;
 use crate::args::Args;
 use crate::args::BuildIdOption;
+use crate::args::DiscardMode;
+use crate::args::FeatureReport;
 use crate::args::OutputKind;
+use crate::args::UnresolvedSymbolAction;
 use crate::debug_assert_bail;
+use crate::diagnostics::WarningCategory;
 use crate::elf;
 use crate::elf::EhFrameHdrEntry;
+use crate::elf::ElfClass;
 use crate::elf::File;
 use crate::elf::FileHeader;
 use crate::elf::Versym;
@@ -48,7 +54,12 @@ use crate::resolution::ResolvedEpilogue;
 use crate::resolution::SectionSlot;
 use crate::resolution::UnloadedSection;
 use crate::resolution::ValueFlags;
+use crate::riscv_attributes::RiscVAttributes;
 use crate::sharding::ShardKey;
+use crate::shrinking::ShrinkContext;
+use crate::shrinking::ShrunkSection;
+use crate::shrinking::TargetLocation;
+use crate::source_location::InputLocation;
 use crate::string_merging::MergedStringStartAddresses;
 use crate::string_merging::MergedStringsSection;
 use crate::string_merging::get_merged_string_output_address;
@@ -65,21 +76,29 @@ use anyhow::ensure;
 use bitflags::bitflags;
 use crossbeam_queue::ArrayQueue;
 use crossbeam_queue::SegQueue;
+use itertools::Either;
 use itertools::Itertools;
 use linker_utils::elf::RelocationKind;
+use linker_utils::elf::RelocationSize;
 use linker_utils::elf::SectionFlags;
+use linker_utils::elf::gnu_warning_symbol;
 use linker_utils::elf::shf;
 use linker_utils::relaxation::RelocationModifier;
-use object::LittleEndian;
+use object::Endian as _;
+use object::Endianness;
 use object::SectionIndex;
 use object::elf::GNU_PROPERTY_AARCH64_FEATURE_1_AND;
+use object::elf::GNU_PROPERTY_AARCH64_FEATURE_1_BTI;
+use object::elf::GNU_PROPERTY_AARCH64_FEATURE_1_PAC;
+use object::elf::GNU_PROPERTY_X86_FEATURE_1_AND;
+use object::elf::GNU_PROPERTY_X86_FEATURE_1_IBT;
+use object::elf::GNU_PROPERTY_X86_FEATURE_1_SHSTK;
 use object::elf::GNU_PROPERTY_X86_UINT32_AND_HI;
 use object::elf::GNU_PROPERTY_X86_UINT32_AND_LO;
 use object::elf::GNU_PROPERTY_X86_UINT32_OR_AND_HI;
 use object::elf::GNU_PROPERTY_X86_UINT32_OR_AND_LO;
 use object::elf::GNU_PROPERTY_X86_UINT32_OR_HI;
 use object::elf::GNU_PROPERTY_X86_UINT32_OR_LO;
-use object::elf::Rela64;
 use object::elf::gnu_hash;
 use object::read::elf::Dyn as _;
 use object::read::elf::Rela as _;
@@ -101,6 +120,7 @@ use std::mem::swap;
 use std::mem::take;
 use std::num::NonZeroU32;
 use std::num::NonZeroU64;
+use std::ops::Range;
 use std::sync::Mutex;
 use std::sync::atomic;
 use std::sync::atomic::AtomicBool;
@@ -144,7 +164,12 @@ pub fn compute<'data, 'symbol_db, A: Arch>(
 
     finalise_copy_relocations(&mut group_states, &symbol_db, &symbol_resolution_flags)?;
     merge_dynamic_symbol_definitions(&mut group_states)?;
-    merge_gnu_property_notes(&mut group_states)?;
+    merge_gnu_property_notes(&mut group_states, symbol_db.args)?;
+    let e_flags = merge_riscv_attributes::<A>(&mut group_states)?;
+    let plt_protection = plt_protection(
+        symbol_db.args,
+        &get_epilogue_mut(&mut group_states).gnu_property_notes,
+    );
 
     finalise_all_sizes(
         &symbol_db,
@@ -152,6 +177,8 @@ pub fn compute<'data, 'symbol_db, A: Arch>(
         &mut group_states,
         &symbol_resolution_flags,
     )?;
+    shrink_sections::<A>(&mut group_states, &output_sections, &symbol_db)?;
+    allocate_thunks::<A>(&mut group_states, &output_sections, symbol_db.args)?;
 
     let mut symbol_resolution_flags: Vec<ResolutionFlags> = symbol_resolution_flags
         .into_iter()
@@ -160,7 +187,7 @@ pub fn compute<'data, 'symbol_db, A: Arch>(
 
     let non_addressable_counts = apply_non_addressable_indexes(&mut group_states, symbol_db.args)?;
 
-    let section_part_sizes = compute_total_section_part_sizes(
+    let mut section_part_sizes = compute_total_section_part_sizes(
         &mut group_states,
         &mut output_sections,
         &mut symbol_resolution_flags,
@@ -168,9 +195,21 @@ pub fn compute<'data, 'symbol_db, A: Arch>(
         &symbol_db,
     )?;
 
-    let section_part_layouts =
-        layout_section_parts(&section_part_sizes, &output_sections, symbol_db.args);
-    let section_layouts = layout_sections(&section_part_layouts);
+    let mut section_part_layouts =
+        layout_section_parts(&section_part_sizes, &output_sections, symbol_db.args)?;
+    if symbol_db.args.fix_cortex_a53_843419 {
+        while allocate_erratum_patches::<A>(
+            &mut group_states,
+            &section_part_layouts,
+            &output_sections,
+            &mut section_part_sizes,
+        ) {
+            section_part_layouts =
+                layout_section_parts(&section_part_sizes, &output_sections, symbol_db.args)?;
+        }
+    }
+    let section_layouts = layout_sections(&section_part_layouts, &output_sections);
+    check_for_overlapping_sections(&section_layouts, &output_sections)?;
     output.set_size(compute_total_file_size(&section_layouts));
 
     let Some(FileLayoutState::Prelude(internal)) =
@@ -241,6 +280,9 @@ pub fn compute<'data, 'symbol_db, A: Arch>(
         merged_strings,
         merged_string_start_addresses,
         has_static_tls: gc_outputs.has_static_tls,
+        has_text_relocations: gc_outputs.has_text_relocations,
+        plt_protection,
+        e_flags,
         relocation_statistics,
     })
 }
@@ -300,6 +342,157 @@ fn finalise_all_sizes<'data>(
     })
 }
 
+/// Allocates range-extension thunks for branches that might not be able to reach their targets.
+/// We don't know addresses yet, so we decide based on the total size of executable code. Each object
+/// gets its own thunks, placed after its sections in each part that needs them, which means that a
+/// branch is always in range of its thunk, even if it turns out not to need it. Patches used to
+/// work around CPU errata go in the same areas, but are only allocated once we know addresses. See
+/// `allocate_erratum_patches`.
+#[tracing::instrument(skip_all, name = "Allocate thunks")]
+fn allocate_thunks<A: Arch>(
+    group_states: &mut [GroupState],
+    output_sections: &OutputSections,
+    args: &Args,
+) -> Result {
+    let executable_size = executable_size(group_states, output_sections, args);
+
+    group_states.par_iter_mut().try_for_each(|group| {
+        for file in &mut group.files {
+            if let FileLayoutState::Object(object) = file {
+                object.allocate_thunks::<A>(&mut group.common, executable_size, args)?;
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Allocates patches for erratum sequences that ended up at addresses where they need them. Adding
+/// patches moves the code that follows them, so, as lld does, our caller lays out the sections again
+/// and calls us again until no more patches are needed. Returns whether we added any.
+#[tracing::instrument(skip_all, name = "Allocate erratum patches")]
+fn allocate_erratum_patches<A: Arch>(
+    group_states: &mut [GroupState],
+    section_part_layouts: &OutputSectionPartMap<OutputRecordLayout>,
+    output_sections: &OutputSections,
+    total_sizes: &mut OutputSectionPartMap<u64>,
+) -> bool {
+    let starting_mem_offsets_by_group =
+        compute_start_offsets_by_group(group_states, starting_memory_offsets(section_part_layouts));
+
+    let extra_sizes_by_group: Vec<Option<OutputSectionPartMap<u64>>> = group_states
+        .par_iter_mut()
+        .zip(starting_mem_offsets_by_group)
+        .map(|(group, mut memory_offsets)| {
+            let mut extra_sizes =
+                OutputSectionPartMap::with_size(group.common.mem_sizes.num_parts());
+            let mut added = false;
+            for file in &mut group.files {
+                if let FileLayoutState::Object(object) = file {
+                    added |= object.allocate_erratum_patches::<A>(
+                        &mut memory_offsets,
+                        output_sections,
+                        &mut extra_sizes,
+                    );
+                }
+            }
+            added.then(|| {
+                group.common.mem_sizes.merge(&extra_sizes);
+                extra_sizes
+            })
+        })
+        .collect();
+
+    let mut added = false;
+    for extra_sizes in extra_sizes_by_group.iter().flatten() {
+        total_sizes.merge(extra_sizes);
+        added = true;
+    }
+    added
+}
+
+/// Applies linker relaxations that delete bytes from executable sections. This needs to happen
+/// before anything depends on the sizes of sections. Only some architectures have relaxations that
+/// do this. Deleting bytes can only bring code closer together, so decisions that we make based on
+/// sizes here remain valid.
+#[tracing::instrument(skip_all, name = "Shrink sections")]
+fn shrink_sections<'data, A: Arch>(
+    group_states: &mut [GroupState<'data>],
+    output_sections: &OutputSections,
+    symbol_db: &SymbolDb<'data>,
+) -> Result {
+    let args = symbol_db.args;
+    if args.is_relocatable() {
+        return Ok(());
+    }
+    let executable_size = executable_size(group_states, output_sections, args);
+    // In shared objects, we don't know where our TLS block will be relative to the thread pointer.
+    let tls_size = args.output_kind().is_executable().then(|| {
+        let mut part_sizes = vec![0; group_states[0].common.mem_sizes.parts.len()];
+        for group in group_states.iter() {
+            for (total, size) in part_sizes.iter_mut().zip(&group.common.mem_sizes.parts) {
+                *total += size;
+            }
+        }
+        // Sizes within each part are already padded to the part's alignment, so we only need to
+        // allow for padding at the start of each part.
+        part_sizes
+            .iter()
+            .enumerate()
+            .filter(|(part_index, size)| {
+                **size > 0
+                    && output_sections
+                        .section_flags(PartId::from_usize(*part_index).output_section_id())
+                        .contains(shf::TLS)
+            })
+            .map(|(part_index, size)| size + PartId::from_usize(part_index).alignment().value() - 1)
+            .sum()
+    });
+
+    group_states.par_iter_mut().try_for_each(|group| {
+        for file in &mut group.files {
+            if let FileLayoutState::Object(object) = file {
+                object.shrink_sections::<A>(
+                    &mut group.common,
+                    executable_size,
+                    tls_size,
+                    symbol_db,
+                )?;
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Returns an upper bound on the total size of executable code, excluding any thunks.
+fn executable_size(
+    group_states: &[GroupState],
+    output_sections: &OutputSections,
+    args: &Args,
+) -> u64 {
+    // If sections have been placed at particular addresses, then code can be arbitrarily far
+    // apart.
+    if !args.section_start.is_empty() {
+        return u64::MAX;
+    }
+    group_states
+        .iter()
+        .flat_map(|group| group.common.mem_sizes.parts.iter().enumerate())
+        .filter(|(part_index, _)| {
+            output_sections
+                .section_flags(PartId::from_usize(*part_index).output_section_id())
+                .contains(shf::EXECINSTR)
+        })
+        .map(|(_, size)| *size)
+        .sum()
+}
+
+/// Returns whether a branch that can reach `reach` bytes in either direction might not reach its
+/// target if all executable code is `executable_size` bytes. We allow a margin for padding between
+/// sections.
+pub(crate) fn branch_may_be_out_of_range(executable_size: u64, reach: u64) -> bool {
+    executable_size.saturating_add(executable_size / 8) >= reach
+}
+
 fn get_epilogue_mut<'a, 'data>(
     group_states: &'a mut [GroupState<'data>],
 ) -> &'a mut EpilogueLayoutState<'data> {
@@ -337,6 +530,10 @@ enum PropertyClass {
     AndOr,
 }
 
+/// The RISC-V equivalent of `GNU_PROPERTY_AARCH64_FEATURE_1_AND`, which has the same value. The
+/// `object` crate doesn't define it yet.
+const GNU_PROPERTY_RISCV_FEATURE_1_AND: u32 = 0xc000_0000;
+
 fn get_property_class(property_type: u32) -> Option<PropertyClass> {
     match property_type {
         GNU_PROPERTY_X86_UINT32_AND_LO..=GNU_PROPERTY_X86_UINT32_AND_HI => Some(PropertyClass::And),
@@ -350,13 +547,13 @@ fn get_property_class(property_type: u32) -> Option<PropertyClass> {
 }
 
 #[tracing::instrument(skip_all, name = "Merge GNU property notes")]
-fn merge_gnu_property_notes(group_states: &mut [GroupState]) -> Result {
-    let properties_per_file = group_states
+fn merge_gnu_property_notes(group_states: &mut [GroupState], args: &Args) -> Result {
+    let objects = group_states
         .iter()
         .flat_map(|group| {
             group.files.iter().filter_map(|file| {
                 if let FileLayoutState::Object(object) = file {
-                    Some(&object.gnu_property_notes)
+                    Some(object)
                 } else {
                     None
                 }
@@ -364,6 +561,14 @@ fn merge_gnu_property_notes(group_states: &mut [GroupState]) -> Result {
         })
         .collect_vec();
 
+    let features = ControlFlowFeatures::for_args(args);
+    features.report_missing(&objects, args)?;
+
+    let properties_per_file = objects
+        .iter()
+        .map(|object| &object.gnu_property_notes)
+        .collect_vec();
+
     // Merge bits of each property type based on type: OR or AND operation. When a property type
     // is newly added to the map, we start either with zero or all bits-set (PropertyClass::And).
     let mut property_map = HashMap::new();
@@ -386,7 +591,7 @@ fn merge_gnu_property_notes(group_states: &mut [GroupState]) -> Result {
     }
 
     // Iterate the properties sorted by property_type so that we have a stable output!
-    let output_properties = property_map
+    let mut output_properties = property_map
         .into_iter()
         .sorted_by_key(|x| x.0)
         .filter_map(|(property_type, property_value)| {
@@ -411,11 +616,174 @@ fn merge_gnu_property_notes(group_states: &mut [GroupState]) -> Result {
         })
         .collect_vec();
 
+    if features.forced != 0 {
+        if let Some(property) = output_properties
+            .iter_mut()
+            .find(|p| p.ptype == features.ptype)
+        {
+            property.data |= features.forced;
+        } else {
+            output_properties.push(GnuProperty {
+                ptype: features.ptype,
+                data: features.forced,
+            });
+            output_properties.sort_by_key(|p| p.ptype);
+        }
+    }
+
     let epilogue = get_epilogue_mut(group_states);
     epilogue.gnu_property_notes = output_properties;
     Ok(())
 }
 
+/// Merges the `.riscv.attributes` sections of our input objects and returns the flags to put in the
+/// file header. Only RISC-V objects have attributes sections or flags that we merge.
+#[tracing::instrument(skip_all, name = "Merge RISC-V attributes")]
+fn merge_riscv_attributes<A: Arch>(group_states: &mut [GroupState]) -> Result<u32> {
+    let objects = || {
+        group_states.iter().flat_map(|group| {
+            group.files.iter().filter_map(|file| {
+                if let FileLayoutState::Object(object) = file {
+                    Some(object)
+                } else {
+                    None
+                }
+            })
+        })
+    };
+
+    let e_flags = A::merge_e_flags(objects().map(|o| o.object.e_flags))?;
+
+    let mut merged: Option<RiscVAttributes> = None;
+    for object in objects() {
+        let Some(attributes) = object.riscv_attributes.as_ref() else {
+            continue;
+        };
+        if let Some(merged) = merged.as_mut() {
+            merged.merge(attributes).with_context(|| {
+                format!("Failed to merge RISC-V attributes from {}", object.input)
+            })?;
+        } else {
+            merged = Some(RiscVAttributes::clone(attributes));
+        }
+    }
+
+    get_epilogue_mut(group_states).riscv_attributes =
+        merged.map_or_else(Vec::new, |merged| merged.to_bytes());
+    Ok(e_flags)
+}
+
+/// The control-flow protection features of the architecture that we're linking for. These are
+/// stored as bits of a single AND-class GNU property.
+struct ControlFlowFeatures {
+    ptype: u32,
+
+    /// Bits that we set in the output regardless of whether all inputs have them.
+    forced: u32,
+
+    /// The features that we should check for in each input, their names and how to report
+    /// objects that lack them.
+    checks: Vec<(u32, &'static str, FeatureReport)>,
+}
+
+impl ControlFlowFeatures {
+    fn for_args(args: &Args) -> Self {
+        match args.arch {
+            Architecture::X86_64 | Architecture::I386 => {
+                let mut forced = 0;
+                if args.force_ibt {
+                    forced |= GNU_PROPERTY_X86_FEATURE_1_IBT;
+                }
+                if args.force_shstk {
+                    forced |= GNU_PROPERTY_X86_FEATURE_1_SHSTK;
+                }
+                Self {
+                    ptype: GNU_PROPERTY_X86_FEATURE_1_AND,
+                    forced,
+                    checks: vec![
+                        (GNU_PROPERTY_X86_FEATURE_1_IBT, "IBT", args.cet_report),
+                        (GNU_PROPERTY_X86_FEATURE_1_SHSTK, "SHSTK", args.cet_report),
+                    ],
+                }
+            }
+            Architecture::AArch64 => {
+                let mut forced = 0;
+                if args.force_bti {
+                    forced |= GNU_PROPERTY_AARCH64_FEATURE_1_BTI;
+                }
+                if args.pac_plt {
+                    forced |= GNU_PROPERTY_AARCH64_FEATURE_1_PAC;
+                }
+                Self {
+                    ptype: GNU_PROPERTY_AARCH64_FEATURE_1_AND,
+                    forced,
+                    checks: vec![(GNU_PROPERTY_AARCH64_FEATURE_1_BTI, "BTI", args.bti_report())],
+                }
+            }
+            // We don't yet support any RISC-V control-flow protection options.
+            Architecture::RiscV64 => Self {
+                ptype: GNU_PROPERTY_RISCV_FEATURE_1_AND,
+                forced: 0,
+                checks: Vec::new(),
+            },
+            // LoongArch doesn't define any control-flow protection properties.
+            Architecture::LoongArch64 => Self {
+                ptype: 0,
+                forced: 0,
+                checks: Vec::new(),
+            },
+        }
+    }
+
+    /// Reports any objects that lack features that we've been asked to check for.
+    fn report_missing(&self, objects: &[&ObjectLayoutState], args: &Args) -> Result {
+        let mut errors = Vec::new();
+        for object in objects {
+            let bits = object
+                .gnu_property_notes
+                .iter()
+                .filter(|p| p.ptype == self.ptype)
+                .fold(0, |bits, p| bits | p.data);
+            for &(bit, name, report) in &self.checks {
+                if report == FeatureReport::None || bits & bit != 0 {
+                    continue;
+                }
+                let message = format!("{}: missing {name} property", object.input);
+                if report == FeatureReport::Error {
+                    errors.push(anyhow!(message));
+                } else {
+                    crate::diagnostics::warning(WarningCategory::MissingProperty, &message);
+                }
+            }
+        }
+        if let Some(error) = crate::diagnostics::combine_errors(errors, args) {
+            return Err(error);
+        }
+        Ok(())
+    }
+}
+
+/// Works out what protection our PLT entries need to provide based on the properties that we're
+/// going to write to the output.
+fn plt_protection(args: &Args, output_properties: &[GnuProperty]) -> PltProtection {
+    let has_feature = |ptype: u32, bit: u32| {
+        output_properties
+            .iter()
+            .any(|p| p.ptype == ptype && p.data & bit != 0)
+    };
+    match args.arch {
+        Architecture::X86_64 | Architecture::I386 => PltProtection::default(),
+        Architecture::AArch64 => PltProtection {
+            landing_pad: has_feature(
+                GNU_PROPERTY_AARCH64_FEATURE_1_AND,
+                GNU_PROPERTY_AARCH64_FEATURE_1_BTI,
+            ),
+            authenticate: args.authenticate_plt(),
+        },
+        Architecture::RiscV64 | Architecture::LoongArch64 => PltProtection::default(),
+    }
+}
+
 fn compute_total_file_size(section_layouts: &OutputSectionMap<OutputRecordLayout>) -> u64 {
     let mut file_size = 0;
     section_layouts.for_each(|_, s| file_size = file_size.max(s.file_offset + s.file_size));
@@ -438,6 +806,15 @@ pub struct Layout<'data> {
     pub(crate) merged_string_start_addresses: MergedStringStartAddresses,
     pub(crate) relocation_statistics: OutputSectionMap<AtomicU64>,
     pub(crate) has_static_tls: bool,
+
+    /// Whether we emitted any dynamic relocations that apply to read-only sections.
+    pub(crate) has_text_relocations: bool,
+
+    /// The control-flow protection that our PLT entries need to provide.
+    pub(crate) plt_protection: PltProtection,
+
+    /// Architecture-specific flags for the file header.
+    pub(crate) e_flags: u32,
 }
 
 pub(crate) struct SegmentLayouts {
@@ -548,6 +925,10 @@ pub(crate) struct EpilogueLayoutState<'data> {
     dynamic_symbol_definitions: Vec<DynamicSymbolDefinition<'data>>,
     gnu_hash_layout: Option<GnuHashLayout>,
     gnu_property_notes: Vec<GnuProperty>,
+
+    /// The contents of our `.riscv.attributes` section. Empty if we're not writing one.
+    riscv_attributes: Vec<u8>,
+
     build_id_size: Option<usize>,
 
     verdefs: Option<Vec<VersionDef>>,
@@ -567,6 +948,7 @@ pub(crate) struct EpilogueLayout<'data> {
     pub(crate) dynamic_symbol_definitions: Vec<DynamicSymbolDefinition<'data>>,
     dynsym_start_index: u32,
     pub(crate) gnu_property_notes: Vec<GnuProperty>,
+    pub(crate) riscv_attributes: Vec<u8>,
     pub(crate) verdefs: Option<Vec<VersionDef>>,
 }
 
@@ -578,6 +960,51 @@ pub(crate) struct ObjectLayout<'data> {
     pub(crate) relocations: RelocationSections,
     pub(crate) section_resolutions: Vec<SectionResolution>,
     pub(crate) symbol_id_range: SymbolIdRange,
+    pub(crate) thunk_areas: Vec<ThunkArea>,
+
+    /// Sections that linker relaxation made smaller, sorted by section index.
+    pub(crate) shrunk_sections: Vec<ShrunkSection>,
+
+    /// The order in which our sections are laid out, if it differs from their order in the input
+    /// file. See `section_output_order`.
+    pub(crate) section_order: Option<Vec<object::SectionIndex>>,
+}
+
+/// Returns the indexes of an object's sections in the order in which we lay them out and write
+/// them. That's their order in the input file, unless `section_order` says otherwise.
+pub(crate) fn section_output_order(
+    section_order: Option<&[object::SectionIndex]>,
+    num_sections: usize,
+) -> impl Iterator<Item = object::SectionIndex> + '_ {
+    match section_order {
+        Some(order) => Either::Left(order.iter().copied()),
+        None => Either::Right((0..num_sections).map(object::SectionIndex)),
+    }
+}
+
+/// Range-extension thunks for branches in a run of an object's sections that share a part of an
+/// output section. The thunks are placed immediately after the last section in the run.
+#[derive(Debug)]
+pub(crate) struct ThunkArea {
+    pub(crate) part_id: PartId,
+
+    /// The last section in the run of sections that uses these thunks.
+    pub(crate) last_section: SectionIndex,
+
+    /// The address of the first thunk. Only valid once layout has been finalised.
+    pub(crate) address: u64,
+
+    /// The symbol and addend that each thunk branches to, sorted so that we can binary search.
+    pub(crate) targets: Vec<(object::SymbolIndex, i64)>,
+
+    /// The sections and offsets of instruction sequences that ended up at addresses where they
+    /// trigger a CPU erratum that we're working around, sorted by section and offset. Each gets a
+    /// patch, placed after the thunks.
+    pub(crate) erratum_sequences: Vec<(SectionIndex, u64)>,
+
+    /// Sequences that might trigger the erratum, depending on their final address, but which
+    /// haven't yet been found at such an address.
+    erratum_candidates: Vec<(SectionIndex, u64)>,
 }
 
 pub(crate) struct PreludeLayout {
@@ -655,12 +1082,15 @@ trait SymbolRequestHandler<'data>: std::fmt::Display + HandlerData {
                 if current_res_flags.contains(ResolutionFlags::COPY_RELOCATION) {
                     // The dynamic symbol is a definition, so is handled by the epilogue. We only
                     // need to deal with the symtab entry here.
-                    let entry_size = size_of::<elf::SymtabEntry>() as u64;
+                    let entry_size = symbol_db.args.elf_class().symtab_entry_size();
                     common.allocate(part_id::SYMTAB_GLOBAL, entry_size);
                     common.allocate(part_id::STRTAB, name.len() as u64 + 1);
                 } else {
                     common.allocate(part_id::DYNSTR, name.len() as u64 + 1);
-                    common.allocate(part_id::DYNSYM, crate::elf::SYMTAB_ENTRY_SIZE);
+                    common.allocate(
+                        part_id::DYNSYM,
+                        symbol_db.args.elf_class().symtab_entry_size(),
+                    );
                 }
             }
 
@@ -668,7 +1098,7 @@ trait SymbolRequestHandler<'data>: std::fmt::Display + HandlerData {
                 verify_consistent_allocation_handling(
                     value_flags,
                     resolution_flags.get(),
-                    symbol_db.args.output_kind(),
+                    symbol_db.args,
                 )?;
             }
 
@@ -676,12 +1106,12 @@ trait SymbolRequestHandler<'data>: std::fmt::Display + HandlerData {
                 value_flags,
                 resolution_flags,
                 &mut common.mem_sizes,
-                symbol_db.args.output_kind(),
+                symbol_db.args,
             );
         }
         if symbol_db.args.should_output_symbol_versions() {
-            let num_dynamic_symbols =
-                common.mem_sizes.get(part_id::DYNSYM) / crate::elf::SYMTAB_ENTRY_SIZE;
+            let num_dynamic_symbols = common.mem_sizes.get(part_id::DYNSYM)
+                / symbol_db.args.elf_class().symtab_entry_size();
             // Note, sets the GNU_VERSION allocation rather than incrementing it. Assuming there are
             // multiple files in our group, we'll update this same value multiple times, each time
             // with a possibly revised dynamic symbol count. The important thing is that when we're
@@ -718,7 +1148,7 @@ fn allocate_symbol_resolution(
     value_flags: ValueFlags,
     resolution_flags: &AtomicResolutionFlags,
     mem_sizes: &mut OutputSectionPartMap<u64>,
-    output_kind: OutputKind,
+    args: &Args,
 ) {
     let mut r = resolution_flags.get();
     if !r.is_empty() && value_flags.contains(ValueFlags::IFUNC) {
@@ -726,21 +1156,21 @@ fn allocate_symbol_resolution(
         r |= ResolutionFlags::GOT | ResolutionFlags::PLT;
     }
 
-    allocate_resolution(value_flags, r, mem_sizes, output_kind);
+    allocate_resolution(value_flags, r, mem_sizes, args);
 }
 
 /// Computes how much to allocate for a particular resolution. This is intended for debug assertions
 /// when we're writing, to make sure that we would have allocated memory before we write.
 pub(crate) fn compute_allocations(
     resolution: &Resolution,
-    output_kind: OutputKind,
+    args: &Args,
 ) -> OutputSectionPartMap<u64> {
     let mut sizes = OutputSectionPartMap::with_size(NUM_GENERATED_PARTS);
     allocate_resolution(
         resolution.value_flags,
         resolution.resolution_flags,
         &mut sizes,
-        output_kind,
+        args,
     );
     sizes
 }
@@ -749,43 +1179,46 @@ fn allocate_resolution(
     value_flags: ValueFlags,
     resolution_flags: ResolutionFlags,
     mem_sizes: &mut OutputSectionPartMap<u64>,
-    output_kind: OutputKind,
+    args: &Args,
 ) {
+    let output_kind = args.output_kind();
+    let got_entry_size = args.got_entry_size();
+    let rela_entry_size = args.rela_entry_size();
     let has_dynamic_symbol = value_flags.contains(ValueFlags::DYNAMIC)
         || resolution_flags.contains(ResolutionFlags::EXPORT_DYNAMIC);
     if resolution_flags.contains(ResolutionFlags::GOT) {
-        mem_sizes.increment(part_id::GOT, elf::GOT_ENTRY_SIZE);
+        mem_sizes.increment(part_id::GOT, got_entry_size);
         if resolution_flags.contains(ResolutionFlags::PLT) {
-            mem_sizes.increment(part_id::PLT_GOT, elf::PLT_ENTRY_SIZE);
+            mem_sizes.increment(part_id::PLT_GOT, args.plt_entry_size());
         }
         if value_flags.contains(ValueFlags::IFUNC) {
-            mem_sizes.increment(part_id::RELA_PLT, elf::RELA_ENTRY_SIZE);
+            mem_sizes.increment(part_id::RELA_PLT, rela_entry_size);
         } else if !value_flags.contains(ValueFlags::CAN_BYPASS_GOT) && has_dynamic_symbol {
-            mem_sizes.increment(part_id::RELA_DYN_GENERAL, elf::RELA_ENTRY_SIZE);
+            mem_sizes.increment(part_id::RELA_DYN_GENERAL, rela_entry_size);
         } else if value_flags.contains(ValueFlags::ADDRESS) && output_kind.is_relocatable() {
-            mem_sizes.increment(part_id::RELA_DYN_RELATIVE, elf::RELA_ENTRY_SIZE);
+            mem_sizes.increment(part_id::RELA_DYN_RELATIVE, rela_entry_size);
         }
     }
     if resolution_flags.contains(ResolutionFlags::GOT_TLS_OFFSET) {
-        mem_sizes.increment(part_id::GOT, elf::GOT_ENTRY_SIZE);
+        mem_sizes.increment(part_id::GOT, got_entry_size);
         if !value_flags.contains(ValueFlags::CAN_BYPASS_GOT) {
-            mem_sizes.increment(part_id::RELA_DYN_GENERAL, elf::RELA_ENTRY_SIZE);
+            mem_sizes.increment(part_id::RELA_DYN_GENERAL, rela_entry_size);
         }
     }
     if resolution_flags.contains(ResolutionFlags::GOT_TLS_MODULE) {
-        mem_sizes.increment(part_id::GOT, elf::GOT_ENTRY_SIZE * 2);
+        mem_sizes.increment(part_id::GOT, got_entry_size * 2);
         // For executables, the TLS module ID is known at link time. For shared objects, we
         // need a runtime relocation to fill it in.
         if !output_kind.is_executable() {
-            mem_sizes.increment(part_id::RELA_DYN_GENERAL, elf::RELA_ENTRY_SIZE);
+            mem_sizes.increment(part_id::RELA_DYN_GENERAL, rela_entry_size);
         }
         if !value_flags.contains(ValueFlags::CAN_BYPASS_GOT) && has_dynamic_symbol {
-            mem_sizes.increment(part_id::RELA_DYN_GENERAL, elf::RELA_ENTRY_SIZE);
+            mem_sizes.increment(part_id::RELA_DYN_GENERAL, rela_entry_size);
         }
     }
     if resolution_flags.contains(ResolutionFlags::GOT_TLS_DESCRIPTOR) {
-        mem_sizes.increment(part_id::GOT, elf::GOT_ENTRY_SIZE * 2);
-        mem_sizes.increment(part_id::RELA_DYN_GENERAL, elf::RELA_ENTRY_SIZE);
+        mem_sizes.increment(part_id::GOT, got_entry_size * 2);
+        mem_sizes.increment(part_id::RELA_DYN_GENERAL, rela_entry_size);
     }
 }
 
@@ -814,7 +1247,7 @@ impl<'data> SymbolRequestHandler<'data> for ObjectLayoutState<'data> {
         );
 
         let object_symbol_index = self.symbol_id_range.id_to_input(symbol_id);
-        let local_symbol = self.object.symbol(object_symbol_index)?;
+        let local_symbol = &self.object.symbol(object_symbol_index)?;
 
         if let Some(section_id) = self
             .object
@@ -826,8 +1259,8 @@ impl<'data> SymbolRequestHandler<'data> for ObjectLayoutState<'data> {
                     self.file_id,
                     section_id,
                 )));
-        } else if local_symbol.is_common(LittleEndian) {
-            let common_symbol = CommonSymbol::new(local_symbol)?;
+        } else if local_symbol.is_common(self.object.endian) {
+            let common_symbol = CommonSymbol::new(local_symbol, self.object.endian)?;
             common.allocate(
                 output_section_id::BSS.part_id_with_alignment(common_symbol.alignment),
                 common_symbol.size,
@@ -858,7 +1291,8 @@ impl<'data> SymbolRequestHandler<'data> for DynamicLayoutState<'data> {
     ) -> Result {
         let local_index = symbol_id.to_offset(self.symbol_id_range());
         if let Some(&version_index) = self.symbol_versions.get(local_index) {
-            let version_index = version_index.0.get(LittleEndian) & object::elf::VERSYM_VERSION;
+            let version_index =
+                version_index.0.get(self.object.endian) & object::elf::VERSYM_VERSION;
             // Versions 0 and 1 are local and global. We care about the versions after that.
             if version_index > object::elf::VER_NDX_GLOBAL {
                 *self
@@ -938,7 +1372,7 @@ struct CommonGroupState<'data> {
     dynamic_symbol_definitions: Vec<DynamicSymbolDefinition<'data>>,
 
     /// Indexed by `FrameIndex`.
-    exception_frames: Vec<ExceptionFrame<'data>>,
+    exception_frames: Vec<ExceptionFrame>,
 }
 
 impl CommonGroupState<'_> {
@@ -950,10 +1384,10 @@ impl CommonGroupState<'_> {
         }
     }
 
-    fn validate_sizes(&self) -> Result {
+    fn validate_sizes(&self, args: &Args) -> Result {
         if *self.mem_sizes.get(part_id::GNU_VERSION) > 0 {
             let num_dynamic_symbols =
-                self.mem_sizes.get(part_id::DYNSYM) / crate::elf::SYMTAB_ENTRY_SIZE;
+                self.mem_sizes.get(part_id::DYNSYM) / args.elf_class().symtab_entry_size();
             let num_versym = self.mem_sizes.get(part_id::GNU_VERSION) / size_of::<Versym>() as u64;
             if num_versym != num_dynamic_symbols {
                 bail!(
@@ -1019,16 +1453,40 @@ struct ObjectLayoutState<'data> {
 
     cies: SmallVec<[CieAtOffset<'data>; 2]>,
 
-    eh_frame_section: Option<&'data object::elf::SectionHeader64<LittleEndian>>,
+    eh_frame_section: Option<Box<EhFrameSection<'data>>>,
     eh_frame_size: u64,
 
     gnu_property_notes: Vec<GnuProperty>,
+
+    riscv_attributes: Option<Box<RiscVAttributes>>,
+
+    /// Sections with SHF_LINK_ORDER that are waiting for the section that they link to to be
+    /// loaded. Each entry is (linked-to section, dependent section). Sorted by linked-to section.
+    link_order_dependents: Vec<(SectionIndex, SectionIndex)>,
+
+    /// The shortest reach of any thunkable branch that we have to a different section. Lets us
+    /// skip looking for branches that need thunks unless executable code is large enough that
+    /// they might.
+    shortest_branch_reach: u64,
+
+    thunk_areas: Vec<ThunkArea>,
+
+    /// Sections that linker relaxation made smaller, sorted by section index.
+    shrunk_sections: Vec<ShrunkSection>,
+}
+
+struct EhFrameSection<'data> {
+    index: SectionIndex,
+    header: elf::SectionHeader,
+
+    /// The relocations for .eh_frame. Indexed by `ExceptionFrame::relocations`.
+    relocations: elf::RelocationList<'data>,
 }
 
 #[derive(Default)]
-struct ExceptionFrame<'data> {
-    /// The relocations that need to be processed if we load this frame.
-    relocations: &'data [Rela64<LittleEndian>],
+struct ExceptionFrame {
+    /// The range of .eh_frame relocations that need to be processed if we load this frame.
+    relocations: Range<usize>,
 
     /// Number of bytes required to store this frame.
     frame_size: u32,
@@ -1192,6 +1650,11 @@ pub(crate) struct Section {
     pub(crate) size: u64,
     pub(crate) resolution_kind: ResolutionFlags,
     pub(crate) is_writable: bool,
+    /// Whether this is a legacy `.ctors` or `.dtors` section whose entries need to be written in
+    /// reverse order.
+    pub(crate) is_reversed: bool,
+    /// For sections with SHF_LINK_ORDER, the section that they link to.
+    pub(crate) link_order_target: Option<object::SectionIndex>,
 }
 
 pub(crate) struct GroupLayout<'data> {
@@ -1237,6 +1700,17 @@ struct GraphResources<'data, 'scope> {
 
     errors: Mutex<Vec<Error>>,
 
+    /// References to undefined symbols. We collect these rather than reporting them immediately so
+    /// that we can list all the places that reference each symbol together.
+    undefined_references: Mutex<Vec<(SymbolId, InputLocation)>>,
+
+    /// Relocations that require a dynamic relocation to be applied to a read-only section.
+    text_relocations: Mutex<Vec<(SymbolId, InputLocation)>>,
+
+    /// Warnings from `.gnu.warning.SYM` sections in loaded objects. These get issued once we know
+    /// whether the symbol was referenced.
+    symbol_warnings: Mutex<Vec<(SymbolId, String)>>,
+
     waiting_workers: ArrayQueue<GroupState<'data>>,
 
     /// A queue in which we store threads when they're idle so that other threads can wake them up
@@ -1398,12 +1872,24 @@ impl<'data> Layout<'data> {
         alignment.align_up(tls_end)
     }
 
+    /// Returns the address that the thread pointer will point to. Offsets from the thread pointer
+    /// are relative to this.
+    pub(crate) fn thread_pointer_address(&self) -> u64 {
+        match self.args().arch {
+            Architecture::X86_64 | Architecture::I386 => self.tls_end_address(),
+            Architecture::AArch64 => self.tls_start_address_aarch64(),
+            Architecture::RiscV64 | Architecture::LoongArch64 => self.tls_start_address(),
+        }
+    }
+
     /// Returns the memory address of the start of the TLS segment used by the AArch64.
     pub(crate) fn tls_start_address_aarch64(&self) -> u64 {
+        let tbss = self.section_layouts.get(output_section_id::TBSS);
         let tdata = self.section_layouts.get(output_section_id::TDATA);
+        let alignment = tbss.alignment.max(tdata.alignment);
         // Two words at TP are reserved by the arch.
         let tls_start = tdata.mem_offset - 2 * 8;
-        tdata.alignment.align_down(tls_start)
+        alignment.align_down(tls_start)
     }
 
     pub(crate) fn layout_data(&self) -> linker_layout::Layout {
@@ -1423,17 +1909,19 @@ impl<'data> Layout<'data> {
                         sections: obj
                             .section_resolutions
                             .iter()
-                            .zip(obj.object.sections.iter())
+                            .zip(obj.object.sections())
                             .zip(&obj.sections)
-                            .map(|((res, section), section_slot)| {
+                            .map(|((res, (_, section)), section_slot)| {
                                 (matches!(section_slot, SectionSlot::Loaded(..))
-                                    && SectionFlags::from_header(section).contains(shf::ALLOC)
-                                    && obj.object.section_size(section).is_ok_and(|s| s > 0))
+                                    && SectionFlags::from_header(&section, obj.object.endian)
+                                        .contains(shf::ALLOC)
+                                    && obj.object.section_size(&section).is_ok_and(|s| s > 0))
                                 .then(|| {
                                     let address = res.address;
                                     linker_layout::Section {
                                         mem_range: address
-                                            ..(address + obj.object.section_size(section).unwrap()),
+                                            ..(address
+                                                + obj.object.section_size(&section).unwrap()),
                                     }
                                 })
                             })
@@ -1472,6 +1960,7 @@ impl<'data> Layout<'data> {
 
     pub(crate) fn info_inputs(&self) -> InfoInputs {
         InfoInputs {
+            elf_class: self.args().elf_class(),
             section_part_layouts: &self.section_part_layouts,
             non_addressable_counts: &self.non_addressable_counts,
             output_section_indexes: &self.output_sections.output_section_indexes,
@@ -1481,8 +1970,9 @@ impl<'data> Layout<'data> {
 
 fn layout_sections(
     section_part_layouts: &OutputSectionPartMap<OutputRecordLayout>,
+    output_sections: &OutputSections,
 ) -> OutputSectionMap<OutputRecordLayout> {
-    section_part_layouts.merge_parts(|layouts| {
+    let mut section_layouts = section_part_layouts.merge_parts(|layouts| {
         let mut file_offset = usize::MAX;
         let mut mem_offset = u64::MAX;
         let mut file_end = 0;
@@ -1504,7 +1994,36 @@ fn layout_sections(
             file_offset,
             mem_offset,
         }
-    })
+    });
+
+    // Extend primary sections like `.init_array` to cover the sections that are output as part of
+    // them. These are placed immediately before the primary in the output order.
+    for (section_id, info) in output_sections.ids_with_info() {
+        let Some(primary) = info.primary else {
+            continue;
+        };
+        let secondary = *section_layouts.get(section_id);
+        if secondary.mem_size == 0 {
+            continue;
+        }
+        let primary = section_layouts.get_mut(primary);
+        let file_end = primary.file_offset + primary.file_size;
+        let mem_end = primary.mem_offset + primary.mem_size;
+        primary.file_offset = primary.file_offset.min(secondary.file_offset);
+        primary.mem_offset = primary.mem_offset.min(secondary.mem_offset);
+        primary.file_size =
+            file_end.max(secondary.file_offset + secondary.file_size) - primary.file_offset;
+        primary.mem_size =
+            mem_end.max(secondary.mem_offset + secondary.mem_size) - primary.mem_offset;
+        primary.alignment = primary.alignment.max(secondary.alignment);
+    }
+
+    // `.tdata` was placed at the alignment of the whole TLS segment. See `layout_section_parts`.
+    let tbss_alignment = section_layouts.get(output_section_id::TBSS).alignment;
+    let tdata = section_layouts.get_mut(output_section_id::TDATA);
+    tdata.alignment = tdata.alignment.max(tbss_alignment);
+
+    section_layouts
 }
 
 #[tracing::instrument(skip_all, name = "Compute per-group start offsets")]
@@ -1574,9 +2093,37 @@ fn compute_segment_layout(
         alignment: Alignment,
     }
 
+    impl Record {
+        fn new(segment_id: ProgramSegmentId) -> Self {
+            Record {
+                segment_id,
+                file_start: usize::MAX,
+                file_end: 0,
+                mem_start: u64::MAX,
+                mem_end: 0,
+                alignment: alignment::MIN,
+            }
+        }
+
+        fn to_layout(&self) -> SegmentLayout {
+            SegmentLayout {
+                id: self.segment_id,
+                sizes: OutputRecordLayout {
+                    file_size: self.file_end - self.file_start,
+                    mem_size: self.mem_end - self.mem_start,
+                    alignment: self.alignment,
+                    file_offset: self.file_start,
+                    mem_offset: self.mem_start,
+                },
+            }
+        }
+    }
+
     use output_section_id::OrderEvent;
     let mut complete = Vec::with_capacity(crate::program_segments::MAX_SEGMENTS);
     let mut active_records = Vec::new();
+    // LOAD segments that were ended early because a section started a LOAD segment of its own.
+    let mut split_records = Vec::with_capacity(header_info.extra_load_segment_starts.len());
 
     for event in output_sections.sections_and_segments_events() {
         match event {
@@ -1595,17 +2142,7 @@ fn compute_segment_layout(
                         },
                     ));
                 } else {
-                    active_records.push((
-                        segment_id,
-                        Record {
-                            segment_id,
-                            file_start: usize::MAX,
-                            file_end: 0,
-                            mem_start: u64::MAX,
-                            mem_end: 0,
-                            alignment: alignment::MIN,
-                        },
-                    ));
+                    active_records.push((segment_id, Record::new(segment_id)));
                 }
             }
             OrderEvent::SegmentEnd(segment_id) => {
@@ -1655,6 +2192,14 @@ fn compute_segment_layout(
                          segment.",
                         output_sections.name(section_id)
                     );
+                    if header_info.extra_load_segment_starts.contains(&section_id) {
+                        for (segment_id, rec) in &mut active_records {
+                            if segment_id.segment_type() == object::elf::PT_LOAD {
+                                split_records
+                                    .push(std::mem::replace(rec, Record::new(*segment_id)));
+                            }
+                        }
+                    }
                     for (_, rec) in &mut active_records {
                         rec.file_start = rec.file_start.min(part.file_offset);
                         rec.mem_start = rec.mem_start.min(part.mem_offset);
@@ -1670,26 +2215,36 @@ fn compute_segment_layout(
     complete.sort_by_key(|r| r.segment_id);
     assert_eq!(complete.len(), MAX_SEGMENTS);
     let mut tls_start_address = None;
-    let segments = header_info
-        .active_segment_ids
+    let mut segments = Vec::with_capacity(header_info.num_program_headers());
+    for &id in &header_info.active_segment_ids {
+        let r = &complete[id.as_usize()];
+        if id == program_segments::TLS {
+            tls_start_address = Some(r.mem_start);
+        }
+        segments.extend(
+            split_records
+                .iter()
+                .filter(|split| split.segment_id == id)
+                .map(Record::to_layout),
+        );
+        segments.push(r.to_layout());
+    }
+
+    // LOAD segments need to be sorted by address, which might not match our output order if
+    // sections were placed at requested addresses.
+    let load_indexes = segments
         .iter()
-        .map(|&id| {
-            let r = &complete[id.as_usize()];
-            if id == program_segments::TLS {
-                tls_start_address = Some(r.mem_start);
-            }
-            SegmentLayout {
-                id,
-                sizes: OutputRecordLayout {
-                    file_size: r.file_end - r.file_start,
-                    mem_size: r.mem_end - r.mem_start,
-                    alignment: r.alignment,
-                    file_offset: r.file_start,
-                    mem_offset: r.mem_start,
-                },
-            }
-        })
-        .collect();
+        .positions(|segment| segment.id.segment_type() == object::elf::PT_LOAD)
+        .collect_vec();
+    let mut loads = load_indexes
+        .iter()
+        .map(|&i| segments[i].clone())
+        .collect_vec();
+    loads.sort_by_key(|segment| segment.sizes.mem_offset);
+    for (i, load) in load_indexes.into_iter().zip(loads) {
+        segments[i] = load;
+    }
+
     Ok(SegmentLayouts {
         segments,
         tls_start_address,
@@ -1804,6 +2359,7 @@ struct GcOutputs<'data> {
     group_states: Vec<GroupState<'data>>,
     sections_with_content: OutputSectionMap<bool>,
     has_static_tls: bool,
+    has_text_relocations: bool,
 }
 
 #[tracing::instrument(skip_all, name = "Find required sections")]
@@ -1830,6 +2386,9 @@ fn find_required_sections<'data, A: Arch>(
         symbol_db,
         worker_slots,
         errors: Mutex::new(Vec::new()),
+        undefined_references: Mutex::new(Vec::new()),
+        text_relocations: Mutex::new(Vec::new()),
+        symbol_warnings: Mutex::new(Vec::new()),
         waiting_workers: ArrayQueue::new(num_workers),
         // NB, the -1 is because we never want all our threads to be idle. Once the last thread is
         // about to go idle, we're done and need to wake up and terminate all the threads.
@@ -1896,8 +2455,10 @@ fn find_required_sections<'data, A: Arch>(
     });
 
     let mut errors: Vec<Error> = take(resources.errors.lock().unwrap().as_mut());
-    // TODO: Figure out good way to report more than one error.
-    if let Some(error) = errors.pop() {
+    resources.report_undefined_symbols(&mut errors);
+    let has_text_relocations = resources.report_text_relocations(&mut errors);
+    resources.report_symbol_warnings();
+    if let Some(error) = crate::diagnostics::combine_errors(errors, resources.symbol_db.args) {
         return Err(error);
     }
 
@@ -1920,6 +2481,7 @@ fn find_required_sections<'data, A: Arch>(
         group_states,
         sections_with_content,
         has_static_tls: resources.has_static_tls.load(atomic::Ordering::Relaxed),
+        has_text_relocations,
     })
 }
 
@@ -2004,7 +2566,7 @@ impl<'data> GroupState<'data> {
                 symbol_resolution_flags,
             )?;
         }
-        self.common.validate_sizes()?;
+        self.common.validate_sizes(symbol_db.args)?;
         Ok(())
     }
 
@@ -2131,37 +2693,221 @@ impl GraphResources<'_, '_> {
         self.errors.lock().unwrap().push(error);
     }
 
-    /// Sends all work in `work` to the worker for `file_id`. Leaves `work` empty so that it can be
-    /// reused.
-    #[inline(always)]
-    fn send_work(&self, file_id: FileId, work: WorkItem) {
-        let worker;
-        {
-            let mut slot = self.worker_slots[file_id.group()].lock().unwrap();
-            worker = slot.worker.take();
-            slot.work.push(work);
-        };
-        if let Some(worker) = worker {
-            // The capacity of `waiting_workers` is equal to the total number of workers, so the
-            // following should never fail.
-            let _ = self.waiting_workers.push(worker);
-            // If there's an idle thread, wake it so that it can process the work.
-            if let Some(thread) = self
-                .idle_threads
-                .as_ref()
-                .and_then(|idle_threads| idle_threads.pop())
-            {
-                thread.unpark();
-            }
-        }
+    fn report_undefined_reference(
+        &self,
+        symbol_id: SymbolId,
+        object: &ObjectLayoutState,
+        section_index: SectionIndex,
+        offset: u64,
+    ) {
+        self.undefined_references
+            .lock()
+            .unwrap()
+            .push((symbol_id, input_location(object, section_index, offset)));
     }
 
-    fn shut_down(&self) {
-        self.done.store(true, atomic::Ordering::SeqCst);
-        // Wake up all sleeping threads so that they can shut down.
-        if let Some(idle_threads) = self.idle_threads.as_ref() {
-            while let Some(thread) = idle_threads.pop() {
-                thread.unpark();
+    fn record_text_relocation(
+        &self,
+        symbol_id: SymbolId,
+        object: &ObjectLayoutState,
+        section_index: SectionIndex,
+        offset: u64,
+    ) {
+        self.text_relocations
+            .lock()
+            .unwrap()
+            .push((symbol_id, input_location(object, section_index, offset)));
+    }
+
+    /// Reports relocations that needed to modify read-only sections at runtime. These are errors
+    /// unless text relocations were permitted, in which case we may warn. Returns whether there were
+    /// any such relocations.
+    fn report_text_relocations(&self, errors: &mut Vec<Error>) -> bool {
+        let mut references = take(&mut *self.text_relocations.lock().unwrap());
+        if references.is_empty() {
+            return false;
+        }
+        let args = self.symbol_db.args;
+        if args.allow_text_relocations && !args.warn_text_relocations {
+            return true;
+        }
+        references.sort_by_key(|(symbol_id, location)| {
+            (
+                *symbol_id,
+                location.file_id,
+                location.section_index.0,
+                location.offset,
+            )
+        });
+        for symbol_references in references.chunk_by(|a, b| a.0 == b.0) {
+            let symbol_id = symbol_references[0].0;
+            let locations: Vec<InputLocation> = symbol_references.iter().map(|r| r.1).collect();
+            // Relocations against local labels generally refer to the section symbol, which has no
+            // name, so we describe where it points instead.
+            let name = self
+                .symbol_db
+                .symbol_name_for_display(symbol_id)
+                .to_string();
+            let target = if name.is_empty() {
+                InputLocation::for_symbol(self.symbol_db, symbol_id)
+                    .map_or_else(|| "local symbol".to_owned(), |l| l.describe(self.symbol_db))
+            } else {
+                format!("`{name}`")
+            };
+            let message = format!(
+                "Relocation against {target} in read-only section{}",
+                crate::source_location::describe_references(self.symbol_db, &locations),
+            );
+            if args.allow_text_relocations {
+                crate::diagnostics::warning(WarningCategory::TextRelocation, &message);
+            } else {
+                errors.push(anyhow::anyhow!(
+                    "{message}\nRecompile with -fPIC or link with -z notext to allow text \
+                     relocations"
+                ));
+            }
+        }
+        if args.allow_text_relocations {
+            crate::diagnostics::warning(
+                WarningCategory::TextRelocation,
+                "Creating DT_TEXTREL in output",
+            );
+        }
+        true
+    }
+
+    /// Records a warning from a `.gnu.warning.SYM` section, to be issued if `symbol_name` gets
+    /// referenced.
+    fn record_symbol_warning(&self, symbol_name: &[u8], message: String) {
+        if let Some(symbol_id) = self
+            .symbol_db
+            .get_unversioned(&UnversionedSymbolName::prehashed(symbol_name))
+        {
+            self.symbol_warnings
+                .lock()
+                .unwrap()
+                .push((symbol_id, message));
+        }
+    }
+
+    /// Issues the warnings from `.gnu.warning.SYM` sections for symbols that were referenced.
+    fn report_symbol_warnings(&self) {
+        let mut warnings = take(&mut *self.symbol_warnings.lock().unwrap());
+        warnings.sort();
+        for (symbol_id, message) in warnings {
+            let definition = self.symbol_db.definition(symbol_id);
+            if !self.symbol_resolution_flags[definition.as_usize()]
+                .get()
+                .is_empty()
+            {
+                let message = match self.first_reference(definition) {
+                    Some(location) => format!("{}: {message}", location.describe(self.symbol_db)),
+                    None => format!(
+                        "Reference to `{}`: {message}",
+                        self.symbol_db.symbol_name_for_display(symbol_id)
+                    ),
+                };
+                crate::diagnostics::warning(WarningCategory::GnuWarning, &message);
+            }
+        }
+    }
+
+    /// Finds the first relocation in a loaded section that references `definition`. This is only
+    /// used when reporting warnings, so it's fine that it's a linear scan over all relocations.
+    fn first_reference(&self, definition: SymbolId) -> Option<InputLocation> {
+        self.worker_slots.iter().find_map(|slot| {
+            let slot = slot.lock().unwrap();
+            slot.worker.as_ref()?.files.iter().find_map(|file| {
+                let FileLayoutState::Object(object) = file else {
+                    return None;
+                };
+                object.first_reference(definition, self.symbol_db)
+            })
+        })
+    }
+
+    /// Reports undefined symbols that were referenced, either as warnings or by adding to `errors`,
+    /// depending on our arguments.
+    fn report_undefined_symbols(&self, errors: &mut Vec<Error>) {
+        let mut references = take(&mut *self.undefined_references.lock().unwrap());
+        references.sort_by_key(|(symbol_id, location)| {
+            (
+                *symbol_id,
+                location.file_id,
+                location.section_index.0,
+                location.offset,
+            )
+        });
+        let symbol_db = self.symbol_db;
+        let by_symbol: Vec<&[(SymbolId, InputLocation)]> =
+            references.chunk_by(|a, b| a.0 == b.0).collect();
+
+        // Finding suggestions requires looking at all symbol names, so we only do it for as many
+        // symbols as we're going to report.
+        let num_to_suggest = match symbol_db.args.error_limit {
+            0 => by_symbol.len(),
+            limit => limit.min(by_symbol.len()),
+        };
+        let suggestions = crate::suggestions::suggest_alternatives(
+            symbol_db,
+            &by_symbol[..num_to_suggest]
+                .iter()
+                .map(|r| r[0].0)
+                .collect_vec(),
+        );
+
+        for (i, symbol_references) in by_symbol.iter().enumerate() {
+            let symbol_id = symbol_references[0].0;
+            let locations: Vec<InputLocation> = symbol_references.iter().map(|r| r.1).collect();
+            let mut message = format!(
+                "Undefined symbol {}, referenced by {}{}",
+                symbol_db.symbol_name_for_display(symbol_id),
+                symbol_db.file(locations[0].file_id),
+                crate::source_location::describe_references(symbol_db, &locations),
+            );
+            if let Some(Some(suggestion)) = suggestions.get(i) {
+                message.push('\n');
+                message.push_str(suggestion);
+            }
+            if symbol_db.args.unresolved_symbols_in_objects() == UnresolvedSymbolAction::Warn {
+                crate::diagnostics::warning(WarningCategory::UndefinedSymbol, &message);
+            } else {
+                errors.push(anyhow::anyhow!(message));
+            }
+        }
+    }
+
+    /// Sends all work in `work` to the worker for `file_id`. Leaves `work` empty so that it can be
+    /// reused.
+    #[inline(always)]
+    fn send_work(&self, file_id: FileId, work: WorkItem) {
+        let worker;
+        {
+            let mut slot = self.worker_slots[file_id.group()].lock().unwrap();
+            worker = slot.worker.take();
+            slot.work.push(work);
+        };
+        if let Some(worker) = worker {
+            // The capacity of `waiting_workers` is equal to the total number of workers, so the
+            // following should never fail.
+            let _ = self.waiting_workers.push(worker);
+            // If there's an idle thread, wake it so that it can process the work.
+            if let Some(thread) = self
+                .idle_threads
+                .as_ref()
+                .and_then(|idle_threads| idle_threads.pop())
+            {
+                thread.unpark();
+            }
+        }
+    }
+
+    fn shut_down(&self) {
+        self.done.store(true, atomic::Ordering::SeqCst);
+        // Wake up all sleeping threads so that they can shut down.
+        if let Some(idle_threads) = self.idle_threads.as_ref() {
+            while let Some(thread) = idle_threads.pop() {
+                thread.unpark();
             }
         }
     }
@@ -2181,7 +2927,7 @@ impl<'data> FileLayoutState<'data> {
                 s.finalise_symbol_sizes(common, symbol_db, symbol_resolution_flags)?;
             }
             FileLayoutState::Dynamic(s) => {
-                s.finalise_sizes(common)?;
+                s.finalise_sizes(common, symbol_db.args)?;
                 s.finalise_symbol_sizes(common, symbol_db, symbol_resolution_flags)?;
             }
             FileLayoutState::Prelude(s) => {
@@ -2401,6 +3147,27 @@ impl std::fmt::Debug for FileLayout<'_> {
     }
 }
 
+/// If the specified section is a `.gnu.warning` or `.gnu.warning.SYM` section, returns the symbol
+/// name, which is empty for the former, and the warning message.
+fn read_gnu_warning<'data>(
+    object: &File<'data>,
+    section_index: SectionIndex,
+) -> Result<Option<(&'data [u8], String)>> {
+    if section_index.0 == 0 {
+        return Ok(None);
+    }
+    let section = &object.section(section_index)?;
+    let Some(symbol_name) = gnu_warning_symbol(object.section_name(section)?) else {
+        return Ok(None);
+    };
+    let data = object.raw_section_data(section)?;
+    let data = data.split(|b| *b == 0).next().unwrap_or_default();
+    Ok(Some((
+        symbol_name,
+        String::from_utf8_lossy(data).into_owned(),
+    )))
+}
+
 impl std::fmt::Display for ObjectLayoutState<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         std::fmt::Display::fmt(&self.input, f)?;
@@ -2439,14 +3206,22 @@ impl Section {
         section_index: object::SectionIndex,
         part_id: PartId,
     ) -> Result<Section> {
-        let object_section = object_state.object.section(section_index)?;
+        let e = object_state.object.endian;
+        let object_section = &object_state.object.section(section_index)?;
         let size = object_state.object.section_size(object_section)?;
+        let section_flags = SectionFlags::from_header(object_section, e);
         let section = Section {
             index: section_index,
             part_id,
             size,
             resolution_kind: ResolutionFlags::empty(),
-            is_writable: SectionFlags::from_header(object_section).contains(shf::WRITE),
+            is_writable: section_flags.contains(shf::WRITE),
+            is_reversed: output_section_id::is_reversed_pointer_list(
+                object_state.object.section_name(object_section)?,
+            ),
+            link_order_target: Some(object_section.sh_link(e) as usize)
+                .filter(|link| section_flags.contains(shf::LINK_ORDER) && *link != 0)
+                .map(object::SectionIndex),
         };
         Ok(section)
     }
@@ -2479,19 +3254,20 @@ impl Section {
 fn process_relocation<A: Arch>(
     object: &mut ObjectLayoutState,
     common: &mut CommonGroupState,
-    rel: &Rela64<LittleEndian>,
-    section: &object::elf::SectionHeader64<LittleEndian>,
+    rel: &elf::Rela,
+    section_index: SectionIndex,
+    section: &elf::SectionHeader,
     resources: &GraphResources,
     queue: &mut LocalWorkQueue,
 ) -> Result<RelocationModifier> {
     let args = resources.symbol_db.args;
     let mut next_modifier = RelocationModifier::Normal;
-    if let Some(local_sym_index) = rel.symbol(LittleEndian, false) {
+    if let Some(local_sym_index) = rel.symbol(object.object.endian, false) {
         let symbol_db = resources.symbol_db;
         let symbol_id = symbol_db.definition(object.symbol_id_range.input_to_id(local_sym_index));
         let symbol_value_flags = symbol_db.local_symbol_value_flags(symbol_id);
-        let rel_offset = rel.r_offset.get(LittleEndian);
-        let r_type = rel.r_type(LittleEndian, false);
+        let rel_offset = rel.r_offset.get(object.object.endian);
+        let r_type = rel.r_type(object.object.endian, false);
 
         let rel_info = if let Some(relaxation) = A::Relaxation::new(
             r_type,
@@ -2499,7 +3275,7 @@ fn process_relocation<A: Arch>(
             rel_offset,
             symbol_value_flags,
             args.output_kind(),
-            SectionFlags::from_header(section),
+            SectionFlags::from_header(section, object.object.endian),
             true,
         ) {
             next_modifier = relaxation.next_modifier();
@@ -2507,28 +3283,35 @@ fn process_relocation<A: Arch>(
         } else {
             A::relocation_from_raw(r_type)?
         };
-        if does_relocation_require_static_tls(r_type) {
+        if does_relocation_require_static_tls::<A>(r_type) {
             resources
                 .has_static_tls
                 .store(true, atomic::Ordering::Relaxed);
         }
 
-        let section_is_writable = SectionFlags::from_header(section).contains(shf::WRITE);
+        let section_is_writable =
+            SectionFlags::from_header(section, object.object.endian).contains(shf::WRITE);
+        let pointer_size = RelocationSize::ByteSize(args.got_entry_size() as usize);
         let mut resolution_kind = resolution_flags(rel_info.kind);
         if resolution_kind.contains(ResolutionFlags::DIRECT)
             && symbol_value_flags.contains(ValueFlags::DYNAMIC)
         {
             if section_is_writable {
-                common.allocate(part_id::RELA_DYN_GENERAL, elf::RELA_ENTRY_SIZE);
+                common.allocate(part_id::RELA_DYN_GENERAL, args.rela_entry_size());
             } else if symbol_value_flags.contains(ValueFlags::FUNCTION) {
                 resolution_kind.remove(ResolutionFlags::DIRECT);
                 resolution_kind |= ResolutionFlags::PLT | ResolutionFlags::GOT;
             } else if !symbol_value_flags.contains(ValueFlags::ABSOLUTE) {
                 if args.allow_copy_relocations {
                     resolution_kind |= ResolutionFlags::COPY_RELOCATION;
+                } else if rel_info.kind == RelocationKind::Absolute && rel_info.size == pointer_size
+                {
+                    // Without a copy relocation, the dynamic loader will need to write the
+                    // address into our read-only section. This needs to be kept consistent with
+                    // `needs_dynamic_text_relocation`, which the writer uses.
+                    common.allocate(part_id::RELA_DYN_GENERAL, args.rela_entry_size());
+                    resources.record_text_relocation(symbol_id, object, section_index, rel_offset);
                 } else {
-                    // We don't at present support text relocations, so if we can't apply a copy
-                    // relocation, we error instead.
                     bail!(
                         "Direct relocation ({}) to dynamic symbol from non-writable section, \
                         but copy relocations are disabled. {}",
@@ -2551,7 +3334,10 @@ fn process_relocation<A: Arch>(
             && symbol_value_flags.contains(ValueFlags::ADDRESS)
         {
             if section_is_writable {
-                common.allocate(part_id::RELA_DYN_RELATIVE, elf::RELA_ENTRY_SIZE);
+                common.allocate(part_id::RELA_DYN_RELATIVE, args.rela_entry_size());
+            } else if rel_info.size == pointer_size {
+                common.allocate(part_id::RELA_DYN_RELATIVE, args.rela_entry_size());
+                resources.record_text_relocation(symbol_id, object, section_index, rel_offset);
             } else {
                 bail!(
                     "Cannot apply relocation {} to read-only section. \
@@ -2563,19 +3349,19 @@ fn process_relocation<A: Arch>(
 
         if previous_flags.is_empty() {
             queue.send_symbol_request(symbol_id, resources);
-            if is_symbol_undefined(
-                object.object.symbol(local_sym_index)?,
+        }
+
+        // Checking the flags first avoids looking up the symbol in the common case.
+        if symbol_value_flags.contains(ValueFlags::ABSOLUTE)
+            && is_symbol_undefined(
+                &object.object.symbol(local_sym_index)?,
                 object.file_id,
                 symbol_db.file_id_for_symbol(symbol_id),
                 symbol_value_flags,
                 args,
-            ) {
-                let symbol_name = symbol_db.symbol_name_for_display(symbol_id);
-                resources.report_error(anyhow::anyhow!(
-                    "Undefined symbol {symbol_name}, referenced by {}",
-                    object.input,
-                ));
-            }
+            )
+        {
+            resources.report_undefined_reference(symbol_id, object, section_index, rel_offset);
         }
 
         if resolution_kind.contains(ResolutionFlags::COPY_RELOCATION)
@@ -2587,11 +3373,44 @@ fn process_relocation<A: Arch>(
     Ok(next_modifier)
 }
 
+/// Returns the location of a relocation at `offset` within `section`, for use in diagnostics.
+fn input_location(
+    object: &ObjectLayoutState,
+    section_index: SectionIndex,
+    offset: u64,
+) -> InputLocation {
+    InputLocation {
+        file_id: object.file_id,
+        section_index,
+        offset,
+    }
+}
+
+/// Returns whether an absolute relocation in a read-only section against a dynamic symbol with
+/// `value_flags` needs a text relocation. Functions can instead use a PLT entry and data in
+/// executables can use a copy relocation.
+pub(crate) fn needs_dynamic_text_relocation(value_flags: ValueFlags, args: &Args) -> bool {
+    !value_flags.contains(ValueFlags::FUNCTION)
+        && !value_flags.contains(ValueFlags::ABSOLUTE)
+        && !args.allow_copy_relocations
+}
+
 /// Returns whether the supplied relocation type requires static TLS. If true and we're writing a
 /// shared object, then the STATIC_TLS will be set in the shared object which is a signal to the
 /// runtime loader that the shared object cannot be loaded at runtime (e.g. with dlopen).
-fn does_relocation_require_static_tls(r_type: u32) -> bool {
-    r_type == object::elf::R_X86_64_GOTTPOFF
+fn does_relocation_require_static_tls<A: Arch>(r_type: u32) -> bool {
+    match A::elf_header_arch_magic() {
+        object::elf::EM_X86_64 => matches!(
+            r_type,
+            object::elf::R_X86_64_GOTTPOFF
+                | linker_utils::x86_64::R_X86_64_CODE_4_GOTTPOFF
+                | linker_utils::x86_64::R_X86_64_CODE_6_GOTTPOFF
+        ),
+        object::elf::EM_386 => {
+            r_type == object::elf::R_386_TLS_IE || r_type == object::elf::R_386_TLS_GOTIE
+        }
+        _ => false,
+    }
 }
 
 fn resolution_flags(rel_kind: RelocationKind) -> ResolutionFlags {
@@ -2616,8 +3435,13 @@ fn resolution_flags(rel_kind: RelocationKind) -> ResolutionFlags {
             ResolutionFlags::empty()
         }
         RelocationKind::Absolute
+        | RelocationKind::AbsoluteSet
+        | RelocationKind::AbsoluteAddition
+        | RelocationKind::AbsoluteSubtraction
         | RelocationKind::Relative
+        | RelocationKind::PcRelLoRiscV
         | RelocationKind::DtpOff
+        | RelocationKind::DtpOffRiscV
         | RelocationKind::TpOff
         | RelocationKind::TpOffAArch64
         | RelocationKind::SymRelGotBase => ResolutionFlags::DIRECT,
@@ -2667,7 +3491,10 @@ impl PreludeLayoutState {
         // The first entry in the symbol table must be null. Similarly, the first string in the
         // strings table must be empty.
         if !resources.symbol_db.args.strip_all {
-            common.allocate(part_id::SYMTAB_LOCAL, size_of::<elf::SymtabEntry>() as u64);
+            common.allocate(
+                part_id::SYMTAB_LOCAL,
+                resources.symbol_db.args.elf_class().symtab_entry_size(),
+            );
             common.allocate(part_id::STRTAB, 1);
         }
 
@@ -2678,7 +3505,10 @@ impl PreludeLayoutState {
         if resources.symbol_db.args.needs_dynsym() {
             // Allocate space for the null symbol.
             common.allocate(part_id::DYNSTR, 1);
-            common.allocate(part_id::DYNSYM, size_of::<elf::SymtabEntry>() as u64);
+            common.allocate(
+                part_id::DYNSYM,
+                resources.symbol_db.args.elf_class().symtab_entry_size(),
+            );
         }
 
         self.dynamic_linker = resources
@@ -2725,12 +3555,12 @@ impl PreludeLayoutState {
     ) {
         if uses_tlsld.load(atomic::Ordering::Relaxed) {
             // Allocate space for a TLS module number and offset for use with TLSLD relocations.
-            common.allocate(part_id::GOT, elf::GOT_ENTRY_SIZE * 2);
+            common.allocate(part_id::GOT, args.got_entry_size() * 2);
             self.needs_tlsld_got_entry = true;
             // For shared objects, we'll need to use a DTPMOD relocation to fill in the TLS module
             // number.
             if !args.output_kind().is_executable() {
-                common.allocate(part_id::RELA_DYN_GENERAL, crate::elf::RELA_ENTRY_SIZE);
+                common.allocate(part_id::RELA_DYN_GENERAL, args.rela_entry_size());
             }
         }
 
@@ -2758,6 +3588,7 @@ impl PreludeLayoutState {
         let mut extra_sizes = OutputSectionPartMap::with_size(common.mem_sizes.num_parts());
 
         self.determine_header_sizes(
+            symbol_db.args,
             total_sizes,
             &mut extra_sizes,
             sections_with_content,
@@ -2828,6 +3659,7 @@ impl PreludeLayoutState {
 
     fn determine_header_sizes(
         &mut self,
+        args: &Args,
         total_sizes: &OutputSectionPartMap<u64>,
         extra_sizes: &mut OutputSectionPartMap<u64>,
         sections_with_content: OutputSectionMap<bool>,
@@ -2867,6 +3699,16 @@ impl PreludeLayoutState {
                     }
                 }
             });
+        // Sections like `.init_array.N` are output as part of their primary section, so if we're
+        // keeping one of them, we need to keep the primary instead.
+        for (section_id, info) in output_sections.ids_with_info() {
+            if let Some(primary) = info.primary {
+                if std::mem::take(keep_sections.get_mut(section_id)) {
+                    *keep_sections.get_mut(primary) = true;
+                }
+            }
+        }
+
         let num_sections = keep_sections.values_iter().filter(|p| **p).count();
 
         // Compute output indexes of each section.
@@ -2880,6 +3722,12 @@ impl PreludeLayoutState {
                 }
             };
         }
+        for (section_id, info) in output_sections.ids_with_info() {
+            if let Some(primary) = info.primary {
+                output_section_indexes[section_id.as_usize()] =
+                    output_section_indexes[primary.as_usize()];
+            }
+        }
         output_sections.output_section_indexes = output_section_indexes;
 
         // Determine which program segments contain sections that we're keeping.
@@ -2904,21 +3752,27 @@ impl PreludeLayoutState {
             .map(ProgramSegmentId::new)
             .collect();
 
+        let elf_class = args.elf_class();
         let header_info = HeaderInfo {
+            elf_class,
             num_output_sections_with_content: num_sections
                 .try_into()
                 .expect("output section count must fit in a u16"),
 
             active_segment_ids,
+            extra_load_segment_starts: sections_starting_extra_load_segments(output_sections, args),
         };
 
         // Allocate space for headers based on segment and section counts.
-        extra_sizes.increment(part_id::FILE_HEADER, u64::from(elf::FILE_HEADER_SIZE));
+        extra_sizes.increment(
+            part_id::FILE_HEADER,
+            u64::from(elf_class.file_header_size()),
+        );
         extra_sizes.increment(part_id::PROGRAM_HEADERS, header_info.program_headers_size());
         extra_sizes.increment(part_id::SECTION_HEADERS, header_info.section_headers_size());
         self.shstrtab_size = output_sections
             .ids_with_info()
-            .filter(|(id, _info)| output_sections.output_index_of_section(*id).is_some())
+            .filter(|(id, _info)| output_sections.has_section_header(*id))
             .map(|(_id, info)| info.name.len() as u64 + 1)
             .sum::<u64>();
         extra_sizes.increment(part_id::SHSTRTAB, self.shstrtab_size);
@@ -2940,13 +3794,17 @@ impl PreludeLayoutState {
         let tlsld_got_entry = self.needs_tlsld_got_entry.then(|| {
             let address = NonZeroU64::new(*memory_offsets.get(part_id::GOT))
                 .expect("GOT address must never be zero");
-            memory_offsets.increment(part_id::GOT, elf::GOT_ENTRY_SIZE * 2);
+            memory_offsets.increment(part_id::GOT, resources.symbol_db.args.got_entry_size() * 2);
             address
         });
 
         // Take the null symbol's index.
         if resources.symbol_db.args.needs_dynsym() {
-            take_dynsym_index(memory_offsets, resources.section_layouts)?;
+            take_dynsym_index(
+                memory_offsets,
+                resources.section_layouts,
+                resources.symbol_db.args,
+            )?;
         }
 
         self.internal_symbols
@@ -2996,8 +3854,15 @@ impl InternalSymbols {
                 continue;
             }
 
-            sizes.increment(part_id::SYMTAB_GLOBAL, size_of::<elf::SymtabEntry>() as u64);
             let symbol_name = symbol_db.symbol_name(symbol_id)?;
+            if !symbol_db.is_retained_in_symtab(symbol_name.bytes()) {
+                continue;
+            }
+
+            sizes.increment(
+                part_id::SYMTAB_GLOBAL,
+                symbol_db.args.elf_class().symtab_entry_size(),
+            );
             sizes.increment(part_id::STRTAB, symbol_name.len() as u64 + 1);
         }
         Ok(())
@@ -3063,6 +3928,7 @@ fn create_start_end_symbol_resolution(
         None,
         value_flags,
         memory_offsets,
+        resources.symbol_db.args,
     ))
 }
 
@@ -3073,12 +3939,12 @@ fn is_symbol_undefined(
     symbol_value_flags: ValueFlags,
     args: &Args,
 ) -> bool {
-    if (args.output_kind() == OutputKind::SharedObject && !args.no_undefined) || symbol.is_weak() {
+    if args.unresolved_symbols_in_objects() == UnresolvedSymbolAction::Ignore || symbol.is_weak() {
         return false;
     }
 
     sym_file_id == sym_def_file_id
-        && symbol.is_undefined(LittleEndian)
+        && symbol.is_undefined(args.endian)
         && symbol_value_flags.contains(ValueFlags::ABSOLUTE)
 }
 
@@ -3114,18 +3980,20 @@ impl<'data> EpilogueLayoutState<'data> {
             dynamic_symbol_definitions: Default::default(),
             gnu_hash_layout: None,
             gnu_property_notes: Default::default(),
+            riscv_attributes: Default::default(),
             build_id_size: Default::default(),
             verdefs: Default::default(),
         }
     }
 
-    fn gnu_property_notes_section_size(&self) -> u64 {
+    fn gnu_property_notes_section_size(&self, elf_class: ElfClass) -> u64 {
         if self.gnu_property_notes.is_empty() {
             0
         } else {
             (size_of::<NoteHeader>()
                 + GNU_NOTE_NAME.len()
-                + self.gnu_property_notes.len() * GNU_NOTE_PROPERTY_ENTRY_SIZE) as u64
+                + self.gnu_property_notes.len() * elf_class.gnu_note_property_entry_size())
+                as u64
         }
     }
 
@@ -3153,21 +4021,21 @@ impl<'data> EpilogueLayoutState<'data> {
         }
 
         if symbol_db.args.needs_dynamic() {
-            let dynamic_entry_size = size_of::<crate::elf::DynamicEntry>();
+            let dynamic_entry_size = symbol_db.args.elf_class().dynamic_entry_size();
             common.allocate(
                 part_id::DYNAMIC,
-                (elf_writer::NUM_EPILOGUE_DYNAMIC_ENTRIES * dynamic_entry_size) as u64,
+                elf_writer::NUM_EPILOGUE_DYNAMIC_ENTRIES as u64 * dynamic_entry_size,
             );
             for rpath in &symbol_db.args.rpaths {
-                common.allocate(part_id::DYNAMIC, dynamic_entry_size as u64);
+                common.allocate(part_id::DYNAMIC, dynamic_entry_size);
                 common.allocate(part_id::DYNSTR, rpath.len() as u64 + 1);
             }
             if let Some(soname) = symbol_db.args.soname.as_ref() {
                 common.allocate(part_id::DYNSTR, soname.len() as u64 + 1);
-                common.allocate(part_id::DYNAMIC, dynamic_entry_size as u64);
+                common.allocate(part_id::DYNAMIC, dynamic_entry_size);
             }
 
-            self.allocate_gnu_hash(common);
+            self.allocate_gnu_hash(common, symbol_db.args.elf_class());
 
             common.allocate(
                 part_id::DYNSTR,
@@ -3178,13 +4046,18 @@ impl<'data> EpilogueLayoutState<'data> {
             );
             common.allocate(
                 part_id::DYNSYM,
-                (self.dynamic_symbol_definitions.len() * size_of::<elf::SymtabEntry>()) as u64,
+                self.dynamic_symbol_definitions.len() as u64
+                    * symbol_db.args.elf_class().symtab_entry_size(),
             );
         }
 
         common.allocate(
             part_id::NOTE_GNU_PROPERTY,
-            self.gnu_property_notes_section_size(),
+            self.gnu_property_notes_section_size(symbol_db.args.elf_class()),
+        );
+        common.allocate(
+            part_id::RISCV_ATTRIBUTES,
+            self.riscv_attributes.len() as u64,
         );
 
         if let Some(build_id_sec_size) = self.gnu_build_id_note_section_size() {
@@ -3241,7 +4114,7 @@ impl<'data> EpilogueLayoutState<'data> {
 
     /// Allocates space required for .gnu.hash. Also sorts dynamic symbol definitions by their hash
     /// bucket as required by .gnu.hash.
-    fn allocate_gnu_hash(&mut self, common: &mut CommonGroupState) {
+    fn allocate_gnu_hash(&mut self, common: &mut CommonGroupState, elf_class: ElfClass) {
         // Our number of buckets is computed somewhat arbitrarily so that we have on average 2
         // symbols per bucket, but then we round up to a power of two.
         let num_defs = self.dynamic_symbol_definitions.len();
@@ -3261,7 +4134,7 @@ impl<'data> EpilogueLayoutState<'data> {
         common.allocate(
             part_id::GNU_HASH,
             (size_of::<elf::GnuHashHeader>()
-                + size_of::<u64>() * num_blume
+                + elf_class.pointer_size() as usize * num_blume
                 + size_of::<u32>() * gnu_hash_layout.bucket_count as usize
                 + size_of::<u32>() * num_defs) as u64,
         );
@@ -3274,6 +4147,19 @@ impl<'data> EpilogueLayoutState<'data> {
         resolutions_out: &mut ResolutionWriter,
         resources: &FinaliseLayoutResources<'_, 'data>,
     ) -> Result<EpilogueLayout<'data>> {
+        // With `-z start-stop-gc`, references to __start_ / __stop_ symbols don't keep their
+        // sections alive. If all the sections got discarded, then, like GNU ld, we leave the
+        // symbols undefined.
+        if resources.symbol_db.args.start_stop_gc {
+            for def_info in &mut self.internal_symbols.symbol_definitions {
+                if def_info.section_id().is_some_and(|section_id| {
+                    resources.section_layouts.get(section_id).mem_size == 0
+                }) {
+                    *def_info = InternalSymDefInfo::Undefined;
+                }
+            }
+        }
+
         self.internal_symbols
             .finalise_layout(memory_offsets, resolutions_out, resources)?;
 
@@ -3282,9 +4168,9 @@ impl<'data> EpilogueLayoutState<'data> {
                 .section_layouts
                 .get(output_section_id::DYNSYM)
                 .mem_offset)
-            / elf::SYMTAB_ENTRY_SIZE)
-            .try_into()
-            .context("Too many dynamic symbols")?;
+            / resources.symbol_db.args.elf_class().symtab_entry_size())
+        .try_into()
+        .context("Too many dynamic symbols")?;
 
         if let Some(gnu_hash_layout) = self.gnu_hash_layout.as_mut() {
             gnu_hash_layout.symbol_base = dynsym_start_index;
@@ -3292,12 +4178,17 @@ impl<'data> EpilogueLayoutState<'data> {
 
         memory_offsets.increment(
             part_id::DYNSYM,
-            self.dynamic_symbol_definitions.len() as u64 * elf::SYMTAB_ENTRY_SIZE,
+            self.dynamic_symbol_definitions.len() as u64
+                * resources.symbol_db.args.elf_class().symtab_entry_size(),
         );
 
         memory_offsets.increment(
             part_id::NOTE_GNU_PROPERTY,
-            self.gnu_property_notes_section_size(),
+            self.gnu_property_notes_section_size(resources.symbol_db.args.elf_class()),
+        );
+        memory_offsets.increment(
+            part_id::RISCV_ATTRIBUTES,
+            self.riscv_attributes.len() as u64,
         );
 
         if let Some(build_id_sec_size) = self.gnu_build_id_note_section_size() {
@@ -3321,23 +4212,34 @@ impl<'data> EpilogueLayoutState<'data> {
             dynamic_symbol_definitions: self.dynamic_symbol_definitions,
             dynsym_start_index,
             gnu_property_notes: self.gnu_property_notes,
+            riscv_attributes: self.riscv_attributes,
             verdefs: self.verdefs,
         })
     }
 }
 
 pub(crate) struct HeaderInfo {
+    pub(crate) elf_class: ElfClass,
     pub(crate) num_output_sections_with_content: u16,
     pub(crate) active_segment_ids: Vec<ProgramSegmentId>,
+
+    /// Sections that start a LOAD segment in addition to those in `active_segment_ids`, because
+    /// they were placed at a requested address.
+    pub(crate) extra_load_segment_starts: Vec<OutputSectionId>,
 }
 
 impl HeaderInfo {
+    pub(crate) fn num_program_headers(&self) -> usize {
+        self.active_segment_ids.len() + self.extra_load_segment_starts.len()
+    }
+
     pub(crate) fn program_headers_size(&self) -> u64 {
-        u64::from(elf::PROGRAM_HEADER_SIZE) * self.active_segment_ids.len() as u64
+        u64::from(self.elf_class.program_header_size()) * self.num_program_headers() as u64
     }
 
     pub(crate) fn section_headers_size(&self) -> u64 {
-        u64::from(elf::SECTION_HEADER_SIZE) * u64::from(self.num_output_sections_with_content)
+        u64::from(self.elf_class.section_header_size())
+            * u64::from(self.num_output_sections_with_content)
     }
 }
 
@@ -3360,6 +4262,11 @@ fn new_object_layout_state(input_state: resolution::ResolvedObject) -> FileLayou
             relocations: non_dynamic.relocations,
             cies: Default::default(),
             gnu_property_notes: Default::default(),
+            riscv_attributes: None,
+            link_order_dependents: Default::default(),
+            shortest_branch_reach: u64::MAX,
+            thunk_areas: Default::default(),
+            shrunk_sections: Default::default(),
         })
     } else {
         FileLayoutState::Dynamic(DynamicLayoutState {
@@ -3390,8 +4297,10 @@ impl<'data> ObjectLayoutState<'data> {
     ) -> Result {
         let mut eh_frame_section = None;
         let mut note_gnu_property_section = None;
+        let mut riscv_attributes_section = None;
 
-        let no_gc = !resources.symbol_db.args.gc_sections;
+        let args = resources.symbol_db.args;
+        let no_gc = !args.gc_sections;
 
         for (i, section) in self.sections.iter().enumerate() {
             match section {
@@ -3411,7 +4320,30 @@ impl<'data> ObjectLayoutState<'data> {
                                 self.file_id,
                                 object::SectionIndex(i),
                             )));
-                    } else if sec.start_stop_eligible {
+                        continue;
+                    }
+                    if sec.is_link_order {
+                        let link = SectionIndex(
+                            self.object
+                                .section(SectionIndex(i))?
+                                .sh_link(self.object.endian)
+                                .try_into()?,
+                        );
+                        match self.sections.get(link.0) {
+                            Some(SectionSlot::Unloaded(..) | SectionSlot::MustLoad(..)) => {
+                                self.link_order_dependents.push((link, SectionIndex(i)));
+                            }
+                            Some(SectionSlot::Discard) => {}
+                            // The linked-to section is always kept, or is something that we don't
+                            // track, so keep the dependent section too.
+                            _ => {
+                                queue.local_work.push(WorkItem::LoadSection(
+                                    SectionLoadRequest::new(self.file_id, object::SectionIndex(i)),
+                                ));
+                            }
+                        }
+                    }
+                    if sec.start_stop_eligible && !args.start_stop_gc {
                         resources
                             .start_stop_sections
                             .get(sec.part_id.output_section_id())
@@ -3427,10 +4359,17 @@ impl<'data> ObjectLayoutState<'data> {
                 SectionSlot::NoteGnuProperty(index) => {
                     note_gnu_property_section = Some(*index);
                 }
+                SectionSlot::RiscVAttributes(index) => {
+                    riscv_attributes_section = Some(*index);
+                }
+                SectionSlot::Discard => self.process_gnu_warning(SectionIndex(i), resources)?,
                 _ => (),
             }
         }
 
+        self.link_order_dependents
+            .sort_unstable_by_key(|(link, _)| link.0);
+
         if let Some(eh_frame_section_index) = eh_frame_section {
             process_eh_frame_data::<A>(
                 self,
@@ -3440,12 +4379,17 @@ impl<'data> ObjectLayoutState<'data> {
                 resources,
                 queue,
             )?;
-            let eh_frame_section = self.object.section(eh_frame_section_index)?;
-            self.eh_frame_section = Some(eh_frame_section);
         }
         if let Some(note_gnu_property_index) = note_gnu_property_section {
             process_gnu_property_note(self, note_gnu_property_index)?;
         }
+        if let Some(riscv_attributes_index) = riscv_attributes_section {
+            let section = &self.object.section(riscv_attributes_index)?;
+            self.riscv_attributes = Some(Box::new(
+                RiscVAttributes::parse(self.object.raw_section_data(section)?)
+                    .with_context(|| format!("Failed to parse .riscv.attributes in {self}"))?,
+            ));
+        }
 
         if resources.symbol_db.args.output_kind() == OutputKind::SharedObject {
             self.load_non_hidden_symbols::<A>(common, resources, queue)?;
@@ -3454,6 +4398,24 @@ impl<'data> ObjectLayoutState<'data> {
         Ok(())
     }
 
+    /// If the specified section is a `.gnu.warning` section, then either issues its warning now or
+    /// records it to be issued if its symbol gets referenced.
+    fn process_gnu_warning(
+        &self,
+        section_index: SectionIndex,
+        resources: &GraphResources<'data, '_>,
+    ) -> Result {
+        let Some((symbol_name, message)) = read_gnu_warning(self.object, section_index)? else {
+            return Ok(());
+        };
+        if symbol_name.is_empty() {
+            crate::diagnostics::warning(WarningCategory::GnuWarning, &format!("{self}: {message}"));
+        } else {
+            resources.record_symbol_warning(symbol_name, message);
+        }
+        Ok(())
+    }
+
     fn handle_section_load_request<'scope, A: Arch>(
         &mut self,
         common: &mut CommonGroupState<'data>,
@@ -3477,7 +4439,8 @@ impl<'data> ObjectLayoutState<'data> {
             SectionSlot::Loaded(_)
             | SectionSlot::EhFrameData(..)
             | SectionSlot::LoadedDebugInfo(..)
-            | SectionSlot::NoteGnuProperty(..) => {}
+            | SectionSlot::NoteGnuProperty(..)
+            | SectionSlot::RiscVAttributes(..) => {}
             SectionSlot::MergeStrings(_) => {
                 // We currently always load everything in merge-string sections. i.e. we don't
                 // GC unreferenced data. So there's nothing to do here.
@@ -3498,16 +4461,27 @@ impl<'data> ObjectLayoutState<'data> {
         let part_id = unloaded.part_id;
         let section = Section::create(self, section_id, part_id)?;
         let mut modifier = RelocationModifier::Normal;
-        for rel in self.relocations(section.index)? {
+        let section_header = self.object.section(section.index)?;
+        for rel in self.relocations(section.index)?.iter() {
             if modifier == RelocationModifier::SkipNextRelocation {
                 modifier = RelocationModifier::Normal;
                 continue;
             }
+            let r_type = rel.r_type(self.object.endian, false);
+            if A::is_thunkable_branch(r_type) {
+                let reach = A::relocation_from_raw(r_type)?.range.max as u64;
+                if reach < self.shortest_branch_reach
+                    && !self.is_branch_within_section(&rel, section.index)?
+                {
+                    self.shortest_branch_reach = reach;
+                }
+            }
             modifier = process_relocation::<A>(
                 self,
                 common,
-                rel,
-                self.object.section(section.index)?,
+                &rel,
+                section.index,
+                &section_header,
                 resources,
                 queue,
             )
@@ -3535,6 +4509,21 @@ impl<'data> ObjectLayoutState<'data> {
 
         self.sections[section_id.0] = SectionSlot::Loaded(section);
 
+        let start = self
+            .link_order_dependents
+            .partition_point(|(link, _)| link.0 < section_id.0);
+        for &(link, dependent) in &self.link_order_dependents[start..] {
+            if link != section_id {
+                break;
+            }
+            queue
+                .local_work
+                .push(WorkItem::LoadSection(SectionLoadRequest::new(
+                    self.file_id,
+                    dependent,
+                )));
+        }
+
         Ok(())
     }
 
@@ -3556,14 +4545,20 @@ impl<'data> ObjectLayoutState<'data> {
 
             num_frames += 1;
 
-            let frame_data_relocations = frame_data.relocations;
+            let frame_data_relocations = frame_data.relocations.clone();
 
             // Request loading of any sections/symbols referenced by the FDEs for our
             // section.
-            if let Some(eh_frame_section) = self.eh_frame_section {
-                for rel in frame_data_relocations {
-                    process_relocation::<A>(self, common, rel, eh_frame_section, resources, queue)?;
-                }
+            for rel_index in frame_data_relocations {
+                let Some(eh_frame) = &self.eh_frame_section else {
+                    break;
+                };
+                let (index, header) = (eh_frame.index, eh_frame.header);
+                let rel = eh_frame
+                    .relocations
+                    .get(rel_index)
+                    .context("Invalid .eh_frame relocation index")?;
+                process_relocation::<A>(self, common, &rel, index, &header, resources, queue)?;
             }
         }
 
@@ -3602,14 +4597,13 @@ impl<'data> ObjectLayoutState<'data> {
         if !symbol_db.args.strip_all {
             self.allocate_symtab_space(common, symbol_db, symbol_resolution_flags);
         }
-        let output_kind = symbol_db.args.output_kind();
         for slot in &mut self.sections {
             if let SectionSlot::Loaded(section) = slot {
                 allocate_resolution(
                     ValueFlags::ADDRESS,
                     section.resolution_kind,
                     &mut common.mem_sizes,
-                    output_kind,
+                    symbol_db.args,
                 );
             }
         }
@@ -3621,6 +4615,217 @@ impl<'data> ObjectLayoutState<'data> {
         common.allocate(part_id::EH_FRAME, self.eh_frame_size);
     }
 
+    fn allocate_thunks<A: Arch>(
+        &mut self,
+        common: &mut CommonGroupState,
+        executable_size: u64,
+        args: &Args,
+    ) -> Result {
+        let thunks_needed = branch_may_be_out_of_range(executable_size, self.shortest_branch_reach);
+        let fix_errata = args.fix_cortex_a53_843419;
+        if !thunks_needed && !fix_errata {
+            return Ok(());
+        }
+        // A single object can have more code in a part than a branch can reach, so rather than
+        // putting all of our thunks at the end, we split our sections in each part into runs that
+        // are small enough that every branch in a run can reach a thunk area placed after it.
+        let mut max_reach = self.shortest_branch_reach;
+        if fix_errata {
+            max_reach = max_reach.min(elf::ERRATUM_PATCH_REACH);
+        }
+        let max_run_size = max_reach / 2;
+        let mut runs: Vec<ThunkArea> = Vec::new();
+        let mut run_sizes: Vec<u64> = Vec::new();
+        for slot in &self.sections {
+            let SectionSlot::Loaded(section) = slot else {
+                continue;
+            };
+            let object_section = &self.object.section(section.index)?;
+            if !SectionFlags::from_header(object_section, self.object.endian)
+                .contains(shf::EXECINSTR)
+            {
+                continue;
+            }
+            let run_index = match runs.iter().rposition(|run| run.part_id == section.part_id) {
+                Some(index) if run_sizes[index] + section.capacity() <= max_run_size => index,
+                _ => {
+                    runs.push(ThunkArea {
+                        part_id: section.part_id,
+                        last_section: section.index,
+                        address: 0,
+                        targets: Vec::new(),
+                        erratum_sequences: Vec::new(),
+                        erratum_candidates: Vec::new(),
+                    });
+                    run_sizes.push(0);
+                    runs.len() - 1
+                }
+            };
+            run_sizes[run_index] += section.capacity();
+            let run = &mut runs[run_index];
+            run.last_section = section.index;
+            if fix_errata {
+                let mut offsets = Vec::new();
+                A::find_erratum_sequences(
+                    self.object.raw_section_data(object_section)?,
+                    section.part_id.alignment(),
+                    &mut offsets,
+                );
+                run.erratum_candidates
+                    .extend(offsets.into_iter().map(|offset| (section.index, offset)));
+            }
+            if !thunks_needed {
+                continue;
+            }
+            for rel in self.relocations(section.index)?.iter() {
+                let r_type = rel.r_type(self.object.endian, false);
+                if !A::is_thunkable_branch(r_type) {
+                    continue;
+                }
+                let reach = A::relocation_from_raw(r_type)?.range.max as u64;
+                if !branch_may_be_out_of_range(executable_size, reach)
+                    || self.is_branch_within_section(&rel, section.index)?
+                {
+                    continue;
+                }
+                let Some(symbol_index) = rel.symbol(self.object.endian, false) else {
+                    continue;
+                };
+                run.targets
+                    .push((symbol_index, rel.r_addend.get(self.object.endian)));
+            }
+        }
+        // We keep areas that turned out to be empty, since they take no space and lookups rely on
+        // each section being covered by the first following area in the same part.
+        for mut area in runs {
+            area.targets
+                .sort_unstable_by_key(|(symbol_index, addend)| (symbol_index.0, *addend));
+            area.targets.dedup();
+            common.allocate(area.part_id, area.capacity());
+            self.thunk_areas.push(area);
+        }
+        self.thunk_areas.sort_by_key(|area| area.last_section.0);
+        Ok(())
+    }
+
+    /// Allocates patches for our erratum candidates that are at addresses where they need them,
+    /// given the addresses that our sections would get, starting from `memory_offsets`. This
+    /// mirrors how `finalise_layout` assigns addresses. Returns whether we added any patches.
+    fn allocate_erratum_patches<A: Arch>(
+        &mut self,
+        memory_offsets: &mut OutputSectionPartMap<u64>,
+        output_sections: &OutputSections,
+        extra_sizes: &mut OutputSectionPartMap<u64>,
+    ) -> bool {
+        let section_order = self.link_order_section_order(output_sections);
+        let mut section_addresses = vec![0; self.sections.len()];
+        let mut thunk_areas = self.thunk_areas.iter().peekable();
+        for section_index in section_output_order(section_order.as_deref(), self.sections.len()) {
+            let SectionSlot::Loaded(sec) = &self.sections[section_index.0] else {
+                continue;
+            };
+            section_addresses[section_index.0] = *memory_offsets.get(sec.part_id);
+            memory_offsets.increment(sec.part_id, sec.capacity());
+            while let Some(area) = thunk_areas.next_if(|area| area.last_section == sec.index) {
+                memory_offsets.increment(sec.part_id, area.capacity());
+            }
+        }
+
+        let mut added = false;
+        for area in &mut self.thunk_areas {
+            let old_capacity = area.capacity();
+            let old_len = area.erratum_sequences.len();
+            area.erratum_candidates.retain(|&(section_index, offset)| {
+                if !A::needs_erratum_patch(section_addresses[section_index.0] + offset) {
+                    return true;
+                }
+                area.erratum_sequences.push((section_index, offset));
+                false
+            });
+            if area.erratum_sequences.len() != old_len {
+                area.erratum_sequences
+                    .sort_unstable_by_key(|(section_index, offset)| (section_index.0, *offset));
+                extra_sizes.increment(area.part_id, area.capacity() - old_capacity);
+                added = true;
+            }
+        }
+        added
+    }
+
+    /// Finds linker relaxations that delete bytes from our executable sections and reduces the
+    /// sizes of those sections accordingly.
+    fn shrink_sections<A: Arch>(
+        &mut self,
+        common: &mut CommonGroupState,
+        executable_size: u64,
+        tls_size: Option<u64>,
+        symbol_db: &SymbolDb<'data>,
+    ) -> Result {
+        let object = self.object;
+        let symbol_id_range = self.symbol_id_range;
+        for slot in &mut self.sections {
+            let SectionSlot::Loaded(section) = slot else {
+                continue;
+            };
+            let object_section = &object.section(section.index)?;
+            if !SectionFlags::from_header(object_section, object.endian).contains(shf::EXECINSTR) {
+                continue;
+            }
+            let relocations = object.relocations(section.index, &self.relocations)?;
+            if relocations.is_empty() {
+                continue;
+            }
+            let section_index = section.index;
+            let target_location = |rel: &elf::Rela| {
+                relocation_target_location(object, symbol_id_range, symbol_db, section_index, rel)
+            };
+            let context = ShrinkContext {
+                relax: symbol_db.args.relax,
+                executable_size,
+                tls_size,
+                target_location: &target_location,
+            };
+            let mut edits = Vec::new();
+            A::find_relaxation_edits(
+                object.raw_section_data(object_section)?,
+                &relocations,
+                &context,
+                &mut edits,
+            )
+            .with_context(|| {
+                format!(
+                    "Failed to relax section `{}` of {}",
+                    object.section_display_name(section_index),
+                    self.input
+                )
+            })?;
+            if edits.is_empty() {
+                continue;
+            }
+            let shrunk = ShrunkSection::new(section_index, edits);
+            let old_capacity = section.capacity();
+            section.size -= shrunk.total_deleted();
+            *common.mem_sizes.get_mut(section.part_id) -= old_capacity - section.capacity();
+            self.shrunk_sections.push(shrunk);
+        }
+        Ok(())
+    }
+
+    /// Returns whether `rel` refers to a symbol in the section that contains it. Such branches
+    /// should have been resolved by the assembler if they were out of range, so they never need a
+    /// thunk.
+    fn is_branch_within_section(
+        &self,
+        rel: &elf::Rela,
+        section_index: SectionIndex,
+    ) -> Result<bool> {
+        let Some(symbol_index) = rel.symbol(self.object.endian, false) else {
+            return Ok(false);
+        };
+        let symbol = &self.object.symbol(symbol_index)?;
+        Ok(self.object.symbol_section(symbol, symbol_index)? == Some(section_index))
+    }
+
     fn allocate_symtab_space(
         &self,
         common: &mut CommonGroupState,
@@ -3634,15 +4839,14 @@ impl<'data> ObjectLayoutState<'data> {
         let mut strings_size = 0;
         for ((sym_index, sym), sym_state) in self
             .object
-            .symbols
-            .enumerate()
+            .symbols()
             .zip(&symbol_resolution_flags[self.symbol_id_range().as_usize()])
         {
             let symbol_id = self.symbol_id_range.input_to_id(sym_index);
             if let Some(info) = SymbolCopyInfo::new(
                 self.object,
                 sym_index,
-                sym,
+                &sym,
                 symbol_id,
                 symbol_db,
                 sym_state.get(),
@@ -3651,7 +4855,7 @@ impl<'data> ObjectLayoutState<'data> {
                 // If we've decided to emit the symbol even though it's not referenced (because it's
                 // in a section we're emitting), then make sure we have a resolution for it.
                 sym_state.fetch_or(ResolutionFlags::DIRECT);
-                if sym.is_local() {
+                if info.is_local {
                     num_locals += 1;
                 } else {
                     num_globals += 1;
@@ -3659,7 +4863,7 @@ impl<'data> ObjectLayoutState<'data> {
                 strings_size += info.name.len() + 1;
             }
         }
-        let entry_size = size_of::<elf::SymtabEntry>() as u64;
+        let entry_size = symbol_db.args.elf_class().symtab_entry_size();
         common.allocate(part_id::SYMTAB_LOCAL, num_locals * entry_size);
         common.allocate(part_id::SYMTAB_GLOBAL, num_globals * entry_size);
         common.allocate(part_id::STRTAB, strings_size as u64);
@@ -3676,15 +4880,23 @@ impl<'data> ObjectLayoutState<'data> {
 
         let emitter = create_global_address_emitter(resources.symbol_resolution_flags);
 
-        let mut section_resolutions = Vec::with_capacity(self.sections.len());
-        for slot in &mut self.sections {
-            let resolution = match slot {
+        let section_order = self.link_order_section_order(resources.output_sections);
+        let mut section_resolutions = vec![SectionResolution::none(); self.sections.len()];
+        let mut thunk_areas = self.thunk_areas.iter_mut().peekable();
+        for section_index in section_output_order(section_order.as_deref(), self.sections.len()) {
+            let resolution = match &mut self.sections[section_index.0] {
                 SectionSlot::Loaded(sec) => {
                     let part_id = sec.part_id;
                     let address = *memory_offsets.get(part_id);
                     // TODO: We probably need to be able to handle sections that are ifuncs and sections
                     // that need a TLS GOT struct.
                     *memory_offsets.get_mut(part_id) += sec.capacity();
+                    while let Some(area) =
+                        thunk_areas.next_if(|area| area.last_section == sec.index)
+                    {
+                        area.address = *memory_offsets.get(part_id);
+                        memory_offsets.increment(part_id, area.capacity());
+                    }
                     SectionResolution { address }
                 }
                 &mut SectionSlot::LoadedDebugInfo(sec) => {
@@ -3701,19 +4913,18 @@ impl<'data> ObjectLayoutState<'data> {
                 }
                 _ => SectionResolution::none(),
             };
-            section_resolutions.push(resolution);
+            section_resolutions[section_index.0] = resolution;
         }
 
         for ((local_symbol_index, local_symbol), &resolution_flags) in self
             .object
-            .symbols
-            .enumerate()
+            .symbols()
             .zip(&resources.symbol_resolution_flags[symbol_id_range.as_usize()])
         {
             self.finalise_symbol(
                 resources,
                 resolution_flags,
-                local_symbol,
+                &local_symbol,
                 local_symbol_index,
                 &section_resolutions,
                 memory_offsets,
@@ -3732,14 +4943,69 @@ impl<'data> ObjectLayoutState<'data> {
             relocations: self.relocations,
             section_resolutions,
             symbol_id_range,
+            thunk_areas: self.thunk_areas,
+            shrunk_sections: self.shrunk_sections,
+            section_order,
         })
     }
 
+    /// Returns the order in which to lay out our sections if our SHF_LINK_ORDER sections need to be
+    /// reordered so that, within each part, they're in the same order as the sections that they
+    /// link to. Link-order sections from different objects stay in the order of their objects,
+    /// which matches the order of the sections that they link to, provided those are in the same
+    /// output section.
+    fn link_order_section_order(
+        &self,
+        output_sections: &OutputSections,
+    ) -> Option<Vec<object::SectionIndex>> {
+        let mut link_order_sections = self
+            .sections
+            .iter()
+            .filter_map(|slot| match slot {
+                SectionSlot::Loaded(sec) => sec
+                    .link_order_target
+                    .map(|target| (sec.part_id, sec.index, target)),
+                _ => None,
+            })
+            .collect_vec();
+        if link_order_sections.len() < 2 {
+            return None;
+        }
+
+        // Where each part's link-order sections currently are. These are the positions that we'll
+        // put the sorted sections into.
+        let mut positions = link_order_sections
+            .iter()
+            .map(|(part_id, index, _)| (*part_id, *index))
+            .collect_vec();
+        positions.sort_by_key(|(part_id, _)| *part_id);
+
+        link_order_sections.sort_by_key(|(part_id, _, target)| {
+            let output_index = match self.sections.get(target.0) {
+                Some(SectionSlot::Loaded(target_section)) => {
+                    output_sections.output_index_of_section(target_section.output_section_id())
+                }
+                _ => None,
+            };
+            (*part_id, output_index.unwrap_or(u16::MAX), target.0)
+        });
+
+        let mut order = (0..self.sections.len())
+            .map(object::SectionIndex)
+            .collect_vec();
+        let mut is_reordered = false;
+        for ((_, position), (_, index, _)) in positions.iter().zip(&link_order_sections) {
+            order[position.0] = *index;
+            is_reordered |= position != index;
+        }
+        is_reordered.then_some(order)
+    }
+
     fn finalise_symbol<'scope>(
         &self,
         resources: &FinaliseLayoutResources<'scope, 'data>,
         resolution_flags: ResolutionFlags,
-        local_symbol: &object::elf::Sym64<LittleEndian>,
+        local_symbol: &object::elf::Sym64<Endianness>,
         local_symbol_index: object::SymbolIndex,
         section_resolutions: &[SectionResolution],
         memory_offsets: &mut OutputSectionPartMap<u64>,
@@ -3763,7 +5029,7 @@ impl<'data> ObjectLayoutState<'data> {
         &self,
         resources: &FinaliseLayoutResources<'scope, 'data>,
         resolution_flags: ResolutionFlags,
-        local_symbol: &object::elf::Sym64<LittleEndian>,
+        local_symbol: &object::elf::Sym64<Endianness>,
         local_symbol_index: object::SymbolIndex,
         section_resolutions: &[SectionResolution],
         memory_offsets: &mut OutputSectionPartMap<u64>,
@@ -3776,7 +5042,7 @@ impl<'data> ObjectLayoutState<'data> {
             return Ok(None);
         }
 
-        let e = LittleEndian;
+        let e = self.object.endian;
         let value_flags = resources.symbol_db.local_symbol_value_flags(symbol_id);
 
         let raw_value = if let Some(section_index) = self
@@ -3784,7 +5050,10 @@ impl<'data> ObjectLayoutState<'data> {
             .symbol_section(local_symbol, local_symbol_index)?
         {
             if let Some(section_address) = section_resolutions[section_index.0].address() {
-                local_symbol.st_value(e) + section_address
+                let offset = local_symbol.st_value(e);
+                section_address
+                    + shrunk_section(&self.shrunk_sections, section_index)
+                        .map_or(offset, |shrunk| shrunk.output_offset(offset))
             } else {
                 match get_merged_string_output_address(
                     local_symbol_index,
@@ -3812,7 +5081,7 @@ impl<'data> ObjectLayoutState<'data> {
                 }
             }
         } else if local_symbol.is_common(e) {
-            let common = CommonSymbol::new(local_symbol)?;
+            let common = CommonSymbol::new(local_symbol, self.object.endian)?;
             let offset = memory_offsets
                 .get_mut(output_section_id::BSS.part_id_with_alignment(common.alignment));
             let address = *offset;
@@ -3826,7 +5095,11 @@ impl<'data> ObjectLayoutState<'data> {
         if value_flags.contains(ValueFlags::DYNAMIC) {
             // This is an undefined weak symbol. Emit it as a dynamic symbol so that it can be
             // overridden at runtime.
-            let dyn_sym_index = take_dynsym_index(memory_offsets, resources.section_layouts)?;
+            let dyn_sym_index = take_dynsym_index(
+                memory_offsets,
+                resources.section_layouts,
+                resources.symbol_db.args,
+            )?;
             dynamic_symbol_index = Some(
                 NonZeroU32::new(dyn_sym_index)
                     .context("Attempted to create dynamic symbol index 0")?,
@@ -3839,6 +5112,7 @@ impl<'data> ObjectLayoutState<'data> {
             dynamic_symbol_index,
             value_flags,
             memory_offsets,
+            resources.symbol_db.args,
         )))
     }
 
@@ -3848,8 +5122,8 @@ impl<'data> ObjectLayoutState<'data> {
         resources: &GraphResources<'data, 'scope>,
         queue: &mut LocalWorkQueue,
     ) -> Result {
-        for (sym_index, sym) in self.object.symbols.enumerate() {
-            if can_export_symbol(sym) {
+        for (sym_index, sym) in self.object.symbols() {
+            if can_export_symbol(&sym, self.object.endian) {
                 let symbol_id = self.symbol_id_range().input_to_id(sym_index);
 
                 if !resources.symbol_db.is_canonical(symbol_id) {
@@ -3898,13 +5172,43 @@ impl<'data> ObjectLayoutState<'data> {
         Ok(())
     }
 
-    fn relocations(&self, index: SectionIndex) -> Result<&'data [elf::Rela]> {
+    fn relocations(&self, index: SectionIndex) -> Result<elf::RelocationList<'data>> {
         self.object.relocations(index, &self.relocations)
     }
+
+    fn first_reference(
+        &self,
+        definition: SymbolId,
+        symbol_db: &SymbolDb<'data>,
+    ) -> Option<InputLocation> {
+        let e = self.object.endian;
+        self.sections
+            .iter()
+            .enumerate()
+            .filter(|(_, slot)| matches!(slot, SectionSlot::Loaded(..)))
+            .find_map(|(i, _)| {
+                let section_index = SectionIndex(i);
+                let rel = self.relocations(section_index).ok()?.iter().find(|rel| {
+                    rel.symbol(e, false).is_some_and(|local_sym_index| {
+                        symbol_db.definition(self.symbol_id_range.input_to_id(local_sym_index))
+                            == definition
+                    })
+                })?;
+                Some(InputLocation {
+                    file_id: self.file_id,
+                    section_index,
+                    offset: rel.r_offset.get(e),
+                })
+            })
+    }
 }
 
 pub(crate) struct SymbolCopyInfo<'data> {
     pub(crate) name: &'data [u8],
+
+    /// Whether the symbol should be written as a local. This is the case for local symbols and for
+    /// global symbols that `--exclude-libs` made local.
+    pub(crate) is_local: bool,
 }
 
 impl<'data> SymbolCopyInfo<'data> {
@@ -3921,7 +5225,7 @@ impl<'data> SymbolCopyInfo<'data> {
         symbol_state: ResolutionFlags,
         sections: &[SectionSlot],
     ) -> Option<SymbolCopyInfo<'data>> {
-        let e = LittleEndian;
+        let e = object.endian;
         if !symbol_db.is_canonical(symbol_id) || sym.is_undefined(e) {
             return None;
         }
@@ -3941,21 +5245,34 @@ impl<'data> SymbolCopyInfo<'data> {
         // checks. That's also the reason why we return the symbol name, so that the caller, if it
         // needs the name, doesn't have a go and read it again.
         let name = object.symbol_name(sym).ok()?;
-        if name.is_empty()
-            || (sym.is_local() && name.starts_with(b".L"))
-            || is_mapping_symbol_name(name)
-        {
+        if name.is_empty() || is_mapping_symbol_name(name) {
+            return None;
+        }
+
+        let is_local = sym.is_local() || symbol_db.is_local_due_to_exclude_libs(symbol_id);
+        if sym.is_local() {
+            let discard = match symbol_db.args.discard {
+                DiscardMode::Temporary => name.starts_with(b".L"),
+                DiscardMode::All => true,
+                DiscardMode::None => false,
+            };
+            if discard {
+                return None;
+            }
+        }
+
+        if !symbol_db.is_retained_in_symtab(name) {
             return None;
         }
 
-        Some(SymbolCopyInfo { name })
+        Some(SymbolCopyInfo { name, is_local })
     }
 }
 
 /// Returns whether the supplied symbol can be exported when we're outputting a shared object.
-pub(crate) fn can_export_symbol(sym: &crate::elf::SymtabEntry) -> bool {
+pub(crate) fn can_export_symbol(sym: &crate::elf::SymtabEntry, e: Endianness) -> bool {
     let visibility = sym.st_visibility();
-    !sym.is_undefined(LittleEndian)
+    !sym.is_undefined(e)
         && !sym.is_local()
         && (visibility == object::elf::STV_DEFAULT || visibility == object::elf::STV_PROTECTED)
 }
@@ -3969,9 +5286,9 @@ fn process_eh_frame_data<'data, A: Arch>(
     queue: &mut LocalWorkQueue,
 ) -> Result {
     let eh_frame_section = object.object.section(eh_frame_section_index)?;
-    let data = object.object.raw_section_data(eh_frame_section)?;
+    let data = object.object.raw_section_data(&eh_frame_section)?;
     const PREFIX_LEN: usize = size_of::<elf::EhFrameEntryPrefix>();
-    let e = LittleEndian;
+    let e = object.object.endian;
     let relocations = object.relocations(eh_frame_section_index)?;
     let mut rel_iter = relocations.iter().enumerate().peekable();
     let mut offset = 0;
@@ -3981,8 +5298,7 @@ fn process_eh_frame_data<'data, A: Arch>(
         // no guarantee that the object is aligned within the archive to any more
         // than 2 bytes, so we can't rely on alignment here. Archives are annoying!
         // See https://www.airs.com/blog/archives/170
-        let prefix: elf::EhFrameEntryPrefix =
-            bytemuck::pod_read_unaligned(&data[offset..offset + PREFIX_LEN]);
+        let prefix = elf::EhFrameEntryPrefix::read(e, &data[offset..offset + PREFIX_LEN]);
         let size = size_of_val(&prefix.length) + prefix.length as usize;
         let next_offset = offset + size;
         if next_offset > data.len() {
@@ -4003,7 +5319,15 @@ fn process_eh_frame_data<'data, A: Arch>(
                 }
                 // We currently always load all CIEs, so any relocations found in CIEs always need
                 // to be processed.
-                process_relocation::<A>(object, common, rel, eh_frame_section, resources, queue)?;
+                process_relocation::<A>(
+                    object,
+                    common,
+                    rel,
+                    eh_frame_section_index,
+                    &eh_frame_section,
+                    resources,
+                    queue,
+                )?;
                 if let Some(local_sym_index) = rel.symbol(e, false) {
                     let local_symbol_id = file_symbol_id_range.input_to_id(local_sym_index);
                     let definition = resources.symbol_db.definition(local_symbol_id);
@@ -4035,7 +5359,7 @@ fn process_eh_frame_data<'data, A: Arch>(
                     if is_pc_begin {
                         if let Some(index) = rel.symbol(e, false) {
                             let elf_symbol = object.object.symbol(index)?;
-                            section_index = object.object.symbol_section(elf_symbol, index)?;
+                            section_index = object.object.symbol_section(&elf_symbol, index)?;
                         }
                     }
                     rel_end_index = rel_index + 1;
@@ -4054,7 +5378,7 @@ fn process_eh_frame_data<'data, A: Arch>(
                     let previous_frame_for_section = unloaded.last_frame_index.replace(frame_index);
 
                     common.exception_frames.push(ExceptionFrame {
-                        relocations: &relocations[rel_start_index..rel_end_index],
+                        relocations: rel_start_index..rel_end_index,
                         frame_size: size as u32,
                         previous_frame_for_section,
                     });
@@ -4067,6 +5391,12 @@ fn process_eh_frame_data<'data, A: Arch>(
     // Allocate space for any remaining bytes in .eh_frame that aren't large enough to constitute an
     // actual entry. crtend.o has a single u32 equal to 0 as an end marker.
     object.eh_frame_size += (data.len() - offset) as u64;
+    drop(rel_iter);
+    object.eh_frame_section = Some(Box::new(EhFrameSection {
+        index: eh_frame_section_index,
+        header: eh_frame_section,
+        relocations,
+    }));
     Ok(())
 }
 
@@ -4074,35 +5404,24 @@ fn process_gnu_property_note(
     object: &mut ObjectLayoutState,
     note_section_index: object::SectionIndex,
 ) -> Result {
-    let section = object.object.section(note_section_index)?;
-    let e = LittleEndian;
-
-    let Some(notes) = section.notes(e, object.object.data)? else {
-        return Ok(());
-    };
-
-    for note in notes {
-        for gnu_property in note?
-            .gnu_properties(e)
-            .ok_or(anyhow!("Invalid type of .note.gnu.property"))?
-        {
-            let gnu_property = gnu_property?;
+    let e = object.object.endian;
 
+    object
+        .object
+        .for_each_gnu_property(note_section_index, |ptype, data| {
             // Right now, skip all properties other than those with size equal to 4.
             // There are existing properties, but unused right now:
             // GNU_PROPERTY_STACK_SIZE, GNU_PROPERTY_NO_COPY_ON_PROTECTED
             // TODO: support in the future
-            if gnu_property.pr_data().len() != 4 {
-                continue;
-            }
+            let Ok(data) = <[u8; 4]>::try_from(data) else {
+                return Ok(());
+            };
             object.gnu_property_notes.push(GnuProperty {
-                ptype: gnu_property.pr_type(),
-                data: gnu_property.data_u32(e)?,
+                ptype,
+                data: e.read_u32_bytes(data),
             });
-        }
-    }
-
-    Ok(())
+            Ok(())
+        })
 }
 
 /// A "common information entry". This is part of the .eh_frame data in ELF.
@@ -4128,8 +5447,7 @@ struct CommonSymbol {
 }
 
 impl CommonSymbol {
-    fn new(local_symbol: &crate::elf::SymtabEntry) -> Result<CommonSymbol> {
-        let e = LittleEndian;
+    fn new(local_symbol: &crate::elf::SymtabEntry, e: Endianness) -> Result<CommonSymbol> {
         debug_assert!(local_symbol.is_common(e));
         // Common symbols misuse the value field (which we access via `address()`) to store the
         // alignment.
@@ -4161,6 +5479,7 @@ fn create_resolution(
     dynamic_symbol_index: Option<NonZeroU32>,
     value_flags: ValueFlags,
     memory_offsets: &mut OutputSectionPartMap<u64>,
+    args: &Args,
 ) -> Resolution {
     let mut resolution = Resolution {
         raw_value,
@@ -4171,14 +5490,14 @@ fn create_resolution(
         value_flags,
     };
     if res_kind.contains(ResolutionFlags::PLT) {
-        let plt_address = allocate_plt(memory_offsets);
+        let plt_address = allocate_plt(memory_offsets, args.plt_entry_size());
         resolution.plt_address = Some(plt_address);
         if value_flags.contains(ValueFlags::DYNAMIC) {
             resolution.raw_value = plt_address.get();
         }
-        resolution.got_address = Some(allocate_got(1, memory_offsets));
+        resolution.got_address = Some(allocate_got(1, memory_offsets, args));
     } else if res_kind.contains(ResolutionFlags::GOT) {
-        resolution.got_address = Some(allocate_got(1, memory_offsets));
+        resolution.got_address = Some(allocate_got(1, memory_offsets, args));
     } else {
         // Handle the TLS GOT addresses where we can combine up to 3 different access methods.
         let mut num_got_slots = 0;
@@ -4192,21 +5511,25 @@ fn create_resolution(
             num_got_slots += 2;
         }
         if num_got_slots > 0 {
-            resolution.got_address = Some(allocate_got(num_got_slots, memory_offsets));
+            resolution.got_address = Some(allocate_got(num_got_slots, memory_offsets, args));
         }
     }
     resolution
 }
 
-fn allocate_got(num_entries: u64, memory_offsets: &mut OutputSectionPartMap<u64>) -> NonZeroU64 {
+fn allocate_got(
+    num_entries: u64,
+    memory_offsets: &mut OutputSectionPartMap<u64>,
+    args: &Args,
+) -> NonZeroU64 {
     let got_address = NonZeroU64::new(*memory_offsets.get(part_id::GOT)).unwrap();
-    memory_offsets.increment(part_id::GOT, elf::GOT_ENTRY_SIZE * num_entries);
+    memory_offsets.increment(part_id::GOT, args.got_entry_size() * num_entries);
     got_address
 }
 
-fn allocate_plt(memory_offsets: &mut OutputSectionPartMap<u64>) -> NonZeroU64 {
+fn allocate_plt(memory_offsets: &mut OutputSectionPartMap<u64>, entry_size: u64) -> NonZeroU64 {
     let plt_address = NonZeroU64::new(*memory_offsets.get(part_id::PLT_GOT)).unwrap();
-    memory_offsets.increment(part_id::PLT_GOT, elf::PLT_ENTRY_SIZE);
+    memory_offsets.increment(part_id::PLT_GOT, entry_size);
     plt_address
 }
 
@@ -4228,12 +5551,45 @@ impl<'data> resolution::ResolvedFile<'data> {
     }
 }
 
+impl ThunkArea {
+    /// How much space our thunks and erratum patches take up, rounded up so that the next object's
+    /// sections in the same part remain aligned.
+    pub(crate) fn capacity(&self) -> u64 {
+        self.part_id.alignment().align_up(
+            self.targets.len() as u64 * elf::THUNK_SIZE
+                + self.erratum_sequences.len() as u64 * elf::ERRATUM_PATCH_SIZE,
+        )
+    }
+
+    /// Returns the address of the patch for the erratum sequence with the specified index in
+    /// `erratum_sequences`.
+    pub(crate) fn erratum_patch_address(&self, index: usize) -> u64 {
+        self.address
+            + self.targets.len() as u64 * elf::THUNK_SIZE
+            + index as u64 * elf::ERRATUM_PATCH_SIZE
+    }
+
+    /// Returns the address of the thunk that branches to `symbol_index` plus `addend`, if we have
+    /// one.
+    pub(crate) fn thunk_address(
+        &self,
+        symbol_index: object::SymbolIndex,
+        addend: i64,
+    ) -> Option<u64> {
+        let index = self
+            .targets
+            .binary_search_by_key(&(symbol_index.0, addend), |(s, a)| (s.0, *a))
+            .ok()?;
+        Some(self.address + index as u64 * elf::THUNK_SIZE)
+    }
+}
+
 impl Resolution {
     pub(crate) fn got_address(&self) -> Result<u64> {
         Ok(self.got_address.context("Missing GOT address")?.get())
     }
 
-    pub(crate) fn tlsgd_got_address(&self) -> Result<u64> {
+    pub(crate) fn tlsgd_got_address(&self, got_entry_size: u64) -> Result<u64> {
         debug_assert_bail!(
             self.resolution_flags
                 .contains(ResolutionFlags::GOT_TLS_MODULE),
@@ -4245,12 +5601,12 @@ impl Resolution {
             .resolution_flags
             .contains(ResolutionFlags::GOT_TLS_OFFSET)
         {
-            got_address += elf::GOT_ENTRY_SIZE;
+            got_address += got_entry_size;
         }
         Ok(got_address)
     }
 
-    pub(crate) fn tls_descriptor_got_address(&self) -> Result<u64> {
+    pub(crate) fn tls_descriptor_got_address(&self, got_entry_size: u64) -> Result<u64> {
         debug_assert_bail!(
             self.resolution_flags
                 .contains(ResolutionFlags::GOT_TLS_DESCRIPTOR),
@@ -4263,13 +5619,13 @@ impl Resolution {
             .resolution_flags
             .contains(ResolutionFlags::GOT_TLS_OFFSET)
         {
-            got_address += elf::GOT_ENTRY_SIZE;
+            got_address += got_entry_size;
         }
         if self
             .resolution_flags
             .contains(ResolutionFlags::GOT_TLS_MODULE)
         {
-            got_address += 2 * elf::GOT_ENTRY_SIZE;
+            got_address += 2 * got_entry_size;
         }
 
         Ok(got_address)
@@ -4337,70 +5693,274 @@ impl Resolution {
                 return Ok(r);
             }
         }
+        if !object_layout.shrunk_sections.is_empty()
+            && let Some(shrunk) = object_layout.shrunk_section_for_section_symbol(symbol_index)
+        {
+            return Ok(self
+                .raw_value
+                .wrapping_add(shrunk.output_offset(addend as u64)));
+        }
         Ok(self.raw_value.wrapping_add(addend as u64))
     }
 }
 
+/// Returns the shrunk section with index `section_index`, if it was shrunk. `shrunk_sections` must
+/// be sorted by section index.
+fn shrunk_section(
+    shrunk_sections: &[ShrunkSection],
+    section_index: SectionIndex,
+) -> Option<&ShrunkSection> {
+    shrunk_sections
+        .binary_search_by_key(&section_index.0, |shrunk| shrunk.section_index.0)
+        .ok()
+        .map(|i| &shrunk_sections[i])
+}
+
 fn layout_section_parts(
     sizes: &OutputSectionPartMap<u64>,
     output_sections: &OutputSections,
     args: &Args,
-) -> OutputSectionPartMap<OutputRecordLayout> {
+) -> Result<OutputSectionPartMap<OutputRecordLayout>> {
+    let section_starts = requested_section_starts(output_sections, args);
+    // The TLS segment starts at `.tdata`, even if it's empty, so `.tdata` needs to be aligned to
+    // the alignment of the whole segment. Otherwise the thread pointer offsets that we compute
+    // from the start of `.tdata` wouldn't match where the runtime puts the TLS block.
+    let tls_alignment = sizes
+        .section_alignment(output_section_id::TDATA)
+        .max(sizes.section_alignment(output_section_id::TBSS));
     let mut file_offset = 0;
     let mut mem_offset = output_sections.base_address;
     let mut current_seg_id = None;
+    let mut current_section_id = None;
+    let mut error = None;
     let mut nonalloc_mem_offsets: OutputSectionMap<u64> =
         OutputSectionMap::with_size(output_sections.num_sections());
 
-    sizes.output_order_map(output_sections, |part_id, section_alignment, part_size| {
-        let section_id = part_id.output_section_id();
-        let section_flags = output_sections.section_flags(section_id);
-        let mem_size = *part_size;
-        // Note, we align up even if our size is zero, otherwise our section will start at an
-        // unaligned address.
-        file_offset = section_alignment.align_up_usize(file_offset);
-
-        if section_flags.contains(shf::ALLOC) {
-            mem_offset = section_alignment.align_up(mem_offset);
-            let seg_id = output_sections.loadable_segment_id_for(section_id);
-            if current_seg_id != seg_id {
-                current_seg_id = seg_id;
-                let segment_alignment = seg_id.map_or(alignment::MIN, |s| s.alignment(args));
-                mem_offset = segment_alignment.align_modulo(file_offset as u64, mem_offset);
-            }
-            let file_size = if output_sections.has_data_in_file(section_id) {
-                mem_size as usize
+    let layouts =
+        sizes.output_order_map(output_sections, |part_id, section_alignment, part_size| {
+            let section_id = part_id.output_section_id();
+            let section_flags = output_sections.section_flags(section_id);
+            let mem_size = *part_size;
+            let is_first_part = current_section_id != Some(section_id);
+            current_section_id = Some(section_id);
+            let start_alignment = if section_id == output_section_id::TDATA && is_first_part {
+                section_alignment.max(tls_alignment)
             } else {
-                0
+                section_alignment
             };
+            // Note, we align up even if our size is zero, otherwise our section will start at an
+            // unaligned address.
+            file_offset = start_alignment.align_up_usize(file_offset);
 
-            let section_layout = OutputRecordLayout {
-                alignment: section_alignment,
-                file_offset,
-                mem_offset,
-                file_size,
-                mem_size,
-            };
-            file_offset += file_size;
-            mem_offset += mem_size;
-            section_layout
-        } else {
-            let section_id = part_id.output_section_id();
-            let mem_offset = section_alignment.align_up(*nonalloc_mem_offsets.get(section_id));
+            if section_flags.contains(shf::ALLOC) {
+                mem_offset = start_alignment.align_up(mem_offset);
+                let seg_id = output_sections.loadable_segment_id_for(section_id);
+                let segment_alignment = seg_id.map_or(alignment::MIN, |s| s.alignment(args));
+                if current_seg_id != seg_id {
+                    current_seg_id = seg_id;
+                    mem_offset = segment_alignment.align_modulo(file_offset as u64, mem_offset);
+                }
 
-            *nonalloc_mem_offsets.get_mut(section_id) += mem_size;
+                if let Some(address) = section_starts.get(section_id).filter(|_| is_first_part) {
+                    match check_section_address(
+                        address,
+                        section_id,
+                        section_alignment,
+                        output_sections,
+                    ) {
+                        Ok(()) => {
+                            // The section either starts its segment or starts a new segment of its
+                            // own, so we only need to pad the file enough for the file offset to
+                            // be congruent with the address.
+                            file_offset += (address.wrapping_sub(file_offset as u64)
+                                & segment_alignment.mask())
+                                as usize;
+                            mem_offset = address;
+                        }
+                        Err(e) => {
+                            error.get_or_insert(e);
+                        }
+                    }
+                }
 
-            let section_layout = OutputRecordLayout {
-                alignment: section_alignment,
-                file_offset,
-                mem_offset,
-                file_size: mem_size as usize,
-                mem_size,
-            };
-            file_offset += mem_size as usize;
-            section_layout
+                let file_size = if output_sections.has_data_in_file(section_id) {
+                    mem_size as usize
+                } else {
+                    0
+                };
+
+                let section_layout = OutputRecordLayout {
+                    alignment: section_alignment,
+                    file_offset,
+                    mem_offset,
+                    file_size,
+                    mem_size,
+                };
+                file_offset += file_size;
+                mem_offset += mem_size;
+                section_layout
+            } else {
+                let section_id = part_id.output_section_id();
+                let mem_offset = section_alignment.align_up(*nonalloc_mem_offsets.get(section_id));
+
+                *nonalloc_mem_offsets.get_mut(section_id) += mem_size;
+
+                let section_layout = OutputRecordLayout {
+                    alignment: section_alignment,
+                    file_offset,
+                    mem_offset,
+                    file_size: mem_size as usize,
+                    mem_size,
+                };
+                file_offset += mem_size as usize;
+                section_layout
+            }
+        });
+
+    match error {
+        Some(error) => Err(error),
+        None => Ok(layouts),
+    }
+}
+
+/// Returns the addresses requested via `--section-start` and similar for each alloc output section
+/// that we're going to emit.
+fn requested_section_starts(
+    output_sections: &OutputSections,
+    args: &Args,
+) -> OutputSectionMap<Option<u64>> {
+    let mut starts = OutputSectionMap::with_size(output_sections.num_sections());
+    for (name, address) in &args.section_start {
+        for index in 0..output_sections.num_sections() {
+            let section_id = OutputSectionId::from_usize(index);
+            if output_sections.name(section_id).bytes() == name.as_bytes()
+                && output_sections.has_section_header(section_id)
+                && output_sections
+                    .section_flags(section_id)
+                    .contains(shf::ALLOC)
+            {
+                *starts.get_mut(section_id) = Some(*address);
+            }
         }
-    })
+    }
+    starts
+}
+
+/// Returns the sections that were given an address, but which aren't the first section in their
+/// LOAD segment. Each of these starts an extra LOAD segment, since the address might be far away
+/// from, or even before, the sections that precede it.
+fn sections_starting_extra_load_segments(
+    output_sections: &OutputSections,
+    args: &Args,
+) -> Vec<OutputSectionId> {
+    use output_section_id::OrderEvent;
+
+    if args.section_start.is_empty() {
+        return Vec::new();
+    }
+    let section_starts = requested_section_starts(output_sections, args);
+    let mut segment_has_sections = false;
+    let mut section_ids = Vec::new();
+    for event in output_sections.sections_and_segments_events() {
+        match event {
+            OrderEvent::SegmentStart(segment_id)
+                if segment_id.segment_type() == object::elf::PT_LOAD =>
+            {
+                segment_has_sections = false;
+            }
+            OrderEvent::Section(section_id) if output_sections.has_section_header(section_id) => {
+                if segment_has_sections && section_starts.get(section_id).is_some() {
+                    section_ids.push(section_id);
+                }
+                segment_has_sections = true;
+            }
+            _ => {}
+        }
+    }
+    section_ids
+}
+
+/// Checks that `address` is suitably aligned for `section_id`.
+fn check_section_address(
+    address: u64,
+    section_id: OutputSectionId,
+    alignment: Alignment,
+    output_sections: &OutputSections,
+) -> Result {
+    if alignment.align_up(address) != address {
+        bail!(
+            "Address 0x{address:x} for section `{}` isn't aligned to its alignment of {}",
+            output_sections.display_name(section_id),
+            alignment.value()
+        );
+    }
+    Ok(())
+}
+
+/// Checks that no two alloc sections occupy the same addresses. Sections are normally laid out one
+/// after the other, but sections placed at requested addresses can end up anywhere.
+fn check_for_overlapping_sections(
+    section_layouts: &OutputSectionMap<OutputRecordLayout>,
+    output_sections: &OutputSections,
+) -> Result {
+    use output_section_id::OrderEvent;
+
+    let mut ranges = output_sections
+        .sections_and_segments_events()
+        .filter_map(|event| match event {
+            OrderEvent::Section(section_id) => Some(section_id),
+            _ => None,
+        })
+        .filter(|section_id| {
+            (output_sections.has_section_header(*section_id)
+                || header_description(*section_id).is_some())
+                && output_sections
+                    .section_flags(*section_id)
+                    .contains(shf::ALLOC)
+        })
+        .map(|section_id| (section_id, section_layouts.get(section_id)))
+        .filter(|(_, layout)| layout.mem_size > 0)
+        .collect_vec();
+    ranges.sort_by_key(|(_, layout)| layout.mem_offset);
+
+    let describe = |section_id| {
+        header_description(section_id).map_or_else(
+            || format!("section `{}`", output_sections.display_name(section_id)),
+            str::to_owned,
+        )
+    };
+
+    let mut previous: Option<(OutputSectionId, u64, u64)> = None;
+    for (section_id, layout) in ranges {
+        let address = layout.mem_offset;
+        let end = address + layout.mem_size;
+        if let Some((previous_id, previous_start, previous_end)) = previous {
+            if address < previous_end {
+                let description = describe(section_id);
+                let mut chars = description.chars();
+                let first = chars.next().unwrap_or_default().to_ascii_uppercase();
+                bail!(
+                    "{first}{} at 0x{address:x} overlaps {} at \
+                     0x{previous_start:x}..0x{previous_end:x}",
+                    chars.as_str(),
+                    describe(previous_id),
+                );
+            }
+        }
+        previous = Some((section_id, address, end));
+    }
+    Ok(())
+}
+
+/// Returns a description of `section_id` if it's one of the sections into which we write headers.
+/// These don't have names of their own that we could report.
+fn header_description(section_id: OutputSectionId) -> Option<&'static str> {
+    match section_id {
+        output_section_id::FILE_HEADER => Some("the ELF header"),
+        output_section_id::PROGRAM_HEADERS => Some("the program headers"),
+        output_section_id::SECTION_HEADERS => Some("the section headers"),
+        _ => None,
+    }
 }
 
 impl<'data> DynamicLayoutState<'data> {
@@ -4419,13 +5979,24 @@ impl<'data> DynamicLayoutState<'data> {
 
         common.allocate(
             part_id::DYNAMIC,
-            size_of::<crate::elf::DynamicEntry>() as u64,
+            resources.symbol_db.args.elf_class().dynamic_entry_size(),
         );
 
         common.allocate(part_id::DYNSTR, self.lib_name.len() as u64 + 1);
 
         self.request_all_undefined_symbols(resources, queue);
 
+        // Shared objects can also ask us to warn about references to their symbols, e.g. glibc
+        // does this for `gets`. A `.gnu.warning` section without a symbol only warns if it gets
+        // copied into the output, which sections from shared objects never do.
+        for i in 0..self.object.num_sections() {
+            if let Some((symbol_name, message)) = read_gnu_warning(self.object, SectionIndex(i))?
+                && !symbol_name.is_empty()
+            {
+                resources.record_symbol_warning(symbol_name, message);
+            }
+        }
+
         Ok(())
     }
 
@@ -4467,19 +6038,19 @@ impl<'data> DynamicLayoutState<'data> {
         Ok(())
     }
 
-    fn finalise_sizes(&mut self, common: &mut CommonGroupState<'data>) -> Result {
-        self.allocate_for_copy_relocations(common)?;
+    fn finalise_sizes(&mut self, common: &mut CommonGroupState<'data>, args: &Args) -> Result {
+        self.allocate_for_copy_relocations(common, args)?;
         self.allocate_for_versions(common)
     }
 
     fn allocate_for_versions(&mut self, common: &mut CommonGroupState<'data>) -> Result {
-        let e = LittleEndian;
+        let e = self.object.endian;
         let mut version_count = 0;
 
         if let Some((mut verdef_iterator, link)) = self.object.verdef.clone() {
             let defs = verdef_iterator.clone();
 
-            let strings = self.object.sections.strings(e, self.object.data, link)?;
+            let strings = self.object.strings(link)?;
             let mut base_size = 0;
             while let Some((verdef, mut aux_iterator)) = verdef_iterator.next()? {
                 let version_index = verdef.vd_ndx.get(e);
@@ -4537,13 +6108,13 @@ impl<'data> DynamicLayoutState<'data> {
         &mut self,
         symbol_resolution_flags: &[AtomicResolutionFlags],
     ) {
-        for (i, symbol) in self.object.symbols.iter().enumerate() {
-            let address = symbol.st_value(LittleEndian);
+        for (index, symbol) in self.object.symbols() {
+            let address = symbol.st_value(self.object.endian);
             let Some(info) = self.copy_relocations.get_mut(&address) else {
                 continue;
             };
 
-            let symbol_id = self.symbol_id_range.offset_to_id(i);
+            let symbol_id = self.symbol_id_range.offset_to_id(index.0);
 
             if symbol.is_weak() || !info.is_weak || info.symbol_id == symbol_id {
                 continue;
@@ -4569,7 +6140,11 @@ impl<'data> DynamicLayoutState<'data> {
         Ok(())
     }
 
-    fn allocate_for_copy_relocations(&self, common: &mut CommonGroupState<'data>) -> Result {
+    fn allocate_for_copy_relocations(
+        &self,
+        common: &mut CommonGroupState<'data>,
+        args: &Args,
+    ) -> Result {
         for value in self.copy_relocations.values() {
             let symbol_id = value.symbol_id;
 
@@ -4577,23 +6152,23 @@ impl<'data> DynamicLayoutState<'data> {
                 .object
                 .symbol(self.symbol_id_range().id_to_input(symbol_id))?;
 
-            let section_index = symbol.st_shndx(LittleEndian);
+            let section_index = symbol.st_shndx(self.object.endian);
 
             let section = self
                 .object
                 .section(SectionIndex(usize::from(section_index)))?;
 
-            let alignment = Alignment::new(self.object.section_alignment(section)?)?;
+            let alignment = Alignment::new(self.object.section_alignment(&section)?)?;
 
             // Allocate space in BSS for the copy of the symbol.
-            let st_size = symbol.st_size(LittleEndian);
+            let st_size = symbol.st_size(self.object.endian);
             common.allocate(
                 output_section_id::BSS.part_id_with_alignment(alignment),
                 st_size,
             );
 
             // Allocate space required for the copy relocation itself.
-            common.allocate(part_id::RELA_DYN_GENERAL, crate::elf::RELA_ENTRY_SIZE);
+            common.allocate(part_id::RELA_DYN_GENERAL, args.rela_entry_size());
         }
 
         Ok(())
@@ -4638,10 +6213,9 @@ impl<'data> DynamicLayoutState<'data> {
         let copy_relocation_addresses =
             self.assign_copy_relocation_addresses(&copy_relocation_symbols, memory_offsets)?;
 
-        for (local_symbol, &resolution_flags) in self
+        for ((_, local_symbol), &resolution_flags) in self
             .object
-            .symbols
-            .iter()
+            .symbols()
             .zip(&resources.symbol_resolution_flags[self.symbol_id_range().as_usize()])
         {
             if resolution_flags.is_empty() {
@@ -4653,7 +6227,7 @@ impl<'data> DynamicLayoutState<'data> {
             let dynamic_symbol_index;
 
             if resolution_flags.contains(ResolutionFlags::COPY_RELOCATION) {
-                let input_address = local_symbol.st_value(LittleEndian);
+                let input_address = local_symbol.st_value(self.object.endian);
 
                 address = *copy_relocation_addresses
                     .get(&input_address)
@@ -4664,7 +6238,11 @@ impl<'data> DynamicLayoutState<'data> {
                 dynamic_symbol_index = None;
             } else {
                 address = 0;
-                let symbol_index = take_dynsym_index(memory_offsets, resources.section_layouts)?;
+                let symbol_index = take_dynsym_index(
+                    memory_offsets,
+                    resources.section_layouts,
+                    resources.symbol_db.args,
+                )?;
 
                 dynamic_symbol_index = Some(
                     NonZeroU32::new(symbol_index)
@@ -4678,6 +6256,7 @@ impl<'data> DynamicLayoutState<'data> {
                 dynamic_symbol_index,
                 ValueFlags::DYNAMIC,
                 memory_offsets,
+                resources.symbol_db.args,
             );
 
             resolutions_out.write(Some(resolution))?;
@@ -4731,7 +6310,7 @@ impl<'data> DynamicLayoutState<'data> {
         // Note, we're a shared object, so this is the address relative to the load address of the
         // shared object, not an offset within a section like with regular input objects. That means
         // that we don't need to take the section into account.
-        let address = symbol.st_value(LittleEndian);
+        let address = symbol.st_value(self.object.endian);
 
         let info = self
             .copy_relocations
@@ -4758,10 +6337,10 @@ impl<'data> DynamicLayoutState<'data> {
                     .object
                     .symbol(self.symbol_id_range.id_to_input(*symbol_id))?;
 
-                let input_address = symbol.st_value(LittleEndian);
+                let input_address = symbol.st_value(self.object.endian);
 
                 let output_address =
-                    assign_copy_relocation_address(self.object, symbol, memory_offsets)?;
+                    assign_copy_relocation_address(self.object, &symbol, memory_offsets)?;
 
                 Ok((input_address, output_address))
             })
@@ -4793,15 +6372,15 @@ impl CopyRelocationInfo {
 /// Assigns the address in BSS for the copy relocation of a symbol.
 fn assign_copy_relocation_address(
     file: &File,
-    local_symbol: &object::elf::Sym64<LittleEndian>,
+    local_symbol: &object::elf::Sym64<Endianness>,
     memory_offsets: &mut OutputSectionPartMap<u64>,
 ) -> Result<u64, Error> {
-    let section_index = local_symbol.st_shndx(LittleEndian);
+    let section_index = local_symbol.st_shndx(file.endian);
     let section = file.section(SectionIndex(usize::from(section_index)))?;
-    let alignment = Alignment::new(file.section_alignment(section)?)?;
+    let alignment = Alignment::new(file.section_alignment(&section)?)?;
     let bss = memory_offsets.get_mut(output_section_id::BSS.part_id_with_alignment(alignment));
     let a = *bss;
-    *bss += local_symbol.st_size(LittleEndian);
+    *bss += local_symbol.st_size(file.endian);
     Ok(a)
 }
 
@@ -4814,11 +6393,8 @@ struct DynamicTagValues<'data> {
 impl<'data> DynamicTagValues<'data> {
     fn read(file: &File<'data>) -> Result<Self> {
         let mut values = DynamicTagValues::default();
-        let Ok(dynamic_tags) = file.dynamic_tags() else {
-            return Ok(values);
-        };
-        let e = LittleEndian;
-        for entry in dynamic_tags {
+        let e = file.endian;
+        for entry in file.dynamic_tags() {
             let value = entry.d_val(e);
             match entry.d_tag(e) as u32 {
                 object::elf::DT_VERDEFNUM => {
@@ -4826,8 +6402,7 @@ impl<'data> DynamicTagValues<'data> {
                 }
                 object::elf::DT_SONAME => {
                     values.soname = Some(
-                        file.symbols
-                            .strings()
+                        file.symbol_strings()
                             .get(value as u32)
                             .map_err(|()| anyhow!("Invalid DT_SONAME 0x{value:x}"))?,
                     );
@@ -4842,14 +6417,16 @@ impl<'data> DynamicTagValues<'data> {
 fn take_dynsym_index(
     memory_offsets: &mut OutputSectionPartMap<u64>,
     section_layouts: &OutputSectionMap<OutputRecordLayout>,
+    args: &Args,
 ) -> Result<u32> {
+    let entry_size = args.elf_class().symtab_entry_size();
     let index = u32::try_from(
         (memory_offsets.get(part_id::DYNSYM)
             - section_layouts.get(output_section_id::DYNSYM).mem_offset)
-            / crate::elf::SYMTAB_ENTRY_SIZE,
+            / entry_size,
     )
     .context("Too many dynamic symbols")?;
-    memory_offsets.increment(part_id::DYNSYM, crate::elf::SYMTAB_ENTRY_SIZE);
+    memory_offsets.increment(part_id::DYNSYM, entry_size);
     Ok(index)
 }
 
@@ -4907,7 +6484,7 @@ fn print_symbol_info(
                                 "  {}: symbol_id={symbol_id} -> {canonical} {value_flags} \
                                     res=[{res_flags}] \n    \
                                     #{local_index} in File #{file_id} {input} ({file_state})",
-                                crate::symbol::SymDebug(sym),
+                                crate::symbol::SymDebug(&sym, o.object.endian),
                                 value_flags = symbol_db.local_symbol_value_flags(symbol_id),
                                 res_flags = resolution_flags[symbol_id.as_usize()].get(),
                                 input = o.input,
@@ -4927,7 +6504,7 @@ fn print_symbol_info(
 fn section_debug(object: &crate::elf::File, section_index: object::SectionIndex) -> SectionDebug {
     let name = object
         .section(section_index)
-        .and_then(|section| object.section_name(section))
+        .and_then(|section| object.section_name(&section))
         .map_or_else(
             |_| "??".to_owned(),
             |name| String::from_utf8_lossy(name).into_owned(),
@@ -4982,9 +6559,85 @@ fn needs_tlsld(relocation_kind: RelocationKind) -> bool {
 }
 
 impl<'data> ObjectLayout<'data> {
-    pub(crate) fn relocations(&self, index: SectionIndex) -> Result<&'data [elf::Rela]> {
+    pub(crate) fn relocations(&self, index: SectionIndex) -> Result<elf::RelocationList<'data>> {
         self.object.relocations(index, &self.relocations)
     }
+
+    /// Returns the address of the thunk that a branch in the section with index `section_index`
+    /// can use to get to `symbol_index` plus `addend`, if we allocated one.
+    pub(crate) fn thunk_address(
+        &self,
+        part_id: PartId,
+        section_index: SectionIndex,
+        symbol_index: object::SymbolIndex,
+        addend: i64,
+    ) -> Option<u64> {
+        self.thunk_area(part_id, section_index)?
+            .thunk_address(symbol_index, addend)
+    }
+
+    /// Returns the thunk area used by the section with index `section_index`, if any.
+    pub(crate) fn thunk_area(
+        &self,
+        part_id: PartId,
+        section_index: SectionIndex,
+    ) -> Option<&ThunkArea> {
+        let start = self
+            .thunk_areas
+            .partition_point(|area| area.last_section.0 < section_index.0);
+        self.thunk_areas[start..]
+            .iter()
+            .find(|area| area.part_id == part_id)
+    }
+
+    /// Returns the section with index `section_index` if linker relaxation made it smaller.
+    pub(crate) fn shrunk_section(&self, section_index: SectionIndex) -> Option<&ShrunkSection> {
+        shrunk_section(&self.shrunk_sections, section_index)
+    }
+
+    /// Returns the shrunk section that `symbol_index` refers to if it's a section symbol.
+    fn shrunk_section_for_section_symbol(
+        &self,
+        symbol_index: object::SymbolIndex,
+    ) -> Option<&ShrunkSection> {
+        let symbol = self.object.symbol(symbol_index).ok()?;
+        if symbol.st_type() != object::elf::STT_SECTION {
+            return None;
+        }
+        let section_index = self.object.symbol_section(&symbol, symbol_index).ok()??;
+        self.shrunk_section(section_index)
+    }
+}
+
+/// Works out what we can tell about where the target of `rel`, which is in the section with index
+/// `section_index`, will end up.
+fn relocation_target_location(
+    object: &File,
+    symbol_id_range: SymbolIdRange,
+    symbol_db: &SymbolDb,
+    section_index: SectionIndex,
+    rel: &elf::Rela,
+) -> TargetLocation {
+    let e = object.endian;
+    let Some(symbol_index) = rel.symbol(e, false) else {
+        return TargetLocation::Unknown;
+    };
+    let symbol_id = symbol_id_range.input_to_id(symbol_index);
+    let value_flags = symbol_db.local_symbol_value_flags(symbol_db.definition(symbol_id));
+    if value_flags.contains(ValueFlags::ABSOLUTE) {
+        return TargetLocation::Unknown;
+    }
+    if value_flags.contains(ValueFlags::CAN_BYPASS_GOT)
+        && !value_flags.contains(ValueFlags::IFUNC)
+        && symbol_db.definition(symbol_id) == symbol_id
+        && let Ok(symbol) = object.symbol(symbol_index)
+        && object.symbol_section(&symbol, symbol_index).ok() == Some(Some(section_index))
+    {
+        return TargetLocation::SameSection(
+            symbol.st_value(e).wrapping_add(rel.r_addend.get(e) as u64),
+        );
+    }
+    TargetLocation::Code
 }
 
 /// Performs layout of sections and segments then makes sure that the loadable segments don't
@@ -4999,8 +6652,9 @@ fn test_no_disallowed_overlaps() {
             .unwrap();
     let args = Args::default();
     let section_part_sizes = output_sections.new_part_map::<u64>().map(|_, _| 7);
-    let section_part_layouts = layout_section_parts(&section_part_sizes, &output_sections, &args);
-    let section_layouts = layout_sections(&section_part_layouts);
+    let section_part_layouts =
+        layout_section_parts(&section_part_sizes, &output_sections, &args).unwrap();
+    let section_layouts = layout_sections(&section_part_layouts, &output_sections);
 
     // Make sure no alloc sections overlap
     let mut last_file_start = 0;
@@ -5039,8 +6693,10 @@ fn test_no_disallowed_overlaps() {
     }
 
     let header_info = HeaderInfo {
+        elf_class: args.elf_class(),
         num_output_sections_with_content: 0,
         active_segment_ids: (0..MAX_SEGMENTS).map(ProgramSegmentId::new).collect(),
+        extra_load_segment_starts: Vec::new(),
     };
 
     let mut section_index = 0;
@@ -5096,14 +6752,15 @@ impl Display for ResolutionFlags {
 fn verify_consistent_allocation_handling(
     value_flags: ValueFlags,
     resolution_flags: ResolutionFlags,
-    output_kind: OutputKind,
+    args: &Args,
 ) -> Result {
+    let output_kind = args.output_kind();
     let output_sections = output_section_id::OutputSectionsBuilder::with_base_address(0)
         .build()
         .unwrap();
     let mut mem_sizes = output_sections.new_part_map();
     let resolution_flags = AtomicResolutionFlags::new(resolution_flags);
-    allocate_symbol_resolution(value_flags, &resolution_flags, &mut mem_sizes, output_kind);
+    allocate_symbol_resolution(value_flags, &resolution_flags, &mut mem_sizes, args);
     let resolution_flags = resolution_flags.get();
     let mut memory_offsets = output_sections.new_part_map();
     *memory_offsets.get_mut(part_id::GOT) = 0x10;
@@ -5118,22 +6775,18 @@ fn verify_consistent_allocation_handling(
         dynamic_symbol_index,
         value_flags,
         &mut memory_offsets,
+        args,
     );
-    elf_writer::verify_resolution_allocation(
-        &output_sections,
-        output_kind,
-        &mem_sizes,
-        &resolution,
-    )
-    .with_context(|| {
-        format!(
-            "Inconsistent allocation detected. \
+    elf_writer::verify_resolution_allocation(&output_sections, args, &mem_sizes, &resolution)
+        .with_context(|| {
+            format!(
+                "Inconsistent allocation detected. \
              output_kind={output_kind:?} \
              value_flags={value_flags} \
              resolution_flags={resolution_flags} \
              has_dynamic_symbol={has_dynamic_symbol:?}"
-        )
-    })?;
+            )
+        })?;
     Ok(())
 }
 
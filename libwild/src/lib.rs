@@ -5,8 +5,11 @@ pub(crate) mod archive;
 pub(crate) mod archive_splitter;
 pub mod args;
 pub(crate) mod debug_trace;
+pub(crate) mod dependency_file;
+pub(crate) mod diagnostics;
 pub(crate) mod diff;
 pub(crate) mod elf;
+pub(crate) mod elf32;
 pub(crate) mod elf_writer;
 pub mod error;
 pub(crate) mod file_kind;
@@ -14,10 +17,13 @@ pub(crate) mod fs;
 pub(crate) mod gc_stats;
 pub(crate) mod grouping;
 pub(crate) mod hash;
+pub(crate) mod i386;
 pub(crate) mod identity;
 pub(crate) mod input_data;
 pub(crate) mod layout;
 pub(crate) mod linker_script;
+pub(crate) mod loongarch64;
+pub(crate) mod needed_libs;
 pub(crate) mod output_section_id;
 pub(crate) mod output_section_map;
 pub(crate) mod output_section_part_map;
@@ -25,16 +31,22 @@ pub(crate) mod output_trace;
 pub(crate) mod parsing;
 pub(crate) mod part_id;
 pub(crate) mod program_segments;
+pub(crate) mod reproduce;
 pub(crate) mod resolution;
+pub(crate) mod riscv64;
+pub(crate) mod riscv_attributes;
 pub(crate) mod save_dir;
 pub(crate) mod sharding;
+pub(crate) mod shrinking;
 pub(crate) mod slice;
+pub(crate) mod source_location;
 pub(crate) mod string_merging;
 #[cfg(feature = "fork")]
 pub(crate) mod subprocess;
 #[cfg(not(feature = "fork"))]
 #[path = "subprocess_unsupported.rs"]
 pub(crate) mod subprocess;
+pub(crate) mod suggestions;
 pub(crate) mod symbol;
 pub(crate) mod symbol_db;
 pub(crate) mod timing;
@@ -134,6 +146,8 @@ impl Linker {
         &'layout_inputs self,
         args: &'layout_inputs Args,
     ) -> error::Result<LinkerOutput<'layout_inputs>> {
+        diagnostics::configure(args);
+
         if args.should_print_version {
             println!(
                 "Wild version {} (compatible with GNU linkers)",
@@ -147,6 +161,9 @@ impl Linker {
         match args.arch {
             arch::Architecture::X86_64 => self.link_for_arch::<x86_64::X86_64>(args),
             arch::Architecture::AArch64 => self.link_for_arch::<aarch64::AArch64>(args),
+            arch::Architecture::RiscV64 => self.link_for_arch::<riscv64::RiscV64>(args),
+            arch::Architecture::LoongArch64 => self.link_for_arch::<loongarch64::LoongArch64>(args),
+            arch::Architecture::I386 => self.link_for_arch::<i386::I386>(args),
         }
     }
 
@@ -156,12 +173,15 @@ impl Linker {
     ) -> error::Result<LinkerOutput<'layout_inputs>> {
         let output = elf_writer::Output::new(args);
         let input_data = self.inputs.alloc(input_data::InputData::from_args(args)?);
+        reproduce::write(args, input_data)?;
 
         // Note, we propagate errors from `link_with_input_data` after we've checked if any files
         // changed. We want inputs-changed errors to take precedence over all other errors.
         let result = self.link_with_input_data::<A>(output, input_data, args);
         input_data.verify_inputs_unchanged()?;
-        result
+        let result = result?;
+        dependency_file::write(args, input_data)?;
+        Ok(result)
     }
 
     fn link_with_input_data<'layout_inputs, A: arch::Arch>(
@@ -173,10 +193,22 @@ impl Linker {
         let inputs = archive_splitter::split_archives(input_data)?;
         let parsed_inputs = parsing::parse_input_files(&inputs, args, &self.herd.get())?;
         let groups = grouping::group_files(parsed_inputs, args);
-        let mut symbol_db =
-            symbol_db::SymbolDb::build(groups, input_data.version_script_data.as_ref(), args)?;
+        let mut symbol_db = symbol_db::SymbolDb::build(
+            groups,
+            input_data.version_script_data.as_ref(),
+            input_data.retain_symbols_data.as_ref(),
+            args,
+        )?;
         let resolved = resolution::resolve_symbols_and_sections(&mut symbol_db, &self.herd)?;
         let layout = layout::compute::<A>(symbol_db, resolved, &mut output)?;
+
+        // All warnings have been issued by now. Like GNU ld, we don't leave an output file behind
+        // if they're fatal.
+        if let Err(error) = diagnostics::check_fatal_warnings(args) {
+            output.discard();
+            return Err(error);
+        }
+
         output.write::<A>(&layout)?;
         diff::maybe_diff()?;
 
@@ -73,6 +73,10 @@ pub(crate) struct VersionScript<'data> {
     globals: MatchRules<'data>,
     locals: MatchRules<'data>,
     versions: Vec<Version<'data>>,
+
+    /// Global patterns that name a single symbol, together with the name of the version they
+    /// assign it to. Used to check that the symbols are actually defined.
+    exact_globals: Vec<(&'data str, &'data str)>,
 }
 
 pub(crate) struct Version<'data> {
@@ -86,10 +90,27 @@ struct MatchRules<'data> {
     matches_all: bool,
     exact: HashSet<PreHashed<UnversionedSymbolName<'data>>, PassThroughHasher>,
     prefixes: Vec<&'data [u8]>,
+
+    /// Patterns with wildcards other than a single trailing `*`.
+    globs: Vec<&'data [u8]>,
+
+    /// Patterns from `extern "C++"` blocks. These are matched against demangled names.
+    demangled: Vec<SymbolMatcher<'data>>,
+}
+
+/// The language of an `extern` block in a version script.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Language {
+    C,
+    Cxx,
 }
 
 impl<'data> MatchRules<'data> {
-    fn push(&mut self, pattern: SymbolMatcher<'data>) {
+    fn push(&mut self, pattern: SymbolMatcher<'data>, language: Language) {
+        if language == Language::Cxx {
+            self.demangled.push(pattern);
+            return;
+        }
         match pattern {
             SymbolMatcher::All => self.matches_all = true,
             SymbolMatcher::Prefix(prefix) => self.prefixes.push(prefix.as_bytes()),
@@ -97,6 +118,7 @@ impl<'data> MatchRules<'data> {
                 self.exact
                     .insert(UnversionedSymbolName::prehashed(exact.as_bytes()));
             }
+            SymbolMatcher::Glob(glob) => self.globs.push(glob.as_bytes()),
         }
     }
 
@@ -107,6 +129,24 @@ impl<'data> MatchRules<'data> {
                 .prefixes
                 .iter()
                 .any(|prefix| name.bytes().starts_with(prefix))
+            || self
+                .globs
+                .iter()
+                .any(|glob| glob_matches(glob, name.bytes()))
+            || self.matches_demangled(name.bytes())
+    }
+
+    fn matches_demangled(&self, name: &[u8]) -> bool {
+        if self.demangled.is_empty() {
+            return false;
+        }
+        let Ok(name) = std::str::from_utf8(name) else {
+            return false;
+        };
+        let demangled = symbolic_demangle::demangle(name);
+        self.demangled
+            .iter()
+            .any(|pattern| pattern.matches(demangled.as_bytes()))
     }
 }
 
@@ -115,6 +155,7 @@ enum SymbolMatcher<'data> {
     All,
     Prefix(&'data str),
     Exact(&'data str),
+    Glob(&'data str),
 }
 
 impl<'data> VersionScript<'data> {
@@ -131,12 +172,7 @@ impl<'data> VersionScript<'data> {
         let mut token = tokens.next().ok_or_else(|| anyhow!("No tokens found"))?;
         // Simple version script, only defines symbols visibility
         if token.starts_with('{') {
-            parse_version_section(
-                &mut tokens,
-                &mut version_script.locals,
-                &mut version_script.globals,
-                None,
-            )?;
+            parse_version_section(&mut tokens, &mut version_script, "global", None)?;
             return Ok(version_script);
         }
 
@@ -155,8 +191,8 @@ impl<'data> VersionScript<'data> {
             let mut version_symbols = MatchRules::default();
             let parent = parse_version_section(
                 &mut tokens,
-                &mut version_script.locals,
-                &mut version_script.globals,
+                &mut version_script,
+                token,
                 Some(&mut version_symbols),
             )?;
             let parent_index = if let Some(parent) = parent {
@@ -221,6 +257,12 @@ impl<'data> VersionScript<'data> {
                 .then(|| number as u16 + object::elf::VER_NDX_GLOBAL)
         })
     }
+
+    /// Returns the names of versions and the symbols that they're assigned by global patterns that
+    /// don't contain wildcards. For a script without named versions, the version name is "global".
+    pub(crate) fn exact_global_patterns(&self) -> impl Iterator<Item = (&'data str, &'data str)> {
+        self.exact_globals.iter().copied()
+    }
 }
 
 enum VersionRuleSection {
@@ -232,8 +274,8 @@ enum VersionRuleSection {
 /// Returns contents after closing brace if any.
 fn parse_version_section<'data>(
     tokens: &mut Tokeniser<'data>,
-    locals: &mut MatchRules<'data>,
-    globals: &mut MatchRules<'data>,
+    version_script: &mut VersionScript<'data>,
+    version_name: &'data str,
     mut versioned_symbols: Option<&mut MatchRules<'data>>,
 ) -> Result<Option<&'data str>> {
     let mut section = None;
@@ -267,18 +309,41 @@ fn parse_version_section<'data>(
             section = Some(VersionRuleSection::Global);
         } else if line == "local:" {
             section = Some(VersionRuleSection::Local);
-        } else if let Some(pattern) = line.strip_suffix(';') {
-            match section {
-                Some(VersionRuleSection::Global) | None => {
-                    globals.push(SymbolMatcher::from_pattern(pattern)?);
+        } else if let Some(rest) = line.strip_prefix("extern") {
+            let (language, mut rest) = parse_extern_start(rest)?;
+            // The block may span multiple lines. Patterns within it are separated by semicolons.
+            loop {
+                let end = find_unquoted(rest, '}');
+                for pattern in rest[..end.unwrap_or(rest.len())]
+                    .split(';')
+                    .map(str::trim)
+                    .filter(|pattern| !pattern.is_empty())
+                {
+                    add_pattern(
+                        version_script,
+                        versioned_symbols.as_deref_mut(),
+                        version_name,
+                        section.as_ref(),
+                        pattern,
+                        language,
+                    )?;
                 }
-                Some(VersionRuleSection::Local) => {
-                    locals.push(SymbolMatcher::from_pattern(pattern)?);
+                if end.is_some() {
+                    break;
                 }
+                rest = tokens
+                    .next_line()
+                    .context("Missing close '}' in extern block in version script")?;
             }
-            if let Some(versioned_symbols) = versioned_symbols.as_deref_mut() {
-                versioned_symbols.push(SymbolMatcher::from_pattern(pattern)?);
-            }
+        } else if let Some(pattern) = line.strip_suffix(';') {
+            add_pattern(
+                version_script,
+                versioned_symbols.as_deref_mut(),
+                version_name,
+                section.as_ref(),
+                pattern,
+                Language::C,
+            )?;
         } else if !line.is_empty() {
             bail!("Unsupported version script line `{line}`");
         }
@@ -286,6 +351,61 @@ fn parse_version_section<'data>(
     bail!("Missing close '}}' in version script");
 }
 
+fn add_pattern<'data>(
+    version_script: &mut VersionScript<'data>,
+    versioned_symbols: Option<&mut MatchRules<'data>>,
+    version_name: &'data str,
+    section: Option<&VersionRuleSection>,
+    pattern: &'data str,
+    language: Language,
+) -> Result {
+    let matcher = SymbolMatcher::from_pattern(pattern)?;
+    match section {
+        Some(VersionRuleSection::Global) | None => {
+            version_script.globals.push(matcher, language);
+            if let (SymbolMatcher::Exact(name), Language::C) = (matcher, language) {
+                version_script.exact_globals.push((version_name, name));
+            }
+        }
+        Some(VersionRuleSection::Local) => {
+            version_script.locals.push(matcher, language);
+        }
+    }
+    if let Some(versioned_symbols) = versioned_symbols {
+        versioned_symbols.push(matcher, language);
+    }
+    Ok(())
+}
+
+/// Parses the start of an extern block, e.g. ` "C++" {`, returning the language and whatever
+/// follows the opening brace.
+fn parse_extern_start(text: &str) -> Result<(Language, &str)> {
+    let text = text.trim_start();
+    let (language, rest) = if let Some(rest) = text.strip_prefix("\"C++\"") {
+        (Language::Cxx, rest)
+    } else if let Some(rest) = text.strip_prefix("\"C\"") {
+        (Language::C, rest)
+    } else {
+        bail!("Unsupported extern language in version script `extern {text}`");
+    };
+    let rest = rest
+        .trim_start()
+        .strip_prefix('{')
+        .context("Expected '{' after extern language in version script")?;
+    Ok((language, rest))
+}
+
+/// Returns the offset of the first occurrence of `needle` in `text` that isn't within double quotes.
+fn find_unquoted(text: &str, needle: char) -> Option<usize> {
+    let mut in_quotes = false;
+    text.char_indices().find_map(|(i, c)| {
+        if c == '"' {
+            in_quotes = !in_quotes;
+        }
+        (c == needle && !in_quotes).then_some(i)
+    })
+}
+
 impl Version<'_> {
     fn is_present(&self, name: &PreHashed<UnversionedSymbolName>) -> bool {
         self.symbols.matches(name)
@@ -294,20 +414,106 @@ impl Version<'_> {
 
 impl<'data> SymbolMatcher<'data> {
     fn from_pattern(token: &'data str) -> Result<SymbolMatcher<'data>> {
+        // Quoted patterns are matched literally, even if they contain wildcard characters.
+        if let Some(quoted) = token.strip_prefix('"') {
+            let exact = quoted
+                .strip_suffix('"')
+                .with_context(|| format!("Unterminated quote in symbol pattern `{token}`"))?;
+            return Ok(SymbolMatcher::Exact(exact));
+        }
         if token == "*" {
             return Ok(SymbolMatcher::All);
         }
+        let has_wildcard = |s: &str| s.contains(['*', '?', '[']);
+        if !has_wildcard(token) {
+            return Ok(SymbolMatcher::Exact(token));
+        }
         if let Some(prefix) = token.strip_suffix('*') {
-            if prefix.contains('*') {
-                bail!("Unsupported symbol pattern '{token}'");
+            if !has_wildcard(prefix) {
+                return Ok(SymbolMatcher::Prefix(prefix));
+            }
+        }
+        Ok(SymbolMatcher::Glob(token))
+    }
+
+    fn matches(&self, name: &[u8]) -> bool {
+        match self {
+            SymbolMatcher::All => true,
+            SymbolMatcher::Prefix(prefix) => name.starts_with(prefix.as_bytes()),
+            SymbolMatcher::Exact(exact) => name == exact.as_bytes(),
+            SymbolMatcher::Glob(glob) => glob_matches(glob.as_bytes(), name),
+        }
+    }
+}
+
+/// Returns whether `name` matches the glob `pattern`, which may contain `*`, `?` and bracket
+/// expressions such as `[a-z_]` or `[!0-9]`.
+fn glob_matches(pattern: &[u8], name: &[u8]) -> bool {
+    let mut p = 0;
+    let mut n = 0;
+
+    // Where to resume from if we fail to match after the most recent `*`. We retry with the `*`
+    // consuming one more byte of the name.
+    let mut backtrack = None;
+
+    while n < name.len() {
+        let pattern_len = match pattern.get(p) {
+            Some(b'*') => {
+                backtrack = Some((p, n));
+                p += 1;
+                continue;
             }
-            return Ok(SymbolMatcher::Prefix(prefix));
+            Some(b'?') => Some(1),
+            Some(b'[') => match_bracket(&pattern[p..], name[n]),
+            Some(&c) => (c == name[n]).then_some(1),
+            None => None,
+        };
+        if let Some(pattern_len) = pattern_len {
+            p += pattern_len;
+            n += 1;
+        } else if let Some((star_p, star_n)) = backtrack {
+            backtrack = Some((star_p, star_n + 1));
+            p = star_p + 1;
+            n = star_n + 1;
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+/// Matches `c` against the bracket expression at the start of `pattern`. Returns the length of the
+/// bracket expression if it matched. An unterminated `[` is treated as a literal.
+fn match_bracket(pattern: &[u8], c: u8) -> Option<usize> {
+    let mut i = 1;
+    let negated = matches!(pattern.get(i), Some(b'!' | b'^'));
+    if negated {
+        i += 1;
+    }
+    let mut matched = false;
+    let mut is_first = true;
+    loop {
+        let Some(&start) = pattern.get(i) else {
+            return (c == b'[').then_some(1);
+        };
+        // A `]` at the start of the set is part of the set rather than terminating it.
+        if start == b']' && !is_first {
+            break;
         }
-        if token.contains('*') {
-            bail!("Unsupported symbol pattern '{token}'");
+        is_first = false;
+        match (pattern.get(i + 1), pattern.get(i + 2)) {
+            (Some(b'-'), Some(&end)) if end != b']' => {
+                matched |= (start..=end).contains(&c);
+                i += 3;
+            }
+            _ => {
+                matched |= start == c;
+                i += 1;
+            }
         }
-        Ok(SymbolMatcher::Exact(token))
     }
+    (matched != negated).then_some(i + 1)
 }
 
 struct Tokeniser<'a> {
@@ -658,6 +864,87 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches(b"foo*bar", b"foobar"));
+        assert!(glob_matches(b"foo*bar", b"foo_x_bar"));
+        assert!(!glob_matches(b"foo*bar", b"foo_x_baz"));
+        assert!(glob_matches(b"*_v[0-9]", b"bar_v2"));
+        assert!(!glob_matches(b"*_v[0-9]", b"bar_vx"));
+        assert!(glob_matches(b"*_v[!0-9]", b"bar_vx"));
+        assert!(glob_matches(b"f?o", b"fxo"));
+        assert!(!glob_matches(b"f?o", b"fo"));
+        assert!(glob_matches(b"a*b*c", b"aXbYbZc"));
+        assert!(glob_matches(b"[]x]", b"]"));
+        assert!(glob_matches(b"a[b", b"a[b"));
+    }
+
+    #[test]
+    fn test_symbol_matcher_from_pattern() {
+        assert_eq!(
+            SymbolMatcher::from_pattern("*").unwrap(),
+            SymbolMatcher::All
+        );
+        assert_eq!(
+            SymbolMatcher::from_pattern("foo").unwrap(),
+            SymbolMatcher::Exact("foo")
+        );
+        assert_eq!(
+            SymbolMatcher::from_pattern("foo*").unwrap(),
+            SymbolMatcher::Prefix("foo")
+        );
+        assert_eq!(
+            SymbolMatcher::from_pattern("*foo").unwrap(),
+            SymbolMatcher::Glob("*foo")
+        );
+        assert_eq!(
+            SymbolMatcher::from_pattern("f?o*").unwrap(),
+            SymbolMatcher::Glob("f?o*")
+        );
+        assert_eq!(
+            SymbolMatcher::from_pattern("\"foo*\"").unwrap(),
+            SymbolMatcher::Exact("foo*")
+        );
+    }
+
+    #[test]
+    fn test_parse_version_script_with_extern_blocks() {
+        let data = VersionScriptData {
+            raw: r#"
+                VERS_1 {
+                    global:
+                        extern "C++" {
+                            ns::*;
+                            "other::f(int)";
+                        };
+                        extern "C" { c_*_func; exact_c; };
+                        plain;
+                    local:
+                        *;
+                };
+            "#
+            .into(),
+        };
+        let script = VersionScript::parse(&data).unwrap();
+        let is_local =
+            |name: &str| script.is_local(&UnversionedSymbolName::prehashed(name.as_bytes()));
+        assert!(!is_local("_ZN2ns3fooEv"));
+        assert!(!is_local("_ZN5other1fEi"));
+        assert!(is_local("_ZN5other1fEl"));
+        assert!(!is_local("c_my_func"));
+        assert!(!is_local("exact_c"));
+        assert!(!is_local("plain"));
+        assert!(is_local("something_else"));
+        assert_eq!(
+            script.version_for_symbol(&UnversionedSymbolName::prehashed(b"_ZN2ns3fooEv")),
+            Some(2)
+        );
+        assert_equal(
+            script.exact_global_patterns(),
+            [("VERS_1", "exact_c"), ("VERS_1", "plain")],
+        );
+    }
+
     #[test]
     fn test_sysroot_application() {
         let sysroot = Path::new("/usr/aarch64-linux-gnu");
@@ -0,0 +1,365 @@
+//! LoongArch 64 (LP64D ABI) support. Like RISC-V, LoongArch code that was assembled with
+//! relaxation enabled expects the linker to shorten some instruction sequences and to fix up
+//! alignment padding afterwards. The relaxations that delete bytes are found here, while the rest
+//! of the process is in `shrinking`.
+
+use crate::arch::PltProtection;
+use crate::elf::PLT_ENTRY_SIZE;
+use crate::resolution::ValueFlags;
+use crate::shrinking::RelaxationEdit;
+use crate::shrinking::ShrinkContext;
+use crate::shrinking::TargetLocation;
+use anyhow::Result;
+use anyhow::anyhow;
+use anyhow::ensure;
+use linker_utils::elf::DynamicRelocationKind;
+use linker_utils::elf::RelocationKind;
+use linker_utils::elf::RelocationKindInfo;
+use linker_utils::elf::loongarch64_rel_type_to_string;
+use linker_utils::loongarch64::LoongArchInstruction;
+use linker_utils::loongarch64::R_LARCH_TLS_DESC_CALL;
+use linker_utils::loongarch64::R_LARCH_TLS_DESC_LD;
+use linker_utils::loongarch64::R_LARCH_TLS_DESC_PC_HI20;
+use linker_utils::loongarch64::R_LARCH_TLS_DESC_PC_LO12;
+use linker_utils::loongarch64::R_LARCH_TLS_DESC_PCREL20_S2;
+use linker_utils::loongarch64::R_LARCH_TLS_LE_ADD_R;
+use linker_utils::loongarch64::R_LARCH_TLS_LE_HI20_R;
+use linker_utils::loongarch64::R_LARCH_TLS_LE_LO12_R;
+use linker_utils::loongarch64::RelaxationKind;
+use linker_utils::loongarch64::relocation_type_from_raw;
+use linker_utils::relaxation::RelocationModifier;
+use object::Endianness;
+use object::elf::R_LARCH_ALIGN;
+use object::elf::R_LARCH_B26;
+use object::elf::R_LARCH_CALL36;
+use object::elf::R_LARCH_RELAX;
+use object::read::elf::Rela as _;
+
+pub(crate) struct LoongArch64;
+
+const PLT_ENTRY_TEMPLATE: &[u8] = &[
+    0x0f, 0x00, 0x00, 0x1c, // pcaddu12i $t3, %pc_hi20(.got.plt[n])
+    0xef, 0x01, 0xc0, 0x28, // ld.d $t3, $t3, %pc_lo12(.got.plt[n])
+    0xed, 0x01, 0x00, 0x4c, // jirl $t1, $t3, 0
+    0x00, 0x00, 0x40, 0x03, // nop
+];
+
+const _ASSERTS: () = {
+    assert!(PLT_ENTRY_TEMPLATE.len() as u64 == PLT_ENTRY_SIZE);
+};
+
+/// How far a `b` or `bl` can reach in either direction.
+const B26_REACH: u64 = 1 << 27;
+
+/// The largest offset from the thread pointer that a 12 bit immediate can hold.
+const TPREL_LO12_LIMIT: u64 = 0x800;
+
+const B_OPCODE: u32 = 0x5000_0000;
+const BL_OPCODE: u32 = 0x5400_0000;
+const ADDI_D_OPCODE: u32 = 0x02c0_0000;
+const OPCODE_MASK_6: u32 = 0xfc00_0000;
+const OPCODE_MASK_10: u32 = 0xffc0_0000;
+const ZERO_REGISTER: u32 = 0;
+const RA_REGISTER: u32 = 1;
+const TP_REGISTER: u32 = 2;
+
+impl crate::arch::Arch for LoongArch64 {
+    type Relaxation = Relaxation;
+
+    fn elf_header_arch_magic() -> u16 {
+        object::elf::EM_LOONGARCH
+    }
+
+    // The table of relocations is documented here:
+    // https://github.com/loongson/la-abi-specs/blob/release/laelf.adoc.
+    #[inline(always)]
+    fn relocation_from_raw(r_type: u32) -> Result<RelocationKindInfo> {
+        relocation_type_from_raw(r_type).ok_or_else(|| {
+            anyhow!(
+                "Unsupported relocation type {}",
+                Self::rel_type_to_string(r_type)
+            )
+        })
+    }
+
+    fn tls_dtv_offset() -> u64 {
+        0
+    }
+
+    fn get_dynamic_relocation_type(relocation: DynamicRelocationKind) -> u32 {
+        relocation.loongarch64_r_type()
+    }
+
+    fn rel_type_to_string(r_type: u32) -> std::borrow::Cow<'static, str> {
+        loongarch64_rel_type_to_string(r_type)
+    }
+
+    fn write_plt_entry(
+        plt_entry: &mut [u8],
+        got_address: u64,
+        plt_address: u64,
+        _got_base: Option<u64>,
+        _protection: PltProtection,
+    ) -> crate::error::Result {
+        plt_entry.copy_from_slice(PLT_ENTRY_TEMPLATE);
+        let offset = got_address.wrapping_sub(plt_address);
+        ensure!(
+            (-(1 << 31)..(1 << 31)).contains(&(offset as i64)),
+            "PLT is more than 2GiB away from GOT"
+        );
+        LoongArchInstruction::Hi20.write_to_value(offset, &mut plt_entry[0..4]);
+        LoongArchInstruction::Si12.write_to_value(offset, &mut plt_entry[4..8]);
+        Ok(())
+    }
+
+    fn find_relaxation_edits(
+        section_data: &[u8],
+        relocations: &crate::elf::RelocationList,
+        context: &ShrinkContext,
+        edits: &mut Vec<RelaxationEdit>,
+    ) -> crate::error::Result {
+        // Only little endian is supported for this architecture.
+        let e = Endianness::Little;
+        let mut deleted = 0;
+        for (index, rel) in relocations.iter().enumerate() {
+            let r_type = rel.r_type(e, false);
+            let offset = rel.r_offset.get(e);
+            let addend = rel.r_addend.get(e);
+            // Optional relaxations are only permitted when the compiler marked the instruction
+            // with an `R_LARCH_RELAX` at the same offset.
+            let can_relax = context.relax
+                && relocations.get(index + 1).is_some_and(|next| {
+                    next.r_type(e, false) == R_LARCH_RELAX && next.r_offset.get(e) == offset
+                });
+            let tls_offset_fits = || {
+                context.tls_size.is_some_and(|tls_size| {
+                    u64::try_from(addend)
+                        .is_ok_and(|addend| tls_size.saturating_add(addend) < TPREL_LO12_LIMIT)
+                })
+            };
+            let edit = |delete_offset, delete_len| RelaxationEdit {
+                offset,
+                r_type,
+                delete_offset,
+                delete_len,
+            };
+            let edit = match r_type {
+                R_LARCH_ALIGN => {
+                    // Without a symbol, the addend is the number of bytes of padding that the
+                    // assembler inserted, which is 4 bytes short of the alignment. With a symbol,
+                    // the low 8 bits of the addend are the log2 of the alignment and the rest is
+                    // the maximum number of bytes that may be skipped.
+                    let addend = u64::try_from(addend)
+                        .map_err(|_| anyhow!("R_LARCH_ALIGN has negative addend {addend}"))?;
+                    let (alignment, max_skip) = if rel.symbol(e, false).is_some() {
+                        (1 << (addend & 0xff), addend >> 8)
+                    } else {
+                        (addend + 4, u64::MAX)
+                    };
+                    ensure!(
+                        alignment.is_power_of_two() && alignment >= 4,
+                        "R_LARCH_ALIGN at 0x{offset:x} has invalid alignment {alignment}"
+                    );
+                    let padding = alignment - 4;
+                    let output_offset = offset - deleted;
+                    let needed = output_offset.next_multiple_of(alignment) - output_offset;
+                    if needed > max_skip {
+                        // The alignment isn't worth the padding, so drop all of it.
+                        edit(offset, padding)
+                    } else {
+                        ensure!(
+                            needed <= padding,
+                            "R_LARCH_ALIGN at 0x{offset:x} has {padding} bytes of padding, but \
+                             needs {needed}"
+                        );
+                        if needed == padding {
+                            continue;
+                        }
+                        edit(offset + needed, padding - needed)
+                    }
+                }
+                R_LARCH_CALL36 if can_relax => {
+                    // Replace `pcaddu18i` + `jirl` with `bl` or `b`, which only works if the
+                    // `jirl` links into `$ra` or doesn't link at all.
+                    let jirl = section_data
+                        .get(offset as usize + 4..)
+                        .map_or(u32::MAX, read_instruction);
+                    if !matches!(jirl & 0x1f, ZERO_REGISTER | RA_REGISTER) {
+                        continue;
+                    }
+                    let in_range = match (context.target_location)(&rel) {
+                        // Deleting bytes can only bring the call closer to its target.
+                        TargetLocation::SameSection(target) => target.abs_diff(offset) < B26_REACH,
+                        TargetLocation::Code => context.can_reach_all_code(B26_REACH),
+                        TargetLocation::Unknown => false,
+                    };
+                    if !in_range {
+                        continue;
+                    }
+                    edit(offset + 4, 4)
+                }
+                R_LARCH_TLS_LE_HI20_R | R_LARCH_TLS_LE_ADD_R if can_relax && tls_offset_fits() => {
+                    // The `lu12i.w` and the `add.d` that adds the thread pointer aren't needed if
+                    // the offset fits in the 12 bit immediate of the final instruction.
+                    edit(offset, 4)
+                }
+                R_LARCH_TLS_LE_LO12_R if can_relax && tls_offset_fits() => edit(offset, 0),
+                _ => continue,
+            };
+            deleted += edit.delete_len;
+            edits.push(edit);
+        }
+        Ok(())
+    }
+
+    fn apply_relaxation_edit(input: &[u8], edit: &RelaxationEdit, out: &mut [u8]) {
+        match edit.r_type {
+            R_LARCH_CALL36 => {
+                let jirl = read_instruction(&input[4..]);
+                let opcode = if jirl & 0x1f == RA_REGISTER {
+                    BL_OPCODE
+                } else {
+                    B_OPCODE
+                };
+                write_instruction(out, opcode);
+            }
+            R_LARCH_TLS_LE_LO12_R => {
+                // Use the thread pointer as the base register in place of the result of the
+                // deleted `add.d`.
+                let instruction = read_instruction(input);
+                write_instruction(out, instruction & !(0x1f << 5) | TP_REGISTER << 5);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn read_instruction(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes(*bytes.first_chunk::<4>().unwrap())
+}
+
+fn write_instruction(out: &mut [u8], instruction: u32) {
+    out[..4].copy_from_slice(&instruction.to_le_bytes());
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Relaxation {
+    kind: RelaxationKind,
+    rel_info: RelocationKindInfo,
+}
+
+impl Relaxation {
+    fn new_unchanged(r_type: u32, kind: RelocationKind) -> Option<Self> {
+        let mut rel_info = relocation_type_from_raw(r_type)?;
+        rel_info.kind = kind;
+        Some(Relaxation {
+            kind: RelaxationKind::NoOp,
+            rel_info,
+        })
+    }
+
+    fn replace(kind: RelaxationKind, r_type: u32) -> Option<Self> {
+        Some(Relaxation {
+            kind,
+            rel_info: relocation_type_from_raw(r_type)?,
+        })
+    }
+}
+
+impl crate::arch::Relaxation for Relaxation {
+    #[allow(unused_variables)]
+    #[inline(always)]
+    fn new(
+        relocation_kind: u32,
+        section_bytes: &[u8],
+        offset_in_section: u64,
+        value_flags: ValueFlags,
+        output_kind: crate::args::OutputKind,
+        section_flags: linker_utils::elf::SectionFlags,
+        non_zero_address: bool,
+    ) -> Option<Self>
+    where
+        Self: std::marker::Sized,
+    {
+        let offset = offset_in_section as usize;
+        let instruction = || {
+            section_bytes
+                .get(offset..offset + 4)
+                .map_or(0, read_instruction)
+        };
+
+        // The `addi.d` that follows the `pcalau12i` of a general or local dynamic TLS access
+        // uses `R_LARCH_GOT_PC_LO12`, but needs the address of the symbol's TLSGD GOT entry rather
+        // than its regular one.
+        if relocation_kind == object::elf::R_LARCH_GOT_PC_LO12
+            && instruction() & OPCODE_MASK_10 == ADDI_D_OPCODE
+        {
+            return Self::new_unchanged(relocation_kind, RelocationKind::TlsGdGot);
+        }
+
+        // IFuncs cannot be referenced directly, they always need to go via the GOT.
+        if value_flags.contains(ValueFlags::IFUNC) {
+            return None;
+        }
+        let can_bypass_got = value_flags.contains(ValueFlags::CAN_BYPASS_GOT);
+        // TLSDESC can only be relaxed once we know the offset from the thread pointer.
+        let can_relax_tls_desc = can_bypass_got && output_kind.is_executable();
+
+        match relocation_kind {
+            R_LARCH_CALL36 => {
+                // If we deleted the `jirl` during layout, then the `pcaddu18i` will have been
+                // replaced with a `bl` or `b`.
+                if matches!(instruction() & OPCODE_MASK_6, B_OPCODE | BL_OPCODE) {
+                    let kind = if can_bypass_got {
+                        RelocationKind::Relative
+                    } else {
+                        RelocationKind::PltRelative
+                    };
+                    return Self::new_unchanged(R_LARCH_B26, kind);
+                }
+                if can_bypass_got {
+                    return Self::new_unchanged(relocation_kind, RelocationKind::Relative);
+                }
+            }
+            R_LARCH_B26 if can_bypass_got => {
+                return Self::new_unchanged(relocation_kind, RelocationKind::Relative);
+            }
+            R_LARCH_TLS_DESC_PC_HI20 | R_LARCH_TLS_DESC_PC_LO12 | R_LARCH_TLS_DESC_PCREL20_S2
+                if can_relax_tls_desc =>
+            {
+                return Self::replace(RelaxationKind::ReplaceWithNop, object::elf::R_LARCH_NONE);
+            }
+            R_LARCH_TLS_DESC_LD if can_relax_tls_desc => {
+                return Self::replace(
+                    RelaxationKind::TlsDescToLocalExecLu12i,
+                    object::elf::R_LARCH_TLS_LE_HI20,
+                );
+            }
+            R_LARCH_TLS_DESC_CALL if can_relax_tls_desc => {
+                return Self::replace(
+                    RelaxationKind::TlsDescToLocalExecOri,
+                    object::elf::R_LARCH_TLS_LE_LO12,
+                );
+            }
+            _ => {}
+        }
+
+        None
+    }
+
+    fn apply(&self, section_bytes: &mut [u8], offset_in_section: &mut u64, addend: &mut i64) {
+        self.kind.apply(section_bytes, offset_in_section, addend);
+    }
+
+    fn rel_info(&self) -> RelocationKindInfo {
+        self.rel_info
+    }
+
+    fn debug_kind(&self) -> impl std::fmt::Debug {
+        &self.kind
+    }
+
+    fn next_modifier(&self) -> RelocationModifier {
+        self.kind.next_modifier()
+    }
+}
@@ -0,0 +1,295 @@
+//! Code for finding the shared objects that the shared objects we link against depend on. i.e. the
+//! libraries named by their DT_NEEDED entries. These indirect dependencies are loaded so that we can
+//! resolve undefined symbols in shared objects, but they're never added to our own DT_NEEDED
+//! entries.
+//!
+//! We search the same places as GNU ld, which are, in order: directories supplied via
+//! `-rpath-link`, directories supplied via `-rpath`, `LD_LIBRARY_PATH` (only when not cross
+//! linking), the DT_RUNPATH or DT_RPATH of the shared object with the DT_NEEDED entry, directories
+//! supplied via `-L`, the default library directories and finally the directories listed in
+//! `/etc/ld.so.conf`.
+
+use crate::arch::Architecture;
+use crate::args::Args;
+use crate::elf::FileHeader;
+use crate::elf32;
+use crate::error::Result;
+use crate::file_kind::FileKind;
+use crate::input_data::FileData;
+use ahash::HashSet;
+use ahash::RandomState;
+use object::Endianness;
+use object::read::elf::Dyn as _;
+use object::read::elf::SectionHeader as _;
+use std::path::Path;
+use std::path::PathBuf;
+
+const DEFAULT_LIBRARY_DIRS: &[&str] = &["/lib64", "/usr/lib64", "/lib", "/usr/lib"];
+
+/// Information from the dynamic section of a shared object that's relevant for finding its
+/// dependencies.
+#[derive(Default)]
+pub(crate) struct DynamicDependencyInfo<'data> {
+    pub(crate) soname: Option<&'data [u8]>,
+    pub(crate) needed: Vec<&'data [u8]>,
+
+    /// The DT_RUNPATH of the shared object, or if that isn't present, the DT_RPATH.
+    runpath: Option<&'data [u8]>,
+}
+
+impl<'data> DynamicDependencyInfo<'data> {
+    pub(crate) fn read(data: &'data [u8]) -> Result<Self> {
+        if elf32::is_elf32(data) {
+            Self::read_with_header::<object::elf::FileHeader32<Endianness>>(data)
+        } else {
+            Self::read_with_header::<FileHeader>(data)
+        }
+    }
+
+    fn read_with_header<H: object::read::elf::FileHeader<Endian = Endianness>>(
+        data: &'data [u8],
+    ) -> Result<Self> {
+        let header = H::parse(data)?;
+        let e = header.endian()?;
+        let sections = header.sections(e, data)?;
+        let mut info = DynamicDependencyInfo::default();
+        let mut rpath = None;
+
+        for section in sections.iter() {
+            let Some((entries, link)) = section.dynamic(e, data)? else {
+                continue;
+            };
+            let strings = sections.strings(e, data, link)?;
+            for entry in entries {
+                match entry.tag32(e) {
+                    Some(object::elf::DT_NEEDED) => info.needed.push(entry.string(e, strings)?),
+                    Some(object::elf::DT_SONAME) => info.soname = Some(entry.string(e, strings)?),
+                    Some(object::elf::DT_RUNPATH) => info.runpath = Some(entry.string(e, strings)?),
+                    Some(object::elf::DT_RPATH) => rpath = Some(entry.string(e, strings)?),
+                    _ => {}
+                }
+            }
+        }
+
+        info.runpath = info.runpath.or(rpath);
+
+        Ok(info)
+    }
+}
+
+/// The directories that we search for DT_NEEDED entries, other than those that come from the
+/// shared object that has the DT_NEEDED entry.
+pub(crate) struct NeededLibSearchPath {
+    arch: Architecture,
+
+    /// Directories searched before the DT_RUNPATH of the object with the DT_NEEDED entry.
+    before_runpath: Vec<PathBuf>,
+
+    /// Directories searched after the DT_RUNPATH of the object with the DT_NEEDED entry.
+    after_runpath: Vec<PathBuf>,
+}
+
+impl NeededLibSearchPath {
+    pub(crate) fn new(args: &Args) -> Self {
+        let mut before_runpath: Vec<PathBuf> = args
+            .rpath_link
+            .iter()
+            .map(|dir| dir.to_path_buf())
+            .collect();
+
+        // -rpath entries that refer to $ORIGIN are relative to the output file, which doesn't help
+        // us find libraries now.
+        before_runpath.extend(
+            args.rpaths
+                .iter()
+                .flat_map(|rpath| rpath.split(':'))
+                .filter(|dir| !dir.is_empty() && !dir.contains("$ORIGIN"))
+                .map(PathBuf::from),
+        );
+
+        // LD_LIBRARY_PATH describes where libraries for the host can be found, so if we're linking
+        // for a different architecture, it's not applicable.
+        if args.arch == crate::args::default_target_arch() {
+            if let Ok(paths) = std::env::var("LD_LIBRARY_PATH") {
+                before_runpath.extend(
+                    paths
+                        .split(':')
+                        .filter(|dir| !dir.is_empty())
+                        .map(PathBuf::from),
+                );
+            }
+        }
+
+        let mut after_runpath: Vec<PathBuf> = args
+            .lib_search_path
+            .iter()
+            .map(|dir| dir.to_path_buf())
+            .collect();
+
+        let sysroot = args.sysroot.as_deref().unwrap_or(Path::new("/"));
+        after_runpath.extend(
+            DEFAULT_LIBRARY_DIRS
+                .iter()
+                .map(|dir| sysroot.join(dir.trim_start_matches('/'))),
+        );
+
+        let mut visited = HashSet::with_hasher(RandomState::new());
+        read_ld_so_conf(
+            &sysroot.join("etc/ld.so.conf"),
+            sysroot,
+            &mut after_runpath,
+            &mut visited,
+        );
+
+        Self {
+            arch: args.arch,
+            before_runpath,
+            after_runpath,
+        }
+    }
+
+    /// Searches for a shared object named `needed` that was listed as a DT_NEEDED entry of the
+    /// shared object at `needed_by`. If found, returns its path and the mapped file.
+    pub(crate) fn find(
+        &self,
+        needed: &str,
+        needed_by: &Path,
+        needed_by_info: &DynamicDependencyInfo,
+        args: &Args,
+    ) -> Option<(PathBuf, FileData)> {
+        // A DT_NEEDED entry that contains a slash is a path rather than a name to search for.
+        if needed.contains('/') {
+            return self.open_if_compatible(PathBuf::from(needed), args);
+        }
+
+        let origin = needed_by
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        let runpath_dirs = needed_by_info
+            .runpath
+            .map(|runpath| String::from_utf8_lossy(runpath))
+            .into_iter()
+            .flat_map(|runpath| {
+                runpath
+                    .split(':')
+                    .filter(|dir| !dir.is_empty())
+                    .map(|dir| expand_origin(dir, origin))
+                    .collect::<Vec<_>>()
+            });
+
+        self.before_runpath
+            .iter()
+            .cloned()
+            .chain(runpath_dirs)
+            .chain(self.after_runpath.iter().cloned())
+            .find_map(|dir| self.open_if_compatible(dir.join(needed), args))
+    }
+
+    /// Opens `path` if it's a shared object that we could link against. Libraries for other
+    /// architectures are commonly found on the search path, so we skip over them rather than
+    /// failing.
+    fn open_if_compatible(&self, path: PathBuf, args: &Args) -> Option<(PathBuf, FileData)> {
+        if !path.is_file() {
+            return None;
+        }
+        let data = FileData::new(&path, args.prepopulate_maps).ok()?;
+        if !matches!(FileKind::identify_bytes(&data), Ok(FileKind::ElfDynamic)) {
+            return None;
+        }
+        let arch = elf32::machine(&data)
+            .ok()
+            .and_then(|machine| Architecture::try_from(machine).ok());
+        (arch == Some(self.arch)).then_some((path, data))
+    }
+}
+
+fn expand_origin(dir: &str, origin: &Path) -> PathBuf {
+    let origin = origin.to_string_lossy();
+    PathBuf::from(
+        dir.replace("${ORIGIN}", &origin)
+            .replace("$ORIGIN", &origin),
+    )
+}
+
+/// Reads library directories from an ld.so.conf file, following any include directives. Errors are
+/// ignored, since a missing or malformed config file just means fewer places to search.
+fn read_ld_so_conf(
+    path: &Path,
+    sysroot: &Path,
+    dirs_out: &mut Vec<PathBuf>,
+    visited: &mut HashSet<PathBuf>,
+) {
+    if !visited.insert(path.to_owned()) {
+        return;
+    }
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return;
+    };
+    for line in contents.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() || line.starts_with("hwcap ") {
+            continue;
+        }
+        if let Some(pattern) = line.strip_prefix("include") {
+            let pattern = pattern.trim();
+            for included in expand_include_pattern(pattern, path, sysroot) {
+                read_ld_so_conf(&included, sysroot, dirs_out, visited);
+            }
+        } else {
+            dirs_out.push(sysroot.join(line.trim_start_matches('/')));
+        }
+    }
+}
+
+/// Expands an ld.so.conf include pattern. We only support a single `*` wildcard in the filename,
+/// which is all that's used in practice.
+fn expand_include_pattern(pattern: &str, conf_path: &Path, sysroot: &Path) -> Vec<PathBuf> {
+    let pattern = if let Some(absolute) = pattern.strip_prefix('/') {
+        sysroot.join(absolute)
+    } else {
+        conf_path.parent().unwrap_or(Path::new("/")).join(pattern)
+    };
+    let Some(file_pattern) = pattern.file_name().and_then(|f| f.to_str()) else {
+        return Vec::new();
+    };
+    let Some((prefix, suffix)) = file_pattern.split_once('*') else {
+        return vec![pattern.clone()];
+    };
+    let dir = pattern.parent().unwrap_or(Path::new("/"));
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut matches: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry.file_name().to_str().is_some_and(|name| {
+                name.len() >= prefix.len() + suffix.len()
+                    && name.starts_with(prefix)
+                    && name.ends_with(suffix)
+            })
+        })
+        .map(|entry| entry.path())
+        .collect();
+    matches.sort();
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_origin() {
+        let origin = Path::new("/opt/foo/lib");
+        assert_eq!(
+            expand_origin("$ORIGIN/../lib64", origin),
+            PathBuf::from("/opt/foo/lib/../lib64")
+        );
+        assert_eq!(
+            expand_origin("${ORIGIN}", origin),
+            PathBuf::from("/opt/foo/lib")
+        );
+        assert_eq!(expand_origin("/usr/lib", origin), PathBuf::from("/usr/lib"));
+    }
+}
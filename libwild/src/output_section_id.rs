@@ -15,13 +15,14 @@
 //! related to `part_id.rs` and insert later in `SECTION_DEFINITIONS` (probably at the end). Also,
 //! update `NUM_BUILT_IN_REGULAR_SECTIONS`.
 
-use self::elf::TLS_MODULE_BASE_SYMBOL_NAME;
 use crate::alignment;
 use crate::alignment::Alignment;
 use crate::alignment::NUM_ALIGNMENTS;
+use crate::args::Args;
 use crate::args::OutputKind;
-use crate::elf;
-use crate::elf::DynamicEntry;
+use crate::elf::ElfClass;
+use crate::elf::RelocationFormat;
+use crate::elf::TLS_MODULE_BASE_SYMBOL_NAME;
 use crate::elf::Versym;
 use crate::error::Result;
 use crate::layout::NonAddressableCounts;
@@ -92,6 +93,7 @@ pub(crate) const NOTE_GNU_PROPERTY: OutputSectionId =
     part_id::NOTE_GNU_PROPERTY.output_section_id();
 pub(crate) const NOTE_GNU_BUILD_ID: OutputSectionId =
     part_id::NOTE_GNU_BUILD_ID.output_section_id();
+pub(crate) const RISCV_ATTRIBUTES: OutputSectionId = part_id::RISCV_ATTRIBUTES.output_section_id();
 
 // These two are multi-part sections, but we can pick any part we wish in order to get the section
 // ID.
@@ -138,6 +140,15 @@ struct CustomSectionIds {
     data: Vec<OutputSectionId>,
     bss: Vec<OutputSectionId>,
     nonalloc: Vec<OutputSectionId>,
+    /// Sections like `.init_array.N` that get placed before `.init_array`, sorted by priority.
+    init_array: Vec<OutputSectionId>,
+    fini_array: Vec<OutputSectionId>,
+    /// Sections like `.text.hot` that we output with `-z keep-text-section-prefix`. These go before
+    /// `.text` in the order of `KEPT_TEXT_PREFIXES`.
+    text_prefixed: Vec<OutputSectionId>,
+    /// Pairs of (primary, secondary) for input sections that are being sorted by name. Each
+    /// secondary gets placed after its primary. Sorted by primary then by name.
+    sorted_by_name: Vec<(OutputSectionId, OutputSectionId)>,
 }
 
 impl OutputSections<'_> {
@@ -225,6 +236,9 @@ pub(crate) struct SectionOutputInfo<'data> {
     pub(crate) name: SectionName<'data>,
     pub(crate) section_flags: SectionFlags,
     pub(crate) ty: SectionType,
+    /// For sections like `.init_array.N`, the section that they're output as part of. Such
+    /// sections don't get a section header of their own.
+    pub(crate) primary: Option<OutputSectionId>,
 }
 
 pub(crate) struct BuiltInSectionDetails {
@@ -237,32 +251,49 @@ pub(crate) struct BuiltInSectionDetails {
     pub(crate) min_alignment: Alignment,
     info_fn: Option<fn(&InfoInputs) -> u32>,
     pub(crate) keep_if_empty: bool,
-    pub(crate) element_size: u64,
+    /// Returns the size of each entry in the section, or 0 if the section doesn't hold a table of
+    /// fixed-size entries. Some entries differ in size between 32 and 64 bit output.
+    element_size: fn(&Args) -> u64,
     pub(crate) ty: SectionType,
 }
 
 impl BuiltInSectionDetails {
-    pub(crate) fn start_symbol_name(&self, output_kind: OutputKind) -> Option<&'static str> {
+    pub(crate) fn start_symbol_name(&self, args: &Args) -> Option<&'static str> {
         if self.start_symbol_name == Some(TLS_MODULE_BASE_SYMBOL_NAME)
-            && output_kind != OutputKind::SharedObject
+            && args.output_kind() != OutputKind::SharedObject
         {
             None
         } else {
             self.start_symbol_name
+                .map(|name| symbol_name_for_relocation_format(name, args))
         }
     }
 
-    pub(crate) fn end_symbol_name(&self, output_kind: OutputKind) -> Option<&'static str> {
+    pub(crate) fn end_symbol_name(&self, args: &Args) -> Option<&'static str> {
         if self.end_symbol_name == Some(TLS_MODULE_BASE_SYMBOL_NAME)
-            && output_kind == OutputKind::SharedObject
+            && args.output_kind() == OutputKind::SharedObject
         {
             None
         } else {
             self.end_symbol_name
+                .map(|name| symbol_name_for_relocation_format(name, args))
         }
     }
 }
 
+/// Architectures that use REL relocations look for their IFUNC relocations between
+/// `__rel_iplt_start` and `__rel_iplt_end` rather than between the `__rela_iplt_*` symbols.
+fn symbol_name_for_relocation_format(name: &'static str, args: &Args) -> &'static str {
+    if args.arch.relocation_format() != RelocationFormat::Rel {
+        return name;
+    }
+    match name {
+        "__rela_iplt_start" => "__rel_iplt_start",
+        "__rela_iplt_end" => "__rel_iplt_end",
+        _ => name,
+    }
+}
+
 const DEFAULT_DEFS: BuiltInSectionDetails = BuiltInSectionDetails {
     name: SectionName(&[]),
     section_flags: SectionFlags::empty(),
@@ -272,7 +303,7 @@ const DEFAULT_DEFS: BuiltInSectionDetails = BuiltInSectionDetails {
     min_alignment: alignment::MIN,
     info_fn: None,
     keep_if_empty: false,
-    element_size: 0,
+    element_size: |_| 0,
     ty: sht::NULL,
 };
 
@@ -313,7 +344,7 @@ const SECTION_DEFINITIONS: [BuiltInSectionDetails; NUM_BUILT_IN_SECTIONS] = [
         name: SectionName(GOT_SECTION_NAME),
         ty: sht::PROGBITS,
         section_flags: shf::WRITE.with(shf::ALLOC),
-        element_size: crate::elf::GOT_ENTRY_SIZE,
+        element_size: Args::got_entry_size,
         min_alignment: alignment::GOT_ENTRY,
         start_symbol_name: Some("_GLOBAL_OFFSET_TABLE_"),
         ..DEFAULT_DEFS
@@ -322,7 +353,7 @@ const SECTION_DEFINITIONS: [BuiltInSectionDetails; NUM_BUILT_IN_SECTIONS] = [
         name: SectionName(PLT_GOT_SECTION_NAME),
         ty: sht::PROGBITS,
         section_flags: shf::ALLOC.with(shf::EXECINSTR),
-        element_size: crate::elf::PLT_ENTRY_SIZE,
+        element_size: Args::plt_entry_size,
         min_alignment: alignment::PLT,
         ..DEFAULT_DEFS
     },
@@ -330,7 +361,7 @@ const SECTION_DEFINITIONS: [BuiltInSectionDetails; NUM_BUILT_IN_SECTIONS] = [
         name: SectionName(RELA_PLT_SECTION_NAME),
         ty: sht::RELA,
         section_flags: shf::ALLOC.with(shf::INFO_LINK),
-        element_size: elf::RELA_ENTRY_SIZE,
+        element_size: Args::rela_entry_size,
         link: &[DYNSYM, SYMTAB],
         min_alignment: alignment::RELA_ENTRY,
         start_symbol_name: Some("__rela_iplt_start"),
@@ -356,7 +387,7 @@ const SECTION_DEFINITIONS: [BuiltInSectionDetails; NUM_BUILT_IN_SECTIONS] = [
         name: SectionName(DYNAMIC_SECTION_NAME),
         ty: sht::DYNAMIC,
         section_flags: shf::ALLOC.with(shf::WRITE),
-        element_size: size_of::<DynamicEntry>() as u64,
+        element_size: |args| args.elf_class().dynamic_entry_size(),
         link: &[DYNSTR],
         min_alignment: alignment::USIZE,
         start_symbol_name: Some("_DYNAMIC"),
@@ -374,7 +405,7 @@ const SECTION_DEFINITIONS: [BuiltInSectionDetails; NUM_BUILT_IN_SECTIONS] = [
         name: SectionName(DYNSYM_SECTION_NAME),
         ty: sht::DYNSYM,
         section_flags: shf::ALLOC,
-        element_size: size_of::<elf::SymtabEntry>() as u64,
+        element_size: |args| args.elf_class().symtab_entry_size(),
         link: &[DYNSTR],
         min_alignment: alignment::SYMTAB_ENTRY,
         info_fn: Some(dynsym_info),
@@ -397,7 +428,7 @@ const SECTION_DEFINITIONS: [BuiltInSectionDetails; NUM_BUILT_IN_SECTIONS] = [
         name: SectionName(GNU_VERSION_SECTION_NAME),
         ty: sht::GNU_VERSYM,
         section_flags: shf::ALLOC,
-        element_size: size_of::<Versym>() as u64,
+        element_size: |_| size_of::<Versym>() as u64,
         min_alignment: alignment::VERSYM,
         link: &[DYNSYM],
         ..DEFAULT_DEFS
@@ -434,11 +465,16 @@ const SECTION_DEFINITIONS: [BuiltInSectionDetails; NUM_BUILT_IN_SECTIONS] = [
         min_alignment: alignment::NOTE_GNU_BUILD_ID,
         ..DEFAULT_DEFS
     },
+    BuiltInSectionDetails {
+        name: SectionName(RISCV_ATTRIBUTES_SECTION_NAME),
+        ty: sht::RISCV_ATTRIBUTES,
+        ..DEFAULT_DEFS
+    },
     // Multi-part generated sections
     BuiltInSectionDetails {
         name: SectionName(SYMTAB_SECTION_NAME),
         ty: sht::SYMTAB,
-        element_size: size_of::<elf::SymtabEntry>() as u64,
+        element_size: |args| args.elf_class().symtab_entry_size(),
         min_alignment: alignment::SYMTAB_ENTRY,
         link: &[STRTAB],
         info_fn: Some(symtab_info),
@@ -448,7 +484,7 @@ const SECTION_DEFINITIONS: [BuiltInSectionDetails; NUM_BUILT_IN_SECTIONS] = [
         name: SectionName(RELA_DYN_SECTION_NAME),
         ty: sht::RELA,
         section_flags: shf::ALLOC,
-        element_size: elf::RELA_ENTRY_SIZE,
+        element_size: Args::rela_entry_size,
         min_alignment: alignment::RELA_ENTRY,
         link: &[DYNSYM],
         ..DEFAULT_DEFS
@@ -464,7 +500,7 @@ const SECTION_DEFINITIONS: [BuiltInSectionDetails; NUM_BUILT_IN_SECTIONS] = [
         name: SectionName(INIT_ARRAY_SECTION_NAME),
         ty: sht::INIT_ARRAY,
         section_flags: shf::ALLOC.with(shf::WRITE).with(shf::GNU_RETAIN),
-        element_size: size_of::<u64>() as u64,
+        element_size: Args::got_entry_size,
         start_symbol_name: Some("__init_array_start"),
         end_symbol_name: Some("__init_array_end"),
         ..DEFAULT_DEFS
@@ -473,7 +509,7 @@ const SECTION_DEFINITIONS: [BuiltInSectionDetails; NUM_BUILT_IN_SECTIONS] = [
         name: SectionName(FINI_ARRAY_SECTION_NAME),
         ty: sht::FINI_ARRAY,
         section_flags: shf::ALLOC.with(shf::WRITE).with(shf::GNU_RETAIN),
-        element_size: size_of::<u64>() as u64,
+        element_size: Args::got_entry_size,
         start_symbol_name: Some("__fini_array_start"),
         end_symbol_name: Some("__fini_array_end"),
         ..DEFAULT_DEFS
@@ -536,7 +572,7 @@ const SECTION_DEFINITIONS: [BuiltInSectionDetails; NUM_BUILT_IN_SECTIONS] = [
         name: SectionName(COMMENT_SECTION_NAME),
         ty: sht::PROGBITS,
         section_flags: shf::STRINGS.with(shf::MERGE).with(shf::GNU_RETAIN),
-        element_size: 1,
+        element_size: |_| 1,
         ..DEFAULT_DEFS
     },
     BuiltInSectionDetails {
@@ -648,13 +684,15 @@ impl OutputSectionId {
             .map_or(0, |info_fn| (info_fn)(inputs))
     }
 
-    pub(crate) fn element_size(self) -> u64 {
-        self.opt_built_in_details().map_or(0, |d| d.element_size)
+    pub(crate) fn element_size(self, args: &Args) -> u64 {
+        self.opt_built_in_details()
+            .map_or(0, |d| (d.element_size)(args))
     }
 }
 
 /// The bits of `Layout` that are needed for computing info fields.
 pub(crate) struct InfoInputs<'layout> {
+    pub(crate) elf_class: ElfClass,
     pub(crate) section_part_layouts: &'layout OutputSectionPartMap<OutputRecordLayout>,
     pub(crate) non_addressable_counts: &'layout NonAddressableCounts,
     pub(crate) output_section_indexes: &'layout [Option<u16>],
@@ -688,21 +726,44 @@ impl Debug for SectionName<'_> {
 
 pub(crate) struct OutputSectionsBuilder<'data> {
     base_address: u64,
+    keep_text_section_prefix: bool,
     custom_by_name: AHashMap<SectionName<'data>, OutputSectionId>,
     // TODO: Change this to be an OutputSectionMap.
     section_infos: Vec<SectionOutputInfo<'data>>,
 }
 
 impl<'data> OutputSectionsBuilder<'data> {
-    pub(crate) fn build(self) -> Result<OutputSections<'data>> {
+    pub(crate) fn build(mut self) -> Result<OutputSections<'data>> {
         let mut custom = CustomSectionIds::default();
-
-        for (offset, info) in self.section_infos[NUM_BUILT_IN_SECTIONS..]
-            .iter()
-            .enumerate()
-        {
-            let id = OutputSectionId::from_usize(NUM_BUILT_IN_SECTIONS + offset);
-            if info.section_flags.contains(shf::EXECINSTR) {
+        let mut init_array = Vec::new();
+        let mut fini_array = Vec::new();
+        let mut sorted_by_name = Vec::new();
+
+        // Note, `sorted_section_primary` can add sections, so we can't just iterate.
+        let mut index = NUM_BUILT_IN_SECTIONS;
+        while index < self.section_infos.len() {
+            let id = OutputSectionId::from_usize(index);
+            index += 1;
+            if let Some(primary) = self.sorted_section_primary(id) {
+                let info = &mut self.section_infos[id.as_usize()];
+                info.primary = Some(primary);
+                sorted_by_name.push((primary, info.name, id));
+                continue;
+            }
+            let info = &mut self.section_infos[id.as_usize()];
+            if let Some((primary, priority)) = init_priority(info.name.bytes()) {
+                info.primary = Some(primary);
+                let sorted = if primary == INIT_ARRAY {
+                    &mut init_array
+                } else {
+                    &mut fini_array
+                };
+                sorted.push((priority, info.name, id));
+            } else if self.keep_text_section_prefix
+                && kept_text_prefix(info.name.bytes()) == Some(info.name.bytes())
+            {
+                custom.text_prefixed.push(id);
+            } else if info.section_flags.contains(shf::EXECINSTR) {
                 custom.exec.push(id);
             } else if !info.section_flags.contains(shf::WRITE) {
                 if info.section_flags.contains(shf::ALLOC) {
@@ -717,6 +778,20 @@ impl<'data> OutputSectionsBuilder<'data> {
             }
         }
 
+        init_array.sort_unstable_by_key(|(priority, name, _)| (*priority, *name));
+        fini_array.sort_unstable_by_key(|(priority, name, _)| (*priority, *name));
+        custom.init_array = init_array.into_iter().map(|(_, _, id)| id).collect();
+        custom.fini_array = fini_array.into_iter().map(|(_, _, id)| id).collect();
+        custom.text_prefixed.sort_by_key(|id| {
+            let name = self.section_infos[id.as_usize()].name.bytes();
+            KEPT_TEXT_PREFIXES.iter().position(|prefix| *prefix == name)
+        });
+        sorted_by_name.sort_unstable_by_key(|(primary, name, _)| (primary.as_usize(), *name));
+        custom.sorted_by_name = sorted_by_name
+            .into_iter()
+            .map(|(primary, _, id)| (primary, id))
+            .collect();
+
         let mut output_sections = OutputSections {
             base_address: self.base_address,
             section_infos: self.section_infos,
@@ -730,6 +805,45 @@ impl<'data> OutputSectionsBuilder<'data> {
         Ok(output_sections)
     }
 
+    /// Returns the section that the custom section `id` should be output as part of, if `id` was
+    /// only created so that we could sort input sections by name.
+    fn sorted_section_primary(&mut self, id: OutputSectionId) -> Option<OutputSectionId> {
+        let info = &self.section_infos[id.as_usize()];
+        let name = info.name.bytes();
+        if self.keep_text_section_prefix {
+            if let Some(prefix) = kept_text_prefix(name) {
+                if prefix == name {
+                    return None;
+                }
+                let (section_flags, ty) = (info.section_flags, info.ty);
+                return Some(self.add_section(SectionName(prefix), section_flags, ty));
+            }
+        }
+        sortable_built_in_section(name)
+    }
+
+    /// Sets whether input sections like `.text.hot.*` get put into an output section named for their
+    /// prefix.
+    pub(crate) fn keep_text_section_prefix(&mut self, keep: bool) {
+        self.keep_text_section_prefix = keep;
+    }
+
+    /// Sets the name and type of our dynamic relocation sections according to whether they hold
+    /// REL or RELA entries.
+    pub(crate) fn relocation_format(&mut self, format: RelocationFormat) {
+        if format != RelocationFormat::Rel {
+            return;
+        }
+        for (section_id, name) in [
+            (RELA_PLT, REL_PLT_SECTION_NAME),
+            (RELA_DYN, REL_DYN_SECTION_NAME),
+        ] {
+            let info = &mut self.section_infos[section_id.as_usize()];
+            info.name = SectionName(name);
+            info.ty = sht::REL;
+        }
+    }
+
     pub(crate) fn add_sections(
         &mut self,
         custom_sections: &[CustomSectionDetails<'data>],
@@ -764,6 +878,7 @@ impl<'data> OutputSectionsBuilder<'data> {
                 // We'll fill this in properly in `determine_loadable_segment_ids`.
                 loadable_segment_id: None,
                 ty: section_type,
+                primary: None,
             });
             id
         })
@@ -777,11 +892,13 @@ impl<'data> OutputSectionsBuilder<'data> {
                 name: d.name,
                 loadable_segment_id: Some(crate::program_segments::LOAD_RO),
                 ty: d.ty,
+                primary: None,
             })
             .collect();
         Self {
             section_infos,
             base_address,
+            keep_text_section_prefix: false,
             custom_by_name: AHashMap::new(),
         }
     }
@@ -809,7 +926,13 @@ impl CustomSectionIds {
         events.push(INTERP.event());
         events.push(OrderEvent::SegmentEnd(crate::program_segments::INTERP));
         events.push(OrderEvent::SegmentStart(crate::program_segments::NOTE));
+        events.push(OrderEvent::SegmentStart(
+            crate::program_segments::GNU_PROPERTY,
+        ));
         events.push(NOTE_GNU_PROPERTY.event());
+        events.push(OrderEvent::SegmentEnd(
+            crate::program_segments::GNU_PROPERTY,
+        ));
         events.push(NOTE_GNU_BUILD_ID.event());
         events.push(NOTE_ABI_TAG.event());
         events.push(OrderEvent::SegmentEnd(crate::program_segments::NOTE));
@@ -833,6 +956,7 @@ impl CustomSectionIds {
 
         events.push(OrderEvent::SegmentStart(crate::program_segments::LOAD_EXEC));
         events.push(PLT_GOT.event());
+        events.extend(build_section_events(&self.text_prefixed));
         events.push(TEXT.event());
         events.push(INIT.event());
         events.push(FINI.event());
@@ -845,7 +969,9 @@ impl CustomSectionIds {
         events.push(TDATA.event());
         events.push(TBSS.event());
         events.push(OrderEvent::SegmentEnd(crate::program_segments::TLS));
+        events.extend(build_section_events(&self.init_array));
         events.push(INIT_ARRAY.event());
+        events.extend(build_section_events(&self.fini_array));
         events.push(FINI_ARRAY.event());
         events.push(DATA_REL_RO.event());
         events.push(OrderEvent::SegmentStart(crate::program_segments::DYNAMIC));
@@ -864,11 +990,30 @@ impl CustomSectionIds {
 
         events.extend(build_section_events(&self.nonalloc));
         events.push(COMMENT.event());
+        events.push(RISCV_ATTRIBUTES.event());
         events.push(SHSTRTAB.event());
         events.push(SYMTAB.event());
         events.push(STRTAB.event());
 
-        events
+        if self.sorted_by_name.is_empty() {
+            return events;
+        }
+        let mut with_sorted = Vec::with_capacity(events.len() + self.sorted_by_name.len());
+        for event in events {
+            with_sorted.push(event);
+            if let OrderEvent::Section(id) = event {
+                let start = self
+                    .sorted_by_name
+                    .partition_point(|(p, _)| p.as_usize() < id.as_usize());
+                with_sorted.extend(
+                    self.sorted_by_name[start..]
+                        .iter()
+                        .take_while(|(p, _)| *p == id)
+                        .map(|(_, secondary)| OrderEvent::Section(*secondary)),
+                );
+            }
+        }
+        with_sorted
     }
 }
 
@@ -912,6 +1057,12 @@ impl<'data> OutputSections<'data> {
             .flatten()
     }
 
+    /// Returns whether the section gets a section header of its own. Sections that are output as
+    /// part of some primary section don't.
+    pub(crate) fn has_section_header(&self, id: OutputSectionId) -> bool {
+        self.output_index_of_section(id).is_some() && self.output_info(id).primary.is_none()
+    }
+
     /// Returns whether we're going to emit the specified section.
     pub(crate) fn will_emit_section(&self, id: OutputSectionId) -> bool {
         self.output_index_of_section(id).is_some()
@@ -944,6 +1095,64 @@ impl<'data> OutputSections<'data> {
     }
 }
 
+/// Returns the section into which an input section named `name` should be sorted and its priority,
+/// or None if `name` doesn't have a numeric priority suffix.
+pub(crate) fn init_priority(name: &[u8]) -> Option<(OutputSectionId, u32)> {
+    let (primary_name, priority) = linker_utils::elf::init_priority(name)?;
+    let primary = if primary_name == INIT_ARRAY_SECTION_NAME {
+        INIT_ARRAY
+    } else {
+        FINI_ARRAY
+    };
+    Some((primary, priority))
+}
+
+/// The prefixes of `.text` input sections that get their own output sections with
+/// `-z keep-text-section-prefix`, in the order in which we place those output sections.
+const KEPT_TEXT_PREFIXES: [&[u8]; 4] = [
+    b".text.unlikely",
+    b".text.exit",
+    b".text.startup",
+    b".text.hot",
+];
+
+/// Returns the name of the output section for an input section named `name` if
+/// `-z keep-text-section-prefix` applies to it.
+pub(crate) fn kept_text_prefix(name: &[u8]) -> Option<&'static [u8]> {
+    KEPT_TEXT_PREFIXES.into_iter().find(|prefix| {
+        name.strip_prefix(*prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(b"."))
+    })
+}
+
+/// Returns the built-in section for input sections whose names start with the name of that section.
+/// These are the sections whose inputs we can sort by name.
+pub(crate) fn sortable_built_in_section(name: &[u8]) -> Option<OutputSectionId> {
+    if name.starts_with(RODATA_SECTION_NAME) {
+        Some(RODATA)
+    } else if name.starts_with(TEXT_SECTION_NAME) {
+        Some(TEXT)
+    } else if name.starts_with(DATA_REL_RO_SECTION_NAME) {
+        Some(DATA_REL_RO)
+    } else if name.starts_with(DATA_SECTION_NAME) {
+        Some(DATA)
+    } else if name.starts_with(BSS_SECTION_NAME) {
+        Some(BSS)
+    } else {
+        None
+    }
+}
+
+/// Returns whether the contents of an input section named `name` are in the reverse of the order
+/// in which they should be run. This is the case for legacy `.ctors` and `.dtors` sections, which
+/// we output as part of `.init_array` and `.fini_array`.
+pub(crate) fn is_reversed_pointer_list(name: &[u8]) -> bool {
+    [b".ctors".as_slice(), b".dtors"].iter().any(|prefix| {
+        name.strip_prefix(*prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(b"."))
+    })
+}
+
 pub(crate) fn link_ids(section_id: OutputSectionId) -> &'static [OutputSectionId] {
     SECTION_DEFINITIONS
         .get(section_id.as_usize())
@@ -963,7 +1172,7 @@ fn symtab_info(info: &InfoInputs) -> u32 {
         .section_part_layouts
         .get(part_id::SYMTAB_LOCAL)
         .file_size
-        / size_of::<elf::SymtabEntry>()) as u32
+        / info.elf_class.symtab_entry_size() as usize) as u32
 }
 
 fn version_d_info(info: &InfoInputs) -> u32 {
@@ -1031,6 +1240,7 @@ fn test_constant_ids() {
         (NOTE_ABI_TAG, NOTE_ABI_TAG_SECTION_NAME),
         (NOTE_GNU_PROPERTY, NOTE_GNU_PROPERTY_SECTION_NAME),
         (NOTE_GNU_BUILD_ID, NOTE_GNU_BUILD_ID_SECTION_NAME),
+        (RISCV_ATTRIBUTES, RISCV_ATTRIBUTES_SECTION_NAME),
         (DATA_REL_RO, DATA_REL_RO_SECTION_NAME),
     ];
     for (id, name) in check {
@@ -1038,3 +1248,14 @@ fn test_constant_ids() {
     }
     assert_eq!(NUM_BUILT_IN_SECTIONS, check.len());
 }
+
+#[test]
+fn test_init_priority() {
+    assert_eq!(init_priority(b".init_array.00099"), Some((INIT_ARRAY, 99)));
+    assert_eq!(init_priority(b".dtors.65434"), Some((FINI_ARRAY, 101)));
+    assert_eq!(init_priority(b".fini_array"), None);
+    assert!(is_reversed_pointer_list(b".ctors"));
+    assert!(is_reversed_pointer_list(b".dtors.00101"));
+    assert!(!is_reversed_pointer_list(b".ctorsx"));
+    assert!(!is_reversed_pointer_list(b".init_array"));
+}
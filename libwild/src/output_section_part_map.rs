@@ -104,6 +104,12 @@ impl<T: Default + PartialEq> OutputSectionPartMap<T> {
         OutputSectionPartMap { parts: parts_out }
     }
 
+    /// Returns the alignment of the highest alignment part of `section_id` with a non-default
+    /// value, or the minimum alignment for the section if there are none.
+    pub(crate) fn section_alignment(&self, section_id: OutputSectionId) -> Alignment {
+        self.max_alignment(section_id.base_part_id(), section_id.num_parts())
+    }
+
     /// Returns the maximum alignment for any part with a non-default value starting from
     /// `base_part_id` for the next `count` parts. The returned value will not be any less than the
     /// minimum alignment for the section.
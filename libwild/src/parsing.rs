@@ -137,7 +137,16 @@ impl<'data> ParsedInputObject<'data> {
             )
         }
 
-        let num_symbols = object.symbols.len();
+        if object.endian != args.endian {
+            bail!(
+                "`{}` has incompatible byte order: {:?}, expecting {:?}",
+                input.input,
+                object.endian,
+                args.endian,
+            )
+        }
+
+        let num_symbols = object.num_symbols();
 
         Ok(Self {
             input: input.input.clone(),
@@ -175,7 +184,9 @@ impl<'data> ParsedInputObject<'data> {
     ) -> Result<UnversionedSymbolName<'data>> {
         let index = symbol_id.to_input(self.symbol_id_range);
         let symbol = self.object.symbol(index)?;
-        Ok(UnversionedSymbolName::new(self.object.symbol_name(symbol)?))
+        Ok(UnversionedSymbolName::new(
+            self.object.symbol_name(&symbol)?,
+        ))
     }
 }
 
@@ -214,11 +225,11 @@ impl<'data> Prelude<'data> {
                 continue;
             }
 
-            if def.start_symbol_name(args.output_kind()).is_some() {
+            if def.start_symbol_name(args).is_some() {
                 symbol_definitions.push(InternalSymDefInfo::SectionStart(section_id));
             }
 
-            if def.end_symbol_name(args.output_kind()).is_some() {
+            if def.end_symbol_name(args).is_some() {
                 symbol_definitions.push(InternalSymDefInfo::SectionEnd(section_id));
             }
         }
@@ -237,16 +248,16 @@ impl<'data> Prelude<'data> {
     pub(crate) fn symbol_name(
         &self,
         symbol_id: SymbolId,
-        output_kind: OutputKind,
+        args: &Args,
     ) -> UnversionedSymbolName<'data> {
         let def = &self.symbol_definitions[symbol_id.as_usize()];
         let name = match def {
             InternalSymDefInfo::Undefined => Some(""),
             InternalSymDefInfo::SectionStart(section_id) => {
-                section_id.built_in_details().start_symbol_name(output_kind)
+                section_id.built_in_details().start_symbol_name(args)
             }
             InternalSymDefInfo::SectionEnd(section_id) => {
-                section_id.built_in_details().end_symbol_name(output_kind)
+                section_id.built_in_details().end_symbol_name(args)
             }
             InternalSymDefInfo::ForceUndefined(i) => Some(self.undefined[i.0 as usize].as_str()),
         }
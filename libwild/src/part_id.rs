@@ -1,6 +1,7 @@
 use crate::alignment::Alignment;
 use crate::alignment::NUM_ALIGNMENTS;
 use crate::args::Args;
+use crate::args::SortSection;
 use crate::elf::SectionHeader;
 use crate::error::Result;
 use crate::output_section_id;
@@ -11,6 +12,7 @@ use crate::output_section_id::OutputSectionId;
 use crate::output_section_id::SectionName;
 use linker_utils::elf::SectionFlags;
 use linker_utils::elf::SectionType;
+use linker_utils::elf::gnu_warning_symbol;
 #[allow(clippy::wildcard_imports)]
 use linker_utils::elf::secnames::*;
 use linker_utils::elf::shf;
@@ -63,8 +65,9 @@ pub(crate) const GNU_VERSION_D: PartId = PartId(16);
 pub(crate) const GNU_VERSION_R: PartId = PartId(17);
 pub(crate) const NOTE_GNU_PROPERTY: PartId = PartId(18);
 pub(crate) const NOTE_GNU_BUILD_ID: PartId = PartId(19);
+pub(crate) const RISCV_ATTRIBUTES: PartId = PartId(20);
 
-pub(crate) const NUM_SINGLE_PART_SECTIONS: u32 = 20;
+pub(crate) const NUM_SINGLE_PART_SECTIONS: u32 = 21;
 
 // Generated sections that have more than one part. Fortunately they all have exactly 2 parts.
 pub(crate) const SYMTAB_LOCAL: PartId = PartId::multi(0);
@@ -99,18 +102,42 @@ impl<'data> UnresolvedSection<'data> {
         // Ideally we support reading an actual linker script to make these decisions, but for now
         // we just hard code stuff.
         let section_name = object.section_name(section).unwrap_or_default();
-        let section_flags = SectionFlags::from_header(section);
+        let section_flags = SectionFlags::from_header(section, object.endian);
         let alignment = Alignment::new(object.section_alignment(section)?.max(1))?;
-        let built_in_section_id = if section_name.starts_with(RODATA_SECTION_NAME) {
-            Some(output_section_id::RODATA)
-        } else if section_name.starts_with(TEXT_SECTION_NAME) {
-            Some(output_section_id::TEXT)
-        } else if section_name.starts_with(DATA_REL_RO_SECTION_NAME) {
-            Some(output_section_id::DATA_REL_RO)
-        } else if section_name.starts_with(DATA_SECTION_NAME) {
-            Some(output_section_id::DATA)
-        } else if section_name.starts_with(BSS_SECTION_NAME) {
-            Some(output_section_id::BSS)
+        let sort_by_name = args.sort_section == Some(SortSection::Name);
+        // When sorting by name, each distinct input section name gets its own output section,
+        // which is then output as part of the section that the input would normally go into.
+        let custom = |name| {
+            Ok(Some(UnresolvedSection {
+                part_id: TemporaryPartId::Custom(
+                    CustomSectionId {
+                        name: SectionName(name),
+                    },
+                    alignment,
+                ),
+                is_string_merge: false,
+            }))
+        };
+        let kept_text_prefix = if args.keep_text_section_prefix {
+            output_section_id::kept_text_prefix(section_name)
+        } else {
+            None
+        };
+        let built_in_section_id = if let Some(prefix) = kept_text_prefix {
+            return custom(if sort_by_name { section_name } else { prefix });
+        } else if let Some(section_id) = output_section_id::sortable_built_in_section(section_name)
+        {
+            if sort_by_name
+                && section_name != section_id.built_in_details().name.bytes()
+                && !should_merge_strings(section, object.section_alignment(section)?, args)
+            {
+                return custom(section_name);
+            }
+            Some(section_id)
+        } else if output_section_id::init_priority(section_name).is_some() {
+            // Sections with a priority suffix each get their own output section so that they can
+            // be sorted by priority. They're then output as part of `.init_array` / `.fini_array`.
+            return custom(section_name);
         } else if section_name.starts_with(INIT_ARRAY_SECTION_NAME)
             || section_name.starts_with(b".ctors")
         {
@@ -142,6 +169,15 @@ impl<'data> UnresolvedSection<'data> {
             Some(output_section_id::NOTE_ABI_TAG)
         } else if section_name == NOTE_GNU_BUILD_ID_SECTION_NAME {
             Some(output_section_id::NOTE_GNU_BUILD_ID)
+        } else if let Some(symbol_name) = gnu_warning_symbol(section_name) {
+            // When we're writing a shared object, we keep `.gnu.warning.SYM` sections, like GNU ld
+            // does, so that executables that link against it can issue the warning. Otherwise,
+            // these sections are never output. If they're relevant, we issue their contents as a
+            // warning instead.
+            if symbol_name.is_empty() || args.output_kind().is_executable() {
+                return Ok(None);
+            }
+            return custom(section_name);
         } else if section_name.starts_with(b".rela")
             || STRTAB_SECTION_NAME == section_name
             || SYMTAB_SECTION_NAME == section_name
@@ -162,8 +198,13 @@ impl<'data> UnresolvedSection<'data> {
                 part_id: TemporaryPartId::BuiltIn(NOTE_GNU_PROPERTY),
                 is_string_merge: false,
             }));
+        } else if section_name == RISCV_ATTRIBUTES_SECTION_NAME {
+            return Ok(Some(UnresolvedSection {
+                part_id: TemporaryPartId::BuiltIn(RISCV_ATTRIBUTES),
+                is_string_merge: false,
+            }));
         } else {
-            let sh_type = SectionType::from_header(section);
+            let sh_type = SectionType::from_header(section, object.endian);
             if !section_name.is_empty() {
                 let custom_section_id = CustomSectionId {
                     name: SectionName(section_name),
@@ -225,7 +266,7 @@ fn should_merge_strings(section: &SectionHeader, section_alignment: u64, args: &
     if !args.merge_strings {
         return false;
     }
-    let section_flags = SectionFlags::from_header(section);
+    let section_flags = SectionFlags::from_header(section, args.endian);
     section_flags.contains(shf::MERGE)
         && section_flags.contains(shf::STRINGS)
         && section_alignment <= 1
@@ -16,6 +16,7 @@ pub(crate) const EH_FRAME: ProgramSegmentId = ProgramSegmentId(7);
 pub(crate) const DYNAMIC: ProgramSegmentId = ProgramSegmentId(8);
 pub(crate) const STACK: ProgramSegmentId = ProgramSegmentId(9);
 pub(crate) const RELRO: ProgramSegmentId = ProgramSegmentId(10);
+pub(crate) const GNU_PROPERTY: ProgramSegmentId = ProgramSegmentId(11);
 
 pub(crate) struct ProgramSegmentDef {
     pub(crate) segment_type: u32,
@@ -67,6 +68,10 @@ const PROGRAM_SEGMENT_DEFS: &[ProgramSegmentDef] = &[
         segment_type: object::elf::PT_GNU_RELRO,
         segment_flags: object::elf::PF_R,
     },
+    ProgramSegmentDef {
+        segment_type: object::elf::PT_GNU_PROPERTY,
+        segment_flags: object::elf::PF_R,
+    },
 ];
 
 impl ProgramSegmentId {
@@ -0,0 +1,240 @@
+//! Support for --reproduce, which writes a tar file containing everything needed to rerun a link on
+//! another machine. This is useful when reporting bugs.
+//!
+//! Like lld, we store all files under a directory named after the tar file, at paths that mirror
+//! their absolute paths. `response.txt` contains our arguments with paths rewritten to be relative
+//! to that directory, so running `wild @response.txt` from within the extracted directory should
+//! reproduce the link.
+
+use crate::args::Args;
+use crate::args::RawPathKind;
+use crate::error::Result;
+use crate::file_kind::FileKind;
+use crate::input_data::InputData;
+use anyhow::Context as _;
+use normalize_path::NormalizePath as _;
+use std::collections::HashSet;
+use std::io::BufWriter;
+use std::io::Write as _;
+use std::path::Path;
+use std::path::PathBuf;
+
+pub(crate) fn write(args: &Args, input_data: &InputData) -> Result {
+    let Some(path) = args.reproduce.as_ref() else {
+        return Ok(());
+    };
+    write_tar(path, args, input_data)
+        .with_context(|| format!("Failed to write reproducer `{}`", path.display()))
+}
+
+fn write_tar(path: &Path, args: &Args, input_data: &InputData) -> Result {
+    let root = PathBuf::from(
+        path.file_stem()
+            .context("--reproduce path must have a file name")?,
+    );
+    let mut builder = tar::Builder::new(BufWriter::new(std::fs::File::create(path)?));
+
+    append(
+        &mut builder,
+        &root.join("response.txt"),
+        response_file(args)?.as_bytes(),
+    )?;
+    append(
+        &mut builder,
+        &root.join("version.txt"),
+        format!("Wild version {}\n", env!("CARGO_PKG_VERSION")).as_bytes(),
+    )?;
+
+    let mut added = HashSet::new();
+    for input in &input_data.read_paths {
+        let relative = relative_path(input)?;
+        if !added.insert(relative.clone()) {
+            continue;
+        }
+        let mut data = std::fs::read(input)
+            .with_context(|| format!("Failed to read `{}`", input.display()))?;
+        if matches!(FileKind::identify_bytes(&data), Ok(FileKind::Text)) {
+            data = relativise_script(&data);
+        }
+        append(&mut builder, &root.join(relative), &data)?;
+    }
+
+    builder.into_inner()?.flush()?;
+    Ok(())
+}
+
+fn append(builder: &mut tar::Builder<impl std::io::Write>, path: &Path, data: &[u8]) -> Result {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    builder.append_data(&mut header, path, data)?;
+    Ok(())
+}
+
+/// Returns the path within the reproducer's root directory at which we store `path`.
+fn relative_path(path: &Path) -> Result<PathBuf> {
+    let absolute = std::path::absolute(path)
+        .with_context(|| format!("Failed to make `{}` absolute", path.display()))?
+        .normalize();
+    Ok(absolute
+        .strip_prefix("/")
+        .map(Path::to_owned)
+        .unwrap_or(absolute))
+}
+
+/// Returns `arg` with its path made relative to the reproducer's root directory.
+fn rewrite_path(arg: &str) -> Result<String> {
+    Ok(relative_path(Path::new(arg))?
+        .to_string_lossy()
+        .into_owned())
+}
+
+/// Returns just the file name of the output path `path`.
+fn output_file_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map_or_else(|| path.to_owned(), |f| f.to_string_lossy().into_owned())
+}
+
+/// Returns the contents of `response.txt` for the supplied arguments. The argument parser recorded
+/// which of our arguments contain paths, so those are all that we need to change.
+fn response_file(args: &Args) -> Result<String> {
+    let mut out: Vec<String> = Vec::new();
+    let mut raw_paths = args.raw_paths.iter().peekable();
+    for (index, arg) in args.raw_args.iter().enumerate() {
+        let Some(raw_path) = raw_paths.next_if(|p| p.index == index) else {
+            out.push(arg.clone());
+            continue;
+        };
+        let (prefix, path) = arg.split_at(arg.len() - raw_path.len);
+        match raw_path.kind {
+            RawPathKind::Input => out.push(format!("{prefix}{}", rewrite_path(path)?)),
+            // Write the output into the current directory rather than where it originally went.
+            RawPathKind::Output => out.push(format!("{prefix}{}", output_file_name(path))),
+            RawPathKind::Reproduce => {
+                // If the path was a separate argument, then drop `--reproduce` as well.
+                if prefix.is_empty() {
+                    out.pop();
+                }
+            }
+        }
+    }
+
+    Ok(out
+        .iter()
+        .map(|arg| quote(arg))
+        .collect::<Vec<_>>()
+        .join("\n")
+        + "\n")
+}
+
+/// Quotes `arg` if necessary so that it'll be parsed as a single argument from a response file.
+fn quote(arg: &str) -> String {
+    if !arg.is_empty()
+        && !arg
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '"' | '\'' | '\\'))
+    {
+        return arg.to_owned();
+    }
+    let mut out = String::from('"');
+    for c in arg.chars() {
+        if matches!(c, '"' | '\\') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out.push('"');
+    out
+}
+
+/// Makes absolute paths in a linker script relative, so that they refer to the copies that we store
+/// in the reproducer rather than to files on the machine doing the linking. Paths in linker scripts
+/// can be preceded by whitespace, an opening parenthesis or a comma. We take care not to touch the
+/// start of comments.
+fn relativise_script(script: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(script.len());
+    let mut previous = b' ';
+    for (i, &b) in script.iter().enumerate() {
+        let is_path_start = b == b'/'
+            && (previous.is_ascii_whitespace() || previous == b'(' || previous == b',')
+            && script.get(i + 1) != Some(&b'*');
+        if !is_path_start {
+            out.push(b);
+        }
+        previous = b;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(args: &[&str]) -> String {
+        response_file(&crate::args::parse(args.iter()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_response_file() {
+        assert_eq!(
+            response(&[
+                "-m",
+                "elf_x86_64",
+                "--reproduce=repro.tar",
+                "-o",
+                "/tmp/out/a.out",
+                "-L/usr/lib",
+                "-L",
+                "/lib",
+                "--version-script=/a/b.ver",
+                "-z",
+                "now",
+                "--dynamic-linker",
+                "/lib64/ld-linux-x86-64.so.2",
+                "/x/y z.o",
+                "-lc",
+            ]),
+            "-m\nelf_x86_64\n-o\na.out\n-Lusr/lib\n-L\nlib\n--version-script=a/b.ver\n-z\nnow\n\
+             --dynamic-linker\n/lib64/ld-linux-x86-64.so.2\n\"x/y z.o\"\n-lc\n"
+        );
+    }
+
+    #[test]
+    fn test_response_file_value_options() {
+        assert_eq!(
+            response(&[
+                "--sort-section",
+                "name",
+                "--sort-section=alignment",
+                "--output=/tmp/out/b.out",
+                "--output",
+                "/tmp/out/c.out",
+                "-o/tmp/out/d.out",
+                "--reproduce",
+                "/tmp/repro.tar",
+                "-rpath",
+                "/opt/lib",
+                "--rpath-link=/r",
+                "--retain-symbols-file",
+                "/s.txt",
+                "--dependency-file=/tmp/out/e.d",
+                "--sysroot=/sysroot",
+                "/x/y.o",
+            ]),
+            "--sort-section\nname\n--sort-section=alignment\n--output=b.out\n--output\nc.out\n\
+             -od.out\n-rpath\n/opt/lib\n--rpath-link=r\n--retain-symbols-file\ns.txt\n\
+             --dependency-file=e.d\n--sysroot=sysroot\nx/y.o\n"
+        );
+    }
+
+    #[test]
+    fn test_relativise_script() {
+        assert_eq!(
+            relativise_script(
+                b"/* GNU ld script */\nGROUP ( /lib/libc.so.6 AS_NEEDED (/lib/ld.so) )"
+            ),
+            b"/* GNU ld script */\nGROUP ( lib/libc.so.6 AS_NEEDED (lib/ld.so) )"
+        );
+    }
+}
@@ -3,8 +3,11 @@
 //! assigned to.
 
 use self::part_id::NOTE_GNU_PROPERTY;
+use self::part_id::RISCV_ATTRIBUTES;
 use crate::args::Args;
+use crate::args::UnresolvedSymbolAction;
 use crate::debug_assert_bail;
+use crate::diagnostics::WarningCategory;
 use crate::elf::File;
 use crate::error::Error;
 use crate::error::Result;
@@ -15,12 +18,14 @@ use crate::input_data::FileId;
 use crate::input_data::InputRef;
 use crate::input_data::PRELUDE_FILE_ID;
 use crate::input_data::UNINITIALISED_FILE_ID;
+use crate::output_section_id;
 use crate::output_section_id::CustomSectionDetails;
 use crate::output_section_id::OutputSections;
 use crate::output_section_id::OutputSectionsBuilder;
 use crate::output_section_id::SectionName;
 use crate::output_section_map::OutputSectionMap;
 use crate::parsing::InternalSymDefInfo;
+use crate::parsing::ParsedInput;
 use crate::parsing::ParsedInputObject;
 use crate::part_id;
 use crate::part_id::PartId;
@@ -37,6 +42,7 @@ use crate::symbol_db::SymbolDb;
 use crate::symbol_db::SymbolId;
 use crate::symbol_db::SymbolIdRange;
 use anyhow::Context;
+use anyhow::anyhow;
 use anyhow::bail;
 use atomic_take::AtomicTake;
 use bitflags::bitflags;
@@ -44,8 +50,9 @@ use crossbeam_queue::ArrayQueue;
 use crossbeam_queue::SegQueue;
 use linker_utils::elf::SectionFlags;
 use linker_utils::elf::SectionType;
+use linker_utils::elf::gnu_warning_symbol;
 use linker_utils::elf::shf;
-use object::LittleEndian;
+use object::read::elf::SectionHeader as _;
 use object::read::elf::Sym as _;
 use rayon::iter::IntoParallelRefMutIterator;
 use rayon::iter::ParallelIterator;
@@ -88,6 +95,8 @@ pub fn resolve_symbols_and_sections<'data>(
 
     crate::symbol_db::resolve_alternative_symbol_definitions(symbol_db, &resolved_groups)?;
 
+    check_version_script_assignments(symbol_db, &resolved_groups)?;
+
     Ok(ResolutionOutputs {
         groups: resolved_groups,
         output_sections,
@@ -411,6 +420,9 @@ pub(crate) enum SectionSlot {
 
     // GNU property section (.note.gnu.property)
     NoteGnuProperty(object::SectionIndex),
+
+    // RISC-V attributes section (.riscv.attributes)
+    RiscVAttributes(object::SectionIndex),
 }
 
 #[derive(Clone, Copy)]
@@ -423,6 +435,10 @@ pub(crate) struct UnloadedSection {
     /// Whether the section has a name that makes it eligible for generation of __start_ / __stop_
     /// symbols. In particular, the name of the section doesn't start with a ".".
     pub(crate) start_stop_eligible: bool,
+
+    /// Whether the section has SHF_LINK_ORDER set and links to another section. Such sections are
+    /// loaded if and only if the section that they link to is loaded.
+    pub(crate) is_link_order: bool,
 }
 
 impl UnloadedSection {
@@ -431,6 +447,7 @@ impl UnloadedSection {
             part_id,
             last_frame_index: None,
             start_stop_eligible: false,
+            is_link_order: false,
         }
     }
 }
@@ -476,6 +493,8 @@ fn assign_section_ids<'data>(
     args: &Args,
 ) -> Result<OutputSections<'data>> {
     let mut output_sections_builder = OutputSectionsBuilder::with_base_address(args.base_address());
+    output_sections_builder.keep_text_section_prefix(args.keep_text_section_prefix);
+    output_sections_builder.relocation_format(args.arch.relocation_format());
     for group in resolved {
         for file in &mut group.files {
             if let ResolvedFile::Object(s) = file {
@@ -543,6 +562,10 @@ struct UndefinedSymbol<'data> {
     ignore_if_loaded: Option<FileId>,
     name: PreHashedSymbolName<'data>,
     symbol_id: SymbolId,
+
+    /// Whether the reference comes from a shared object and isn't weak. Such references are
+    /// subject to --allow-shlib-undefined.
+    is_strong_shared_object_reference: bool,
 }
 
 fn load_prelude(
@@ -605,6 +628,8 @@ fn canonicalise_undefined_symbols<'data>(
     // for any given name will be the one for the earliest file that refers to that symbol.
     undefined_symbols.sort_by_key(|u| u.symbol_id);
 
+    let mut shared_object_references = Vec::new();
+
     for undefined in undefined_symbols {
         let is_defined = undefined.ignore_if_loaded.is_some_and(|file_id| {
             !matches!(
@@ -653,11 +678,101 @@ fn canonicalise_undefined_symbols<'data>(
                 }
             }
         }
+
+        if undefined.is_strong_shared_object_reference {
+            shared_object_references.push(undefined.symbol_id);
+        }
     }
 
+    report_undefined_in_shared_objects(&shared_object_references, symbol_db)?;
+
     Ok(custom_start_stop_defs)
 }
 
+/// Reports non-weak references from shared objects to symbols that nothing defines. This is what
+/// GNU ld does by default when linking an executable, since such references would fail at runtime.
+fn report_undefined_in_shared_objects(symbol_ids: &[SymbolId], symbol_db: &SymbolDb) -> Result {
+    let action = symbol_db.args.unresolved_symbols_in_shared_libs();
+    if action == UnresolvedSymbolAction::Ignore {
+        return Ok(());
+    }
+
+    let errors = symbol_ids
+        .iter()
+        .filter(|&&symbol_id| {
+            // Start/stop symbols get defined by the epilogue, so they're not undefined.
+            let definition = symbol_db.definition(symbol_id);
+            matches!(
+                symbol_db.file(symbol_db.file_id_for_symbol(definition)),
+                ParsedInput::Object(_)
+            )
+        })
+        .map(|&symbol_id| {
+            anyhow!(
+                "Undefined symbol {}, referenced by {}",
+                symbol_db.symbol_name_for_display(symbol_id),
+                symbol_db.file(symbol_db.file_id_for_symbol(symbol_id)),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    if action == UnresolvedSymbolAction::Warn {
+        for error in &errors {
+            crate::diagnostics::warning(WarningCategory::UndefinedSymbol, &error.to_string());
+        }
+    } else if let Some(error) = crate::diagnostics::combine_errors(errors, symbol_db.args) {
+        return Err(error);
+    }
+
+    Ok(())
+}
+
+/// Reports symbols that the version script names explicitly, but which aren't defined. This is an
+/// error by default, matching recent versions of LLD. Symbols defined only by shared objects don't
+/// count, since we can't assign versions to symbols that we don't define.
+fn check_version_script_assignments(symbol_db: &SymbolDb, groups: &[ResolvedGroup]) -> Result {
+    if symbol_db.args.undefined_version {
+        return Ok(());
+    }
+
+    let is_defined = |name: &str| {
+        let Some(symbol_id) =
+            symbol_db.get_unversioned(&UnversionedSymbolName::prehashed(name.as_bytes()))
+        else {
+            return false;
+        };
+        let file_id = symbol_db.file_id_for_symbol(symbol_db.definition(symbol_id));
+        match symbol_db.file(file_id) {
+            ParsedInput::Object(obj) => {
+                !obj.is_dynamic()
+                    && !matches!(
+                        groups[file_id.group()].files[file_id.file()],
+                        ResolvedFile::NotLoaded(_)
+                    )
+            }
+            ParsedInput::Prelude(_) | ParsedInput::Epilogue(_) => true,
+        }
+    };
+
+    let errors = symbol_db
+        .version_script
+        .exact_global_patterns()
+        .filter(|(_, name)| !is_defined(name))
+        .map(|(version, name)| {
+            anyhow!(
+                "Version script assignment of `{version}` to symbol `{name}` failed: \
+                 symbol not defined"
+            )
+        })
+        .collect::<Vec<_>>();
+
+    if let Some(error) = crate::diagnostics::combine_errors(errors, symbol_db.args) {
+        return Err(error);
+    }
+
+    Ok(())
+}
+
 fn allocate_start_stop_symbol_id<'data>(
     name: PreHashed<UnversionedSymbolName<'data>>,
     symbol_db: &mut SymbolDb<'data>,
@@ -734,13 +849,16 @@ fn resolve_sections_for_object<'data>(
 ) -> Result<Vec<SectionSlot>> {
     let sections = obj
         .object
-        .sections
-        .enumerate()
+        .sections()
         .map(|(input_section_index, input_section)| {
+            let input_section = &input_section;
             if let Some(unloaded) =
                 UnresolvedSection::from_section(obj.object, input_section, args)?
             {
-                let section_flags = SectionFlags::from_header(input_section);
+                let e = obj.object.endian;
+                let section_flags = SectionFlags::from_header(input_section, e);
+                let is_link_order =
+                    section_flags.contains(shf::LINK_ORDER) && input_section.sh_link(e) != 0;
                 let mut part_id = part_id::CUSTOM_PLACEHOLDER;
                 let mut custom_section = None;
                 match unloaded.part_id {
@@ -749,7 +867,7 @@ fn resolve_sections_for_object<'data>(
                             name: unloaded.name(),
                             alignment,
                             section_flags,
-                            ty: SectionType::from_header(input_section),
+                            ty: SectionType::from_header(input_section, e),
                             index: input_section_index,
                         });
                     }
@@ -770,17 +888,23 @@ fn resolve_sections_for_object<'data>(
                         TemporaryPartId::BuiltIn(id) if id == NOTE_GNU_PROPERTY => {
                             SectionSlot::NoteGnuProperty(input_section_index)
                         }
+                        TemporaryPartId::BuiltIn(id) if id == RISCV_ATTRIBUTES => {
+                            SectionSlot::RiscVAttributes(input_section_index)
+                        }
                         TemporaryPartId::BuiltIn(id)
-                            if id
-                                .output_section_id()
-                                .built_in_details()
-                                .section_flags
-                                .should_retain() =>
+                            if section_flags.should_retain()
+                                || id
+                                    .output_section_id()
+                                    .built_in_details()
+                                    .section_flags
+                                    .should_retain() =>
                         {
                             SectionSlot::MustLoad(UnloadedSection::new(id))
                         }
                         TemporaryPartId::BuiltIn(id) => {
-                            SectionSlot::Unloaded(UnloadedSection::new(id))
+                            let mut unloaded_section = UnloadedSection::new(id);
+                            unloaded_section.is_link_order = is_link_order;
+                            SectionSlot::Unloaded(unloaded_section)
                         }
                         TemporaryPartId::Custom(custom_section_id, _alignment) => {
                             let section_name = custom_section_id.name.bytes();
@@ -793,7 +917,13 @@ fn resolve_sections_for_object<'data>(
                                 } else {
                                     SectionSlot::UnloadedDebugInfo(part_id::CUSTOM_PLACEHOLDER)
                                 }
-                            } else if section_flags.should_retain() {
+                            } else if section_flags.should_retain()
+                                || output_section_id::init_priority(section_name).is_some()
+                                // We only get `.gnu.warning.SYM` sections here when we're writing
+                                // a shared object, in which case nothing references them, but we
+                                // still need to keep them.
+                                || gnu_warning_symbol(section_name).is_some()
+                            {
                                 SectionSlot::MustLoad(UnloadedSection::new(
                                     part_id::CUSTOM_PLACEHOLDER,
                                 ))
@@ -802,6 +932,7 @@ fn resolve_sections_for_object<'data>(
                                     UnloadedSection::new(part_id::CUSTOM_PLACEHOLDER);
                                 unloaded_section.start_stop_eligible =
                                     !section_name.starts_with(b".");
+                                unloaded_section.is_link_order = is_link_order;
                                 SectionSlot::Unloaded(unloaded_section)
                             }
                         }
@@ -826,23 +957,19 @@ fn resolve_symbols<'data>(
     undefined_symbols_out: &SegQueue<UndefinedSymbol<'data>>,
     definitions_out: &mut [SymbolId],
 ) -> Result {
-    obj.object
-        .symbols
-        .enumerate()
-        .zip(definitions_out)
-        .try_for_each(
-            |((local_symbol_index, local_symbol), definition)| -> Result {
-                resolve_symbol(
-                    local_symbol_index,
-                    local_symbol,
-                    definition,
-                    resources,
-                    obj,
-                    undefined_symbols_out,
-                    false,
-                )
-            },
-        )?;
+    obj.object.symbols().zip(definitions_out).try_for_each(
+        |((local_symbol_index, local_symbol), definition)| -> Result {
+            resolve_symbol(
+                local_symbol_index,
+                &local_symbol,
+                definition,
+                resources,
+                obj,
+                undefined_symbols_out,
+                false,
+            )
+        },
+    )?;
     Ok(())
 }
 
@@ -852,23 +979,19 @@ fn resolve_dynamic_symbols<'data>(
     undefined_symbols_out: &SegQueue<UndefinedSymbol<'data>>,
     definitions_out: &mut [SymbolId],
 ) -> Result {
-    obj.object
-        .symbols
-        .enumerate()
-        .zip(definitions_out)
-        .try_for_each(
-            |((local_symbol_index, local_symbol), definition)| -> Result {
-                resolve_symbol(
-                    local_symbol_index,
-                    local_symbol,
-                    definition,
-                    resources,
-                    obj,
-                    undefined_symbols_out,
-                    true,
-                )
-            },
-        )?;
+    obj.object.symbols().zip(definitions_out).try_for_each(
+        |((local_symbol_index, local_symbol), definition)| -> Result {
+            resolve_symbol(
+                local_symbol_index,
+                &local_symbol,
+                definition,
+                resources,
+                obj,
+                undefined_symbols_out,
+                true,
+            )
+        },
+    )?;
     Ok(())
 }
 
@@ -896,17 +1019,32 @@ fn resolve_symbol<'data>(
         name_info,
     );
 
-    assert!(!local_symbol.is_definition(LittleEndian));
+    assert!(!local_symbol.is_definition(obj.object.endian));
     let prehashed_name = PreHashedSymbolName::from_raw(&name_info);
+    let is_strong_shared_object_reference = is_from_shared_object && !local_symbol.is_weak();
+
+    // Regular objects can't use definitions from shared objects that we only found via DT_NEEDED
+    // entries, since that would require us to add DT_NEEDED entries of our own.
+    let symbol_id = resources
+        .symbol_db
+        .get(&prehashed_name)
+        .filter(|&symbol_id| {
+            is_from_shared_object
+                || !resources
+                    .symbol_db
+                    .is_indirect_dependency(resources.symbol_db.file_id_for_symbol(symbol_id))
+        });
 
-    match resources.symbol_db.get(&prehashed_name) {
+    match symbol_id {
         Some(symbol_id) => {
             *definition_out = symbol_id;
             let symbol_file_id = resources.symbol_db.file_id_for_symbol(symbol_id);
 
             if symbol_file_id != obj.file_id && !local_symbol.is_weak() {
-                // Undefined symbols in shared objects don't trigger loading of other objects.
-                if !is_from_shared_object {
+                // Undefined symbols in shared objects don't trigger loading of other shared
+                // objects. Like GNU ld however, they do cause archive members that define the
+                // symbol to be loaded.
+                if !is_from_shared_object || !resources.symbol_db.is_shared_object(symbol_file_id) {
                     resources.request_file_id(symbol_file_id);
                 }
             } else if symbol_file_id != PRELUDE_FILE_ID {
@@ -920,6 +1058,7 @@ fn resolve_symbol<'data>(
                     ignore_if_loaded: Some(symbol_file_id),
                     name: prehashed_name,
                     symbol_id: obj.symbol_id_range.input_to_id(local_symbol_index),
+                    is_strong_shared_object_reference,
                 });
             }
         }
@@ -928,6 +1067,7 @@ fn resolve_symbol<'data>(
                 ignore_if_loaded: None,
                 name: prehashed_name,
                 symbol_id: obj.symbol_id_range.input_to_id(local_symbol_index),
+                is_strong_shared_object_reference,
             });
         }
     }
@@ -966,7 +1106,7 @@ impl SectionSlot {
             SectionSlot::MergeStrings(section) => section.part_id = part_id,
             SectionSlot::UnloadedDebugInfo(out) => *out = part_id,
             SectionSlot::LoadedDebugInfo(section) => section.part_id = part_id,
-            SectionSlot::NoteGnuProperty(_) => {}
+            SectionSlot::NoteGnuProperty(_) | SectionSlot::RiscVAttributes(_) => {}
         }
     }
 
@@ -0,0 +1,328 @@
+//! RISC-V 64 (LP64D ABI) support. Unlike other architectures, RISC-V relies on the linker to
+//! shorten instruction sequences, deleting the bytes that are no longer needed. The relaxations
+//! that do that are found here, while the rest of the process is in `shrinking`.
+
+use crate::arch::PltProtection;
+use crate::elf::PLT_ENTRY_SIZE;
+use crate::resolution::ValueFlags;
+use crate::shrinking::RelaxationEdit;
+use crate::shrinking::ShrinkContext;
+use crate::shrinking::TargetLocation;
+use anyhow::Result;
+use anyhow::anyhow;
+use anyhow::ensure;
+use linker_utils::elf::DynamicRelocationKind;
+use linker_utils::elf::RelocationKind;
+use linker_utils::elf::RelocationKindInfo;
+use linker_utils::elf::riscv64_rel_type_to_string;
+use linker_utils::relaxation::RelocationModifier;
+use linker_utils::riscv64::NOP;
+use linker_utils::riscv64::RelaxationKind;
+use linker_utils::riscv64::RiscVInstruction;
+use linker_utils::riscv64::TLS_DTV_OFFSET;
+use linker_utils::riscv64::relocation_type_from_raw;
+use object::Endianness;
+use object::elf::R_RISCV_ALIGN;
+use object::elf::R_RISCV_CALL;
+use object::elf::R_RISCV_CALL_PLT;
+use object::elf::R_RISCV_RELAX;
+use object::elf::R_RISCV_TPREL_ADD;
+use object::elf::R_RISCV_TPREL_HI20;
+use object::elf::R_RISCV_TPREL_LO12_I;
+use object::elf::R_RISCV_TPREL_LO12_S;
+
+pub(crate) struct RiscV64;
+
+const PLT_ENTRY_TEMPLATE: &[u8] = &[
+    0x17, 0x0e, 0x00, 0x00, // auipc t3, %pcrel_hi(.got.plt[n])
+    0x03, 0x3e, 0x0e, 0x00, // ld t3, %pcrel_lo(.got.plt[n])(t3)
+    0x67, 0x03, 0x0e, 0x00, // jalr t1, t3
+    0x13, 0x00, 0x00, 0x00, // nop
+];
+
+const _ASSERTS: () = {
+    assert!(PLT_ENTRY_TEMPLATE.len() as u64 == PLT_ENTRY_SIZE);
+};
+
+/// How far a `jal` can reach in either direction.
+const JAL_REACH: u64 = 1 << 20;
+
+/// The largest offset from the thread pointer that a 12 bit immediate can hold.
+const TPREL_LO12_LIMIT: u64 = 0x800;
+
+const JAL_OPCODE: u32 = 0x6f;
+const OPCODE_MASK: u32 = 0x7f;
+const C_NOP: u16 = 0x0001;
+const TP_REGISTER: u32 = 4;
+
+impl crate::arch::Arch for RiscV64 {
+    type Relaxation = Relaxation;
+
+    fn elf_header_arch_magic() -> u16 {
+        object::elf::EM_RISCV
+    }
+
+    // The table of relocations is documented here:
+    // https://github.com/riscv-non-isa/riscv-elf-psabi-doc/blob/master/riscv-elf.adoc.
+    #[inline(always)]
+    fn relocation_from_raw(r_type: u32) -> Result<RelocationKindInfo> {
+        relocation_type_from_raw(r_type).ok_or_else(|| {
+            anyhow!(
+                "Unsupported relocation type {}",
+                Self::rel_type_to_string(r_type)
+            )
+        })
+    }
+
+    fn tls_dtv_offset() -> u64 {
+        TLS_DTV_OFFSET
+    }
+
+    fn get_dynamic_relocation_type(relocation: DynamicRelocationKind) -> u32 {
+        relocation.riscv64_r_type()
+    }
+
+    fn rel_type_to_string(r_type: u32) -> std::borrow::Cow<'static, str> {
+        riscv64_rel_type_to_string(r_type)
+    }
+
+    fn write_plt_entry(
+        plt_entry: &mut [u8],
+        got_address: u64,
+        plt_address: u64,
+        _got_base: Option<u64>,
+        _protection: PltProtection,
+    ) -> crate::error::Result {
+        plt_entry.copy_from_slice(PLT_ENTRY_TEMPLATE);
+        let offset = got_address.wrapping_sub(plt_address);
+        ensure!(
+            (-(1 << 31)..(1 << 31)).contains(&(offset as i64)),
+            "PLT is more than 2GiB away from GOT"
+        );
+        RiscVInstruction::UType.write_to_value(offset, &mut plt_entry[0..4]);
+        RiscVInstruction::IType.write_to_value(offset, &mut plt_entry[4..8]);
+        Ok(())
+    }
+
+    fn find_relaxation_edits(
+        _section_data: &[u8],
+        relocations: &crate::elf::RelocationList,
+        context: &ShrinkContext,
+        edits: &mut Vec<RelaxationEdit>,
+    ) -> crate::error::Result {
+        // Only little endian is supported for this architecture.
+        let e = Endianness::Little;
+        let mut deleted = 0;
+        for (index, rel) in relocations.iter().enumerate() {
+            let r_type = rel.r_type(e, false);
+            let offset = rel.r_offset.get(e);
+            let addend = rel.r_addend.get(e);
+            // Optional relaxations are only permitted when the compiler marked the instruction
+            // with an `R_RISCV_RELAX` at the same offset.
+            let can_relax = context.relax
+                && relocations.get(index + 1).is_some_and(|next| {
+                    next.r_type(e, false) == R_RISCV_RELAX && next.r_offset.get(e) == offset
+                });
+            let tls_offset_fits = || {
+                context.tls_size.is_some_and(|tls_size| {
+                    u64::try_from(addend)
+                        .is_ok_and(|addend| tls_size.saturating_add(addend) < TPREL_LO12_LIMIT)
+                })
+            };
+            let edit = |delete_offset, delete_len| RelaxationEdit {
+                offset,
+                r_type,
+                delete_offset,
+                delete_len,
+            };
+            let edit = match r_type {
+                R_RISCV_ALIGN => {
+                    // The addend is the number of bytes of padding that the assembler inserted.
+                    // That's enough for the worst case, which is 2 bytes short of the alignment.
+                    let padding = u64::try_from(addend)
+                        .map_err(|_| anyhow!("R_RISCV_ALIGN has negative addend {addend}"))?;
+                    let alignment = (padding + 2).next_power_of_two();
+                    let output_offset = offset - deleted;
+                    let needed = output_offset.next_multiple_of(alignment) - output_offset;
+                    ensure!(
+                        needed <= padding,
+                        "R_RISCV_ALIGN at 0x{offset:x} has {padding} bytes of padding, but needs \
+                         {needed}"
+                    );
+                    if needed == padding {
+                        continue;
+                    }
+                    edit(offset + needed, padding - needed)
+                }
+                R_RISCV_CALL | R_RISCV_CALL_PLT if can_relax => {
+                    let in_range = match (context.target_location)(&rel) {
+                        // Deleting bytes can only bring the call closer to its target.
+                        TargetLocation::SameSection(target) => target.abs_diff(offset) < JAL_REACH,
+                        TargetLocation::Code => context.can_reach_all_code(JAL_REACH),
+                        TargetLocation::Unknown => false,
+                    };
+                    if !in_range {
+                        continue;
+                    }
+                    // Replace `auipc` + `jalr` with `jal`.
+                    edit(offset + 4, 4)
+                }
+                R_RISCV_TPREL_HI20 | R_RISCV_TPREL_ADD if can_relax && tls_offset_fits() => {
+                    // The `lui` and the `add` that adds the thread pointer aren't needed if the
+                    // offset fits in the 12 bit immediate of the final instruction.
+                    edit(offset, 4)
+                }
+                R_RISCV_TPREL_LO12_I | R_RISCV_TPREL_LO12_S if can_relax && tls_offset_fits() => {
+                    edit(offset, 0)
+                }
+                _ => continue,
+            };
+            deleted += edit.delete_len;
+            edits.push(edit);
+        }
+        Ok(())
+    }
+
+    fn apply_relaxation_edit(input: &[u8], edit: &RelaxationEdit, out: &mut [u8]) {
+        match edit.r_type {
+            R_RISCV_ALIGN => {
+                let mut padding = &mut out[..(edit.delete_offset - edit.offset) as usize];
+                if padding.len() % 4 == 2 {
+                    padding[..2].copy_from_slice(&C_NOP.to_le_bytes());
+                    padding = &mut padding[2..];
+                }
+                for nop in padding.chunks_exact_mut(4) {
+                    nop.copy_from_slice(&NOP.to_le_bytes());
+                }
+            }
+            R_RISCV_CALL | R_RISCV_CALL_PLT => {
+                // `jal` links into the same register as the `jalr` that it replaces.
+                let jalr = read_instruction(&input[4..]);
+                write_instruction(out, JAL_OPCODE | (jalr & 0xf80));
+            }
+            R_RISCV_TPREL_LO12_I | R_RISCV_TPREL_LO12_S => {
+                // Use the thread pointer as the base register in place of the result of the
+                // deleted `add`.
+                let instruction = read_instruction(input);
+                write_instruction(out, instruction & !(0x1f << 15) | TP_REGISTER << 15);
+            }
+            _ => {}
+        }
+    }
+
+    fn merge_e_flags(flags: impl Iterator<Item = u32>) -> Result<u32> {
+        crate::riscv_attributes::merge_e_flags(flags)
+    }
+}
+
+fn read_instruction(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes(*bytes.first_chunk::<4>().unwrap())
+}
+
+fn write_instruction(out: &mut [u8], instruction: u32) {
+    out[..4].copy_from_slice(&instruction.to_le_bytes());
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Relaxation {
+    kind: RelaxationKind,
+    rel_info: RelocationKindInfo,
+}
+
+impl Relaxation {
+    fn new_unchanged(r_type: u32, kind: RelocationKind) -> Option<Self> {
+        let mut rel_info = relocation_type_from_raw(r_type)?;
+        rel_info.kind = kind;
+        Some(Relaxation {
+            kind: RelaxationKind::NoOp,
+            rel_info,
+        })
+    }
+
+    fn replace(kind: RelaxationKind, r_type: u32) -> Option<Self> {
+        Some(Relaxation {
+            kind,
+            rel_info: relocation_type_from_raw(r_type)?,
+        })
+    }
+}
+
+impl crate::arch::Relaxation for Relaxation {
+    #[allow(unused_variables)]
+    #[inline(always)]
+    fn new(
+        relocation_kind: u32,
+        section_bytes: &[u8],
+        offset_in_section: u64,
+        value_flags: ValueFlags,
+        output_kind: crate::args::OutputKind,
+        section_flags: linker_utils::elf::SectionFlags,
+        non_zero_address: bool,
+    ) -> Option<Self>
+    where
+        Self: std::marker::Sized,
+    {
+        // IFuncs cannot be referenced directly, they always need to go via the GOT.
+        if value_flags.contains(ValueFlags::IFUNC) {
+            return None;
+        }
+        let can_bypass_got = value_flags.contains(ValueFlags::CAN_BYPASS_GOT);
+        // TLSDESC can only be relaxed once we know the offset from the thread pointer.
+        let can_relax_tls_desc = can_bypass_got && output_kind.is_executable();
+
+        match relocation_kind {
+            R_RISCV_CALL | R_RISCV_CALL_PLT => {
+                // If we deleted the `jalr` during layout, then the `auipc` will have been
+                // replaced with a `jal`.
+                let offset = offset_in_section as usize;
+                let instruction = section_bytes
+                    .get(offset..offset + 4)
+                    .map_or(0, read_instruction);
+                if instruction & OPCODE_MASK == JAL_OPCODE {
+                    let kind = if can_bypass_got {
+                        RelocationKind::Relative
+                    } else {
+                        RelocationKind::PltRelative
+                    };
+                    return Self::new_unchanged(object::elf::R_RISCV_JAL, kind);
+                }
+                if can_bypass_got {
+                    return Self::new_unchanged(relocation_kind, RelocationKind::Relative);
+                }
+            }
+            object::elf::R_RISCV_PLT32 if can_bypass_got => {
+                return Self::new_unchanged(relocation_kind, RelocationKind::Relative);
+            }
+            object::elf::R_RISCV_TLSDESC_HI20 | object::elf::R_RISCV_TLSDESC_LOAD_LO12
+                if can_relax_tls_desc =>
+            {
+                return Self::replace(RelaxationKind::ReplaceWithNop, object::elf::R_RISCV_NONE);
+            }
+            object::elf::R_RISCV_TLSDESC_ADD_LO12 if can_relax_tls_desc => {
+                return Self::replace(RelaxationKind::TlsDescToLocalExecLui, R_RISCV_TPREL_HI20);
+            }
+            object::elf::R_RISCV_TLSDESC_CALL if can_relax_tls_desc => {
+                return Self::replace(RelaxationKind::TlsDescToLocalExecAddi, R_RISCV_TPREL_LO12_I);
+            }
+            _ => {}
+        }
+
+        None
+    }
+
+    fn apply(&self, section_bytes: &mut [u8], offset_in_section: &mut u64, addend: &mut i64) {
+        self.kind.apply(section_bytes, offset_in_section, addend);
+    }
+
+    fn rel_info(&self) -> RelocationKindInfo {
+        self.rel_info
+    }
+
+    fn debug_kind(&self) -> impl std::fmt::Debug {
+        &self.kind
+    }
+
+    fn next_modifier(&self) -> RelocationModifier {
+        self.kind.next_modifier()
+    }
+}
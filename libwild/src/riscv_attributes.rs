@@ -0,0 +1,463 @@
+//! Merging of the RISC-V specific information that objects carry about the ISA and ABI that they
+//! were compiled for. This is found in `.riscv.attributes` sections and in the ELF header flags.
+
+use crate::error::Result;
+use anyhow::Context as _;
+use anyhow::bail;
+use anyhow::ensure;
+use object::elf::EF_RISCV_FLOAT_ABI;
+use object::elf::EF_RISCV_RVC;
+use object::elf::EF_RISCV_RVE;
+use object::elf::EF_RISCV_TSO;
+
+const FORMAT_VERSION: u8 = b'A';
+const VENDOR_NAME: &[u8] = b"riscv";
+
+const TAG_FILE: u64 = 1;
+const TAG_STACK_ALIGN: u64 = 4;
+const TAG_ARCH: u64 = 5;
+const TAG_UNALIGNED_ACCESS: u64 = 6;
+const TAG_PRIV_SPEC: u64 = 8;
+const TAG_PRIV_SPEC_MINOR: u64 = 10;
+const TAG_PRIV_SPEC_REVISION: u64 = 12;
+const TAG_ATOMIC_ABI: u64 = 14;
+const TAG_X3_REG_USAGE: u64 = 16;
+
+/// The canonical order of single-letter extensions. Multi-letter extensions starting with `z` are
+/// ordered by the position of their second letter in this list.
+const CANONICAL_ORDER: &[u8] = b"iegmafdqlcbkjtpvh";
+
+/// The attributes from the file-level subsection of a `.riscv.attributes` section.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct RiscVAttributes {
+    stack_align: Option<u64>,
+    arch: Option<IsaString>,
+    unaligned_access: Option<u64>,
+    priv_spec: Option<[u64; 3]>,
+    atomic_abi: Option<u64>,
+    x3_reg_usage: Option<u64>,
+}
+
+/// A parsed ISA string such as `rv64i2p1_m2p0_zicsr2p0`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct IsaString {
+    xlen: u32,
+
+    /// Kept in canonical order.
+    extensions: Vec<Extension>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Extension {
+    name: String,
+    version: Option<(u32, u32)>,
+}
+
+impl RiscVAttributes {
+    pub(crate) fn parse(data: &[u8]) -> Result<Self> {
+        let mut attributes = RiscVAttributes::default();
+        let Some((&format, mut rest)) = data.split_first() else {
+            return Ok(attributes);
+        };
+        ensure!(
+            format == FORMAT_VERSION,
+            "Unsupported attributes format version 0x{format:x}"
+        );
+        while !rest.is_empty() {
+            let (subsection, remainder) = take_length_prefixed(rest, 0)?;
+            rest = remainder;
+            let vendor_end = memchr::memchr(0, subsection).context("Unterminated vendor name")?;
+            if &subsection[..vendor_end] != VENDOR_NAME {
+                continue;
+            }
+            let mut subsection = &subsection[vendor_end + 1..];
+            while !subsection.is_empty() {
+                let tag = read_uleb128(&mut subsection)?;
+                let tag_len = uleb128_len(tag);
+                let (contents, remainder) = take_length_prefixed(subsection, tag_len)?;
+                subsection = remainder;
+                // We don't support attributes that only apply to particular sections or symbols.
+                if tag == TAG_FILE {
+                    attributes.parse_file_attributes(contents)?;
+                }
+            }
+        }
+        Ok(attributes)
+    }
+
+    fn parse_file_attributes(&mut self, mut data: &[u8]) -> Result {
+        let mut priv_spec = [0; 3];
+        let mut has_priv_spec = false;
+        while !data.is_empty() {
+            let tag = read_uleb128(&mut data)?;
+            // Odd tags have string values, even tags have integer values.
+            if tag % 2 == 1 {
+                let end = memchr::memchr(0, data).context("Unterminated attribute string")?;
+                let value = std::str::from_utf8(&data[..end])?;
+                data = &data[end + 1..];
+                if tag == TAG_ARCH {
+                    self.arch = Some(IsaString::parse(value)?);
+                }
+                continue;
+            }
+            let value = read_uleb128(&mut data)?;
+            match tag {
+                TAG_STACK_ALIGN => self.stack_align = Some(value),
+                TAG_UNALIGNED_ACCESS => self.unaligned_access = Some(value),
+                TAG_PRIV_SPEC => {
+                    priv_spec[0] = value;
+                    has_priv_spec = true;
+                }
+                TAG_PRIV_SPEC_MINOR => {
+                    priv_spec[1] = value;
+                    has_priv_spec = true;
+                }
+                TAG_PRIV_SPEC_REVISION => {
+                    priv_spec[2] = value;
+                    has_priv_spec = true;
+                }
+                TAG_ATOMIC_ABI => self.atomic_abi = Some(value),
+                TAG_X3_REG_USAGE => self.x3_reg_usage = Some(value),
+                _ => {}
+            }
+        }
+        if has_priv_spec {
+            self.priv_spec = Some(priv_spec);
+        }
+        Ok(())
+    }
+
+    /// Merges `other` into `self`. The ISA extensions used are combined, as is whether unaligned
+    /// access is used. Attributes where we can't reasonably combine differing values must either
+    /// match, or we keep the first value that we saw.
+    pub(crate) fn merge(&mut self, other: &RiscVAttributes) -> Result {
+        match (self.stack_align, other.stack_align) {
+            (Some(a), Some(b)) if a != b => {
+                bail!("Conflicting stack alignment attributes {a} and {b}");
+            }
+            (None, b) => self.stack_align = b,
+            _ => {}
+        }
+        match (&mut self.arch, &other.arch) {
+            (Some(a), Some(b)) => a.merge(b)?,
+            (a @ None, b) => a.clone_from(b),
+            _ => {}
+        }
+        if let Some(b) = other.unaligned_access {
+            self.unaligned_access = Some(self.unaligned_access.unwrap_or(0) | b);
+        }
+        self.priv_spec = self.priv_spec.or(other.priv_spec);
+        self.atomic_abi = self.atomic_abi.or(other.atomic_abi);
+        self.x3_reg_usage = self.x3_reg_usage.or(other.x3_reg_usage);
+        Ok(())
+    }
+
+    /// Returns the contents of a `.riscv.attributes` section containing our attributes.
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        let mut attributes = Vec::new();
+        write_integer_attribute(&mut attributes, TAG_STACK_ALIGN, self.stack_align);
+        if let Some(arch) = &self.arch {
+            write_uleb128(&mut attributes, TAG_ARCH);
+            attributes.extend_from_slice(arch.to_string().as_bytes());
+            attributes.push(0);
+        }
+        write_integer_attribute(&mut attributes, TAG_UNALIGNED_ACCESS, self.unaligned_access);
+        if let Some([major, minor, revision]) = self.priv_spec {
+            write_integer_attribute(&mut attributes, TAG_PRIV_SPEC, Some(major));
+            write_integer_attribute(&mut attributes, TAG_PRIV_SPEC_MINOR, Some(minor));
+            write_integer_attribute(&mut attributes, TAG_PRIV_SPEC_REVISION, Some(revision));
+        }
+        write_integer_attribute(&mut attributes, TAG_ATOMIC_ABI, self.atomic_abi);
+        write_integer_attribute(&mut attributes, TAG_X3_REG_USAGE, self.x3_reg_usage);
+
+        let mut file_subsection = Vec::new();
+        write_uleb128(&mut file_subsection, TAG_FILE);
+        let length = (uleb128_len(TAG_FILE) + 4 + attributes.len()) as u32;
+        file_subsection.extend_from_slice(&length.to_le_bytes());
+        file_subsection.extend_from_slice(&attributes);
+
+        let mut out = vec![FORMAT_VERSION];
+        let length = (4 + VENDOR_NAME.len() + 1 + file_subsection.len()) as u32;
+        out.extend_from_slice(&length.to_le_bytes());
+        out.extend_from_slice(VENDOR_NAME);
+        out.push(0);
+        out.extend_from_slice(&file_subsection);
+        out
+    }
+}
+
+impl IsaString {
+    fn parse(text: &str) -> Result<Self> {
+        let lower = text.to_ascii_lowercase();
+        let rest = lower
+            .strip_prefix("rv")
+            .with_context(|| format!("Invalid ISA string `{text}`"))?;
+        let digits_end = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .with_context(|| format!("Invalid ISA string `{text}`"))?;
+        let xlen = rest[..digits_end]
+            .parse()
+            .with_context(|| format!("Invalid ISA string `{text}`"))?;
+        let mut rest = &rest[digits_end..];
+        let mut extensions = Vec::new();
+        while let Some(first) = rest.chars().next() {
+            if first == '_' {
+                rest = &rest[1..];
+                continue;
+            }
+            let (name, version, remainder) = if matches!(first, 'z' | 's' | 'x') {
+                // Multi-letter extensions continue until the next underscore and may end with a
+                // version.
+                let end = rest.find('_').unwrap_or(rest.len());
+                let (name, version) = split_multi_letter_version(&rest[..end]);
+                (name, version, &rest[end..])
+            } else {
+                let (version, remainder) = parse_version(&rest[1..]);
+                (&rest[..1], version, remainder)
+            };
+            extensions.push(Extension {
+                name: name.to_owned(),
+                version,
+            });
+            rest = remainder;
+        }
+        let mut isa = IsaString { xlen, extensions };
+        isa.sort();
+        Ok(isa)
+    }
+
+    fn merge(&mut self, other: &IsaString) -> Result {
+        ensure!(
+            self.xlen == other.xlen,
+            "Cannot link RV{} and RV{} objects together",
+            self.xlen,
+            other.xlen
+        );
+        for extension in &other.extensions {
+            if let Some(existing) = self
+                .extensions
+                .iter_mut()
+                .find(|e| e.name == extension.name)
+            {
+                existing.version = existing.version.max(extension.version);
+            } else {
+                self.extensions.push(extension.clone());
+            }
+        }
+        self.sort();
+        Ok(())
+    }
+
+    fn sort(&mut self) {
+        self.extensions
+            .sort_by(|a, b| canonical_key(&a.name).cmp(&canonical_key(&b.name)));
+    }
+}
+
+impl std::fmt::Display for IsaString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "rv{}", self.xlen)?;
+        for (i, extension) in self.extensions.iter().enumerate() {
+            if i > 0 {
+                f.write_str("_")?;
+            }
+            f.write_str(&extension.name)?;
+            if let Some((major, minor)) = extension.version {
+                write!(f, "{major}p{minor}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns a key that sorts extensions into the canonical order. Single-letter extensions come
+/// first, then `z` extensions, then `s` extensions and lastly `x` extensions.
+fn canonical_key(name: &str) -> (u8, usize, &str) {
+    let position = |c: u8| {
+        CANONICAL_ORDER
+            .iter()
+            .position(|&o| o == c)
+            .unwrap_or(CANONICAL_ORDER.len())
+    };
+    let bytes = name.as_bytes();
+    match bytes {
+        [c] => (0, position(*c), name),
+        [b'z', second, ..] => (1, position(*second), name),
+        [b's', ..] => (2, 0, name),
+        _ => (3, 0, name),
+    }
+}
+
+/// Parses an optional version like `2p1` or `2` from the start of `text`.
+fn parse_version(text: &str) -> (Option<(u32, u32)>, &str) {
+    let major_end = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    let Ok(major) = text[..major_end].parse() else {
+        return (None, text);
+    };
+    let rest = &text[major_end..];
+    if let Some(after_p) = rest.strip_prefix('p') {
+        let minor_end = after_p
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(after_p.len());
+        if let Ok(minor) = after_p[..minor_end].parse() {
+            return (Some((major, minor)), &after_p[minor_end..]);
+        }
+    }
+    (Some((major, 0)), rest)
+}
+
+/// Splits a multi-letter extension like `zve32x1p0` into its name and version.
+fn split_multi_letter_version(text: &str) -> (&str, Option<(u32, u32)>) {
+    let without_last = text.trim_end_matches(|c: char| c.is_ascii_digit());
+    let last = &text[without_last.len()..];
+    if last.is_empty() {
+        return (text, None);
+    }
+    if let Some(before_p) = without_last.strip_suffix('p') {
+        let name = before_p.trim_end_matches(|c: char| c.is_ascii_digit());
+        let major = &before_p[name.len()..];
+        if !major.is_empty()
+            && name.len() > 1
+            && let (Ok(major), Ok(minor)) = (major.parse(), last.parse())
+        {
+            return (name, Some((major, minor)));
+        }
+    }
+    match last.parse() {
+        Ok(major) => (without_last, Some((major, 0))),
+        Err(_) => (text, None),
+    }
+}
+
+/// Merges the RISC-V specific ELF header flags of our input objects.
+pub(crate) fn merge_e_flags(flags: impl Iterator<Item = u32>) -> Result<u32> {
+    let mut merged: Option<u32> = None;
+    for flags in flags {
+        let Some(current) = merged.as_mut() else {
+            merged = Some(flags);
+            continue;
+        };
+        for (mask, name) in [
+            (EF_RISCV_FLOAT_ABI, "floating-point ABI"),
+            (EF_RISCV_RVE, "RVE"),
+        ] {
+            ensure!(
+                *current & mask == flags & mask,
+                "Cannot link objects with different {name} flags (0x{:x} and 0x{:x})",
+                *current & mask,
+                flags & mask
+            );
+        }
+        *current |= flags & (EF_RISCV_RVC | EF_RISCV_TSO);
+    }
+    Ok(merged.unwrap_or(0))
+}
+
+/// Splits off a region that starts with a 4 byte length, which includes the length itself and the
+/// `header_len` bytes before it.
+fn take_length_prefixed(data: &[u8], header_len: usize) -> Result<(&[u8], &[u8])> {
+    let length = data
+        .first_chunk::<4>()
+        .map(|bytes| u32::from_le_bytes(*bytes) as usize)
+        .context("Truncated attributes section")?;
+    let content_len = length
+        .checked_sub(header_len + 4)
+        .context("Invalid attributes length")?;
+    let data = &data[4..];
+    ensure!(data.len() >= content_len, "Truncated attributes section");
+    Ok(data.split_at(content_len))
+}
+
+fn read_uleb128(data: &mut &[u8]) -> Result<u64> {
+    let mut value = 0;
+    let mut shift = 0;
+    loop {
+        let (&byte, rest) = data.split_first().context("Truncated ULEB128 value")?;
+        *data = rest;
+        ensure!(shift < 64, "ULEB128 value too large");
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+        shift += 7;
+    }
+}
+
+fn write_integer_attribute(out: &mut Vec<u8>, tag: u64, value: Option<u64>) {
+    if let Some(value) = value {
+        write_uleb128(out, tag);
+        write_uleb128(out, value);
+    }
+}
+
+fn write_uleb128(out: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn uleb128_len(value: u64) -> usize {
+    (64 - value.leading_zeros() as usize).div_ceil(7).max(1)
+}
+
+#[test]
+fn test_merge_attributes() {
+    let attributes = |arch: &str, unaligned| RiscVAttributes {
+        stack_align: Some(16),
+        arch: Some(IsaString::parse(arch).unwrap()),
+        unaligned_access: Some(unaligned),
+        ..Default::default()
+    };
+    let mut merged = attributes("rv64i2p1_m2p0_c2p0_zicsr2p0", 0);
+    merged
+        .merge(&attributes("rv64i2p1_a2p1_f2p2_d2p2_zifencei2p0_zba1p0", 1))
+        .unwrap();
+    assert_eq!(
+        merged.arch.as_ref().unwrap().to_string(),
+        "rv64i2p1_m2p0_a2p1_f2p2_d2p2_c2p0_zicsr2p0_zifencei2p0_zba1p0"
+    );
+    assert_eq!(merged.unaligned_access, Some(1));
+
+    let bytes = merged.to_bytes();
+    assert_eq!(RiscVAttributes::parse(&bytes).unwrap(), merged);
+
+    let mut other = attributes("rv64imac", 0);
+    other.stack_align = Some(8);
+    assert!(merged.merge(&other).is_err());
+    assert!(merged.merge(&attributes("rv32i2p1", 0)).is_err());
+}
+
+#[test]
+fn test_parse_isa_string() {
+    assert_eq!(
+        IsaString::parse("RV64IMAFDC_zve32x1p0_xfoo")
+            .unwrap()
+            .to_string(),
+        "rv64i_m_a_f_d_c_zve32x1p0_xfoo"
+    );
+    assert_eq!(
+        IsaString::parse("rv64i2p1_zicsr2_zvl128b1p0")
+            .unwrap()
+            .to_string(),
+        "rv64i2p1_zicsr2p0_zvl128b1p0"
+    );
+}
+
+#[test]
+fn test_merge_e_flags() {
+    let rvc_double = EF_RISCV_RVC | object::elf::EF_RISCV_FLOAT_ABI_DOUBLE;
+    assert_eq!(
+        merge_e_flags([object::elf::EF_RISCV_FLOAT_ABI_DOUBLE, rvc_double].into_iter()).unwrap(),
+        rvc_double
+    );
+    assert!(merge_e_flags([rvc_double, EF_RISCV_RVC].into_iter()).is_err());
+    assert_eq!(merge_e_flags(std::iter::empty()).unwrap(), 0);
+}
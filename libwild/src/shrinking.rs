@@ -0,0 +1,167 @@
+//! Linker relaxations that delete bytes from executable sections. RISC-V and LoongArch code is
+//! written on the assumption that the linker will shorten instruction sequences where it can, so
+//! sections can get smaller during layout. Anything that refers to an offset within such a section
+//! needs to map the offset via the section's `ShrunkSection`.
+
+use crate::elf;
+use object::SectionIndex;
+
+/// A change that a linker relaxation makes to a section, possibly deleting some of its bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct RelaxationEdit {
+    /// The offset in the input section of the relocation that the edit is for.
+    pub(crate) offset: u64,
+
+    /// The type of that relocation.
+    pub(crate) r_type: u32,
+
+    /// The offset in the input section of the first byte that we delete.
+    pub(crate) delete_offset: u64,
+
+    /// The number of bytes that we delete. Zero if the edit only rewrites an instruction.
+    pub(crate) delete_len: u64,
+}
+
+/// What arch-specific code needs to know in order to decide which relaxations are safe.
+pub(crate) struct ShrinkContext<'a> {
+    /// Whether to apply relaxations that are optional. Edits that are needed for correctness, such
+    /// as reducing alignment padding after earlier deletions, are made regardless.
+    pub(crate) relax: bool,
+
+    /// An upper bound on the total size of executable code.
+    pub(crate) executable_size: u64,
+
+    /// An upper bound on the size of the TLS segment, or `None` if offsets from the thread pointer
+    /// aren't known at link time.
+    pub(crate) tls_size: Option<u64>,
+
+    /// Returns what we know about where the target of a relocation will end up.
+    pub(crate) target_location: &'a dyn Fn(&elf::Rela) -> TargetLocation,
+}
+
+/// Where the target of a relocation is, as far as we can tell before addresses are known.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TargetLocation {
+    /// At the specified offset in the section that contains the relocation. The target can't be
+    /// interposed.
+    SameSection(u64),
+
+    /// Somewhere in executable code, possibly a PLT entry.
+    Code,
+
+    /// Somewhere that we can't bound. e.g. an absolute or undefined symbol.
+    Unknown,
+}
+
+impl ShrinkContext<'_> {
+    /// Returns whether a branch that can reach `reach` bytes in either direction can reach
+    /// anywhere in executable code.
+    pub(crate) fn can_reach_all_code(&self, reach: u64) -> bool {
+        !crate::layout::branch_may_be_out_of_range(self.executable_size, reach)
+    }
+}
+
+/// A section that linker relaxation has made smaller.
+#[derive(Debug)]
+pub(crate) struct ShrunkSection {
+    pub(crate) section_index: SectionIndex,
+
+    /// Sorted by offset. Deleted ranges don't overlap and are in the same order.
+    pub(crate) edits: Vec<RelaxationEdit>,
+
+    /// The number of bytes deleted by each edit together with all edits before it.
+    deleted_totals: Vec<u64>,
+}
+
+impl ShrunkSection {
+    pub(crate) fn new(section_index: SectionIndex, edits: Vec<RelaxationEdit>) -> Self {
+        let deleted_totals = edits
+            .iter()
+            .scan(0, |total, edit| {
+                *total += edit.delete_len;
+                Some(*total)
+            })
+            .collect();
+        Self {
+            section_index,
+            edits,
+            deleted_totals,
+        }
+    }
+
+    pub(crate) fn total_deleted(&self) -> u64 {
+        self.deleted_totals.last().copied().unwrap_or(0)
+    }
+
+    /// Maps an offset in the input section to the corresponding offset in the output. Offsets of
+    /// deleted bytes map to wherever the deleted range would have started.
+    pub(crate) fn output_offset(&self, input_offset: u64) -> u64 {
+        let num_before = self
+            .edits
+            .partition_point(|edit| edit.delete_offset < input_offset);
+        let Some(previous) = num_before.checked_sub(1) else {
+            return input_offset;
+        };
+        let edit = &self.edits[previous];
+        let deleted = self.deleted_totals[previous];
+        if input_offset < edit.delete_offset + edit.delete_len {
+            edit.delete_offset - (deleted - edit.delete_len)
+        } else {
+            input_offset - deleted
+        }
+    }
+
+    /// Returns whether the byte at `input_offset` was deleted.
+    pub(crate) fn is_deleted(&self, input_offset: u64) -> bool {
+        let num_before = self
+            .edits
+            .partition_point(|edit| edit.delete_offset <= input_offset);
+        num_before.checked_sub(1).is_some_and(|previous| {
+            let edit = &self.edits[previous];
+            input_offset < edit.delete_offset + edit.delete_len
+        })
+    }
+
+    /// Copies `input` into `out`, leaving out the bytes that we deleted.
+    pub(crate) fn copy_without_deleted(&self, input: &[u8], out: &mut [u8]) {
+        let mut input_pos = 0;
+        let mut output_pos = 0;
+        for edit in &self.edits {
+            let len = edit.delete_offset as usize - input_pos;
+            out[output_pos..output_pos + len].copy_from_slice(&input[input_pos..input_pos + len]);
+            output_pos += len;
+            input_pos += len + edit.delete_len as usize;
+        }
+        out[output_pos..].copy_from_slice(&input[input_pos..]);
+    }
+}
+
+#[test]
+fn test_shrunk_section_offsets() {
+    let edit = |offset, delete_len| RelaxationEdit {
+        offset,
+        r_type: 0,
+        delete_offset: offset + 4,
+        delete_len,
+    };
+    let shrunk = ShrunkSection::new(SectionIndex(1), vec![edit(0, 4), edit(12, 2), edit(20, 0)]);
+    assert_eq!(shrunk.total_deleted(), 6);
+    assert_eq!(shrunk.output_offset(0), 0);
+    assert_eq!(shrunk.output_offset(4), 4);
+    assert_eq!(shrunk.output_offset(6), 4);
+    assert_eq!(shrunk.output_offset(8), 4);
+    assert_eq!(shrunk.output_offset(16), 12);
+    assert_eq!(shrunk.output_offset(18), 12);
+    assert_eq!(shrunk.output_offset(24), 18);
+    assert!(!shrunk.is_deleted(3));
+    assert!(shrunk.is_deleted(4));
+    assert!(shrunk.is_deleted(7));
+    assert!(!shrunk.is_deleted(8));
+    assert!(!shrunk.is_deleted(24));
+
+    let input: Vec<u8> = (0..26).collect();
+    let mut out = vec![0; 20];
+    shrunk.copy_without_deleted(&input, &mut out);
+    assert_eq!(out[..6], [0, 1, 2, 3, 8, 9]);
+    assert_eq!(out[12..], [18, 19, 20, 21, 22, 23, 24, 25]);
+}
@@ -0,0 +1,293 @@
+//! Code for describing locations within input files in error messages. e.g. where an undefined
+//! symbol was referenced or where a duplicate symbol was defined. We name the input file, section
+//! and offset and, where we can find them, the function containing the location and the source file
+//! and line from `.debug_line`.
+
+use crate::arch::Architecture;
+use crate::elf::File;
+use crate::elf::write_relocation_to_buffer;
+use crate::elf_writer::combine_with_existing_value;
+use crate::input_data::FileId;
+use crate::parsing::ParsedInput;
+use crate::symbol_db::SymbolDb;
+use crate::symbol_db::SymbolId;
+use linker_utils::elf::RelocationKind;
+use linker_utils::elf::RelocationKindInfo;
+use object::Endian as _;
+use object::SectionIndex;
+use object::read::elf::Rela as _;
+use object::read::elf::Sym as _;
+use std::fmt::Write as _;
+
+/// The number of references that we list for each undefined symbol.
+pub(crate) const MAX_REFERENCES_PER_SYMBOL: usize = 3;
+
+/// When we apply relocations to `.debug_line`, we store the index of the referenced section in the
+/// upper bits of each address. This lets us tell which section each line-number sequence is for,
+/// since in a relocatable object, all sections start at address zero.
+const SECTION_SHIFT: u32 = 40;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct InputLocation {
+    pub(crate) file_id: FileId,
+    pub(crate) section_index: SectionIndex,
+    pub(crate) offset: u64,
+}
+
+impl InputLocation {
+    /// Returns the location at which `symbol_id` is defined, if it's defined in a section of an
+    /// object file.
+    pub(crate) fn for_symbol(symbol_db: &SymbolDb, symbol_id: SymbolId) -> Option<InputLocation> {
+        let file_id = symbol_db.file_id_for_symbol(symbol_id);
+        let ParsedInput::Object(obj) = symbol_db.file(file_id) else {
+            return None;
+        };
+        let symbol_index = symbol_id.to_input(obj.symbol_id_range);
+        let symbol = obj.object.symbol(symbol_index).ok()?;
+        let section_index = obj.object.symbol_section(&symbol, symbol_index).ok()??;
+        Some(InputLocation {
+            file_id,
+            section_index,
+            offset: symbol.st_value(obj.object.endian),
+        })
+    }
+
+    /// Returns a description of this location, e.g. `foo.c:12 (foo.o:(.text.main+0x1a) in function
+    /// main)`. Only called when reporting errors, so efficiency isn't a concern.
+    pub(crate) fn describe(&self, symbol_db: &SymbolDb) -> String {
+        let ParsedInput::Object(obj) = symbol_db.file(self.file_id) else {
+            return symbol_db.file(self.file_id).to_string();
+        };
+        let mut out = format!(
+            "{}:({}+0x{:x})",
+            obj.input,
+            obj.object.section_display_name(self.section_index),
+            self.offset
+        );
+        if let Some(symbol_index) = containing_function(&obj.object, self) {
+            let symbol_id = obj.symbol_id_range.input_to_id(symbol_index);
+            let _ = write!(
+                out,
+                " in function {}",
+                symbol_db.symbol_name_for_display(symbol_id)
+            );
+        }
+        match find_source_line(&obj.object, self.section_index, self.offset) {
+            Some((file, line)) => format!("{file}:{line} ({out})"),
+            None => out,
+        }
+    }
+}
+
+/// Describes the references in `references`, which should all be to the same symbol, one per line.
+/// We list at most `MAX_REFERENCES_PER_SYMBOL`.
+pub(crate) fn describe_references(symbol_db: &SymbolDb, references: &[InputLocation]) -> String {
+    let mut out = String::new();
+    for reference in references.iter().take(MAX_REFERENCES_PER_SYMBOL) {
+        let _ = write!(out, "\n>>> referenced by {}", reference.describe(symbol_db));
+    }
+    if let Some(remaining) = references.len().checked_sub(MAX_REFERENCES_PER_SYMBOL) {
+        if remaining > 0 {
+            let _ = write!(out, "\n>>> referenced {remaining} more times");
+        }
+    }
+    out
+}
+
+/// Returns the index of the function symbol that contains `location`, if any.
+fn containing_function(object: &File, location: &InputLocation) -> Option<object::SymbolIndex> {
+    let e = object.endian;
+    object
+        .symbols()
+        .find(|(_, symbol)| {
+            let start = symbol.st_value(e);
+            symbol.st_type() == object::elf::STT_FUNC
+                && usize::from(symbol.st_shndx(e)) == location.section_index.0
+                && (start..start + symbol.st_size(e)).contains(&location.offset)
+        })
+        .map(|(index, _)| index)
+}
+
+/// Looks up the source file and line for the specified offset within a section using the
+/// `.debug_line` section of `object`, if it has one.
+fn find_source_line(
+    object: &File,
+    section_index: SectionIndex,
+    offset: u64,
+) -> Option<(String, u64)> {
+    let debug_line = relocated_debug_line(object)?;
+    let debug_str = section_data(object, ".debug_str");
+    let debug_line_str = section_data(object, ".debug_line_str");
+    let endian = if object.endian.is_big_endian() {
+        gimli::RunTimeEndian::Big
+    } else {
+        gimli::RunTimeEndian::Little
+    };
+    let strings = Strings {
+        debug_str: gimli::DebugStr::new(&debug_str, endian),
+        debug_line_str: gimli::DebugLineStr::new(&debug_line_str, endian),
+    };
+    let debug_line_section = gimli::DebugLine::new(&debug_line, endian);
+    let target = ((section_index.0 as u64 + 1) << SECTION_SHIFT) + offset;
+
+    // Without parsing `.debug_info`, we don't know where each line program starts, but since
+    // they're stored one after the other, we can just walk through them.
+    let mut program_offset = 0;
+    while program_offset < debug_line.len() {
+        let program = debug_line_section
+            .program(gimli::DebugLineOffset(program_offset), 8, None, None)
+            .ok()?;
+        let header = program.header();
+        program_offset += header.unit_length() + header.format().initial_length_size() as usize;
+
+        let mut rows = program.rows();
+        let mut previous: Option<gimli::LineRow> = None;
+        while let Ok(Some((header, row))) = rows.next_row() {
+            if let Some(previous) = previous.filter(|p| {
+                (p.address()..row.address()).contains(&target)
+                    && p.address() >> SECTION_SHIFT == target >> SECTION_SHIFT
+            }) {
+                let file = previous.file(header)?;
+                let line = previous.line()?.get();
+                return Some((strings.file_path(file, header)?, line));
+            }
+            previous = (!row.end_sequence()).then_some(*row);
+        }
+    }
+    None
+}
+
+type Slice<'data> = gimli::EndianSlice<'data, gimli::RunTimeEndian>;
+
+struct Strings<'data> {
+    debug_str: gimli::DebugStr<Slice<'data>>,
+    debug_line_str: gimli::DebugLineStr<Slice<'data>>,
+}
+
+impl<'data> Strings<'data> {
+    fn file_path(
+        &self,
+        file: &gimli::FileEntry<Slice<'data>>,
+        header: &gimli::LineProgramHeader<Slice<'data>>,
+    ) -> Option<String> {
+        let path = self.get(file.path_name())?;
+        // Directory zero is the compilation directory, which would just make the path longer.
+        if file.directory_index() == 0 || path.starts_with('/') {
+            return Some(path);
+        }
+        match file.directory(header).and_then(|dir| self.get(dir)) {
+            Some(dir) => Some(format!("{dir}/{path}")),
+            None => Some(path),
+        }
+    }
+
+    fn get(&self, value: gimli::AttributeValue<Slice<'data>>) -> Option<String> {
+        let bytes = match value {
+            gimli::AttributeValue::String(s) => s,
+            gimli::AttributeValue::DebugStrRef(offset) => self.debug_str.get_str(offset).ok()?,
+            gimli::AttributeValue::DebugLineStrRef(offset) => {
+                self.debug_line_str.get_str(offset).ok()?
+            }
+            _ => return None,
+        };
+        Some(bytes.to_string_lossy().into_owned())
+    }
+}
+
+/// Returns the (decompressed) data for the section with the specified name, or an empty vec if
+/// there's no such section.
+fn section_data(object: &File, name: &str) -> Vec<u8> {
+    let Some((_, section)) = object.section_by_name(name) else {
+        return Vec::new();
+    };
+    let Ok(size) = object.section_size(&section) else {
+        return Vec::new();
+    };
+    let mut data = vec![0; size as usize];
+    if object.copy_section_data(&section, &mut data).is_err() {
+        return Vec::new();
+    }
+    data
+}
+
+/// Returns the contents of `.debug_line` with relocations applied. Addresses are encoded as
+/// described for `SECTION_SHIFT`.
+fn relocated_debug_line(object: &File) -> Option<Vec<u8>> {
+    let e = object.endian;
+    let (debug_line_index, _) = object.section_by_name(".debug_line")?;
+    let mut data = section_data(object, ".debug_line");
+
+    // 32 bit debug info only has room for 32 bit addresses, which is too small for our encoding of
+    // section indexes, so we only relocate string offsets there.
+    let (abs64, abs32) = match object.arch {
+        Architecture::X86_64 => (Some(object::elf::R_X86_64_64), object::elf::R_X86_64_32),
+        Architecture::AArch64 => (
+            Some(object::elf::R_AARCH64_ABS64),
+            object::elf::R_AARCH64_ABS32,
+        ),
+        Architecture::RiscV64 => (Some(object::elf::R_RISCV_64), object::elf::R_RISCV_32),
+        Architecture::LoongArch64 => (Some(object::elf::R_LARCH_64), object::elf::R_LARCH_32),
+        Architecture::I386 => (None, object::elf::R_386_32),
+    };
+
+    let relocations = object.parse_relocations().ok()?;
+    for rel in object
+        .relocations(debug_line_index, &relocations)
+        .ok()?
+        .iter()
+    {
+        let offset = rel.r_offset(e) as usize;
+        let Some(symbol_index) = rel.symbol(e, false) else {
+            continue;
+        };
+        let Ok(symbol) = object.symbol(symbol_index) else {
+            continue;
+        };
+        let value = symbol.st_value(e).wrapping_add(rel.r_addend(e) as u64);
+        let r_type = rel.r_type(e, false);
+        if Some(r_type) == abs64 {
+            let Ok(Some(section_index)) = object.symbol_section(&symbol, symbol_index) else {
+                continue;
+            };
+            let encoded = ((section_index.0 as u64 + 1) << SECTION_SHIFT) + value;
+            if let Some(out) = data.get_mut(offset..offset + 8) {
+                crate::elf::write_uint(e, encoded, out);
+            }
+        } else if r_type == abs32 {
+            // These are offsets into string sections.
+            if let Some(out) = data.get_mut(offset..offset + 4) {
+                crate::elf::write_uint(e, value, out);
+            }
+        } else if let Some(rel_info) = label_difference_relocation(object.arch, r_type) {
+            // Both labels of each pair are in the same section, so using their offsets within that
+            // section gives the right difference.
+            let Some(out) = data.get_mut(offset..) else {
+                continue;
+            };
+            if let Ok(value) = combine_with_existing_value(rel_info, value, out, e) {
+                let _ = write_relocation_to_buffer(rel_info, value, out, e);
+            }
+        }
+    }
+
+    Some(data)
+}
+
+/// Returns information about `r_type` if it's one of the relocations that RISC-V and LoongArch use
+/// to compute the difference between two labels, such as the address advances in `.debug_line`.
+/// These can't be computed by the assembler, since linker relaxation can change the distance
+/// between the labels.
+fn label_difference_relocation(arch: Architecture, r_type: u32) -> Option<RelocationKindInfo> {
+    let rel_info = match arch {
+        Architecture::RiscV64 => linker_utils::riscv64::relocation_type_from_raw(r_type),
+        Architecture::LoongArch64 => linker_utils::loongarch64::relocation_type_from_raw(r_type),
+        Architecture::X86_64 | Architecture::AArch64 | Architecture::I386 => None,
+    }?;
+    matches!(
+        rel_info.kind,
+        RelocationKind::AbsoluteSet
+            | RelocationKind::AbsoluteAddition
+            | RelocationKind::AbsoluteSubtraction
+    )
+    .then_some(rel_info)
+}
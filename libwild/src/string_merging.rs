@@ -40,7 +40,6 @@ use anyhow::bail;
 use crossbeam_queue::ArrayQueue;
 use crossbeam_utils::atomic::AtomicCell;
 use itertools::Itertools as _;
-use object::LittleEndian;
 use object::read::elf::Sym as _;
 use sharded_offset_map::OffsetMap;
 use std::mem::take;
@@ -780,20 +779,20 @@ pub(crate) fn get_merged_string_output_address(
     zero_unnamed: bool,
 ) -> Result<Option<u64>> {
     let symbol = object.symbol(symbol_index)?;
-    let Some(section_index) = object.symbol_section(symbol, symbol_index)? else {
+    let Some(section_index) = object.symbol_section(&symbol, symbol_index)? else {
         return Ok(None);
     };
     let SectionSlot::MergeStrings(merge_slot) = &sections[section_index.0] else {
         return Ok(None);
     };
-    let mut input_offset = symbol.st_value(LittleEndian);
+    let mut input_offset = symbol.st_value(object.endian);
 
     // When we reference data in a string-merge section via a named symbol, we determine which
     // string we're referencing without taking the addend into account, then apply the addend
     // afterward. However when the reference is to a section (a symbol without a name), we take the
     // addend into account up-front before we determine which string we're pointing at. This is a
     // bit weird, but seems to match what other linkers do.
-    let symbol_has_name = symbol.st_name(LittleEndian) != 0;
+    let symbol_has_name = symbol.st_name(object.endian) != 0;
     if !symbol_has_name {
         // We're computing a resolution for an unnamed symbol, just use the value of 0 for now.
         // We'll compute the address later when we're processing relocations that reference the
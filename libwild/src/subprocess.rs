@@ -25,7 +25,7 @@ pub unsafe fn run_in_subprocess(args: &Args) -> ! {
     let exit_code = match subprocess_result(args) {
         Ok(code) => code,
         Err(error) => {
-            eprintln!("Error: {error:?}");
+            crate::diagnostics::print_error(&error);
             -1
         }
     };
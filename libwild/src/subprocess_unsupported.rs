@@ -4,7 +4,7 @@ pub unsafe fn run_in_subprocess(args: &crate::Args) -> ! {
     let exit_code = match crate::run(args) {
         Ok(()) => 0,
         Err(error) => {
-            eprintln!("{error}");
+            crate::diagnostics::print_error(&error);
             -1
         }
     };
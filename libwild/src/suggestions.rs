@@ -0,0 +1,215 @@
+//! Suggestions for symbols that might have been intended when a symbol is undefined. Undefined
+//! symbols are often the result of a mismatch in mangling. e.g. a missing `extern "C"`, a wrong
+//! namespace, a difference in const qualification or a mismatched Rust crate hash. We compare
+//! undefined symbols with defined symbols both in their raw and demangled forms.
+
+use crate::symbol_db::SymbolDb;
+use crate::symbol_db::SymbolId;
+
+/// The maximum edit distance between raw symbol names for us to consider them to be similar.
+const MAX_EDIT_DISTANCE: usize = 2;
+
+/// How good a match a candidate is. Lower values are better.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum MatchKind {
+    /// The demangled names are identical, e.g. Rust symbols that differ only by crate hash.
+    Demangled,
+
+    /// The demangled names are the same except for parameters. e.g. a missing `extern "C"` or
+    /// differences in const qualification.
+    Parameters,
+
+    /// The raw names differ by only a few characters.
+    Typo,
+
+    /// The demangled names have the same unqualified name, but differ in namespace.
+    Namespace,
+}
+
+/// Information about a symbol name that we'd like to find a match for.
+struct Target<'data> {
+    raw: &'data [u8],
+    demangled: String,
+    without_params: String,
+    unqualified: String,
+    best: Option<(MatchKind, &'data [u8], SymbolId)>,
+}
+
+/// Returns a suggestion for each of `undefined`, which may span multiple lines, or None if there
+/// wasn't any defined symbol that looked similar.
+pub(crate) fn suggest_alternatives(
+    symbol_db: &SymbolDb,
+    undefined: &[SymbolId],
+) -> Vec<Option<String>> {
+    let mut targets: Vec<Option<Target>> = undefined
+        .iter()
+        .map(|&symbol_id| {
+            let raw = symbol_db.symbol_name(symbol_id).ok()?.bytes();
+            let demangled = demangle(raw)?;
+            let without_params = strip_parameters(&demangled).to_owned();
+            let unqualified = unqualified(&without_params).to_owned();
+            Some(Target {
+                raw,
+                demangled,
+                without_params,
+                unqualified,
+                best: None,
+            })
+        })
+        .collect();
+
+    for (name, &symbol_id) in symbol_db.all_unversioned_symbols() {
+        let candidate = name.bytes();
+        // Demangling is expensive, so we only do it for each candidate once and only if its raw
+        // name contains the unqualified name of at least one of our targets.
+        let mut demangled: Option<Option<String>> = None;
+
+        for target in targets.iter_mut().flatten() {
+            if candidate == target.raw {
+                continue;
+            }
+            let kind = if memchr::memmem::find(candidate, target.unqualified.as_bytes()).is_some() {
+                let Some(candidate_demangled) =
+                    demangled.get_or_insert_with(|| demangle(candidate))
+                else {
+                    continue;
+                };
+                let candidate_without_params = strip_parameters(candidate_demangled);
+                if *candidate_demangled == target.demangled {
+                    Some(MatchKind::Demangled)
+                } else if candidate_without_params == target.without_params {
+                    Some(MatchKind::Parameters)
+                } else if is_typo(candidate, target.raw) {
+                    Some(MatchKind::Typo)
+                } else if unqualified(candidate_without_params) == target.unqualified {
+                    Some(MatchKind::Namespace)
+                } else {
+                    None
+                }
+            } else {
+                is_typo(candidate, target.raw).then_some(MatchKind::Typo)
+            };
+
+            let Some(kind) = kind else {
+                continue;
+            };
+            // Break ties by name so that our output is deterministic.
+            if target
+                .best
+                .is_none_or(|(best_kind, best_name, _)| (kind, candidate) < (best_kind, best_name))
+            {
+                target.best = Some((kind, candidate, symbol_id));
+            }
+        }
+    }
+
+    targets
+        .into_iter()
+        .map(|target| {
+            let target = target?;
+            let (_, candidate, symbol_id) = target.best?;
+            let display = symbol_db.symbol_name_for_display(symbol_id);
+            let suggestion = match (is_mangled(target.raw), is_mangled(candidate)) {
+                (true, false) => format!(">>> did you mean: extern \"C\" {display}"),
+                (false, true) => {
+                    format!(">>> did you mean to declare {display} as extern \"C\"?")
+                }
+                _ => format!(">>> did you mean: {display}"),
+            };
+            let defined_in = symbol_db.file(symbol_db.file_id_for_symbol(symbol_id));
+            Some(format!("{suggestion}\n>>> defined in: {defined_in}"))
+        })
+        .collect()
+}
+
+fn demangle(name: &[u8]) -> Option<String> {
+    let name = std::str::from_utf8(name).ok()?;
+    Some(symbolic_demangle::demangle(name).into_owned())
+}
+
+/// Returns whether `name` looks like an Itanium C++ or a Rust symbol. Legacy Rust mangling uses the
+/// same `_Z` prefix as C++, while the v0 scheme uses `_R` followed by an optional decimal version
+/// and an uppercase tag.
+fn is_mangled(name: &[u8]) -> bool {
+    if name.starts_with(b"_Z") {
+        return true;
+    }
+    name.strip_prefix(b"_R")
+        .and_then(|rest| rest.first())
+        .is_some_and(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+}
+
+/// Returns `name` with any parameter list and trailing qualifiers removed. e.g. `ns::foo(int)
+/// const` becomes `ns::foo`.
+fn strip_parameters(name: &str) -> &str {
+    let mut depth = 0_i32;
+    for (i, c) in name.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth -= 1,
+            '(' if depth == 0 && i > 0 => return &name[..i],
+            _ => {}
+        }
+    }
+    name
+}
+
+/// Returns the last path component of `name`. e.g. `ns::foo` becomes `foo`.
+fn unqualified(name: &str) -> &str {
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+fn is_typo(a: &[u8], b: &[u8]) -> bool {
+    // Very short names are too likely to be similar to something by chance.
+    a.len().min(b.len()) > MAX_EDIT_DISTANCE * 2
+        && a.len().abs_diff(b.len()) <= MAX_EDIT_DISTANCE
+        && edit_distance(a, b) <= MAX_EDIT_DISTANCE
+}
+
+/// Returns the Levenshtein distance between `a` and `b`.
+fn edit_distance(a: &[u8], b: &[u8]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, &ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_parameters() {
+        assert_eq!(strip_parameters("ns::foo(int) const"), "ns::foo");
+        assert_eq!(strip_parameters("foo<bar(int)>(int)"), "foo<bar(int)>");
+        assert_eq!(strip_parameters("foo"), "foo");
+        assert_eq!(unqualified("a::b::foo"), "foo");
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance(b"foobar", b"foobar"), 0);
+        assert_eq!(edit_distance(b"foobar", b"fobar"), 1);
+        assert_eq!(edit_distance(b"foobar", b"foobaz"), 1);
+        assert_eq!(edit_distance(b"foobar", b"barfoo"), 6);
+        assert!(is_typo(b"my_function", b"my_fucntion"));
+        assert!(!is_typo(b"ab", b"cd"));
+    }
+
+    #[test]
+    fn test_is_mangled() {
+        assert!(is_mangled(b"_Z3fooi"));
+        assert!(is_mangled(b"_ZN7mycrate8function17h0123456789abcdefE"));
+        assert!(is_mangled(b"_RNvCs1234_7mycrate8function"));
+        assert!(!is_mangled(b"function"));
+        assert!(!is_mangled(b"_Rfoo"));
+        assert!(!is_mangled(b"_R"));
+    }
+}
@@ -1,5 +1,5 @@
 use crate::hash::PreHashed;
-use object::LittleEndian;
+use object::Endianness;
 use object::read::elf::Sym as _;
 use std::fmt::Display;
 use std::ops::BitXor as _;
@@ -71,7 +71,10 @@ impl Display for UnversionedSymbolName<'_> {
     }
 }
 
-pub(crate) struct SymDebug<'data>(pub(crate) &'data crate::elf::SymtabEntry);
+pub(crate) struct SymDebug<'data>(
+    pub(crate) &'data crate::elf::SymtabEntry,
+    pub(crate) Endianness,
+);
 
 impl<'data> PreHashedSymbolName<'data> {
     pub(crate) fn from_raw(
@@ -88,7 +91,7 @@ impl<'data> PreHashedSymbolName<'data> {
 
 impl Display for SymDebug<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let e = LittleEndian;
+        let e = self.1;
         let sym = self.0;
 
         let vis = if sym.is_local() {
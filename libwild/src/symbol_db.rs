@@ -9,6 +9,7 @@ use crate::hash::PassThroughHashMap;
 use crate::hash::PreHashed;
 use crate::input_data::FileId;
 use crate::input_data::PRELUDE_FILE_ID;
+use crate::input_data::RetainSymbolsData;
 use crate::input_data::UNINITIALISED_FILE_ID;
 use crate::input_data::VersionScriptData;
 use crate::linker_script::VersionScript;
@@ -21,16 +22,19 @@ use crate::resolution::ResolvedFile;
 use crate::resolution::ResolvedGroup;
 use crate::resolution::ValueFlags;
 use crate::sharding::ShardKey;
+use crate::source_location::InputLocation;
 use crate::symbol::PreHashedSymbolName;
 use crate::symbol::UnversionedSymbolName;
 use crate::symbol::VersionedSymbolName;
 use ahash::HashMap;
+use ahash::HashSet;
 use ahash::RandomState;
 use anyhow::Context;
 use anyhow::bail;
 use crossbeam_queue::SegQueue;
 use itertools::Itertools;
-use object::LittleEndian;
+use linker_utils::elf::SectionFlags;
+use linker_utils::elf::shf;
 use object::read::elf::Sym as _;
 use rayon::iter::IndexedParallelIterator;
 use rayon::iter::IntoParallelRefIterator;
@@ -70,6 +74,9 @@ pub struct SymbolDb<'data> {
     start_stop_symbol_names: Vec<UnversionedSymbolName<'data>>,
 
     pub(crate) version_script: VersionScript<'data>,
+
+    /// If --retain-symbols-file was given, the names of the symbols that it listed.
+    retained_symbols: Option<HashSet<&'data [u8]>>,
 }
 
 struct SymbolBucket<'data> {
@@ -253,6 +260,7 @@ impl<'data> SymbolDb<'data> {
     pub fn build(
         groups: Vec<Group<'data>>,
         version_script_data: Option<&'data VersionScriptData>,
+        retain_symbols_data: Option<&'data RetainSymbolsData>,
         args: &'data Args,
     ) -> Result<Self> {
         let version_script = version_script_data
@@ -260,6 +268,17 @@ impl<'data> SymbolDb<'data> {
             .transpose()?
             .unwrap_or_default();
 
+        let retained_symbols = retain_symbols_data.map(|data| {
+            let mut names = HashSet::with_hasher(RandomState::new());
+            names.extend(
+                data.raw
+                    .lines()
+                    .map(|line| line.trim().as_bytes())
+                    .filter(|name| !name.is_empty()),
+            );
+            names
+        });
+
         let num_symbols_per_group = groups.iter().map(|g| g.num_symbols()).collect_vec();
 
         let num_symbols = num_symbols_per_group.iter().sum();
@@ -321,6 +340,7 @@ impl<'data> SymbolDb<'data> {
             start_stop_symbol_names: Default::default(),
             symbol_value_flags,
             version_script,
+            retained_symbols,
         };
 
         index.populate_symbol_db(&per_group_outputs)?;
@@ -399,7 +419,7 @@ impl<'data> SymbolDb<'data> {
     pub(crate) fn symbol_name(&self, symbol_id: SymbolId) -> Result<UnversionedSymbolName<'data>> {
         let file_id = self.file_id_for_symbol(symbol_id);
         match &self.groups[file_id.group()] {
-            Group::Prelude(prelude) => Ok(prelude.symbol_name(symbol_id, self.args.output_kind())),
+            Group::Prelude(prelude) => Ok(prelude.symbol_name(symbol_id, self.args)),
             Group::Objects(parsed_input_objects) => {
                 parsed_input_objects[file_id.file()].symbol_name(symbol_id)
             }
@@ -415,6 +435,22 @@ impl<'data> SymbolDb<'data> {
         self.symbol_value_flags[symbol_id.as_usize()]
     }
 
+    /// Returns whether `symbol_id` was downgraded to a local because `--exclude-libs` applies to
+    /// the archive member that defines it. Like GNU ld, we make such symbols local in `.symtab`,
+    /// whereas symbols downgraded by a version script remain global there.
+    pub(crate) fn is_local_due_to_exclude_libs(&self, symbol_id: SymbolId) -> bool {
+        if !self
+            .local_symbol_value_flags(symbol_id)
+            .contains(ValueFlags::DOWNGRADE_TO_LOCAL)
+        {
+            return false;
+        }
+        match self.file(self.file_id_for_symbol(symbol_id)) {
+            ParsedInput::Object(object) => is_excluded_lib(object, self.args),
+            _ => false,
+        }
+    }
+
     pub(crate) fn symbol_value_flags(&self, symbol_id: SymbolId) -> ValueFlags {
         let mut flags = self.local_symbol_value_flags(self.definition(symbol_id));
         flags.merge(self.local_symbol_value_flags(symbol_id));
@@ -454,6 +490,14 @@ impl<'data> SymbolDb<'data> {
             .unwrap_or(self.epilogue_file_id)
     }
 
+    /// Returns whether a symbol with the supplied name may be written to the symbol table. This is
+    /// only false if --retain-symbols-file was used and didn't list the symbol.
+    pub(crate) fn is_retained_in_symtab(&self, name: &[u8]) -> bool {
+        self.retained_symbols
+            .as_ref()
+            .is_none_or(|retained| retained.contains(name))
+    }
+
     /// Returns whether the supplied symbol ID is the canonical ID. A symbol won't be canonical, if
     /// it resolves to a different symbol. The symbol may still be undefined.
     pub(crate) fn is_canonical(&self, symbol_id: SymbolId) -> bool {
@@ -485,6 +529,23 @@ impl<'data> SymbolDb<'data> {
         }
     }
 
+    /// Returns whether the specified file is a shared object.
+    pub(crate) fn is_shared_object(&self, file_id: FileId) -> bool {
+        match self.file(file_id) {
+            ParsedInput::Object(obj) => obj.is_dynamic(),
+            _ => false,
+        }
+    }
+
+    /// Returns whether the specified file is a shared object that we only loaded because it was
+    /// needed by another shared object.
+    pub(crate) fn is_indirect_dependency(&self, file_id: FileId) -> bool {
+        match self.file(file_id) {
+            ParsedInput::Object(obj) => obj.input.file.is_indirect_dependency,
+            _ => false,
+        }
+    }
+
     pub(crate) fn is_mapping_symbol(&self, symbol_id: SymbolId) -> bool {
         let Ok(name) = self.symbol_name(symbol_id) else {
             // We don't want to bother the caller with an error here. If there's a problem getting
@@ -529,7 +590,11 @@ impl<'data> SymbolDb<'data> {
     }
 
     #[inline(always)]
-    fn symbol_strength(&self, symbol_id: SymbolId, resolved: &[ResolvedGroup]) -> SymbolStrength {
+    fn symbol_strength(
+        &self,
+        symbol_id: SymbolId,
+        resolved: &[ResolvedGroup<'data>],
+    ) -> SymbolStrength<'data> {
         let file_id = self.file_id_for_symbol(symbol_id);
         if let ResolvedFile::Object(obj) = &resolved[file_id.group()].files[file_id.file()] {
             let local_index = symbol_id.to_input(obj.symbol_id_range);
@@ -537,13 +602,26 @@ impl<'data> SymbolDb<'data> {
                 // Errors from this function should have been reported elsewhere.
                 return SymbolStrength::Undefined;
             };
-            let e = LittleEndian;
+            let e = obj.object.endian;
             if obj_symbol.is_weak() {
                 SymbolStrength::Weak
             } else if obj_symbol.is_common(e) {
                 SymbolStrength::Common(obj_symbol.st_size(e))
             } else if obj_symbol.st_bind() == object::elf::STB_GNU_UNIQUE {
                 SymbolStrength::GnuUnique
+            } else if let Some(signature) = obj
+                .object
+                .symbol_section(&obj_symbol, local_index)
+                .ok()
+                .flatten()
+                .filter(|&index| {
+                    obj.object.section(index).is_ok_and(|section| {
+                        SectionFlags::from_header(&section, e).contains(shf::GROUP)
+                    })
+                })
+                .and_then(|index| obj.object.comdat_group_signature(index).ok().flatten())
+            {
+                SymbolStrength::Comdat(signature)
             } else {
                 SymbolStrength::Strong
             }
@@ -644,17 +722,11 @@ pub(crate) fn resolve_alternative_symbol_definitions<'data>(
         })
         .collect();
 
-    let mut duplicate_errors: Vec<anyhow::Error> = error_queue.into_iter().collect();
-    duplicate_errors.sort_by_key(|e| e.to_string());
+    let duplicate_errors: Vec<anyhow::Error> = error_queue.into_iter().collect();
 
-    if !duplicate_errors.is_empty() {
-        let error_details = duplicate_errors
-            .iter()
-            .map(|e| e.to_string())
-            .collect::<Vec<_>>()
-            .join("\n");
+    if let Some(error) = crate::diagnostics::combine_errors(duplicate_errors, symbol_db.args) {
         return Err(anyhow::Error::msg(format!(
-            "Duplicate symbols detected: {error_details}"
+            "Duplicate symbols detected: {error:#}"
         )));
     }
 
@@ -690,7 +762,8 @@ fn select_symbol(
 
         let strength = symbol_db.symbol_strength(alt, resolved);
         match strength {
-            SymbolStrength::Strong => strong_symbols.push(alt),
+            SymbolStrength::Strong => strong_symbols.push((alt, None)),
+            SymbolStrength::Comdat(signature) => strong_symbols.push((alt, Some(signature))),
             SymbolStrength::Common(size) => {
                 if let Some((previous_size, _)) = max_common {
                     if size <= previous_size {
@@ -703,16 +776,38 @@ fn select_symbol(
         }
     }
 
-    if strong_symbols.len() > 1 {
-        let already_defined_in = symbol_db.file_id_for_symbol(*strong_symbols.first().unwrap());
+    // Definitions from copies of the same COMDAT group are interchangeable, so we use the first.
+    // Any other combination of strong definitions is an error.
+    let all_in_same_comdat_group = matches!(
+        strong_symbols
+            .iter()
+            .map(|&(_, group)| group)
+            .all_equal_value(),
+        Ok(Some(_))
+    );
+
+    if strong_symbols.len() > 1 && !all_in_same_comdat_group {
+        let already_defined_in = symbol_db.file_id_for_symbol(strong_symbols[0].0);
+
+        let mut locations = String::new();
+        for &(s, _) in &strong_symbols {
+            if let Some(location) = InputLocation::for_symbol(symbol_db, s) {
+                write!(
+                    locations,
+                    "\n>>> defined at {}",
+                    location.describe(symbol_db)
+                )
+                .unwrap();
+            }
+        }
 
         return Err(anyhow::Error::msg(format!(
-            "{}{}, previously defined in {}",
+            "{}{}, previously defined in {}{locations}",
             symbol_db.symbol_name_for_display(symbol_id),
             strong_symbols
                 .iter()
                 .skip(1)
-                .fold(String::new(), |mut output, &s| {
+                .fold(String::new(), |mut output, &(s, _)| {
                     let fid = symbol_db.file_id_for_symbol(s);
                     write!(output, ", defined in {}", symbol_db.file(fid)).unwrap();
                     output
@@ -721,7 +816,7 @@ fn select_symbol(
         )));
     }
 
-    if let Some(&strong_symbol) = strong_symbols.first() {
+    if let Some(&(strong_symbol, _)) = strong_symbols.first() {
         return Ok(strong_symbol);
     }
 
@@ -745,7 +840,7 @@ fn select_symbol(
 }
 
 #[derive(PartialEq, Eq, Clone, Copy)]
-enum SymbolStrength {
+enum SymbolStrength<'data> {
     /// The object containing this symbol wasn't loaded, so the definition can be ignored.
     Undefined,
 
@@ -758,6 +853,11 @@ enum SymbolStrength {
     /// The object strongly defines the symbol.
     Strong,
 
+    /// The object strongly defines the symbol in a COMDAT group with the specified signature.
+    /// Copies of the group in other objects define the same thing, so if all definitions are from
+    /// groups with the same signature, the first is used. e.g. `__x86.get_pc_thunk.bx` on i386.
+    Comdat(&'data [u8]),
+
     /// The symbol is a "common" symbol with the specified size. The definition with the largest
     /// size will be selected.
     Common(u64),
@@ -788,7 +888,7 @@ fn read_symbols<'data, 'out>(
 
             match group {
                 Group::Prelude(prelude) => {
-                    prelude.load_symbols(symbols_out, &mut outputs, args.output_kind());
+                    prelude.load_symbols(symbols_out, &mut outputs, args);
                 }
                 Group::Objects(parsed_input_objects) => {
                     for obj in *parsed_input_objects {
@@ -822,17 +922,29 @@ fn load_symbols_from_file<'data>(
     if s.is_dynamic() {
         DynamicObjectSymbolLoader::new(&s.object)?.load_symbols(s.file_id, symbols_out, outputs)
     } else {
+        // Symbols defined by archive members named by --exclude-libs are treated as though a
+        // version script had made them local.
         RegularObjectSymbolLoader {
             object: &s.object,
             args,
             version_script,
+            is_excluded_lib: is_excluded_lib(s, args),
         }
         .load_symbols(s.file_id, symbols_out, outputs)
     }
 }
 
+/// Returns whether `--exclude-libs` applies to `object`, which it does if it's an archive member of
+/// one of the named archives.
+fn is_excluded_lib(object: &ParsedInputObject, args: &Args) -> bool {
+    object.input.entry.is_some()
+        && args
+            .exclude_libs
+            .should_exclude(&object.input.file.filename)
+}
+
 fn value_flags_from_elf_symbol(sym: &crate::elf::Symbol, args: &Args) -> ValueFlags {
-    let is_undefined = sym.is_undefined(LittleEndian);
+    let is_undefined = sym.is_undefined(args.endian);
     let mut can_bypass_got = sym.st_visibility() != object::elf::STV_DEFAULT
         || sym.is_local()
         || args.output_kind().is_static_executable()
@@ -844,7 +956,7 @@ fn value_flags_from_elf_symbol(sym: &crate::elf::Symbol, args: &Args) -> ValueFl
     if args.output_kind() == OutputKind::SharedObject && sym.st_type() == object::elf::STT_TLS {
         can_bypass_got = false;
     }
-    let mut flags: ValueFlags = if sym.is_absolute(LittleEndian) {
+    let mut flags: ValueFlags = if sym.is_absolute(args.endian) {
         ValueFlags::ABSOLUTE
     } else if sym.st_type() == object::elf::STT_GNU_IFUNC {
         ValueFlags::IFUNC
@@ -903,10 +1015,11 @@ trait SymbolLoader<'data> {
         symbols_out: &mut SymbolInfoWriter,
         outputs: &mut SymbolLoadOutputs<'data>,
     ) -> Result {
-        let e = LittleEndian;
+        let e = self.object().endian;
         let base_symbol_id = symbols_out.next;
 
-        for symbol in self.object().symbols.iter() {
+        for (_, symbol) in self.object().symbols() {
+            let symbol = &symbol;
             let symbol_id = symbols_out.next;
             let mut value_flags = self.compute_value_flags(symbol);
             if symbol.is_undefined(e) {
@@ -928,8 +1041,9 @@ trait SymbolLoader<'data> {
 
             if self.should_downgrade_to_local(&name) {
                 value_flags |= ValueFlags::DOWNGRADE_TO_LOCAL;
-                // If we're downgrading to a local, then we're writing a shared object. Shared
-                // objects should never bypass the GOT for TLS variables.
+                // Downgraded symbols can't be interposed, so references to them can bypass the
+                // GOT. The exception is TLS variables, since in a shared object, their offset from
+                // the thread pointer isn't known until runtime.
                 if symbol.st_type() != object::elf::STT_TLS {
                     value_flags |= ValueFlags::CAN_BYPASS_GOT;
                 }
@@ -980,6 +1094,9 @@ struct RegularObjectSymbolLoader<'a, 'data> {
     object: &'a crate::elf::File<'data>,
     args: &'a Args,
     version_script: &'a VersionScript<'a>,
+
+    /// Whether the object came from an archive that was named by --exclude-libs.
+    is_excluded_lib: bool,
 }
 
 struct DynamicObjectSymbolLoader<'a, 'data> {
@@ -989,7 +1106,7 @@ struct DynamicObjectSymbolLoader<'a, 'data> {
 
 impl<'a, 'data> DynamicObjectSymbolLoader<'a, 'data> {
     fn new(object: &'a crate::elf::File<'data>) -> Result<Self> {
-        let endian = LittleEndian;
+        let endian = object.endian;
 
         let mut version_names = vec![None; object.verdefnum as usize + 1];
 
@@ -997,9 +1114,7 @@ impl<'a, 'data> DynamicObjectSymbolLoader<'a, 'data> {
         // for information about symbol versioning.
 
         if let Some((verdefs, string_table_index)) = &object.verdef {
-            let strings = object
-                .sections
-                .strings(endian, object.data, *string_table_index)?;
+            let strings = object.strings(*string_table_index)?;
 
             for r in verdefs.clone() {
                 let (verdef, mut aux_iterator) = r?;
@@ -1029,7 +1144,7 @@ impl<'data> SymbolLoader<'data> for RegularObjectSymbolLoader<'_, 'data> {
     }
 
     fn should_downgrade_to_local(&self, name: &PreHashed<UnversionedSymbolName>) -> bool {
-        self.version_script.is_local(name)
+        self.is_excluded_lib || self.version_script.is_local(name)
     }
 
     fn get_symbol_name_and_version(
@@ -1078,7 +1193,7 @@ impl<'data> SymbolLoader<'data> for DynamicObjectSymbolLoader<'_, 'data> {
         if st_type == object::elf::STT_FUNC || st_type == object::elf::STT_GNU_IFUNC {
             flags |= ValueFlags::FUNCTION;
         }
-        if symbol.is_undefined(LittleEndian) {
+        if symbol.is_undefined(self.object.endian) {
             flags |= ValueFlags::ABSOLUTE;
         }
         flags
@@ -1095,7 +1210,7 @@ impl<'data> SymbolLoader<'data> for DynamicObjectSymbolLoader<'_, 'data> {
         let version_name;
 
         if let Some(versym) = self.object.versym.get(local_index) {
-            let versym = versym.0.get(LittleEndian);
+            let versym = versym.0.get(self.object.endian);
             is_default = versym & object::elf::VERSYM_HIDDEN == 0;
             let version_index = versym & object::elf::VERSYM_VERSION;
             version_name = self
@@ -1150,7 +1265,10 @@ impl std::fmt::Display for SymbolDebug<'_, '_> {
                         .symbol(symbol_index)
                         .ok()
                         .and_then(|symbol| {
-                            o.object.symbol_section(symbol, symbol_index).ok().flatten()
+                            o.object
+                                .symbol_section(&symbol, symbol_index)
+                                .ok()
+                                .flatten()
                         })
                         .map(|section_index| o.object.section_display_name(section_index))
                     {
@@ -1228,7 +1346,7 @@ impl Prelude<'_> {
         &self,
         symbols_out: &mut SymbolInfoWriter,
         outputs: &mut SymbolLoadOutputs,
-        output_kind: OutputKind,
+        args: &Args,
     ) {
         for definition in &self.symbol_definitions {
             let symbol_id = symbols_out.next;
@@ -1238,13 +1356,13 @@ impl Prelude<'_> {
                 }
                 InternalSymDefInfo::SectionStart(section_id) => {
                     let def = section_id.built_in_details();
-                    let name = def.start_symbol_name(output_kind).unwrap().as_bytes();
+                    let name = def.start_symbol_name(args).unwrap().as_bytes();
                     outputs.add_non_versioned(PendingSymbol::new(symbol_id, name));
                     ValueFlags::ADDRESS | ValueFlags::CAN_BYPASS_GOT
                 }
                 InternalSymDefInfo::SectionEnd(section_id) => {
                     let def = section_id.built_in_details();
-                    let name = def.end_symbol_name(output_kind).unwrap().as_bytes();
+                    let name = def.end_symbol_name(args).unwrap().as_bytes();
                     outputs.add_non_versioned(PendingSymbol::new(symbol_id, name));
                     ValueFlags::ADDRESS | ValueFlags::CAN_BYPASS_GOT
                 }
@@ -7,7 +7,7 @@ use crate::resolution::ValueFlags;
 use anyhow::Context;
 use anyhow::bail;
 use linker_utils::elf::secnames::GOT_SECTION_NAME_STR;
-use object::LittleEndian;
+use object::Endianness;
 use object::read::elf::SectionHeader as _;
 
 pub(crate) fn validate_bytes(layout: &Layout, file_bytes: &[u8]) -> Result {
@@ -28,13 +28,21 @@ fn validate_object(object: &crate::elf::File, layout: &Layout) -> Result {
         return Ok(());
     };
 
-    let got_data = got.data(LittleEndian, object.data)?;
+    let got_data = got.data(object.endian, object.data)?;
+    let got_entry_size = layout.args().got_entry_size() as usize;
 
     for (symbol_name, symbol_id) in layout.symbol_db.all_unversioned_symbols() {
         match layout.local_symbol_resolution(*symbol_id) {
             None => {}
             Some(resolution) => {
-                validate_resolution(symbol_name.bytes(), resolution, got, got_data)?;
+                validate_resolution(
+                    symbol_name.bytes(),
+                    resolution,
+                    &got,
+                    got_data,
+                    got_entry_size,
+                    object.endian,
+                )?;
             }
         }
     }
@@ -43,15 +51,17 @@ fn validate_object(object: &crate::elf::File, layout: &Layout) -> Result {
             match file {
                 crate::layout::FileLayout::Prelude(_) => {}
                 crate::layout::FileLayout::Object(obj) => {
-                    for (sec_index, sec) in obj.object.sections.enumerate() {
+                    for (sec_index, sec) in obj.object.sections() {
                         if let Some(resolution) =
                             obj.section_resolutions[sec_index.0].full_resolution()
                         {
                             validate_resolution(
-                                obj.object.section_name(sec)?,
+                                obj.object.section_name(&sec)?,
                                 &resolution,
-                                got,
+                                &got,
                                 got_data,
+                                got_entry_size,
+                                object.endian,
                             )?;
                         }
                     }
@@ -70,6 +80,8 @@ fn validate_resolution(
     resolution: &crate::layout::Resolution,
     got: &crate::elf::SectionHeader,
     got_data: &[u8],
+    got_entry_size: usize,
+    e: Endianness,
 ) -> Result {
     let res_flags = resolution.resolution_flags;
     let value_flags = resolution.value_flags;
@@ -81,8 +93,8 @@ fn validate_resolution(
         return Ok(());
     };
     if let Some(got_address) = resolution.got_address {
-        let start_offset = (got_address.get() - got.sh_addr(LittleEndian)) as usize;
-        let end_offset = start_offset + size_of::<u64>();
+        let start_offset = (got_address.get() - got.sh_addr(e)) as usize;
+        let end_offset = start_offset + got_entry_size;
         if end_offset > got_data.len() {
             bail!("GOT offset beyond end of GOT 0x{end_offset}");
         }
@@ -92,7 +104,7 @@ fn validate_resolution(
             return Ok(());
         }
         let expected = resolution.raw_value;
-        let address = bytemuck::pod_read_unaligned(&got_data[start_offset..end_offset]);
+        let address = crate::elf::read_uint(e, &got_data[start_offset..end_offset]);
         if expected != address {
             let name = String::from_utf8_lossy(name);
             bail!(
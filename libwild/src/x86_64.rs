@@ -4,6 +4,7 @@
 //! static-PIE binary because dynamic relocations haven't yet been applied to the GOT yet.
 
 use crate::arch::Arch;
+use crate::arch::PltProtection;
 use crate::args::OutputKind;
 use crate::elf::PLT_ENTRY_SIZE;
 use crate::resolution::ValueFlags;
@@ -17,6 +18,11 @@ use linker_utils::elf::SectionFlags;
 use linker_utils::elf::shf;
 use linker_utils::elf::x86_64_rel_type_to_string;
 use linker_utils::relaxation::RelocationModifier;
+use linker_utils::x86_64::R_X86_64_CODE_4_GOTPC32_TLSDESC;
+use linker_utils::x86_64::R_X86_64_CODE_4_GOTPCRELX;
+use linker_utils::x86_64::R_X86_64_CODE_4_GOTTPOFF;
+use linker_utils::x86_64::R_X86_64_CODE_6_GOTPCRELX;
+use linker_utils::x86_64::R_X86_64_CODE_6_GOTTPOFF;
 use linker_utils::x86_64::RelaxationKind;
 
 pub(crate) struct X86_64;
@@ -56,6 +62,10 @@ impl crate::arch::Arch for X86_64 {
         })
     }
 
+    fn tls_dtv_offset() -> u64 {
+        0
+    }
+
     fn get_dynamic_relocation_type(relocation: DynamicRelocationKind) -> u32 {
         relocation.x86_64_r_type()
     }
@@ -64,6 +74,8 @@ impl crate::arch::Arch for X86_64 {
         plt_entry: &mut [u8],
         got_address: u64,
         plt_address: u64,
+        _got_base: Option<u64>,
+        _protection: PltProtection,
     ) -> crate::error::Result {
         plt_entry.copy_from_slice(PLT_ENTRY_TEMPLATE);
         let offset: i32 = ((got_address.wrapping_sub(plt_address + 0xb)) as i64)
@@ -251,6 +263,53 @@ impl crate::arch::Relaxation for Relaxation {
                     _ => {}
                 }
             }
+            R_X86_64_CODE_4_GOTPCRELX => {
+                let (opcode, _) = rex2_instruction(section_bytes, offset)?;
+                if is_absolute || is_absolute_address {
+                    let kind = match opcode {
+                        // mov *x(%rip), reg
+                        0x8b => RelaxationKind::Rex2MovIndirectToAbsolute,
+                        // sub *x(%rip), reg
+                        0x2b => RelaxationKind::Rex2SubIndirectToAbsolute,
+                        // cmp *x(%rip), reg
+                        0x3b => RelaxationKind::Rex2CmpIndirectToAbsolute,
+                        _ => return None,
+                    };
+                    return create(kind, object::elf::R_X86_64_32);
+                } else if can_bypass_got && opcode == 0x8b {
+                    // mov *x(%rip), reg
+                    return create(RelaxationKind::MovIndirectToLea, object::elf::R_X86_64_PC32);
+                }
+            }
+            R_X86_64_CODE_6_GOTPCRELX if is_absolute || is_absolute_address => {
+                let kind = match evex_instruction(section_bytes, offset)? {
+                    // add *x(%rip), reg1, reg2
+                    0x03 => RelaxationKind::EvexAddIndirectToAbsolute,
+                    // sub *x(%rip), reg1, reg2
+                    0x2b => RelaxationKind::EvexSubIndirectToAbsolute,
+                    _ => return None,
+                };
+                return create(kind, object::elf::R_X86_64_32);
+            }
+            R_X86_64_CODE_4_GOTTPOFF if can_bypass_got => {
+                let kind = match rex2_instruction(section_bytes, offset)? {
+                    // mov *x(%rip), reg
+                    (0x8b, _) => RelaxationKind::Rex2MovIndirectToAbsolute,
+                    // add *x(%rip), reg
+                    (0x03, _) => RelaxationKind::Rex2AddIndirectToAbsolute,
+                    _ => return None,
+                };
+                return create(kind, object::elf::R_X86_64_TPOFF32);
+            }
+            R_X86_64_CODE_6_GOTTPOFF if can_bypass_got => {
+                // add *x(%rip), reg1, reg2
+                if evex_instruction(section_bytes, offset)? == 0x03 {
+                    return create(
+                        RelaxationKind::EvexAddIndirectToAbsolute,
+                        object::elf::R_X86_64_TPOFF32,
+                    );
+                }
+            }
             object::elf::R_X86_64_PLT32 if can_bypass_got => {
                 return create(RelaxationKind::NoOp, object::elf::R_X86_64_PC32);
             }
@@ -308,6 +367,30 @@ impl crate::arch::Relaxation for Relaxation {
                     );
                 }
             }
+            R_X86_64_CODE_4_GOTPC32_TLSDESC if output_kind.is_executable() => {
+                // lea x@tlsdesc(%rip), reg
+                let (0x8d, mod_rm) = rex2_instruction(section_bytes, offset)? else {
+                    return None;
+                };
+                if mod_rm & 0xc7 != 0x05 {
+                    return None;
+                }
+                // The call gets replaced with a no-op as part of relaxing the lea, so we require
+                // that it immediately follows. call *(%rax)
+                if section_bytes.get(offset + 4..offset + 6)? != [0xff, 0x10] {
+                    return None;
+                }
+                if can_bypass_got && output_kind.is_static_executable() {
+                    return create(
+                        RelaxationKind::Rex2TlsDescToLocalExec,
+                        object::elf::R_X86_64_TPOFF32,
+                    );
+                }
+                return create(
+                    RelaxationKind::Rex2TlsDescToInitialExec,
+                    R_X86_64_CODE_4_GOTTPOFF,
+                );
+            }
             _ => return None,
         };
         None
@@ -330,6 +413,26 @@ impl crate::arch::Relaxation for Relaxation {
     }
 }
 
+/// Returns the opcode and ModRM byte of an instruction with a REX2 prefix that ends with the 32 bit
+/// PC-relative displacement at `offset`. Returns `None` if the instruction doesn't have a REX2
+/// prefix or isn't from the legacy one-byte opcode map.
+fn rex2_instruction(bytes: &[u8], offset: usize) -> Option<(u8, u8)> {
+    match bytes.get(offset.checked_sub(4)?..offset)? {
+        &[0xd5, payload, opcode, mod_rm] if payload & 0x80 == 0 => Some((opcode, mod_rm)),
+        _ => None,
+    }
+}
+
+/// Returns the opcode of an EVEX-encoded instruction that ends with the 32 bit PC-relative
+/// displacement at `offset`. Returns `None` if the instruction isn't EVEX-encoded or isn't from
+/// map 4, which is where APX puts the promoted legacy instructions.
+fn evex_instruction(bytes: &[u8], offset: usize) -> Option<u8> {
+    match bytes.get(offset.checked_sub(6)?..offset)? {
+        &[0x62, p0, _, _, opcode, _] if p0 & 0x07 == 0x04 => Some(opcode),
+        _ => None,
+    }
+}
+
 enum TlsGdForm {
     Regular,
     Large,
@@ -409,4 +512,25 @@ fn test_relaxation() {
         &[0x48, 0x8d, 0xae],
         &[0x48, 0xc7, 0xc5],
     );
+    // mov *x(%rip), %r29
+    check(
+        R_X86_64_CODE_4_GOTPCRELX,
+        &[0xd5, 0x4c, 0x8b, 0x2d],
+        &[0xd5, 0x4c, 0x8d, 0x2d],
+        &[0xd5, 0x19, 0xc7, 0xc5],
+    );
+    // sub *x(%rip), %r16
+    check(
+        R_X86_64_CODE_4_GOTPCRELX,
+        &[0xd5, 0x48, 0x2b, 0x05],
+        &[0xd5, 0x48, 0x2b, 0x05],
+        &[0xd5, 0x18, 0x81, 0xe8],
+    );
+    // add *x(%rip), %r8, %r17
+    check(
+        R_X86_64_CODE_6_GOTPCRELX,
+        &[0x62, 0x74, 0xf4, 0x10, 0x03, 0x05],
+        &[0x62, 0x74, 0xf4, 0x10, 0x03, 0x05],
+        &[0x62, 0xd4, 0xf4, 0x10, 0x81, 0xc0],
+    );
 }
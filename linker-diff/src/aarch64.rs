@@ -80,6 +80,18 @@ fn test_align_up() {
     }
 }
 
+#[test]
+fn test_decode_thunk() {
+    // A thunk at 0x2000001c that branches backwards to 0x4104d4.
+    let thunk = [
+        0x90, 0x20, 0xf0, 0x90, // adrp x16, 0x410000
+        0x10, 0x52, 0x13, 0x91, // add x16, x16, #0x4d4
+        0x00, 0x02, 0x1f, 0xd6, // br x16
+    ];
+    assert_eq!(decode_thunk(&thunk, 0x2000001c), Some(0x4104d4));
+    assert_eq!(decode_thunk(&thunk[..8], 0x2000001c), None);
+}
+
 impl Arch for AArch64 {
     type RType = RType;
 
@@ -238,6 +250,20 @@ impl Arch for AArch64 {
     ) -> Option<crate::arch::PltEntry> {
         decode_plt_entry_template_1(plt_entry, plt_base, plt_offset)
             .or_else(|| decode_plt_entry_template_2(plt_entry, plt_base, plt_offset))
+            .or_else(|| decode_protected_plt_entry(plt_entry, plt_base, plt_offset))
+    }
+
+    fn decode_thunk(r_type: Self::RType, bytes: &[u8], address: u64) -> Option<u64> {
+        if !matches!(
+            r_type.0,
+            object::elf::R_AARCH64_CALL26
+                | object::elf::R_AARCH64_JUMP26
+                | object::elf::R_AARCH64_CONDBR19
+                | object::elf::R_AARCH64_TSTBR14
+        ) {
+            return None;
+        }
+        decode_thunk(bytes.get(..THUNK_TEMPLATE.len())?, address)
     }
 
     fn should_chain_relocations(chain_prefix: &[Self::RType]) -> bool {
@@ -424,6 +450,105 @@ fn decode_plt_entry_template_2(
     Some(crate::arch::PltEntry::DerefJmp(got_address))
 }
 
+/// PLT entries used when BTI and/or PAC are enabled. Each template is paired with the offset of its
+/// `adrp` and whether it has an `add`.
+const PROTECTED_PLT_ENTRY_TEMPLATES: &[(&[u8], usize, bool)] = &[
+    (
+        &[
+            0x5f, 0x24, 0x03, 0xd5, // bti c
+            0x10, 0x00, 0x00, 0x90, // adrp x16, page(&(.got.plt[n]))
+            0x11, 0x02, 0x40, 0xf9, // ldr x17, [x16, offset(&(.got.plt[n]))]
+            0x20, 0x02, 0x1f, 0xd6, // br x17
+        ],
+        4,
+        false,
+    ),
+    (
+        &[
+            0x5f, 0x24, 0x03, 0xd5, // bti c
+            0x10, 0x00, 0x00, 0x90, // adrp x16, page(&(.got.plt[n]))
+            0x11, 0x02, 0x40, 0xf9, // ldr x17, [x16, offset(&(.got.plt[n]))]
+            0x10, 0x02, 0x00, 0x91, // add x16, x16, offset(&(.got.plt[n]))
+            0x20, 0x02, 0x1f, 0xd6, // br x17
+            0x1f, 0x20, 0x03, 0xd5, // nop
+        ],
+        4,
+        true,
+    ),
+    (
+        &[
+            0x10, 0x00, 0x00, 0x90, // adrp x16, page(&(.got.plt[n]))
+            0x11, 0x02, 0x40, 0xf9, // ldr x17, [x16, offset(&(.got.plt[n]))]
+            0x10, 0x02, 0x00, 0x91, // add x16, x16, offset(&(.got.plt[n]))
+            0x9f, 0x21, 0x03, 0xd5, // autia1716
+            0x20, 0x02, 0x1f, 0xd6, // br x17
+            0x1f, 0x20, 0x03, 0xd5, // nop
+        ],
+        0,
+        true,
+    ),
+    (
+        &[
+            0x5f, 0x24, 0x03, 0xd5, // bti c
+            0x10, 0x00, 0x00, 0x90, // adrp x16, page(&(.got.plt[n]))
+            0x11, 0x02, 0x40, 0xf9, // ldr x17, [x16, offset(&(.got.plt[n]))]
+            0x10, 0x02, 0x00, 0x91, // add x16, x16, offset(&(.got.plt[n]))
+            0x9f, 0x21, 0x03, 0xd5, // autia1716
+            0x20, 0x02, 0x1f, 0xd6, // br x17
+        ],
+        4,
+        true,
+    ),
+];
+
+fn decode_protected_plt_entry(
+    plt_entry: &[u8],
+    plt_base: u64,
+    plt_offset: u64,
+) -> Option<crate::arch::PltEntry> {
+    PROTECTED_PLT_ENTRY_TEMPLATES
+        .iter()
+        .find_map(|&(template, adrp_offset, has_add)| {
+            let mut relocations = vec![
+                (adrp_offset, REL_ADR_PAGE),
+                (adrp_offset + 4, REL_LDR_OFFSET),
+            ];
+            if has_add {
+                relocations.push((adrp_offset + 8, REL_ADD_LITERAL));
+            }
+            let values = extract_values_from_template(plt_entry, template, &relocations)?;
+
+            let entry_page_base =
+                (plt_base + plt_offset + adrp_offset as u64) & DEFAULT_AARCH64_PAGE_IGNORED_MASK;
+
+            // As for template 2, the `add` is only needed by `autia1716` or for lazy binding, so
+            // we ignore its value.
+            let got_address = entry_page_base.wrapping_add(values[0]) | values[1];
+
+            Some(crate::arch::PltEntry::DerefJmp(got_address))
+        })
+}
+
+/// The range-extension thunk that both we and GNU ld use for branches that can't reach their
+/// target.
+const THUNK_TEMPLATE: &[u8] = &[
+    0x10, 0x00, 0x00, 0x90, // adrp x16, page(target)
+    0x10, 0x02, 0x00, 0x91, // add x16, x16, offset(target)
+    0x00, 0x02, 0x1f, 0xd6, // br x16
+];
+
+fn decode_thunk(thunk: &[u8], address: u64) -> Option<u64> {
+    let values = extract_values_from_template(
+        thunk,
+        THUNK_TEMPLATE,
+        &[(0, REL_ADR_PAGE), (4, REL_ADD_LITERAL)],
+    )?;
+
+    let page_base = address & DEFAULT_AARCH64_PAGE_IGNORED_MASK;
+
+    Some(page_base.wrapping_add(values[0]) | values[1])
+}
+
 /// Extracts the relocation values from `plt_entry`, making sure that the non-relocation parts match
 /// `template`.
 fn extract_values_from_template(
@@ -54,7 +54,7 @@ pub(crate) trait Arch: Clone + Copy + Eq + PartialEq + Debug {
                     }
                 }
                 linker_utils::elf::RelocationSize::BitMasking(BitMask { range, instruction }) => {
-                    mask.bitmask = Vec::from(instruction.bit_mask(range));
+                    mask.bitmask = instruction.bit_mask(range);
                 }
             }
         }
@@ -93,6 +93,11 @@ pub(crate) trait Arch: Clone + Copy + Eq + PartialEq + Debug {
 
     fn decode_plt_entry(plt_entry: &[u8], plt_base: u64, plt_offset: u64) -> Option<PltEntry>;
 
+    /// If a relocation of type `r_type` points at `address` and `bytes`, which start at `address`,
+    /// are a range-extension thunk (also known as a veneer), returns the address that the thunk
+    /// branches to.
+    fn decode_thunk(r_type: Self::RType, bytes: &[u8], address: u64) -> Option<u64>;
+
     /// Returns whether the supplied relocations should be chained together. `chain_prefix` will
     /// always be of length at least 2.
     fn should_chain_relocations(chain_prefix: &[Self::RType]) -> bool;
@@ -238,6 +243,7 @@ impl<A: Arch> Instruction<'_, A> {
     }
 }
 
+#[derive(Debug, PartialEq, Eq)]
 pub(crate) enum PltEntry {
     /// The parameter is an address (most likely of a GOT entry) that will be dereferenced by the
     /// PLT entry then jumped to.
@@ -1811,6 +1811,16 @@ impl<'data> RelaxationTester<'data> {
         let mut reference_props = ReferenceProperties::default();
 
         if is_pointer {
+            // Branches that can't reach their target go via a thunk, so look through it to what
+            // it branches to.
+            if let Some(target) =
+                read_bytes_starting_at(self.bin.elf_file, merged_value).and_then(|bytes| {
+                    A::decode_thunk(last_match.relaxation.new_r_type, bytes, merged_value)
+                })
+            {
+                merged_value = target;
+            }
+
             let mut pointer = merged_value.wrapping_sub(addend as u64);
 
             if let Some(got_address) = self.bin.address_index.plt_to_got_address::<A>(pointer)? {
@@ -2007,6 +2017,11 @@ impl<'data> RelaxationTester<'data> {
             | RelocationKind::TpOff
             | RelocationKind::TpOffAArch64
             | RelocationKind::TlsDescCall
+            | RelocationKind::DtpOffRiscV
+            | RelocationKind::PcRelLoRiscV
+            | RelocationKind::AbsoluteSet
+            | RelocationKind::AbsoluteAddition
+            | RelocationKind::AbsoluteSubtraction
             | RelocationKind::None => 0,
         };
 
@@ -2169,6 +2184,10 @@ fn is_non_pointer_relocation(relocation_kind: RelocationKind) -> bool {
             | RelocationKind::TpOff
             | RelocationKind::TpOffAArch64
             | RelocationKind::TlsDescCall
+            | RelocationKind::DtpOffRiscV
+            | RelocationKind::PcRelLoRiscV
+            | RelocationKind::AbsoluteAddition
+            | RelocationKind::AbsoluteSubtraction
             | RelocationKind::None
     )
 }
@@ -2489,10 +2508,11 @@ impl PltIndex<'_> {
         }
 
         let plt_entry = if self.entry_length == 0 {
-            // Sometimes linkers don't set the entry size on PLT sections. In that case, we try both
-            // size 8 and if that fails, try size 16.
+            // Sometimes linkers don't set the entry size on PLT sections. In that case, we try size
+            // 8, then 16, then 24.
             self.decode_plt_entry_with_size::<A>(offset, 8)
                 .or_else(|| self.decode_plt_entry_with_size::<A>(offset, 16))
+                .or_else(|| self.decode_plt_entry_with_size::<A>(offset, 24))
         } else {
             self.decode_plt_entry_with_size::<A>(offset, self.entry_length)
         }
@@ -2520,7 +2540,9 @@ impl PltIndex<'_> {
         offset: u64,
         entry_size: u64,
     ) -> Option<PltEntry> {
-        let entry_bytes = &self.bytes[offset as usize..(offset + entry_size) as usize];
+        let entry_bytes = self
+            .bytes
+            .get(offset as usize..(offset + entry_size) as usize)?;
         A::decode_plt_entry(entry_bytes, self.plt_base, offset)
     }
 }
@@ -2736,7 +2758,7 @@ impl<'data> AddressIndex<'data> {
 
         let entry_length = section.elf_section_header().sh_entsize(LittleEndian) as usize;
 
-        if ![0, 8, 0x10].contains(&entry_length) {
+        if ![0, 8, 0x10, 0x18].contains(&entry_length) {
             bail!("{section_name} has unrecognised entry length {entry_length}");
         }
 
@@ -2958,6 +2980,7 @@ impl<'data> GotIndex<'data> {
                 | RelocationKind::TlsLdGot
                 | RelocationKind::TlsLdGotBase
                 | RelocationKind::DtpOff
+                | RelocationKind::DtpOffRiscV
                 | RelocationKind::GotTpOff
                 | RelocationKind::GotTpOffGot
                 | RelocationKind::GotTpOffGotBase
@@ -3002,7 +3025,9 @@ fn read_segment<'data>(elf_file: &ElfFile64<'data>, address: u64) -> Option<Data
             let file_size = raw_seg.p_filesz(e) as usize;
             let file_end = file_start + file_size;
             let file_bytes = elf_file.data();
-            if file_bytes.is_empty() {
+            // Addresses past the end of the file data are in the zero-initialised tail of the
+            // segment.
+            if file_bytes.is_empty() || start >= file_size {
                 return Some(Data::Bss);
             }
             let bytes = &file_bytes[file_start..file_end];
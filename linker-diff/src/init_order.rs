@@ -7,6 +7,7 @@ use crate::arch::RType as _;
 use crate::get_r_type;
 use crate::header_diff::ResolvedValue;
 use anyhow::Context;
+use anyhow::bail;
 use linker_utils::elf::DynamicRelocationKind;
 use object::Object;
 use object::ObjectSection;
@@ -28,6 +29,52 @@ pub(crate) fn report_diffs<A: Arch>(report: &mut crate::Report, objects: &[crate
         |bin| get_pointer_list::<A>(bin, ".fini_array"),
         "fini_array",
     ));
+
+    crate::validate_objects(report, objects, "init_array.priority", |bin| {
+        check_priorities(bin, ".init_array")
+    });
+
+    crate::validate_objects(report, objects, "fini_array.priority", |bin| {
+        check_priorities(bin, ".fini_array")
+    });
+}
+
+/// Checks that the input sections that make up `section_name` are sorted by the priority in their
+/// names, with sections that don't have a priority last. We can only do this when we have a layout
+/// file that tells us where each input section was placed.
+fn check_priorities(bin: &Binary, section_name: &str) -> Result {
+    let (Some(layout), Some(sec)) = (&bin.indexed_layout, bin.section_by_name(section_name)) else {
+        return Ok(());
+    };
+
+    let section_range = sec.address()..sec.address() + sec.size();
+
+    let mut input_sections = Vec::new();
+    layout.all_sections_do(|info| {
+        if section_range.contains(&info.addresses.start) {
+            input_sections.push((info.addresses.start, info.section_id));
+        }
+    });
+    input_sections.sort_by_key(|(address, _)| *address);
+
+    let mut previous: Option<(Option<u32>, &[u8])> = None;
+    for (_, section_id) in input_sections {
+        let name = layout.get_elf_section(section_id)?.name_bytes()?;
+        let priority = linker_utils::elf::init_priority(name).map(|(_, priority)| priority);
+        if let Some((previous_priority, previous_name)) = previous {
+            let sort_key = |priority: Option<u32>| (priority.is_none(), priority);
+            if sort_key(priority) < sort_key(previous_priority) {
+                bail!(
+                    "{} is placed after `{}`",
+                    layout.input_section_display(section_id),
+                    String::from_utf8_lossy(previous_name),
+                );
+            }
+        }
+        previous = Some((priority, name));
+    }
+
+    Ok(())
 }
 
 fn get_pointer_list<A: Arch>(bin: &Binary, section_name: &str) -> Result<Vec<ResolvedValue>> {
@@ -42,6 +42,8 @@ mod eh_frame_diff;
 mod gnu_hash;
 mod header_diff;
 mod init_order;
+mod loongarch64;
+mod riscv64;
 pub(crate) mod section_map;
 mod symtab;
 mod trace;
@@ -556,6 +558,12 @@ impl Report {
             object::elf::EM_AARCH64 => {
                 self.report_arch_specific_diffs::<crate::aarch64::AArch64>(objects);
             }
+            object::elf::EM_RISCV => {
+                self.report_arch_specific_diffs::<crate::riscv64::RiscV64>(objects);
+            }
+            object::elf::EM_LOONGARCH => {
+                self.report_arch_specific_diffs::<crate::loongarch64::LoongArch64>(objects);
+            }
             _ => {}
         }
     }
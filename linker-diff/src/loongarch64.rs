@@ -0,0 +1,355 @@
+use crate::arch::Arch;
+use crate::arch::Instruction;
+use crate::arch::Relaxation;
+use crate::arch::RelaxationByteRange;
+use anyhow::Context;
+use anyhow::Result;
+use itertools::Itertools;
+use linker_utils::elf::DynamicRelocationKind;
+use linker_utils::elf::PageMask;
+use linker_utils::elf::RelocationKindInfo;
+use linker_utils::elf::loongarch64_rel_type_to_string;
+use linker_utils::loongarch64::LoongArchInstruction;
+use linker_utils::loongarch64::RelaxationKind;
+use linker_utils::relaxation::RelocationModifier;
+use std::fmt::Display;
+use std::io::Write;
+use std::process::Command;
+use std::process::Stdio;
+use std::sync::OnceLock;
+use tempfile::NamedTempFile;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) struct LoongArch64;
+
+fn decode_insn_with_objdump(insn: &[u8], address: u64) -> Result<String> {
+    static OBJDUMP_BIN: OnceLock<&'static str> = OnceLock::new();
+
+    let mut tmpfile = NamedTempFile::new()?;
+    tmpfile.write_all(insn)?;
+    tmpfile.flush()?;
+
+    let objdump = OBJDUMP_BIN.get_or_init(|| {
+        ["loongarch64-linux-gnu-objdump", "objdump"]
+            .iter()
+            .find(|bin| which::which(bin).is_ok())
+            .unwrap()
+    });
+
+    let command = Command::new(objdump)
+        .arg("-b")
+        .arg("binary")
+        .arg(format!("--adjust-vma=0x{address:x}"))
+        .arg("-m")
+        .arg("loongarch64")
+        .arg("-D")
+        .arg(tmpfile.path())
+        .stdout(Stdio::piped())
+        .spawn()
+        .context("Failed to spawn objdump")?;
+
+    let output = command.wait_with_output().expect("Failed to read stdout");
+    // Sample output: 0:	1c00000f 	pcaddu12i   	$t3, 0
+    let insn_line = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .last()
+        .context("No objdump output")?
+        .to_owned();
+    Ok(insn_line
+        .split_whitespace()
+        .skip(2)
+        .join(" ")
+        .replacen(" ", "\t", 1))
+}
+
+#[test]
+fn test_decode_plt_entry() {
+    // A PLT entry at 0x1010 that jumps via a GOT entry at 0x3008.
+    let entry = [
+        0x4f, 0x00, 0x00, 0x1c, // pcaddu12i $t3, 2
+        0xef, 0xe1, 0xff, 0x28, // ld.d $t3, $t3, -8
+        0xed, 0x01, 0x00, 0x4c, // jirl $t1, $t3, 0
+        0x00, 0x00, 0x40, 0x03, // nop
+    ];
+    assert_eq!(
+        LoongArch64::decode_plt_entry(&entry, 0x1000, 0x10),
+        Some(crate::arch::PltEntry::DerefJmp(0x3008))
+    );
+    assert_eq!(
+        LoongArch64::decode_plt_entry(&entry[..12], 0x1000, 0x10),
+        None
+    );
+}
+
+impl Arch for LoongArch64 {
+    type RType = RType;
+
+    type RelaxationKind = RelaxationKind;
+
+    type RawInstruction = Option<String>;
+
+    const MAX_RELAX_MODIFY_BEFORE: u64 = 0;
+    const MAX_RELAX_MODIFY_AFTER: u64 = 4;
+
+    fn possible_relaxations_do(
+        r_type: Self::RType,
+        _section_kind: object::SectionKind,
+        mut cb: impl FnMut(crate::arch::Relaxation<Self>),
+    ) {
+        let mut relax = |relaxation_kind, new_r_type| {
+            cb(Relaxation {
+                relaxation_kind,
+                new_r_type: RType(new_r_type),
+                alt_r_type: None,
+            });
+        };
+
+        match r_type.0 {
+            linker_utils::loongarch64::R_LARCH_TLS_DESC_PC_HI20
+            | linker_utils::loongarch64::R_LARCH_TLS_DESC_PC_LO12
+            | linker_utils::loongarch64::R_LARCH_TLS_DESC_PCREL20_S2 => {
+                relax(RelaxationKind::ReplaceWithNop, object::elf::R_LARCH_NONE);
+            }
+            linker_utils::loongarch64::R_LARCH_TLS_DESC_LD => {
+                relax(
+                    RelaxationKind::TlsDescToLocalExecLu12i,
+                    object::elf::R_LARCH_TLS_LE_HI20,
+                );
+            }
+            linker_utils::loongarch64::R_LARCH_TLS_DESC_CALL => {
+                relax(
+                    RelaxationKind::TlsDescToLocalExecOri,
+                    object::elf::R_LARCH_TLS_LE_LO12,
+                );
+            }
+            object::elf::R_LARCH_CALL36 => {
+                relax(RelaxationKind::NoOp, object::elf::R_LARCH_B26);
+            }
+            _ => {}
+        }
+
+        relax(Self::RelaxationKind::NoOp, r_type.0);
+    }
+
+    fn relaxation_byte_range(_relaxation: Relaxation<Self>) -> RelaxationByteRange {
+        RelaxationByteRange {
+            offset_shift: 0,
+            num_bytes: 4,
+        }
+    }
+
+    fn apply_relaxation(
+        relaxation_kind: Self::RelaxationKind,
+        section_bytes: &mut [u8],
+        offset_in_section: &mut u64,
+        addend: &mut i64,
+    ) {
+        relaxation_kind.apply(section_bytes, offset_in_section, addend);
+    }
+
+    fn next_relocation_modifier(relaxation_kind: Self::RelaxationKind) -> RelocationModifier {
+        relaxation_kind.next_modifier()
+    }
+
+    fn instruction_to_string(instruction: &Instruction<Self>) -> String {
+        if let Some(str) = instruction.raw_instruction.as_ref() {
+            return str.to_owned();
+        }
+        String::new()
+    }
+
+    fn decode_instructions_in_range(
+        section_bytes: &[u8],
+        section_address: u64,
+        _function_offset_in_section: u64,
+        range: std::ops::Range<u64>,
+    ) -> Vec<crate::arch::Instruction<'_, Self>> {
+        let mut offset = range.start & !3;
+
+        let mut instructions = Vec::new();
+
+        while offset < range.end {
+            let Some(bytes) = section_bytes.get(offset as usize..offset as usize + 4) else {
+                break;
+            };
+            let address = section_address + offset;
+
+            instructions.push(crate::arch::Instruction {
+                raw_instruction: decode_insn_with_objdump(bytes, address).ok(),
+                address,
+                bytes,
+            });
+
+            offset += 4;
+        }
+
+        instructions
+    }
+
+    fn decode_plt_entry(
+        plt_entry: &[u8],
+        plt_base: u64,
+        plt_offset: u64,
+    ) -> Option<crate::arch::PltEntry> {
+        const PLT_ENTRY_TEMPLATE: &[u8] = &[
+            0x0f, 0x00, 0x00, 0x1c, // pcaddu12i $t3, %pc_hi20(.got.plt[n])
+            0xef, 0x01, 0xc0, 0x28, // ld.d $t3, $t3, %pc_lo12(.got.plt[n])
+            0xed, 0x01, 0x00, 0x4c, // jirl $t1, $t3, 0
+            0x00, 0x00, 0x40, 0x03, // nop
+        ];
+        const MASK: &[u8] = &[
+            0x1f, 0x00, 0x00, 0xfe, // pcaddu12i
+            0xff, 0x03, 0xc0, 0xff, // ld.d
+            0xff, 0xff, 0xff, 0xff, // jirl
+            0xff, 0xff, 0xff, 0xff, // nop
+        ];
+
+        if plt_entry.len() != PLT_ENTRY_TEMPLATE.len()
+            || !plt_entry
+                .iter()
+                .zip(PLT_ENTRY_TEMPLATE)
+                .zip(MASK)
+                .all(|((a, b), m)| a & m == b & m)
+        {
+            return None;
+        }
+
+        let hi = LoongArchInstruction::Hi20.read_value(plt_entry);
+        let lo = LoongArchInstruction::Si12.read_value(&plt_entry[4..]);
+        let got_address = (plt_base + plt_offset).wrapping_add(hi).wrapping_add(lo);
+
+        Some(crate::arch::PltEntry::DerefJmp(got_address))
+    }
+
+    fn decode_thunk(_r_type: Self::RType, _bytes: &[u8], _address: u64) -> Option<u64> {
+        // Neither we nor GNU ld use range-extension thunks on LoongArch.
+        None
+    }
+
+    fn should_chain_relocations(chain_prefix: &[Self::RType]) -> bool {
+        CHAINS
+            .iter()
+            .any(|full_chain| full_chain.starts_with(chain_prefix))
+    }
+
+    fn get_relocation_base_mask(relocation_info: &RelocationKindInfo) -> u64 {
+        match relocation_info.mask {
+            Some(PageMask::Position) => linker_utils::aarch64::DEFAULT_AARCH64_PAGE_IGNORED_MASK,
+            _ => u64::MAX,
+        }
+    }
+
+    fn relocation_to_pc_offset(_relocation_info: &RelocationKindInfo) -> u64 {
+        // PC-relative values are relative to the instruction containing the relocation.
+        0
+    }
+
+    fn is_complete_chain(chain: impl Iterator<Item = Self::RType>) -> bool {
+        let chain = chain.collect::<Vec<_>>();
+        for candidate in CHAINS {
+            if candidate.starts_with(&chain) && *candidate != chain {
+                return false;
+            }
+        }
+
+        // Parts of addresses only make sense together with the other parts.
+        const NOT_IN_ISOLATION: &[RType] = &[
+            RType(object::elf::R_LARCH_ABS_HI20),
+            RType(object::elf::R_LARCH_ABS_LO12),
+            RType(object::elf::R_LARCH_ABS64_LO20),
+            RType(object::elf::R_LARCH_ABS64_HI12),
+            RType(object::elf::R_LARCH_PCALA_HI20),
+            RType(object::elf::R_LARCH_PCALA_LO12),
+            RType(object::elf::R_LARCH_GOT_PC_HI20),
+            RType(object::elf::R_LARCH_GOT_PC_LO12),
+            RType(object::elf::R_LARCH_TLS_LE_HI20),
+            RType(object::elf::R_LARCH_TLS_LE_LO12),
+            RType(object::elf::R_LARCH_TLS_IE_PC_HI20),
+            RType(object::elf::R_LARCH_TLS_IE_PC_LO12),
+            RType(object::elf::R_LARCH_TLS_GD_PC_HI20),
+            RType(object::elf::R_LARCH_TLS_LD_PC_HI20),
+            RType(linker_utils::loongarch64::R_LARCH_TLS_LE_HI20_R),
+            RType(linker_utils::loongarch64::R_LARCH_TLS_LE_LO12_R),
+            RType(linker_utils::loongarch64::R_LARCH_TLS_DESC_PC_HI20),
+            RType(linker_utils::loongarch64::R_LARCH_TLS_DESC_PC_LO12),
+            RType(linker_utils::loongarch64::R_LARCH_TLS_DESC_LD),
+            RType(linker_utils::loongarch64::R_LARCH_TLS_DESC_CALL),
+        ];
+
+        match chain.as_slice() {
+            [r_type] => !NOT_IN_ISOLATION.contains(r_type),
+            _ => true,
+        }
+    }
+}
+
+const CHAINS: &[&[RType]] = &[
+    &[
+        RType(object::elf::R_LARCH_ABS_HI20),
+        RType(object::elf::R_LARCH_ABS_LO12),
+    ],
+    &[
+        RType(object::elf::R_LARCH_PCALA_HI20),
+        RType(object::elf::R_LARCH_PCALA_LO12),
+    ],
+    &[
+        RType(object::elf::R_LARCH_GOT_PC_HI20),
+        RType(object::elf::R_LARCH_GOT_PC_LO12),
+    ],
+    &[
+        RType(object::elf::R_LARCH_TLS_LE_HI20),
+        RType(object::elf::R_LARCH_TLS_LE_LO12),
+    ],
+    &[
+        RType(object::elf::R_LARCH_TLS_IE_PC_HI20),
+        RType(object::elf::R_LARCH_TLS_IE_PC_LO12),
+    ],
+    &[
+        RType(linker_utils::loongarch64::R_LARCH_TLS_LE_HI20_R),
+        RType(linker_utils::loongarch64::R_LARCH_TLS_LE_ADD_R),
+        RType(linker_utils::loongarch64::R_LARCH_TLS_LE_LO12_R),
+    ],
+    &[
+        RType(linker_utils::loongarch64::R_LARCH_TLS_DESC_PC_HI20),
+        RType(linker_utils::loongarch64::R_LARCH_TLS_DESC_PC_LO12),
+        RType(linker_utils::loongarch64::R_LARCH_TLS_DESC_LD),
+        RType(linker_utils::loongarch64::R_LARCH_TLS_DESC_CALL),
+    ],
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct RType(u32);
+
+impl crate::arch::RType for RType {
+    fn from_raw(raw: u32) -> Self {
+        RType(raw)
+    }
+
+    fn from_dynamic_relocation_kind(kind: DynamicRelocationKind) -> Self {
+        Self::from_raw(kind.loongarch64_r_type())
+    }
+
+    fn opt_relocation_info(self) -> Option<RelocationKindInfo> {
+        linker_utils::loongarch64::relocation_type_from_raw(self.0)
+    }
+
+    fn dynamic_relocation_kind(self) -> Option<DynamicRelocationKind> {
+        DynamicRelocationKind::from_loongarch64_r_type(self.0)
+    }
+}
+
+impl Display for RType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&loongarch64_rel_type_to_string(self.0), f)
+    }
+}
+
+impl crate::arch::RelaxationKind for RelaxationKind {
+    fn is_no_op(self) -> bool {
+        matches!(self, RelaxationKind::NoOp)
+    }
+
+    fn is_replace_with_no_op(self) -> bool {
+        matches!(self, RelaxationKind::ReplaceWithNop)
+    }
+}
@@ -0,0 +1,343 @@
+use crate::arch::Arch;
+use crate::arch::Instruction;
+use crate::arch::Relaxation;
+use crate::arch::RelaxationByteRange;
+use anyhow::Context;
+use anyhow::Result;
+use itertools::Itertools;
+use linker_utils::elf::DynamicRelocationKind;
+use linker_utils::elf::RelocationKindInfo;
+use linker_utils::elf::riscv64_rel_type_to_string;
+use linker_utils::relaxation::RelocationModifier;
+use linker_utils::riscv64::RelaxationKind;
+use linker_utils::riscv64::RiscVInstruction;
+use std::fmt::Display;
+use std::io::Write;
+use std::process::Command;
+use std::process::Stdio;
+use std::sync::OnceLock;
+use tempfile::NamedTempFile;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) struct RiscV64;
+
+fn decode_insn_with_objdump(insn: &[u8], address: u64) -> Result<String> {
+    static OBJDUMP_BIN: OnceLock<&'static str> = OnceLock::new();
+
+    let mut tmpfile = NamedTempFile::new()?;
+    tmpfile.write_all(insn)?;
+    tmpfile.flush()?;
+
+    let objdump = OBJDUMP_BIN.get_or_init(|| {
+        ["riscv64-linux-gnu-objdump", "objdump"]
+            .iter()
+            .find(|bin| which::which(bin).is_ok())
+            .unwrap()
+    });
+
+    let command = Command::new(objdump)
+        .arg("-b")
+        .arg("binary")
+        .arg(format!("--adjust-vma=0x{address:x}"))
+        .arg("-m")
+        .arg("riscv:rv64")
+        .arg("-D")
+        .arg(tmpfile.path())
+        .stdout(Stdio::piped())
+        .spawn()
+        .context("Failed to spawn objdump")?;
+
+    let output = command.wait_with_output().expect("Failed to read stdout");
+    // Sample output: 0:	00000e17          	auipc	t3,0x0
+    let insn_line = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .last()
+        .context("No objdump output")?
+        .to_owned();
+    Ok(insn_line
+        .split_whitespace()
+        .skip(2)
+        .join(" ")
+        .replacen(" ", "\t", 1))
+}
+
+/// Returns the length of the instruction that starts with `first_byte`. We only support 16 and 32
+/// bit instructions.
+fn instruction_len(first_byte: u8) -> u64 {
+    if first_byte & 0b11 == 0b11 { 4 } else { 2 }
+}
+
+#[test]
+fn test_decode_plt_entry() {
+    // A PLT entry at 0x1010 that jumps via a GOT entry at 0x3008.
+    let entry = [
+        0x17, 0x2e, 0x00, 0x00, // auipc t3, 0x2
+        0x03, 0x3e, 0x8e, 0xff, // ld t3, -8(t3)
+        0x67, 0x03, 0x0e, 0x00, // jalr t1, t3
+        0x13, 0x00, 0x00, 0x00, // nop
+    ];
+    assert_eq!(
+        RiscV64::decode_plt_entry(&entry, 0x1000, 0x10),
+        Some(crate::arch::PltEntry::DerefJmp(0x3008))
+    );
+    assert_eq!(RiscV64::decode_plt_entry(&entry[..12], 0x1000, 0x10), None);
+}
+
+impl Arch for RiscV64 {
+    type RType = RType;
+
+    type RelaxationKind = RelaxationKind;
+
+    type RawInstruction = Option<String>;
+
+    const MAX_RELAX_MODIFY_BEFORE: u64 = 0;
+    const MAX_RELAX_MODIFY_AFTER: u64 = 4;
+
+    fn possible_relaxations_do(
+        r_type: Self::RType,
+        _section_kind: object::SectionKind,
+        mut cb: impl FnMut(crate::arch::Relaxation<Self>),
+    ) {
+        let mut relax = |relaxation_kind, new_r_type| {
+            cb(Relaxation {
+                relaxation_kind,
+                new_r_type: RType(new_r_type),
+                alt_r_type: None,
+            });
+        };
+
+        match r_type.0 {
+            object::elf::R_RISCV_TLSDESC_HI20 | object::elf::R_RISCV_TLSDESC_LOAD_LO12 => {
+                relax(RelaxationKind::ReplaceWithNop, object::elf::R_RISCV_NONE);
+            }
+            object::elf::R_RISCV_TLSDESC_ADD_LO12 => {
+                relax(
+                    RelaxationKind::TlsDescToLocalExecLui,
+                    object::elf::R_RISCV_TPREL_HI20,
+                );
+            }
+            object::elf::R_RISCV_TLSDESC_CALL => {
+                relax(
+                    RelaxationKind::TlsDescToLocalExecAddi,
+                    object::elf::R_RISCV_TPREL_LO12_I,
+                );
+            }
+            object::elf::R_RISCV_CALL | object::elf::R_RISCV_CALL_PLT => {
+                relax(RelaxationKind::NoOp, object::elf::R_RISCV_JAL);
+            }
+            _ => {}
+        }
+
+        relax(Self::RelaxationKind::NoOp, r_type.0);
+    }
+
+    fn relaxation_byte_range(_relaxation: Relaxation<Self>) -> RelaxationByteRange {
+        RelaxationByteRange {
+            offset_shift: 0,
+            num_bytes: 4,
+        }
+    }
+
+    fn apply_relaxation(
+        relaxation_kind: Self::RelaxationKind,
+        section_bytes: &mut [u8],
+        offset_in_section: &mut u64,
+        addend: &mut i64,
+    ) {
+        relaxation_kind.apply(section_bytes, offset_in_section, addend);
+    }
+
+    fn next_relocation_modifier(relaxation_kind: Self::RelaxationKind) -> RelocationModifier {
+        relaxation_kind.next_modifier()
+    }
+
+    fn instruction_to_string(instruction: &Instruction<Self>) -> String {
+        if let Some(str) = instruction.raw_instruction.as_ref() {
+            return str.to_owned();
+        }
+        String::new()
+    }
+
+    fn decode_instructions_in_range(
+        section_bytes: &[u8],
+        section_address: u64,
+        function_offset_in_section: u64,
+        range: std::ops::Range<u64>,
+    ) -> Vec<crate::arch::Instruction<'_, Self>> {
+        // With compressed instructions, we can't tell where an instruction starts without decoding
+        // from the start of the function.
+        let mut offset = function_offset_in_section;
+        let mut instructions = Vec::new();
+
+        while offset < range.end {
+            let Some(&first_byte) = section_bytes.get(offset as usize) else {
+                break;
+            };
+            let len = instruction_len(first_byte);
+            let Some(bytes) = section_bytes.get(offset as usize..(offset + len) as usize) else {
+                break;
+            };
+            if offset + len > range.start {
+                let address = section_address + offset;
+                instructions.push(crate::arch::Instruction {
+                    raw_instruction: decode_insn_with_objdump(bytes, address).ok(),
+                    address,
+                    bytes,
+                });
+            }
+            offset += len;
+        }
+
+        instructions
+    }
+
+    fn decode_plt_entry(
+        plt_entry: &[u8],
+        plt_base: u64,
+        plt_offset: u64,
+    ) -> Option<crate::arch::PltEntry> {
+        const PLT_ENTRY_TEMPLATE: &[u8] = &[
+            0x17, 0x0e, 0x00, 0x00, // auipc t3, %pcrel_hi(.got.plt[n])
+            0x03, 0x3e, 0x0e, 0x00, // ld t3, %pcrel_lo(.got.plt[n])(t3)
+            0x67, 0x03, 0x0e, 0x00, // jalr t1, t3
+            0x13, 0x00, 0x00, 0x00, // nop
+        ];
+        const MASK: &[u8] = &[
+            0xff, 0x0f, 0x00, 0x00, // auipc
+            0xff, 0xff, 0x0f, 0x00, // ld
+            0xff, 0xff, 0xff, 0xff, // jalr
+            0xff, 0xff, 0xff, 0xff, // nop
+        ];
+
+        if plt_entry.len() != PLT_ENTRY_TEMPLATE.len()
+            || !plt_entry
+                .iter()
+                .zip(PLT_ENTRY_TEMPLATE)
+                .zip(MASK)
+                .all(|((a, b), m)| a & m == b & m)
+        {
+            return None;
+        }
+
+        let hi = RiscVInstruction::UType.read_value(plt_entry);
+        let lo = RiscVInstruction::IType.read_value(&plt_entry[4..]);
+        let got_address = (plt_base + plt_offset).wrapping_add(hi).wrapping_add(lo);
+
+        Some(crate::arch::PltEntry::DerefJmp(got_address))
+    }
+
+    fn decode_thunk(_r_type: Self::RType, _bytes: &[u8], _address: u64) -> Option<u64> {
+        // We don't use range-extension thunks on RISC-V. Calls that can't reach use `auipc` and
+        // `jalr` instead.
+        None
+    }
+
+    fn should_chain_relocations(chain_prefix: &[Self::RType]) -> bool {
+        CHAINS
+            .iter()
+            .any(|full_chain| full_chain.starts_with(chain_prefix))
+    }
+
+    fn get_relocation_base_mask(_relocation_info: &RelocationKindInfo) -> u64 {
+        u64::MAX
+    }
+
+    fn relocation_to_pc_offset(_relocation_info: &RelocationKindInfo) -> u64 {
+        // Like AArch64, PC-relative values are relative to the instruction containing the
+        // relocation.
+        0
+    }
+
+    fn is_complete_chain(chain: impl Iterator<Item = Self::RType>) -> bool {
+        let chain = chain.collect::<Vec<_>>();
+        for candidate in CHAINS {
+            if candidate.starts_with(&chain) && *candidate != chain {
+                return false;
+            }
+        }
+
+        // The low parts of addresses only make sense together with the corresponding high part.
+        const NOT_IN_ISOLATION: &[RType] = &[
+            RType(object::elf::R_RISCV_PCREL_HI20),
+            RType(object::elf::R_RISCV_PCREL_LO12_I),
+            RType(object::elf::R_RISCV_PCREL_LO12_S),
+            RType(object::elf::R_RISCV_GOT_HI20),
+            RType(object::elf::R_RISCV_TLS_GOT_HI20),
+            RType(object::elf::R_RISCV_TLS_GD_HI20),
+            RType(object::elf::R_RISCV_HI20),
+            RType(object::elf::R_RISCV_LO12_I),
+            RType(object::elf::R_RISCV_LO12_S),
+            RType(object::elf::R_RISCV_TPREL_HI20),
+            RType(object::elf::R_RISCV_TPREL_LO12_I),
+            RType(object::elf::R_RISCV_TPREL_LO12_S),
+            RType(object::elf::R_RISCV_TLSDESC_HI20),
+            RType(object::elf::R_RISCV_TLSDESC_LOAD_LO12),
+            RType(object::elf::R_RISCV_TLSDESC_ADD_LO12),
+            RType(object::elf::R_RISCV_TLSDESC_CALL),
+        ];
+
+        match chain.as_slice() {
+            [r_type] => !NOT_IN_ISOLATION.contains(r_type),
+            _ => true,
+        }
+    }
+}
+
+const CHAINS: &[&[RType]] = &[
+    &[
+        RType(object::elf::R_RISCV_HI20),
+        RType(object::elf::R_RISCV_LO12_I),
+    ],
+    &[
+        RType(object::elf::R_RISCV_HI20),
+        RType(object::elf::R_RISCV_LO12_S),
+    ],
+    &[
+        RType(object::elf::R_RISCV_TPREL_HI20),
+        RType(object::elf::R_RISCV_TPREL_ADD),
+        RType(object::elf::R_RISCV_TPREL_LO12_I),
+    ],
+    &[
+        RType(object::elf::R_RISCV_TLSDESC_HI20),
+        RType(object::elf::R_RISCV_TLSDESC_LOAD_LO12),
+        RType(object::elf::R_RISCV_TLSDESC_ADD_LO12),
+        RType(object::elf::R_RISCV_TLSDESC_CALL),
+    ],
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct RType(u32);
+
+impl crate::arch::RType for RType {
+    fn from_raw(raw: u32) -> Self {
+        RType(raw)
+    }
+
+    fn from_dynamic_relocation_kind(kind: DynamicRelocationKind) -> Self {
+        Self::from_raw(kind.riscv64_r_type())
+    }
+
+    fn opt_relocation_info(self) -> Option<RelocationKindInfo> {
+        linker_utils::riscv64::relocation_type_from_raw(self.0)
+    }
+
+    fn dynamic_relocation_kind(self) -> Option<DynamicRelocationKind> {
+        DynamicRelocationKind::from_riscv64_r_type(self.0)
+    }
+}
+
+impl Display for RType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&riscv64_rel_type_to_string(self.0), f)
+    }
+}
+
+impl crate::arch::RelaxationKind for RelaxationKind {
+    fn is_no_op(self) -> bool {
+        matches!(self, RelaxationKind::NoOp)
+    }
+
+    fn is_replace_with_no_op(self) -> bool {
+        matches!(self, RelaxationKind::ReplaceWithNop)
+    }
+}
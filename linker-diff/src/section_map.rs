@@ -323,7 +323,7 @@ impl Display for DisplaySection<'_> {
 
 #[derive(Clone)]
 pub(crate) struct SectionInfo<'data> {
-    addresses: Range<u64>,
+    pub(crate) addresses: Range<u64>,
     pub(crate) section_id: InputSectionId,
     functions: Vec<FunctionInfo<'data>>,
 }
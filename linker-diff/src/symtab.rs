@@ -25,7 +25,7 @@ fn validate(object: &Binary, dynamic: bool) -> Result {
         (sht::SYMTAB, object.elf_file.symbols())
     };
     for section in object.elf_file.elf_section_table().iter() {
-        if SectionType::from_header(section) == symtab_section_type {
+        if SectionType::from_header(section, LittleEndian) == symtab_section_type {
             symtab_info = section.sh_info(LittleEndian);
         }
     }
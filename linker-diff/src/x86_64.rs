@@ -9,6 +9,11 @@ use linker_utils::elf::DynamicRelocationKind;
 use linker_utils::elf::RelocationKindInfo;
 use linker_utils::elf::x86_64_rel_type_to_string;
 use linker_utils::utils::u32_from_slice;
+use linker_utils::x86_64::R_X86_64_CODE_4_GOTPC32_TLSDESC;
+use linker_utils::x86_64::R_X86_64_CODE_4_GOTPCRELX;
+use linker_utils::x86_64::R_X86_64_CODE_4_GOTTPOFF;
+use linker_utils::x86_64::R_X86_64_CODE_6_GOTPCRELX;
+use linker_utils::x86_64::R_X86_64_CODE_6_GOTTPOFF;
 use linker_utils::x86_64::RelaxationKind;
 use object::SectionKind;
 use std::fmt::Display;
@@ -25,7 +30,7 @@ impl Arch for X86_64 {
 
     type RawInstruction = iced_x86::Instruction;
 
-    const MAX_RELAX_MODIFY_BEFORE: u64 = 4;
+    const MAX_RELAX_MODIFY_BEFORE: u64 = 5;
     const MAX_RELAX_MODIFY_AFTER: u64 = 19;
 
     fn next_relocation_modifier(
@@ -41,6 +46,14 @@ impl Arch for X86_64 {
             Self::RelaxationKind::RexMovIndirectToAbsolute => RelaxationByteRange::new(3, 7),
             Self::RelaxationKind::RexSubIndirectToAbsolute => RelaxationByteRange::new(3, 7),
             Self::RelaxationKind::RexCmpIndirectToAbsolute => RelaxationByteRange::new(3, 7),
+            Self::RelaxationKind::Rex2MovIndirectToAbsolute => RelaxationByteRange::new(3, 7),
+            Self::RelaxationKind::Rex2SubIndirectToAbsolute => RelaxationByteRange::new(3, 7),
+            Self::RelaxationKind::Rex2CmpIndirectToAbsolute => RelaxationByteRange::new(3, 7),
+            Self::RelaxationKind::Rex2AddIndirectToAbsolute => RelaxationByteRange::new(3, 7),
+            Self::RelaxationKind::EvexAddIndirectToAbsolute => RelaxationByteRange::new(5, 9),
+            Self::RelaxationKind::EvexSubIndirectToAbsolute => RelaxationByteRange::new(5, 9),
+            Self::RelaxationKind::Rex2TlsDescToLocalExec => RelaxationByteRange::new(3, 7),
+            Self::RelaxationKind::Rex2TlsDescToInitialExec => RelaxationByteRange::new(2, 6),
             Self::RelaxationKind::CallIndirectToRelative => RelaxationByteRange::new(2, 6),
             Self::RelaxationKind::JmpIndirectToRelative => RelaxationByteRange::new(2, 6),
             Self::RelaxationKind::TlsGdToLocalExec => RelaxationByteRange::new(4, 16),
@@ -141,6 +154,60 @@ impl Arch for X86_64 {
                     object::elf::R_X86_64_TPOFF32,
                 );
             }
+            (SectionKind::Text, R_X86_64_CODE_4_GOTPCRELX) => {
+                relax(
+                    Self::RelaxationKind::Rex2MovIndirectToAbsolute,
+                    object::elf::R_X86_64_32,
+                );
+                relax(
+                    Self::RelaxationKind::Rex2SubIndirectToAbsolute,
+                    object::elf::R_X86_64_32,
+                );
+                relax(
+                    Self::RelaxationKind::Rex2CmpIndirectToAbsolute,
+                    object::elf::R_X86_64_32,
+                );
+                relax(
+                    Self::RelaxationKind::MovIndirectToLea,
+                    object::elf::R_X86_64_PC32,
+                );
+            }
+            (SectionKind::Text, R_X86_64_CODE_6_GOTPCRELX) => {
+                relax(
+                    Self::RelaxationKind::EvexAddIndirectToAbsolute,
+                    object::elf::R_X86_64_32,
+                );
+                relax(
+                    Self::RelaxationKind::EvexSubIndirectToAbsolute,
+                    object::elf::R_X86_64_32,
+                );
+            }
+            (SectionKind::Text, R_X86_64_CODE_4_GOTTPOFF) => {
+                relax(
+                    Self::RelaxationKind::Rex2MovIndirectToAbsolute,
+                    object::elf::R_X86_64_TPOFF32,
+                );
+                relax(
+                    Self::RelaxationKind::Rex2AddIndirectToAbsolute,
+                    object::elf::R_X86_64_TPOFF32,
+                );
+            }
+            (SectionKind::Text, R_X86_64_CODE_6_GOTTPOFF) => {
+                relax(
+                    Self::RelaxationKind::EvexAddIndirectToAbsolute,
+                    object::elf::R_X86_64_TPOFF32,
+                );
+            }
+            (SectionKind::Text, R_X86_64_CODE_4_GOTPC32_TLSDESC) => {
+                relax(
+                    Self::RelaxationKind::Rex2TlsDescToLocalExec,
+                    object::elf::R_X86_64_TPOFF32,
+                );
+                relax(
+                    Self::RelaxationKind::Rex2TlsDescToInitialExec,
+                    R_X86_64_CODE_4_GOTTPOFF,
+                );
+            }
             (SectionKind::Text, object::elf::R_X86_64_PLT32) => {
                 relax(Self::RelaxationKind::NoOp, object::elf::R_X86_64_PC32);
             }
@@ -225,6 +292,11 @@ impl Arch for X86_64 {
         out
     }
 
+    fn decode_thunk(_r_type: Self::RType, _bytes: &[u8], _address: u64) -> Option<u64> {
+        // We don't use thunks on x86-64.
+        None
+    }
+
     fn decode_plt_entry(
         plt_entry: &[u8],
         plt_base: u64,
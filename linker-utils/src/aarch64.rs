@@ -961,6 +961,14 @@ impl RelocationInstruction {
     pub fn write_to_value(self, extracted_value: u64, negative: bool, dest: &mut [u8]) {
         let mut mask;
         match self {
+            RelocationInstruction::RiscV(instruction) => {
+                instruction.write_to_value(extracted_value, dest);
+                return;
+            }
+            RelocationInstruction::LoongArch(instruction) => {
+                instruction.write_to_value(extracted_value, dest);
+                return;
+            }
             // C6.2.13
             RelocationInstruction::Adr => {
                 mask = ((extract_bits(extracted_value, 0, 2) as u32) << 29)
@@ -1021,6 +1029,15 @@ impl RelocationInstruction {
     /// must be at least 4 bytes, otherwise we panic.
     #[must_use]
     pub fn read_value(self, bytes: &[u8]) -> (u64, bool) {
+        match self {
+            RelocationInstruction::RiscV(instruction) => {
+                return (instruction.read_value(bytes), false);
+            }
+            RelocationInstruction::LoongArch(instruction) => {
+                return (instruction.read_value(bytes), false);
+            }
+            _ => {}
+        }
         let mut negative = false;
         let value = u32::from_le_bytes(*bytes.first_chunk::<4>().expect("Need at least 4 bytes"));
         let extracted_value = match self {
@@ -1050,6 +1067,7 @@ impl RelocationInstruction {
             RelocationInstruction::Bcond => low_bits_signed(value >> 5, 19),
             // C6.2.33
             RelocationInstruction::JumpCall => low_bits_signed(value, 26),
+            RelocationInstruction::RiscV(_) | RelocationInstruction::LoongArch(_) => unreachable!(),
         };
 
         (extracted_value, negative)
@@ -1,5 +1,5 @@
 use anyhow::Result;
-use object::LittleEndian;
+use object::Endian;
 use object::read::elf::SectionHeader;
 use std::borrow::Cow;
 
@@ -14,6 +14,8 @@ macro_rules! const_name_by_value {
 
 #[must_use]
 pub fn x86_64_rel_type_to_string(r_type: u32) -> Cow<'static, str> {
+    use crate::x86_64;
+
     if let Some(name) = const_name_by_value![
         r_type,
         R_X86_64_NONE,
@@ -57,10 +59,72 @@ pub fn x86_64_rel_type_to_string(r_type: u32) -> Cow<'static, str> {
         R_X86_64_RELATIVE64,
         R_X86_64_GOTPCRELX,
         R_X86_64_REX_GOTPCRELX
+    ] {
+        return Cow::Borrowed(name);
+    }
+    // Relocations that the `object` crate doesn't define yet.
+    let name = match r_type {
+        x86_64::R_X86_64_CODE_4_GOTPCRELX => "R_X86_64_CODE_4_GOTPCRELX",
+        x86_64::R_X86_64_CODE_4_GOTTPOFF => "R_X86_64_CODE_4_GOTTPOFF",
+        x86_64::R_X86_64_CODE_4_GOTPC32_TLSDESC => "R_X86_64_CODE_4_GOTPC32_TLSDESC",
+        x86_64::R_X86_64_CODE_6_GOTPCRELX => "R_X86_64_CODE_6_GOTPCRELX",
+        x86_64::R_X86_64_CODE_6_GOTTPOFF => "R_X86_64_CODE_6_GOTTPOFF",
+        x86_64::R_X86_64_CODE_6_GOTPC32_TLSDESC => "R_X86_64_CODE_6_GOTPC32_TLSDESC",
+        _ => return Cow::Owned(format!("Unknown x86_64 relocation type 0x{r_type:x}")),
+    };
+    Cow::Borrowed(name)
+}
+
+#[must_use]
+pub fn i386_rel_type_to_string(r_type: u32) -> Cow<'static, str> {
+    if let Some(name) = const_name_by_value![
+        r_type,
+        R_386_NONE,
+        R_386_32,
+        R_386_PC32,
+        R_386_GOT32,
+        R_386_PLT32,
+        R_386_COPY,
+        R_386_GLOB_DAT,
+        R_386_JMP_SLOT,
+        R_386_RELATIVE,
+        R_386_GOTOFF,
+        R_386_GOTPC,
+        R_386_32PLT,
+        R_386_TLS_TPOFF,
+        R_386_TLS_IE,
+        R_386_TLS_GOTIE,
+        R_386_TLS_LE,
+        R_386_TLS_GD,
+        R_386_TLS_LDM,
+        R_386_16,
+        R_386_PC16,
+        R_386_8,
+        R_386_PC8,
+        R_386_TLS_GD_32,
+        R_386_TLS_GD_PUSH,
+        R_386_TLS_GD_CALL,
+        R_386_TLS_GD_POP,
+        R_386_TLS_LDM_32,
+        R_386_TLS_LDM_PUSH,
+        R_386_TLS_LDM_CALL,
+        R_386_TLS_LDM_POP,
+        R_386_TLS_LDO_32,
+        R_386_TLS_IE_32,
+        R_386_TLS_LE_32,
+        R_386_TLS_DTPMOD32,
+        R_386_TLS_DTPOFF32,
+        R_386_TLS_TPOFF32,
+        R_386_SIZE32,
+        R_386_TLS_GOTDESC,
+        R_386_TLS_DESC_CALL,
+        R_386_TLS_DESC,
+        R_386_IRELATIVE,
+        R_386_GOT32X
     ] {
         Cow::Borrowed(name)
     } else {
-        Cow::Owned(format!("Unknown x86_64 relocation type 0x{r_type:x}"))
+        Cow::Owned(format!("Unknown i386 relocation type 0x{r_type:x}"))
     }
 }
 
@@ -208,6 +272,183 @@ pub fn aarch64_rel_type_to_string(r_type: u32) -> Cow<'static, str> {
     }
 }
 
+#[must_use]
+pub fn riscv64_rel_type_to_string(r_type: u32) -> Cow<'static, str> {
+    if let Some(name) = const_name_by_value![
+        r_type,
+        R_RISCV_NONE,
+        R_RISCV_32,
+        R_RISCV_64,
+        R_RISCV_RELATIVE,
+        R_RISCV_COPY,
+        R_RISCV_JUMP_SLOT,
+        R_RISCV_TLS_DTPMOD32,
+        R_RISCV_TLS_DTPMOD64,
+        R_RISCV_TLS_DTPREL32,
+        R_RISCV_TLS_DTPREL64,
+        R_RISCV_TLS_TPREL32,
+        R_RISCV_TLS_TPREL64,
+        R_RISCV_BRANCH,
+        R_RISCV_JAL,
+        R_RISCV_CALL,
+        R_RISCV_CALL_PLT,
+        R_RISCV_GOT_HI20,
+        R_RISCV_TLS_GOT_HI20,
+        R_RISCV_TLS_GD_HI20,
+        R_RISCV_PCREL_HI20,
+        R_RISCV_PCREL_LO12_I,
+        R_RISCV_PCREL_LO12_S,
+        R_RISCV_HI20,
+        R_RISCV_LO12_I,
+        R_RISCV_LO12_S,
+        R_RISCV_TPREL_HI20,
+        R_RISCV_TPREL_LO12_I,
+        R_RISCV_TPREL_LO12_S,
+        R_RISCV_TPREL_ADD,
+        R_RISCV_ADD8,
+        R_RISCV_ADD16,
+        R_RISCV_ADD32,
+        R_RISCV_ADD64,
+        R_RISCV_SUB8,
+        R_RISCV_SUB16,
+        R_RISCV_SUB32,
+        R_RISCV_SUB64,
+        R_RISCV_ALIGN,
+        R_RISCV_RVC_BRANCH,
+        R_RISCV_RVC_JUMP,
+        R_RISCV_RVC_LUI,
+        R_RISCV_RELAX,
+        R_RISCV_SUB6,
+        R_RISCV_SET6,
+        R_RISCV_SET8,
+        R_RISCV_SET16,
+        R_RISCV_SET32,
+        R_RISCV_32_PCREL,
+        R_RISCV_IRELATIVE,
+        R_RISCV_PLT32,
+        R_RISCV_SET_ULEB128,
+        R_RISCV_SUB_ULEB128,
+        R_RISCV_TLSDESC_HI20,
+        R_RISCV_TLSDESC_LOAD_LO12,
+        R_RISCV_TLSDESC_ADD_LO12,
+        R_RISCV_TLSDESC_CALL
+    ] {
+        Cow::Borrowed(name)
+    } else if r_type == crate::riscv64::R_RISCV_TLSDESC {
+        Cow::Borrowed("R_RISCV_TLSDESC")
+    } else {
+        Cow::Owned(format!("Unknown riscv64 relocation type 0x{r_type:x}"))
+    }
+}
+
+#[must_use]
+pub fn loongarch64_rel_type_to_string(r_type: u32) -> Cow<'static, str> {
+    use crate::loongarch64;
+
+    if let Some(name) = const_name_by_value![
+        r_type,
+        R_LARCH_NONE,
+        R_LARCH_32,
+        R_LARCH_64,
+        R_LARCH_RELATIVE,
+        R_LARCH_COPY,
+        R_LARCH_JUMP_SLOT,
+        R_LARCH_TLS_DTPMOD32,
+        R_LARCH_TLS_DTPMOD64,
+        R_LARCH_TLS_DTPREL32,
+        R_LARCH_TLS_DTPREL64,
+        R_LARCH_TLS_TPREL32,
+        R_LARCH_TLS_TPREL64,
+        R_LARCH_IRELATIVE,
+        R_LARCH_MARK_LA,
+        R_LARCH_MARK_PCREL,
+        R_LARCH_ADD6,
+        R_LARCH_ADD8,
+        R_LARCH_ADD16,
+        R_LARCH_ADD24,
+        R_LARCH_ADD32,
+        R_LARCH_ADD64,
+        R_LARCH_ADD_ULEB128,
+        R_LARCH_SUB6,
+        R_LARCH_SUB8,
+        R_LARCH_SUB16,
+        R_LARCH_SUB24,
+        R_LARCH_SUB32,
+        R_LARCH_SUB64,
+        R_LARCH_SUB_ULEB128,
+        R_LARCH_GNU_VTINHERIT,
+        R_LARCH_GNU_VTENTRY,
+        R_LARCH_B16,
+        R_LARCH_B21,
+        R_LARCH_B26,
+        R_LARCH_ABS_HI20,
+        R_LARCH_ABS_LO12,
+        R_LARCH_ABS64_LO20,
+        R_LARCH_ABS64_HI12,
+        R_LARCH_PCALA_HI20,
+        R_LARCH_PCALA_LO12,
+        R_LARCH_PCALA64_LO20,
+        R_LARCH_PCALA64_HI12,
+        R_LARCH_GOT_PC_HI20,
+        R_LARCH_GOT_PC_LO12,
+        R_LARCH_GOT64_PC_LO20,
+        R_LARCH_GOT64_PC_HI12,
+        R_LARCH_GOT_HI20,
+        R_LARCH_GOT_LO12,
+        R_LARCH_GOT64_LO20,
+        R_LARCH_GOT64_HI12,
+        R_LARCH_TLS_LE_HI20,
+        R_LARCH_TLS_LE_LO12,
+        R_LARCH_TLS_LE64_LO20,
+        R_LARCH_TLS_LE64_HI12,
+        R_LARCH_TLS_IE_PC_HI20,
+        R_LARCH_TLS_IE_PC_LO12,
+        R_LARCH_TLS_IE64_PC_LO20,
+        R_LARCH_TLS_IE64_PC_HI12,
+        R_LARCH_TLS_IE_HI20,
+        R_LARCH_TLS_IE_LO12,
+        R_LARCH_TLS_IE64_LO20,
+        R_LARCH_TLS_IE64_HI12,
+        R_LARCH_TLS_LD_PC_HI20,
+        R_LARCH_TLS_LD_HI20,
+        R_LARCH_TLS_GD_PC_HI20,
+        R_LARCH_TLS_GD_HI20,
+        R_LARCH_32_PCREL,
+        R_LARCH_64_PCREL,
+        R_LARCH_RELAX,
+        R_LARCH_DELETE,
+        R_LARCH_ALIGN,
+        R_LARCH_PCREL20_S2,
+        R_LARCH_CFA,
+        R_LARCH_CALL36
+    ] {
+        return Cow::Borrowed(name);
+    }
+    // Relocations that the `object` crate doesn't define yet.
+    let name = match r_type {
+        loongarch64::R_LARCH_TLS_DESC32 => "R_LARCH_TLS_DESC32",
+        loongarch64::R_LARCH_TLS_DESC64 => "R_LARCH_TLS_DESC64",
+        loongarch64::R_LARCH_TLS_DESC_PC_HI20 => "R_LARCH_TLS_DESC_PC_HI20",
+        loongarch64::R_LARCH_TLS_DESC_PC_LO12 => "R_LARCH_TLS_DESC_PC_LO12",
+        loongarch64::R_LARCH_TLS_DESC64_PC_LO20 => "R_LARCH_TLS_DESC64_PC_LO20",
+        loongarch64::R_LARCH_TLS_DESC64_PC_HI12 => "R_LARCH_TLS_DESC64_PC_HI12",
+        loongarch64::R_LARCH_TLS_DESC_HI20 => "R_LARCH_TLS_DESC_HI20",
+        loongarch64::R_LARCH_TLS_DESC_LO12 => "R_LARCH_TLS_DESC_LO12",
+        loongarch64::R_LARCH_TLS_DESC64_LO20 => "R_LARCH_TLS_DESC64_LO20",
+        loongarch64::R_LARCH_TLS_DESC64_HI12 => "R_LARCH_TLS_DESC64_HI12",
+        loongarch64::R_LARCH_TLS_DESC_LD => "R_LARCH_TLS_DESC_LD",
+        loongarch64::R_LARCH_TLS_DESC_CALL => "R_LARCH_TLS_DESC_CALL",
+        loongarch64::R_LARCH_TLS_LE_HI20_R => "R_LARCH_TLS_LE_HI20_R",
+        loongarch64::R_LARCH_TLS_LE_ADD_R => "R_LARCH_TLS_LE_ADD_R",
+        loongarch64::R_LARCH_TLS_LE_LO12_R => "R_LARCH_TLS_LE_LO12_R",
+        loongarch64::R_LARCH_TLS_LD_PCREL20_S2 => "R_LARCH_TLS_LD_PCREL20_S2",
+        loongarch64::R_LARCH_TLS_GD_PCREL20_S2 => "R_LARCH_TLS_GD_PCREL20_S2",
+        loongarch64::R_LARCH_TLS_DESC_PCREL20_S2 => "R_LARCH_TLS_DESC_PCREL20_S2",
+        _ => return Cow::Owned(format!("Unknown loongarch64 relocation type 0x{r_type:x}")),
+    };
+    Cow::Borrowed(name)
+}
+
 /// Section flag bit values.
 pub mod shf {
     use super::SectionFlags;
@@ -264,6 +505,7 @@ pub mod sht {
     pub const HIPROC: SectionType = SectionType(object::elf::SHT_HIPROC);
     pub const LOUSER: SectionType = SectionType(object::elf::SHT_LOUSER);
     pub const HIUSER: SectionType = SectionType(object::elf::SHT_HIUSER);
+    pub const RISCV_ATTRIBUTES: SectionType = SectionType(object::elf::SHT_RISCV_ATTRIBUTES);
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
@@ -276,8 +518,8 @@ impl SectionFlags {
     }
 
     #[must_use]
-    pub fn from_header(header: &object::elf::SectionHeader64<LittleEndian>) -> Self {
-        Self(header.sh_flags(LittleEndian) as u32)
+    pub fn from_header<E: Endian>(header: &object::elf::SectionHeader64<E>, endian: E) -> Self {
+        Self(header.sh_flags(endian) as u32)
     }
 
     #[must_use]
@@ -380,8 +622,8 @@ impl SectionType {
     }
 
     #[must_use]
-    pub fn from_header(header: &object::elf::SectionHeader64<LittleEndian>) -> Self {
-        Self(header.sh_type(LittleEndian))
+    pub fn from_header<E: Endian>(header: &object::elf::SectionHeader64<E>, endian: E) -> Self {
+        Self(header.sh_type(endian))
     }
 
     #[must_use]
@@ -439,6 +681,10 @@ pub mod secnames {
     pub const DYNSTR_SECTION_NAME: &[u8] = DYNSTR_SECTION_NAME_STR.as_bytes();
     pub const RELA_DYN_SECTION_NAME_STR: &str = ".rela.dyn";
     pub const RELA_DYN_SECTION_NAME: &[u8] = RELA_DYN_SECTION_NAME_STR.as_bytes();
+    pub const REL_PLT_SECTION_NAME_STR: &str = ".rel.plt";
+    pub const REL_PLT_SECTION_NAME: &[u8] = REL_PLT_SECTION_NAME_STR.as_bytes();
+    pub const REL_DYN_SECTION_NAME_STR: &str = ".rel.dyn";
+    pub const REL_DYN_SECTION_NAME: &[u8] = REL_DYN_SECTION_NAME_STR.as_bytes();
     pub const GCC_EXCEPT_TABLE_SECTION_NAME_STR: &str = ".gcc_except_table";
     pub const GCC_EXCEPT_TABLE_SECTION_NAME: &[u8] = GCC_EXCEPT_TABLE_SECTION_NAME_STR.as_bytes();
     pub const INTERP_SECTION_NAME_STR: &str = ".interp";
@@ -469,6 +715,8 @@ pub mod secnames {
     pub const NOTE_GNU_PROPERTY_SECTION_NAME: &[u8] = NOTE_GNU_PROPERTY_SECTION_NAME_STR.as_bytes();
     pub const NOTE_GNU_BUILD_ID_SECTION_NAME_STR: &str = ".note.gnu.build-id";
     pub const NOTE_GNU_BUILD_ID_SECTION_NAME: &[u8] = NOTE_GNU_BUILD_ID_SECTION_NAME_STR.as_bytes();
+    pub const RISCV_ATTRIBUTES_SECTION_NAME_STR: &str = ".riscv.attributes";
+    pub const RISCV_ATTRIBUTES_SECTION_NAME: &[u8] = RISCV_ATTRIBUTES_SECTION_NAME_STR.as_bytes();
     pub const DEBUG_LOC_SECTION_NAME_STR: &str = ".debug.loc";
     pub const DEBUG_LOC_SECTION_NAME: &[u8] = DEBUG_LOC_SECTION_NAME_STR.as_bytes();
     pub const DEBUG_RANGES_SECTION_NAME_STR: &str = ".debug.ranges";
@@ -477,6 +725,52 @@ pub mod secnames {
     pub const GROUP_SECTION_NAME: &[u8] = GROUP_SECTION_NAME_STR.as_bytes();
     pub const DATA_REL_RO_SECTION_NAME_STR: &str = ".data.rel.ro";
     pub const DATA_REL_RO_SECTION_NAME: &[u8] = DATA_REL_RO_SECTION_NAME_STR.as_bytes();
+    pub const GNU_WARNING_SECTION_NAME_STR: &str = ".gnu.warning";
+    pub const GNU_WARNING_SECTION_NAME: &[u8] = GNU_WARNING_SECTION_NAME_STR.as_bytes();
+}
+
+/// If `name` is the name of a `.gnu.warning` section, returns the name of the symbol that the
+/// warning is for, or an empty name if the warning is issued whenever the containing object is
+/// linked.
+#[must_use]
+pub fn gnu_warning_symbol(name: &[u8]) -> Option<&[u8]> {
+    let rest = name.strip_prefix(secnames::GNU_WARNING_SECTION_NAME)?;
+    if rest.is_empty() {
+        return Some(rest);
+    }
+    rest.strip_prefix(b".").filter(|symbol| !symbol.is_empty())
+}
+
+/// Returns the name of the section into which an input section named `name` should be sorted along
+/// with its priority, or None if `name` doesn't have a numeric priority suffix. Lower priorities
+/// come first. Legacy `.ctors.N` and `.dtors.N` sections run in the opposite order, so their
+/// priorities are inverted the same way as GNU ld's `SORT_BY_INIT_PRIORITY`.
+#[must_use]
+pub fn init_priority(name: &[u8]) -> Option<(&'static [u8], u32)> {
+    fn parse(suffix: &[u8]) -> Option<u32> {
+        if suffix.is_empty() || !suffix.iter().all(u8::is_ascii_digit) {
+            return None;
+        }
+        core::str::from_utf8(suffix).ok()?.parse().ok()
+    }
+
+    if let Some(suffix) = name.strip_prefix(b".init_array.") {
+        Some((secnames::INIT_ARRAY_SECTION_NAME, parse(suffix)?))
+    } else if let Some(suffix) = name.strip_prefix(b".fini_array.") {
+        Some((secnames::FINI_ARRAY_SECTION_NAME, parse(suffix)?))
+    } else if let Some(suffix) = name.strip_prefix(b".ctors.") {
+        Some((
+            secnames::INIT_ARRAY_SECTION_NAME,
+            65535u32.checked_sub(parse(suffix)?)?,
+        ))
+    } else if let Some(suffix) = name.strip_prefix(b".dtors.") {
+        Some((
+            secnames::FINI_ARRAY_SECTION_NAME,
+            65535u32.checked_sub(parse(suffix)?)?,
+        ))
+    } else {
+        None
+    }
 }
 
 /// For additional information on ELF relocation types, see "ELF-64 Object File Format" -
@@ -550,7 +844,7 @@ pub enum RelocationKind {
     /// TLS storage, relative to the start of the GOT.
     GotTpOffGotBase,
 
-    /// The offset of a TLS variable within the executable's TLS storage.
+    /// The offset of a TLS variable from the thread pointer.
     TpOff,
 
     /// The offset of a TLS variable within the executable's TLS storage, AArch64 TLS block layout.
@@ -568,6 +862,26 @@ pub enum RelocationKind {
     /// Call to the TLS descriptor trampoline. Used only as a placeholder for a linker relaxation opportunity.
     TlsDescCall,
 
+    /// The offset of a thread-local within the TLS block of the module that defines it, less the
+    /// 0x800 bias that RISC-V applies to such offsets. Unlike `DtpOff`, this never becomes an
+    /// offset from the thread pointer in executables. Also used by LoongArch, which has no bias.
+    DtpOffRiscV,
+
+    /// The absolute address of a symbol or section, written over whatever was there before. Unlike
+    /// `Absolute`, this never produces a dynamic relocation.
+    AbsoluteSet,
+
+    /// The absolute address of a symbol or section, added to the value that's already there.
+    AbsoluteAddition,
+
+    /// The absolute address of a symbol or section, subtracted from the value that's already there.
+    AbsoluteSubtraction,
+
+    /// The low 12 bits of the value computed for the RISC-V `%pcrel_hi` (or similar) relocation at
+    /// the address of the referenced label. The value is relative to the address of that
+    /// relocation, not to the place of this one.
+    PcRelLoRiscV,
+
     /// No relocation needs to be applied. Produced when we eliminate a relocation due to an
     /// optimisation.
     None,
@@ -623,6 +937,41 @@ impl DynamicRelocationKind {
         }
     }
 
+    #[must_use]
+    pub fn from_i386_r_type(r_type: u32) -> Option<Self> {
+        let kind = match r_type {
+            object::elf::R_386_COPY => DynamicRelocationKind::Copy,
+            object::elf::R_386_IRELATIVE => DynamicRelocationKind::Irelative,
+            object::elf::R_386_TLS_DTPMOD32 => DynamicRelocationKind::DtpMod,
+            object::elf::R_386_TLS_DTPOFF32 => DynamicRelocationKind::DtpOff,
+            object::elf::R_386_TLS_TPOFF => DynamicRelocationKind::TpOff,
+            object::elf::R_386_RELATIVE => DynamicRelocationKind::Relative,
+            object::elf::R_386_GLOB_DAT => DynamicRelocationKind::GotEntry,
+            object::elf::R_386_32 => DynamicRelocationKind::Absolute,
+            object::elf::R_386_TLS_DESC => DynamicRelocationKind::TlsDesc,
+            object::elf::R_386_JMP_SLOT => DynamicRelocationKind::JumpSlot,
+            _ => return None,
+        };
+
+        Some(kind)
+    }
+
+    #[must_use]
+    pub fn i386_r_type(self) -> u32 {
+        match self {
+            DynamicRelocationKind::Copy => object::elf::R_386_COPY,
+            DynamicRelocationKind::Irelative => object::elf::R_386_IRELATIVE,
+            DynamicRelocationKind::DtpMod => object::elf::R_386_TLS_DTPMOD32,
+            DynamicRelocationKind::DtpOff => object::elf::R_386_TLS_DTPOFF32,
+            DynamicRelocationKind::TpOff => object::elf::R_386_TLS_TPOFF,
+            DynamicRelocationKind::Relative => object::elf::R_386_RELATIVE,
+            DynamicRelocationKind::Absolute => object::elf::R_386_32,
+            DynamicRelocationKind::GotEntry => object::elf::R_386_GLOB_DAT,
+            DynamicRelocationKind::TlsDesc => object::elf::R_386_TLS_DESC,
+            DynamicRelocationKind::JumpSlot => object::elf::R_386_JMP_SLOT,
+        }
+    }
+
     #[must_use]
     pub fn from_aarch64_r_type(r_type: u32) -> Option<Self> {
         let kind = match r_type {
@@ -657,6 +1006,78 @@ impl DynamicRelocationKind {
             DynamicRelocationKind::JumpSlot => object::elf::R_AARCH64_JUMP_SLOT,
         }
     }
+
+    #[must_use]
+    pub fn from_riscv64_r_type(r_type: u32) -> Option<Self> {
+        let kind = match r_type {
+            object::elf::R_RISCV_COPY => DynamicRelocationKind::Copy,
+            object::elf::R_RISCV_IRELATIVE => DynamicRelocationKind::Irelative,
+            object::elf::R_RISCV_TLS_DTPMOD64 => DynamicRelocationKind::DtpMod,
+            object::elf::R_RISCV_TLS_DTPREL64 => DynamicRelocationKind::DtpOff,
+            object::elf::R_RISCV_TLS_TPREL64 => DynamicRelocationKind::TpOff,
+            object::elf::R_RISCV_RELATIVE => DynamicRelocationKind::Relative,
+            // RISC-V uses R_RISCV_64 for both GOT entries and other absolute addresses.
+            object::elf::R_RISCV_64 => DynamicRelocationKind::Absolute,
+            crate::riscv64::R_RISCV_TLSDESC => DynamicRelocationKind::TlsDesc,
+            object::elf::R_RISCV_JUMP_SLOT => DynamicRelocationKind::JumpSlot,
+            _ => return None,
+        };
+
+        Some(kind)
+    }
+
+    #[must_use]
+    pub fn riscv64_r_type(&self) -> u32 {
+        match self {
+            DynamicRelocationKind::Copy => object::elf::R_RISCV_COPY,
+            DynamicRelocationKind::Irelative => object::elf::R_RISCV_IRELATIVE,
+            DynamicRelocationKind::DtpMod => object::elf::R_RISCV_TLS_DTPMOD64,
+            DynamicRelocationKind::DtpOff => object::elf::R_RISCV_TLS_DTPREL64,
+            DynamicRelocationKind::TpOff => object::elf::R_RISCV_TLS_TPREL64,
+            DynamicRelocationKind::Relative => object::elf::R_RISCV_RELATIVE,
+            DynamicRelocationKind::Absolute | DynamicRelocationKind::GotEntry => {
+                object::elf::R_RISCV_64
+            }
+            DynamicRelocationKind::TlsDesc => crate::riscv64::R_RISCV_TLSDESC,
+            DynamicRelocationKind::JumpSlot => object::elf::R_RISCV_JUMP_SLOT,
+        }
+    }
+
+    #[must_use]
+    pub fn from_loongarch64_r_type(r_type: u32) -> Option<Self> {
+        let kind = match r_type {
+            object::elf::R_LARCH_COPY => DynamicRelocationKind::Copy,
+            object::elf::R_LARCH_IRELATIVE => DynamicRelocationKind::Irelative,
+            object::elf::R_LARCH_TLS_DTPMOD64 => DynamicRelocationKind::DtpMod,
+            object::elf::R_LARCH_TLS_DTPREL64 => DynamicRelocationKind::DtpOff,
+            object::elf::R_LARCH_TLS_TPREL64 => DynamicRelocationKind::TpOff,
+            object::elf::R_LARCH_RELATIVE => DynamicRelocationKind::Relative,
+            // LoongArch uses R_LARCH_64 for both GOT entries and other absolute addresses.
+            object::elf::R_LARCH_64 => DynamicRelocationKind::Absolute,
+            crate::loongarch64::R_LARCH_TLS_DESC64 => DynamicRelocationKind::TlsDesc,
+            object::elf::R_LARCH_JUMP_SLOT => DynamicRelocationKind::JumpSlot,
+            _ => return None,
+        };
+
+        Some(kind)
+    }
+
+    #[must_use]
+    pub fn loongarch64_r_type(&self) -> u32 {
+        match self {
+            DynamicRelocationKind::Copy => object::elf::R_LARCH_COPY,
+            DynamicRelocationKind::Irelative => object::elf::R_LARCH_IRELATIVE,
+            DynamicRelocationKind::DtpMod => object::elf::R_LARCH_TLS_DTPMOD64,
+            DynamicRelocationKind::DtpOff => object::elf::R_LARCH_TLS_DTPREL64,
+            DynamicRelocationKind::TpOff => object::elf::R_LARCH_TLS_TPREL64,
+            DynamicRelocationKind::Relative => object::elf::R_LARCH_RELATIVE,
+            DynamicRelocationKind::Absolute | DynamicRelocationKind::GotEntry => {
+                object::elf::R_LARCH_64
+            }
+            DynamicRelocationKind::TlsDesc => crate::loongarch64::R_LARCH_TLS_DESC64,
+            DynamicRelocationKind::JumpSlot => object::elf::R_LARCH_JUMP_SLOT,
+        }
+    }
 }
 
 // Half-opened range bounded inclusively below and exclusively above: [`start`, `end`)
@@ -678,17 +1099,37 @@ pub enum RelocationInstruction {
     TstBr,
     Bcond,
     JumpCall,
+    RiscV(crate::riscv64::RiscVInstruction),
+    LoongArch(crate::loongarch64::LoongArchInstruction),
 }
 
 impl RelocationInstruction {
+    /// Returns the number of bytes that the instruction occupies.
     #[must_use]
-    pub fn bit_mask(&self, range: BitRange) -> [u8; 4] {
-        let mut mask = [0; 4];
+    pub fn num_bytes(&self) -> usize {
+        match self {
+            RelocationInstruction::RiscV(instruction) => instruction.num_bytes(),
+            RelocationInstruction::LoongArch(instruction) => instruction.num_bytes(),
+            _ => 4,
+        }
+    }
+
+    #[must_use]
+    pub fn bit_mask(&self, range: BitRange) -> Vec<u8> {
+        let mut mask = vec![0; self.num_bytes()];
 
         // To figure out which bits are part of the relocation, we write a value with
         // all ones into a buffer that initially contains zeros.
-        let all_ones = (1 << (range.end - range.start)) - 1;
-        self.write_to_value(all_ones, false, &mut mask);
+        let all_ones = u64::MAX >> (64 - (range.end - range.start));
+        match self {
+            RelocationInstruction::RiscV(instruction) => {
+                instruction.write_mask_value(all_ones, &mut mask);
+            }
+            RelocationInstruction::LoongArch(instruction) => {
+                instruction.write_mask_value(all_ones, &mut mask);
+            }
+            _ => self.write_to_value(all_ones, false, &mut mask),
+        }
 
         // Wherever we get a 1 is part of the relocation, so invert all bits.
         for b in &mut mask {
@@ -726,6 +1167,9 @@ pub enum PageMask {
     SymbolPlusAddendAndPosition,
     GotEntryAndPosition,
     GotBase,
+    /// Only the place is reduced to the start of its page. Used when the instruction rounds the
+    /// value itself.
+    Position,
 }
 
 // Allow range (half-open) of a computed value of a relocation
@@ -792,7 +1236,7 @@ impl BitMask {
 #[must_use]
 pub fn extract_bits(value: u64, start: u32, end: u32) -> u64 {
     debug_assert!(start < end);
-    (value >> (start)) & ((1 << (end - start)) - 1)
+    (value >> (start)) & (u64::MAX >> (64 - (end - start)))
 }
 
 #[cfg(test)]
@@ -810,15 +1254,84 @@ mod tests {
             &x86_64_rel_type_to_string(R_X86_64_GOTPC32_TLSDESC),
             stringify!(R_X86_64_GOTPC32_TLSDESC)
         );
+        assert_eq!(
+            &x86_64_rel_type_to_string(crate::x86_64::R_X86_64_CODE_4_GOTTPOFF),
+            "R_X86_64_CODE_4_GOTTPOFF"
+        );
         assert_eq!(
             &x86_64_rel_type_to_string(64),
             "Unknown x86_64 relocation type 0x40"
         );
 
+        assert_eq!(
+            &i386_rel_type_to_string(R_386_TLS_GOTDESC),
+            stringify!(R_386_TLS_GOTDESC)
+        );
+        assert_eq!(
+            &i386_rel_type_to_string(64),
+            "Unknown i386 relocation type 0x40"
+        );
+
         assert_eq!(
             &aarch64_rel_type_to_string(64),
             "Unknown aarch64 relocation type 0x40"
         );
+
+        assert_eq!(
+            &riscv64_rel_type_to_string(R_RISCV_CALL_PLT),
+            stringify!(R_RISCV_CALL_PLT)
+        );
+        assert_eq!(
+            &riscv64_rel_type_to_string(crate::riscv64::R_RISCV_TLSDESC),
+            "R_RISCV_TLSDESC"
+        );
+
+        assert_eq!(
+            &loongarch64_rel_type_to_string(R_LARCH_CALL36),
+            stringify!(R_LARCH_CALL36)
+        );
+        assert_eq!(
+            &loongarch64_rel_type_to_string(crate::loongarch64::R_LARCH_TLS_DESC_CALL),
+            "R_LARCH_TLS_DESC_CALL"
+        );
+        assert_eq!(
+            &loongarch64_rel_type_to_string(200),
+            "Unknown loongarch64 relocation type 0xc8"
+        );
+    }
+
+    #[test]
+    fn test_init_priority() {
+        use secnames::*;
+        assert_eq!(
+            init_priority(b".init_array.00099"),
+            Some((INIT_ARRAY_SECTION_NAME, 99))
+        );
+        assert_eq!(
+            init_priority(b".fini_array.101"),
+            Some((FINI_ARRAY_SECTION_NAME, 101))
+        );
+        assert_eq!(
+            init_priority(b".ctors.65435"),
+            Some((INIT_ARRAY_SECTION_NAME, 100))
+        );
+        assert_eq!(
+            init_priority(b".dtors.00000"),
+            Some((FINI_ARRAY_SECTION_NAME, 65535))
+        );
+        assert_eq!(init_priority(b".init_array"), None);
+        assert_eq!(init_priority(b".init_array."), None);
+        assert_eq!(init_priority(b".init_array.foo"), None);
+        assert_eq!(init_priority(b".ctors.70000"), None);
+    }
+
+    #[test]
+    fn test_gnu_warning_symbol() {
+        assert_eq!(gnu_warning_symbol(b".gnu.warning"), Some(&b""[..]));
+        assert_eq!(gnu_warning_symbol(b".gnu.warning.gets"), Some(&b"gets"[..]));
+        assert_eq!(gnu_warning_symbol(b".gnu.warning."), None);
+        assert_eq!(gnu_warning_symbol(b".gnu.warnings"), None);
+        assert_eq!(gnu_warning_symbol(b".gnu.version"), None);
     }
 
     #[test]
@@ -826,6 +1339,7 @@ mod tests {
         assert_eq!(0b11000, extract_bits(0b1100_0000, 3, 8));
         assert_eq!(0b1010_1010_0000, extract_bits(0b10101010_00001111, 4, 16));
         assert_eq!(u32::MAX, extract_bits(u64::MAX, 0, 32) as u32);
+        assert_eq!(u64::MAX, extract_bits(u64::MAX, 0, 64));
     }
 
     #[test]
@@ -0,0 +1,211 @@
+use crate::elf::RelocationKind;
+use crate::relaxation::RelocationModifier;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelaxationKind {
+    /// Transforms a mov instruction that would have loaded an address to not use the GOT. The
+    /// transformation will look like `mov x@GOT(%reg1), %reg2` -> `lea x@GOTOFF(%reg1), %reg2`.
+    MovIndirectToLea,
+
+    /// Transforms a mov instruction that would have loaded an absolute value to not use the GOT.
+    /// The transformation will look like `mov x@GOT(%reg1), %reg2` -> `mov $x, %reg2`.
+    MovIndirectToAbsolute,
+
+    /// Transforms an add of a value loaded from the GOT into an add of an immediate. The
+    /// transformation will look like `add x@GOT(%reg1), %reg2` -> `add $x, %reg2`.
+    AddIndirectToAbsolute,
+
+    /// Transforms the short form of a mov from an absolute address into %eax into a mov of an
+    /// immediate. The transformation will look like `mov x, %eax` -> `mov $x, %eax`.
+    MovEaxIndirectToAbsolute,
+
+    /// Transform a call instruction like `call *x@GOT(%reg)` -> `addr32 call x`.
+    CallIndirectToRelative,
+
+    /// Transform a jump instruction like `jmp *x@GOT(%reg)` -> `jmp x; nop`.
+    JmpIndirectToRelative,
+
+    /// Leave the instruction alone. Used when we only want to change the kind of relocation used.
+    NoOp,
+
+    /// Transform general dynamic (GD) into local exec.
+    TlsGdToLocalExec,
+
+    /// Transform general dynamic (GD) into initial exec. The low 3 bits hold the register that
+    /// the original code used to address the GOT.
+    TlsGdToInitialExec(u8),
+
+    /// Transform local dynamic (LD) into local exec.
+    TlsLdToLocalExec,
+
+    /// Transform TLSDESC to local exec.
+    TlsDescToLocalExec,
+
+    /// Transform TLSDESC to initial exec.
+    TlsDescToInitialExec,
+
+    /// Convert a TLS_DESC_CALL to a no-op.
+    SkipTlsDescCall,
+}
+
+impl RelaxationKind {
+    pub fn apply(self, section_bytes: &mut [u8], offset_in_section: &mut u64, addend: &mut i64) {
+        let offset = *offset_in_section as usize;
+        match self {
+            RelaxationKind::MovIndirectToLea => {
+                section_bytes[offset - 2] = 0x8d;
+            }
+            RelaxationKind::MovIndirectToAbsolute => {
+                section_bytes[offset - 2] = 0xc7;
+                let mod_rm = &mut section_bytes[offset - 1];
+                *mod_rm = (*mod_rm >> 3) & 0x7 | 0xc0;
+                *addend = 0;
+            }
+            RelaxationKind::AddIndirectToAbsolute => {
+                section_bytes[offset - 2] = 0x81;
+                let mod_rm = &mut section_bytes[offset - 1];
+                *mod_rm = (*mod_rm >> 3) & 0x7 | 0xc0;
+                *addend = 0;
+            }
+            RelaxationKind::MovEaxIndirectToAbsolute => {
+                section_bytes[offset - 1] = 0xb8;
+                *addend = 0;
+            }
+            RelaxationKind::CallIndirectToRelative => {
+                section_bytes[offset - 2..offset].copy_from_slice(&[0x67, 0xe8]);
+                // The call is now relative to the end of the instruction rather than being an
+                // offset from the GOT.
+                *addend = -4;
+            }
+            RelaxationKind::JmpIndirectToRelative => {
+                section_bytes[offset - 2..offset + 4].copy_from_slice(&[0xe9, 0, 0, 0, 0, 0x90]);
+                *offset_in_section -= 1; // Instruction is 1 byte shorter
+                *addend = -4;
+            }
+            RelaxationKind::TlsGdToLocalExec => {
+                let start = tls_gd_start(section_bytes, offset);
+                section_bytes[start..start + 12].copy_from_slice(&[
+                    0x65, 0xa1, 0, 0, 0, 0, // mov %gs:0,%eax
+                    0x8d, 0x80, 0, 0, 0, 0, // lea {offset}(%eax),%eax
+                ]);
+                *offset_in_section = start as u64 + 8;
+                *addend = 0;
+            }
+            RelaxationKind::TlsGdToInitialExec(got_register) => {
+                let start = tls_gd_start(section_bytes, offset);
+                section_bytes[start..start + 12].copy_from_slice(&[
+                    0x65,
+                    0xa1,
+                    0,
+                    0,
+                    0,
+                    0, // mov %gs:0,%eax
+                    0x03,
+                    0x80 | got_register,
+                    0,
+                    0,
+                    0,
+                    0, // add {GOT offset}(%reg),%eax
+                ]);
+                *offset_in_section = start as u64 + 8;
+                *addend = 0;
+            }
+            RelaxationKind::TlsLdToLocalExec => {
+                if section_bytes[offset + 4] == 0xe8 {
+                    // The call was `call ___tls_get_addr@PLT`.
+                    section_bytes[offset - 2..offset + 9].copy_from_slice(&[
+                        0x65, 0xa1, 0, 0, 0, 0,    // mov %gs:0,%eax
+                        0x90, // nop
+                        0x8d, 0x74, 0x26, 0, // lea 0(%esi,%eiz,1),%esi
+                    ]);
+                } else {
+                    // The call was `call *___tls_get_addr@GOT(%reg)`, which is one byte longer.
+                    section_bytes[offset - 2..offset + 10].copy_from_slice(&[
+                        0x65, 0xa1, 0, 0, 0, 0, // mov %gs:0,%eax
+                        0x8d, 0xb6, 0, 0, 0, 0, // lea 0(%esi),%esi
+                    ]);
+                }
+            }
+            RelaxationKind::TlsDescToLocalExec => {
+                // lea {offset},%eax
+                section_bytes[offset - 1] = 0x05;
+                *addend = 0;
+            }
+            RelaxationKind::TlsDescToInitialExec => {
+                // mov {GOT offset}(%reg),%eax
+                section_bytes[offset - 2] = 0x8b;
+                *addend = 0;
+            }
+            RelaxationKind::SkipTlsDescCall => {
+                section_bytes[offset..offset + 2].copy_from_slice(&[
+                    // xchg %ax,%ax
+                    0x66, 0x90,
+                ]);
+            }
+            RelaxationKind::NoOp => {}
+        }
+    }
+
+    #[must_use]
+    pub fn next_modifier(&self) -> RelocationModifier {
+        match self {
+            RelaxationKind::TlsGdToInitialExec(_)
+            | RelaxationKind::TlsGdToLocalExec
+            | RelaxationKind::TlsLdToLocalExec => RelocationModifier::SkipNextRelocation,
+            RelaxationKind::MovIndirectToLea
+            | RelaxationKind::MovIndirectToAbsolute
+            | RelaxationKind::AddIndirectToAbsolute
+            | RelaxationKind::MovEaxIndirectToAbsolute
+            | RelaxationKind::CallIndirectToRelative
+            | RelaxationKind::JmpIndirectToRelative
+            | RelaxationKind::NoOp
+            | RelaxationKind::TlsDescToLocalExec
+            | RelaxationKind::TlsDescToInitialExec
+            | RelaxationKind::SkipTlsDescCall => RelocationModifier::Normal,
+        }
+    }
+}
+
+/// Returns the offset of the start of a general dynamic TLS sequence with a relocation at `offset`.
+/// The sequence is either `lea x@tlsgd(,%ebx,1),%eax; call ___tls_get_addr@PLT` or
+/// `lea x@tlsgd(%reg),%eax; call *___tls_get_addr@GOT(%reg)`. Both are 12 bytes.
+fn tls_gd_start(section_bytes: &[u8], offset: usize) -> usize {
+    if section_bytes[offset - 2] == 0x04 {
+        offset - 3
+    } else {
+        offset - 2
+    }
+}
+
+/// Returns the supplied i386 relocation type split into a relocation kind and a size (in bytes)
+/// for the relocation. Returns `None` if the r_type isn't recognised.
+#[must_use]
+pub fn relocation_kind_and_size(r_type: u32) -> Option<(RelocationKind, usize)> {
+    let (kind, size) = match r_type {
+        object::elf::R_386_32 => (RelocationKind::Absolute, 4),
+        object::elf::R_386_PC32 => (RelocationKind::Relative, 4),
+        object::elf::R_386_16 => (RelocationKind::Absolute, 2),
+        object::elf::R_386_PC16 => (RelocationKind::Relative, 2),
+        object::elf::R_386_8 => (RelocationKind::Absolute, 1),
+        object::elf::R_386_PC8 => (RelocationKind::Relative, 1),
+        object::elf::R_386_GOT32 | object::elf::R_386_GOT32X => (RelocationKind::GotRelGotBase, 4),
+        object::elf::R_386_PLT32 => (RelocationKind::PltRelative, 4),
+        object::elf::R_386_GOTOFF => (RelocationKind::SymRelGotBase, 4),
+
+        // Like x86-64's GOTPC32, we rely on GOTPC always referencing the symbol
+        // _GLOBAL_OFFSET_TABLE_, so we can treat it as a normal relative relocation.
+        object::elf::R_386_GOTPC => (RelocationKind::Relative, 4),
+
+        object::elf::R_386_TLS_GD => (RelocationKind::TlsGdGotBase, 4),
+        object::elf::R_386_TLS_LDM => (RelocationKind::TlsLdGotBase, 4),
+        object::elf::R_386_TLS_LDO_32 => (RelocationKind::DtpOff, 4),
+        object::elf::R_386_TLS_IE => (RelocationKind::GotTpOffGot, 4),
+        object::elf::R_386_TLS_GOTIE => (RelocationKind::GotTpOffGotBase, 4),
+        object::elf::R_386_TLS_LE => (RelocationKind::TpOff, 4),
+        object::elf::R_386_TLS_GOTDESC => (RelocationKind::TlsDescGotBase, 4),
+        object::elf::R_386_TLS_DESC_CALL => (RelocationKind::TlsDescCall, 0),
+        object::elf::R_386_NONE => (RelocationKind::None, 0),
+        _ => return None,
+    };
+    Some((kind, size))
+}
@@ -0,0 +1,889 @@
+use crate::elf::AllowedRange;
+use crate::elf::PageMask;
+use crate::elf::RelocationInstruction;
+use crate::elf::RelocationKind;
+use crate::elf::RelocationKindInfo;
+use crate::elf::RelocationSize;
+use crate::relaxation::RelocationModifier;
+use crate::riscv64::RiscVInstruction;
+
+// TLS descriptor relocations. The `object` crate doesn't define these yet.
+pub const R_LARCH_TLS_DESC32: u32 = 13;
+pub const R_LARCH_TLS_DESC64: u32 = 14;
+pub const R_LARCH_TLS_DESC_PC_HI20: u32 = 111;
+pub const R_LARCH_TLS_DESC_PC_LO12: u32 = 112;
+pub const R_LARCH_TLS_DESC64_PC_LO20: u32 = 113;
+pub const R_LARCH_TLS_DESC64_PC_HI12: u32 = 114;
+pub const R_LARCH_TLS_DESC_HI20: u32 = 115;
+pub const R_LARCH_TLS_DESC_LO12: u32 = 116;
+pub const R_LARCH_TLS_DESC64_LO20: u32 = 117;
+pub const R_LARCH_TLS_DESC64_HI12: u32 = 118;
+pub const R_LARCH_TLS_DESC_LD: u32 = 119;
+pub const R_LARCH_TLS_DESC_CALL: u32 = 120;
+
+// Relocations for the local-exec sequences that the linker can shorten.
+pub const R_LARCH_TLS_LE_HI20_R: u32 = 121;
+pub const R_LARCH_TLS_LE_ADD_R: u32 = 122;
+pub const R_LARCH_TLS_LE_LO12_R: u32 = 123;
+
+// Relocations for `pcaddi`, which can replace a `pcalau12i` + `addi.d` pair.
+pub const R_LARCH_TLS_LD_PCREL20_S2: u32 = 124;
+pub const R_LARCH_TLS_GD_PCREL20_S2: u32 = 125;
+pub const R_LARCH_TLS_DESC_PCREL20_S2: u32 = 126;
+
+/// `andi $zero, $zero, 0`
+pub const NOP: u32 = 0x0340_0000;
+
+/// The range of values that can be split into a `%hi20` part for a `pcalau12i` or `lu12i.w` and a
+/// `%lo12` part for a following instruction with a sign-extended 12 bit immediate.
+const HI20_RANGE: AllowedRange = AllowedRange::new(-(1 << 31) - 0x800, (1 << 31) - 0x800);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelaxationKind {
+    /// Leave the instruction alone. Used when we only want to change the kind of relocation used.
+    NoOp,
+
+    /// Replace with nop
+    ReplaceWithNop,
+
+    /// Replace with `lu12i.w $a0, %le_hi20(sym)`. Used for the `ld.d` of a TLSDESC sequence.
+    TlsDescToLocalExecLu12i,
+
+    /// Replace with `ori $a0, $a0, %le_lo12(sym)`. Used for the `jirl` of a TLSDESC sequence.
+    TlsDescToLocalExecOri,
+}
+
+impl RelaxationKind {
+    pub fn apply(self, section_bytes: &mut [u8], offset_in_section: &mut u64, _addend: &mut i64) {
+        let offset = *offset_in_section as usize;
+        let instruction = match self {
+            RelaxationKind::NoOp => return,
+            RelaxationKind::ReplaceWithNop => NOP,
+            // lu12i.w $a0, 0
+            RelaxationKind::TlsDescToLocalExecLu12i => 0x1400_0004,
+            // ori $a0, $a0, 0
+            RelaxationKind::TlsDescToLocalExecOri => 0x0380_0084,
+        };
+        section_bytes[offset..offset + 4].copy_from_slice(&instruction.to_le_bytes());
+    }
+
+    #[must_use]
+    pub fn next_modifier(&self) -> RelocationModifier {
+        RelocationModifier::Normal
+    }
+}
+
+/// The ways in which LoongArch relocations encode their values. Instructions are described in the
+/// "LoongArch Reference Manual, Volume 1: Basic Architecture".
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
+pub enum LoongArchInstruction {
+    /// A 20 bit immediate in bits 5..25, as used by `lu12i.w`, `lu32i.d`, `pcaddi` and
+    /// `pcalau12i`.
+    Si20,
+
+    /// A 12 bit immediate in bits 10..22, as used by loads, stores, `addi.d`, `ori` and `lu52i.d`.
+    Si12,
+
+    /// The upper 20 bits of a 32 bit value, rounded so that adding the sign-extended lower 12
+    /// bits gives the full value. Written to the same field as `Si20`.
+    Hi20,
+
+    /// A 16 bit branch offset in units of 4 bytes, as used by `beq` and `jirl`.
+    Offs16,
+
+    /// A 21 bit branch offset in units of 4 bytes, as used by `beqz` and `bnez`.
+    Offs21,
+
+    /// A 26 bit branch offset in units of 4 bytes, as used by `b` and `bl`.
+    Offs26,
+
+    /// A `pcaddu18i` followed by a `jirl`, which together hold a 38 bit offset.
+    Call36,
+
+    /// The low 6 bits of a byte.
+    Set6,
+
+    /// A ULEB128 value, which we write using the same number of bytes as are already there.
+    Uleb128,
+}
+
+#[must_use]
+pub const fn relocation_type_from_raw(r_type: u32) -> Option<RelocationKindInfo> {
+    let mut mask = None;
+    let (kind, size, range, alignment) = match r_type {
+        object::elf::R_LARCH_NONE
+        | object::elf::R_LARCH_MARK_LA
+        | object::elf::R_LARCH_MARK_PCREL
+        | object::elf::R_LARCH_GNU_VTINHERIT
+        | object::elf::R_LARCH_GNU_VTENTRY
+        | object::elf::R_LARCH_RELAX
+        | object::elf::R_LARCH_ALIGN
+        | R_LARCH_TLS_LE_ADD_R => (
+            RelocationKind::None,
+            RelocationSize::ByteSize(0),
+            AllowedRange::no_check(),
+            1,
+        ),
+
+        // Data relocations
+        object::elf::R_LARCH_32 => (
+            RelocationKind::Absolute,
+            RelocationSize::ByteSize(4),
+            AllowedRange::new(-(2i64.pow(31)), 2i64.pow(32)),
+            1,
+        ),
+        object::elf::R_LARCH_64 => (
+            RelocationKind::Absolute,
+            RelocationSize::ByteSize(8),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_LARCH_32_PCREL => (
+            RelocationKind::Relative,
+            RelocationSize::ByteSize(4),
+            AllowedRange::new(-(2i64.pow(31)), 2i64.pow(31)),
+            1,
+        ),
+        object::elf::R_LARCH_64_PCREL => (
+            RelocationKind::Relative,
+            RelocationSize::ByteSize(8),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_LARCH_TLS_DTPREL32 => (
+            RelocationKind::DtpOffRiscV,
+            RelocationSize::ByteSize(4),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_LARCH_TLS_DTPREL64 => (
+            RelocationKind::DtpOffRiscV,
+            RelocationSize::ByteSize(8),
+            AllowedRange::no_check(),
+            1,
+        ),
+
+        // Relocations that adjust a value in place. These are mostly used for the differences
+        // between labels in sections that linker relaxation might shrink.
+        object::elf::R_LARCH_ADD6 => (
+            RelocationKind::AbsoluteAddition,
+            RelocationSize::bit_mask(
+                0,
+                6,
+                RelocationInstruction::LoongArch(LoongArchInstruction::Set6),
+            ),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_LARCH_ADD8 => (
+            RelocationKind::AbsoluteAddition,
+            RelocationSize::ByteSize(1),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_LARCH_ADD16 => (
+            RelocationKind::AbsoluteAddition,
+            RelocationSize::ByteSize(2),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_LARCH_ADD24 => (
+            RelocationKind::AbsoluteAddition,
+            RelocationSize::ByteSize(3),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_LARCH_ADD32 => (
+            RelocationKind::AbsoluteAddition,
+            RelocationSize::ByteSize(4),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_LARCH_ADD64 => (
+            RelocationKind::AbsoluteAddition,
+            RelocationSize::ByteSize(8),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_LARCH_ADD_ULEB128 => (
+            RelocationKind::AbsoluteAddition,
+            RelocationSize::bit_mask(
+                0,
+                64,
+                RelocationInstruction::LoongArch(LoongArchInstruction::Uleb128),
+            ),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_LARCH_SUB6 => (
+            RelocationKind::AbsoluteSubtraction,
+            RelocationSize::bit_mask(
+                0,
+                6,
+                RelocationInstruction::LoongArch(LoongArchInstruction::Set6),
+            ),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_LARCH_SUB8 => (
+            RelocationKind::AbsoluteSubtraction,
+            RelocationSize::ByteSize(1),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_LARCH_SUB16 => (
+            RelocationKind::AbsoluteSubtraction,
+            RelocationSize::ByteSize(2),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_LARCH_SUB24 => (
+            RelocationKind::AbsoluteSubtraction,
+            RelocationSize::ByteSize(3),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_LARCH_SUB32 => (
+            RelocationKind::AbsoluteSubtraction,
+            RelocationSize::ByteSize(4),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_LARCH_SUB64 => (
+            RelocationKind::AbsoluteSubtraction,
+            RelocationSize::ByteSize(8),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_LARCH_SUB_ULEB128 => (
+            RelocationKind::AbsoluteSubtraction,
+            RelocationSize::bit_mask(
+                0,
+                64,
+                RelocationInstruction::LoongArch(LoongArchInstruction::Uleb128),
+            ),
+            AllowedRange::no_check(),
+            1,
+        ),
+
+        // Control transfer
+        object::elf::R_LARCH_B16 => (
+            RelocationKind::Relative,
+            RelocationSize::bit_mask(
+                0,
+                18,
+                RelocationInstruction::LoongArch(LoongArchInstruction::Offs16),
+            ),
+            AllowedRange::new(-(2i64.pow(17)), 2i64.pow(17)),
+            4,
+        ),
+        object::elf::R_LARCH_B21 => (
+            RelocationKind::Relative,
+            RelocationSize::bit_mask(
+                0,
+                23,
+                RelocationInstruction::LoongArch(LoongArchInstruction::Offs21),
+            ),
+            AllowedRange::new(-(2i64.pow(22)), 2i64.pow(22)),
+            4,
+        ),
+        object::elf::R_LARCH_B26 => (
+            RelocationKind::PltRelative,
+            RelocationSize::bit_mask(
+                0,
+                28,
+                RelocationInstruction::LoongArch(LoongArchInstruction::Offs26),
+            ),
+            AllowedRange::new(-(2i64.pow(27)), 2i64.pow(27)),
+            4,
+        ),
+        object::elf::R_LARCH_CALL36 => (
+            RelocationKind::PltRelative,
+            RelocationSize::bit_mask(
+                0,
+                38,
+                RelocationInstruction::LoongArch(LoongArchInstruction::Call36),
+            ),
+            AllowedRange::new(-(2i64.pow(37)) - 0x20000, 2i64.pow(37) - 0x20000),
+            4,
+        ),
+
+        // PC-relative addressing. The high part is relative to the 4KiB page containing the
+        // `pcalau12i`, while the low part is the offset of the target within its page.
+        object::elf::R_LARCH_PCALA_HI20 => {
+            mask = Some(PageMask::Position);
+            (
+                RelocationKind::Relative,
+                RelocationSize::bit_mask(
+                    0,
+                    32,
+                    RelocationInstruction::LoongArch(LoongArchInstruction::Hi20),
+                ),
+                HI20_RANGE,
+                1,
+            )
+        }
+        object::elf::R_LARCH_PCALA_LO12 => (
+            RelocationKind::AbsoluteAArch64,
+            RelocationSize::bit_mask(
+                0,
+                12,
+                RelocationInstruction::LoongArch(LoongArchInstruction::Si12),
+            ),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_LARCH_PCREL20_S2 => (
+            RelocationKind::Relative,
+            RelocationSize::bit_mask(
+                2,
+                22,
+                RelocationInstruction::LoongArch(LoongArchInstruction::Si20),
+            ),
+            AllowedRange::new(-(2i64.pow(21)), 2i64.pow(21)),
+            4,
+        ),
+        object::elf::R_LARCH_GOT_PC_HI20 => {
+            mask = Some(PageMask::Position);
+            (
+                RelocationKind::GotRelative,
+                RelocationSize::bit_mask(
+                    0,
+                    32,
+                    RelocationInstruction::LoongArch(LoongArchInstruction::Hi20),
+                ),
+                HI20_RANGE,
+                1,
+            )
+        }
+        object::elf::R_LARCH_GOT_PC_LO12 => (
+            RelocationKind::Got,
+            RelocationSize::bit_mask(
+                0,
+                12,
+                RelocationInstruction::LoongArch(LoongArchInstruction::Si12),
+            ),
+            AllowedRange::no_check(),
+            1,
+        ),
+
+        // Absolute addressing
+        object::elf::R_LARCH_ABS_HI20 => (
+            RelocationKind::AbsoluteSet,
+            RelocationSize::bit_mask(
+                12,
+                32,
+                RelocationInstruction::LoongArch(LoongArchInstruction::Si20),
+            ),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_LARCH_ABS_LO12 => (
+            RelocationKind::AbsoluteSet,
+            RelocationSize::bit_mask(
+                0,
+                12,
+                RelocationInstruction::LoongArch(LoongArchInstruction::Si12),
+            ),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_LARCH_ABS64_LO20 => (
+            RelocationKind::AbsoluteSet,
+            RelocationSize::bit_mask(
+                32,
+                52,
+                RelocationInstruction::LoongArch(LoongArchInstruction::Si20),
+            ),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_LARCH_ABS64_HI12 => (
+            RelocationKind::AbsoluteSet,
+            RelocationSize::bit_mask(
+                52,
+                64,
+                RelocationInstruction::LoongArch(LoongArchInstruction::Si12),
+            ),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_LARCH_GOT_HI20 => (
+            RelocationKind::Got,
+            RelocationSize::bit_mask(
+                12,
+                32,
+                RelocationInstruction::LoongArch(LoongArchInstruction::Si20),
+            ),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_LARCH_GOT_LO12 => (
+            RelocationKind::Got,
+            RelocationSize::bit_mask(
+                0,
+                12,
+                RelocationInstruction::LoongArch(LoongArchInstruction::Si12),
+            ),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_LARCH_GOT64_LO20 => (
+            RelocationKind::Got,
+            RelocationSize::bit_mask(
+                32,
+                52,
+                RelocationInstruction::LoongArch(LoongArchInstruction::Si20),
+            ),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_LARCH_GOT64_HI12 => (
+            RelocationKind::Got,
+            RelocationSize::bit_mask(
+                52,
+                64,
+                RelocationInstruction::LoongArch(LoongArchInstruction::Si12),
+            ),
+            AllowedRange::no_check(),
+            1,
+        ),
+
+        // Thread-local storage: local exec
+        object::elf::R_LARCH_TLS_LE_HI20 => (
+            RelocationKind::TpOff,
+            RelocationSize::bit_mask(
+                12,
+                32,
+                RelocationInstruction::LoongArch(LoongArchInstruction::Si20),
+            ),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_LARCH_TLS_LE_LO12 => (
+            RelocationKind::TpOff,
+            RelocationSize::bit_mask(
+                0,
+                12,
+                RelocationInstruction::LoongArch(LoongArchInstruction::Si12),
+            ),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_LARCH_TLS_LE64_LO20 => (
+            RelocationKind::TpOff,
+            RelocationSize::bit_mask(
+                32,
+                52,
+                RelocationInstruction::LoongArch(LoongArchInstruction::Si20),
+            ),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_LARCH_TLS_LE64_HI12 => (
+            RelocationKind::TpOff,
+            RelocationSize::bit_mask(
+                52,
+                64,
+                RelocationInstruction::LoongArch(LoongArchInstruction::Si12),
+            ),
+            AllowedRange::no_check(),
+            1,
+        ),
+        R_LARCH_TLS_LE_HI20_R => (
+            RelocationKind::TpOff,
+            RelocationSize::bit_mask(
+                0,
+                32,
+                RelocationInstruction::LoongArch(LoongArchInstruction::Hi20),
+            ),
+            HI20_RANGE,
+            1,
+        ),
+        R_LARCH_TLS_LE_LO12_R => (
+            RelocationKind::TpOff,
+            RelocationSize::bit_mask(
+                0,
+                12,
+                RelocationInstruction::LoongArch(LoongArchInstruction::Si12),
+            ),
+            AllowedRange::no_check(),
+            1,
+        ),
+
+        // Thread-local storage: initial exec
+        object::elf::R_LARCH_TLS_IE_PC_HI20 => {
+            mask = Some(PageMask::Position);
+            (
+                RelocationKind::GotTpOff,
+                RelocationSize::bit_mask(
+                    0,
+                    32,
+                    RelocationInstruction::LoongArch(LoongArchInstruction::Hi20),
+                ),
+                HI20_RANGE,
+                1,
+            )
+        }
+        object::elf::R_LARCH_TLS_IE_PC_LO12 | object::elf::R_LARCH_TLS_IE_LO12 => (
+            RelocationKind::GotTpOffGot,
+            RelocationSize::bit_mask(
+                0,
+                12,
+                RelocationInstruction::LoongArch(LoongArchInstruction::Si12),
+            ),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_LARCH_TLS_IE_HI20 => (
+            RelocationKind::GotTpOffGot,
+            RelocationSize::bit_mask(
+                12,
+                32,
+                RelocationInstruction::LoongArch(LoongArchInstruction::Si20),
+            ),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_LARCH_TLS_IE64_LO20 => (
+            RelocationKind::GotTpOffGot,
+            RelocationSize::bit_mask(
+                32,
+                52,
+                RelocationInstruction::LoongArch(LoongArchInstruction::Si20),
+            ),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_LARCH_TLS_IE64_HI12 => (
+            RelocationKind::GotTpOffGot,
+            RelocationSize::bit_mask(
+                52,
+                64,
+                RelocationInstruction::LoongArch(LoongArchInstruction::Si12),
+            ),
+            AllowedRange::no_check(),
+            1,
+        ),
+
+        // Thread-local storage: general and local dynamic. Local dynamic accesses use a per-symbol
+        // GOT entry just like general dynamic, so we treat them the same. The low part of the
+        // address of the entry is supplied by an `R_LARCH_GOT_PC_LO12` on the following `addi.d`.
+        object::elf::R_LARCH_TLS_GD_PC_HI20 | object::elf::R_LARCH_TLS_LD_PC_HI20 => {
+            mask = Some(PageMask::Position);
+            (
+                RelocationKind::TlsGd,
+                RelocationSize::bit_mask(
+                    0,
+                    32,
+                    RelocationInstruction::LoongArch(LoongArchInstruction::Hi20),
+                ),
+                HI20_RANGE,
+                1,
+            )
+        }
+        object::elf::R_LARCH_TLS_GD_HI20 | object::elf::R_LARCH_TLS_LD_HI20 => (
+            RelocationKind::TlsGdGot,
+            RelocationSize::bit_mask(
+                12,
+                32,
+                RelocationInstruction::LoongArch(LoongArchInstruction::Si20),
+            ),
+            AllowedRange::no_check(),
+            1,
+        ),
+        R_LARCH_TLS_GD_PCREL20_S2 | R_LARCH_TLS_LD_PCREL20_S2 => (
+            RelocationKind::TlsGd,
+            RelocationSize::bit_mask(
+                2,
+                22,
+                RelocationInstruction::LoongArch(LoongArchInstruction::Si20),
+            ),
+            AllowedRange::new(-(2i64.pow(21)), 2i64.pow(21)),
+            4,
+        ),
+
+        // Thread-local storage: descriptors
+        R_LARCH_TLS_DESC_PC_HI20 => {
+            mask = Some(PageMask::Position);
+            (
+                RelocationKind::TlsDesc,
+                RelocationSize::bit_mask(
+                    0,
+                    32,
+                    RelocationInstruction::LoongArch(LoongArchInstruction::Hi20),
+                ),
+                HI20_RANGE,
+                1,
+            )
+        }
+        R_LARCH_TLS_DESC_PC_LO12 | R_LARCH_TLS_DESC_LO12 => (
+            RelocationKind::TlsDescGot,
+            RelocationSize::bit_mask(
+                0,
+                12,
+                RelocationInstruction::LoongArch(LoongArchInstruction::Si12),
+            ),
+            AllowedRange::no_check(),
+            1,
+        ),
+        R_LARCH_TLS_DESC_HI20 => (
+            RelocationKind::TlsDescGot,
+            RelocationSize::bit_mask(
+                12,
+                32,
+                RelocationInstruction::LoongArch(LoongArchInstruction::Si20),
+            ),
+            AllowedRange::no_check(),
+            1,
+        ),
+        R_LARCH_TLS_DESC64_LO20 => (
+            RelocationKind::TlsDescGot,
+            RelocationSize::bit_mask(
+                32,
+                52,
+                RelocationInstruction::LoongArch(LoongArchInstruction::Si20),
+            ),
+            AllowedRange::no_check(),
+            1,
+        ),
+        R_LARCH_TLS_DESC64_HI12 => (
+            RelocationKind::TlsDescGot,
+            RelocationSize::bit_mask(
+                52,
+                64,
+                RelocationInstruction::LoongArch(LoongArchInstruction::Si12),
+            ),
+            AllowedRange::no_check(),
+            1,
+        ),
+        R_LARCH_TLS_DESC_PCREL20_S2 => (
+            RelocationKind::TlsDesc,
+            RelocationSize::bit_mask(
+                2,
+                22,
+                RelocationInstruction::LoongArch(LoongArchInstruction::Si20),
+            ),
+            AllowedRange::new(-(2i64.pow(21)), 2i64.pow(21)),
+            4,
+        ),
+        // Only used as markers for relaxation.
+        R_LARCH_TLS_DESC_LD | R_LARCH_TLS_DESC_CALL => (
+            RelocationKind::TlsDescCall,
+            RelocationSize::ByteSize(0),
+            AllowedRange::no_check(),
+            1,
+        ),
+
+        _ => return None,
+    };
+
+    Some(RelocationKindInfo {
+        kind,
+        size,
+        mask,
+        range,
+        alignment,
+    })
+}
+
+impl LoongArchInstruction {
+    /// Writes `extracted_value` into the instruction at the start of `dest`, replacing whatever
+    /// the instruction's immediate field previously held.
+    pub fn write_to_value(self, extracted_value: u64, dest: &mut [u8]) {
+        let value = extracted_value as u32;
+        match self {
+            LoongArchInstruction::Si20 => {
+                update_u32(dest, |insn| {
+                    (insn & !(0xf_ffff << 5)) | ((value & 0xf_ffff) << 5)
+                });
+            }
+            LoongArchInstruction::Si12 => {
+                update_u32(dest, |insn| {
+                    (insn & !(0xfff << 10)) | ((value & 0xfff) << 10)
+                });
+            }
+            LoongArchInstruction::Hi20 => {
+                LoongArchInstruction::Si20
+                    .write_to_value(u64::from(value.wrapping_add(0x800) >> 12), dest);
+            }
+            LoongArchInstruction::Offs16 => {
+                update_u32(dest, |insn| {
+                    (insn & !(0xffff << 10)) | ((value >> 2 & 0xffff) << 10)
+                });
+            }
+            LoongArchInstruction::Offs21 => {
+                update_u32(dest, |insn| {
+                    (insn & !(0xffff << 10 | 0x1f))
+                        | ((value >> 2 & 0xffff) << 10)
+                        | (value >> 18 & 0x1f)
+                });
+            }
+            LoongArchInstruction::Offs26 => {
+                update_u32(dest, |insn| {
+                    (insn & !0x03ff_ffff) | ((value >> 2 & 0xffff) << 10) | (value >> 18 & 0x3ff)
+                });
+            }
+            LoongArchInstruction::Call36 => {
+                LoongArchInstruction::Si20
+                    .write_to_value(extracted_value.wrapping_add(0x20000) >> 18, dest);
+                LoongArchInstruction::Offs16.write_to_value(extracted_value, &mut dest[4..]);
+            }
+            LoongArchInstruction::Set6 => {
+                RiscVInstruction::Set6.write_to_value(extracted_value, dest);
+            }
+            LoongArchInstruction::Uleb128 => {
+                RiscVInstruction::Uleb128.write_to_value(extracted_value, dest);
+            }
+        }
+    }
+
+    /// Like `write_to_value`, but without the rounding that some instructions apply, so that
+    /// writing all ones sets every bit of the immediate.
+    pub fn write_mask_value(self, all_ones: u64, dest: &mut [u8]) {
+        match self {
+            LoongArchInstruction::Hi20 => self.write_to_value(all_ones & !0x800, dest),
+            LoongArchInstruction::Call36 => {
+                LoongArchInstruction::Si20.write_to_value(all_ones >> 18, dest);
+                LoongArchInstruction::Offs16.write_to_value(all_ones, &mut dest[4..]);
+            }
+            _ => self.write_to_value(all_ones, dest),
+        }
+    }
+
+    /// The inverse of `write_to_value`. For `Hi20`, only the upper 20 bits of the value can be
+    /// recovered.
+    #[must_use]
+    pub fn read_value(self, bytes: &[u8]) -> u64 {
+        let insn = || u32::from_le_bytes(*bytes.first_chunk::<4>().expect("Need at least 4 bytes"));
+        match self {
+            LoongArchInstruction::Si20 => sign_extend(19, u64::from(insn() >> 5 & 0xf_ffff)),
+            LoongArchInstruction::Si12 => sign_extend(11, u64::from(insn() >> 10 & 0xfff)),
+            LoongArchInstruction::Hi20 => LoongArchInstruction::Si20.read_value(bytes) << 12,
+            LoongArchInstruction::Offs16 => sign_extend(17, u64::from(insn() >> 10 & 0xffff) << 2),
+            LoongArchInstruction::Offs21 => {
+                let insn = insn();
+                sign_extend(
+                    22,
+                    u64::from((insn & 0x1f) << 18 | (insn >> 10 & 0xffff) << 2),
+                )
+            }
+            LoongArchInstruction::Offs26 => {
+                let insn = insn();
+                sign_extend(
+                    27,
+                    u64::from((insn & 0x3ff) << 18 | (insn >> 10 & 0xffff) << 2),
+                )
+            }
+            LoongArchInstruction::Call36 => (LoongArchInstruction::Si20.read_value(bytes) << 18)
+                .wrapping_add(LoongArchInstruction::Offs16.read_value(&bytes[4..])),
+            LoongArchInstruction::Set6 => RiscVInstruction::Set6.read_value(bytes),
+            LoongArchInstruction::Uleb128 => RiscVInstruction::Uleb128.read_value(bytes),
+        }
+    }
+
+    /// Returns the number of bytes occupied by the instruction or value.
+    #[must_use]
+    pub fn num_bytes(self) -> usize {
+        match self {
+            LoongArchInstruction::Call36 => 8,
+            LoongArchInstruction::Set6 | LoongArchInstruction::Uleb128 => 1,
+            _ => 4,
+        }
+    }
+}
+
+fn update_u32(dest: &mut [u8], update: impl FnOnce(u32) -> u32) {
+    let bytes = dest.first_chunk_mut::<4>().expect("Need at least 4 bytes");
+    *bytes = update(u32::from_le_bytes(*bytes)).to_le_bytes();
+}
+
+fn sign_extend(sign_bit: u32, value: u64) -> u64 {
+    if value & (1 << sign_bit) != 0 {
+        value | !((2 << sign_bit) - 1)
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(instruction: LoongArchInstruction, template: u32, value: i64, bits: u32) {
+        let mut bytes = template.to_le_bytes().to_vec();
+        if instruction == LoongArchInstruction::Call36 {
+            // jirl $ra, $ra, 0
+            bytes.extend_from_slice(&0x4c00_0021u32.to_le_bytes());
+        }
+        let extracted = value as u64 & ((1 << bits) - 1);
+        instruction.write_to_value(extracted, &mut bytes);
+        assert_eq!(
+            instruction.read_value(&bytes) as i64,
+            value,
+            "{instruction:?} {value}"
+        );
+        // Writing again should replace rather than combine with the previous value.
+        instruction.write_to_value(extracted, &mut bytes);
+        assert_eq!(instruction.read_value(&bytes) as i64, value);
+    }
+
+    #[test]
+    fn test_instruction_round_trip() {
+        // beq $a0, $a1, 0
+        for value in [-(1 << 17), -4, 4, 0x1_fffc] {
+            round_trip(LoongArchInstruction::Offs16, 0x5800_0085, value, 18);
+        }
+        // beqz $a0, 0
+        for value in [-(1 << 22), -4, 4, 0x4_0000, (1 << 22) - 4] {
+            round_trip(LoongArchInstruction::Offs21, 0x4000_0080, value, 23);
+        }
+        // bl 0
+        for value in [-(1 << 27), -4, 4, 0x4_0000, (1 << 27) - 4] {
+            round_trip(LoongArchInstruction::Offs26, 0x5400_0000, value, 28);
+        }
+        // addi.d $a0, $a0, 0
+        for value in [-2048, -1, 0, 1, 2047] {
+            round_trip(LoongArchInstruction::Si12, 0x02c0_0084, value, 12);
+        }
+        // lu12i.w $a0, 0
+        for value in [-(1 << 19), -1, 0, 1, (1 << 19) - 1] {
+            round_trip(LoongArchInstruction::Si20, 0x1400_0004, value, 20);
+        }
+        // pcaddu18i $ra, 0
+        for value in [
+            -(1 << 37),
+            -0x2_0000,
+            -4,
+            0,
+            0x1_fffc,
+            0x2_0000,
+            0x12_3456_7890,
+        ] {
+            round_trip(LoongArchInstruction::Call36, 0x1e00_0001, value, 38);
+        }
+    }
+
+    #[test]
+    fn test_hi_lo_split() {
+        // pcalau12i $a0, 0
+        let mut pcalau12i = 0x1a00_0004u32.to_le_bytes();
+        LoongArchInstruction::Hi20.write_to_value(0x1234_5fff, &mut pcalau12i);
+        assert_eq!(u32::from_le_bytes(pcalau12i), 0x1a00_0004 | 0x12346 << 5);
+        assert_eq!(
+            LoongArchInstruction::Hi20.read_value(&pcalau12i),
+            0x1234_6000
+        );
+
+        let mut mask = [0; 8];
+        LoongArchInstruction::Call36.write_mask_value(u64::MAX >> 26, &mut mask);
+        assert_eq!(
+            u32::from_le_bytes(mask[..4].try_into().unwrap()),
+            0x01ff_ffe0
+        );
+        assert_eq!(
+            u32::from_le_bytes(mask[4..].try_into().unwrap()),
+            0x03ff_fc00
+        );
+    }
+}
@@ -0,0 +1,680 @@
+use crate::elf::AllowedRange;
+use crate::elf::RelocationInstruction;
+use crate::elf::RelocationKind;
+use crate::elf::RelocationKindInfo;
+use crate::elf::RelocationSize;
+use crate::relaxation::RelocationModifier;
+
+/// The dynamic TLSDESC relocation. The `object` crate doesn't define it yet.
+pub const R_RISCV_TLSDESC: u32 = 12;
+
+/// Dynamic thread vector pointers point this far past the start of each module's TLS block, so
+/// that a signed 12-bit offset can reach more of the block. Offsets within a module's TLS block
+/// are biased by the same amount.
+pub const TLS_DTV_OFFSET: u64 = 0x800;
+
+pub const NOP: u32 = 0x0000_0013;
+
+/// The range of values that can be split into a `%hi` part for a U-type instruction and a `%lo`
+/// part for a following I-type or S-type instruction.
+const HI20_RANGE: AllowedRange = AllowedRange::new(-(1 << 31) - 0x800, (1 << 31) - 0x800);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelaxationKind {
+    /// Leave the instruction alone. Used when we only want to change the kind of relocation used.
+    NoOp,
+
+    /// Replace with nop
+    ReplaceWithNop,
+
+    /// Replace with `lui a0, %tprel_hi(sym)`. Used for the `addi` of a TLSDESC sequence.
+    TlsDescToLocalExecLui,
+
+    /// Replace with `addi a0, a0, %tprel_lo(sym)`. Used for the `jalr` of a TLSDESC sequence.
+    TlsDescToLocalExecAddi,
+}
+
+impl RelaxationKind {
+    pub fn apply(self, section_bytes: &mut [u8], offset_in_section: &mut u64, _addend: &mut i64) {
+        let offset = *offset_in_section as usize;
+        let instruction = match self {
+            RelaxationKind::NoOp => return,
+            RelaxationKind::ReplaceWithNop => NOP,
+            // lui a0, 0
+            RelaxationKind::TlsDescToLocalExecLui => 0x0000_0537,
+            // addi a0, a0, 0
+            RelaxationKind::TlsDescToLocalExecAddi => 0x0005_0513,
+        };
+        section_bytes[offset..offset + 4].copy_from_slice(&instruction.to_le_bytes());
+    }
+
+    #[must_use]
+    pub fn next_modifier(&self) -> RelocationModifier {
+        RelocationModifier::Normal
+    }
+}
+
+/// The ways in which RISC-V relocations encode their values. Instructions are described in "The
+/// RISC-V Instruction Set Manual, Volume I: Unprivileged Architecture".
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
+pub enum RiscVInstruction {
+    /// The upper 20 bits of a 32 bit value, rounded so that adding the sign-extended lower 12
+    /// bits gives the full value. Used by `lui` and `auipc`.
+    UType,
+
+    /// A 12 bit immediate in bits 20..32, as used by loads, `addi` and `jalr`.
+    IType,
+
+    /// A 12 bit immediate split between bits 7..12 and 25..32, as used by stores.
+    SType,
+
+    /// A 13 bit conditional branch offset.
+    BType,
+
+    /// A 21 bit `jal` offset.
+    JType,
+
+    /// A 9 bit `c.beqz` or `c.bnez` offset.
+    CbType,
+
+    /// A 12 bit `c.j` offset.
+    CjType,
+
+    /// An `auipc` followed by a `jalr`, which together hold a 32 bit offset.
+    Call,
+
+    /// The low 6 bits of a byte.
+    Set6,
+
+    /// A ULEB128 value, which we write using the same number of bytes as are already there.
+    Uleb128,
+}
+
+#[must_use]
+pub const fn relocation_type_from_raw(r_type: u32) -> Option<RelocationKindInfo> {
+    let (kind, size, range, alignment) = match r_type {
+        object::elf::R_RISCV_NONE
+        | object::elf::R_RISCV_TPREL_ADD
+        | object::elf::R_RISCV_ALIGN
+        | object::elf::R_RISCV_RELAX => (
+            RelocationKind::None,
+            RelocationSize::ByteSize(0),
+            AllowedRange::no_check(),
+            1,
+        ),
+
+        // Data relocations
+        object::elf::R_RISCV_32 => (
+            RelocationKind::Absolute,
+            RelocationSize::ByteSize(4),
+            AllowedRange::new(-(2i64.pow(31)), 2i64.pow(32)),
+            1,
+        ),
+        object::elf::R_RISCV_64 => (
+            RelocationKind::Absolute,
+            RelocationSize::ByteSize(8),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_RISCV_32_PCREL => (
+            RelocationKind::Relative,
+            RelocationSize::ByteSize(4),
+            AllowedRange::new(-(2i64.pow(31)), 2i64.pow(31)),
+            1,
+        ),
+        object::elf::R_RISCV_PLT32 => (
+            RelocationKind::PltRelative,
+            RelocationSize::ByteSize(4),
+            AllowedRange::new(-(2i64.pow(31)), 2i64.pow(31)),
+            1,
+        ),
+        object::elf::R_RISCV_TLS_DTPREL32 => (
+            RelocationKind::DtpOffRiscV,
+            RelocationSize::ByteSize(4),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_RISCV_TLS_DTPREL64 => (
+            RelocationKind::DtpOffRiscV,
+            RelocationSize::ByteSize(8),
+            AllowedRange::no_check(),
+            1,
+        ),
+
+        // Relocations that overwrite or adjust a value in place. These are mostly used for the
+        // differences between labels in sections that linker relaxation might shrink.
+        object::elf::R_RISCV_SET6 => (
+            RelocationKind::AbsoluteSet,
+            RelocationSize::bit_mask(0, 6, RelocationInstruction::RiscV(RiscVInstruction::Set6)),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_RISCV_SET8 => (
+            RelocationKind::AbsoluteSet,
+            RelocationSize::ByteSize(1),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_RISCV_SET16 => (
+            RelocationKind::AbsoluteSet,
+            RelocationSize::ByteSize(2),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_RISCV_SET32 => (
+            RelocationKind::AbsoluteSet,
+            RelocationSize::ByteSize(4),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_RISCV_SET_ULEB128 => (
+            RelocationKind::AbsoluteSet,
+            RelocationSize::bit_mask(
+                0,
+                64,
+                RelocationInstruction::RiscV(RiscVInstruction::Uleb128),
+            ),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_RISCV_ADD8 => (
+            RelocationKind::AbsoluteAddition,
+            RelocationSize::ByteSize(1),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_RISCV_ADD16 => (
+            RelocationKind::AbsoluteAddition,
+            RelocationSize::ByteSize(2),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_RISCV_ADD32 => (
+            RelocationKind::AbsoluteAddition,
+            RelocationSize::ByteSize(4),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_RISCV_ADD64 => (
+            RelocationKind::AbsoluteAddition,
+            RelocationSize::ByteSize(8),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_RISCV_SUB6 => (
+            RelocationKind::AbsoluteSubtraction,
+            RelocationSize::bit_mask(0, 6, RelocationInstruction::RiscV(RiscVInstruction::Set6)),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_RISCV_SUB8 => (
+            RelocationKind::AbsoluteSubtraction,
+            RelocationSize::ByteSize(1),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_RISCV_SUB16 => (
+            RelocationKind::AbsoluteSubtraction,
+            RelocationSize::ByteSize(2),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_RISCV_SUB32 => (
+            RelocationKind::AbsoluteSubtraction,
+            RelocationSize::ByteSize(4),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_RISCV_SUB64 => (
+            RelocationKind::AbsoluteSubtraction,
+            RelocationSize::ByteSize(8),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_RISCV_SUB_ULEB128 => (
+            RelocationKind::AbsoluteSubtraction,
+            RelocationSize::bit_mask(
+                0,
+                64,
+                RelocationInstruction::RiscV(RiscVInstruction::Uleb128),
+            ),
+            AllowedRange::no_check(),
+            1,
+        ),
+
+        // Control transfer
+        object::elf::R_RISCV_BRANCH => (
+            RelocationKind::Relative,
+            RelocationSize::bit_mask(0, 13, RelocationInstruction::RiscV(RiscVInstruction::BType)),
+            AllowedRange::new(-(2i64.pow(12)), 2i64.pow(12)),
+            2,
+        ),
+        object::elf::R_RISCV_JAL => (
+            RelocationKind::Relative,
+            RelocationSize::bit_mask(0, 21, RelocationInstruction::RiscV(RiscVInstruction::JType)),
+            AllowedRange::new(-(2i64.pow(20)), 2i64.pow(20)),
+            2,
+        ),
+        object::elf::R_RISCV_CALL | object::elf::R_RISCV_CALL_PLT => (
+            RelocationKind::PltRelative,
+            RelocationSize::bit_mask(0, 32, RelocationInstruction::RiscV(RiscVInstruction::Call)),
+            HI20_RANGE,
+            1,
+        ),
+        object::elf::R_RISCV_RVC_BRANCH => (
+            RelocationKind::Relative,
+            RelocationSize::bit_mask(0, 9, RelocationInstruction::RiscV(RiscVInstruction::CbType)),
+            AllowedRange::new(-(2i64.pow(8)), 2i64.pow(8)),
+            2,
+        ),
+        object::elf::R_RISCV_RVC_JUMP => (
+            RelocationKind::Relative,
+            RelocationSize::bit_mask(
+                0,
+                12,
+                RelocationInstruction::RiscV(RiscVInstruction::CjType),
+            ),
+            AllowedRange::new(-(2i64.pow(11)), 2i64.pow(11)),
+            2,
+        ),
+
+        // PC-relative addressing. Each `%pcrel_hi` relocation on an `auipc` is paired with one or
+        // more `%pcrel_lo` relocations that refer to a label on the `auipc`.
+        object::elf::R_RISCV_PCREL_HI20 => (
+            RelocationKind::Relative,
+            RelocationSize::bit_mask(0, 32, RelocationInstruction::RiscV(RiscVInstruction::UType)),
+            HI20_RANGE,
+            1,
+        ),
+        object::elf::R_RISCV_GOT_HI20 => (
+            RelocationKind::GotRelative,
+            RelocationSize::bit_mask(0, 32, RelocationInstruction::RiscV(RiscVInstruction::UType)),
+            HI20_RANGE,
+            1,
+        ),
+        object::elf::R_RISCV_PCREL_LO12_I => (
+            RelocationKind::PcRelLoRiscV,
+            RelocationSize::bit_mask(0, 12, RelocationInstruction::RiscV(RiscVInstruction::IType)),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_RISCV_PCREL_LO12_S => (
+            RelocationKind::PcRelLoRiscV,
+            RelocationSize::bit_mask(0, 12, RelocationInstruction::RiscV(RiscVInstruction::SType)),
+            AllowedRange::no_check(),
+            1,
+        ),
+
+        // Absolute addressing
+        object::elf::R_RISCV_HI20 => (
+            RelocationKind::AbsoluteSet,
+            RelocationSize::bit_mask(0, 32, RelocationInstruction::RiscV(RiscVInstruction::UType)),
+            HI20_RANGE,
+            1,
+        ),
+        object::elf::R_RISCV_LO12_I => (
+            RelocationKind::AbsoluteSet,
+            RelocationSize::bit_mask(0, 12, RelocationInstruction::RiscV(RiscVInstruction::IType)),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_RISCV_LO12_S => (
+            RelocationKind::AbsoluteSet,
+            RelocationSize::bit_mask(0, 12, RelocationInstruction::RiscV(RiscVInstruction::SType)),
+            AllowedRange::no_check(),
+            1,
+        ),
+
+        // Thread-local storage
+        object::elf::R_RISCV_TPREL_HI20 => (
+            RelocationKind::TpOff,
+            RelocationSize::bit_mask(0, 32, RelocationInstruction::RiscV(RiscVInstruction::UType)),
+            HI20_RANGE,
+            1,
+        ),
+        object::elf::R_RISCV_TPREL_LO12_I => (
+            RelocationKind::TpOff,
+            RelocationSize::bit_mask(0, 12, RelocationInstruction::RiscV(RiscVInstruction::IType)),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_RISCV_TPREL_LO12_S => (
+            RelocationKind::TpOff,
+            RelocationSize::bit_mask(0, 12, RelocationInstruction::RiscV(RiscVInstruction::SType)),
+            AllowedRange::no_check(),
+            1,
+        ),
+        object::elf::R_RISCV_TLS_GOT_HI20 => (
+            RelocationKind::GotTpOff,
+            RelocationSize::bit_mask(0, 32, RelocationInstruction::RiscV(RiscVInstruction::UType)),
+            HI20_RANGE,
+            1,
+        ),
+        object::elf::R_RISCV_TLS_GD_HI20 => (
+            RelocationKind::TlsGd,
+            RelocationSize::bit_mask(0, 32, RelocationInstruction::RiscV(RiscVInstruction::UType)),
+            HI20_RANGE,
+            1,
+        ),
+        object::elf::R_RISCV_TLSDESC_HI20 => (
+            RelocationKind::TlsDesc,
+            RelocationSize::bit_mask(0, 32, RelocationInstruction::RiscV(RiscVInstruction::UType)),
+            HI20_RANGE,
+            1,
+        ),
+        object::elf::R_RISCV_TLSDESC_LOAD_LO12 | object::elf::R_RISCV_TLSDESC_ADD_LO12 => (
+            RelocationKind::PcRelLoRiscV,
+            RelocationSize::bit_mask(0, 12, RelocationInstruction::RiscV(RiscVInstruction::IType)),
+            AllowedRange::no_check(),
+            1,
+        ),
+        // Only used as a marker for relaxation. It refers to the `auipc` label like the other
+        // relocations in the sequence.
+        object::elf::R_RISCV_TLSDESC_CALL => (
+            RelocationKind::PcRelLoRiscV,
+            RelocationSize::ByteSize(0),
+            AllowedRange::no_check(),
+            1,
+        ),
+
+        _ => return None,
+    };
+
+    Some(RelocationKindInfo {
+        kind,
+        size,
+        mask: None,
+        range,
+        alignment,
+    })
+}
+
+impl RiscVInstruction {
+    /// Writes `extracted_value` into the instruction at the start of `dest`, replacing whatever
+    /// the instruction's immediate field previously held.
+    pub fn write_to_value(self, extracted_value: u64, dest: &mut [u8]) {
+        let value = extracted_value as u32;
+        match self {
+            RiscVInstruction::UType => {
+                update_u32(dest, |insn| {
+                    (insn & 0xfff) | (value.wrapping_add(0x800) & 0xffff_f000)
+                });
+            }
+            RiscVInstruction::IType => {
+                update_u32(dest, |insn| (insn & 0x000f_ffff) | ((value & 0xfff) << 20));
+            }
+            RiscVInstruction::SType => {
+                update_u32(dest, |insn| {
+                    (insn & 0x01ff_f07f) | ((value >> 5 & 0x7f) << 25) | ((value & 0x1f) << 7)
+                });
+            }
+            RiscVInstruction::BType => {
+                update_u32(dest, |insn| {
+                    (insn & 0x01ff_f07f)
+                        | ((value >> 12 & 1) << 31)
+                        | ((value >> 5 & 0x3f) << 25)
+                        | ((value >> 1 & 0xf) << 8)
+                        | ((value >> 11 & 1) << 7)
+                });
+            }
+            RiscVInstruction::JType => {
+                update_u32(dest, |insn| {
+                    (insn & 0xfff)
+                        | ((value >> 20 & 1) << 31)
+                        | ((value >> 1 & 0x3ff) << 21)
+                        | ((value >> 11 & 1) << 20)
+                        | ((value >> 12 & 0xff) << 12)
+                });
+            }
+            RiscVInstruction::CbType => {
+                update_u16(dest, |insn| {
+                    (insn & 0xe383)
+                        | ((value >> 8 & 1) << 12)
+                        | ((value >> 3 & 0x3) << 10)
+                        | ((value >> 6 & 0x3) << 5)
+                        | ((value >> 1 & 0x3) << 3)
+                        | ((value >> 5 & 1) << 2)
+                });
+            }
+            RiscVInstruction::CjType => {
+                update_u16(dest, |insn| {
+                    (insn & 0xe003)
+                        | ((value >> 11 & 1) << 12)
+                        | ((value >> 4 & 1) << 11)
+                        | ((value >> 8 & 0x3) << 9)
+                        | ((value >> 10 & 1) << 8)
+                        | ((value >> 6 & 1) << 7)
+                        | ((value >> 7 & 1) << 6)
+                        | ((value >> 1 & 0x7) << 3)
+                        | ((value >> 5 & 1) << 2)
+                });
+            }
+            RiscVInstruction::Call => {
+                RiscVInstruction::UType.write_to_value(extracted_value, dest);
+                RiscVInstruction::IType.write_to_value(extracted_value, &mut dest[4..]);
+            }
+            RiscVInstruction::Set6 => {
+                dest[0] = (dest[0] & 0xc0) | (value as u8 & 0x3f);
+            }
+            RiscVInstruction::Uleb128 => {
+                let len = dest
+                    .iter()
+                    .position(|b| b & 0x80 == 0)
+                    .map_or(dest.len(), |last| last + 1);
+                let mut remaining = extracted_value;
+                for (i, byte) in dest[..len].iter_mut().enumerate() {
+                    *byte = (remaining & 0x7f) as u8;
+                    if i + 1 < len {
+                        *byte |= 0x80;
+                    }
+                    remaining = remaining.checked_shr(7).unwrap_or(0);
+                }
+            }
+        }
+    }
+
+    /// Like `write_to_value`, but without the rounding that U-type instructions apply, so that
+    /// writing all ones sets every bit of the immediate.
+    pub fn write_mask_value(self, all_ones: u64, dest: &mut [u8]) {
+        match self {
+            RiscVInstruction::UType => self.write_to_value(all_ones & !0x800, dest),
+            RiscVInstruction::Call => {
+                RiscVInstruction::UType.write_mask_value(all_ones, dest);
+                RiscVInstruction::IType.write_to_value(all_ones, &mut dest[4..]);
+            }
+            _ => self.write_to_value(all_ones, dest),
+        }
+    }
+
+    /// The inverse of `write_to_value`. For `UType`, only the upper 20 bits of the value can be
+    /// recovered.
+    #[must_use]
+    pub fn read_value(self, bytes: &[u8]) -> u64 {
+        let insn = || u32::from_le_bytes(*bytes.first_chunk::<4>().expect("Need at least 4 bytes"));
+        let compressed = || {
+            u32::from(u16::from_le_bytes(
+                *bytes.first_chunk::<2>().expect("Need at least 2 bytes"),
+            ))
+        };
+        match self {
+            RiscVInstruction::UType => sign_extend(31, u64::from(insn() & 0xffff_f000)),
+            RiscVInstruction::IType => sign_extend(11, u64::from(insn() >> 20)),
+            RiscVInstruction::SType => {
+                let insn = insn();
+                sign_extend(11, u64::from((insn >> 25) << 5 | (insn >> 7 & 0x1f)))
+            }
+            RiscVInstruction::BType => {
+                let insn = insn();
+                let value = (insn >> 31 & 1) << 12
+                    | (insn >> 25 & 0x3f) << 5
+                    | (insn >> 8 & 0xf) << 1
+                    | (insn >> 7 & 1) << 11;
+                sign_extend(12, u64::from(value))
+            }
+            RiscVInstruction::JType => {
+                let insn = insn();
+                let value = (insn >> 31 & 1) << 20
+                    | (insn >> 21 & 0x3ff) << 1
+                    | (insn >> 20 & 1) << 11
+                    | (insn >> 12 & 0xff) << 12;
+                sign_extend(20, u64::from(value))
+            }
+            RiscVInstruction::CbType => {
+                let insn = compressed();
+                let value = (insn >> 12 & 1) << 8
+                    | (insn >> 10 & 0x3) << 3
+                    | (insn >> 5 & 0x3) << 6
+                    | (insn >> 3 & 0x3) << 1
+                    | (insn >> 2 & 1) << 5;
+                sign_extend(8, u64::from(value))
+            }
+            RiscVInstruction::CjType => {
+                let insn = compressed();
+                let value = (insn >> 12 & 1) << 11
+                    | (insn >> 11 & 1) << 4
+                    | (insn >> 9 & 0x3) << 8
+                    | (insn >> 8 & 1) << 10
+                    | (insn >> 7 & 1) << 6
+                    | (insn >> 6 & 1) << 7
+                    | (insn >> 3 & 0x7) << 1
+                    | (insn >> 2 & 1) << 5;
+                sign_extend(11, u64::from(value))
+            }
+            RiscVInstruction::Call => RiscVInstruction::UType
+                .read_value(bytes)
+                .wrapping_add(RiscVInstruction::IType.read_value(&bytes[4..])),
+            RiscVInstruction::Set6 => u64::from(bytes[0] & 0x3f),
+            RiscVInstruction::Uleb128 => {
+                let mut value = 0;
+                for (i, byte) in bytes.iter().take(10).enumerate() {
+                    value |= u64::from(byte & 0x7f) << (7 * i);
+                    if byte & 0x80 == 0 {
+                        break;
+                    }
+                }
+                value
+            }
+        }
+    }
+
+    /// Returns the number of bytes occupied by the instruction or value.
+    #[must_use]
+    pub fn num_bytes(self) -> usize {
+        match self {
+            RiscVInstruction::CbType | RiscVInstruction::CjType => 2,
+            RiscVInstruction::Call => 8,
+            RiscVInstruction::Set6 | RiscVInstruction::Uleb128 => 1,
+            _ => 4,
+        }
+    }
+}
+
+fn update_u32(dest: &mut [u8], update: impl FnOnce(u32) -> u32) {
+    let bytes = dest.first_chunk_mut::<4>().expect("Need at least 4 bytes");
+    *bytes = update(u32::from_le_bytes(*bytes)).to_le_bytes();
+}
+
+fn update_u16(dest: &mut [u8], update: impl FnOnce(u32) -> u32) {
+    let bytes = dest.first_chunk_mut::<2>().expect("Need at least 2 bytes");
+    *bytes = (update(u32::from(u16::from_le_bytes(*bytes))) as u16).to_le_bytes();
+}
+
+fn sign_extend(sign_bit: u32, value: u64) -> u64 {
+    if value & (1 << sign_bit) != 0 {
+        value | !((2 << sign_bit) - 1)
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(instruction: RiscVInstruction, template: &[u8], value: i64, bits: u32) {
+        let mut bytes = template.to_vec();
+        let extracted = value as u64 & ((1 << bits) - 1);
+        instruction.write_to_value(extracted, &mut bytes);
+        assert_eq!(
+            instruction.read_value(&bytes) as i64,
+            value,
+            "{instruction:?} {value}"
+        );
+        // Writing again should replace rather than combine with the previous value.
+        instruction.write_to_value(extracted, &mut bytes);
+        assert_eq!(instruction.read_value(&bytes) as i64, value);
+    }
+
+    #[test]
+    fn test_instruction_round_trip() {
+        // beq a0, a1, 0
+        let branch = 0x00b5_0063u32.to_le_bytes();
+        for value in [-4096, -2, 0, 2, 0x7fe, 0x800, 4094] {
+            round_trip(RiscVInstruction::BType, &branch, value, 13);
+        }
+        // jal ra, 0
+        let jal = 0x0000_00efu32.to_le_bytes();
+        for value in [-(1 << 20), -2, 2, 0x800, 0x7_fffe, (1 << 20) - 2] {
+            round_trip(RiscVInstruction::JType, &jal, value, 21);
+        }
+        // addi a0, a0, 0
+        let addi = 0x0005_0513u32.to_le_bytes();
+        for value in [-2048, -1, 0, 1, 2047] {
+            round_trip(RiscVInstruction::IType, &addi, value, 12);
+        }
+        // sd a0, 0(a1)
+        let store = 0x00a5_b023u32.to_le_bytes();
+        for value in [-2048, -1, 0, 31, 32, 2047] {
+            round_trip(RiscVInstruction::SType, &store, value, 12);
+        }
+        // c.beqz a0, 0
+        let c_beqz = 0xc101u16.to_le_bytes();
+        for value in [-256, -2, 2, 0x3e, 0x40, 254] {
+            round_trip(RiscVInstruction::CbType, &c_beqz, value, 9);
+        }
+        // c.j 0
+        let c_j = 0xa001u16.to_le_bytes();
+        for value in [-2048, -2, 2, 0x10, 0x20, 0x400, 2046] {
+            round_trip(RiscVInstruction::CjType, &c_j, value, 12);
+        }
+        // auipc ra, 0; jalr ra, 0(ra)
+        let mut call = 0x0000_0097u32.to_le_bytes().to_vec();
+        call.extend_from_slice(&0x0000_80e7u32.to_le_bytes());
+        for value in [-0x8000_0000, -0x800, -1, 0, 0x7ff, 0x800, 0x1234_5678] {
+            round_trip(RiscVInstruction::Call, &call, value, 32);
+        }
+    }
+
+    #[test]
+    fn test_hi_lo_split() {
+        // auipc a0, 0
+        let mut auipc = 0x0000_0517u32.to_le_bytes();
+        RiscVInstruction::UType.write_to_value(0x1234_5fff, &mut auipc);
+        assert_eq!(u32::from_le_bytes(auipc), 0x1234_6517);
+        assert_eq!(RiscVInstruction::UType.read_value(&auipc), 0x1234_6000);
+
+        let mut mask = [0; 8];
+        RiscVInstruction::Call.write_mask_value(u64::from(u32::MAX), &mut mask);
+        assert_eq!(
+            u32::from_le_bytes(mask[..4].try_into().unwrap()),
+            0xffff_f000
+        );
+        assert_eq!(
+            u32::from_le_bytes(mask[4..].try_into().unwrap()),
+            0xfff0_0000
+        );
+    }
+
+    #[test]
+    fn test_uleb128() {
+        // A 3-byte encoding of zero. The length must be preserved.
+        let mut bytes = [0x80, 0x80, 0x00, 0xff];
+        RiscVInstruction::Uleb128.write_to_value(300, &mut bytes);
+        assert_eq!(bytes, [0xac, 0x82, 0x00, 0xff]);
+        assert_eq!(RiscVInstruction::Uleb128.read_value(&bytes), 300);
+
+        let mut bytes = [0x05];
+        RiscVInstruction::Set6.write_to_value(0x3f, &mut bytes);
+        assert_eq!(bytes, [0x3f]);
+    }
+}
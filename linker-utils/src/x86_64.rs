@@ -1,6 +1,15 @@
 use crate::elf::RelocationKind;
 use crate::relaxation::RelocationModifier;
 
+// Relocations for instructions with APX REX2 (CODE_4) or EVEX (CODE_6) prefixes. The `object` crate
+// doesn't define these yet.
+pub const R_X86_64_CODE_4_GOTPCRELX: u32 = 43;
+pub const R_X86_64_CODE_4_GOTTPOFF: u32 = 44;
+pub const R_X86_64_CODE_4_GOTPC32_TLSDESC: u32 = 45;
+pub const R_X86_64_CODE_6_GOTPCRELX: u32 = 49;
+pub const R_X86_64_CODE_6_GOTTPOFF: u32 = 50;
+pub const R_X86_64_CODE_6_GOTPC32_TLSDESC: u32 = 51;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RelaxationKind {
     /// Transforms a mov instruction that would have loaded an address to not use the GOT. The
@@ -21,6 +30,25 @@ pub enum RelaxationKind {
     // Transforms an indirect cmp to an absolute cmp.
     RexCmpIndirectToAbsolute,
 
+    /// As for `RexMovIndirectToAbsolute`, but for an instruction with a REX2 prefix.
+    Rex2MovIndirectToAbsolute,
+
+    /// As for `RexSubIndirectToAbsolute`, but for an instruction with a REX2 prefix.
+    Rex2SubIndirectToAbsolute,
+
+    /// As for `RexCmpIndirectToAbsolute`, but for an instruction with a REX2 prefix.
+    Rex2CmpIndirectToAbsolute,
+
+    /// Transforms an indirect add with a REX2 prefix to an absolute add.
+    Rex2AddIndirectToAbsolute,
+
+    /// Transforms an EVEX-encoded indirect add like `add *x(%rip), reg1, reg2` -> `add x, reg1,
+    /// reg2`.
+    EvexAddIndirectToAbsolute,
+
+    /// Transforms an EVEX-encoded indirect sub to an absolute sub.
+    EvexSubIndirectToAbsolute,
+
     /// Transform a call instruction like `call *x(%rip)` -> `call x(%rip)`.
     CallIndirectToRelative,
 
@@ -52,6 +80,16 @@ pub enum RelaxationKind {
     // Transform TLSDESC to initial exec.
     TlsDescToInitialExec,
 
+    /// Transform TLSDESC with a REX2 prefix to local exec. The transformation will look like `lea
+    /// x@tlsdesc(%rip), reg` -> `mov x@tpoff, reg`. The `call *(%rax)` that immediately follows
+    /// is replaced with a no-op.
+    Rex2TlsDescToLocalExec,
+
+    /// Transform TLSDESC with a REX2 prefix to initial exec. The transformation will look like
+    /// `lea x@tlsdesc(%rip), reg` -> `mov x@gottpoff(%rip), reg`. The `call *(%rax)` that
+    /// immediately follows is replaced with a no-op.
+    Rex2TlsDescToInitialExec,
+
     /// Convert a TLSDESC_CALL to a no-op.
     SkipTlsDescCall,
 }
@@ -99,6 +137,30 @@ impl RelaxationKind {
                 *mod_rm = (*mod_rm >> 3) & 0x7 | 0xf8;
                 *addend = 0;
             }
+            RelaxationKind::Rex2MovIndirectToAbsolute => {
+                rex2_indirect_to_absolute(section_bytes, offset, 0xc7, 0xc0);
+                *addend = 0;
+            }
+            RelaxationKind::Rex2SubIndirectToAbsolute => {
+                rex2_indirect_to_absolute(section_bytes, offset, 0x81, 0xe8);
+                *addend = 0;
+            }
+            RelaxationKind::Rex2CmpIndirectToAbsolute => {
+                rex2_indirect_to_absolute(section_bytes, offset, 0x81, 0xf8);
+                *addend = 0;
+            }
+            RelaxationKind::Rex2AddIndirectToAbsolute => {
+                rex2_indirect_to_absolute(section_bytes, offset, 0x81, 0xc0);
+                *addend = 0;
+            }
+            RelaxationKind::EvexAddIndirectToAbsolute => {
+                evex_indirect_to_absolute(section_bytes, offset, 0xc0);
+                *addend = 0;
+            }
+            RelaxationKind::EvexSubIndirectToAbsolute => {
+                evex_indirect_to_absolute(section_bytes, offset, 0xe8);
+                *addend = 0;
+            }
             RelaxationKind::CallIndirectToRelative => {
                 section_bytes[offset - 2..offset].copy_from_slice(&[0x67, 0xe8]);
             }
@@ -161,6 +223,18 @@ impl RelaxationKind {
                     0x66, 0x90,
                 ]);
             }
+            RelaxationKind::Rex2TlsDescToLocalExec => {
+                rex2_indirect_to_absolute(section_bytes, offset, 0xc7, 0xc0);
+                *addend = 0;
+                // xchg %ax,%ax
+                section_bytes[offset + 4..offset + 6].copy_from_slice(&[0x66, 0x90]);
+            }
+            RelaxationKind::Rex2TlsDescToInitialExec => {
+                // lea -> mov
+                section_bytes[offset - 2] = 0x8b;
+                // xchg %ax,%ax
+                section_bytes[offset + 4..offset + 6].copy_from_slice(&[0x66, 0x90]);
+            }
             RelaxationKind::SkipTlsDescCall => {
                 section_bytes[offset..offset + 2].copy_from_slice(&[
                     // xchg %ax,%ax
@@ -180,12 +254,20 @@ impl RelaxationKind {
             | RelaxationKind::TlsLdToLocalExec
             | RelaxationKind::TlsLdToLocalExec64
             | RelaxationKind::TlsDescToLocalExec
-            | RelaxationKind::TlsDescToInitialExec => RelocationModifier::SkipNextRelocation,
+            | RelaxationKind::TlsDescToInitialExec
+            | RelaxationKind::Rex2TlsDescToLocalExec
+            | RelaxationKind::Rex2TlsDescToInitialExec => RelocationModifier::SkipNextRelocation,
             RelaxationKind::MovIndirectToLea
             | RelaxationKind::MovIndirectToAbsolute
             | RelaxationKind::RexMovIndirectToAbsolute
             | RelaxationKind::RexSubIndirectToAbsolute
             | RelaxationKind::RexCmpIndirectToAbsolute
+            | RelaxationKind::Rex2MovIndirectToAbsolute
+            | RelaxationKind::Rex2SubIndirectToAbsolute
+            | RelaxationKind::Rex2CmpIndirectToAbsolute
+            | RelaxationKind::Rex2AddIndirectToAbsolute
+            | RelaxationKind::EvexAddIndirectToAbsolute
+            | RelaxationKind::EvexSubIndirectToAbsolute
             | RelaxationKind::CallIndirectToRelative
             | RelaxationKind::JmpIndirectToRelative
             | RelaxationKind::NoOp
@@ -194,6 +276,29 @@ impl RelaxationKind {
     }
 }
 
+/// Turns a PC-relative instruction with a REX2 prefix (`d5 {payload} {opcode} {modrm}`) into one
+/// that takes an immediate. The register operand moves from the ModRM reg field to the rm field, so
+/// the R3 and R4 bits of the payload become the B3 and B4 bits.
+fn rex2_indirect_to_absolute(section_bytes: &mut [u8], offset: usize, opcode: u8, mod_rm_base: u8) {
+    let payload = section_bytes[offset - 3];
+    section_bytes[offset - 3] = (payload & !0x44) | ((payload & 0x44) >> 2);
+    section_bytes[offset - 2] = opcode;
+    let mod_rm = &mut section_bytes[offset - 1];
+    *mod_rm = (*mod_rm >> 3) & 0x7 | mod_rm_base;
+}
+
+/// Turns a PC-relative EVEX-encoded (`62 {p0} {p1} {p2} {opcode} {modrm}`) arithmetic instruction
+/// into `81 /n` with an immediate. As for REX2, the register operand moves from the reg field to
+/// the rm field. In P0, R3, B3 and R4 are stored inverted, while B4 isn't.
+fn evex_indirect_to_absolute(section_bytes: &mut [u8], offset: usize, mod_rm_base: u8) {
+    let p0 = section_bytes[offset - 5];
+    // Clear R3 and R4 (set their inverted bits), then copy them into B3 and B4.
+    section_bytes[offset - 5] = ((p0 | 0x90) & !0x28) | ((p0 & 0x80) >> 2) | ((!p0 & 0x10) >> 1);
+    section_bytes[offset - 2] = 0x81;
+    let mod_rm = &mut section_bytes[offset - 1];
+    *mod_rm = (*mod_rm >> 3) & 0x7 | mod_rm_base;
+}
+
 /// Returns the supplied x86-64 relocation type split into a relocation kind and a size (in bytes)
 /// for the relocation. Returns `None` if the r_type isn't recognised.
 #[must_use]
@@ -225,11 +330,17 @@ pub fn relocation_kind_and_size(r_type: u32) -> Option<(RelocationKind, usize)>
         object::elf::R_X86_64_DTPOFF32 => (RelocationKind::DtpOff, 4),
         object::elf::R_X86_64_DTPOFF64 => (RelocationKind::DtpOff, 8),
         object::elf::R_X86_64_GOTTPOFF => (RelocationKind::GotTpOff, 4),
-        object::elf::R_X86_64_GOTPCRELX | object::elf::R_X86_64_REX_GOTPCRELX => {
-            (RelocationKind::GotRelative, 4)
-        }
+        object::elf::R_X86_64_GOTPCRELX
+        | object::elf::R_X86_64_REX_GOTPCRELX
+        | R_X86_64_CODE_4_GOTPCRELX
+        | R_X86_64_CODE_6_GOTPCRELX => (RelocationKind::GotRelative, 4),
+        R_X86_64_CODE_4_GOTTPOFF | R_X86_64_CODE_6_GOTTPOFF => (RelocationKind::GotTpOff, 4),
         object::elf::R_X86_64_TPOFF32 => (RelocationKind::TpOff, 4),
-        object::elf::R_X86_64_GOTPC32_TLSDESC => (RelocationKind::TlsDesc, 4),
+        // R_X86_64_CODE_6_GOTPC32_TLSDESC is deliberately absent. There's no EVEX-encoded form of
+        // the TLSDESC `lea`, so we don't know what instruction it would apply to.
+        object::elf::R_X86_64_GOTPC32_TLSDESC | R_X86_64_CODE_4_GOTPC32_TLSDESC => {
+            (RelocationKind::TlsDesc, 4)
+        }
         object::elf::R_X86_64_TLSDESC_CALL => (RelocationKind::TlsDescCall, 0),
         object::elf::R_X86_64_NONE => (RelocationKind::None, 0),
         _ => return None,
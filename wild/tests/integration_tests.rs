@@ -40,6 +40,14 @@
 //!
 //! Contains:{string} Checks that the output binary does contain the specified string.
 //!
+//! MaxFileSize:{bytes} Checks that the output binary is no larger than the specified size.
+//!
+//! ExpectGnuProperty:ibt|shstk|bti|pac Checks that the output's .note.gnu.property sets the
+//! specified control-flow protection feature.
+//!
+//! ExpectNoSection:{section-name} Checks that the output doesn't have a section with the specified
+//! name.
+//!
 //! Static:{bool} Only applicable when LinkerDriver=none. Defaults to true. Set to false to disable
 //! passing `-static` to the linker.
 //!
@@ -75,10 +83,14 @@
 //! Shared:{source-filename}[:extra-compilation-args] Builds the specified filename as a shared
 //! object and adds it to the link.
 //!
+//! IndirectShared:{source-filename}[:extra-compilation-args] Builds the specified filename as a
+//! shared object that isn't added to the link. Shared objects built from `Shared` directives are
+//! linked against it, so it only becomes an indirect dependency of the output.
+//!
 //! Compiler:gcc|g++|clang|clang++ Specifies what compiler should be used to compile C/C++ code.
 //!
 //! Arch:{arch1}[,{arch2}...] Specifies which architectures this test should be run with. Defaults
-//! to all supported architectures.
+//! to all supported 64 bit architectures.
 //!
 //! RequiresGlibc:{bool} Defaults to false. Set to true to disable this test if we're running on a
 //! system without glibc.
@@ -86,7 +98,13 @@
 //! RequiresClangWithTlsDesc:{bool} Defaults to false. Set to true to disable this test if we detect
 //! that the version of clang available to us doesn't support TLSDESC.
 //!
+//! RequiresAssemblerWithApx:{bool} Defaults to false. Set to true to disable this test if the
+//! assembler available to us doesn't support x86-64 APX instructions.
+//!
 //! VersionScript:{filename} Specifies a version script file that will be passed to the linker.
+//!
+//! RetainSymbolsFile:{filename} Specifies a file listing symbols that will be passed to the linker
+//! via --retain-symbols-file.
 
 use anyhow::Context;
 use anyhow::anyhow;
@@ -97,6 +115,7 @@ use object::Object as _;
 use object::ObjectSection as _;
 use object::ObjectSymbol as _;
 use object::read::elf::ProgramHeader;
+use object::read::elf::SectionHeader as _;
 use os_info::Type;
 use rstest::fixture;
 use rstest::rstest;
@@ -169,7 +188,9 @@ impl Linker {
     fn link_shared(
         &self,
         obj_paths: &[PathBuf],
+        dependencies: &[LinkerInput],
         so_path: &Path,
+        is_indirect: bool,
         config: &Config,
         cross_arch: Option<Architecture>,
     ) -> Result<LinkerInput> {
@@ -181,11 +202,26 @@ impl Linker {
 
         linker_args.args.push("-shared".to_owned());
 
+        if is_indirect {
+            // Set a soname, so that DT_NEEDED entries referring to this shared object contain just
+            // the filename, which then needs to be searched for.
+            let filename = so_path.file_name().context("Invalid shared object path")?;
+            linker_args.args.push("-soname".to_owned());
+            linker_args
+                .args
+                .push(filename.to_string_lossy().into_owned());
+        }
+
         let mut command = LinkCommand::new(
             self,
             &obj_paths
                 .iter()
                 .map(|p| LinkerInput::new(p.clone()))
+                .chain(
+                    dependencies
+                        .iter()
+                        .map(|dep| LinkerInput::new(dep.path.clone())),
+                )
                 .collect_vec(),
             so_path,
             &linker_args,
@@ -194,7 +230,8 @@ impl Linker {
         )?;
 
         if self.is_wild() || !is_newer(so_path, obj_paths.iter()) || !command.can_skip {
-            command.run(config)?;
+            // Any expected error is for the main link, not for building its shared objects.
+            command.run(None)?;
             write_cmd_file(so_path, &command.to_string())?;
         }
 
@@ -258,15 +295,51 @@ enum LinkerInvocationMode {
 enum Architecture {
     X86_64,
     AArch64,
-}
-
-const ALL_ARCHITECTURES: &[Architecture] = &[Architecture::X86_64, Architecture::AArch64];
+    RiscV64,
+    LoongArch64,
+    I386,
+    AArch64Be,
+}
+
+const ALL_ARCHITECTURES: &[Architecture] = &[
+    Architecture::X86_64,
+    Architecture::AArch64,
+    Architecture::RiscV64,
+    Architecture::LoongArch64,
+    Architecture::I386,
+    Architecture::AArch64Be,
+];
+
+/// The architectures that tests support unless they say otherwise. Tests need to opt in to i386,
+/// since most of them assume a 64 bit target, and to big-endian AArch64, since most of them assume
+/// little endian.
+const DEFAULT_ARCHITECTURES: &[Architecture] = &[
+    Architecture::X86_64,
+    Architecture::AArch64,
+    Architecture::RiscV64,
+    Architecture::LoongArch64,
+];
 
 impl Architecture {
     fn name(&self) -> &'static str {
         match self {
             Architecture::X86_64 => "x86_64",
             Architecture::AArch64 => "aarch64",
+            Architecture::RiscV64 => "riscv64",
+            Architecture::LoongArch64 => "loongarch64",
+            Architecture::I386 => "i386",
+            Architecture::AArch64Be => "aarch64_be",
+        }
+    }
+
+    /// Returns the architecture name used by cross toolchains, e.g. `i686-linux-gnu-gcc`.
+    fn toolchain_name(&self) -> &'static str {
+        match self {
+            Architecture::I386 => "i686",
+            // There's no separate big-endian toolchain. We use the little-endian one with
+            // `-mbig-endian`.
+            Architecture::AArch64Be => "aarch64",
+            _ => self.name(),
         }
     }
 
@@ -274,6 +347,10 @@ impl Architecture {
         match self {
             Architecture::X86_64 => "x86_64",
             Architecture::AArch64 => "aarch64elf",
+            Architecture::RiscV64 => "elf64lriscv",
+            Architecture::LoongArch64 => "elf64loongarch",
+            Architecture::I386 => "elf_i386",
+            Architecture::AArch64Be => "aarch64linuxb",
         }
     }
 
@@ -281,14 +358,19 @@ impl Architecture {
         match self {
             Architecture::X86_64 => "x86_64-unknown-linux-gnu",
             Architecture::AArch64 => "aarch64-unknown-linux-gnu",
+            Architecture::RiscV64 => "riscv64gc-unknown-linux-gnu",
+            Architecture::LoongArch64 => "loongarch64-unknown-linux-gnu",
+            Architecture::I386 => "i686-unknown-linux-gnu",
+            Architecture::AArch64Be => "aarch64_be-unknown-linux-gnu",
         }
     }
 
     fn get_cross_sysroot_path(&self) -> String {
+        let toolchain_name = self.toolchain_name();
         if is_host_opensuse() {
-            format!("/usr/{self}-suse-linux/sys-root")
+            format!("/usr/{toolchain_name}-suse-linux/sys-root")
         } else {
-            format!("/usr/{self}-linux-gnu")
+            format!("/usr/{toolchain_name}-linux-gnu")
         }
     }
 }
@@ -298,6 +380,10 @@ fn dynamic_linker_path(cross_arch: Option<Architecture>) -> &'static str {
         None => host_dynamic_linker_cached(),
         Some(Architecture::X86_64) => "/lib64/ld-linux-x86-64.so.2",
         Some(Architecture::AArch64) => "/lib/ld-linux-aarch64.so.1",
+        Some(Architecture::RiscV64) => "/lib/ld-linux-riscv64-lp64d.so.1",
+        Some(Architecture::LoongArch64) => "/lib64/ld-linux-loongarch-lp64d.so.1",
+        Some(Architecture::I386) => "/lib/ld-linux.so.2",
+        Some(Architecture::AArch64Be) => "/lib/ld-linux-aarch64_be.so.1",
     }
 }
 
@@ -352,6 +438,18 @@ fn get_host_architecture() -> Architecture {
     {
         return Architecture::AArch64;
     }
+    #[cfg(target_arch = "riscv64")]
+    {
+        return Architecture::RiscV64;
+    }
+    #[cfg(target_arch = "loongarch64")]
+    {
+        return Architecture::LoongArch64;
+    }
+    #[cfg(target_arch = "x86")]
+    {
+        return Architecture::I386;
+    }
     todo!("Unsupported architecture")
 }
 
@@ -389,6 +487,29 @@ fn host_supports_clang_with_tls_desc() -> bool {
     })
 }
 
+fn host_supports_assembler_with_apx() -> bool {
+    static ASSEMBLER_SUPPORTS_APX: OnceLock<bool> = OnceLock::new();
+
+    *ASSEMBLER_SUPPORTS_APX.get_or_init(|| {
+        // GNU as deletes its output file when assembly fails, so we mustn't point it at
+        // `/dev/null`, since when running as root, that would delete `/dev/null`.
+        std::fs::create_dir_all(build_dir()).expect("Failed to create build directory");
+        let mut gcc = Command::new("gcc")
+            .args(["-c", "-x", "assembler", "-", "-o"])
+            .arg(build_dir().join("apx-probe.o"))
+            .stdin(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("Failed to run gcc");
+        let mut stdin = gcc.stdin.take().expect("Failed to open stdin");
+        stdin
+            .write_all("addq x@gottpoff(%rip), %r16, %r17\n".as_bytes())
+            .expect("Write of a source file failed");
+        drop(stdin);
+        gcc.wait().expect("Wait failed").success()
+    })
+}
+
 #[derive(Clone, PartialEq, Eq)]
 struct Config {
     name: String,
@@ -414,7 +535,9 @@ struct Config {
     support_architectures: Vec<Architecture>,
     requires_glibc: bool,
     requires_clang_with_tlsdesc: bool,
+    requires_assembler_with_apx: bool,
     version_script: Option<PathBuf>,
+    retain_symbols_file: Option<PathBuf>,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -433,6 +556,7 @@ impl Config {
         !self.support_architectures.contains(&arch)
             || self.requires_glibc && !cfg!(target_env = "gnu")
             || (self.requires_clang_with_tlsdesc && !host_supports_clang_with_tls_desc())
+            || (self.requires_assembler_with_apx && !host_supports_assembler_with_apx())
             || (arch != get_host_architecture()
                 && (self.compiler == "clang" || !self.cross_enabled))
     }
@@ -483,14 +607,20 @@ impl FilenameArgumentPair {
 struct Dep {
     files: Vec<FilenameArgumentPair>,
     input_type: InputType,
+    /// Whether this is only a dependency of the shared objects that we link against.
+    indirect: bool,
 }
 
 #[derive(Default, Clone, PartialEq, Eq)]
 struct Assertions {
     expected_symtab_entries: Vec<ExpectedSymtabEntry>,
+    expected_local_symbols: Vec<String>,
     expected_comments: Vec<String>,
     does_not_contain: Vec<String>,
     contains_strings: Vec<String>,
+    max_file_size: Option<u64>,
+    expected_gnu_properties: Vec<String>,
+    unexpected_sections: Vec<String>,
 }
 
 #[derive(Clone, PartialEq, Eq)]
@@ -576,10 +706,12 @@ impl Default for Config {
             should_run: true,
             expect_error: None,
             cross_enabled: true,
-            support_architectures: ALL_ARCHITECTURES.to_owned(),
+            support_architectures: DEFAULT_ARCHITECTURES.to_owned(),
             requires_glibc: false,
             requires_clang_with_tlsdesc: false,
+            requires_assembler_with_apx: false,
             version_script: None,
+            retain_symbols_file: None,
         }
     }
 }
@@ -656,6 +788,10 @@ fn parse_configs(src_filename: &Path) -> Result<Vec<Config>> {
                     .assertions
                     .expected_symtab_entries
                     .push(ExpectedSymtabEntry::parse(arg.trim())?),
+                "ExpectLocalSym" => config
+                    .assertions
+                    .expected_local_symbols
+                    .push(arg.trim().to_owned()),
                 "ExpectComment" => config
                     .assertions
                     .expected_comments
@@ -668,6 +804,18 @@ fn parse_configs(src_filename: &Path) -> Result<Vec<Config>> {
                     .assertions
                     .contains_strings
                     .push(arg.trim().to_owned()),
+                "ExpectGnuProperty" => config
+                    .assertions
+                    .expected_gnu_properties
+                    .push(arg.trim().to_owned()),
+                "ExpectNoSection" => config
+                    .assertions
+                    .unexpected_sections
+                    .push(arg.trim().to_owned()),
+                "MaxFileSize" => {
+                    config.assertions.max_file_size =
+                        Some(arg.parse().context("Invalid size for MaxFileSize")?)
+                }
                 "Static" => config.linker_driver.direct_mut()?.is_static = arg.parse()?,
                 "DiffIgnore" => config.diff_ignore.push(arg.trim().to_owned()),
                 "DiffEnabled" => {
@@ -701,8 +849,14 @@ fn parse_configs(src_filename: &Path) -> Result<Vec<Config>> {
                         .ok_or_else(|| anyhow!("DiffIgnore missing '='"))
                         .map(|(a, b)| (a.to_owned(), b.to_owned()))?,
                 ),
-                input_type @ ("Object" | "Archive" | "ThinArchive" | "Shared") => {
-                    let input_type = InputType::from_str(input_type)?;
+                directive
+                @ ("Object" | "Archive" | "ThinArchive" | "Shared" | "IndirectShared") => {
+                    let indirect = directive == "IndirectShared";
+                    let input_type = if indirect {
+                        InputType::SharedObject
+                    } else {
+                        InputType::from_str(directive)?
+                    };
                     let files = arg
                         .split(",")
                         .map(|arg| {
@@ -714,7 +868,11 @@ fn parse_configs(src_filename: &Path) -> Result<Vec<Config>> {
                         })
                         .collect::<Result<Vec<_>>>()?;
 
-                    config.deps.push(Dep { files, input_type })
+                    config.deps.push(Dep {
+                        files,
+                        input_type,
+                        indirect,
+                    })
                 }
                 "Compiler" => config.compiler = arg.trim().to_owned(),
                 "Arch" => {
@@ -726,6 +884,10 @@ fn parse_configs(src_filename: &Path) -> Result<Vec<Config>> {
                             match arch.as_str() {
                                 "x86_64" => Ok(Architecture::X86_64),
                                 "aarch64" => Ok(Architecture::AArch64),
+                                "riscv64" => Ok(Architecture::RiscV64),
+                                "loongarch64" => Ok(Architecture::LoongArch64),
+                                "i386" => Ok(Architecture::I386),
+                                "aarch64_be" => Ok(Architecture::AArch64Be),
                                 _ => Err(anyhow!(format!("Unsupported architecture: `{}`", arch))),
                             }
                         })
@@ -735,9 +897,13 @@ fn parse_configs(src_filename: &Path) -> Result<Vec<Config>> {
                 "RequiresClangWithTlsDesc" => {
                     config.requires_clang_with_tlsdesc = arg.to_lowercase().parse()?;
                 }
+                "RequiresAssemblerWithApx" => {
+                    config.requires_assembler_with_apx = arg.to_lowercase().parse()?;
+                }
                 "VersionScript" => {
                     config.version_script = Some(src_path(&arg.trim().to_lowercase()))
                 }
+                "RetainSymbolsFile" => config.retain_symbols_file = Some(src_path(arg.trim())),
                 other => bail!("{}: Unknown directive '{other}'", src_filename.display()),
             }
         }
@@ -767,6 +933,12 @@ impl ProgramInputs {
         config: &'a Config,
         cross_arch: Option<Architecture>,
     ) -> Result<Program<'a>> {
+        let indirect_inputs = config
+            .deps
+            .iter()
+            .filter(|dep| dep.indirect)
+            .map(|dep| build_linker_input(dep, &[], config, linker, cross_arch))
+            .collect::<Result<Vec<_>>>()?;
         let primary = build_linker_input(
             &Dep {
                 files: vec![FilenameArgumentPair::new(
@@ -774,24 +946,25 @@ impl ProgramInputs {
                     ArgumentSet::empty(),
                 )],
                 input_type: InputType::Object,
+                indirect: false,
             },
+            &[],
             config,
             linker,
             cross_arch,
         );
-        let inputs = std::iter::once(primary)
-            .chain(
-                config
-                    .deps
-                    .iter()
-                    .map(|dep| build_linker_input(dep, config, linker, cross_arch)),
-            )
-            .collect::<Result<Vec<_>>>()?;
+        let inputs =
+            std::iter::once(primary)
+                .chain(config.deps.iter().filter(|dep| !dep.indirect).map(|dep| {
+                    build_linker_input(dep, &indirect_inputs, config, linker, cross_arch)
+                }))
+                .collect::<Result<Vec<_>>>()?;
 
         let link_output = linker.link(self.name(), &inputs, config, cross_arch)?;
         let shared_objects = inputs
             .into_iter()
             .filter(|input| input.path.extension().is_some_and(|ext| ext == "so"))
+            .chain(indirect_inputs)
             .collect();
         Ok(Program {
             link_output,
@@ -806,10 +979,14 @@ impl ProgramInputs {
 }
 
 impl Program<'_> {
-    fn run(&self, cross_arch: Option<Architecture>) -> Result {
+    fn check_assertions(&self) -> Result {
         self.assertions
             .check(&self.link_output)
-            .context("Output binary assertions failed")?;
+            .context("Output binary assertions failed")
+    }
+
+    fn run(&self, cross_arch: Option<Architecture>) -> Result {
+        self.check_assertions()?;
 
         let mut command = if let Some(arch) = cross_arch {
             let mut c = Command::new(format!("qemu-{arch}"));
@@ -919,9 +1096,11 @@ impl LinkerInput {
     }
 }
 
-/// Creates a linker input from a source file. This will be either an object file or an archive.
+/// Creates a linker input from a source file. This will be either an object file, an archive or a
+/// shared object. Shared objects are linked against `indirect_inputs`.
 fn build_linker_input(
     dep: &Dep,
+    indirect_inputs: &[LinkerInput],
     config: &Config,
     linker: &Linker,
     cross_arch: Option<Architecture>,
@@ -964,7 +1143,14 @@ fn build_linker_input(
         }
         InputType::SharedObject => {
             let so_path = first_obj_path.with_extension(format!("{linker}.so"));
-            let out = linker.link_shared(&obj_paths, &so_path, config, cross_arch)?;
+            let out = linker.link_shared(
+                &obj_paths,
+                indirect_inputs,
+                &so_path,
+                dep.indirect,
+                config,
+                cross_arch,
+            )?;
             let assertions = Assertions::default();
             assertions
                 .check_path(&out.path, linker)
@@ -996,15 +1182,53 @@ fn get_c_compiler(
         (None, "gcc", CLanguage::Cpp) => Ok("g++"),
         (None, "clang", CLanguage::C) => Ok("clang"),
         (None, "clang", CLanguage::Cpp) => Ok("clang++"),
-        (Some(Architecture::AArch64), "gcc" | "g++", CLanguage::C) => Ok(if is_host_opensuse() {
-            "aarch64-suse-linux-gcc"
+        (Some(Architecture::AArch64 | Architecture::AArch64Be), "gcc" | "g++", CLanguage::C) => {
+            Ok(if is_host_opensuse() {
+                "aarch64-suse-linux-gcc"
+            } else {
+                "aarch64-linux-gnu-gcc"
+            })
+        }
+        (Some(Architecture::AArch64 | Architecture::AArch64Be), "gcc" | "g++", CLanguage::Cpp) => {
+            Ok(if is_host_opensuse() {
+                "aarch64-suse-linux-g++"
+            } else {
+                "aarch64-linux-gnu-g++"
+            })
+        }
+        (Some(Architecture::RiscV64), "gcc" | "g++", CLanguage::C) => Ok(if is_host_opensuse() {
+            "riscv64-suse-linux-gcc"
         } else {
-            "aarch64-linux-gnu-gcc"
+            "riscv64-linux-gnu-gcc"
         }),
-        (Some(Architecture::AArch64), "gcc" | "g++", CLanguage::Cpp) => Ok(if is_host_opensuse() {
-            "aarch64-suse-linux-g++"
+        (Some(Architecture::RiscV64), "gcc" | "g++", CLanguage::Cpp) => Ok(if is_host_opensuse() {
+            "riscv64-suse-linux-g++"
         } else {
-            "aarch64-linux-gnu-g++"
+            "riscv64-linux-gnu-g++"
+        }),
+        (Some(Architecture::LoongArch64), "gcc" | "g++", CLanguage::C) => {
+            Ok(if is_host_opensuse() {
+                "loongarch64-suse-linux-gcc"
+            } else {
+                "loongarch64-linux-gnu-gcc"
+            })
+        }
+        (Some(Architecture::LoongArch64), "gcc" | "g++", CLanguage::Cpp) => {
+            Ok(if is_host_opensuse() {
+                "loongarch64-suse-linux-g++"
+            } else {
+                "loongarch64-linux-gnu-g++"
+            })
+        }
+        (Some(Architecture::I386), "gcc" | "g++", CLanguage::C) => Ok(if is_host_opensuse() {
+            "i686-suse-linux-gcc"
+        } else {
+            "i686-linux-gnu-gcc"
+        }),
+        (Some(Architecture::I386), "gcc" | "g++", CLanguage::Cpp) => Ok(if is_host_opensuse() {
+            "i686-suse-linux-g++"
+        } else {
+            "i686-linux-gnu-g++"
         }),
         _ => bail!("Unsupported compiler and or architecture `{compiler}` / {cross_arch:?}"),
     }
@@ -1069,6 +1293,9 @@ fn build_obj(
             // than trying to continue and getting a harder-to-diagnose failure.
             command.arg("-Werror=attributes");
             command.arg("-c");
+            if cross_arch == Some(Architecture::AArch64Be) {
+                command.arg("-mbig-endian");
+            }
         }
         CompilerKind::Rust => {
             let wild = wild_path().to_str().context("Need UTF-8 path")?.to_owned();
@@ -1307,7 +1534,7 @@ impl Linker {
         let mut command =
             LinkCommand::new(self, inputs, &output_path, &linker_args, config, cross_arch)?;
         if !command.can_skip {
-            command.run(config)?;
+            command.run(config.expect_error.as_deref())?;
             write_cmd_file(&output_path, &command.to_string())?;
         }
         Ok(LinkOutput {
@@ -1438,6 +1665,13 @@ impl LinkCommand {
                         command.arg(format!("-Wl,--version-script={}", version_script.display()));
                     }
 
+                    if let Some(retain_symbols_file) = &config.retain_symbols_file {
+                        command.arg(format!(
+                            "-Wl,--retain-symbols-file={}",
+                            retain_symbols_file.display()
+                        ));
+                    }
+
                     command.args(&linker_args.args);
                 }
                 LinkerDriver::Direct(direct_config) => {
@@ -1459,6 +1693,13 @@ impl LinkCommand {
                         command.arg(format!("--version-script={}", version_script.display()));
                     }
 
+                    if let Some(retain_symbols_file) = &config.retain_symbols_file {
+                        command.arg(format!(
+                            "--retain-symbols-file={}",
+                            retain_symbols_file.display()
+                        ));
+                    }
+
                     command.arg("--gc-sections").args(&linker_args.args);
                 }
             }
@@ -1512,14 +1753,15 @@ impl LinkCommand {
         let can_skip = !matches!(linker, Linker::Wild)
             && is_newer(output_path, inputs.iter().map(|i| i.path.as_path()))
             && is_newer(output_path, config.version_script.iter())
+            && is_newer(output_path, config.retain_symbols_file.iter())
             && cmd_file_is_current(output_path, &link_command.to_string());
         link_command.can_skip = can_skip;
 
         Ok(link_command)
     }
 
-    fn run(&mut self, config: &Config) -> Result {
-        if let Some(expected_error) = config.expect_error.as_ref() {
+    fn run(&mut self, expect_error: Option<&str>) -> Result {
+        if let Some(expected_error) = expect_error {
             let output = self
                 .command
                 .output()
@@ -1597,15 +1839,18 @@ impl Assertions {
 
     fn check_path(&self, path: &PathBuf, linker_used: &Linker) -> Result {
         let bytes = std::fs::read(path)?;
-        let obj = ElfFile64::parse(bytes.as_slice())?;
+        let obj = object::File::parse(bytes.as_slice())?;
 
         self.verify_symbol_assertions(&obj)?;
+        self.verify_sections(&obj)?;
         self.verify_comment_section(&obj, linker_used)?;
         self.verify_strings(&bytes)?;
+        self.verify_file_size(&bytes)?;
+        self.verify_gnu_properties(&bytes)?;
         Ok(())
     }
 
-    fn verify_symbol_assertions(&self, obj: &ElfFile64<'_>) -> Result {
+    fn verify_symbol_assertions(&self, obj: &object::File<'_>) -> Result {
         let mut missing = self
             .expected_symtab_entries
             .iter()
@@ -1633,10 +1878,27 @@ impl Assertions {
         if !missing.is_empty() {
             bail!("Missing expected symbol(s): {}", missing.join(", "));
         };
+        for name in &self.expected_local_symbols {
+            let Some(sym) = obj.symbols().find(|sym| sym.name() == Ok(name.as_str())) else {
+                bail!("Missing expected local symbol `{name}`");
+            };
+            if !sym.is_local() {
+                bail!("Expected symbol `{name}` to be local");
+            }
+        }
         Ok(())
     }
 
-    fn verify_comment_section(&self, obj: &ElfFile64, linker_used: &Linker) -> Result {
+    fn verify_sections(&self, obj: &object::File<'_>) -> Result {
+        for name in &self.unexpected_sections {
+            if obj.section_by_name(name).is_some() {
+                bail!("Output has section `{name}` when it shouldn't");
+            }
+        }
+        Ok(())
+    }
+
+    fn verify_comment_section(&self, obj: &object::File, linker_used: &Linker) -> Result {
         if self.expected_comments.is_empty() {
             match linker_used {
                 Linker::Wild => {
@@ -1685,10 +1947,72 @@ impl Assertions {
         }
         Ok(())
     }
+
+    fn verify_gnu_properties(&self, bytes: &[u8]) -> Result {
+        if self.expected_gnu_properties.is_empty() {
+            return Ok(());
+        }
+        let file = ElfFile64::parse(bytes)?;
+        let mut properties = Vec::new();
+        for section in file.sections() {
+            let Some(mut notes) = section.elf_section_header().notes(LittleEndian, bytes)? else {
+                continue;
+            };
+            while let Some(note) = notes.next()? {
+                let Some(mut gnu_properties) = note.gnu_properties(LittleEndian) else {
+                    continue;
+                };
+                while let Some(property) = gnu_properties.next()? {
+                    properties.push((property.pr_type(), property.data_u32(LittleEndian)?));
+                }
+            }
+        }
+        for name in &self.expected_gnu_properties {
+            let (pr_type, bit) = match name.as_str() {
+                "ibt" => (
+                    object::elf::GNU_PROPERTY_X86_FEATURE_1_AND,
+                    object::elf::GNU_PROPERTY_X86_FEATURE_1_IBT,
+                ),
+                "shstk" => (
+                    object::elf::GNU_PROPERTY_X86_FEATURE_1_AND,
+                    object::elf::GNU_PROPERTY_X86_FEATURE_1_SHSTK,
+                ),
+                "bti" => (
+                    object::elf::GNU_PROPERTY_AARCH64_FEATURE_1_AND,
+                    object::elf::GNU_PROPERTY_AARCH64_FEATURE_1_BTI,
+                ),
+                "pac" => (
+                    object::elf::GNU_PROPERTY_AARCH64_FEATURE_1_AND,
+                    object::elf::GNU_PROPERTY_AARCH64_FEATURE_1_PAC,
+                ),
+                other => bail!("Unknown GNU property `{other}`"),
+            };
+            if !properties
+                .iter()
+                .any(|&(t, data)| t == pr_type && data & bit != 0)
+            {
+                bail!("Expected .note.gnu.property to set `{name}`");
+            }
+        }
+        Ok(())
+    }
+
+    fn verify_file_size(&self, bytes: &[u8]) -> Result {
+        if let Some(max_file_size) = self.max_file_size {
+            if bytes.len() as u64 > max_file_size {
+                bail!(
+                    "Binary is {} bytes, which is more than the expected maximum of \
+                     {max_file_size}",
+                    bytes.len()
+                );
+            }
+        }
+        Ok(())
+    }
 }
 
 /// Returns whether the supplied object indicates that it was linked with wild.
-fn was_linked_with_wild(obj: &ElfFile64) -> bool {
+fn was_linked_with_wild(obj: &object::File) -> bool {
     let Ok(actual_comments) = read_comments(obj) else {
         return false;
     };
@@ -1697,7 +2021,7 @@ fn was_linked_with_wild(obj: &ElfFile64) -> bool {
         .any(|comment| comment.starts_with("Linker: Wild version"))
 }
 
-fn read_comments<'data>(obj: &ElfFile64<'data>) -> Result<Vec<std::borrow::Cow<'data, str>>> {
+fn read_comments<'data>(obj: &object::File<'data>) -> Result<Vec<std::borrow::Cow<'data, str>>> {
     let comment_section = obj
         .section_by_name(".comment")
         .context("Missing .comment section")?;
@@ -1911,7 +2235,8 @@ fn diff_executables(instructions: &Config, programs: &[Program]) -> Result {
 
 /// Diff the supplied files. The last file should be the one that we produced.
 fn diff_files(instructions: &Config, files: Vec<PathBuf>, display: &dyn Display) -> Result {
-    if !instructions.should_diff {
+    // If all the reference linkers were skipped, then there's nothing to compare against.
+    if !instructions.should_diff || files.len() < 2 {
         return Ok(());
     }
 
@@ -1974,21 +2299,28 @@ fn find_bin(names: &[&str]) -> Result<PathBuf> {
 }
 
 fn find_cross_paths(name: &str) -> HashMap<Architecture, PathBuf> {
-    [Architecture::AArch64]
-        .into_iter()
-        .filter_map(|arch| {
-            let path = PathBuf::from(if is_host_opensuse() {
-                format!("/usr/{arch}-suse-linux/bin/{name}")
-            } else {
-                format!("/usr/{arch}-linux-gnu/bin/{name}")
-            });
-            if path.exists() {
-                Some((arch, path))
-            } else {
-                None
-            }
-        })
-        .collect()
+    [
+        Architecture::AArch64,
+        Architecture::RiscV64,
+        Architecture::LoongArch64,
+        Architecture::I386,
+        Architecture::AArch64Be,
+    ]
+    .into_iter()
+    .filter_map(|arch| {
+        let toolchain_name = arch.toolchain_name();
+        let path = PathBuf::from(if is_host_opensuse() {
+            format!("/usr/{toolchain_name}-suse-linux/bin/{name}")
+        } else {
+            format!("/usr/{toolchain_name}-linux-gnu/bin/{name}")
+        });
+        if path.exists() {
+            Some((arch, path))
+        } else {
+            None
+        }
+    })
+    .collect()
 }
 
 static INIT: Once = Once::new();
@@ -2068,6 +2400,11 @@ fn run_with_config(
         config.enabled_linkers.remove("lld");
     }
 
+    // linker-diff only supports 64 bit little-endian ELF files.
+    if matches!(arch, Architecture::I386 | Architecture::AArch64Be) {
+        config.should_diff = false;
+    }
+
     let programs = linkers
         .iter()
         .filter(|linker| config.is_linker_enabled(linker))
@@ -2111,12 +2448,20 @@ fn run_with_config(
         );
     }
 
-    if config.should_run {
+    // When cross compiling, we can only run the output if qemu-user is available for the target.
+    let can_run = cross_arch.is_none_or(|arch| which::which(format!("qemu-{arch}")).is_ok());
+
+    if config.should_run && can_run {
         for program in programs {
             program
                 .run(cross_arch)
                 .with_context(|| format!("Failed to run program. {program}"))?;
         }
+    } else if config.assertions != Assertions::default() {
+        // Outputs that we don't run still need to satisfy any assertions that were specified.
+        for program in &programs {
+            program.check_assertions()?;
+        }
     }
 
     Ok(())
@@ -2166,10 +2511,35 @@ fn integration_test(
         "input_does_not_exist.c",
         "ifunc2.c",
         "tls-local-exec.c",
+        "tls-shared-alignment.c",
         "undefined_symbols.c",
+        "undefined-suggestions.c",
         "whole_archive.c",
         "shared.c",
-        "duplicate_strong_symbols.c"
+        "shlib-undefined.c",
+        "transitive-needed.c",
+        "section-start.c",
+        "discard-locals.c",
+        "version-script-patterns.c",
+        "dynamic-flags.c",
+        "text-relocations.s",
+        "cet.c",
+        "init-priority.c",
+        "gc-retain.c",
+        "gnu-warning.c",
+        "sort-section.c",
+        "aarch64-thunks.c",
+        "aarch64-erratum-843419.c",
+        "aarch64-bti.c",
+        "aarch64_be-basic.s",
+        "aarch64_be-relocs.s",
+        "riscv64-relax.c",
+        "loongarch64-relax.c",
+        "i386-basic.c",
+        "i386-tls.c",
+        "x86_64-apx.s",
+        "duplicate_strong_symbols.c",
+        "section-groups.c"
     )]
     program_name: &'static str,
     #[allow(unused_variables)] setup_symlink: (),
@@ -2184,12 +2554,15 @@ fn integration_test(
 
     let host_arch = get_host_architecture();
 
-    // We only currently support cross compilation from x86_64 to aarch64, so we don't need to track
-    // which targets are enabled, since there's only one.
-    let cross_enabled = std::env::var("WILD_TEST_CROSS").is_ok_and(|v| v == "aarch64");
+    // A comma-separated list of architectures that we cross compile for in addition to the host.
+    let cross_archs = std::env::var("WILD_TEST_CROSS").unwrap_or_default();
 
     for &arch in ALL_ARCHITECTURES {
-        if arch != host_arch && !cross_enabled {
+        if arch != host_arch
+            && !cross_archs
+                .split(',')
+                .any(|name| name.trim() == arch.name())
+        {
             continue;
         }
 
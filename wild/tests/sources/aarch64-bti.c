@@ -0,0 +1,40 @@
+// Tests the AArch64 options that control branch target identification (BTI) and pointer
+// authentication of PLT entries. Our inputs are compiled without BTI, so the property only gets set
+// and PLT entries only get a landing pad if we force them. linker-diff checks that the PLT entries
+// that we write still resolve to the right GOT entries.
+
+//#AbstractConfig:default
+//#Object:exit.c
+//#Static:false
+//#Shared:trivial-dynamic-2.c
+//#Arch:aarch64
+//#CompArgs:-mbranch-protection=none
+// We're linking different .so files, so this is expected.
+//#DiffIgnore:.dynamic.DT_NEEDED
+//#DiffIgnore:.dynamic.DT_RELA
+//#DiffIgnore:.dynamic.DT_RELAENT
+//#DiffIgnore:section.got
+// GNU ld adds DT_AARCH64_BTI_PLT and DT_AARCH64_PAC_PLT, which we don't.
+//#DiffIgnore:.dynamic.Unknown*
+
+//#Config:force-bti:default
+//#LinkArgs:-z now -z force-bti
+//#ExpectGnuProperty:bti
+
+//#Config:pac-plt:default
+//#LinkArgs:-z now -z pac-plt
+
+//#Config:force-bti-pac-plt:default
+//#LinkArgs:-z now -z force-bti -z pac-plt
+//#ExpectGnuProperty:bti
+
+#include "exit.h"
+
+int foo(void);
+
+void _start(void) {
+    if (foo() != 10) {
+        exit_syscall(20);
+    }
+    exit_syscall(42);
+}
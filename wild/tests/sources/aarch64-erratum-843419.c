@@ -0,0 +1,65 @@
+// Tests the workaround for Cortex-A53 erratum 843419, where a load or store that uses an address
+// computed by an `adrp` at offset 0xff8 or 0xffc in a page can access the wrong address.
+
+//#AbstractConfig:default
+//#Object:exit.c
+//#LinkArgs:--fix-cortex-a53-843419
+//#DiffEnabled:false
+//#Arch: aarch64
+
+// The data is close enough that the `adrp` can be replaced with an `adr`.
+//#Config:adr:default
+
+// The data is too far away for an `adr`, so the load gets moved to a patch.
+//#Config:patch:default
+//#CompArgs:-DFAR
+//#LinkArgs:--fix-cortex-a53-843419 --section-start=.fardata=0x20000000
+
+#include "exit.h"
+
+#include <stdint.h>
+
+#ifdef FAR
+__attribute__((section(".fardata")))
+#endif
+long value = 42;
+
+long load_value(void);
+extern const uint32_t erratum_adrp[];
+extern const uint32_t erratum_load[];
+
+// Puts the `adrp` at offset 0xff8 within a page, followed by a load that doesn't write x0 and a
+// load that uses x0 as its base.
+__asm__(".section .text.erratum,\"ax\",%progbits\n"
+        ".p2align 12\n"
+        ".global load_value\n"
+        ".type load_value, %function\n"
+        "load_value:\n"
+        "  .rept (0xff8 / 4)\n"
+        "  nop\n"
+        "  .endr\n"
+        "erratum_adrp:\n"
+        "  adrp x0, value\n"
+        "  ldr x1, [sp]\n"
+        "erratum_load:\n"
+        "  ldr x0, [x0, :lo12:value]\n"
+        "  ret\n"
+        ".text\n");
+
+void _start(void) {
+    if (load_value() != 42) {
+        exit_syscall(10);
+    }
+#ifdef FAR
+    // The load should have been replaced with a branch to a patch.
+    if ((erratum_load[0] & 0xfc000000) != 0x14000000) {
+        exit_syscall(11);
+    }
+#else
+    // The `adrp` should have been replaced with an `adr`.
+    if ((erratum_adrp[0] & 0x9f000000) != 0x10000000) {
+        exit_syscall(12);
+    }
+#endif
+    exit_syscall(42);
+}
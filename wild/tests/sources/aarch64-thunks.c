@@ -0,0 +1,70 @@
+// Tests range-extension thunks for AArch64 branches whose targets are out of range.
+
+//#AbstractConfig:default
+//#Object:exit.c
+//#LinkArgs:--section-start=.far=0x20000000
+//#DiffEnabled:false
+//#Arch: aarch64
+//#MaxFileSize:200000
+
+//#Config:unconditional:default
+
+//#Config:conditional:default
+//#CompArgs:-DCONDITIONAL
+//#SkipLinker:ld
+
+#include "exit.h"
+
+#include <stdint.h>
+
+int near_fn(int x);
+
+__attribute__((noinline, section(".far"))) int far_fn(int x) {
+    // Branch back from `.far` into `.text`, which also needs a thunk.
+    return near_fn(x) + 1;
+}
+
+__attribute__((noinline)) int near_fn(int x) { return x + 10; }
+
+#ifdef CONDITIONAL
+int is_zero(int x);
+int is_even(int x);
+
+// Conditional branches to `far_one` are out of range of `cbz` and `tbz`.
+__asm__(".text\n"
+        ".global is_zero\n"
+        ".type is_zero, %function\n"
+        "is_zero:\n"
+        "  cbz w0, far_one\n"
+        "  mov w0, #0\n"
+        "  ret\n"
+        ".global is_even\n"
+        ".type is_even, %function\n"
+        "is_even:\n"
+        "  tbz w0, #0, far_one\n"
+        "  mov w0, #0\n"
+        "  ret\n"
+        ".section .far,\"ax\",%progbits\n"
+        "far_one:\n"
+        "  mov w0, #1\n"
+        "  ret\n"
+        ".text\n");
+#endif
+
+void _start(void) {
+    if ((uintptr_t)&far_fn < 0x20000000 || (uintptr_t)&far_fn >= 0x20001000) {
+        exit_syscall(10);
+    }
+    if (far_fn(5) != 16) {
+        exit_syscall(11);
+    }
+#ifdef CONDITIONAL
+    if (is_zero(0) != 1 || is_zero(3) != 0) {
+        exit_syscall(12);
+    }
+    if (is_even(4) != 1 || is_even(7) != 0) {
+        exit_syscall(13);
+    }
+#endif
+    exit_syscall(42);
+}
@@ -0,0 +1,37 @@
+// Tests a basic statically linked big-endian AArch64 executable. Data is big endian, whereas
+// instructions are always little endian, so this checks that we get both right.
+
+//#LinkArgs:-z noexecstack
+//#Arch: aarch64_be
+
+.section .text, "ax", @progbits
+.globl _start
+.type _start, @function
+_start:
+    mov     x0, #10
+    adrp    x1, value
+    add     x1, x1, :lo12:value
+    ldr     x2, [x1]
+    movz    x3, #0x1122, lsl #48
+    movk    x3, #0x3344, lsl #32
+    movk    x3, #0x5566, lsl #16
+    movk    x3, #0x7788
+    cmp     x2, x3
+    b.ne    exit
+
+    // The most significant byte comes first.
+    mov     x0, #11
+    ldrb    w2, [x1]
+    cmp     w2, #0x11
+    b.ne    exit
+
+    mov     x0, #42
+exit:
+    mov     x8, #93
+    svc     #0
+.size _start, .-_start
+
+.section .data, "aw", @progbits
+.p2align 3
+value:
+    .quad 0x1122334455667788
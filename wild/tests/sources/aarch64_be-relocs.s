@@ -0,0 +1,173 @@
+// Tests that relocations are applied correctly in a statically linked big-endian AArch64
+// executable. Relocations that patch instructions need to write little endian, while those that
+// patch data, GOT entries included, need to write big endian.
+
+//#LinkArgs:-z noexecstack
+//#Arch: aarch64_be
+
+.section .text, "ax", @progbits
+.globl _start
+.type _start, @function
+_start:
+    // R_AARCH64_ADR_PREL_PG_HI21 and R_AARCH64_ADD_ABS_LO12_NC. We use this as the expected
+    // address of `target` below.
+    adrp    x19, target
+    add     x19, x19, :lo12:target
+
+    // R_AARCH64_ADR_PREL_LO21
+    mov     x0, #10
+    adr     x1, target
+    cmp     x1, x19
+    b.ne    exit
+
+    // R_AARCH64_MOVW_UABS_G0_NC to R_AARCH64_MOVW_UABS_G3
+    mov     x0, #11
+    movz    x1, #:abs_g3:target
+    movk    x1, #:abs_g2_nc:target
+    movk    x1, #:abs_g1_nc:target
+    movk    x1, #:abs_g0_nc:target
+    cmp     x1, x19
+    b.ne    exit
+
+    // R_AARCH64_ABS64
+    mov     x0, #12
+    adrp    x2, abs64
+    // R_AARCH64_LDST64_ABS_LO12_NC
+    ldr     x1, [x2, :lo12:abs64]
+    cmp     x1, x19
+    b.ne    exit
+
+    // R_AARCH64_LD_PREL_LO19
+    mov     x0, #13
+    ldr     x1, abs64
+    cmp     x1, x19
+    b.ne    exit
+
+    // R_AARCH64_ABS32
+    mov     x0, #14
+    adrp    x2, abs32
+    // R_AARCH64_LDST32_ABS_LO12_NC
+    ldr     w1, [x2, :lo12:abs32]
+    cmp     x1, x19
+    b.ne    exit
+
+    // R_AARCH64_PREL32
+    mov     x0, #15
+    adrp    x2, prel32
+    add     x2, x2, :lo12:prel32
+    ldrsw   x1, [x2]
+    add     x1, x1, x2
+    cmp     x1, x19
+    b.ne    exit
+
+    // R_AARCH64_PREL64
+    mov     x0, #16
+    adrp    x2, prel64
+    add     x2, x2, :lo12:prel64
+    ldr     x1, [x2]
+    add     x1, x1, x2
+    cmp     x1, x19
+    b.ne    exit
+
+    // R_AARCH64_LDST16_ABS_LO12_NC and R_AARCH64_LDST8_ABS_LO12_NC reading big-endian data.
+    mov     x0, #17
+    adrp    x2, halfword
+    ldrh    w1, [x2, :lo12:halfword]
+    mov     w3, #0x1234
+    cmp     w1, w3
+    b.ne    exit
+    adrp    x2, halfword
+    ldrb    w1, [x2, :lo12:halfword]
+    cmp     w1, #0x12
+    b.ne    exit
+
+    // R_AARCH64_ADR_GOT_PAGE and R_AARCH64_LD64_GOT_LO12_NC
+    mov     x0, #18
+    adrp    x1, :got:target
+    ldr     x1, [x1, :got_lo12:target]
+    cmp     x1, x19
+    b.ne    exit
+
+    // R_AARCH64_CALL26, R_AARCH64_JUMP26, R_AARCH64_CONDBR19 and R_AARCH64_TSTBR14
+    mov     x0, #19
+    bl      return_7
+    cmp     x1, #7
+    b.ne    exit
+    mov     x0, #20
+    mov     x1, #1
+    tbz     x1, #0, exit
+    cbz     x1, exit
+
+    // Thread pointer setup. The TLS block comes after a 16 byte TCB.
+    adrp    x20, tls_area
+    add     x20, x20, :lo12:tls_area
+    msr     tpidr_el0, x20
+    add     x20, x20, #16
+
+    // R_AARCH64_TLSLE_ADD_TPREL_HI12 and R_AARCH64_TLSLE_ADD_TPREL_LO12_NC
+    mov     x0, #21
+    mrs     x1, tpidr_el0
+    add     x1, x1, :tprel_hi12:tvar
+    add     x1, x1, :tprel_lo12_nc:tvar
+    cmp     x1, x20
+    b.ne    exit
+
+    // R_AARCH64_TLSIE_ADR_GOTTPREL_PAGE21 and R_AARCH64_TLSIE_LD64_GOTTPREL_LO12_NC
+    mov     x0, #22
+    mrs     x2, tpidr_el0
+    adrp    x1, :gottprel:tvar
+    ldr     x1, [x1, :gottprel_lo12:tvar]
+    add     x1, x1, x2
+    cmp     x1, x20
+    b.ne    exit
+
+    mov     x0, #42
+    b       exit
+.size _start, .-_start
+
+// A separate section, so that branches to these functions need relocations.
+.section .text.helpers, "ax", @progbits
+.globl return_7
+.type return_7, @function
+return_7:
+    mov     x1, #7
+    ret
+.size return_7, .-return_7
+
+.globl exit
+.type exit, @function
+exit:
+    mov     x8, #93
+    svc     #0
+.size exit, .-exit
+
+.section .rodata, "a", @progbits
+.p2align 3
+.globl target
+target:
+    .quad 0
+
+.section .data, "aw", @progbits
+.p2align 3
+abs64:
+    .quad target
+abs32:
+    .word target
+prel32:
+    .word target - .
+.p2align 3
+prel64:
+    .quad target - .
+halfword:
+    .hword 0x1234
+
+.section .bss, "aw", @nobits
+.p2align 4
+tls_area:
+    .zero 64
+
+.section .tbss, "awT", @nobits
+.p2align 3
+.globl tvar
+tvar:
+    .zero 8
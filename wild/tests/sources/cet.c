@@ -0,0 +1,29 @@
+// Tests options that control the x86 control-flow enforcement (CET) properties that we write to
+// .note.gnu.property. Our inputs are compiled without CET, so the properties only get set if we
+// force them.
+
+//#AbstractConfig:default
+//#Object:exit.c
+//#CompArgs:-fcf-protection=none
+//#Arch:x86_64
+
+//#Config:force:default
+//#LinkArgs:-z ibt -z shstk
+//#ExpectGnuProperty:ibt
+//#ExpectGnuProperty:shstk
+
+//#Config:report-warning:default
+//#LinkArgs:-z ibt -z cet-report=warning --fatal-warnings
+//#SkipLinker:ld
+//#ExpectError:missing IBT property
+
+//#Config:report-error:default
+//#LinkArgs:-z cet-report=error
+//#SkipLinker:ld
+//#ExpectError:missing SHSTK property
+
+#include "exit.h"
+
+void _start(void) {
+    exit_syscall(42);
+}
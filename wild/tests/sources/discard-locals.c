@@ -0,0 +1,47 @@
+// Tests options that control which symbols end up in the symbol table.
+
+//#AbstractConfig:default
+//#Object:exit.c
+//#CompArgs:-Wa,-L
+
+//#Config:discard-locals:default
+//#LinkArgs:-X
+//#Contains:local_marker_fn
+//#DoesNotContain:.Ltemp_marker
+
+//#Config:discard-all:default
+//#LinkArgs:-x
+//#DoesNotContain:local_marker_fn
+//#ExpectSym:global_marker_fn
+
+//#Config:discard-none:default
+//#LinkArgs:--discard-none
+//#Contains:local_marker_fn
+//#Contains:.Ltemp_marker
+
+//#Config:retain-symbols-file:default
+//#RetainSymbolsFile:retain-symbols.txt
+//#ExpectSym:local_marker_fn
+//#ExpectSym:global_marker_fn
+//#DoesNotContain:dropped_marker_fn
+//#DoesNotContain:exit_syscall
+
+#include "exit.h"
+
+__asm__(".text\n.Ltemp_marker:\nnop\n");
+
+static __attribute__((noinline)) int local_marker_fn(int x) {
+    return x + 2;
+}
+
+int global_marker_fn(int x) {
+    return local_marker_fn(x);
+}
+
+int dropped_marker_fn(int x) {
+    return global_marker_fn(x);
+}
+
+void _start(void) {
+    exit_syscall(dropped_marker_fn(40));
+}
@@ -0,0 +1,34 @@
+// Tests `-z` options that set bits in DT_FLAGS and DT_FLAGS_1. We pass `-z now` so that other
+// linkers set the same NOW / BIND_NOW flags that we always set.
+
+//#AbstractConfig:default
+//#Object:exit.c
+//#Static:false
+//#Shared:trivial-dynamic-2.c
+//#DiffIgnore:.dynamic.DT_NEEDED
+//#DiffIgnore:.dynamic.DT_RELA
+//#DiffIgnore:.dynamic.DT_RELAENT
+//#DiffIgnore:section.got
+
+//#Config:executable:default
+//#LinkArgs:-z now -z origin -z global -z nodefaultlib -z interpose -z nodump -z loadfltr
+
+// These flags only apply to shared objects, so should be ignored when linking an executable.
+//#Config:shared-only:default
+//#LinkArgs:-z now -z nodelete -z nodlopen -z initfirst
+
+//#Config:shared:default
+//#LinkArgs:--shared -z now -z nodelete -z nodlopen -z initfirst -z origin
+//#DiffIgnore:file-header.entry
+//#RunEnabled:false
+
+#include "exit.h"
+
+int foo(void);
+
+void _start(void) {
+    if (foo() != 10) {
+        exit_syscall(foo());
+    }
+    exit_syscall(42);
+}
@@ -24,4 +24,34 @@ void exit_syscall(int exit_code) {
         : "r"(w8)
         : "cc", "memory");
 }
+#elif defined(__i386__)
+void exit_syscall(int exit_code) {
+    register int eax __asm__("eax") = 1;
+    register int ebx __asm__("ebx") = exit_code;
+    __asm__ __volatile__(
+        "int $0x80"
+        : "+r"(eax)
+        : "r"(ebx)
+        : "memory");
+}
+#elif defined(__riscv)
+void exit_syscall(int exit_code) {
+    register long a7 __asm__("a7") = 93;
+    register long a0 __asm__("a0") = exit_code;
+    __asm__ __volatile__(
+        "ecall"
+        : "+r"(a0)
+        : "r"(a7)
+        : "memory");
+}
+#elif defined(__loongarch__)
+void exit_syscall(int exit_code) {
+    register long a7 __asm__("a7") = 93;
+    register long a0 __asm__("a0") = exit_code;
+    __asm__ __volatile__(
+        "syscall 0"
+        : "+r"(a0)
+        : "r"(a7)
+        : "memory");
+}
 #endif
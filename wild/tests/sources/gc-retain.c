@@ -0,0 +1,102 @@
+// Tests garbage collection of sections with SHF_GNU_RETAIN and SHF_LINK_ORDER and of sections that
+// are only referenced via __start_ / __stop_ symbols.
+
+//#AbstractConfig:default
+//#Object:exit.c
+//#CompArgs:-ffunction-sections -fdata-sections
+//#ExpectSym:retained_fn
+//#ExpectSym:retained_data
+
+//#Config:start-stop-gc:default
+//#CompArgs:-ffunction-sections -fdata-sections -DSTART_STOP_GC
+//#LinkArgs:--gc-sections -z start-stop-gc
+//#DoesNotContain:start-stop-kept
+//#ExpectNoSection:start_stop_sec
+
+//#Config:nostart-stop-gc:default
+//#LinkArgs:--gc-sections -z nostart-stop-gc
+//#Contains:start-stop-kept
+
+#include "exit.h"
+
+// Nothing references these, but they're marked as retained, so they should be kept.
+__attribute__((retain, used)) int retained_fn(void) {
+    return 10;
+}
+
+__attribute__((retain, used)) int retained_data = 20;
+
+int used_fn(void) {
+    return 12;
+}
+
+int unused_fn(void) {
+    return 13;
+}
+
+int used_fn2(void) {
+    return 14;
+}
+
+// Each of these sections has SHF_LINK_ORDER and links to the section containing one of our
+// functions. With `-z start-stop-gc`, the one for `unused_fn` should be discarded along with
+// `unused_fn`. Otherwise, the reference to `__start_lo_sec` keeps both, which in turn keeps
+// `unused_fn`.
+__asm__(
+    ".section lo_sec,\"ao\",@progbits,.text.used_fn,unique,1\n"
+    ".quad used_fn\n"
+    ".section lo_sec,\"ao\",@progbits,.text.unused_fn,unique,2\n"
+    ".quad unused_fn\n"
+    ".previous\n");
+
+// These link to sections that are both kept, but are in the opposite order to those sections, so
+// they should get sorted.
+__asm__(
+    ".section lo_order_sec,\"ao\",@progbits,.text.used_fn2,unique,3\n"
+    ".quad used_fn2\n"
+    ".section lo_order_sec,\"ao\",@progbits,.text.used_fn,unique,4\n"
+    ".quad used_fn\n"
+    ".previous\n");
+
+// This section is only referenced via __start_ / __stop_ symbols.
+__attribute__((used, section("start_stop_sec"))) static const char start_stop_data[] =
+    "start-stop-kept";
+
+extern const char __start_start_stop_sec[] __attribute__((weak));
+extern const char __stop_start_stop_sec[] __attribute__((weak));
+
+extern void* __start_lo_sec[];
+extern void* __stop_lo_sec[];
+
+extern void* __start_lo_order_sec[];
+extern void* __stop_lo_order_sec[];
+
+void _start(void) {
+    int (*used)(void) = used_fn;
+
+#ifdef START_STOP_GC
+    if (__stop_lo_sec - __start_lo_sec != 1) {
+        exit_syscall(20);
+    }
+#else
+    if (__stop_lo_sec - __start_lo_sec != 2 || __start_lo_sec[1] != (void*)unused_fn) {
+        exit_syscall(20);
+    }
+#endif
+
+    if (__start_lo_sec[0] != (void*)used) {
+        exit_syscall(21);
+    }
+
+    // Link-order sections are written in the same order as the sections that they link to.
+    if (__stop_lo_order_sec - __start_lo_order_sec != 2 ||
+        __start_lo_order_sec[0] != (void*)used || __start_lo_order_sec[1] != (void*)used_fn2) {
+        exit_syscall(23);
+    }
+
+    if (__stop_start_stop_sec < __start_start_stop_sec) {
+        exit_syscall(22);
+    }
+
+    exit_syscall(used() + 30);
+}
@@ -0,0 +1,46 @@
+// Tests that we drop `.gnu.warning` sections and issue warnings based on them.
+
+//#AbstractConfig:default
+//#Object:exit.c
+
+//#Config:unreferenced:default
+//#Object:gnu-warning0.c
+//#Archive:gnu-warning1.c
+//#LinkArgs:--fatal-warnings
+//#DoesNotContain:is deprecated
+//#DoesNotContain:shouldn't be linked
+
+//#Config:referenced:default
+//#Object:gnu-warning0.c
+//#CompArgs:-DUSE_DEPRECATED
+//#LinkArgs:--fatal-warnings
+//#SkipLinker:ld
+//#ExpectError:in function _start: deprecated_fn is deprecated
+
+//#Config:object:default
+//#Object:gnu-warning0.c
+//#Object:gnu-warning1.c
+//#LinkArgs:--fatal-warnings
+//#SkipLinker:ld
+//#ExpectError:gnu-warning1 shouldn't be linked
+
+//#Config:shared:default
+//#Shared:gnu-warning0.c
+//#Static:false
+//#CompArgs:-DUSE_DEPRECATED
+//#LinkArgs:--fatal-warnings -z now
+//#SkipLinker:ld
+//#ExpectError:in function _start: deprecated_fn is deprecated
+
+#include "exit.h"
+
+int deprecated_fn(void);
+int supported_fn(void);
+
+void _start(void) {
+    int value = supported_fn();
+#ifdef USE_DEPRECATED
+    value = deprecated_fn();
+#endif
+    exit_syscall(value);
+}
@@ -0,0 +1,14 @@
+int deprecated_fn(void) {
+    return 42;
+}
+
+int supported_fn(void) {
+    return 42;
+}
+
+// Like glibc's `link_warning`, the `#` comments out the section flags that the compiler emits, so
+// that the section isn't allocated. GNU ld only issues warnings from shared objects for sections
+// like this.
+static const char deprecated_fn_warning[]
+    __attribute__((used, section(".gnu.warning.deprecated_fn\n\t#"))) =
+        "deprecated_fn is deprecated";
@@ -0,0 +1,6 @@
+int gnu_warning1_fn(void) {
+    return 1;
+}
+
+static const char object_warning[] __attribute__((used, section(".gnu.warning"))) =
+    "gnu-warning1 shouldn't be linked";
@@ -0,0 +1,70 @@
+// Tests linking of static 32 bit x86 executables, including legacy `.ctors` sections, which we need
+// to reverse one 4-byte pointer at a time.
+
+//#AbstractConfig:default
+//#Object:exit.c
+//#Object:init.c
+//#Arch: i386
+//#ExpectSym: _start .text
+
+//#Config:static:default
+
+//#Config:pic:default
+//#CompArgs:-fPIC
+
+#include "exit.h"
+#include "init.h"
+
+int data_var = 10;
+int bss_var;
+int *data_ptr = &data_var;
+static const char *strings[] = {"abc", "defg"};
+
+static int order[4];
+static int num_called;
+
+static void record(int id) { order[num_called++] = id; }
+
+static void ctor1(void) { record(1); }
+
+static void ctor2(void) { record(2); }
+
+static void init_array_fn(void) { record(3); }
+
+typedef void (*init_fn_t)(void);
+
+// Entries in `.ctors` are run from last to first, so `ctor1` should run before `ctor2`.
+static init_fn_t ctors[] __attribute__((section(".ctors"), used)) = {ctor2, ctor1};
+
+static init_fn_t init_array[] __attribute__((section(".init_array"), used)) = {init_array_fn};
+
+__attribute__((noinline)) int add(int a, int b) { return a + b; }
+
+static int position_of(int id) {
+    for (int i = 0; i < num_called; i++) {
+        if (order[i] == id) {
+            return i;
+        }
+    }
+    return -1;
+}
+
+void _start(void) {
+    call_init_functions();
+    if (add(data_var, bss_var) != 10) {
+        exit_syscall(10);
+    }
+    if (*data_ptr != 10) {
+        exit_syscall(11);
+    }
+    if (strings[1][3] != 'g') {
+        exit_syscall(12);
+    }
+    if (num_called != 3 || position_of(3) < 0) {
+        exit_syscall(13);
+    }
+    if (position_of(1) < 0 || position_of(1) > position_of(2)) {
+        exit_syscall(14);
+    }
+    exit_syscall(42);
+}
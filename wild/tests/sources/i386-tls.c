@@ -0,0 +1,126 @@
+// Tests thread-local variables in static 32 bit x86 executables with each of the TLS models. The
+// dynamic models get relaxed, since there's no dynamic linker to call.
+
+//#AbstractConfig:default
+//#Object:exit.c
+//#Arch: i386
+
+//#Config:local-exec:default
+//#CompArgs:-ftls-model=local-exec
+
+//#Config:initial-exec:default
+//#CompArgs:-ftls-model=initial-exec
+
+//#Config:global-dynamic:default
+//#CompArgs:-fPIC -ftls-model=global-dynamic
+
+//#Config:local-dynamic:default
+//#CompArgs:-fPIC -ftls-model=local-dynamic
+
+#include "exit.h"
+
+#include <stdint.h>
+
+struct FileHeader {
+    uint8_t ident[16];
+    uint16_t ty;
+    uint16_t machine;
+    uint32_t version;
+    uint32_t entry_point;
+    uint32_t program_header_offset;
+    uint32_t section_header_offset;
+    uint32_t flags;
+    uint16_t ehsize;
+    uint16_t program_header_entry_size;
+    uint16_t program_header_num;
+    uint16_t section_header_entry_size;
+    uint16_t section_header_num;
+    uint16_t section_names_index;
+};
+
+struct ProgramHeader {
+    uint32_t segment_type;
+    uint32_t offset;
+    uint32_t virtual_addr;
+    uint32_t physical_addr;
+    uint32_t file_size;
+    uint32_t mem_size;
+    uint32_t flags;
+    uint32_t alignment;
+};
+
+// The argument to the `set_thread_area` syscall.
+struct UserDesc {
+    uint32_t entry_number;
+    uint32_t base_addr;
+    uint32_t limit;
+    // seg_32bit, limit_in_pages and useable.
+    uint32_t flags;
+};
+
+extern const struct FileHeader __ehdr_start;
+
+__thread int tvar1 = 10;
+__thread char tvar2 = 20;
+static __thread int tvar3 = 0;
+__thread int tvar4;
+
+// Copies the TLS initialisation image into a static buffer and points %gs at the thread pointer
+// after it. Returns 0 on success.
+static int init_tls(void) {
+    static uint8_t tls_area[256] __attribute__((aligned(64)));
+    static struct UserDesc desc;
+
+    const struct ProgramHeader *headers =
+        (const struct ProgramHeader *)((const uint8_t *)&__ehdr_start +
+                                       __ehdr_start.program_header_offset);
+    for (int i = 0; i < __ehdr_start.program_header_num; i++) {
+        const struct ProgramHeader *h = &headers[i];
+        if (h->segment_type != 7) {
+            continue;
+        }
+        uint32_t alignment = h->alignment ? h->alignment : 1;
+        uint32_t size = (h->mem_size + alignment - 1) & ~(alignment - 1);
+        if (size + 4 > sizeof(tls_area) || alignment > 64) {
+            return 2;
+        }
+        const uint8_t *t_in = (const uint8_t *)h->virtual_addr;
+        for (uint32_t j = 0; j < size; j++) {
+            tls_area[j] = j < h->file_size ? t_in[j] : 0;
+        }
+
+        // The thread pointer points to the end of the TLS block and to a pointer to itself.
+        uint32_t *tp = (uint32_t *)&tls_area[size];
+        tp[0] = (uint32_t)tp;
+
+        desc.entry_number = -1;
+        desc.base_addr = (uint32_t)tp;
+        desc.limit = 0xfffff;
+        desc.flags = 0x51;
+        register int eax __asm__("eax") = 243; // set_thread_area
+        register struct UserDesc *ebx __asm__("ebx") = &desc;
+        __asm__ __volatile__("int $0x80" : "+r"(eax) : "r"(ebx) : "memory");
+        if (eax != 0) {
+            return 3;
+        }
+        uint32_t selector = desc.entry_number * 8 + 3;
+        __asm__ __volatile__("movw %w0, %%gs" : : "r"(selector) : "memory");
+        return 0;
+    }
+    return 1;
+}
+
+__attribute__((noinline)) int *get_tvar4(void) { return &tvar4; }
+
+void _start(void) {
+    int ret = init_tls();
+    if (ret != 0) {
+        exit_syscall(ret);
+    }
+    *get_tvar4() = 5;
+    tvar3 = 7;
+    if (tvar1 + tvar2 + tvar3 + tvar4 != 42) {
+        exit_syscall(10);
+    }
+    exit_syscall(42);
+}
@@ -0,0 +1,67 @@
+// Checks that we sort .init_array.N and .ctors.N sections by priority, across files, and that the
+// contents of legacy .ctors sections are reversed so that they run in their original order.
+
+//#AbstractConfig:default
+//#Object:init-priority0.c
+//#Object:init.c
+//#Object:exit.c
+
+//#Config:static:default
+
+//#Config:gc:default
+//#LinkArgs:--gc-sections
+
+//#Config:pie:default
+//#CompArgs:-fpie
+
+#include "exit.h"
+#include "init.h"
+
+typedef void (*init_fn_t)(void);
+
+int order[16];
+int count = 0;
+
+void record(int value) {
+    if (count < 16) {
+        order[count++] = value;
+    }
+}
+
+void __attribute__((constructor(101))) init_101(void) {
+    record(1);
+}
+
+static void init_300(void) {
+    record(4);
+}
+
+static void ctors_8(void) {
+    record(8);
+}
+
+static void ctors_9(void) {
+    record(9);
+}
+
+static init_fn_t init_array_300 __attribute__((used, section(".init_array.00300"))) = init_300;
+
+// .ctors entries run from last to first. We set the alignment so that the linker doesn't need to
+// insert null padding before this section.
+static init_fn_t ctors[] __attribute__((used, section(".ctors"), aligned(8))) = {ctors_9, ctors_8};
+
+void _start(void) {
+    call_init_functions();
+    // Sections without a priority come last, in input order.
+    static const int expected[] = {1, 2, 3, 4, 5, 8, 9, 7};
+    int num_expected = sizeof(expected) / sizeof(expected[0]);
+    if (count != num_expected) {
+        exit_syscall(count + 100);
+    }
+    for (int i = 0; i < num_expected; i++) {
+        if (order[i] != expected[i]) {
+            exit_syscall(i + 1);
+        }
+    }
+    exit_syscall(42);
+}
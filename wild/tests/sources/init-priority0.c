@@ -0,0 +1,22 @@
+typedef void (*init_fn_t)(void);
+
+void record(int value);
+
+void __attribute__((constructor(200))) init_200(void) {
+    record(2);
+}
+
+static void ctors_250(void) {
+    record(3);
+}
+
+// .ctors.N sections have their priority inverted, so this is priority 250.
+static init_fn_t ctors_65285 __attribute__((used, section(".ctors.65285"))) = ctors_250;
+
+void __attribute__((constructor(1000))) init_1000(void) {
+    record(5);
+}
+
+void __attribute__((constructor)) init_default(void) {
+    record(7);
+}
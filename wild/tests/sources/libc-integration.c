@@ -163,6 +163,8 @@ __attribute__ ((section (".ctors"), used)) static void* init3_ptr = init3;
 __asm__(".symver old_timer_gettime, timer_gettime@GLIBC_2.2.5");
 #elif defined(__aarch64__)
 __asm__(".symver old_timer_gettime, timer_gettime@GLIBC_2.17");
+#elif defined(__riscv)
+__asm__(".symver old_timer_gettime, timer_gettime@GLIBC_2.27");
 #endif
 
 // The signature here doesn't actually matter since we don't call it. Symbol is weak to prevent the
@@ -0,0 +1,96 @@
+// Tests LoongArch linker relaxation. With the medium code model, calls are `pcaddu18i` + `jirl`
+// pairs that get shortened to `bl`. Local-exec TLS accesses drop their `lu12i.w` and `add.d` when
+// the offset is small and `.align` directives need their padding adjusted once earlier code has
+// shrunk.
+
+//#AbstractConfig:default
+//#Object:exit.c
+//#Arch: loongarch64
+//#CompArgs:-mcmodel=medium -ftls-model=local-exec
+
+//#Config:relax:default
+
+//#Config:no-relax:default
+//#LinkArgs:--no-relax
+
+#include "exit.h"
+
+#include <elf.h>
+#include <stdint.h>
+
+extern const Elf64_Ehdr __ehdr_start;
+
+__thread int tvar1 = 10;
+__thread int tvar2;
+
+// Copies the TLS initialisation image into a static buffer and points $tp at it. Returns 0 on
+// success.
+static int init_tls(void) {
+    static uint8_t tls_area[256] __attribute__((aligned(64)));
+
+    const Elf64_Phdr *headers =
+        (const Elf64_Phdr *)((const uint8_t *)&__ehdr_start + __ehdr_start.e_phoff);
+    for (int i = 0; i < __ehdr_start.e_phnum; i++) {
+        const Elf64_Phdr *h = &headers[i];
+        if (h->p_type != PT_TLS) {
+            continue;
+        }
+        if (h->p_memsz > sizeof(tls_area) || h->p_align > 64) {
+            return 2;
+        }
+        const uint8_t *t_in = (const uint8_t *)h->p_vaddr;
+        for (uint64_t j = 0; j < h->p_memsz; j++) {
+            tls_area[j] = j < h->p_filesz ? t_in[j] : 0;
+        }
+        // The thread pointer points at the start of the TLS block.
+        __asm__ __volatile__("move $tp, %0" : : "r"(tls_area) : "memory");
+        return 0;
+    }
+    return 1;
+}
+
+__attribute__((noinline)) int add_one(int x) { return x + 1; }
+
+int aligned_fn(int x);
+
+// Code after a `.align` directive must stay aligned even when the calls before it shrink.
+__asm__(".text\n"
+        ".global aligned_fn\n"
+        ".type aligned_fn, @function\n"
+        "aligned_fn:\n"
+        "  addi.d $sp, $sp, -16\n"
+        "  st.d $ra, $sp, 8\n"
+        "  pcaddu18i $ra, %call36(add_one)\n"
+        "  jirl $ra, $ra, 0\n"
+        "  pcaddu18i $ra, %call36(add_one)\n"
+        "  jirl $ra, $ra, 0\n"
+        "  ld.d $ra, $sp, 8\n"
+        "  addi.d $sp, $sp, 16\n"
+        "  b 1f\n"
+        "  .align 4\n"
+        "1:\n"
+        "  pcaddi $t0, 0\n"
+        "  andi $t0, $t0, 15\n"
+        "  add.d $a0, $a0, $t0\n"
+        "  jr $ra\n");
+
+void _start(void) {
+    int ret = init_tls();
+    if (ret != 0) {
+        exit_syscall(ret);
+    }
+    if (add_one(1) != 2) {
+        exit_syscall(10);
+    }
+    if (aligned_fn(1) != 3) {
+        exit_syscall(11);
+    }
+    if (tvar1 != 10) {
+        exit_syscall(12);
+    }
+    tvar2 = add_one(tvar1);
+    if (tvar2 != 11) {
+        exit_syscall(13);
+    }
+    exit_syscall(42);
+}
@@ -0,0 +1,49 @@
+// Tests RISC-V linker relaxation. Calls get shortened to `jal` and `.align` directives need their
+// padding adjusted once earlier code has shrunk.
+
+//#AbstractConfig:default
+//#Object:exit.c
+//#Arch: riscv64
+
+//#Config:relax:default
+
+//#Config:no-relax:default
+//#LinkArgs:--no-relax
+
+#include "exit.h"
+
+__attribute__((noinline)) int add_one(int x) { return x + 1; }
+
+int aligned_fn(int x);
+
+// Code after a `.align` directive must stay aligned even when the calls before it shrink.
+__asm__(".text\n"
+        ".option push\n"
+        ".option relax\n"
+        ".global aligned_fn\n"
+        ".type aligned_fn, %function\n"
+        "aligned_fn:\n"
+        "  addi sp, sp, -16\n"
+        "  sd ra, 8(sp)\n"
+        "  call add_one\n"
+        "  call add_one\n"
+        "  ld ra, 8(sp)\n"
+        "  addi sp, sp, 16\n"
+        "  j 1f\n"
+        "  .align 4\n"
+        "1:\n"
+        "  lla t0, 1b\n"
+        "  andi t0, t0, 15\n"
+        "  add a0, a0, t0\n"
+        "  ret\n"
+        ".option pop\n");
+
+void _start(void) {
+    if (add_one(1) != 2) {
+        exit_syscall(10);
+    }
+    if (aligned_fn(1) != 3) {
+        exit_syscall(11);
+    }
+    exit_syscall(42);
+}
@@ -0,0 +1,3 @@
+#include "section-groups.h"
+
+DEFINE_GROUPED_VALUE(42);
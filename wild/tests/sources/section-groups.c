@@ -0,0 +1,23 @@
+// Checks how we resolve a symbol that is strongly defined in a section group in two objects. If the
+// group is a COMDAT group, then the definitions are interchangeable and the first is used. Otherwise
+// they're duplicate definitions, the same as if they weren't in a group.
+
+//#Object:section-groups-1.c
+//#Object:exit.c
+//#Arch: x86_64
+
+//#Config:non-comdat:default
+//#CompArgs:-DNON_COMDAT
+//#SkipLinker:ld
+//#ExpectError:grouped_value, defined in
+
+#include "exit.h"
+#include "section-groups.h"
+
+DEFINE_GROUPED_VALUE(42);
+
+extern int grouped_value;
+
+void _start(void) {
+    exit_syscall(grouped_value);
+}
@@ -0,0 +1,12 @@
+#ifdef NON_COMDAT
+#define GROUP_LINKAGE ""
+#else
+#define GROUP_LINKAGE ",comdat"
+#endif
+
+// Defines `grouped_value` in a section group with the signature `grouped`.
+#define DEFINE_GROUPED_VALUE(value)                                                                \
+    __asm__(".section .data.grouped,\"awG\",@progbits,grouped" GROUP_LINKAGE "\n"                  \
+            ".globl grouped_value\n"                                                               \
+            "grouped_value: .long " #value "\n"                                                    \
+            ".previous\n")
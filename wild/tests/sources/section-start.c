@@ -0,0 +1,81 @@
+// Tests options that place the output image or particular sections at fixed addresses.
+
+//#AbstractConfig:default
+//#Object:exit.c
+//#DiffEnabled:false
+
+//#Config:image-base:default
+//#LinkArgs:--image-base=0x10000000
+//#SkipLinker:ld
+//#CompArgs:-DIMAGE_BASE=0x10000000
+
+//#Config:text:default
+//#LinkArgs:-Ttext=0x800000
+//#CompArgs:-DTEXT=0x800000
+
+//#Config:data-bss:default
+//#LinkArgs:-Tdata 0xa00000 --section-start=.bss=0xc00000
+//#CompArgs:-DDATA=0xa00000 -DBSS=0xc00000
+
+//#Config:far-rodata:default
+//#LinkArgs:--section-start=.rodata=0x30000000
+//#CompArgs:-DRODATA=0x30000000
+//#MaxFileSize:100000
+
+//#Config:text-below-base:default
+//#LinkArgs:-Ttext=0x200000
+//#CompArgs:-DTEXT=0x200000
+
+//#Config:overlap:default
+//#LinkArgs:-Ttext=0x800000 -Tdata=0x800000
+//#SkipLinker:ld
+//#ExpectError:Section `.data` at 0x800000 overlaps section
+
+//#Config:overlap-headers:default
+//#LinkArgs:-Ttext=0x400010
+//#SkipLinker:ld
+//#ExpectError:Section `.text` at 0x400010 overlaps the ELF header at 0x400000..0x400040
+
+#include "exit.h"
+
+#include <stdint.h>
+
+extern char __ehdr_start;
+
+int data_var = 10;
+int bss_var;
+const int rodata_var = 32;
+
+void _start(void) {
+#ifdef IMAGE_BASE
+    if ((uintptr_t)&__ehdr_start != IMAGE_BASE) {
+        exit_syscall(10);
+    }
+#endif
+#ifdef TEXT
+    // The start of .text depends on what other functions end up there, so we just check that .text
+    // is where we asked for it to be.
+    if ((uintptr_t)&_start < TEXT || (uintptr_t)&_start >= TEXT + 0x1000) {
+        exit_syscall(11);
+    }
+#endif
+#ifdef DATA
+    if ((uintptr_t)&data_var < DATA || (uintptr_t)&data_var >= DATA + 0x1000) {
+        exit_syscall(12);
+    }
+#endif
+#ifdef RODATA
+    if ((uintptr_t)&rodata_var < RODATA || (uintptr_t)&rodata_var >= RODATA + 0x1000) {
+        exit_syscall(15);
+    }
+#endif
+#ifdef BSS
+    if ((uintptr_t)&bss_var < BSS || (uintptr_t)&bss_var >= BSS + 0x1000) {
+        exit_syscall(13);
+    }
+#endif
+    if (data_var + bss_var != 10 || rodata_var != 32) {
+        exit_syscall(14);
+    }
+    exit_syscall(42);
+}
@@ -13,6 +13,12 @@
 //#DiffIgnore:.dynamic.DT_RELAENT
 //#DiffIgnore:.dynamic.DT_NEEDED
 
+// Symbols from the archive (bar1) shouldn't be exported. They should however still be in .symtab,
+// as locals.
+//#Config:exclude-libs:default
+//#LinkArgs:-shared -z now --exclude-libs ALL
+//#ExpectLocalSym:bar1
+
 int bar1(void);
 int bar2(void);
 
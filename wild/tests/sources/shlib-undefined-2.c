@@ -0,0 +1,9 @@
+int not_defined_anywhere(void);
+
+int foo(void) {
+    return 42;
+}
+
+int call_not_defined_anywhere(void) {
+    return not_defined_anywhere();
+}
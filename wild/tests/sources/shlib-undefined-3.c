@@ -0,0 +1,6 @@
+// Only included via an archive. Provides the symbol that shlib-undefined-2.c leaves undefined, so
+// it should get loaded because of the reference from the shared object.
+
+int not_defined_anywhere(void) {
+    return 10;
+}
@@ -0,0 +1,42 @@
+// Tests handling of undefined symbols in shared objects that we link against. By default, these are
+// an error when linking an executable. A definition in an archive member causes that member to be
+// loaded.
+
+//#AbstractConfig:default
+//#Object:exit.c
+//#Static:false
+//#Shared:shlib-undefined-2.c
+//#RunEnabled:false
+//#DiffIgnore:.dynamic.DT_NEEDED
+//#DiffIgnore:.dynamic.DT_RELA
+//#DiffIgnore:.dynamic.DT_RELAENT
+
+//#Config:error:default
+//#ExpectError:Undefined symbol not_defined_anywhere
+//#SkipLinker:ld
+
+//#Config:no-allow-shlib-undefined:default
+//#LinkArgs:--unresolved-symbols=ignore-all --no-allow-shlib-undefined -z now
+//#ExpectError:Undefined symbol not_defined_anywhere
+//#SkipLinker:ld
+
+//#Config:archive:default
+//#Archive:shlib-undefined-3.c
+//#ExpectSym:not_defined_anywhere
+
+//#Config:allow-shlib-undefined:default
+//#LinkArgs:--allow-shlib-undefined -z now
+
+//#Config:ignore-in-shared-libs:default
+//#LinkArgs:--unresolved-symbols=ignore-in-shared-libs -z now
+// The arguments above also get used when building the shared object, where they'd make the
+// undefined reference an error.
+//#LinkSoArgs:-z now --unresolved-symbols=ignore-all
+
+#include "exit.h"
+
+int foo(void);
+
+void _start(void) {
+    exit_syscall(foo());
+}
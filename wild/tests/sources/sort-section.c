@@ -0,0 +1,68 @@
+// Tests `-z keep-text-section-prefix` and `--sort-section`.
+
+//#AbstractConfig:default
+//#Object:exit.c
+
+//#Config:unsorted:default
+
+//#Config:keep-prefix:default
+//#CompArgs:-DKEEP_PREFIX
+//#LinkArgs:-z keep-text-section-prefix
+//#SkipLinker:ld
+//#ExpectSym:hot_a .text.hot
+//#ExpectSym:cold .text.unlikely
+
+//#Config:sort-name:default
+//#CompArgs:-DSORT_NAME
+//#LinkArgs:--sort-section=name
+
+//#Config:sort-alignment:default
+//#LinkArgs:--sort-section=alignment
+
+//#Config:keep-prefix-sort-name:default
+//#CompArgs:-DKEEP_PREFIX -DSORT_NAME
+//#LinkArgs:-z keep-text-section-prefix --sort-section name
+//#SkipLinker:ld
+//#ExpectSym:hot_a .text.hot
+//#ExpectSym:hot_b .text.hot
+
+#include "exit.h"
+
+#define SECTION(name) __attribute__((noinline, used, section(name)))
+
+SECTION(".text.b_fn") int b_fn(void) { return 1; }
+SECTION(".text.a_fn") int a_fn(void) { return 2; }
+SECTION(".text.hot.b") int hot_b(void) { return 3; }
+SECTION(".text.hot.a") int hot_a(void) { return 4; }
+SECTION(".text.unlikely.cold") int cold(void) { return 5; }
+
+__attribute__((section(".data.z"))) int data_z = 6;
+__attribute__((section(".data.y"))) int data_y = 7;
+
+void _start(void) {
+    int total = b_fn() + a_fn() + hot_b() + hot_a() + cold() + data_z + data_y;
+    if (total != 28) {
+        exit_syscall(20);
+    }
+
+#ifdef SORT_NAME
+    if ((char*)a_fn > (char*)b_fn) {
+        exit_syscall(21);
+    }
+    if (&data_y > &data_z) {
+        exit_syscall(22);
+    }
+    if ((char*)hot_a > (char*)hot_b) {
+        exit_syscall(23);
+    }
+#endif
+
+#ifdef KEEP_PREFIX
+    // Cold code comes first, then hot code, then everything else.
+    if ((char*)cold > (char*)hot_a || (char*)hot_a > (char*)a_fn || (char*)hot_b > (char*)b_fn) {
+        exit_syscall(24);
+    }
+#endif
+
+    exit_syscall(42);
+}
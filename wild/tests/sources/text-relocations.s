@@ -0,0 +1,33 @@
+// Tests text relocations. i.e. dynamic relocations that need to be applied to read-only sections.
+
+//#AbstractConfig:default
+//#Object:exit.c
+//#Arch: x86_64
+//#Static:false
+
+//#Config:notext:default
+//#LinkArgs:-pie -z now -z notext
+
+//#Config:warn:default
+//#LinkArgs:-pie -z now -z notext --warn-textrel --fatal-warnings
+//#SkipLinker:ld
+//#ExpectError:Relocation against
+
+//#Config:text:default
+//#LinkArgs:-pie -z now -z text
+//#SkipLinker:ld
+//#ExpectError:link with -z notext
+
+.text
+.globl _start
+.type _start, @function
+_start:
+    movabs $value, %rax
+    mov (%rax), %rdi
+    call exit_syscall
+
+.data
+value:
+    .quad 42
+
+.section .note.GNU-stack,"",@progbits
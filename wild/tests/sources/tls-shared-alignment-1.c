@@ -0,0 +1,20 @@
+static __thread long a = 5;
+static __thread char pad[3];
+static __thread long z __attribute__((aligned(64)));
+
+// Custom executable sections go at the end of the executable segment, after `.text`, which is
+// 16-byte aligned. Ending the segment 8 bytes past a 16-byte boundary means that, unless we align
+// it, `.tdata` won't have the alignment that `.tbss` needs.
+__asm__(".section tls_pad,\"axR\",@progbits\n"
+        ".balign 8\n"
+        ".skip 8\n"
+        ".text\n");
+
+long get_a(void) {
+    pad[0] = 1;
+    return a;
+}
+
+long get_z(void) {
+    return z + pad[0] - 1;
+}
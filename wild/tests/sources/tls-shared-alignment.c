@@ -0,0 +1,38 @@
+// Tests TLS variables in a shared object where `.tbss` needs more alignment than `.tdata`. The
+// offsets that we compute at link time need to be relative to where the TLS segment starts.
+
+//#Object:exit.c
+//#Static:false
+//#LinkArgs:-z now
+//#Shared:tls-shared-alignment-1.c:-fPIC -O0
+//#Arch: x86_64
+// We link and name different .so files, so this is expected.
+//#DiffIgnore:.dynamic.DT_NEEDED
+//#DiffIgnore:.dynamic.DT_RELA
+//#DiffIgnore:.dynamic.DT_RELAENT
+
+#include "exit.h"
+
+#include <stddef.h>
+
+long get_a(void);
+long get_z(void);
+
+// We don't link against libc, so we provide `__tls_get_addr` for the shared object. The dynamic
+// linker puts the TLS blocks of modules loaded at startup into the DTV, which is pointed to by the
+// second word of the TCB. Each DTV entry is two words.
+void* __tls_get_addr(size_t* mod_and_offset) {
+    char** dtv;
+    __asm__("mov %%fs:8, %0" : "=r"(dtv));
+    return dtv[mod_and_offset[0] * 2] + mod_and_offset[1];
+}
+
+void _start(void) {
+    if (get_a() != 5) {
+        exit_syscall(20);
+    }
+    if (get_z() != 0) {
+        exit_syscall(21);
+    }
+    exit_syscall(42);
+}
@@ -0,0 +1,5 @@
+int get_indirect_value(void);
+
+int get_value(void) {
+    return get_indirect_value() + 2;
+}
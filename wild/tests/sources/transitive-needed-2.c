@@ -0,0 +1,3 @@
+int get_indirect_value(void) {
+    return 40;
+}
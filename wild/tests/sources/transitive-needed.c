@@ -0,0 +1,28 @@
+// Tests that we load the DT_NEEDED entries of shared objects that we link against, so that undefined
+// symbols in those shared objects can be checked. The indirect dependency is found via the DT_RUNPATH
+// of the shared object that needs it and mustn't be added to our own DT_NEEDED.
+
+//#Object:exit.c
+//#Static:false
+//#LinkArgs:-z now --no-allow-shlib-undefined
+//#LinkSoArgs:-rpath $ORIGIN
+//#Shared:transitive-needed-1.c
+//#IndirectShared:transitive-needed-2.c
+//#DoesNotContain:transitive-needed-2
+// We link and name different .so files, so this is expected.
+//#DiffIgnore:.dynamic.DT_NEEDED
+//#DiffIgnore:.dynamic.DT_SONAME
+//#DiffIgnore:.dynamic.DT_RELA
+//#DiffIgnore:.dynamic.DT_RELAENT
+//#DiffIgnore:section.got
+
+#include "exit.h"
+
+int get_value(void);
+
+void _start(void) {
+    if (get_value() != 42) {
+        exit_syscall(20);
+    }
+    exit_syscall(42);
+}
@@ -0,0 +1,54 @@
+// Tests the suggestions that we make when a symbol is undefined, but there's a defined symbol with a
+// similar name.
+
+//#AbstractConfig:default
+//#Object:exit.c
+//#SkipLinker:ld
+
+//#Config:typo:default
+//#CompArgs:-DTYPO
+//#ExpectError:>>> did you mean: suggestion_target
+
+//#Config:cpp-to-c:default
+//#CompArgs:-DCPP_TO_C
+//#ExpectError:>>> did you mean: extern "C" suggestion_target
+
+//#Config:c-to-cpp:default
+//#CompArgs:-DC_TO_CPP
+//#ExpectError:>>> did you mean to declare cpp_target_symbol(int) as extern "C"?
+
+//#Config:rust-v0-to-c:default
+//#CompArgs:-DRUST_V0_TO_C
+//#ExpectError:>>> did you mean: extern "C" suggestion_target
+
+#include "exit.h"
+
+int suggestion_target(int x) {
+    return x + 1;
+}
+
+// A C++ function `cpp_target_symbol(int)`.
+__asm__(".globl _Z17cpp_target_symboli\n"
+        ".type _Z17cpp_target_symboli, @function\n"
+        "_Z17cpp_target_symboli:\n"
+        "ret\n");
+
+#if defined(TYPO)
+int suggestion_targte(int x);
+#define CALL suggestion_targte
+#elif defined(CPP_TO_C)
+// What we'd reference if a C++ file declared `suggestion_target` without `extern "C"`.
+int wrong(int x) __asm__("_Z17suggestion_targeti");
+#define CALL wrong
+#elif defined(C_TO_CPP)
+int cpp_target_symbol(int x);
+#define CALL cpp_target_symbol
+#elif defined(RUST_V0_TO_C)
+// What we'd reference if Rust code declared `suggestion_target` without `#[no_mangle]`.
+int wrong(int x) __asm__("_RNvCs1234_7mycrate17suggestion_target");
+#define CALL wrong
+#endif
+
+void _start(void) {
+    exit_syscall(CALL(41));
+}
@@ -11,10 +11,20 @@
 //#ExpectError:Undefined symbols:
 //#ExpectError:  undefined_strong
 
+//#Config:z-defs:default
+//#LinkArgs:--shared -z defs
+//#ExpectError:undefined_strong
+
 //#Config:executable:default
 //#ExpectError:Undefined symbols:
 //#ExpectError:  undefined_strong
 
+// On RISC-V and LoongArch, getting the line right requires applying the relocations that
+// `.debug_line` uses for address advances.
+//#Config:reference-location:default
+//#CompArgs:-g
+//#ExpectError:sources/undefined_symbols.c:33
+
 int undefined_strong();
 __attribute__((weak)) int undefined_weak();
 
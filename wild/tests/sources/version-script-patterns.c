@@ -0,0 +1,42 @@
+// Tests version scripts that use wildcards other than a trailing `*`, `extern` blocks and checks
+// that naming an undefined symbol in a version script is an error.
+
+//#AbstractConfig:default
+//#LinkArgs:--shared --soname=version-script-patterns.so
+//#RunEnabled:false
+//#DiffIgnore:.dynamic.DT_FLAGS*
+//#DiffIgnore:.dynamic.DT_RELA
+//#DiffIgnore:.dynamic.DT_RELAENT
+//#DiffIgnore:file-header.entry
+
+//#Config:patterns:default
+//#VersionScript:version-script-patterns.map
+
+//#Config:undefined:default
+//#VersionScript:version-script-undefined.map
+//#SkipLinker:ld
+//#ExpectError:assignment of `VER_1` to symbol `does_not_exist` failed
+
+//#Config:allow-undefined:default
+//#VersionScript:version-script-undefined.map
+//#WildExtraLinkArgs:--undefined-version
+
+int pat_a_1(void) {
+    return 1;
+}
+
+int pat_c_1(void) {
+    return 2;
+}
+
+int value_exported(void) {
+    return 3;
+}
+
+int hidden_value(void) {
+    return 4;
+}
+
+int plain(void) {
+    return 5;
+}
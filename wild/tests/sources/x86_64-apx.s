@@ -0,0 +1,40 @@
+// Tests relaxation of relocations on x86-64 APX instructions, which use REX2 and EVEX prefixes.
+// Running the output would need a CPU with APX, so we only link it and diff against GNU ld.
+
+//#LinkArgs:-z noexecstack
+//#Object:exit.c
+//#Arch: x86_64
+//#RequiresAssemblerWithApx:true
+//#RunEnabled:false
+
+.section .text, "ax", @progbits
+
+.globl _start
+.type _start, @function
+_start:
+    // R_X86_64_CODE_4_GOTPCRELX
+    movq    value@GOTPCREL(%rip), %r16
+
+    // R_X86_64_CODE_6_GOTTPOFF
+    addq    tvar1@gottpoff(%rip), %r16, %r17
+
+    // R_X86_64_CODE_4_GOTPC32_TLSDESC followed by R_X86_64_TLSDESC_CALL
+    {rex2} leaq tvar2@tlsdesc(%rip), %rax
+    call    *tvar2@tlscall(%rax)
+
+    mov     $42, %rdi
+    call    exit_syscall
+.size _start, .-_start
+
+.section .data, "aw", @progbits
+.globl value
+value:
+    .quad 3
+
+.section .tdata, "awT", @progbits
+.globl tvar1
+tvar1:
+    .quad 10
+.globl tvar2
+tvar2:
+    .quad 20